@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::shutdown::CancelSignal;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityConfig {
+    #[serde(default = "default_tail_bytes")]
+    pub tail_bytes: usize,
+    #[serde(default = "default_recent_events")]
+    pub recent_events: usize,
+    #[serde(default = "default_finished_runs")]
+    pub finished_runs: usize,
+}
+
+fn default_tail_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_recent_events() -> usize {
+    200
+}
+
+fn default_finished_runs() -> usize {
+    20
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        Self {
+            tail_bytes: default_tail_bytes(),
+            recent_events: default_recent_events(),
+            finished_runs: default_finished_runs(),
+        }
+    }
+}
+
+struct RunActivity {
+    id: String,
+    run_token: String,
+    tool: String,
+    host: String,
+    client: Option<String>,
+    started_ms: u128,
+    finished_ms: Option<u128>,
+    exit_code: Option<i32>,
+    outcome: Option<&'static str>,
+    output_bytes: usize,
+    tail: VecDeque<u8>,
+    cancel: Arc<CancelSignal>,
+}
+
+impl RunActivity {
+    fn summary(&self) -> Value {
+        json!({
+            "id": self.id,
+            "run_token": self.run_token,
+            "tool": self.tool,
+            "host": self.host,
+            "client": self.client,
+            "started_ms": self.started_ms,
+            "finished_ms": self.finished_ms,
+            "exit_code": self.exit_code,
+            "outcome": self.outcome,
+            "output_bytes": self.output_bytes,
+            "cancelled": self.cancel.is_triggered()
+        })
+    }
+}
+
+#[derive(Default)]
+struct State {
+    active: Vec<RunActivity>,
+    finished: VecDeque<RunActivity>,
+    events: VecDeque<Value>,
+    seq: u64,
+}
+
+pub struct Activity {
+    config: ActivityConfig,
+    state: Mutex<State>,
+}
+
+pub struct ActiveRun {
+    activity: Arc<Activity>,
+    run_token: String,
+    finished: bool,
+}
+
+impl Activity {
+    pub fn new(config: &ActivityConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config: config.clone(),
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    pub fn start(
+        self: &Arc<Self>,
+        id: &str,
+        run_token: &str,
+        tool: &str,
+        host: &str,
+        client: Option<&str>,
+    ) -> ActiveRun {
+        let run = RunActivity {
+            id: id.to_string(),
+            run_token: run_token.to_string(),
+            tool: tool.to_string(),
+            host: host.to_string(),
+            client: client.map(str::to_string),
+            started_ms: now_ms(),
+            finished_ms: None,
+            exit_code: None,
+            outcome: None,
+            output_bytes: 0,
+            tail: VecDeque::new(),
+            cancel: Arc::new(CancelSignal::new()),
+        };
+        let mut state = self.state.lock().expect("activity poisoned");
+        self.push_event(&mut state, "run_started", &run);
+        state.active.push(run);
+        ActiveRun {
+            activity: self.clone(),
+            run_token: run_token.to_string(),
+            finished: false,
+        }
+    }
+
+    pub fn output(&self, run_token: &str, data: &[u8]) {
+        let mut state = self.state.lock().expect("activity poisoned");
+        let Some(run) = state
+            .active
+            .iter_mut()
+            .find(|run| run.run_token == run_token)
+        else {
+            return;
+        };
+        run.output_bytes += data.len();
+        run.tail.extend(data);
+        let excess = run.tail.len().saturating_sub(self.config.tail_bytes);
+        run.tail.drain(..excess);
+    }
+
+    pub fn cancel_signal(&self, run_token: &str) -> Option<Arc<CancelSignal>> {
+        let state = self.state.lock().expect("activity poisoned");
+        state
+            .active
+            .iter()
+            .find(|run| run.run_token == run_token)
+            .map(|run| run.cancel.clone())
+    }
+
+    pub fn cancel(&self, selected: &str) -> Option<Value> {
+        let mut state = self.state.lock().expect("activity poisoned");
+        let index = state
+            .active
+            .iter()
+            .position(|run| run.id == selected || run.run_token == selected)?;
+        let run = &state.active[index];
+        run.cancel.trigger();
+        let summary = run.summary();
+        let event = json!({"event": "run_cancel_requested", "id": run.id, "tool": run.tool, "host": run.host, "exit_code": null});
+        self.push(&mut state, event);
+        Some(summary)
+    }
+
+    pub fn jobs(&self) -> Value {
+        let state = self.state.lock().expect("activity poisoned");
+        json!(
+            state
+                .active
+                .iter()
+                .map(RunActivity::summary)
+                .collect::<Vec<_>>()
+        )
+    }
+
+    pub fn events_after(&self, after: Option<u64>) -> Value {
+        let state = self.state.lock().expect("activity poisoned");
+        let events = state
+            .events
+            .iter()
+            .filter(|event| {
+                after.is_none_or(|after| event["seq"].as_u64().is_some_and(|seq| seq > after))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        json!({"events": events, "last_seq": state.seq})
+    }
+
+    pub fn record(&self, kind: &str, mut fields: Value) {
+        let mut state = self.state.lock().expect("activity poisoned");
+        fields["event"] = json!(kind);
+        self.push(&mut state, fields);
+    }
+
+    pub fn snapshot(&self, selected: Option<&str>) -> Value {
+        let state = self.state.lock().expect("activity poisoned");
+        let tail = selected.and_then(|selected| {
+            let run = state
+                .active
+                .iter()
+                .chain(state.finished.iter())
+                .find(|run| run.id == selected || run.run_token == selected)?;
+            let (front, back) = run.tail.as_slices();
+            let bytes = [front, back].concat();
+            Some(json!({
+                "id": run.id,
+                "run_token": run.run_token,
+                "running": run.finished_ms.is_none(),
+                "data": String::from_utf8_lossy(&bytes)
+            }))
+        });
+        json!({
+            "active": state.active.iter().map(RunActivity::summary).collect::<Vec<_>>(),
+            "finished": state.finished.iter().rev().map(RunActivity::summary).collect::<Vec<_>>(),
+            "events": state.events,
+            "tail": tail
+        })
+    }
+
+    fn finish(&self, run_token: &str, outcome: &'static str, exit_code: Option<i32>) {
+        let mut state = self.state.lock().expect("activity poisoned");
+        let Some(index) = state
+            .active
+            .iter()
+            .position(|run| run.run_token == run_token)
+        else {
+            return;
+        };
+        let mut run = state.active.remove(index);
+        run.finished_ms = Some(now_ms());
+        run.exit_code = exit_code;
+        run.outcome = Some(if run.cancel.is_triggered() && outcome == "failed" {
+            "cancelled"
+        } else {
+            outcome
+        });
+        let outcome = run.outcome.unwrap_or(outcome);
+        self.push_event(&mut state, &format!("run_{}", outcome), &run);
+        if self.config.finished_runs == 0 {
+            return;
+        }
+        if state.finished.len() >= self.config.finished_runs {
+            state.finished.pop_front();
+        }
+        state.finished.push_back(run);
+    }
+
+    fn push_event(&self, state: &mut State, kind: &str, run: &RunActivity) {
+        let event = json!({
+            "event": kind,
+            "id": run.id,
+            "tool": run.tool,
+            "host": run.host,
+            "exit_code": run.exit_code
+        });
+        self.push(state, event);
+    }
+
+    fn push(&self, state: &mut State, mut event: Value) {
+        state.seq += 1;
+        if self.config.recent_events == 0 {
+            return;
+        }
+        if state.events.len() >= self.config.recent_events {
+            state.events.pop_front();
+        }
+        event["seq"] = json!(state.seq);
+        event["ts_ms"] = json!(now_ms());
+        state.events.push_back(event);
+    }
+}
+
+impl ActiveRun {
+    pub fn finish(mut self, exit_code: Option<i32>, timed_out: bool) {
+        self.finished = true;
+        let outcome = if timed_out { "timed_out" } else { "finished" };
+        self.activity.finish(&self.run_token, outcome, exit_code);
+    }
+}
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.activity.finish(&self.run_token, "failed", None);
+        }
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::{broadcast, oneshot};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalsConfig {
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default)]
+    pub elicitation: bool,
+}
+
+fn default_timeout_sec() -> u64 {
+    300
+}
+
+impl Default for ApprovalsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_sec: default_timeout_sec(),
+            elicitation: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Decision {
+    Approved,
+    Denied { reason: Option<String> },
+}
+
+#[derive(Debug, Clone)]
+pub enum ApprovalEvent {
+    Requested { id: String, request: Value },
+    Resolved { id: String },
+}
+
+struct Pending {
+    request: Value,
+    requested_ms: u128,
+    decision: oneshot::Sender<Decision>,
+}
+
+pub struct Approvals {
+    next: AtomicU64,
+    pending: Mutex<BTreeMap<String, Pending>>,
+    events: broadcast::Sender<ApprovalEvent>,
+}
+
+impl Approvals {
+    pub fn new() -> Self {
+        Self {
+            next: AtomicU64::new(1),
+            pending: Mutex::new(BTreeMap::new()),
+            events: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn request(&self, request: Value) -> (String, oneshot::Receiver<Decision>) {
+        let id = format!("apr-{}", self.next.fetch_add(1, Ordering::Relaxed));
+        let (decision, receiver) = oneshot::channel();
+        self.pending.lock().expect("approvals poisoned").insert(
+            id.clone(),
+            Pending {
+                request: request.clone(),
+                requested_ms: now_ms(),
+                decision,
+            },
+        );
+        let _ = self.events.send(ApprovalEvent::Requested {
+            id: id.clone(),
+            request,
+        });
+        (id, receiver)
+    }
+
+    pub fn decide(&self, id: &str, decision: Decision) -> Result<Value> {
+        let Some(pending) = self.pending.lock().expect("approvals poisoned").remove(id) else {
+            bail!("keine offene Freigabe '{}'", id);
+        };
+        let _ = self
+            .events
+            .send(ApprovalEvent::Resolved { id: id.to_string() });
+        let approved = matches!(decision, Decision::Approved);
+        if pending.decision.send(decision).is_err() {
+            bail!("Freigabe '{}': der wartende Run ist bereits beendet", id);
+        }
+        Ok(json!({"approval_id": id, "approved": approved, "request": pending.request}))
+    }
+
+    pub fn withdraw(&self, id: &str) {
+        if self
+            .pending
+            .lock()
+            .expect("approvals poisoned")
+            .remove(id)
+            .is_some()
+        {
+            let _ = self
+                .events
+                .send(ApprovalEvent::Resolved { id: id.to_string() });
+        }
+    }
+
+    pub fn is_pending(&self, id: &str) -> bool {
+        self.pending
+            .lock()
+            .expect("approvals poisoned")
+            .contains_key(id)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn list(&self) -> Vec<Value> {
+        self.pending
+            .lock()
+            .expect("approvals poisoned")
+            .iter()
+            .map(|(id, pending)| {
+                json!({
+                    "approval_id": id,
+                    "requested_ms": pending.requested_ms,
+                    "request": pending.request
+                })
+            })
+            .collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.pending.lock().expect("approvals poisoned").len()
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,496 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use hmac::{Hmac, Mac};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::encryption::{self, Sealer};
+
+const SEALED_SUFFIX: &str = ".enc";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactsConfig {
+    #[serde(default)]
+    pub s3: Option<S3Config>,
+    #[serde(default = "default_max_transcript_bytes")]
+    pub max_transcript_bytes: usize,
+    #[serde(default)]
+    pub local_dir: Option<String>,
+    #[serde(default = "default_max_workspace_bytes")]
+    pub max_workspace_bytes: usize,
+    #[serde(default)]
+    pub client_roots: ClientRootsConfig,
+}
+
+fn default_max_transcript_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+fn default_max_workspace_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            s3: None,
+            max_transcript_bytes: default_max_transcript_bytes(),
+            local_dir: None,
+            max_workspace_bytes: default_max_workspace_bytes(),
+            client_roots: ClientRootsConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientRootsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_roots_subdir")]
+    pub subdir: String,
+    #[serde(default)]
+    pub allowed_prefixes: Vec<String>,
+}
+
+fn default_roots_subdir() -> String {
+    "kali-bridge".to_string()
+}
+
+impl Default for ClientRootsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            subdir: default_roots_subdir(),
+            allowed_prefixes: Vec::new(),
+        }
+    }
+}
+
+impl ClientRootsConfig {
+    pub fn select(&self, uris: &[String]) -> Option<PathBuf> {
+        uris.iter().find_map(|uri| {
+            let root = Url::parse(uri)
+                .ok()
+                .filter(|url| url.scheme() == "file")?
+                .to_file_path()
+                .ok()?;
+            let root = fs::canonicalize(root).ok().filter(|root| root.is_dir())?;
+            let allowed = self.allowed_prefixes.is_empty()
+                || self
+                    .allowed_prefixes
+                    .iter()
+                    .any(|prefix| root.starts_with(Path::new(prefix)));
+            allowed.then(|| root.join(key_segment(&self.subdir)))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default = "default_path_style")]
+    pub path_style: bool,
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    #[serde(default)]
+    pub session_token: Option<String>,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_prefix() -> String {
+    "runs".to_string()
+}
+
+fn default_path_style() -> bool {
+    true
+}
+
+fn default_timeout_sec() -> u64 {
+    60
+}
+
+#[derive(Debug, Default)]
+pub struct Transcript {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub truncated: bool,
+}
+
+impl Transcript {
+    pub fn push(&mut self, is_stdout: bool, data: &[u8], max_bytes: usize) {
+        let used = self.stdout.len() + self.stderr.len();
+        let remaining = max_bytes.saturating_sub(used);
+        if data.len() > remaining {
+            self.truncated = true;
+        }
+        let part = &data[..data.len().min(remaining)];
+        if is_stdout {
+            self.stdout.extend_from_slice(part);
+        } else {
+            self.stderr.extend_from_slice(part);
+        }
+    }
+}
+
+pub struct ArtifactStore {
+    client: reqwest::Client,
+    config: S3Config,
+    endpoint: Url,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    sealer: Option<Sealer>,
+    pub max_transcript_bytes: usize,
+}
+
+impl ArtifactStore {
+    pub fn open(config: &ArtifactsConfig, sealer: Option<Sealer>) -> Result<Option<Self>> {
+        let Some(s3) = &config.s3 else {
+            return Ok(None);
+        };
+        let endpoint = Url::parse(&s3.endpoint)
+            .with_context(|| format!("ungültiger S3-Endpoint '{}'", s3.endpoint))?;
+        if endpoint.host_str().is_none() {
+            bail!("S3-Endpoint '{}' enthält keinen Host", s3.endpoint);
+        }
+        let access_key_id = s3
+            .access_key_id
+            .clone()
+            .or_else(|| std::env::var("AWS_ACCESS_KEY_ID").ok())
+            .context("artifacts.s3: access_key_id fehlt (Config oder AWS_ACCESS_KEY_ID)")?;
+        let secret_access_key = s3
+            .secret_access_key
+            .clone()
+            .or_else(|| std::env::var("AWS_SECRET_ACCESS_KEY").ok())
+            .context("artifacts.s3: secret_access_key fehlt (Config oder AWS_SECRET_ACCESS_KEY)")?;
+        let session_token = s3
+            .session_token
+            .clone()
+            .or_else(|| std::env::var("AWS_SESSION_TOKEN").ok());
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(s3.timeout_sec))
+            .build()
+            .context("HTTP-Client für S3 konnte nicht erstellt werden")?;
+
+        Ok(Some(Self {
+            client,
+            config: s3.clone(),
+            endpoint,
+            access_key_id,
+            secret_access_key,
+            session_token,
+            sealer,
+            max_transcript_bytes: config.max_transcript_bytes,
+        }))
+    }
+
+    pub async fn upload_transcript(
+        &self,
+        engagement: Option<&str>,
+        correlation_id: &str,
+        run_token: &str,
+        transcript: &Transcript,
+    ) -> Result<Vec<String>> {
+        let base = self.run_prefix(engagement, correlation_id, run_token);
+        let mut urls = Vec::new();
+        for (name, body) in [
+            ("stdout", &transcript.stdout),
+            ("stderr", &transcript.stderr),
+        ] {
+            urls.push(self.put_object(&format!("{}/{}", base, name), body).await?);
+        }
+        Ok(urls)
+    }
+
+    pub async fn upload_workspace_file(
+        &self,
+        engagement: Option<&str>,
+        correlation_id: &str,
+        run_token: &str,
+        name: &str,
+        body: &[u8],
+    ) -> Result<String> {
+        let base = self.run_prefix(engagement, correlation_id, run_token);
+        let key = name
+            .split('/')
+            .map(key_segment)
+            .collect::<Vec<_>>()
+            .join("/");
+        self.put_object(&format!("{}/workspace/{}", base, key), body)
+            .await
+    }
+
+    fn run_prefix(
+        &self,
+        engagement: Option<&str>,
+        correlation_id: &str,
+        run_token: &str,
+    ) -> String {
+        let mut base = self.config.prefix.trim_matches('/').to_string();
+        if let Some(engagement) = engagement {
+            base = format!("{}/{}", base, key_segment(engagement));
+        }
+        format!(
+            "{}/{}/{}",
+            base,
+            key_segment(correlation_id),
+            key_segment(run_token)
+        )
+    }
+
+    pub async fn get_object(&self, url: &str) -> Result<Vec<u8>> {
+        let url = Url::parse(url).with_context(|| format!("ungültige Artefakt-URL '{}'", url))?;
+        if url.host_str() != self.object_url("")?.host_str() {
+            bail!(
+                "Artefakt-URL {} gehört nicht zum konfigurierten S3-Endpoint",
+                url
+            );
+        }
+        let response = self
+            .signed_request(reqwest::Method::GET, &url, &[])
+            .send()
+            .await
+            .with_context(|| format!("Download von {} fehlgeschlagen", url))?;
+        if !response.status().is_success() {
+            bail!(
+                "Download von {} fehlgeschlagen: HTTP {}",
+                url,
+                response.status().as_u16()
+            );
+        }
+        let body = response
+            .bytes()
+            .await
+            .with_context(|| format!("Download von {} fehlgeschlagen", url))?;
+        encryption::unseal(self.sealer.as_ref(), body.to_vec())
+            .with_context(|| format!("Artefakt {}", url))
+    }
+
+    async fn put_object(&self, key: &str, body: &[u8]) -> Result<String> {
+        let sealed;
+        let (key, body) = match &self.sealer {
+            Some(sealer) => {
+                sealed = sealer.seal(body)?;
+                (format!("{}{}", key, SEALED_SUFFIX), sealed.as_slice())
+            }
+            None => (key.to_string(), body),
+        };
+        let url = self.object_url(&key)?;
+        let response = self
+            .signed_request(reqwest::Method::PUT, &url, body)
+            .header("content-type", "application/octet-stream")
+            .header("content-length", body.len())
+            .body(body.to_vec())
+            .send()
+            .await
+            .with_context(|| format!("Upload von {} fehlgeschlagen", url))?;
+        if !response.status().is_success() {
+            bail!(
+                "Upload von {} fehlgeschlagen: HTTP {}",
+                url,
+                response.status().as_u16()
+            );
+        }
+        Ok(url.to_string())
+    }
+
+    fn signed_request(
+        &self,
+        method: reqwest::Method,
+        url: &Url,
+        body: &[u8],
+    ) -> reqwest::RequestBuilder {
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let payload_hash = hex_sha256(body);
+        let (amz_date, date) = amz_timestamps(SystemTime::now());
+
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect::<String>();
+        let canonical_request = format!(
+            "{}\n{}\n\n{}\n{}\n{}",
+            method,
+            url.path(),
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex_sha256(canonical_request.as_bytes())
+        );
+        let mut signing_key = hmac_sha256(
+            format!("AWS4{}", self.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.config.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac_sha256(&signing_key, part.as_bytes());
+        }
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        );
+
+        let mut request = self
+            .client
+            .request(method, url.clone())
+            .header("authorization", authorization);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        request
+    }
+
+    fn object_url(&self, key: &str) -> Result<Url> {
+        let mut url = self.endpoint.clone();
+        let base_path = url.path().trim_end_matches('/').to_string();
+        if self.config.path_style {
+            url.set_path(&format!("{}/{}/{}", base_path, self.config.bucket, key));
+        } else {
+            let host = format!(
+                "{}.{}",
+                self.config.bucket,
+                url.host_str().unwrap_or_default()
+            );
+            url.set_host(Some(&host))
+                .with_context(|| format!("ungültiger S3-Host '{}'", host))?;
+            url.set_path(&format!("{}/{}", base_path, key));
+        }
+        Ok(url)
+    }
+}
+
+pub fn store_local(
+    dir: &str,
+    engagement: Option<&str>,
+    correlation_id: &str,
+    run_token: &str,
+    name: &str,
+    body: &[u8],
+    sealer: Option<&Sealer>,
+) -> Result<String> {
+    let mut path = PathBuf::from(dir);
+    if let Some(engagement) = engagement {
+        path.push(key_segment(engagement));
+    }
+    path.push(key_segment(correlation_id));
+    path.push(key_segment(run_token));
+    path.push("workspace");
+    path.extend(name.split('/').map(key_segment));
+    let sealed = sealer.map(|sealer| sealer.seal(body)).transpose()?;
+    if sealed.is_some() {
+        path.as_mut_os_string().push(SEALED_SUFFIX);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "Artefakt-Verzeichnis {} konnte nicht angelegt werden",
+                parent.display()
+            )
+        })?;
+    }
+    fs::write(&path, sealed.as_deref().unwrap_or(body)).with_context(|| {
+        format!(
+            "Artefakt {} konnte nicht geschrieben werden",
+            path.display()
+        )
+    })?;
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    Ok(format!("file://{}", path.display()))
+}
+
+pub fn key_segment(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key).expect("HMAC akzeptiert beliebige Schlüssellängen");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn amz_timestamps(now: SystemTime) -> (String, String) {
+    let secs = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let amz_date = format!(
+        "{}T{:02}{:02}{:02}Z",
+        date,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    );
+    (amz_date, date)
+}
+
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
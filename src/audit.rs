@@ -0,0 +1,431 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::platform::OpenOptionsExt;
+use crate::secrets::random_bytes;
+
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: u64,
+    #[serde(default)]
+    pub rotate_interval_sec: Option<u64>,
+    #[serde(default)]
+    pub signing_key_path: Option<String>,
+}
+
+fn default_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_bytes: default_max_bytes(),
+            rotate_interval_sec: None,
+            signing_key_path: None,
+        }
+    }
+}
+
+struct State {
+    file: File,
+    size: u64,
+    opened_at: Instant,
+    seq: u64,
+    prev_hash: String,
+}
+
+pub struct AuditLog {
+    config: AuditConfig,
+    path: PathBuf,
+    signing_key: Option<SigningKey>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    pub files: Vec<String>,
+    pub records: u64,
+    pub signed: u64,
+    pub first_seq: Option<u64>,
+    pub last_seq: Option<u64>,
+    pub last_hash: Option<String>,
+}
+
+impl AuditLog {
+    pub fn open(config: &AuditConfig) -> Result<Option<Self>> {
+        let Some(path) = &config.path else {
+            return Ok(None);
+        };
+        let path = PathBuf::from(path);
+        let signing_key = config
+            .signing_key_path
+            .as_deref()
+            .map(load_signing_key)
+            .transpose()?;
+        let (seq, prev_hash) = match last_record(&path)? {
+            Some(record) => record,
+            None => match rotated_files(&path)?.pop() {
+                Some(rotated) => last_record(&rotated)?.unwrap_or((0, GENESIS_HASH.to_string())),
+                None => (0, GENESIS_HASH.to_string()),
+            },
+        };
+        let file = open_append(&path)?;
+        let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+
+        Ok(Some(Self {
+            config: config.clone(),
+            path,
+            signing_key,
+            state: Mutex::new(State {
+                file,
+                size,
+                opened_at: Instant::now(),
+                seq,
+                prev_hash,
+            }),
+        }))
+    }
+
+    pub fn append(&self, kind: &str, entry: Value) -> Result<()> {
+        let mut state = self.state.lock().expect("audit state poisoned");
+        if self.should_rotate(&state) {
+            self.rotate(&mut state)?;
+        }
+
+        let seq = state.seq + 1;
+        let mut record = json!({
+            "seq": seq,
+            "ts_ms": now_ms(),
+            "kind": kind,
+            "entry": entry,
+            "prev_hash": state.prev_hash
+        });
+        let hash = record_hash(&record);
+        record["hash"] = Value::String(hash.clone());
+        if let Some(key) = &self.signing_key {
+            record["signature"] =
+                Value::String(BASE64.encode(key.sign(hash.as_bytes()).to_bytes()));
+        }
+
+        let mut line =
+            serde_json::to_vec(&record).context("Audit-Record konnte nicht serialisiert werden")?;
+        line.push(b'\n');
+        state
+            .file
+            .write_all(&line)
+            .and_then(|_| state.file.sync_data())
+            .with_context(|| {
+                format!(
+                    "Audit-Log {} konnte nicht geschrieben werden",
+                    self.path.display()
+                )
+            })?;
+
+        state.size += line.len() as u64;
+        state.seq = seq;
+        state.prev_hash = hash;
+        Ok(())
+    }
+
+    fn should_rotate(&self, state: &State) -> bool {
+        if state.size == 0 {
+            return false;
+        }
+        state.size >= self.config.max_bytes
+            || self
+                .config
+                .rotate_interval_sec
+                .is_some_and(|interval| state.opened_at.elapsed() >= Duration::from_secs(interval))
+    }
+
+    fn rotate(&self, state: &mut State) -> Result<()> {
+        let rotated = PathBuf::from(format!("{}.{}", self.path.display(), now_ms()));
+        fs::rename(&self.path, &rotated).with_context(|| {
+            format!(
+                "Audit-Log konnte nicht nach {} rotiert werden",
+                rotated.display()
+            )
+        })?;
+        state.file = open_append(&self.path)?;
+        state.size = 0;
+        state.opened_at = Instant::now();
+        Ok(())
+    }
+}
+
+pub fn record_hash(record: &Value) -> String {
+    let mut unhashed = record.clone();
+    if let Some(object) = unhashed.as_object_mut() {
+        object.remove("hash");
+        object.remove("signature");
+    }
+    let canonical = serde_json::to_vec(&unhashed).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&canonical))
+}
+
+pub fn verify(path: &Path, public_key: Option<&VerifyingKey>) -> Result<VerifyReport> {
+    let files = log_files(path)?;
+
+    let mut report = VerifyReport {
+        files: files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect(),
+        records: 0,
+        signed: 0,
+        first_seq: None,
+        last_seq: None,
+        last_hash: None,
+    };
+
+    for file in &files {
+        let reader = BufReader::new(File::open(file).with_context(|| {
+            format!("Audit-Log {} konnte nicht gelesen werden", file.display())
+        })?);
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.with_context(|| {
+                format!("Audit-Log {} konnte nicht gelesen werden", file.display())
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let position = format!("{}:{}", file.display(), index + 1);
+            let record: Value = serde_json::from_str(&line)
+                .with_context(|| format!("{}: Audit-Record ist kein gültiges JSON", position))?;
+            verify_record(&record, &report, public_key)
+                .map_err(|error| anyhow!("{}: {}", position, error))?;
+
+            let seq = record["seq"].as_u64();
+            report.first_seq = report.first_seq.or(seq);
+            report.last_seq = seq;
+            report.last_hash = record["hash"].as_str().map(str::to_string);
+            report.records += 1;
+            if record.get("signature").is_some() {
+                report.signed += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn verify_record(
+    record: &Value,
+    report: &VerifyReport,
+    public_key: Option<&VerifyingKey>,
+) -> Result<()> {
+    let seq = record["seq"].as_u64().context("seq fehlt")?;
+    let hash = record["hash"].as_str().context("hash fehlt")?;
+    let prev_hash = record["prev_hash"].as_str().context("prev_hash fehlt")?;
+
+    match (report.last_seq, &report.last_hash) {
+        (Some(last_seq), Some(last_hash)) => {
+            if seq != last_seq + 1 {
+                bail!("seq {} folgt nicht auf {}", seq, last_seq);
+            }
+            if prev_hash != last_hash {
+                bail!("seq {}: prev_hash passt nicht zum vorherigen Record", seq);
+            }
+        }
+        _ => {
+            if seq == 1 && prev_hash != GENESIS_HASH {
+                bail!("seq 1: prev_hash ist nicht der Genesis-Hash");
+            }
+        }
+    }
+
+    if record_hash(record) != hash {
+        bail!("seq {}: hash stimmt nicht mit dem Inhalt überein", seq);
+    }
+
+    if let Some(key) = public_key {
+        let encoded = record["signature"]
+            .as_str()
+            .with_context(|| format!("seq {}: Signatur fehlt", seq))?;
+        let bytes = BASE64
+            .decode(encoded)
+            .with_context(|| format!("seq {}: Signatur ist kein gültiges base64", seq))?;
+        let signature = Signature::from_slice(&bytes)
+            .with_context(|| format!("seq {}: Signatur ungültig", seq))?;
+        key.verify_strict(hash.as_bytes(), &signature)
+            .with_context(|| format!("seq {}: Signaturprüfung fehlgeschlagen", seq))?;
+    }
+    Ok(())
+}
+
+pub fn read_records(path: &Path) -> Result<Vec<Value>> {
+    let files = log_files(path)?;
+
+    let mut records = Vec::new();
+    for file in &files {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Audit-Log {} konnte nicht gelesen werden", file.display()))?;
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(line).with_context(|| {
+                format!(
+                    "{}:{}: Audit-Record ist kein gültiges JSON",
+                    file.display(),
+                    index + 1
+                )
+            })?;
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+pub fn generate_signing_key(path: &Path) -> Result<VerifyingKey> {
+    let key = SigningKey::from_bytes(&random_bytes::<32>()?);
+
+    let mut file = OpenOptions::new()
+        .create_new(true)
+        .write(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| {
+            format!(
+                "Schlüsseldatei {} konnte nicht angelegt werden",
+                path.display()
+            )
+        })?;
+    writeln!(file, "{}", BASE64.encode(key.to_bytes())).with_context(|| {
+        format!(
+            "Schlüsseldatei {} konnte nicht geschrieben werden",
+            path.display()
+        )
+    })?;
+    Ok(key.verifying_key())
+}
+
+pub fn load_signing_key(path: &str) -> Result<SigningKey> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Signaturschlüssel {} konnte nicht gelesen werden", path))?;
+    let bytes = decode_key(content.trim())
+        .with_context(|| format!("Signaturschlüssel {} ist ungültig", path))?;
+    Ok(SigningKey::from_bytes(&bytes))
+}
+
+pub fn decode_public_key(encoded: &str) -> Result<VerifyingKey> {
+    let bytes = decode_key(encoded.trim()).context("öffentlicher Schlüssel ist ungültig")?;
+    VerifyingKey::from_bytes(&bytes)
+        .context("öffentlicher Schlüssel ist kein gültiger Ed25519-Punkt")
+}
+
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    BASE64.encode(key.to_bytes())
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32]> {
+    let bytes = BASE64.decode(encoded).context("kein gültiges base64")?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| anyhow!("32 Bytes erwartet, {} erhalten", bytes.len()))
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Audit-Log {} konnte nicht geöffnet werden", path.display()))
+}
+
+fn last_record(path: &Path) -> Result<Option<(u64, String)>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!("Audit-Log {} konnte nicht gelesen werden", path.display())
+            });
+        }
+    };
+    let mut last = None;
+    for line in BufReader::new(file).lines() {
+        let line = line
+            .with_context(|| format!("Audit-Log {} konnte nicht gelesen werden", path.display()))?;
+        if !line.trim().is_empty() {
+            last = Some(line);
+        }
+    }
+    let Some(line) = last else {
+        return Ok(None);
+    };
+    let record: Value = serde_json::from_str(&line).with_context(|| {
+        format!(
+            "letzter Audit-Record in {} ist kein gültiges JSON",
+            path.display()
+        )
+    })?;
+    let (Some(seq), Some(hash)) = (record["seq"].as_u64(), record["hash"].as_str()) else {
+        bail!(
+            "letzter Audit-Record in {} hat kein seq/hash",
+            path.display()
+        );
+    };
+    Ok(Some((seq, hash.to_string())))
+}
+
+fn log_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = rotated_files(path)?;
+    if path.exists() {
+        files.push(path.to_path_buf());
+    }
+    if files.is_empty() {
+        bail!("kein Audit-Log unter {} gefunden", path.display());
+    }
+    Ok(files)
+}
+
+fn rotated_files(path: &Path) -> Result<Vec<PathBuf>> {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(Vec::new());
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let prefix = format!("{}.", name);
+    let mut rotated = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let stamp = file_name.strip_prefix(&prefix)?.parse::<u128>().ok()?;
+            Some((stamp, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    rotated.sort_by_key(|(stamp, _)| *stamp);
+    Ok(rotated.into_iter().map(|(_, path)| path).collect())
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,73 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BandwidthConfig {
+    #[serde(default)]
+    pub output_bytes_per_sec: u64,
+    #[serde(default)]
+    pub artifact_bytes_per_sec: u64,
+    #[serde(default)]
+    pub global_bytes_per_sec: u64,
+}
+
+#[derive(Default)]
+pub struct Bucket {
+    next: Mutex<Option<Instant>>,
+}
+
+impl Bucket {
+    fn reserve(&self, rate: u64, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let mut next = self.next.lock().expect("bucket poisoned");
+        let start = next.filter(|next| *next > now).unwrap_or(now);
+        let end = start + Duration::from_secs_f64(bytes as f64 / rate as f64);
+        *next = Some(end);
+        end - now
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Throttle {
+    stages: Vec<(Arc<Bucket>, u64)>,
+}
+
+impl Throttle {
+    pub fn output(config: &BandwidthConfig, global: &Arc<Bucket>) -> Self {
+        Self::default()
+            .with(Arc::default(), config.output_bytes_per_sec)
+            .with(global.clone(), config.global_bytes_per_sec)
+    }
+
+    pub fn artifacts(config: &BandwidthConfig, global: &Arc<Bucket>) -> Self {
+        Self::default()
+            .with(Arc::default(), config.artifact_bytes_per_sec)
+            .with(global.clone(), config.global_bytes_per_sec)
+    }
+
+    fn with(mut self, bucket: Arc<Bucket>, rate: u64) -> Self {
+        if rate > 0 {
+            self.stages.push((bucket, rate));
+        }
+        self
+    }
+
+    pub fn slowest_rate(&self) -> Option<u64> {
+        self.stages.iter().map(|(_, rate)| *rate).min()
+    }
+
+    pub async fn pace(&self, bytes: usize) {
+        let wait = self
+            .stages
+            .iter()
+            .map(|(bucket, rate)| bucket.reserve(*rate, bytes))
+            .max()
+            .unwrap_or_default();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
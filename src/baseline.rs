@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::encryption::{self, Sealer};
+use crate::findings::Finding;
+use crate::targets;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselinesConfig {
+    #[serde(default = "default_state_dir")]
+    pub state_dir: String,
+    #[serde(default)]
+    pub jobs: BTreeMap<String, BaselineJob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineJob {
+    pub group: String,
+    #[serde(default = "default_interval_sec")]
+    pub interval_sec: u64,
+    pub workflow: Value,
+}
+
+fn default_state_dir() -> String {
+    "baselines".to_string()
+}
+
+fn default_interval_sec() -> u64 {
+    86400
+}
+
+impl Default for BaselinesConfig {
+    fn default() -> Self {
+        Self {
+            state_dir: default_state_dir(),
+            jobs: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn validate(config: &BaselinesConfig) -> Result<()> {
+    for (name, job) in &config.jobs {
+        if !targets::is_name(name) {
+            bail!(
+                "baselines.jobs: ungültiger Name '{}' (a-z, A-Z, 0-9, _, -, .)",
+                name
+            );
+        }
+        if !targets::is_name(&job.group) {
+            bail!(
+                "baselines.jobs.{}.group: ungültiger Listenname '{}'",
+                name,
+                job.group
+            );
+        }
+        if job.interval_sec == 0 {
+            bail!(
+                "baselines.jobs.{}.interval_sec muss größer als 0 sein",
+                name
+            );
+        }
+        if !job.workflow.is_object() {
+            bail!(
+                "baselines.jobs.{}.workflow muss ein Workflow-Objekt sein",
+                name
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub run_id: String,
+    pub finished_ms: u128,
+    pub findings: Vec<Finding>,
+}
+
+pub fn snapshot(run_id: &str, events: &[Value]) -> Result<Snapshot> {
+    let mut findings = Vec::new();
+    let mut failures = Vec::new();
+    for event in events {
+        let payload = &event["payload"];
+        match event["event"].as_str() {
+            Some("step_finished") => {
+                if payload["timed_out"] == true
+                    || payload["exit_code"].as_i64() != Some(0)
+                    || payload.get("script_error").is_some()
+                {
+                    failures.push(format!("Schritt {}", step_label(payload)));
+                    continue;
+                }
+                let step = serde_json::from_value::<Vec<Finding>>(payload["findings"].clone())
+                    .unwrap_or_default();
+                let target = payload["target"].as_str();
+                findings.extend(step.into_iter().map(|mut finding| {
+                    if finding.host.is_none() {
+                        finding.host = target.map(str::to_string);
+                    }
+                    finding
+                }));
+            }
+            Some("step_failed") => failures.push(format!(
+                "Schritt {}: {}",
+                step_label(payload),
+                payload["error"].as_str().unwrap_or("-")
+            )),
+            Some("error") => failures.push(
+                payload["message"]
+                    .as_str()
+                    .unwrap_or("Workflow-Fehler")
+                    .to_string(),
+            ),
+            _ => {}
+        }
+    }
+    if !failures.is_empty() {
+        bail!(
+            "Baseline-Lauf {} unvollständig: {}",
+            run_id,
+            failures.join("; ")
+        );
+    }
+    if !events
+        .iter()
+        .any(|event| event["event"] == "workflow_finished")
+    {
+        bail!("Baseline-Lauf {} wurde nicht abgeschlossen", run_id);
+    }
+    Ok(Snapshot {
+        run_id: run_id.to_string(),
+        finished_ms: now_ms(),
+        findings,
+    })
+}
+
+fn step_label(payload: &Value) -> String {
+    match payload["target"].as_str() {
+        Some(target) => format!("{} ({})", payload["index"], target),
+        None => payload["index"].to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Delta {
+    pub added: Vec<Finding>,
+    pub removed: Vec<Finding>,
+}
+
+impl Delta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+type FindingKey = (
+    String,
+    String,
+    Option<String>,
+    Option<u16>,
+    Option<String>,
+    String,
+);
+
+fn key(finding: &Finding) -> FindingKey {
+    (
+        finding.source.clone(),
+        finding.kind.clone(),
+        finding.host.clone(),
+        finding.port,
+        finding.protocol.clone(),
+        finding.title.clone(),
+    )
+}
+
+pub fn diff(previous: &[Finding], current: &[Finding]) -> Delta {
+    let before = previous.iter().map(key).collect::<BTreeSet<_>>();
+    let after = current.iter().map(key).collect::<BTreeSet<_>>();
+    let mut seen = BTreeSet::new();
+    Delta {
+        added: current
+            .iter()
+            .filter(|finding| !before.contains(&key(finding)) && seen.insert(key(finding)))
+            .cloned()
+            .collect(),
+        removed: previous
+            .iter()
+            .filter(|finding| !after.contains(&key(finding)) && seen.insert(key(finding)))
+            .cloned()
+            .collect(),
+    }
+}
+
+fn state_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.json", name))
+}
+
+pub fn load(dir: &Path, name: &str, sealer: Option<&Sealer>) -> Result<Option<Snapshot>> {
+    let path = state_path(dir, name);
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!("Baseline {} konnte nicht gelesen werden", path.display())
+            });
+        }
+    };
+    let data = encryption::unseal(sealer, data).with_context(|| {
+        format!(
+            "Baseline {} konnte nicht entschlüsselt werden",
+            path.display()
+        )
+    })?;
+    let snapshot = serde_json::from_slice(&data)
+        .with_context(|| format!("Baseline {} ist ungültig", path.display()))?;
+    Ok(Some(snapshot))
+}
+
+pub fn save(dir: &Path, name: &str, snapshot: &Snapshot, sealer: Option<&Sealer>) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| {
+        format!(
+            "Baseline-Verzeichnis {} konnte nicht angelegt werden",
+            dir.display()
+        )
+    })?;
+    let path = state_path(dir, name);
+    let data = serde_json::to_vec_pretty(snapshot)?;
+    let data = match sealer {
+        Some(sealer) => sealer.seal(&data)?,
+        None => data,
+    };
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data)
+        .with_context(|| format!("Baseline {} konnte nicht geschrieben werden", tmp.display()))?;
+    fs::rename(&tmp, &path)
+        .with_context(|| format!("Baseline {} konnte nicht ersetzt werden", path.display()))
+}
+
+pub fn due_in(previous: Option<&Snapshot>, interval_sec: u64) -> Duration {
+    let Some(previous) = previous else {
+        return Duration::ZERO;
+    };
+    let due_ms = previous
+        .finished_ms
+        .saturating_add(u128::from(interval_sec) * 1000);
+    Duration::from_millis(due_ms.saturating_sub(now_ms()).min(u128::from(u64::MAX)) as u64)
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
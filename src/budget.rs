@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::AsyncWrite;
+
+use crate::log_observation;
+
+pub const STDIO_SESSION: &str = "stdio";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextBudgetConfig {
+    #[serde(default)]
+    pub max_tokens: u64,
+    #[serde(default = "default_bytes_per_token")]
+    pub bytes_per_token: u64,
+    #[serde(default = "default_trim_from_percent")]
+    pub trim_from_percent: u64,
+    #[serde(default = "default_min_output_bytes")]
+    pub min_output_bytes: usize,
+}
+
+fn default_bytes_per_token() -> u64 {
+    4
+}
+
+fn default_trim_from_percent() -> u64 {
+    50
+}
+
+fn default_min_output_bytes() -> usize {
+    2048
+}
+
+impl Default for ContextBudgetConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens: 0,
+            bytes_per_token: default_bytes_per_token(),
+            trim_from_percent: default_trim_from_percent(),
+            min_output_bytes: default_min_output_bytes(),
+        }
+    }
+}
+
+pub fn validate(config: &ContextBudgetConfig) -> Result<()> {
+    if config.bytes_per_token == 0 {
+        bail!("context_budget.bytes_per_token muss größer als 0 sein");
+    }
+    if config.trim_from_percent > 100 {
+        bail!("context_budget.trim_from_percent darf höchstens 100 sein");
+    }
+    Ok(())
+}
+
+impl ContextBudgetConfig {
+    pub fn tokens(&self, bytes: u64) -> u64 {
+        bytes.div_ceil(self.bytes_per_token.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Counter {
+    calls: u64,
+    request_bytes: u64,
+    response_bytes: u64,
+}
+
+impl Counter {
+    fn add(&mut self, request_bytes: u64, response_bytes: u64) {
+        self.calls += 1;
+        self.request_bytes += request_bytes;
+        self.response_bytes += response_bytes;
+    }
+
+    fn bytes(&self) -> u64 {
+        self.request_bytes + self.response_bytes
+    }
+
+    fn to_json(&self, config: &ContextBudgetConfig) -> Value {
+        json!({
+            "calls": self.calls,
+            "request_bytes": self.request_bytes,
+            "response_bytes": self.response_bytes,
+            "approx_tokens": config.tokens(self.bytes())
+        })
+    }
+}
+
+#[derive(Default)]
+struct Usage {
+    client: Option<String>,
+    started_ms: u128,
+    total: Counter,
+    methods: BTreeMap<String, Counter>,
+    tools: BTreeMap<String, Counter>,
+    trimmed: u64,
+    exhausted: bool,
+}
+
+#[derive(Default)]
+pub struct Budgets {
+    usage: Mutex<HashMap<String, Usage>>,
+}
+
+impl Budgets {
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        &self,
+        config: &ContextBudgetConfig,
+        session: &str,
+        client: Option<&str>,
+        method: &str,
+        tool: Option<&str>,
+        request_bytes: u64,
+        response_bytes: u64,
+    ) {
+        let mut usage = self.usage.lock().expect("budgets poisoned");
+        let entry = usage.entry(session.to_string()).or_insert_with(|| Usage {
+            client: client.map(str::to_string),
+            started_ms: now_ms(),
+            ..Usage::default()
+        });
+        entry.total.add(request_bytes, response_bytes);
+        entry
+            .methods
+            .entry(method.to_string())
+            .or_default()
+            .add(request_bytes, response_bytes);
+        if let Some(tool) = tool {
+            entry
+                .tools
+                .entry(tool.to_string())
+                .or_default()
+                .add(request_bytes, response_bytes);
+        }
+        let used = config.tokens(entry.total.bytes());
+        if config.max_tokens > 0 && used >= config.max_tokens && !entry.exhausted {
+            entry.exhausted = true;
+            log_observation(
+                "context_budget_exhausted",
+                json!({"session": session, "client": entry.client, "used_tokens": used, "max_tokens": config.max_tokens}),
+            );
+        }
+    }
+
+    pub fn output_limit(
+        &self,
+        config: &ContextBudgetConfig,
+        session: &str,
+        base: usize,
+    ) -> Option<usize> {
+        if config.max_tokens == 0 {
+            return None;
+        }
+        let used = self.used_tokens(config, session).min(config.max_tokens);
+        let start = config.max_tokens * config.trim_from_percent.min(100) / 100;
+        if used < start {
+            return None;
+        }
+        let left = config.max_tokens - used;
+        let span = (config.max_tokens - start).max(1);
+        let scaled = (base as u128 * left as u128 / span as u128) as usize;
+        let remaining = (left * config.bytes_per_token.max(1)) as usize;
+        Some(scaled.min(remaining).max(config.min_output_bytes))
+    }
+
+    pub fn note_trimmed(&self, session: &str) {
+        let mut usage = self.usage.lock().expect("budgets poisoned");
+        if let Some(entry) = usage.get_mut(session) {
+            entry.trimmed += 1;
+        }
+    }
+
+    pub fn status(&self, config: &ContextBudgetConfig, session: &str) -> Value {
+        let used = self.used_tokens(config, session);
+        json!({
+            "used_tokens": used,
+            "max_tokens": config.max_tokens,
+            "remaining_tokens": config.max_tokens.saturating_sub(used)
+        })
+    }
+
+    pub fn retain(&self, open: &[String]) {
+        let mut usage = self.usage.lock().expect("budgets poisoned");
+        usage.retain(|session, _| session == STDIO_SESSION || open.contains(session));
+    }
+
+    pub fn snapshot(&self, config: &ContextBudgetConfig, session: Option<&str>) -> Value {
+        let usage = self.usage.lock().expect("budgets poisoned");
+        let mut sessions = usage
+            .iter()
+            .filter(|(id, _)| session.is_none_or(|session| session == id.as_str()))
+            .map(|(id, entry)| {
+                let used = config.tokens(entry.total.bytes());
+                let mut value = entry.total.to_json(config);
+                value["session"] = json!(id);
+                value["client"] = json!(entry.client);
+                value["started_ms"] = json!(entry.started_ms);
+                value["trimmed_outputs"] = json!(entry.trimmed);
+                if config.max_tokens > 0 {
+                    value["remaining_tokens"] = json!(config.max_tokens.saturating_sub(used));
+                    value["used_percent"] = json!(used * 100 / config.max_tokens);
+                }
+                value["methods"] = counters(config, &entry.methods);
+                value["tools"] = counters(config, &entry.tools);
+                value
+            })
+            .collect::<Vec<_>>();
+        sessions.sort_by_key(|value| value["started_ms"].as_u64().unwrap_or_default());
+        json!({
+            "max_tokens": config.max_tokens,
+            "bytes_per_token": config.bytes_per_token,
+            "trim_from_percent": config.trim_from_percent,
+            "sessions": sessions
+        })
+    }
+
+    fn used_tokens(&self, config: &ContextBudgetConfig, session: &str) -> u64 {
+        let usage = self.usage.lock().expect("budgets poisoned");
+        usage
+            .get(session)
+            .map_or(0, |entry| config.tokens(entry.total.bytes()))
+    }
+}
+
+fn counters(config: &ContextBudgetConfig, counters: &BTreeMap<String, Counter>) -> Value {
+    Value::Object(
+        counters
+            .iter()
+            .map(|(name, counter)| (name.clone(), counter.to_json(config)))
+            .collect(),
+    )
+}
+
+pub struct Metered<'a, W> {
+    inner: &'a mut W,
+    pub bytes: u64,
+}
+
+impl<'a, W> Metered<'a, W> {
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner, bytes: 0 }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for Metered<'_, W> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut *self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = &poll {
+            self.bytes += *written as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_shutdown(cx)
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
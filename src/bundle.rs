@@ -0,0 +1,272 @@
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::artifacts::{ArtifactStore, key_segment};
+use crate::encryption::{self, Sealer};
+use crate::engagement::Registry;
+
+const REDACTED_KEYS: &[&str] = &[
+    "secret_access_key",
+    "session_token",
+    "access_key_id",
+    "headers",
+    "token",
+];
+
+#[derive(Debug, Serialize)]
+pub struct BundleReport {
+    pub run_id: String,
+    pub runs: usize,
+    pub audit_records: usize,
+    pub transcripts: usize,
+    pub missing: Vec<String>,
+}
+
+pub async fn export(
+    run_id: &str,
+    records: &[Value],
+    mut config_snapshot: Value,
+    engagements: &Registry,
+    store: Option<&ArtifactStore>,
+    sealer: Option<&Sealer>,
+    out: &Path,
+) -> Result<BundleReport> {
+    let step_prefix = format!("{}-step-", run_id);
+    let matches_id = |entry: &Value| {
+        entry["correlation_id"]
+            .as_str()
+            .is_some_and(|id| id == run_id || id.starts_with(&step_prefix))
+            || entry["run_token"].as_str() == Some(run_id)
+    };
+    let run_tokens = records
+        .iter()
+        .filter(|record| record["kind"] == "run_started" && matches_id(&record["entry"]))
+        .filter_map(|record| record["entry"]["run_token"].as_str())
+        .collect::<BTreeSet<_>>();
+    let selected = records
+        .iter()
+        .filter(|record| {
+            let entry = &record["entry"];
+            matches_id(entry)
+                || entry["run_token"]
+                    .as_str()
+                    .is_some_and(|token| run_tokens.contains(token))
+        })
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        bail!("keine Audit-Records für Run '{}' gefunden", run_id);
+    }
+
+    let file = File::create(out)
+        .with_context(|| format!("Bundle {} konnte nicht angelegt werden", out.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut missing = Vec::new();
+    let mut transcripts = 0;
+    let mut runs = Vec::new();
+
+    for token in &run_tokens {
+        let of_run = |kind: &str| {
+            selected
+                .iter()
+                .find(|record| {
+                    record["kind"] == kind && record["entry"]["run_token"].as_str() == Some(*token)
+                })
+                .map(|record| record["entry"].clone())
+        };
+        let started = of_run("run_started").unwrap_or(Value::Null);
+        let finished = of_run("run_finished")
+            .or_else(|| of_run("run_failed"))
+            .unwrap_or(Value::Null);
+        let uploaded = of_run("artifacts_uploaded");
+        let dir = format!("runs/{}", key_segment(token));
+
+        write_json(
+            &mut zip,
+            &format!("{}/request.json", dir),
+            &started,
+            options,
+        )?;
+        write_json(
+            &mut zip,
+            &format!("{}/result.json", dir),
+            &finished,
+            options,
+        )?;
+
+        let urls = uploaded
+            .as_ref()
+            .and_then(|entry| entry["urls"].as_array().cloned())
+            .unwrap_or_default();
+        let transcript_truncated = uploaded
+            .as_ref()
+            .and_then(|entry| entry["transcript_truncated"].as_bool())
+            .unwrap_or(false);
+        let mut included = Vec::new();
+        for (stream, url) in ["stdout", "stderr"]
+            .into_iter()
+            .zip(urls.iter().filter_map(Value::as_str))
+        {
+            let Some(store) = store else {
+                missing.push(format!(
+                    "{}/{}: kein S3-Artefakt-Store konfiguriert ({})",
+                    dir, stream, url
+                ));
+                continue;
+            };
+            let data = match store.get_object(url).await {
+                Ok(data) => data,
+                Err(error) => {
+                    missing.push(format!("{}/{}: {:#}", dir, stream, error));
+                    continue;
+                }
+            };
+            let expected = finished[format!("{}_sha256", stream).as_str()].as_str();
+            let verified = !transcript_truncated
+                && expected == Some(format!("{:x}", Sha256::digest(&data)).as_str());
+            zip.start_file(format!("{}/{}", dir, stream), options)?;
+            zip.write_all(&data)?;
+            transcripts += 1;
+            included.push(json!({"stream": stream, "url": url, "bytes": data.len(), "sha256_verified": verified}));
+        }
+        if urls.is_empty() {
+            missing.push(format!(
+                "{}: keine Transkripte hochgeladen (artifacts.s3 nicht aktiv)",
+                dir
+            ));
+        }
+
+        runs.push(json!({
+            "run_token": token,
+            "correlation_id": started["correlation_id"],
+            "attempt": started["attempt"],
+            "tool": started["tool"],
+            "target": started["target"],
+            "exit_code": finished["exit_code"],
+            "timed_out": finished["timed_out"],
+            "transcript_truncated": transcript_truncated,
+            "transcripts": included
+        }));
+    }
+
+    let referenced = selected
+        .iter()
+        .filter_map(|record| record["entry"]["engagement"].as_str())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|name| engagements.engagements.get(name))
+        .collect::<Vec<_>>();
+    if !referenced.is_empty() {
+        write_json(
+            &mut zip,
+            "engagement.json",
+            &serde_json::to_value(&referenced)?,
+            options,
+        )?;
+    }
+
+    let mut findings = Vec::new();
+    for record in selected
+        .iter()
+        .filter(|record| record["kind"] == "findings")
+    {
+        let entry = &record["entry"];
+        match encryption::unseal_value(sealer, &entry["findings"]) {
+            Ok(decrypted) => findings.push(json!({
+                "correlation_id": entry["correlation_id"],
+                "source": entry["source"],
+                "tool": entry["tool"],
+                "findings": decrypted
+            })),
+            Err(error) => missing.push(format!(
+                "findings von {}: {:#}",
+                entry["correlation_id"], error
+            )),
+        }
+    }
+    if !findings.is_empty() {
+        write_json(&mut zip, "findings.json", &json!(findings), options)?;
+    }
+
+    let manifests = selected
+        .iter()
+        .filter(|record| record["kind"] == "run_manifest")
+        .map(|record| record["entry"].clone())
+        .collect::<Vec<_>>();
+    if !manifests.is_empty() {
+        write_json(&mut zip, "run_manifests.json", &json!(manifests), options)?;
+    }
+
+    redact_config(&mut config_snapshot);
+    write_json(&mut zip, "config.json", &config_snapshot, options)?;
+
+    zip.start_file("audit.jsonl", options)?;
+    for record in &selected {
+        writeln!(zip, "{}", record)?;
+    }
+
+    let created_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0);
+    write_json(
+        &mut zip,
+        "manifest.json",
+        &json!({
+            "run_id": run_id,
+            "created_ms": created_ms,
+            "runs": runs,
+            "audit_records": selected.len(),
+            "config_snapshot": "Konfiguration zum Exportzeitpunkt, Zugangsdaten entfernt",
+            "missing": missing
+        }),
+        options,
+    )?;
+    zip.finish()
+        .with_context(|| format!("Bundle {} konnte nicht geschrieben werden", out.display()))?;
+
+    Ok(BundleReport {
+        run_id: run_id.to_string(),
+        runs: run_tokens.len(),
+        audit_records: selected.len(),
+        transcripts,
+        missing,
+    })
+}
+
+fn write_json(
+    zip: &mut ZipWriter<File>,
+    name: &str,
+    value: &Value,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    zip.start_file(name, options)?;
+    zip.write_all(serde_json::to_string_pretty(value)?.as_bytes())?;
+    Ok(())
+}
+
+pub fn redact_config(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_KEYS.contains(&key.as_str()) && !value.is_null() {
+                    *value = Value::String("***".to_string());
+                } else {
+                    redact_config(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_config),
+        _ => {}
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_ttl")]
+    pub ttl_sec: u64,
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_ttl() -> u64 {
+    300
+}
+
+fn default_max_entries() -> usize {
+    128
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_sec: default_ttl(),
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+struct Entry<V> {
+    value: V,
+    stored_at: Instant,
+}
+
+pub struct ResultCache<K, V> {
+    config: CacheConfig,
+    entries: Mutex<HashMap<K, Entry<V>>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> ResultCache<K, V> {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            config: config.clone(),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled && self.config.max_entries > 0
+    }
+
+    pub fn get(&self, key: &K) -> Option<(V, Duration)> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut entries = self.entries.lock().expect("cache state poisoned");
+        let age = entries.get(key)?.stored_at.elapsed();
+        if age >= self.ttl() {
+            entries.remove(key);
+            return None;
+        }
+        entries.get(key).map(|entry| (entry.value.clone(), age))
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if !self.is_enabled() {
+            return;
+        }
+        let ttl = self.ttl();
+        let mut entries = self.entries.lock().expect("cache state poisoned");
+        entries.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+        if entries.len() >= self.config.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                value,
+                stored_at: Instant::now(),
+            },
+        );
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.config.ttl_sec)
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::platform;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionSharingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub socket_dir: Option<String>,
+    #[serde(default = "default_persist_sec")]
+    pub persist_sec: u64,
+    #[serde(default = "default_reconnect_attempts")]
+    pub reconnect_attempts: u32,
+}
+
+fn default_persist_sec() -> u64 {
+    300
+}
+
+fn default_reconnect_attempts() -> u32 {
+    2
+}
+
+impl Default for ConnectionSharingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            socket_dir: None,
+            persist_sec: default_persist_sec(),
+            reconnect_attempts: default_reconnect_attempts(),
+        }
+    }
+}
+
+impl ConnectionSharingConfig {
+    fn socket_dir(&self) -> PathBuf {
+        match &self.socket_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::temp_dir().join("ollama-kali-bridge-ssh"),
+        }
+    }
+
+    fn control_path(&self, target: &str) -> PathBuf {
+        let digest = format!("{:x}", Sha256::digest(target.as_bytes()));
+        self.socket_dir().join(format!("{}.sock", &digest[..16]))
+    }
+
+    pub fn ssh_options(&self, target: &str) -> Vec<String> {
+        let path = self.control_path(target);
+        if !self.enabled || !path.exists() {
+            return Vec::new();
+        }
+        vec![
+            "ControlMaster=no".to_string(),
+            format!("ControlPath={}", path.display()),
+        ]
+    }
+}
+
+struct Entry {
+    holders: usize,
+    gate: Arc<tokio::sync::Mutex<()>>,
+}
+
+#[derive(Default)]
+pub struct Leases {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+pub struct Lease<'a> {
+    leases: &'a Leases,
+    pub target: String,
+    pub path: PathBuf,
+    program: String,
+    gate: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl Leases {
+    pub fn acquire(
+        &self,
+        config: &ConnectionSharingConfig,
+        program: &str,
+        target: &str,
+    ) -> Result<Lease<'_>> {
+        let dir = config.socket_dir();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("{} konnte nicht angelegt werden", dir.display()))?;
+        platform::set_mode(&dir, 0o700).with_context(|| {
+            format!("Rechte für {} konnten nicht gesetzt werden", dir.display())
+        })?;
+        let mut entries = self.entries.lock().expect("leases poisoned");
+        let entry = entries.entry(target.to_string()).or_insert_with(|| Entry {
+            holders: 0,
+            gate: Arc::new(tokio::sync::Mutex::new(())),
+        });
+        entry.holders += 1;
+        Ok(Lease {
+            leases: self,
+            target: target.to_string(),
+            path: config.control_path(target),
+            program: program.to_string(),
+            gate: entry.gate.clone(),
+        })
+    }
+}
+
+impl Lease<'_> {
+    pub async fn lock(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.gate.lock().await
+    }
+
+    pub async fn alive(&self) -> bool {
+        self.control(&["-O", "check"])
+            .status()
+            .await
+            .is_ok_and(|status| status.success())
+    }
+
+    fn control(&self, args: &[&str]) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.program);
+        command
+            .arg("-o")
+            .arg(format!("ControlPath={}", self.path.display()))
+            .args(args)
+            .arg(&self.target)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null());
+        command
+    }
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.leases.entries.lock().expect("leases poisoned");
+        let Some(entry) = entries.get_mut(&self.target) else {
+            return;
+        };
+        entry.holders -= 1;
+        if entry.holders > 0 {
+            return;
+        }
+        entries.remove(&self.target);
+        if self.path.exists() && tokio::runtime::Handle::try_current().is_ok() {
+            let _ = self.control(&["-O", "exit"]).spawn();
+        }
+    }
+}
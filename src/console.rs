@@ -0,0 +1,414 @@
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use serde_json::{Value, json};
+
+use crate::messages;
+
+pub const HELP: &str = "\
+Befehle:
+  tools                          freigegebene Tools anzeigen
+  show <tool>                    Policy und Parameter eines Tools anzeigen
+  set [host|user|engagement|timeout <wert>]
+                                 Vorgaben für neue Requests setzen bzw. anzeigen ('-' löscht)
+  run <tool> [args…]             Request starten, Ausgabe läuft live mit
+  run {json}                     RunRequest als JSON starten
+  dry <tool> [args…] | dry {json}
+                                 Request prüfen und Kommandozeile anzeigen, ohne ihn auszuführen
+  history [n]                    bisherige Runs bzw. Details zu Run n
+  rerun <n>                      Run n erneut starten
+  approvals                      wartende Freigaben anzeigen
+  approve <id>                   Freigabe erteilen
+  deny <id> [grund]              Freigabe ablehnen
+  status | pause [--kill] | resume
+  help | quit
+
+Bei Profil-Tools werden Argumente der Form key=value zu params (Werte als JSON, sonst Text).";
+
+const KNOWN: &[&str] = &[
+    "tools",
+    "show",
+    "set",
+    "run",
+    "dry",
+    "history",
+    "rerun",
+    "approvals",
+    "approve",
+    "deny",
+    "status",
+    "pause",
+    "resume",
+];
+
+pub enum ConsoleCommand {
+    Help,
+    Tools,
+    Show(String),
+    Set(Option<(String, String)>),
+    Run { dry: bool, spec: RequestSpec },
+    History(Option<usize>),
+    Rerun(usize),
+    Control(Value),
+    Quit,
+}
+
+pub enum RequestSpec {
+    Json(String),
+    Words(String, Vec<String>),
+}
+
+#[derive(Default)]
+pub struct Defaults {
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub engagement: Option<String>,
+    pub timeout_sec: Option<u64>,
+}
+
+impl Defaults {
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = (value != "-").then(|| value.to_string());
+        match key {
+            "host" => self.host = value,
+            "user" => self.user = value,
+            "engagement" => self.engagement = value,
+            "timeout" => {
+                self.timeout_sec = value
+                    .map(|value| value.parse::<u64>())
+                    .transpose()
+                    .map_err(|_| anyhow::anyhow!("timeout erwartet Sekunden"))?
+            }
+            _ => bail!(
+                "unbekannte Vorgabe '{}' (host, user, engagement, timeout)",
+                key
+            ),
+        }
+        Ok(())
+    }
+
+    pub fn show(&self) -> Value {
+        json!({"host": self.host, "user": self.user, "engagement": self.engagement, "timeout_sec": self.timeout_sec})
+    }
+
+    pub fn apply(&self, request: &mut serde_json::Map<String, Value>) {
+        for (key, value) in [
+            ("host", self.host.as_ref().map(|host| json!(host))),
+            ("user", self.user.as_ref().map(|user| json!(user))),
+            (
+                "engagement",
+                self.engagement.as_ref().map(|engagement| json!(engagement)),
+            ),
+            (
+                "timeout_sec",
+                self.timeout_sec.map(|timeout| json!(timeout)),
+            ),
+            ("stream_mode", Some(json!("lines"))),
+        ] {
+            if let Some(value) = value {
+                request.entry(key).or_insert(value);
+            }
+        }
+    }
+}
+
+pub fn parse(line: &str) -> Result<Option<ConsoleCommand>> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    if matches!(name, "run" | "dry") && rest.starts_with('{') {
+        return Ok(Some(ConsoleCommand::Run {
+            dry: name == "dry",
+            spec: RequestSpec::Json(rest.to_string()),
+        }));
+    }
+    let words = split_words(rest)?;
+    let command = match (name, words.as_slice()) {
+        ("", _) => return Ok(None),
+        ("help" | "?", _) => ConsoleCommand::Help,
+        ("quit" | "exit", _) => ConsoleCommand::Quit,
+        ("tools", []) => ConsoleCommand::Tools,
+        ("show", [tool]) => ConsoleCommand::Show(tool.clone()),
+        ("set", []) => ConsoleCommand::Set(None),
+        ("set", [key, value]) => ConsoleCommand::Set(Some((key.clone(), value.clone()))),
+        ("run" | "dry", [tool, args @ ..]) => ConsoleCommand::Run {
+            dry: name == "dry",
+            spec: RequestSpec::Words(tool.clone(), args.to_vec()),
+        },
+        ("history", []) => ConsoleCommand::History(None),
+        ("history", [index]) => ConsoleCommand::History(Some(parse_index(index)?)),
+        ("rerun", [index]) => ConsoleCommand::Rerun(parse_index(index)?),
+        ("approvals" | "status" | "resume", []) => {
+            ConsoleCommand::Control(json!({"command": name}))
+        }
+        ("pause", []) => ConsoleCommand::Control(json!({"command": "pause"})),
+        ("pause", [flag]) if flag == "--kill" => {
+            ConsoleCommand::Control(json!({"command": "pause", "kill": true}))
+        }
+        ("approve", [id]) => {
+            ConsoleCommand::Control(json!({"command": "approve", "approval_id": id}))
+        }
+        ("deny", [id, reason @ ..]) => ConsoleCommand::Control(json!({
+            "command": "deny",
+            "approval_id": id,
+            "reason": (!reason.is_empty()).then(|| reason.join(" "))
+        })),
+        (name, _) if KNOWN.contains(&name) => bail!("falsche Argumente für '{}', siehe help", name),
+        _ => bail!("unbekannter Befehl '{}', siehe help", name),
+    };
+    Ok(Some(command))
+}
+
+fn parse_index(value: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(index) if index > 0 => Ok(index),
+        _ => bail!("'{}' ist keine Run-Nummer", value),
+    }
+}
+
+pub fn split_words(input: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars();
+    while let Some(character) = chars.next() {
+        match character {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(character) => current.push(character),
+                        None => bail!("schließendes ' fehlt"),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => current.push(escaped),
+                            None => bail!("schließendes \" fehlt"),
+                        },
+                        Some(character) => current.push(character),
+                        None => bail!("schließendes \" fehlt"),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.extend(chars.next());
+            }
+            character if character.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            character => {
+                in_word = true;
+                current.push(character);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+pub fn params(words: &[String]) -> Result<serde_json::Map<String, Value>> {
+    words
+        .iter()
+        .map(|word| {
+            let Some((key, value)) = word.split_once('=') else {
+                bail!("Profil-Tools erwarten key=value statt '{}'", word);
+            };
+            let value =
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+            Ok((key.to_string(), value))
+        })
+        .collect()
+}
+
+pub fn format_event(event: &Value) -> Option<String> {
+    let id = event["id"].as_str().unwrap_or("?");
+    let payload = &event["payload"];
+    let data = || payload["data"].as_str().unwrap_or_default().to_string();
+    let line = match event["event"].as_str()? {
+        "stdout_chunk" | "stderr_chunk" => {
+            let mut text = String::new();
+            for line in data().split_inclusive('\n') {
+                text.push_str(&format!("[{}] {}", id, line));
+            }
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            return Some(text);
+        }
+        "stdout_line" => format!("[{}] {}", id, data()),
+        "stderr_line" => format!("[{}] ! {}", id, data()),
+        "started" => format!(
+            "[{}] gestartet: {} auf {} (Timeout {}s)",
+            id,
+            payload["tool"].as_str().unwrap_or("?"),
+            payload["target"].as_str().unwrap_or("?"),
+            payload["timeout_sec"]
+        ),
+        "queued" => format!(
+            "[{}] wartet in der Queue (Position {})",
+            id, payload["position"]
+        ),
+        "approval_pending" => {
+            let approval = payload["approval_id"].as_str().unwrap_or("?");
+            format!(
+                "[{}] wartet auf Freigabe: approve {} | deny {}",
+                id, approval, approval
+            )
+        }
+        "output_truncated" => format!(
+            "[{}] Ausgabe abgeschnitten nach {} Bytes",
+            id, payload["max_output_bytes"]
+        ),
+        "retry" => format!("[{}] neuer Versuch {}", id, payload["attempt"]),
+        "finished" => {
+            let mut line = format!(
+                "[{}] beendet: exit_code={} timed_out={} {} ms",
+                id, payload["exit_code"], payload["timed_out"], payload["duration_ms"]
+            );
+            if let Some(summary) = payload.get("summary") {
+                line.push_str(&format!("\n[{}] summary: {}", id, summary));
+            }
+            line
+        }
+        "error" => format!(
+            "[{}] {}: {}",
+            id,
+            messages::error_prefix(),
+            payload["message"].as_str().unwrap_or("?")
+        ),
+        other => format!("[{}] {}: {}", id, other, payload),
+    };
+    Some(format!("{}\n", line))
+}
+
+struct Entry {
+    id: String,
+    request: Value,
+    started_ms: u128,
+    finished_ms: Option<u128>,
+    result: Option<Result<Value, String>>,
+}
+
+#[derive(Default)]
+pub struct History {
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl History {
+    pub fn next_id(&self) -> String {
+        format!(
+            "console-{}",
+            self.entries.lock().expect("console history poisoned").len() + 1
+        )
+    }
+
+    pub fn push(&self, id: &str, request: Value) -> usize {
+        let mut entries = self.entries.lock().expect("console history poisoned");
+        entries.push(Entry {
+            id: id.to_string(),
+            request,
+            started_ms: now_ms(),
+            finished_ms: None,
+            result: None,
+        });
+        entries.len()
+    }
+
+    pub fn finish(&self, index: usize, result: Result<Value, String>) {
+        if let Some(entry) = self
+            .entries
+            .lock()
+            .expect("console history poisoned")
+            .get_mut(index - 1)
+        {
+            entry.finished_ms = Some(now_ms());
+            entry.result = Some(result);
+        }
+    }
+
+    pub fn request(&self, index: usize) -> Result<Value> {
+        match self
+            .entries
+            .lock()
+            .expect("console history poisoned")
+            .get(index - 1)
+        {
+            Some(entry) => Ok(entry.request.clone()),
+            None => bail!("Run {} gibt es nicht", index),
+        }
+    }
+
+    pub fn detail(&self, index: usize) -> Result<Value> {
+        let entries = self.entries.lock().expect("console history poisoned");
+        let Some(entry) = entries.get(index - 1) else {
+            bail!("Run {} gibt es nicht", index);
+        };
+        let mut detail = json!({
+            "id": entry.id,
+            "request": entry.request,
+            "started_ms": entry.started_ms,
+            "finished_ms": entry.finished_ms
+        });
+        match &entry.result {
+            Some(Ok(status)) => detail["status"] = status.clone(),
+            Some(Err(error)) => detail["error"] = json!(error),
+            None => detail["running"] = json!(true),
+        }
+        Ok(detail)
+    }
+
+    pub fn overview(&self) -> String {
+        let entries = self.entries.lock().expect("console history poisoned");
+        if entries.is_empty() {
+            return "noch keine Runs".to_string();
+        }
+        let now = now_ms();
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let state = match &entry.result {
+                    None => "läuft".to_string(),
+                    Some(Ok(status)) if status["timed_out"] == true => "Timeout".to_string(),
+                    Some(Ok(status)) => format!("exit {}", status["exit_code"]),
+                    Some(Err(_)) => "Fehler".to_string(),
+                };
+                let duration = entry
+                    .finished_ms
+                    .unwrap_or(now)
+                    .saturating_sub(entry.started_ms);
+                format!(
+                    "{:>3}  {:<12} {:<12} {:<16} {:>8.1}s  {}",
+                    index + 1,
+                    entry.id,
+                    entry.request["tool"].as_str().unwrap_or("?"),
+                    entry.request["host"].as_str().unwrap_or("?"),
+                    duration as f64 / 1000.0,
+                    state
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,380 @@
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+#[cfg(not(unix))]
+use tokio::net::{TcpListener as Listener, TcpStream as Stream};
+#[cfg(unix)]
+use tokio::net::{UnixListener as Listener, UnixStream as Stream};
+use tokio::sync::watch;
+
+#[cfg(unix)]
+use crate::messages::Message;
+use crate::platform;
+
+const MAX_COMMAND_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ControlConfig {
+    #[serde(default)]
+    pub socket_path: Option<String>,
+    #[serde(default)]
+    pub mcp_admin: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    Pause {
+        #[arg(long)]
+        #[serde(default)]
+        kill: bool,
+    },
+    Resume,
+    Status,
+    Health,
+    Jobs,
+    Cancel {
+        run: String,
+    },
+    Tail {
+        #[arg(long)]
+        #[serde(default)]
+        after: Option<u64>,
+        #[arg(long, short)]
+        #[serde(skip)]
+        follow: bool,
+    },
+    Activity {
+        #[arg(long)]
+        #[serde(default)]
+        run: Option<String>,
+    },
+    GetConfig,
+    ReloadConfig,
+    SetToolEnabled {
+        tool: String,
+        #[arg(long, action = clap::ArgAction::Set, default_value_t = true)]
+        #[serde(default = "default_enabled")]
+        enabled: bool,
+    },
+    Approvals,
+    Approve {
+        approval_id: String,
+    },
+    Deny {
+        approval_id: String,
+        #[arg(long)]
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    ContextBudget {
+        #[arg(long)]
+        #[serde(default)]
+        session: Option<String>,
+    },
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub struct Control {
+    paused: AtomicBool,
+    kills: watch::Sender<u64>,
+}
+
+impl Control {
+    pub fn new() -> Self {
+        let (kills, _) = watch::channel(0);
+        Self {
+            paused: AtomicBool::new(false),
+            kills,
+        }
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn kill_all(&self) {
+        self.kills.send_modify(|generation| *generation += 1);
+    }
+
+    pub fn kill_generation(&self) -> u64 {
+        *self.kills.borrow()
+    }
+
+    pub async fn killed_since(&self, generation: u64) {
+        let mut rx = self.kills.subscribe();
+        let _ = rx.wait_for(|current| *current > generation).await;
+    }
+}
+
+pub fn mcp_tools(config: &ControlConfig) -> Vec<Value> {
+    if !config.mcp_admin {
+        return Vec::new();
+    }
+    vec![
+        json!({
+            "name": "admin.pause",
+            "description": "Stops accepting new runs; with kill=true all in-flight remote processes are killed",
+            "inputSchema": {"type": "object", "properties": {"kill": {"type": "boolean"}}}
+        }),
+        json!({
+            "name": "admin.resume",
+            "description": "Accepts new runs again after a pause",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.status",
+            "description": "Shows pause state, active sessions and queue depth",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.health",
+            "description": "Reports readiness: config validity, control socket, pause and drain state, queue depth and per-host SSH circuit state",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.jobs",
+            "description": "Lists running jobs with run id, run token, tool, host and output size, plus per-host queue depth",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.cancel",
+            "description": "Cancels one running job by run id or run token and kills its remote process group; other runs keep running",
+            "inputSchema": {
+                "type": "object",
+                "required": ["run"],
+                "properties": {"run": {"type": "string", "description": "Run id or run token"}}
+            }
+        }),
+        json!({
+            "name": "admin.tail",
+            "description": "Returns recent run and control events; pass the last seen seq as after to get only newer events",
+            "inputSchema": {"type": "object", "properties": {"after": {"type": "integer", "minimum": 0}}}
+        }),
+        json!({
+            "name": "admin.activity",
+            "description": "Shows active and recently finished runs, per-host load, recent run events and the output tail of one run",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"run": {"type": "string", "description": "Run id or run token whose output tail is returned"}}
+            }
+        }),
+        json!({
+            "name": "admin.get_config",
+            "description": "Shows the effective configuration with credentials removed",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.reload_config",
+            "description": "Reloads tools, limits, RBAC and engagements from the config file",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.set_tool_enabled",
+            "description": "Enables or disables a configured tool until the next reload",
+            "inputSchema": {
+                "type": "object",
+                "required": ["tool", "enabled"],
+                "properties": {"tool": {"type": "string"}, "enabled": {"type": "boolean"}}
+            }
+        }),
+        json!({
+            "name": "admin.approvals",
+            "description": "Lists runs waiting for operator approval",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "admin.approve",
+            "description": "Approves a pending approval-gated run",
+            "inputSchema": {
+                "type": "object",
+                "required": ["approval_id"],
+                "properties": {"approval_id": {"type": "string"}}
+            }
+        }),
+        json!({
+            "name": "admin.deny",
+            "description": "Denies a pending approval-gated run",
+            "inputSchema": {
+                "type": "object",
+                "required": ["approval_id"],
+                "properties": {"approval_id": {"type": "string"}, "reason": {"type": "string"}}
+            }
+        }),
+        json!({
+            "name": "admin.context_budget",
+            "description": "Shows per-session MCP traffic: request and response bytes, approximate tokens per method and tool, and the remaining context budget",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"session": {"type": "string", "description": "MCP session id (\"stdio\" for mcp-serve); all sessions if omitted"}}
+            }
+        }),
+    ]
+}
+
+pub fn mcp_command(
+    config: &ControlConfig,
+    action: &str,
+    arguments: Value,
+) -> Result<ControlCommand> {
+    if !config.mcp_admin {
+        bail!(Message::Disabled {
+            tool: format!("admin.{}", action),
+            setting: "control.mcp_admin=false"
+        });
+    }
+    let mut arguments = match arguments {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    arguments.insert("command".to_string(), Value::String(action.to_string()));
+    serde_json::from_value(Value::Object(arguments)).map_err(|error| {
+        Message::InvalidArguments {
+            tool: format!("admin.{}", action),
+            error: error.to_string(),
+        }
+        .into()
+    })
+}
+
+#[cfg(unix)]
+pub async fn bind(path: &str) -> Result<Listener> {
+    if Path::new(path).exists() {
+        if Stream::connect(path).await.is_ok() {
+            bail!(
+                "Control-Socket {} wird bereits von einer anderen Bridge verwendet",
+                path
+            );
+        }
+        std::fs::remove_file(path).with_context(|| {
+            format!(
+                "veralteter Control-Socket {} konnte nicht entfernt werden",
+                path
+            )
+        })?;
+    }
+    let listener = Listener::bind(path)
+        .with_context(|| format!("Control-Socket {} konnte nicht angelegt werden", path))?;
+    platform::set_mode(Path::new(path), 0o600).with_context(|| {
+        format!(
+            "Rechte für Control-Socket {} konnten nicht gesetzt werden",
+            path
+        )
+    })?;
+    Ok(listener)
+}
+
+#[cfg(not(unix))]
+pub async fn bind(address: &str) -> Result<Listener> {
+    let parsed = address.parse::<std::net::SocketAddr>().with_context(|| {
+        format!(
+            "control.socket_path {} muss unter Windows eine Loopback-Adresse mit Port sein",
+            address
+        )
+    })?;
+    if !parsed.ip().is_loopback() {
+        bail!(
+            "control.socket_path {} muss unter Windows eine Loopback-Adresse sein",
+            address
+        );
+    }
+    Listener::bind(parsed)
+        .await
+        .with_context(|| format!("Control-Socket {} konnte nicht angelegt werden", address))
+}
+
+#[cfg(unix)]
+pub fn listening(path: &str) -> bool {
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+
+#[cfg(not(unix))]
+pub fn listening(address: &str) -> bool {
+    std::net::TcpStream::connect(address).is_ok()
+}
+
+pub async fn serve<F>(listener: Listener, handle: Arc<F>)
+where
+    F: Fn(ControlCommand) -> Result<Value> + Send + Sync + 'static,
+{
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            continue;
+        };
+        let handle = handle.clone();
+        tokio::spawn(async move {
+            let _ = tokio::time::timeout(
+                Duration::from_secs(5),
+                handle_connection(stream, handle.as_ref()),
+            )
+            .await;
+        });
+    }
+}
+
+async fn handle_connection<F>(stream: Stream, handle: &F) -> Result<()>
+where
+    F: Fn(ControlCommand) -> Result<Value>,
+{
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader.take(MAX_COMMAND_BYTES as u64))
+        .read_line(&mut line)
+        .await
+        .context("Control-Kommando konnte nicht gelesen werden")?;
+    let response = match serde_json::from_str::<ControlCommand>(&line) {
+        Ok(command) => match handle(command) {
+            Ok(status) => json!({"ok": true, "status": status}),
+            Err(error) => json!({"ok": false, "error": format!("{:#}", error)}),
+        },
+        Err(error) => {
+            json!({"ok": false, "error": format!("ungültiges Control-Kommando: {}", error)})
+        }
+    };
+    writer
+        .write_all(format!("{}\n", response).as_bytes())
+        .await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+pub async fn send(path: &str, command: &ControlCommand) -> Result<Value> {
+    let stream = Stream::connect(path)
+        .await
+        .with_context(|| format!("Control-Socket {} nicht erreichbar", path))?;
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(format!("{}\n", serde_json::to_string(command)?).as_bytes())
+        .await?;
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    let response: Value =
+        serde_json::from_str(&line).context("ungültige Antwort vom Control-Socket")?;
+    if response["ok"] != true {
+        bail!(
+            "{}",
+            response["error"].as_str().unwrap_or("unbekannter Fehler")
+        );
+    }
+    Ok(response["status"].clone())
+}
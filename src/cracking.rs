@@ -0,0 +1,770 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::findings::{Finding, Severity};
+use crate::fs::{self, FsConfig};
+use crate::messages::Message;
+use crate::secrets::{self, SecretsConfig};
+use crate::shell_escape;
+use crate::wordlists::{self, WordlistsConfig};
+
+const DENIED_EXIT: i32 = 3;
+const MISSING_EXIT: i32 = 4;
+const BUSY_EXIT: i32 = 6;
+const WORDLIST_EXIT: i32 = 7;
+const HASHES_EXIT: i32 = 8;
+const KILL_AFTER_SEC: u64 = 30;
+const STATUS_TIMER_SEC: u64 = 15;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrackingConfig {
+    #[serde(default)]
+    pub john: Option<String>,
+    #[serde(default)]
+    pub hashcat: Option<String>,
+    #[serde(default = "default_jobs_dir")]
+    pub jobs_dir: String,
+    #[serde(default)]
+    pub wordlists: Vec<String>,
+    #[serde(default)]
+    pub john_rules: Vec<String>,
+    #[serde(default)]
+    pub hashcat_rules: BTreeMap<String, String>,
+    #[serde(default = "default_max_hashes")]
+    pub max_hashes: usize,
+    #[serde(default = "default_max_runtime_sec")]
+    pub max_runtime_sec: u64,
+    #[serde(default = "default_max_jobs")]
+    pub max_jobs: usize,
+}
+
+fn default_jobs_dir() -> String {
+    "/tmp/ollama-kali-bridge-crack".to_string()
+}
+
+fn default_max_hashes() -> usize {
+    10_000
+}
+
+fn default_max_runtime_sec() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_max_jobs() -> usize {
+    1
+}
+
+impl Default for CrackingConfig {
+    fn default() -> Self {
+        Self {
+            john: None,
+            hashcat: None,
+            jobs_dir: default_jobs_dir(),
+            wordlists: Vec::new(),
+            john_rules: Vec::new(),
+            hashcat_rules: BTreeMap::new(),
+            max_hashes: default_max_hashes(),
+            max_runtime_sec: default_max_runtime_sec(),
+            max_jobs: default_max_jobs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    John,
+    Hashcat,
+}
+
+impl Engine {
+    fn as_str(self) -> &'static str {
+        match self {
+            Engine::John => "john",
+            Engine::Hashcat => "hashcat",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum CrackAction {
+    Submit {
+        host: Option<String>,
+        user: Option<String>,
+        engine: Engine,
+        #[serde(default)]
+        hashes: Vec<String>,
+        hash_file: Option<String>,
+        format: Option<String>,
+        mode: Option<u32>,
+        wordlist: String,
+        rules: Option<String>,
+        max_runtime_sec: Option<u64>,
+    },
+    Status {
+        host: Option<String>,
+        user: Option<String>,
+        job_id: String,
+    },
+    Results {
+        host: Option<String>,
+        user: Option<String>,
+        job_id: String,
+        #[serde(default)]
+        purge: bool,
+    },
+    Cancel {
+        host: Option<String>,
+        user: Option<String>,
+        job_id: String,
+    },
+}
+
+pub struct CrackJob {
+    pub tool: &'static str,
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub job_id: String,
+    pub script: String,
+    pub stdin: Option<Vec<u8>>,
+    pub engine: Option<Engine>,
+    hash_file: Option<String>,
+    wordlist: Option<(String, String)>,
+    max_hashes: usize,
+    max_jobs: usize,
+}
+
+pub fn validate(config: &CrackingConfig) -> Result<()> {
+    if !config.jobs_dir.starts_with('/')
+        || !config
+            .jobs_dir
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '.'))
+    {
+        bail!(
+            "cracking.jobs_dir '{}' muss ein absoluter Pfad aus [A-Za-z0-9/_.-] sein",
+            config.jobs_dir
+        );
+    }
+    if let Some(rule) = config.john_rules.iter().find(|rule| !is_name(rule)) {
+        bail!("cracking.john_rules: ungültiger Regelname '{}'", rule);
+    }
+    for (name, path) in &config.hashcat_rules {
+        if !is_name(name) || !path.starts_with('/') || path.chars().any(char::is_control) {
+            bail!("cracking.hashcat_rules.{}: ungültiger Name oder Pfad", name);
+        }
+    }
+    if config.max_hashes == 0 || config.max_runtime_sec == 0 || config.max_jobs == 0 {
+        bail!("cracking: max_hashes, max_runtime_sec und max_jobs müssen größer als 0 sein");
+    }
+    Ok(())
+}
+
+pub fn mcp_tools(config: &CrackingConfig) -> Vec<Value> {
+    let engines = [
+        config.john.as_ref().map(|_| "john"),
+        config.hashcat.as_ref().map(|_| "hashcat"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if engines.is_empty() {
+        return Vec::new();
+    }
+    let job = |name: &str, description: &str| {
+        json!({
+            "name": name,
+            "description": description,
+            "inputSchema": {
+                "type": "object",
+                "required": ["job_id"],
+                "properties": {
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "job_id": {"type": "string"}
+                }
+            }
+        })
+    };
+    let mut results = job(
+        "crack.results",
+        "Returns cracked hashes of a job; plaintexts are stored in the bridge secrets file and referenced by name, never returned",
+    );
+    results["inputSchema"]["properties"]["purge"] = json!({"type": "boolean", "description": "Delete the job directory on the Kali host afterwards"});
+    vec![
+        json!({
+            "name": "crack.submit",
+            "description": "Starts a detached john/hashcat wordlist job on the Kali host; poll with crack.status, fetch with crack.results",
+            "inputSchema": {
+                "type": "object",
+                "required": ["engine", "wordlist"],
+                "properties": {
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "engine": {"type": "string", "enum": engines},
+                    "hashes": {"type": "array", "items": {"type": "string"}, "description": "Hash lines (john: optionally login:hash)"},
+                    "hash_file": {"type": "string", "description": "Hash file on the Kali host inside the fs read/write directories"},
+                    "format": {"type": "string", "description": "john --format, e.g. nt, raw-md5, bcrypt"},
+                    "mode": {"type": "integer", "minimum": 0, "description": "hashcat -m hash mode, e.g. 1000 for NTLM"},
+                    "wordlist": {"type": "string", "description": "Logical wordlist name (see wordlists.list)"},
+                    "rules": {"type": "string", "description": "Rule set allowed by policy"},
+                    "max_runtime_sec": {"type": "integer", "minimum": 1, "description": "Capped by policy"}
+                }
+            }
+        }),
+        job("crack.status", "Shows state and progress of a cracking job"),
+        results,
+        job("crack.cancel", "Stops a running cracking job"),
+    ]
+}
+
+pub fn prepare(
+    config: &CrackingConfig,
+    wordlists: &WordlistsConfig,
+    fs: &FsConfig,
+    action: &str,
+    arguments: Value,
+) -> Result<CrackJob> {
+    let mut arguments = match arguments {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    arguments.insert("action".to_string(), Value::String(action.to_string()));
+    let action =
+        serde_json::from_value::<CrackAction>(Value::Object(arguments)).map_err(|error| {
+            Message::InvalidArguments {
+                tool: format!("crack.{}", action),
+                error: error.to_string(),
+            }
+        })?;
+
+    match action {
+        CrackAction::Submit {
+            host,
+            user,
+            engine,
+            hashes,
+            hash_file,
+            format,
+            mode,
+            wordlist,
+            rules,
+            max_runtime_sec,
+        } => {
+            let command = match engine {
+                Engine::John => config.john.as_ref(),
+                Engine::Hashcat => config.hashcat.as_ref(),
+            }
+            .ok_or_else(|| Message::NotConfigured {
+                key: format!("cracking.{}", engine.as_str()),
+            })?;
+            if !config.wordlists.is_empty() && !config.wordlists.contains(&wordlist) {
+                bail!(Message::NotReleased {
+                    key: "cracking.wordlists".to_string(),
+                    name: wordlist
+                });
+            }
+            let wordlist_path = wordlists::resolve(wordlists, &wordlist)?;
+            let job_id = new_job_id();
+            let (hashes_script, stdin) = match (hashes.is_empty(), hash_file.clone()) {
+                (false, None) => {
+                    if hashes.len() > config.max_hashes {
+                        bail!(Message::LimitExceeded {
+                            field: "hashes",
+                            value: hashes.len(),
+                            max: config.max_hashes
+                        });
+                    }
+                    if let Some(hash) = hashes.iter().find(|hash| {
+                        hash.trim().is_empty()
+                            || hash.len() > 4096
+                            || hash.chars().any(char::is_control)
+                    }) {
+                        bail!(Message::InvalidValue {
+                            field: "hash",
+                            value: hash.chars().take(80).collect()
+                        });
+                    }
+                    let mut data = hashes.join("\n");
+                    data.push('\n');
+                    (
+                        format!("head -c {} > hashes", data.len()),
+                        Some(data.into_bytes()),
+                    )
+                }
+                (true, Some(path)) => (
+                    format!(
+                        "{}n=$(wc -l < \"$p\"); [ \"$n\" -le {} ] || {{ echo \"$n\" >&2; exit {}; }}; cp -- \"$p\" hashes",
+                        fs::readable(fs, "crack.submit", &path)?,
+                        config.max_hashes,
+                        HASHES_EXIT
+                    ),
+                    None,
+                ),
+                _ => bail!(Message::ExactlyOneOf {
+                    tool: "crack.submit".to_string(),
+                    options: "hashes, hash_file"
+                }),
+            };
+            let runtime = max_runtime_sec
+                .unwrap_or(config.max_runtime_sec)
+                .clamp(1, config.max_runtime_sec);
+            let mut run = vec![command.clone()];
+            let mut show = vec![command.clone()];
+            let mut status = Vec::new();
+            match engine {
+                Engine::John => {
+                    if mode.is_some() {
+                        bail!(Message::ArgumentNotApplicable {
+                            tool: "john".to_string(),
+                            argument: "mode"
+                        });
+                    }
+                    run.extend([
+                        "--session=job".to_string(),
+                        "--pot=pot".to_string(),
+                        format!("--wordlist={}", wordlist_path),
+                    ]);
+                    show.extend(["--show".to_string(), "--pot=pot".to_string()]);
+                    if let Some(format) = format {
+                        if !is_name(&format) {
+                            bail!(Message::InvalidValue {
+                                field: "format",
+                                value: format
+                            });
+                        }
+                        run.push(format!("--format={}", format));
+                        show.push(format!("--format={}", format));
+                    }
+                    if let Some(rules) = rules {
+                        if !config.john_rules.contains(&rules) {
+                            bail!(Message::NotReleased {
+                                key: "cracking.john_rules".to_string(),
+                                name: rules
+                            });
+                        }
+                        run.push(format!("--rules={}", rules));
+                    }
+                    status.extend([command.clone(), "--status=job".to_string()]);
+                }
+                Engine::Hashcat => {
+                    if format.is_some() {
+                        bail!(Message::ArgumentNotApplicable {
+                            tool: "hashcat".to_string(),
+                            argument: "format"
+                        });
+                    }
+                    let mode = mode.ok_or_else(|| Message::MissingArgument {
+                        tool: "hashcat".to_string(),
+                        argument: "mode",
+                    })?;
+                    run.extend([
+                        "-a".to_string(),
+                        "0".to_string(),
+                        "-m".to_string(),
+                        mode.to_string(),
+                        format!("--session={}", job_id),
+                        "--potfile-path=pot".to_string(),
+                        "--status".to_string(),
+                        "--status-json".to_string(),
+                        format!("--status-timer={}", STATUS_TIMER_SEC),
+                        "hashes".to_string(),
+                        wordlist_path.clone(),
+                    ]);
+                    if let Some(rules) = rules {
+                        let path = config.hashcat_rules.get(&rules).ok_or_else(|| {
+                            Message::NotReleased {
+                                key: "cracking.hashcat_rules".to_string(),
+                                name: rules.clone(),
+                            }
+                        })?;
+                        run.extend(["-r".to_string(), path.clone()]);
+                    }
+                    show.extend([
+                        "--show".to_string(),
+                        "--potfile-path=pot".to_string(),
+                        "-m".to_string(),
+                        mode.to_string(),
+                        "--outfile-format=1,3".to_string(),
+                    ]);
+                }
+            }
+            if engine == Engine::John {
+                run.push("hashes".to_string());
+            }
+            show.push("hashes".to_string());
+            let quote = |args: &[String]| {
+                args.iter()
+                    .map(|arg| shell_escape(arg))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            let inner = format!(
+                "timeout --signal=TERM --kill-after={}s {}s {}; echo $? > exit",
+                KILL_AFTER_SEC,
+                runtime,
+                quote(&run)
+            );
+            let script = format!(
+                "umask 077; jobs={jobs}; n=0; for p in \"$jobs\"/*/pid; do [ -f \"$p\" ] || continue; \
+                 [ -f \"${{p%pid}}exit\" ] && continue; kill -0 \"$(cat \"$p\")\" 2>/dev/null && n=$((n+1)); done; \
+                 [ \"$n\" -lt {max_jobs} ] || {{ echo 'bereits {max_jobs} Cracking-Jobs aktiv' >&2; exit {busy}; }}; \
+                 mkdir -p \"$jobs\" && mkdir \"$jobs\"/{id} && cd \"$jobs\"/{id} || exit 1; \
+                 [ -f {wordlist} ] || {{ echo 'Wordlist nicht gefunden' >&2; exit {missing}; }}; {hashes} || exit 1; \
+                 printf '%s\\n' {engine} > engine; printf '%s\\n' {show} > show.sh; printf '%s\\n' {status} > status.sh; \
+                 setsid nohup sh -c {inner} > out.log 2>&1 < /dev/null & echo $! > pid; \
+                 printf '%s\\t%s\\n' \"$(wc -l < hashes)\" \"$(cat pid)\"",
+                jobs = shell_escape(&config.jobs_dir),
+                max_jobs = config.max_jobs,
+                busy = BUSY_EXIT,
+                id = job_id,
+                wordlist = shell_escape(&wordlist_path),
+                missing = WORDLIST_EXIT,
+                hashes = hashes_script,
+                engine = engine.as_str(),
+                show = shell_escape(&format!("exec {} 2>/dev/null", quote(&show))),
+                status = shell_escape(&if status.is_empty() {
+                    "grep '^{' out.log | tail -n 1".to_string()
+                } else {
+                    format!("{} 2>&1 | tail -n 1", quote(&status))
+                }),
+                inner = shell_escape(&inner)
+            );
+            Ok(CrackJob {
+                tool: "crack.submit",
+                host,
+                user,
+                job_id,
+                script,
+                stdin,
+                engine: Some(engine),
+                hash_file,
+                wordlist: Some((wordlist, wordlist_path)),
+                max_hashes: config.max_hashes,
+                max_jobs: config.max_jobs,
+            })
+        }
+        CrackAction::Status { host, user, job_id } => {
+            let script = format!(
+                "{}state=running; if [ -f exit ]; then state=finished; elif [ -f cancelled ]; then state=cancelled; elif ! kill -0 \"$(cat pid)\" 2>/dev/null; then state=lost; fi; \
+                 printf '%s\\t%s\\t%s\\t%s\\n' \"$state\" \"$(cat exit 2>/dev/null)\" \"$(cat engine)\" \"$(wc -l < hashes)\"; sh status.sh",
+                enter(config, &job_id)?
+            );
+            Ok(CrackJob {
+                tool: "crack.status",
+                host,
+                user,
+                job_id,
+                script,
+                stdin: None,
+                engine: None,
+                hash_file: None,
+                wordlist: None,
+                max_hashes: config.max_hashes,
+                max_jobs: config.max_jobs,
+            })
+        }
+        CrackAction::Results {
+            host,
+            user,
+            job_id,
+            purge,
+        } => {
+            let mut script = format!(
+                "{}printf '%s\\t%s\\n' \"$(cat engine)\" \"$(wc -l < hashes)\"; sh show.sh",
+                enter(config, &job_id)?
+            );
+            if purge {
+                script.push_str(&format!(
+                    "; [ -f exit ] || kill -TERM -\"$(cat pid)\" 2>/dev/null; cd / && rm -rf {}/{}",
+                    shell_escape(&config.jobs_dir),
+                    job_id
+                ));
+            }
+            Ok(CrackJob {
+                tool: "crack.results",
+                host,
+                user,
+                job_id,
+                script,
+                stdin: None,
+                engine: None,
+                hash_file: None,
+                wordlist: None,
+                max_hashes: config.max_hashes,
+                max_jobs: config.max_jobs,
+            })
+        }
+        CrackAction::Cancel { host, user, job_id } => {
+            let script = format!(
+                "{}if [ -f exit ]; then echo finished; elif [ -f cancelled ]; then echo cancelled; elif kill -TERM -\"$(cat pid)\" 2>/dev/null; then touch cancelled; echo cancelled; \
+                 else echo lost; fi",
+                enter(config, &job_id)?
+            );
+            Ok(CrackJob {
+                tool: "crack.cancel",
+                host,
+                user,
+                job_id,
+                script,
+                stdin: None,
+                engine: None,
+                hash_file: None,
+                wordlist: None,
+                max_hashes: config.max_hashes,
+                max_jobs: config.max_jobs,
+            })
+        }
+    }
+}
+
+impl CrackJob {
+    pub fn finish(
+        &self,
+        secrets: &SecretsConfig,
+        exit_code: Option<i32>,
+        stdout: &[u8],
+        stderr: &[u8],
+    ) -> Result<(Value, Vec<Finding>)> {
+        if exit_code != Some(0) {
+            let detail = String::from_utf8_lossy(stderr).trim().to_string();
+            let tool = self.tool.to_string();
+            let path = self.hash_file.clone().unwrap_or_default();
+            match exit_code {
+                Some(DENIED_EXIT) => bail!(Message::RemoteDenied { tool, path }),
+                Some(MISSING_EXIT) if self.hash_file.is_some() => {
+                    bail!(Message::RemoteNotFound { tool, path })
+                }
+                Some(MISSING_EXIT) => bail!(Message::CrackJobMissing {
+                    job_id: self.job_id.clone()
+                }),
+                Some(BUSY_EXIT) => bail!(Message::CrackBusy {
+                    max_jobs: self.max_jobs
+                }),
+                Some(WORDLIST_EXIT) => {
+                    let (name, path) = self.wordlist.clone().unwrap_or_default();
+                    bail!(Message::WordlistMissing { name, path })
+                }
+                Some(HASHES_EXIT) => bail!(Message::LimitExceeded {
+                    field: "hashes",
+                    value: detail.parse().unwrap_or_default(),
+                    max: self.max_hashes
+                }),
+                Some(255) => bail!(Message::RemoteSshFailed { tool, detail }),
+                _ => bail!(Message::RemoteFailed {
+                    tool,
+                    exit_code,
+                    detail
+                }),
+            }
+        }
+        let text = String::from_utf8_lossy(stdout);
+        let mut lines = text.lines();
+        let header = lines
+            .next()
+            .unwrap_or_default()
+            .split('\t')
+            .collect::<Vec<_>>();
+        match self.tool {
+            "crack.submit" => {
+                let [hashes, pid] = header.as_slice() else {
+                    return Err(Message::UnexpectedResponse.into());
+                };
+                Ok((
+                    json!({
+                        "job_id": self.job_id,
+                        "engine": self.engine.map(Engine::as_str),
+                        "hashes": number(hashes),
+                        "pid": number(pid),
+                        "state": "running"
+                    }),
+                    Vec::new(),
+                ))
+            }
+            "crack.status" => {
+                let [state, exit, engine, hashes] = header.as_slice() else {
+                    return Err(Message::UnexpectedResponse.into());
+                };
+                let mut status = json!({
+                    "job_id": self.job_id,
+                    "state": state,
+                    "exit_code": number(exit),
+                    "engine": engine,
+                    "hashes": number(hashes)
+                });
+                let progress = lines.next().unwrap_or_default().trim();
+                let details = match *engine {
+                    "hashcat" => hashcat_progress(progress),
+                    _ => john_progress(progress),
+                };
+                if let (Value::Object(status), Value::Object(details)) = (&mut status, details) {
+                    status.extend(details);
+                }
+                Ok((status, Vec::new()))
+            }
+            "crack.results" => {
+                let [engine, hashes] = header.as_slice() else {
+                    return Err(Message::UnexpectedResponse.into());
+                };
+                let cracked = lines
+                    .filter_map(|line| match *engine {
+                        "hashcat" => {
+                            let (hash, hex) = line.rsplit_once(':')?;
+                            Some((
+                                "hash",
+                                hash.to_string(),
+                                String::from_utf8(decode_hex(hex)?).ok()?,
+                            ))
+                        }
+                        _ => {
+                            if line.contains(" password hash") {
+                                return None;
+                            }
+                            let (login, plain) = line.split_once(':')?;
+                            Some(("login", login.to_string(), plain.to_string()))
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let mut results = Vec::new();
+                let mut findings = Vec::new();
+                for (index, (label, identity, plaintext)) in cracked.into_iter().enumerate() {
+                    let name = format!("{}.{}", self.job_id, index + 1);
+                    let stored = secrets.file.is_some()
+                        && secrets::store(secrets, &name, &plaintext).is_ok();
+                    let secret = stored.then_some(name);
+                    let mut finding = Finding::new(
+                        engine,
+                        "credential",
+                        Severity::High,
+                        format!(
+                            "Hash geknackt ({})",
+                            identity.chars().take(40).collect::<String>()
+                        ),
+                    );
+                    finding.data = json!({label: identity, "job_id": self.job_id, "secret": secret, "stored": stored});
+                    results.push(finding.data.clone());
+                    findings.push(finding);
+                }
+                Ok((
+                    json!({
+                        "job_id": self.job_id,
+                        "engine": engine,
+                        "hashes": number(hashes),
+                        "cracked": results.len(),
+                        "results": results
+                    }),
+                    findings,
+                ))
+            }
+            _ => Ok((
+                json!({"job_id": self.job_id, "state": header.first().copied().unwrap_or_default()}),
+                Vec::new(),
+            )),
+        }
+    }
+}
+
+fn enter(config: &CrackingConfig, job_id: &str) -> Result<String> {
+    let valid = job_id.strip_prefix("crack-").is_some_and(|rest| {
+        !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit() || c == '-')
+    });
+    if !valid {
+        bail!(Message::InvalidValue {
+            field: "job_id",
+            value: job_id.to_string()
+        });
+    }
+    Ok(format!(
+        "cd {}/{} 2>/dev/null || {{ echo 'Job nicht gefunden' >&2; exit {}; }}; ",
+        shell_escape(&config.jobs_dir),
+        job_id,
+        MISSING_EXIT
+    ))
+}
+
+fn hashcat_progress(line: &str) -> Value {
+    let Ok(status) = serde_json::from_str::<Value>(line) else {
+        return json!({});
+    };
+    let ratio = |pair: &Value| match (pair[0].as_f64(), pair[1].as_f64()) {
+        (Some(done), Some(total)) if total > 0.0 => Some((done / total * 10_000.0).round() / 100.0),
+        _ => None,
+    };
+    json!({
+        "progress_percent": ratio(&status["progress"]),
+        "cracked": status["recovered_hashes"][0],
+        "speed": status["devices"].as_array().map(|devices| devices.iter().filter_map(|device| device["speed"].as_u64()).sum::<u64>()),
+        "estimated_stop": status["estimated_stop"],
+        "status_code": status["status"]
+    })
+}
+
+fn john_progress(line: &str) -> Value {
+    if line.is_empty() {
+        return json!({});
+    }
+    let cracked = line
+        .split_whitespace()
+        .next()
+        .and_then(|field| field.strip_suffix('g'))
+        .and_then(|count| count.parse::<u64>().ok());
+    let percent = line
+        .split_whitespace()
+        .find_map(|field| field.strip_suffix('%'))
+        .and_then(|percent| percent.parse::<f64>().ok());
+    let eta = line
+        .split_once("(ETA: ")
+        .and_then(|(_, rest)| rest.split_once(')'))
+        .map(|(eta, _)| eta.to_string());
+    json!({
+        "progress_percent": percent.or(line.contains(" DONE ").then_some(100.0)),
+        "cracked": cracked,
+        "eta": eta,
+        "status_line": line
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn number(value: &str) -> Value {
+    value
+        .trim()
+        .parse::<u64>()
+        .map_or(Value::Null, |number| json!(number))
+}
+
+fn new_job_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let timestamp_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0);
+    format!(
+        "crack-{}-{}-{}",
+        std::process::id(),
+        timestamp_ms,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn is_name(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with('-')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
@@ -0,0 +1,267 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::findings::{Finding, Severity};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CveConfig {
+    #[serde(default)]
+    pub database: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct CveRecord {
+    #[serde(default)]
+    cvss: Option<f64>,
+    #[serde(default)]
+    severity: Option<String>,
+    #[serde(default)]
+    summary: Option<String>,
+}
+
+pub struct CveDatabase {
+    records: HashMap<String, CveRecord>,
+}
+
+impl CveDatabase {
+    pub fn open(config: &CveConfig) -> Result<Option<Self>> {
+        let Some(path) = &config.database else {
+            return Ok(None);
+        };
+        let path = Path::new(path);
+        let mut records = HashMap::new();
+        if path.is_dir() {
+            let mut files = fs::read_dir(path)
+                .with_context(|| {
+                    format!(
+                        "CVE-Datenbank {} konnte nicht gelesen werden",
+                        path.display()
+                    )
+                })?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|file| {
+                    file.extension()
+                        .is_some_and(|ext| ext == "json" || ext == "jsonl")
+                })
+                .collect::<Vec<_>>();
+            files.sort();
+            for file in files {
+                load_file(&file, &mut records)?;
+            }
+        } else {
+            load_file(path, &mut records)?;
+        }
+        Ok(Some(Self { records }))
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+}
+
+fn load_file(path: &Path, records: &mut HashMap<String, CveRecord>) -> Result<()> {
+    let content = fs::read_to_string(path).with_context(|| {
+        format!(
+            "CVE-Datenbank {} konnte nicht gelesen werden",
+            path.display()
+        )
+    })?;
+    if path.extension().is_some_and(|ext| ext == "jsonl") {
+        for (index, line) in content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+        {
+            let value = serde_json::from_str::<Value>(line)
+                .with_context(|| format!("{}:{}: kein gültiges JSON", path.display(), index + 1))?;
+            insert(records, &value);
+        }
+        return Ok(());
+    }
+    let document = serde_json::from_str::<Value>(&content)
+        .with_context(|| format!("CVE-Datenbank {} ist kein gültiges JSON", path.display()))?;
+    match &document {
+        Value::Object(object) if object.contains_key("vulnerabilities") => {
+            for item in document["vulnerabilities"].as_array().into_iter().flatten() {
+                if let Some((id, record)) = nvd_record(&item["cve"]) {
+                    records.insert(id, record);
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|value| insert(records, value)),
+        _ => bail!(
+            "CVE-Datenbank {}: unbekanntes Format (NVD-2.0-Feed, JSON-Array oder JSONL erwartet)",
+            path.display()
+        ),
+    }
+    Ok(())
+}
+
+fn insert(records: &mut HashMap<String, CveRecord>, value: &Value) {
+    if let Some(id) = value["id"].as_str().and_then(normalize)
+        && let Ok(record) = serde_json::from_value::<CveRecord>(value.clone())
+    {
+        records.insert(id, record);
+    }
+}
+
+fn nvd_record(cve: &Value) -> Option<(String, CveRecord)> {
+    let id = cve["id"].as_str().and_then(normalize)?;
+    let metrics = &cve["metrics"];
+    let metric = [
+        "cvssMetricV40",
+        "cvssMetricV31",
+        "cvssMetricV30",
+        "cvssMetricV2",
+    ]
+    .iter()
+    .filter_map(|key| metrics[*key].as_array())
+    .find_map(|entries| {
+        entries
+            .iter()
+            .find(|entry| entry["type"] == "Primary")
+            .or(entries.first())
+    });
+    let summary = cve["descriptions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|description| description["lang"] == "en")
+        .and_then(|description| description["value"].as_str())
+        .map(str::to_string);
+    Some((
+        id,
+        CveRecord {
+            cvss: metric.and_then(|metric| metric["cvssData"]["baseScore"].as_f64()),
+            severity: metric
+                .and_then(|metric| {
+                    metric["cvssData"]["baseSeverity"]
+                        .as_str()
+                        .or(metric["baseSeverity"].as_str())
+                })
+                .map(str::to_string),
+            summary,
+        },
+    ))
+}
+
+fn cve_regex() -> &'static Regex {
+    static CVE: OnceLock<Regex> = OnceLock::new();
+    CVE.get_or_init(|| Regex::new(r"(?i)\bCVE-\d{4}-\d{4,}\b").expect("cve regex"))
+}
+
+fn normalize(id: &str) -> Option<String> {
+    cve_regex()
+        .find(id)
+        .map(|found| found.as_str().to_ascii_uppercase())
+}
+
+pub fn from_cvss(score: f64) -> Severity {
+    match score {
+        score if score >= 9.0 => Severity::Critical,
+        score if score >= 7.0 => Severity::High,
+        score if score >= 4.0 => Severity::Medium,
+        score if score > 0.0 => Severity::Low,
+        _ => Severity::Info,
+    }
+}
+
+pub fn enrich(findings: &mut [Finding], database: Option<&CveDatabase>) {
+    for finding in findings {
+        enrich_finding(finding, database);
+    }
+}
+
+fn enrich_finding(finding: &mut Finding, database: Option<&CveDatabase>) {
+    let mut tool_scores = BTreeMap::<String, Option<f64>>::new();
+    for entry in finding.data["cves"].as_array().into_iter().flatten() {
+        if let Some(id) = entry["id"].as_str().and_then(normalize) {
+            let score = entry["cvss"].as_f64();
+            let known = tool_scores.entry(id).or_default();
+            *known = max_score(*known, score);
+        }
+    }
+    let nuclei_score = finding.data["info"]["classification"]["cvss-score"].as_f64();
+    let sources = finding
+        .references
+        .iter()
+        .map(String::as_str)
+        .chain([finding.title.as_str()]);
+    for id in sources.flat_map(|text| cve_regex().find_iter(text)) {
+        tool_scores
+            .entry(id.as_str().to_ascii_uppercase())
+            .or_insert(nuclei_score);
+    }
+    if tool_scores.is_empty() {
+        return;
+    }
+
+    let mut cves = Vec::new();
+    let mut highest: Option<f64> = None;
+    let mut severity = finding.severity;
+    for (id, tool_score) in tool_scores {
+        let record = database.and_then(|database| database.records.get(&id));
+        let (cvss, source) = match record.and_then(|record| record.cvss) {
+            Some(score) => (Some(score), "database"),
+            None => (tool_score, "tool"),
+        };
+        if let Some(score) = cvss {
+            highest = max_score(highest, Some(score));
+            severity = severity.max(from_cvss(score));
+        } else if let Some(rated) = record
+            .and_then(|record| record.severity.as_deref())
+            .and_then(Severity::parse)
+        {
+            severity = severity.max(rated);
+        }
+        if !finding.references.contains(&id) {
+            finding.references.push(id.clone());
+        }
+        let mut entry = json!({"id": id, "cvss": cvss, "source": source});
+        if let Some(record) = record {
+            entry["severity"] = json!(record.severity.as_deref().and_then(Severity::parse));
+            entry["summary"] = json!(record.summary);
+        }
+        cves.push(entry);
+    }
+    if !finding.data.is_object() {
+        finding.data = json!({});
+    }
+    finding.data["cves"] = json!(cves);
+    finding.data["cvss"] = json!(highest);
+    finding.severity = severity;
+}
+
+pub fn scored(text: &str) -> Vec<Value> {
+    static SCORED: OnceLock<Regex> = OnceLock::new();
+    let scored = SCORED.get_or_init(|| {
+        Regex::new(r"(?i)\b(CVE-\d{4}-\d{4,})\b(?:[ \t]+(\d{1,2}(?:\.\d)?)\b)?")
+            .expect("cvss regex")
+    });
+    let mut seen = BTreeMap::new();
+    for captures in scored.captures_iter(text) {
+        let score = captures
+            .get(2)
+            .and_then(|score| score.as_str().parse::<f64>().ok())
+            .filter(|score| *score <= 10.0);
+        let known = seen.entry(captures[1].to_ascii_uppercase()).or_insert(None);
+        *known = max_score(*known, score);
+    }
+    seen.into_iter()
+        .map(|(id, cvss)| json!({"id": id, "cvss": cvss}))
+        .collect()
+}
+
+fn max_score(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
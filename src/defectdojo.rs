@@ -0,0 +1,439 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::artifacts;
+use crate::encryption::{self, Sealer};
+use crate::findings::{Finding, Severity};
+use crate::findings_store;
+use crate::secrets::{self, SecretsConfig};
+
+const SCAN_TYPE: &str = "Generic Findings Import";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefectDojoConfig {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub insecure_tls: bool,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default = "default_product_type")]
+    pub product_type: String,
+    #[serde(default = "default_product")]
+    pub product: String,
+    #[serde(default = "default_engagement")]
+    pub engagement: String,
+    #[serde(default)]
+    pub engagements: BTreeMap<String, DojoTarget>,
+    #[serde(default)]
+    pub minimum_severity: Severity,
+    #[serde(default)]
+    pub close_old_findings: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DojoTarget {
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub engagement: Option<String>,
+}
+
+fn default_timeout_sec() -> u64 {
+    30
+}
+
+fn default_product_type() -> String {
+    "Research and Development".to_string()
+}
+
+fn default_product() -> String {
+    "ollama-kali-mcp-bridge".to_string()
+}
+
+fn default_engagement() -> String {
+    "bridge".to_string()
+}
+
+impl Default for DefectDojoConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            token: None,
+            insecure_tls: false,
+            timeout_sec: default_timeout_sec(),
+            product_type: default_product_type(),
+            product: default_product(),
+            engagement: default_engagement(),
+            engagements: BTreeMap::new(),
+            minimum_severity: Severity::Info,
+            close_old_findings: false,
+            tags: Vec::new(),
+        }
+    }
+}
+
+pub fn validate(config: &DefectDojoConfig) -> Result<()> {
+    if let Some(url) = &config.url {
+        let parsed =
+            Url::parse(url).with_context(|| format!("ungültige defectdojo.url '{}'", url))?;
+        if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+            bail!(
+                "defectdojo.url '{}' muss eine http(s)-URL mit Host sein",
+                url
+            );
+        }
+    }
+    if config.timeout_sec == 0 {
+        bail!("defectdojo.timeout_sec muss größer als 0 sein");
+    }
+    if config.product.trim().is_empty() || config.engagement.trim().is_empty() {
+        bail!("defectdojo.product und defectdojo.engagement dürfen nicht leer sein");
+    }
+    Ok(())
+}
+
+pub enum Selection {
+    Run(String),
+    Engagement(String),
+}
+
+struct Batch {
+    product: String,
+    engagement: String,
+    tool: String,
+    scan_date: String,
+    findings: Vec<Value>,
+}
+
+pub async fn export(
+    config: &DefectDojoConfig,
+    secrets: &SecretsConfig,
+    records: &[Value],
+    selection: &Selection,
+    sealer: Option<&Sealer>,
+    dry_run: bool,
+) -> Result<Value> {
+    let batches = collect(config, records, selection, sealer)?;
+    if batches.is_empty() {
+        match selection {
+            Selection::Run(run) => bail!("keine Findings für Run '{}' im Audit-Log gefunden", run),
+            Selection::Engagement(name) => bail!(
+                "keine Findings für Engagement '{}' im Audit-Log gefunden",
+                name
+            ),
+        }
+    }
+    if dry_run {
+        let imports = batches
+            .iter()
+            .map(|batch| {
+                json!({
+                    "product": batch.product,
+                    "engagement": batch.engagement,
+                    "test_title": test_title(&batch.tool),
+                    "scan_date": batch.scan_date,
+                    "findings": {"findings": batch.findings}
+                })
+            })
+            .collect::<Vec<_>>();
+        return Ok(json!({"dry_run": true, "imports": imports}));
+    }
+
+    let url = config
+        .url
+        .as_ref()
+        .context("defectdojo.url ist nicht gesetzt")?;
+    let endpoint = Url::parse(url)
+        .and_then(|base| base.join("api/v2/reimport-scan/"))
+        .with_context(|| format!("ungültige defectdojo.url '{}'", url))?;
+    let token = config
+        .token
+        .clone()
+        .or_else(|| std::env::var("DEFECTDOJO_TOKEN").ok())
+        .context("defectdojo: token fehlt (Config oder DEFECTDOJO_TOKEN)")?;
+    let token = secrets::resolve(secrets, std::iter::once(&token))
+        .await?
+        .substitute(&token);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_sec))
+        .danger_accept_invalid_certs(config.insecure_tls)
+        .user_agent(concat!(
+            "ollama-kali-mcp-bridge/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()
+        .context("HTTP-Client für DefectDojo konnte nicht erstellt werden")?;
+
+    let mut imports = Vec::new();
+    for batch in &batches {
+        let title = test_title(&batch.tool);
+        let mut form = Multipart::new();
+        form.text("scan_type", SCAN_TYPE);
+        form.text("product_type_name", &config.product_type);
+        form.text("product_name", &batch.product);
+        form.text("engagement_name", &batch.engagement);
+        form.text("test_title", &title);
+        form.text("scan_date", &batch.scan_date);
+        form.text("auto_create_context", "true");
+        form.text("minimum_severity", dojo_severity(config.minimum_severity));
+        form.text(
+            "close_old_findings",
+            if config.close_old_findings {
+                "true"
+            } else {
+                "false"
+            },
+        );
+        form.text("active", "true");
+        form.text("verified", "false");
+        for tag in &config.tags {
+            form.text("tags", tag);
+        }
+        form.file(
+            "file",
+            "findings.json",
+            &serde_json::to_vec(&json!({"findings": batch.findings}))?,
+        );
+        let response = client
+            .post(endpoint.clone())
+            .header("Authorization", format!("Token {}", token))
+            .header("Content-Type", form.content_type())
+            .body(form.finish())
+            .send()
+            .await
+            .with_context(|| format!("DefectDojo {} ist nicht erreichbar", endpoint))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            let body = body.chars().take(500).collect::<String>();
+            bail!(
+                "DefectDojo lehnt den Import für {} / {} ab ({}): {}",
+                batch.product,
+                batch.engagement,
+                status,
+                body
+            );
+        }
+        let result = serde_json::from_str::<Value>(&body).unwrap_or(Value::Null);
+        imports.push(json!({
+            "product": batch.product,
+            "engagement": batch.engagement,
+            "test_title": title,
+            "findings": batch.findings.len(),
+            "test": result["test"],
+            "statistics": result["statistics"]
+        }));
+    }
+    Ok(json!({"url": url, "imports": imports}))
+}
+
+fn collect(
+    config: &DefectDojoConfig,
+    records: &[Value],
+    selection: &Selection,
+    sealer: Option<&Sealer>,
+) -> Result<Vec<Batch>> {
+    let mut batches = BTreeMap::<(String, String, String), Batch>::new();
+    for record in records.iter().filter(|record| record["kind"] == "findings") {
+        let entry = &record["entry"];
+        let selected = match selection {
+            Selection::Run(run) => entry["correlation_id"]
+                .as_str()
+                .is_some_and(|id| id == run || id.starts_with(&format!("{}-step-", run))),
+            Selection::Engagement(name) => entry["engagement"].as_str() == Some(name.as_str()),
+        };
+        if !selected {
+            continue;
+        }
+        let findings = encryption::unseal_value(sealer, &entry["findings"]).with_context(|| {
+            format!(
+                "Findings von {} konnten nicht entschlüsselt werden",
+                entry["correlation_id"]
+            )
+        })?;
+        let findings = serde_json::from_value::<Vec<Finding>>(findings)
+            .context("findings-Record ist ungültig")?;
+        let engagement = entry["engagement"].as_str();
+        let target = engagement.and_then(|name| config.engagements.get(name));
+        let product = target
+            .and_then(|target| target.product.clone())
+            .unwrap_or_else(|| config.product.clone());
+        let dojo_engagement = target
+            .and_then(|target| target.engagement.clone())
+            .or_else(|| engagement.map(str::to_string))
+            .unwrap_or_else(|| config.engagement.clone());
+        let tool = entry["tool"].as_str().unwrap_or("unknown").to_string();
+        let ts_ms = record["ts_ms"].as_u64().unwrap_or(0);
+        let batch = batches
+            .entry((product.clone(), dojo_engagement.clone(), tool.clone()))
+            .or_insert_with(|| Batch {
+                product,
+                engagement: dojo_engagement,
+                tool,
+                scan_date: date(ts_ms),
+                findings: Vec::new(),
+            });
+        batch.scan_date = batch.scan_date.clone().max(date(ts_ms));
+        batch.findings.extend(
+            findings
+                .iter()
+                .map(|finding| generic_finding(finding, entry, &date(ts_ms))),
+        );
+    }
+    Ok(batches.into_values().collect())
+}
+
+fn generic_finding(finding: &Finding, entry: &Value, date: &str) -> Value {
+    let cves = finding.data["cves"].as_array().cloned().unwrap_or_default();
+    let cve = cves
+        .first()
+        .and_then(|cve| cve["id"].as_str())
+        .map(str::to_string);
+    let mut description = vec![format!(
+        "**{}** ({}, Quelle `{}`)",
+        finding.title, finding.kind, finding.source
+    )];
+    if let Some(evidence) = &finding.evidence {
+        description.push(format!("Evidence: `{}`", evidence));
+    }
+    let cve_lines = cves
+        .iter()
+        .map(|cve| {
+            let mut line = format!("- {}", cve["id"].as_str().unwrap_or("-"));
+            if let Some(cvss) = cve["cvss"].as_f64() {
+                line.push_str(&format!(" (CVSS {:.1})", cvss));
+            }
+            if let Some(summary) = cve["summary"].as_str() {
+                line.push_str(&format!(": {}", summary));
+            }
+            line
+        })
+        .collect::<Vec<_>>();
+    if !cve_lines.is_empty() {
+        description.push(cve_lines.join("\n"));
+    }
+    if let Some(id) = entry["correlation_id"].as_str() {
+        description.push(format!("Bridge-Run: `{}`", id));
+    }
+    let mut endpoint = json!({});
+    if let Some(host) = &finding.host {
+        endpoint["host"] = json!(host);
+    }
+    if let Some(port) = finding.port {
+        endpoint["port"] = json!(port);
+    }
+    if let Some(path) = finding
+        .evidence
+        .as_deref()
+        .filter(|evidence| evidence.starts_with('/'))
+    {
+        endpoint["path"] = json!(path);
+    }
+    let mut value = json!({
+        "title": finding.title,
+        "severity": dojo_severity(finding.severity),
+        "description": description.join("\n\n"),
+        "date": date,
+        "unique_id_from_tool": findings_store::fingerprint(finding),
+        "vuln_id_from_tool": cve,
+        "active": true,
+        "verified": false,
+        "dynamic_finding": true,
+        "static_finding": false
+    });
+    if let Some(cve) = cve {
+        value["cve"] = json!(cve);
+    }
+    if !finding.references.is_empty() {
+        value["references"] = json!(finding.references.join("\n"));
+    }
+    if let Some(cvss) = finding.data["cvss"].as_f64() {
+        value["cvssv3_score"] = json!(cvss);
+    }
+    if endpoint
+        .as_object()
+        .is_some_and(|endpoint| endpoint.contains_key("host"))
+    {
+        value["endpoints"] = json!([endpoint]);
+    }
+    value
+}
+
+fn dojo_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "Info",
+        Severity::Low => "Low",
+        Severity::Medium => "Medium",
+        Severity::High => "High",
+        Severity::Critical => "Critical",
+    }
+}
+
+fn test_title(tool: &str) -> String {
+    format!("ollama-kali-mcp-bridge: {}", tool)
+}
+
+fn date(ts_ms: u64) -> String {
+    let (year, month, day) = artifacts::civil_from_days((ts_ms / 86_400_000) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+struct Multipart {
+    boundary: String,
+    body: Vec<u8>,
+}
+
+impl Multipart {
+    fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|value| value.as_nanos())
+            .unwrap_or(0);
+        Self {
+            boundary: format!("okb-{:x}-{:x}", nanos, std::process::id()),
+            body: Vec::new(),
+        }
+    }
+
+    fn text(&mut self, name: &str, value: &str) {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                self.boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+
+    fn file(&mut self, name: &str, filename: &str, data: &[u8]) {
+        self.body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: application/json\r\n\r\n",
+                self.boundary, name, filename
+            )
+            .as_bytes(),
+        );
+        self.body.extend_from_slice(data);
+        self.body.extend_from_slice(b"\r\n");
+    }
+
+    fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.body
+            .extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        self.body
+    }
+}
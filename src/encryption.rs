@@ -0,0 +1,234 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::platform::OpenOptionsExt;
+use crate::secrets::random_bytes;
+
+const MAGIC: &[u8; 4] = b"OKB1";
+const NONCE_BYTES: usize = 12;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub key: KeySource,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum KeySource {
+    Env {
+        var: String,
+    },
+    File {
+        path: String,
+    },
+    Keyring {
+        #[serde(default = "default_keyring_service")]
+        service: String,
+        #[serde(default = "default_keyring_account")]
+        account: String,
+    },
+}
+
+fn default_keyring_service() -> String {
+    "ollama-kali-mcp-bridge".to_string()
+}
+
+fn default_keyring_account() -> String {
+    "data-key".to_string()
+}
+
+impl Default for KeySource {
+    fn default() -> Self {
+        KeySource::Env {
+            var: "BRIDGE_DATA_KEY".to_string(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum EncryptionAction {
+    Keygen {
+        #[arg(long)]
+        out: Option<String>,
+    },
+    Decrypt {
+        path: String,
+        #[arg(long)]
+        out: String,
+    },
+}
+
+#[derive(Clone)]
+pub struct Sealer {
+    cipher: Aes256Gcm,
+}
+
+impl Sealer {
+    pub fn open(config: &EncryptionConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let encoded = match &config.key {
+            KeySource::Env { var } => std::env::var(var).with_context(|| {
+                format!(
+                    "encryption.key: Umgebungsvariable {} ist nicht gesetzt",
+                    var
+                )
+            })?,
+            KeySource::File { path } => fs::read_to_string(path).with_context(|| {
+                format!(
+                    "encryption.key: Schlüsseldatei {} konnte nicht gelesen werden",
+                    path
+                )
+            })?,
+            KeySource::Keyring { service, account } => keyring_entry(service, account)?
+                .get_password()
+                .with_context(|| {
+                    format!(
+                        "encryption.key: kein Schlüssel im Keyring ({}/{})",
+                        service, account
+                    )
+                })?,
+        };
+        let bytes = BASE64
+            .decode(encoded.trim())
+            .context("encryption.key: Schlüssel ist kein gültiges base64")?;
+        if bytes.len() != 32 {
+            bail!(
+                "encryption.key: 32 Bytes erwartet, {} erhalten",
+                bytes.len()
+            );
+        }
+        Ok(Some(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+        }))
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = random_bytes::<NONCE_BYTES>()?;
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| anyhow!("Daten konnten nicht verschlüsselt werden"))?;
+        let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_BYTES + ciphertext.len());
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    pub fn unseal(&self, data: &[u8]) -> Result<Vec<u8>> {
+        if !is_sealed(data) || data.len() < MAGIC.len() + NONCE_BYTES {
+            bail!("Daten sind nicht verschlüsselt oder unvollständig");
+        }
+        let (nonce, ciphertext) = data[MAGIC.len()..].split_at(NONCE_BYTES);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Daten konnten nicht entschlüsselt werden (falscher Schlüssel?)"))
+    }
+
+    pub fn seal_value(&self, value: &Value) -> Result<Value> {
+        Ok(json!({"sealed": BASE64.encode(self.seal(&serde_json::to_vec(value)?)?)}))
+    }
+}
+
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn unseal(sealer: Option<&Sealer>, data: Vec<u8>) -> Result<Vec<u8>> {
+    match sealer {
+        _ if !is_sealed(&data) => Ok(data),
+        Some(sealer) => sealer.unseal(&data),
+        None => bail!("Daten sind verschlüsselt, aber encryption ist nicht aktiviert"),
+    }
+}
+
+pub fn unseal_value(sealer: Option<&Sealer>, value: &Value) -> Result<Value> {
+    let Some(sealed) = value["sealed"].as_str() else {
+        return Ok(value.clone());
+    };
+    let data = BASE64
+        .decode(sealed)
+        .context("ungültiger verschlüsselter Wert")?;
+    Ok(serde_json::from_slice(&unseal(sealer, data)?)?)
+}
+
+fn keyring_entry(service: &str, account: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(service, account)
+        .with_context(|| format!("Keyring-Eintrag {}/{} nicht verfügbar", service, account))
+}
+
+pub fn apply(config: &EncryptionConfig, action: EncryptionAction) -> Result<Value> {
+    match action {
+        EncryptionAction::Keygen { out } => {
+            let key = BASE64.encode(random_bytes::<32>()?);
+            match (out, &config.key) {
+                (Some(out), _) => {
+                    let mut file = OpenOptions::new()
+                        .create_new(true)
+                        .write(true)
+                        .mode(0o600)
+                        .open(&out)
+                        .with_context(|| {
+                            format!("Schlüsseldatei {} konnte nicht angelegt werden", out)
+                        })?;
+                    writeln!(file, "{}", key).with_context(|| {
+                        format!("Schlüsseldatei {} konnte nicht geschrieben werden", out)
+                    })?;
+                    Ok(json!({"key_path": out}))
+                }
+                (None, KeySource::Keyring { service, account }) => {
+                    let entry = keyring_entry(service, account)?;
+                    if entry.get_password().is_ok() {
+                        bail!(
+                            "im Keyring liegt bereits ein Schlüssel ({}/{})",
+                            service,
+                            account
+                        );
+                    }
+                    entry.set_password(&key).with_context(|| {
+                        format!(
+                            "Schlüssel konnte nicht im Keyring abgelegt werden ({}/{})",
+                            service, account
+                        )
+                    })?;
+                    Ok(json!({"keyring": {"service": service, "account": account}}))
+                }
+                (None, KeySource::File { path }) => {
+                    bail!("--out fehlt (encryption.key.path ist {})", path)
+                }
+                (None, KeySource::Env { var }) => Ok(json!({"env": var, "key": key})),
+            }
+        }
+        EncryptionAction::Decrypt { path, out } => {
+            let sealer = Sealer::open(config)?.context("encryption ist nicht aktiviert")?;
+            let data =
+                fs::read(&path).with_context(|| format!("{} konnte nicht gelesen werden", path))?;
+            let plaintext = sealer
+                .unseal(&data)
+                .with_context(|| format!("{} konnte nicht entschlüsselt werden", path))?;
+            let mut file = OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .mode(0o600)
+                .open(&out)
+                .with_context(|| format!("{} konnte nicht angelegt werden", out))?;
+            file.write_all(&plaintext)
+                .with_context(|| format!("{} konnte nicht geschrieben werden", out))?;
+            Ok(json!({"decrypted": path, "out": out, "bytes": plaintext.len()}))
+        }
+    }
+}
@@ -0,0 +1,728 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+use crate::stats::parse_window;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngagementsConfig {
+    #[serde(default = "default_path")]
+    pub path: String,
+    #[serde(default)]
+    pub require: bool,
+    #[serde(default)]
+    pub mcp_manage: bool,
+}
+
+fn default_path() -> String {
+    "engagements.json".to_string()
+}
+
+impl Default for EngagementsConfig {
+    fn default() -> Self {
+        Self {
+            path: default_path(),
+            require: false,
+            mcp_manage: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Engagement {
+    pub name: String,
+    #[serde(default)]
+    pub scope: Vec<String>,
+    pub created_ms: u128,
+    pub starts_ms: u128,
+    #[serde(default)]
+    pub ends_ms: Option<u128>,
+    #[serde(default)]
+    pub notes: Vec<Note>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub ts_ms: u128,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub engagements: BTreeMap<String, Engagement>,
+}
+
+impl Registry {
+    pub fn load(path: &str) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str(&content).with_context(|| Message::StateFileInvalid {
+                    kind: "Engagement",
+                    path: path.to_string(),
+                })
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| Message::StateFileUnreadable {
+                kind: "Engagement",
+                path: path.to_string(),
+            }),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let tmp = format!("{}.tmp", path);
+        fs::write(&tmp, serde_json::to_string_pretty(self)?).with_context(|| {
+            Message::StateFileWriteFailed {
+                kind: "Engagement",
+                path: tmp.clone(),
+            }
+        })?;
+        fs::rename(&tmp, path).with_context(|| Message::StateFileWriteFailed {
+            kind: "Engagement",
+            path: path.to_string(),
+        })
+    }
+
+    pub fn create(
+        &mut self,
+        name: &str,
+        scope: Vec<String>,
+        valid_for_ms: Option<u128>,
+        note: Option<String>,
+    ) -> Result<&Engagement> {
+        if name.trim().is_empty() {
+            bail!(Message::InvalidValue {
+                field: "name",
+                value: name.to_string()
+            });
+        }
+        if self.engagements.contains_key(name) {
+            bail!(Message::EngagementExists {
+                name: name.to_string()
+            });
+        }
+        for entry in &scope {
+            ScopeEntry::parse(entry)?;
+        }
+        let now = now_ms();
+        let notes = note
+            .map(|text| vec![Note { ts_ms: now, text }])
+            .unwrap_or_default();
+        self.engagements.insert(
+            name.to_string(),
+            Engagement {
+                name: name.to_string(),
+                scope,
+                created_ms: now,
+                starts_ms: now,
+                ends_ms: valid_for_ms.map(|window| now + window),
+                notes,
+            },
+        );
+        Ok(&self.engagements[name])
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Engagement> {
+        self.engagements.get(name).ok_or_else(|| {
+            Message::EngagementMissing {
+                name: name.to_string(),
+            }
+            .into()
+        })
+    }
+
+    pub fn activate(&mut self, name: &str) -> Result<()> {
+        self.get(name)?;
+        self.active = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn add_note(&mut self, name: &str, text: String) -> Result<&Engagement> {
+        let engagement =
+            self.engagements
+                .get_mut(name)
+                .ok_or_else(|| Message::EngagementMissing {
+                    name: name.to_string(),
+                })?;
+        engagement.notes.push(Note {
+            ts_ms: now_ms(),
+            text,
+        });
+        Ok(engagement)
+    }
+}
+
+#[derive(Subcommand, Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum EngagementAction {
+    Create {
+        name: String,
+        #[arg(long)]
+        #[serde(default)]
+        scope: Vec<String>,
+        #[arg(long)]
+        valid_for: Option<String>,
+        #[arg(long)]
+        note: Option<String>,
+        #[arg(long)]
+        #[serde(default)]
+        activate: bool,
+    },
+    List,
+    Show {
+        name: String,
+    },
+    Activate {
+        name: String,
+    },
+    Note {
+        name: String,
+        text: String,
+    },
+}
+
+pub fn apply(config: &EngagementsConfig, action: EngagementAction) -> Result<Value> {
+    let mut registry = Registry::load(&config.path)?;
+    let result = match action {
+        EngagementAction::Create {
+            name,
+            scope,
+            valid_for,
+            note,
+            activate,
+        } => {
+            let valid_for_ms = valid_for.as_deref().map(parse_window).transpose()?;
+            let engagement =
+                serde_json::to_value(registry.create(&name, scope, valid_for_ms, note)?)?;
+            if activate {
+                registry.activate(&name)?;
+            }
+            registry.save(&config.path)?;
+            engagement
+        }
+        EngagementAction::List => {
+            return Ok(json!({
+                "active": registry.active,
+                "engagements": registry.engagements.values().collect::<Vec<_>>()
+            }));
+        }
+        EngagementAction::Show { name } => return Ok(serde_json::to_value(registry.get(&name)?)?),
+        EngagementAction::Activate { name } => {
+            registry.activate(&name)?;
+            registry.save(&config.path)?;
+            json!({"active": name})
+        }
+        EngagementAction::Note { name, text } => {
+            let engagement = serde_json::to_value(registry.add_note(&name, text)?)?;
+            registry.save(&config.path)?;
+            engagement
+        }
+    };
+    Ok(result)
+}
+
+pub fn mcp_tools(config: &EngagementsConfig) -> Vec<Value> {
+    let name = json!({"type": "string"});
+    let mut tools = vec![
+        json!({
+            "name": "engagement.list",
+            "description": "Lists engagements and the active engagement",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "engagement.show",
+            "description": "Shows scope, time window and notes of an engagement",
+            "inputSchema": {"type": "object", "required": ["name"], "properties": {"name": name}}
+        }),
+        json!({
+            "name": "engagement.note",
+            "description": "Appends a note to an engagement",
+            "inputSchema": {
+                "type": "object",
+                "required": ["name", "text"],
+                "properties": {"name": name, "text": {"type": "string"}}
+            }
+        }),
+    ];
+    if config.mcp_manage {
+        tools.push(json!({
+            "name": "engagement.create",
+            "description": "Creates an engagement with scope (IPs, CIDRs, hostnames, *.domain) and optional time window",
+            "inputSchema": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": name,
+                    "scope": {"type": "array", "items": {"type": "string"}},
+                    "valid_for": {"type": "string"},
+                    "note": {"type": "string"},
+                    "activate": {"type": "boolean"}
+                }
+            }
+        }));
+        tools.push(json!({
+            "name": "engagement.activate",
+            "description": "Makes an engagement the active default for runs",
+            "inputSchema": {"type": "object", "required": ["name"], "properties": {"name": name}}
+        }));
+    }
+    tools
+}
+
+pub fn mcp_call(config: &EngagementsConfig, action: &str, arguments: Value) -> Result<Value> {
+    if matches!(action, "create" | "activate") && !config.mcp_manage {
+        bail!(Message::Disabled {
+            tool: format!("engagement.{}", action),
+            setting: "engagements.mcp_manage=false"
+        });
+    }
+    let mut arguments = match arguments {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    arguments.insert("action".to_string(), Value::String(action.to_string()));
+    let tool = format!("engagement.{}", action);
+    let action =
+        serde_json::from_value::<EngagementAction>(Value::Object(arguments)).map_err(|error| {
+            Message::InvalidArguments {
+                tool,
+                error: error.to_string(),
+            }
+        })?;
+    apply(config, action)
+}
+
+pub fn resolve(config: &EngagementsConfig, requested: Option<&str>) -> Result<Option<Engagement>> {
+    let registry = Registry::load(&config.path)?;
+    let Some(name) = requested.or(registry.active.as_deref()) else {
+        if config.require {
+            bail!(Message::EngagementRequired);
+        }
+        return Ok(None);
+    };
+    let engagement = registry.get(name)?;
+    let now = now_ms();
+    if now < engagement.starts_ms || engagement.ends_ms.is_some_and(|ends| now > ends) {
+        return Err(Message::EngagementClosed {
+            engagement: name.to_string(),
+        }
+        .into());
+    }
+    Ok(Some(engagement.clone()))
+}
+
+pub fn check_scope(engagement: &Engagement, args: &[String]) -> Result<()> {
+    if engagement.scope.is_empty() {
+        return Ok(());
+    }
+    let scope = engagement
+        .scope
+        .iter()
+        .map(|entry| ScopeEntry::parse(entry))
+        .collect::<Result<Vec<_>>>()?;
+    for arg in args {
+        let value = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with('-') => value,
+            _ => arg.as_str(),
+        };
+        let parts = match parse_octets(value) {
+            Some(_) => vec![value],
+            None => value.split(',').collect(),
+        };
+        for part in parts {
+            let targets = match Target::parse(part)
+                .map(|target| vec![target])
+                .or_else(|| parse_octets(part))
+            {
+                Some(targets) => targets,
+                None if host_like(part) => {
+                    return Err(Message::UnrecognizedTarget {
+                        target: part.to_string(),
+                        engagement: engagement.name.clone(),
+                    }
+                    .into());
+                }
+                None => continue,
+            };
+            if !targets
+                .iter()
+                .all(|target| scope.iter().any(|entry| entry.covers(target)))
+            {
+                return Err(Message::OutOfScope {
+                    target: part.to_string(),
+                    engagement: engagement.name.clone(),
+                }
+                .into());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn covers_host(engagement: &Engagement, host: &str) -> bool {
+    if engagement.scope.is_empty() {
+        return true;
+    }
+    let target =
+        Target::parse(host).unwrap_or_else(|| Target::Host(host.trim().to_ascii_lowercase()));
+    engagement
+        .scope
+        .iter()
+        .filter_map(|entry| ScopeEntry::parse(entry).ok())
+        .any(|entry| entry.covers(&target))
+}
+
+pub fn check_hosts(engagement: &Engagement, hosts: &[String]) -> Result<()> {
+    if let Some(host) = hosts.iter().find(|host| !covers_host(engagement, host)) {
+        return Err(Message::OutOfScope {
+            target: host.clone(),
+            engagement: engagement.name.clone(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+enum ScopeEntry {
+    Net(IpAddr, u8),
+    Host(String),
+    Wildcard(String),
+}
+
+impl ScopeEntry {
+    fn parse(entry: &str) -> Result<Self> {
+        let entry = entry.trim();
+        if let Some(net) = parse_net(entry) {
+            return Ok(ScopeEntry::Net(net.0, net.1));
+        }
+        if let Some(suffix) = entry.strip_prefix("*.") {
+            return Ok(ScopeEntry::Wildcard(suffix.to_ascii_lowercase()));
+        }
+        if entry.is_empty() || entry.contains(['/', ' ', '*']) {
+            bail!(Message::InvalidValue {
+                field: "scope",
+                value: entry.to_string()
+            });
+        }
+        Ok(ScopeEntry::Host(entry.to_ascii_lowercase()))
+    }
+
+    fn covers(&self, target: &Target) -> bool {
+        match (self, target) {
+            (ScopeEntry::Net(net, prefix), Target::Net(ip, target_prefix)) => {
+                target_prefix >= prefix && net_contains(*net, *prefix, *ip)
+            }
+            (ScopeEntry::Net(net, prefix), Target::Range(first, last)) => {
+                net_contains(*net, *prefix, *first) && net_contains(*net, *prefix, *last)
+            }
+            (ScopeEntry::Host(host), Target::Host(target)) => host == target,
+            (ScopeEntry::Wildcard(suffix), Target::Host(target)) => {
+                target == suffix || target.ends_with(&format!(".{}", suffix))
+            }
+            _ => false,
+        }
+    }
+}
+
+enum Target {
+    Net(IpAddr, u8),
+    Range(IpAddr, IpAddr),
+    Host(String),
+}
+
+impl Target {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some((_, rest)) = value.split_once("://") {
+            let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+            let authority = authority.rsplit('@').next().unwrap_or(authority);
+            let host = match authority.strip_prefix('[') {
+                Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+                None => authority.split(':').next().unwrap_or_default(),
+            };
+            return match host.parse::<IpAddr>() {
+                Ok(ip) => Some(Target::Net(ip, max_prefix(ip))),
+                Err(_) if !host.is_empty() => Some(Target::Host(host.to_ascii_lowercase())),
+                Err(_) => None,
+            };
+        }
+        if let Some((ip, prefix)) = parse_net(value) {
+            return Some(Target::Net(ip, prefix));
+        }
+        if let Some((host, port)) = value.rsplit_once(':')
+            && port.parse::<u16>().is_ok()
+            && let Ok(ip) = host.parse::<IpAddr>()
+        {
+            return Some(Target::Net(ip, max_prefix(ip)));
+        }
+        if let Some((first, last)) = value.split_once('-')
+            && let Ok(first) = first.parse::<IpAddr>()
+            && let Ok(last) = last.parse::<IpAddr>()
+        {
+            return Some(Target::Range(first, last));
+        }
+        if let Some((first, last_octet)) = value.rsplit_once('-')
+            && let Ok(IpAddr::V4(first)) = first.parse::<IpAddr>()
+            && let Ok(last_octet) = last_octet.parse::<u8>()
+        {
+            let [a, b, c, _] = first.octets();
+            return Some(Target::Range(
+                IpAddr::V4(first),
+                IpAddr::from([a, b, c, last_octet]),
+            ));
+        }
+        parse_hostname(value).map(Target::Host)
+    }
+}
+
+const FILE_EXTENSIONS: &[&str] = &[
+    "bak", "cfg", "conf", "crt", "csv", "db", "dic", "gnmap", "gz", "hash", "hashes", "htm",
+    "html", "ini", "json", "jsonl", "key", "log", "lst", "nmap", "nse", "out", "pcap", "pcapng",
+    "pem", "pot", "rule", "rules", "tar", "tmp", "txt", "xml", "yaml", "yml",
+];
+
+fn parse_hostname(value: &str) -> Option<String> {
+    let value = value.rsplit('@').next().unwrap_or(value);
+    let host = match value.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => host,
+        _ => value,
+    };
+    let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+    let labels = host.split('.').collect::<Vec<_>>();
+    if labels.len() < 2 || host.len() > 253 {
+        return None;
+    }
+    let valid_label = |label: &&str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let tld = labels.last()?;
+    if !labels.iter().all(valid_label)
+        || tld.len() < 2
+        || !tld.chars().all(|c| c.is_ascii_alphabetic())
+        || FILE_EXTENSIONS.contains(tld)
+    {
+        return None;
+    }
+    Some(host)
+}
+
+const MAX_OCTET_RANGES: usize = 1024;
+
+fn parse_octets(value: &str) -> Option<Vec<Target>> {
+    let octets = value
+        .split('.')
+        .map(parse_octet)
+        .collect::<Option<Vec<_>>>()?;
+    if octets.len() != 4 {
+        return None;
+    }
+    let mut ranges = vec![([0u8; 4], [0u8; 4])];
+    for (index, intervals) in octets.iter().enumerate() {
+        ranges = ranges
+            .iter()
+            .flat_map(|(first, last)| {
+                intervals.iter().map(move |(low, high)| {
+                    let (mut first, mut last) = (*first, *last);
+                    first[index] = *low;
+                    last[index] = *high;
+                    (first, last)
+                })
+            })
+            .collect();
+        if ranges.len() > MAX_OCTET_RANGES {
+            return None;
+        }
+    }
+    Some(
+        ranges
+            .into_iter()
+            .map(|(first, last)| Target::Range(IpAddr::from(first), IpAddr::from(last)))
+            .collect(),
+    )
+}
+
+fn parse_octet(octet: &str) -> Option<Vec<(u8, u8)>> {
+    octet
+        .split(',')
+        .map(|part| match part {
+            "*" => Some((0, 255)),
+            _ => match part.split_once('-') {
+                Some((low, high)) => {
+                    let low = if low.is_empty() {
+                        0
+                    } else {
+                        low.parse::<u8>().ok()?
+                    };
+                    let high = if high.is_empty() {
+                        255
+                    } else {
+                        high.parse::<u8>().ok()?
+                    };
+                    (low <= high).then_some((low, high))
+                }
+                None => part.parse::<u8>().ok().map(|value| (value, value)),
+            },
+        })
+        .collect()
+}
+
+fn host_like(value: &str) -> bool {
+    let value = value.trim();
+    value.contains('.')
+        && !value.starts_with(['-', '.', '/'])
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".-*/:,[]@".contains(c))
+        && (value.contains('*')
+            || value.matches('.').count() >= 3 && value.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+fn parse_net(value: &str) -> Option<(IpAddr, u8)> {
+    let (ip, prefix) = match value.split_once('/') {
+        Some((ip, prefix)) => {
+            let ip = ip.parse::<IpAddr>().ok()?;
+            let prefix = prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max_prefix(ip))?;
+            (ip, prefix)
+        }
+        None => {
+            let ip = value.parse::<IpAddr>().ok()?;
+            (ip, max_prefix(ip))
+        }
+    };
+    Some((ip, prefix))
+}
+
+fn max_prefix(ip: IpAddr) -> u8 {
+    match ip {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn net_contains(net: IpAddr, prefix: u8, ip: IpAddr) -> bool {
+    match (net, ip) {
+        (IpAddr::V4(net), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(net) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(net), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(net) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engagement(scope: &[&str]) -> Engagement {
+        Engagement {
+            name: "test".to_string(),
+            scope: scope.iter().map(|entry| entry.to_string()).collect(),
+            created_ms: 0,
+            starts_ms: 0,
+            ends_ms: None,
+            notes: Vec::new(),
+        }
+    }
+
+    fn check(scope: &[&str], args: &[&str]) -> Result<(), &'static str> {
+        let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        check_scope(&engagement(scope), &args).map_err(|error| {
+            match error.downcast_ref::<Message>() {
+                Some(message) => message.code(),
+                None => "other",
+            }
+        })
+    }
+
+    #[test]
+    fn bare_hostnames_are_checked() {
+        let scope = ["10.0.0.0/24", "target.example", "*.corp.example"];
+        assert!(check(&scope, &["-sV", "target.example"]).is_ok());
+        assert!(check(&scope, &["www.corp.example:443"]).is_ok());
+        assert!(check(&scope, &["admin@corp.example"]).is_ok());
+        assert_eq!(check(&scope, &["evil.example.com"]), Err("E_OUT_OF_SCOPE"));
+        assert_eq!(
+            check(&scope, &["--target=evil.example.com"]),
+            Err("E_OUT_OF_SCOPE")
+        );
+        assert_eq!(
+            check(&scope, &["target.example,evil.example.com"]),
+            Err("E_OUT_OF_SCOPE")
+        );
+        assert_eq!(
+            check(&["10.0.0.0/24"], &["scanme.nmap.org"]),
+            Err("E_OUT_OF_SCOPE")
+        );
+    }
+
+    #[test]
+    fn file_arguments_are_not_hostnames() {
+        let scope = ["10.0.0.0/24"];
+        assert!(
+            check(
+                &scope,
+                &[
+                    "-iL",
+                    "targets.txt",
+                    "-oX",
+                    "scan.xml",
+                    "/usr/share/wordlists/rockyou.txt"
+                ]
+            )
+            .is_ok()
+        );
+        assert!(check(&scope, &["-p", "80,443", "--min-rate", "1.5", "10.0.0.5"]).is_ok());
+    }
+
+    #[test]
+    fn nmap_octet_ranges_are_checked() {
+        let scope = ["192.168.0.0/16", "10.0.0.0/24"];
+        assert!(check(&scope, &["10.0.0.*"]).is_ok());
+        assert!(check(&scope, &["192.168.0-255.1"]).is_ok());
+        assert!(check(&scope, &["192.168.3-5,7.1-"]).is_ok());
+        assert_eq!(check(&scope, &["10.0.*.1"]), Err("E_OUT_OF_SCOPE"));
+        assert_eq!(check(&scope, &["10.0.0-1.*"]), Err("E_OUT_OF_SCOPE"));
+        assert_eq!(check(&scope, &["192.167-168.0.1"]), Err("E_OUT_OF_SCOPE"));
+    }
+
+    #[test]
+    fn unrecognized_host_like_arguments_are_rejected() {
+        let scope = ["10.0.0.0/24"];
+        assert_eq!(check(&scope, &["10.0.0.256"]), Err("E_OUT_OF_SCOPE"));
+        assert_eq!(check(&scope, &["10.0.0.0/33"]), Err("E_OUT_OF_SCOPE"));
+        assert_eq!(check(&scope, &["*.example.com"]), Err("E_OUT_OF_SCOPE"));
+    }
+
+    #[test]
+    fn empty_scope_allows_everything() {
+        assert!(check(&[], &["evil.example.com", "10.0.0.256"]).is_ok());
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde_json::Value;
+
+use crate::audit;
+use crate::platform;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum EventsFormat {
+    #[default]
+    Jsonl,
+    Pretty,
+}
+
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub run: Option<String>,
+    pub tool: Option<String>,
+    pub host: Option<String>,
+    pub kinds: Vec<String>,
+}
+
+struct RunInfo {
+    id: Option<String>,
+    tool: Option<String>,
+    host: Option<String>,
+}
+
+#[derive(Default)]
+struct Tracker {
+    runs: HashMap<String, (Option<String>, Option<String>)>,
+    tokens: HashMap<String, String>,
+}
+
+impl Tracker {
+    fn resolve(&mut self, record: &Value) -> RunInfo {
+        let entry = &record["entry"];
+        let token = entry["run_token"].as_str();
+        if let (Some(id), Some(token)) = (entry["correlation_id"].as_str(), token) {
+            self.tokens.insert(token.to_string(), id.to_string());
+        }
+        let id = entry["correlation_id"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| token.and_then(|token| self.tokens.get(token).cloned()));
+        let mut tool = entry["tool"].as_str().map(str::to_string);
+        let mut host = entry["host"]
+            .as_str()
+            .or_else(|| {
+                entry["target"]
+                    .as_str()
+                    .and_then(|target| target.rsplit('@').next())
+            })
+            .map(str::to_string);
+        if let Some(id) = &id {
+            let known = self.runs.entry(id.clone()).or_default();
+            if tool.is_some() {
+                known.0 = tool.clone();
+            }
+            if host.is_some() {
+                known.1 = host.clone();
+            }
+            tool = known.0.clone();
+            host = known.1.clone();
+        }
+        RunInfo { id, tool, host }
+    }
+}
+
+impl Filter {
+    fn matches(&self, record: &Value, info: &RunInfo, tokens: &HashMap<String, String>) -> bool {
+        let kind = record["kind"].as_str().unwrap_or_default();
+        if !self.kinds.is_empty() && !self.kinds.iter().any(|selected| selected == kind) {
+            return false;
+        }
+        if let Some(run) = &self.run {
+            let by_id = info.id.as_deref() == Some(run.as_str());
+            let by_token = record["entry"]["run_token"] == run.as_str()
+                || tokens
+                    .get(run)
+                    .is_some_and(|id| info.id.as_deref() == Some(id.as_str()));
+            if !by_id && !by_token {
+                return false;
+            }
+        }
+        self.tool
+            .as_ref()
+            .is_none_or(|tool| info.tool.as_deref() == Some(tool.as_str()))
+            && self
+                .host
+                .as_ref()
+                .is_none_or(|host| info.host.as_deref() == Some(host.as_str()))
+    }
+}
+
+pub async fn tail(
+    path: &Path,
+    filter: &Filter,
+    last: Option<usize>,
+    follow: bool,
+    format: EventsFormat,
+) -> Result<()> {
+    let mut tracker = Tracker::default();
+    let mut history = VecDeque::new();
+    let mut last_seq = 0;
+    let records = if path.exists() || !follow {
+        audit::read_records(path)?
+    } else {
+        Vec::new()
+    };
+    for record in records {
+        last_seq = last_seq.max(record["seq"].as_u64().unwrap_or(0));
+        let info = tracker.resolve(&record);
+        if filter.matches(&record, &info, &tracker.tokens) {
+            history.push_back((record, info));
+            if last.is_some_and(|last| history.len() > last) {
+                history.pop_front();
+            }
+        }
+    }
+    for (record, info) in history {
+        print(&record, &info, format);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    let mut follower = Follower::new(path);
+    loop {
+        for record in follower.poll()? {
+            let seq = record["seq"].as_u64().unwrap_or(0);
+            if seq != 0 && seq <= last_seq {
+                continue;
+            }
+            last_seq = last_seq.max(seq);
+            let info = tracker.resolve(&record);
+            if filter.matches(&record, &info, &tracker.tokens) {
+                print(&record, &info, format);
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+struct Follower {
+    path: PathBuf,
+    file: Option<(File, u64)>,
+    pending: Vec<u8>,
+}
+
+impl Follower {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            file: None,
+            pending: Vec::new(),
+        }
+    }
+
+    fn open(&mut self) -> bool {
+        let Ok(file) = File::open(&self.path) else {
+            return false;
+        };
+        let inode = file
+            .metadata()
+            .map(|meta| platform::file_id(&meta))
+            .unwrap_or(0);
+        self.file = Some((file, inode));
+        true
+    }
+
+    fn read(&mut self) -> Result<()> {
+        if let Some((file, _)) = self.file.as_mut() {
+            file.read_to_end(&mut self.pending).with_context(|| {
+                format!(
+                    "Audit-Log {} konnte nicht gelesen werden",
+                    self.path.display()
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Result<Vec<Value>> {
+        if self.file.is_none() && !self.open() {
+            return Ok(Vec::new());
+        }
+        let current = self.file.as_ref().map_or(0, |(_, inode)| *inode);
+        let rotated =
+            fs::metadata(&self.path).is_ok_and(|meta| platform::file_id(&meta) != current);
+        self.read()?;
+        if rotated && self.open() {
+            self.read()?;
+        }
+
+        let mut records = Vec::new();
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line = self.pending.drain(..=end).collect::<Vec<_>>();
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let record = serde_json::from_slice(&line).with_context(|| {
+                format!(
+                    "{}: Audit-Record ist kein gültiges JSON",
+                    self.path.display()
+                )
+            })?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
+fn print(record: &Value, info: &RunInfo, format: EventsFormat) {
+    match format {
+        EventsFormat::Jsonl => println!("{}", record),
+        EventsFormat::Pretty => println!("{}", pretty(record, info)),
+    }
+}
+
+fn pretty(record: &Value, info: &RunInfo) -> String {
+    let entry = &record["entry"];
+    let mut line = format!(
+        "{} #{:<6} {:<18} {}",
+        clock(record["ts_ms"].as_u64().unwrap_or(0)),
+        record["seq"].as_u64().unwrap_or(0),
+        record["kind"].as_str().unwrap_or("-"),
+        info.id.as_deref().unwrap_or("-")
+    );
+    if let Some(tool) = &info.tool {
+        line.push_str(&format!(
+            " {}@{}",
+            tool,
+            info.host.as_deref().unwrap_or("-")
+        ));
+    }
+    if let Some(attempt) = entry["attempt"].as_u64() {
+        line.push_str(&format!(" Versuch {}", attempt));
+    }
+    if let Some(code) = entry["exit_code"].as_i64() {
+        line.push_str(&format!(" exit={}", code));
+    }
+    if entry["timed_out"] == true {
+        line.push_str(" (Timeout)");
+    }
+    if let Some(duration) = entry["duration_ms"].as_u64() {
+        line.push_str(&format!(" {:.1} s", duration as f64 / 1000.0));
+    }
+    if let Some(command) = entry["command"]["command"].as_str() {
+        line.push_str(&format!(" {}", command));
+    }
+    if let Some(error) = entry["error"].as_str() {
+        line.push_str(&format!(": {}", error));
+    }
+    line
+}
+
+fn clock(ts_ms: u64) -> String {
+    let secs = (ts_ms / 1000) % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        ts_ms % 1000
+    )
+}
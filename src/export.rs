@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use crate::artifacts;
+use crate::encryption::{self, Sealer};
+use crate::findings::{Finding, Severity};
+use crate::findings_store;
+use crate::stats;
+
+const RUN_COLUMNS: &[&str] = &[
+    "started_at",
+    "started_ms",
+    "run_token",
+    "correlation_id",
+    "attempt",
+    "tool",
+    "host",
+    "target",
+    "engagement",
+    "client",
+    "operator",
+    "args",
+    "labels",
+    "status",
+    "exit_code",
+    "timed_out",
+    "truncated",
+    "duration_ms",
+    "error",
+    "findings",
+    "max_severity",
+];
+
+const DEFAULT_RUN_COLUMNS: &[&str] = &[
+    "started_at",
+    "correlation_id",
+    "tool",
+    "host",
+    "engagement",
+    "status",
+    "exit_code",
+    "duration_ms",
+    "findings",
+    "max_severity",
+];
+
+const FINDING_COLUMNS: &[&str] = &[
+    "ts",
+    "ts_ms",
+    "correlation_id",
+    "tool",
+    "engagement",
+    "labels",
+    "source",
+    "kind",
+    "severity",
+    "title",
+    "host",
+    "port",
+    "protocol",
+    "evidence",
+    "references",
+    "cves",
+    "cvss",
+    "fingerprint",
+];
+
+const DEFAULT_FINDING_COLUMNS: &[&str] = &[
+    "ts",
+    "correlation_id",
+    "tool",
+    "engagement",
+    "severity",
+    "kind",
+    "host",
+    "port",
+    "title",
+    "cvss",
+    "fingerprint",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportKind {
+    Runs,
+    Findings,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Jsonl,
+}
+
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub since_ms: Option<u128>,
+    pub until_ms: Option<u128>,
+    pub host: Option<String>,
+    pub tool: Option<String>,
+    pub engagement: Option<String>,
+    pub min_severity: Option<Severity>,
+}
+
+impl Filter {
+    fn matches(
+        &self,
+        row: &HashMap<&'static str, Value>,
+        ts_ms: u128,
+        severity: Option<Severity>,
+    ) -> bool {
+        let text = |column: &str| row.get(column).and_then(Value::as_str);
+        self.since_ms.is_none_or(|since| ts_ms >= since)
+            && self.until_ms.is_none_or(|until| ts_ms < until)
+            && self
+                .host
+                .as_deref()
+                .is_none_or(|host| text("host") == Some(host))
+            && self
+                .tool
+                .as_deref()
+                .is_none_or(|tool| text("tool") == Some(tool))
+            && self
+                .engagement
+                .as_deref()
+                .is_none_or(|engagement| text("engagement") == Some(engagement))
+            && self
+                .min_severity
+                .is_none_or(|min| severity.is_some_and(|severity| severity >= min))
+    }
+}
+
+pub fn columns(kind: ExportKind, requested: &[String]) -> Result<Vec<&'static str>> {
+    let (available, defaults) = match kind {
+        ExportKind::Runs => (RUN_COLUMNS, DEFAULT_RUN_COLUMNS),
+        ExportKind::Findings => (FINDING_COLUMNS, DEFAULT_FINDING_COLUMNS),
+    };
+    if requested.is_empty() {
+        return Ok(defaults.to_vec());
+    }
+    requested
+        .iter()
+        .map(|name| {
+            let name = name.trim();
+            available
+                .iter()
+                .copied()
+                .find(|column| *column == name)
+                .with_context(|| {
+                    format!(
+                        "unbekannte Spalte '{}' (verfügbar: {})",
+                        name,
+                        available.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+pub fn parse_instant(input: &str, now_ms: u128) -> Result<u128> {
+    let input = input.trim();
+    if input.len() < 10 || input.as_bytes().get(4) != Some(&b'-') {
+        return Ok(now_ms.saturating_sub(stats::parse_window(input)?));
+    }
+    let invalid = || {
+        format!(
+            "ungültiger Zeitpunkt '{}' (z. B. 2026-10-01, 2026-10-01T08:30:00Z oder 7d)",
+            input
+        )
+    };
+    let (date, time) = input.split_once(['T', ' ']).unwrap_or((input, ""));
+    let date = date
+        .split('-')
+        .map(str::parse::<u32>)
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(invalid)?;
+    let time = time.trim_end_matches('Z');
+    let time = if time.is_empty() {
+        Vec::new()
+    } else {
+        time.split(':')
+            .map(str::parse::<u32>)
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(invalid)?
+    };
+    let [year, month, day] = date[..] else {
+        bail!(invalid());
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || time.len() > 3
+        || time.iter().any(|part| *part > 59)
+    {
+        bail!(invalid());
+    }
+    let seconds = time
+        .iter()
+        .chain([0, 0, 0].iter())
+        .take(3)
+        .fold(0, |acc, part| acc * 60 + u128::from(*part));
+    let days = days_from_civil(i64::from(year), month, day);
+    if days < 0 {
+        bail!(invalid());
+    }
+    Ok((days as u128 * 86_400 + seconds) * 1000)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+pub fn export(
+    records: &[Value],
+    kind: ExportKind,
+    filter: &Filter,
+    columns: &[&'static str],
+    format: ExportFormat,
+    sealer: Option<&Sealer>,
+    out: &mut dyn Write,
+) -> Result<usize> {
+    let rows = match kind {
+        ExportKind::Runs => run_rows(records, filter, sealer)?,
+        ExportKind::Findings => finding_rows(records, filter, sealer)?,
+    };
+    if format == ExportFormat::Csv {
+        let header = columns
+            .iter()
+            .map(|column| csv_field(column, false))
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", header.join(","))?;
+    }
+    for row in &rows {
+        let line = match format {
+            ExportFormat::Csv => columns
+                .iter()
+                .map(|column| csv_cell(row.get(column).unwrap_or(&Value::Null)))
+                .collect::<Vec<_>>()
+                .join(","),
+            ExportFormat::Jsonl => {
+                let fields = columns
+                    .iter()
+                    .map(|column| {
+                        format!(
+                            "{}:{}",
+                            json!(column),
+                            row.get(column).unwrap_or(&Value::Null)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!("{{{}}}", fields.join(","))
+            }
+        };
+        writeln!(out, "{}", line)?;
+    }
+    out.flush()?;
+    Ok(rows.len())
+}
+
+fn run_rows(
+    records: &[Value],
+    filter: &Filter,
+    sealer: Option<&Sealer>,
+) -> Result<Vec<HashMap<&'static str, Value>>> {
+    let mut rows = Vec::<(u128, HashMap<&'static str, Value>)>::new();
+    let mut index = HashMap::<String, usize>::new();
+    let mut latest = HashMap::<String, usize>::new();
+
+    for record in records {
+        let ts_ms = record["ts_ms"].as_u64().map(u128::from).unwrap_or(0);
+        let entry = &record["entry"];
+        match record["kind"].as_str() {
+            Some("run_started") => {
+                let Some(run_token) = entry["run_token"].as_str() else {
+                    continue;
+                };
+                let host = entry["host"].as_str().or_else(|| {
+                    entry["target"]
+                        .as_str()
+                        .map(|target| target.rsplit('@').next().unwrap_or(target))
+                });
+                let attempt = entry["attempt"].as_u64().unwrap_or(1);
+                let row = HashMap::from([
+                    ("started_at", json!(timestamp(ts_ms))),
+                    ("started_ms", json!(ts_ms)),
+                    ("run_token", json!(run_token)),
+                    ("correlation_id", entry["correlation_id"].clone()),
+                    ("attempt", json!(attempt)),
+                    ("tool", entry["tool"].clone()),
+                    ("host", json!(host)),
+                    ("target", entry["target"].clone()),
+                    ("engagement", entry["engagement"].clone()),
+                    ("client", entry["client"].clone()),
+                    ("operator", entry["operator"].clone()),
+                    ("args", json!(join_args(&entry["args"]))),
+                    ("labels", json!(join_labels(&entry["labels"]))),
+                    ("status", json!("unfinished")),
+                    ("findings", json!(0)),
+                ]);
+                if let Some(correlation_id) = entry["correlation_id"].as_str() {
+                    latest.insert(correlation_id.to_string(), rows.len());
+                }
+                index.insert(run_token.to_string(), rows.len());
+                rows.push((ts_ms, row));
+            }
+            Some("run_finished") => {
+                let Some(row) = entry["run_token"]
+                    .as_str()
+                    .and_then(|token| index.get(token))
+                    .map(|at| &mut rows[*at].1)
+                else {
+                    continue;
+                };
+                let timed_out = entry["timed_out"].as_bool().unwrap_or(false);
+                let status = match entry["exit_code"].as_i64() {
+                    _ if timed_out => "timed_out",
+                    Some(0) => "succeeded",
+                    _ => "failed",
+                };
+                row.insert("status", json!(status));
+                row.insert("exit_code", entry["exit_code"].clone());
+                row.insert("timed_out", json!(timed_out));
+                row.insert(
+                    "truncated",
+                    json!(entry["truncated"].as_bool().unwrap_or(false)),
+                );
+                row.insert("duration_ms", entry["duration_ms"].clone());
+            }
+            Some("run_failed") => {
+                let Some(row) = entry["run_token"]
+                    .as_str()
+                    .and_then(|token| index.get(token))
+                    .map(|at| &mut rows[*at].1)
+                else {
+                    continue;
+                };
+                row.insert("status", json!("error"));
+                row.insert("error", entry["error"].clone());
+            }
+            Some("findings") => {
+                let Some(at) = entry["correlation_id"]
+                    .as_str()
+                    .and_then(|id| latest.get(id))
+                else {
+                    continue;
+                };
+                let findings = load_findings(entry, sealer)?;
+                let row = &mut rows[*at].1;
+                let count = row["findings"].as_u64().unwrap_or(0) + findings.len() as u64;
+                let highest = row
+                    .get("max_severity")
+                    .and_then(Value::as_str)
+                    .and_then(Severity::parse);
+                let highest = findings
+                    .iter()
+                    .map(|finding| finding.severity)
+                    .chain(highest)
+                    .max();
+                row.insert("findings", json!(count));
+                row.insert("max_severity", json!(highest.map(Severity::as_str)));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(rows
+        .into_iter()
+        .filter(|(ts_ms, row)| {
+            let severity = row
+                .get("max_severity")
+                .and_then(Value::as_str)
+                .and_then(Severity::parse);
+            filter.matches(row, *ts_ms, severity)
+        })
+        .map(|(_, row)| row)
+        .collect())
+}
+
+fn finding_rows(
+    records: &[Value],
+    filter: &Filter,
+    sealer: Option<&Sealer>,
+) -> Result<Vec<HashMap<&'static str, Value>>> {
+    let mut rows = Vec::new();
+    for record in records.iter().filter(|record| record["kind"] == "findings") {
+        let ts_ms = record["ts_ms"].as_u64().map(u128::from).unwrap_or(0);
+        let entry = &record["entry"];
+        for finding in load_findings(entry, sealer)? {
+            let row = HashMap::from([
+                ("ts", json!(timestamp(ts_ms))),
+                ("ts_ms", json!(ts_ms)),
+                ("correlation_id", entry["correlation_id"].clone()),
+                ("tool", entry["tool"].clone()),
+                ("engagement", entry["engagement"].clone()),
+                ("labels", json!(join_labels(&entry["labels"]))),
+                ("source", json!(finding.source)),
+                ("kind", json!(finding.kind)),
+                ("severity", json!(finding.severity.as_str())),
+                ("title", json!(finding.title)),
+                ("host", json!(finding.host)),
+                ("port", json!(finding.port)),
+                ("protocol", json!(finding.protocol)),
+                ("evidence", json!(finding.evidence)),
+                ("references", json!(finding.references)),
+                ("cves", json!(cve_ids(&finding))),
+                ("cvss", finding.data["cvss"].clone()),
+                ("fingerprint", json!(findings_store::fingerprint(&finding))),
+            ]);
+            if filter.matches(&row, ts_ms, Some(finding.severity)) {
+                rows.push(row);
+            }
+        }
+    }
+    Ok(rows)
+}
+
+fn load_findings(entry: &Value, sealer: Option<&Sealer>) -> Result<Vec<Finding>> {
+    let findings = encryption::unseal_value(sealer, &entry["findings"]).with_context(|| {
+        format!(
+            "Findings von {} konnten nicht entschlüsselt werden",
+            entry["correlation_id"]
+        )
+    })?;
+    serde_json::from_value(findings).context("findings-Record ist ungültig")
+}
+
+fn cve_ids(finding: &Finding) -> Vec<&str> {
+    finding.data["cves"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|cve| cve["id"].as_str())
+        .collect()
+}
+
+fn join_args(args: &Value) -> Option<String> {
+    let args = args.as_array()?;
+    Some(
+        args.iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn join_labels(labels: &Value) -> Option<String> {
+    let labels = labels.as_object().filter(|labels| !labels.is_empty())?;
+    let pairs = labels
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value.as_str().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    Some(pairs.join(";"))
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => csv_field(text, true),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| {
+                    item.as_str()
+                        .map_or_else(|| item.to_string(), str::to_string)
+                })
+                .collect::<Vec<_>>();
+            csv_field(&items.join(";"), true)
+        }
+        Value::Object(_) => csv_field(&value.to_string(), true),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(text: &str, neutralize: bool) -> String {
+    let text = if neutralize && text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text)
+    } else {
+        text.to_string()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn timestamp(ms: u128) -> String {
+    let secs = (ms / 1000) as u64;
+    let (year, month, day) = artifacts::civil_from_days((secs / 86_400) as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    #[default]
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 5] = [
+        Severity::Info,
+        Severity::Low,
+        Severity::Medium,
+        Severity::High,
+        Severity::Critical,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "info" | "informational" | "unknown" | "none" => Some(Severity::Info),
+            "low" => Some(Severity::Low),
+            "medium" | "moderate" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub source: String,
+    pub kind: String,
+    pub severity: Severity,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub data: Value,
+}
+
+impl Finding {
+    pub fn new(source: &str, kind: &str, severity: Severity, title: impl Into<String>) -> Self {
+        Self {
+            source: source.to_string(),
+            kind: kind.to_string(),
+            severity,
+            title: title.into(),
+            host: None,
+            port: None,
+            protocol: None,
+            references: Vec::new(),
+            evidence: None,
+            data: Value::Null,
+        }
+    }
+}
+
+pub fn summary(findings: &[Finding]) -> Value {
+    let mut counts = serde_json::Map::new();
+    for severity in Severity::ALL.iter().rev() {
+        let count = findings
+            .iter()
+            .filter(|finding| finding.severity == *severity)
+            .count();
+        if count > 0 {
+            counts.insert(severity.as_str().to_string(), json!(count));
+        }
+    }
+    json!({"total": findings.len(), "by_severity": counts})
+}
+
+pub fn highest(findings: &[Finding], limit: usize) -> Value {
+    let mut sorted = findings.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+    sorted
+        .into_iter()
+        .take(limit)
+        .map(|finding| json!({"severity": finding.severity, "title": finding.title, "host": finding.host, "port": finding.port}))
+        .collect()
+}
@@ -0,0 +1,320 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::artifacts;
+use crate::encryption::{self, Sealer};
+use crate::findings::{Finding, Severity};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FindingsStoreConfig {
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: String,
+    pub target: String,
+    pub location: String,
+    pub signature: String,
+    pub finding: Finding,
+    #[serde(default)]
+    pub sources: BTreeSet<String>,
+    #[serde(default)]
+    pub engagements: BTreeSet<String>,
+    pub first_seen_ms: u128,
+    pub last_seen_ms: u128,
+    pub occurrences: u64,
+    #[serde(default)]
+    pub first_run: Option<String>,
+    #[serde(default)]
+    pub last_run: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct State {
+    entries: BTreeMap<String, Entry>,
+}
+
+pub struct FindingsStore {
+    path: PathBuf,
+    sealer: Option<Sealer>,
+    lock: Mutex<()>,
+}
+
+impl FindingsStore {
+    pub fn open(config: &FindingsStoreConfig, sealer: Option<Sealer>) -> Option<Self> {
+        config.path.as_ref().map(|path| Self {
+            path: PathBuf::from(path),
+            sealer,
+            lock: Mutex::new(()),
+        })
+    }
+
+    pub fn observe(
+        &self,
+        run_id: Option<&str>,
+        engagement: Option<&str>,
+        findings: &[Finding],
+    ) -> Result<Value> {
+        let _guard = self.lock.lock().expect("findings store poisoned");
+        let mut state = load(&self.path, self.sealer.as_ref())?;
+        let now = now_ms();
+        let (mut new, mut repeated) = (0, 0);
+        for finding in findings {
+            let (target, location, signature) = key(finding);
+            let id = entry_id(&target, &location, &signature);
+            let entry = state.entries.entry(id.clone()).or_insert_with(|| {
+                new += 1;
+                Entry {
+                    id,
+                    target,
+                    location,
+                    signature,
+                    finding: finding.clone(),
+                    sources: BTreeSet::new(),
+                    engagements: BTreeSet::new(),
+                    first_seen_ms: now,
+                    last_seen_ms: now,
+                    occurrences: 0,
+                    first_run: run_id.map(str::to_string),
+                    last_run: None,
+                }
+            });
+            if entry.occurrences > 0 {
+                repeated += 1;
+            }
+            let mut references = std::mem::take(&mut entry.finding.references);
+            for reference in &finding.references {
+                if !references.contains(reference) {
+                    references.push(reference.clone());
+                }
+            }
+            entry.finding = Finding {
+                severity: entry.finding.severity.max(finding.severity),
+                references,
+                ..finding.clone()
+            };
+            entry.sources.insert(finding.source.clone());
+            entry.engagements.extend(engagement.map(str::to_string));
+            entry.last_seen_ms = now;
+            entry.occurrences += 1;
+            entry.last_run = run_id.map(str::to_string).or(entry.last_run.take());
+        }
+        save(&self.path, &state, self.sealer.as_ref())?;
+        Ok(json!({"new": new, "repeated": repeated, "total": state.entries.len()}))
+    }
+}
+
+fn key(finding: &Finding) -> (String, String, String) {
+    let target = finding.host.clone().unwrap_or_else(|| "-".to_string());
+    let port = finding
+        .port
+        .map(|port| format!("{}/{}", port, finding.protocol.as_deref().unwrap_or("tcp")));
+    let location = match (port, finding.evidence.as_deref().and_then(path)) {
+        (Some(port), Some(path)) => format!("{} {}", port, path),
+        (Some(port), None) => port,
+        (None, Some(path)) => path.to_string(),
+        (None, None) => String::new(),
+    };
+    let title = finding
+        .title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    (target, location, format!("{}:{}", finding.kind, title))
+}
+
+fn path(evidence: &str) -> Option<&str> {
+    let evidence = evidence.trim();
+    if evidence.starts_with('/') {
+        return Some(evidence);
+    }
+    let rest = evidence.split_once("://")?.1;
+    Some(rest.find('/').map_or("/", |index| &rest[index..]))
+}
+
+pub fn fingerprint(finding: &Finding) -> String {
+    let (target, location, signature) = key(finding);
+    entry_id(&target, &location, &signature)
+}
+
+fn entry_id(target: &str, location: &str, signature: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{}\n{}", target, location, signature));
+    format!("{:x}", digest)[..16].to_string()
+}
+
+fn load(path: &Path, sealer: Option<&Sealer>) -> Result<State> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(State::default()),
+        Err(error) => {
+            return Err(error).with_context(|| {
+                format!(
+                    "Findings-Store {} konnte nicht gelesen werden",
+                    path.display()
+                )
+            });
+        }
+    };
+    let data = encryption::unseal(sealer, data).with_context(|| {
+        format!(
+            "Findings-Store {} konnte nicht entschlüsselt werden",
+            path.display()
+        )
+    })?;
+    serde_json::from_slice(&data)
+        .with_context(|| format!("Findings-Store {} ist ungültig", path.display()))
+}
+
+fn save(path: &Path, state: &State, sealer: Option<&Sealer>) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| {
+            format!("Verzeichnis {} konnte nicht angelegt werden", dir.display())
+        })?;
+    }
+    let data = serde_json::to_vec_pretty(state)?;
+    let data = match sealer {
+        Some(sealer) => sealer.seal(&data)?,
+        None => data,
+    };
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).with_context(|| {
+        format!(
+            "Findings-Store {} konnte nicht geschrieben werden",
+            tmp.display()
+        )
+    })?;
+    fs::rename(&tmp, path).with_context(|| {
+        format!(
+            "Findings-Store {} konnte nicht ersetzt werden",
+            path.display()
+        )
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct Query {
+    pub host: Option<String>,
+    pub engagement: Option<String>,
+    pub min_severity: Option<Severity>,
+}
+
+impl Query {
+    fn matches(&self, entry: &Entry) -> bool {
+        self.host.as_ref().is_none_or(|host| entry.target == *host)
+            && self
+                .engagement
+                .as_ref()
+                .is_none_or(|engagement| entry.engagements.contains(engagement))
+            && self
+                .min_severity
+                .is_none_or(|severity| entry.finding.severity >= severity)
+    }
+}
+
+pub fn list(
+    path: &Path,
+    sealer: Option<&Sealer>,
+    query: &Query,
+    since_ms: Option<u128>,
+) -> Result<Vec<Entry>> {
+    let state = load(path, sealer)?;
+    let mut entries = state
+        .entries
+        .into_values()
+        .filter(|entry| {
+            query.matches(entry) && since_ms.is_none_or(|since| entry.last_seen_ms >= since)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| {
+        b.finding
+            .severity
+            .cmp(&a.finding.severity)
+            .then_with(|| a.target.cmp(&b.target))
+            .then_with(|| a.location.cmp(&b.location))
+    });
+    Ok(entries)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Diff {
+    pub since_ms: u128,
+    pub new: Vec<Entry>,
+    pub recurring: Vec<Entry>,
+    pub stale: Vec<Entry>,
+}
+
+pub fn diff(entries: Vec<Entry>, since_ms: u128) -> Diff {
+    let mut diff = Diff {
+        since_ms,
+        new: Vec::new(),
+        recurring: Vec::new(),
+        stale: Vec::new(),
+    };
+    for entry in entries {
+        if entry.first_seen_ms >= since_ms {
+            diff.new.push(entry);
+        } else if entry.last_seen_ms >= since_ms {
+            diff.recurring.push(entry);
+        } else {
+            diff.stale.push(entry);
+        }
+    }
+    diff
+}
+
+pub fn render_table(entries: &[Entry]) -> String {
+    let mut out = format!(
+        "{:<16} {:<9} {:<18} {:<20} {:>5}  {:<20} TITEL\n",
+        "ID", "SCHWERE", "ZIEL", "ORT", "ANZ.", "ZULETZT"
+    );
+    for entry in entries {
+        out.push_str(&format!(
+            "{:<16} {:<9} {:<18} {:<20} {:>5}  {:<20} {}\n",
+            entry.id,
+            entry.finding.severity.as_str(),
+            entry.target,
+            if entry.location.is_empty() {
+                "-"
+            } else {
+                &entry.location
+            },
+            entry.occurrences,
+            timestamp(entry.last_seen_ms),
+            entry.finding.title
+        ));
+    }
+    out
+}
+
+fn timestamp(ms: u128) -> String {
+    let secs = (ms / 1000) as u64;
+    let days = secs / 86400;
+    let (year, month, day) = artifacts::civil_from_days(days as i64);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,157 @@
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+const MAX_FRAME_BYTES: usize = 64 * 1024 * 1024;
+const MAX_HEADER_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Framing {
+    #[default]
+    Auto,
+    Lines,
+    ContentLength,
+}
+
+pub struct FrameReader<R> {
+    reader: R,
+    detected: Arc<OnceLock<Framing>>,
+}
+
+impl<R: AsyncBufRead + Unpin> FrameReader<R> {
+    pub fn new(reader: R, framing: Framing) -> Self {
+        let detected = Arc::new(OnceLock::new());
+        if framing != Framing::Auto {
+            let _ = detected.set(framing);
+        }
+        Self { reader, detected }
+    }
+
+    pub fn detected(&self) -> Arc<OnceLock<Framing>> {
+        self.detected.clone()
+    }
+
+    pub async fn next_message(&mut self) -> Result<Option<String>> {
+        let framing = match self.detected.get() {
+            Some(framing) => *framing,
+            None => match self.detect().await? {
+                Some(framing) => framing,
+                None => return Ok(None),
+            },
+        };
+        match framing {
+            Framing::ContentLength => self.read_frame().await,
+            _ => {
+                let mut line = String::new();
+                if self.reader.read_line(&mut line).await? == 0 {
+                    return Ok(None);
+                }
+                Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+            }
+        }
+    }
+
+    async fn detect(&mut self) -> Result<Option<Framing>> {
+        loop {
+            let buffer = self.reader.fill_buf().await?;
+            let Some(first) = buffer.first().copied() else {
+                return Ok(None);
+            };
+            if first.is_ascii_whitespace() {
+                self.reader.consume(1);
+                continue;
+            }
+            let framing = if first == b'{' || first == b'[' {
+                Framing::Lines
+            } else {
+                Framing::ContentLength
+            };
+            let _ = self.detected.set(framing);
+            return Ok(Some(framing));
+        }
+    }
+
+    async fn read_frame(&mut self) -> Result<Option<String>> {
+        let mut length = None;
+        let mut header_bytes = 0;
+        let mut started = false;
+        loop {
+            let mut line = String::new();
+            let read = self.reader.read_line(&mut line).await?;
+            if read == 0 {
+                if started {
+                    bail!("stdin endete mitten im Content-Length-Header");
+                }
+                return Ok(None);
+            }
+            header_bytes += read;
+            if header_bytes > MAX_HEADER_BYTES {
+                bail!(
+                    "Content-Length-Header ist länger als {} Bytes",
+                    MAX_HEADER_BYTES
+                );
+            }
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                if started {
+                    break;
+                }
+                continue;
+            }
+            started = true;
+            let Some((name, value)) = line.split_once(':') else {
+                bail!("ungültige Header-Zeile '{}'", line);
+            };
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let value = value
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("ungültige Content-Length '{}'", value.trim()))?;
+                length = Some(value);
+            }
+        }
+        let length = length.context("Frame ohne Content-Length-Header")?;
+        if length > MAX_FRAME_BYTES {
+            bail!(
+                "Frame mit {} Bytes überschreitet das Limit von {} Bytes",
+                length,
+                MAX_FRAME_BYTES
+            );
+        }
+        let mut body = vec![0_u8; length];
+        self.reader
+            .read_exact(&mut body)
+            .await
+            .context("stdin endete mitten im Frame")?;
+        String::from_utf8(body)
+            .map(Some)
+            .context("Frame ist kein gültiges UTF-8")
+    }
+}
+
+pub async fn write_stdout(
+    mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    detected: Arc<OnceLock<Framing>>,
+) -> Result<()> {
+    let mut out = tokio::io::stdout();
+    let mut pending = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+        pending.extend(chunk);
+        while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+            let line = pending.drain(..=end).collect::<Vec<_>>();
+            if detected.get() == Some(&Framing::ContentLength) {
+                let body = &line[..line.len() - 1];
+                out.write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+                    .await?;
+                out.write_all(body).await?;
+            } else {
+                out.write_all(&line).await?;
+            }
+        }
+        out.flush().await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,394 @@
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+use crate::{redact, shell_escape};
+
+const DENIED_EXIT: i32 = 3;
+const MISSING_EXIT: i32 = 4;
+const EXISTS_EXIT: i32 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsConfig {
+    #[serde(default)]
+    pub read: Vec<String>,
+    #[serde(default)]
+    pub write: Vec<String>,
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+}
+
+fn default_max_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for FsConfig {
+    fn default() -> Self {
+        Self {
+            read: Vec::new(),
+            write: Vec::new(),
+            max_bytes: default_max_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum FsAction {
+    Read {
+        host: Option<String>,
+        user: Option<String>,
+        path: String,
+        #[serde(default)]
+        offset: u64,
+        max_bytes: Option<usize>,
+    },
+    Write {
+        host: Option<String>,
+        user: Option<String>,
+        path: String,
+        content: Option<String>,
+        base64: Option<String>,
+        #[serde(default)]
+        append: bool,
+        #[serde(default)]
+        overwrite: bool,
+    },
+}
+
+pub struct FsOperation {
+    pub tool: &'static str,
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub path: String,
+    pub script: String,
+    pub stdin: Option<Vec<u8>>,
+    mode: &'static str,
+    offset: u64,
+}
+
+pub fn validate(config: &FsConfig) -> Result<()> {
+    for prefix in config.read.iter().chain(&config.write) {
+        if let Err(error) = check_path(prefix) {
+            bail!("fs: ungültiges Verzeichnis '{}': {}", prefix, error);
+        }
+    }
+    Ok(())
+}
+
+pub fn mcp_tools(config: &FsConfig) -> Vec<Value> {
+    let mut tools = Vec::new();
+    if !config.read.is_empty() || !config.write.is_empty() {
+        tools.push(json!({
+            "name": "fs.read",
+            "description": "Reads a file on the Kali host from an allowed directory (text or base64)",
+            "inputSchema": {
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "path": {"type": "string"},
+                    "offset": {"type": "integer", "minimum": 0},
+                    "max_bytes": {"type": "integer", "minimum": 1}
+                }
+            }
+        }));
+    }
+    if !config.write.is_empty() {
+        tools.push(json!({
+            "name": "fs.write",
+            "description": "Writes a file on the Kali host into an allowed directory; content as text or base64",
+            "inputSchema": {
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "path": {"type": "string"},
+                    "content": {"type": "string"},
+                    "base64": {"type": "string"},
+                    "append": {"type": "boolean"},
+                    "overwrite": {"type": "boolean"}
+                }
+            }
+        }));
+    }
+    tools
+}
+
+pub fn prepare(config: &FsConfig, action: &str, arguments: Value) -> Result<FsOperation> {
+    let mut arguments = match arguments {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    arguments.insert("action".to_string(), Value::String(action.to_string()));
+    let tool = format!("fs.{}", action);
+    let action = serde_json::from_value::<FsAction>(Value::Object(arguments)).map_err(|error| {
+        Message::InvalidArguments {
+            tool,
+            error: error.to_string(),
+        }
+    })?;
+
+    match action {
+        FsAction::Read {
+            host,
+            user,
+            path,
+            offset,
+            max_bytes,
+        } => {
+            let limit = max_bytes.unwrap_or(config.max_bytes).min(config.max_bytes);
+            let script = format!(
+                "{readable}printf '%s\\n' \"$(($(wc -c < \"$p\")))\"; tail -c +{start} < \"$p\" | head -c {limit}",
+                readable = readable(config, "fs.read", &path)?,
+                start = offset.saturating_add(1),
+                limit = limit
+            );
+            Ok(FsOperation {
+                tool: "fs.read",
+                host,
+                user,
+                path,
+                script,
+                stdin: None,
+                mode: "read",
+                offset,
+            })
+        }
+        FsAction::Write {
+            host,
+            user,
+            path,
+            content,
+            base64,
+            append,
+            overwrite,
+        } => {
+            check_path(&path)?;
+            let roots = config.write.iter().collect::<Vec<_>>();
+            if !roots.iter().any(|root| within(&path, root)) {
+                bail!(Message::PathOutsideRoots {
+                    tool: "fs.write",
+                    path
+                });
+            }
+            let data = match (content, base64) {
+                (Some(content), None) => content.into_bytes(),
+                (None, Some(encoded)) => BASE64
+                    .decode(encoded.as_bytes())
+                    .map_err(|_| Message::InvalidEncoding { field: "base64" })?,
+                _ => bail!(Message::ExactlyOneOf {
+                    tool: "fs.write".to_string(),
+                    options: "content, base64"
+                }),
+            };
+            if data.len() > config.max_bytes {
+                bail!(Message::LimitExceeded {
+                    field: "content",
+                    value: data.len(),
+                    max: config.max_bytes
+                });
+            }
+            let (dir, name) = path.rsplit_once('/').unwrap_or_default();
+            let dir = if dir.is_empty() { "/" } else { dir };
+            if name.is_empty() {
+                bail!(Message::NotAFile {
+                    tool: "fs.write",
+                    path
+                });
+            }
+            let (mode, redirect) = match (append, overwrite) {
+                (true, _) => ("append", ">>"),
+                (false, true) => ("overwrite", ">"),
+                (false, false) => ("create", ">"),
+            };
+            let exists_check = if mode == "create" {
+                format!(
+                    "[ ! -e \"$p\" ] || {{ echo 'Datei existiert bereits (overwrite oder append setzen)' >&2; exit {}; }}; ",
+                    EXISTS_EXIT
+                )
+            } else {
+                String::new()
+            };
+            let script = format!(
+                "d=$(readlink -f -- {dir}) && [ -d \"$d\" ] || {{ echo 'Verzeichnis nicht gefunden' >&2; exit {missing}; }}; \
+                 p=\"$d\"/{name}; {guard}[ ! -L \"$p\" ] || {{ echo 'Ziel ist ein Symlink' >&2; exit {denied}; }}; {exists}\
+                 umask 077; head -c {len} {redirect} \"$p\" && printf '%s\\n' \"$(($(wc -c < \"$p\")))\"",
+                dir = shell_escape(dir),
+                name = shell_escape(name),
+                missing = MISSING_EXIT,
+                guard = guard(&roots).replace("\"$p\"", "\"$d\""),
+                denied = DENIED_EXIT,
+                exists = exists_check,
+                len = data.len(),
+                redirect = redirect
+            );
+            Ok(FsOperation {
+                tool: "fs.write",
+                host,
+                user,
+                path,
+                script,
+                stdin: Some(data),
+                mode,
+                offset: 0,
+            })
+        }
+    }
+}
+
+impl FsOperation {
+    pub fn finish(
+        &self,
+        exit_code: Option<i32>,
+        stdout: &[u8],
+        stderr: &[u8],
+        secrets: Vec<(String, String)>,
+    ) -> Result<Value> {
+        if exit_code != Some(0) {
+            let detail = String::from_utf8_lossy(stderr).trim().to_string();
+            let tool = self.tool.to_string();
+            let path = self.path.clone();
+            match exit_code {
+                Some(DENIED_EXIT) => bail!(Message::RemoteDenied { tool, path }),
+                Some(MISSING_EXIT) => bail!(Message::RemoteNotFound { tool, path }),
+                Some(EXISTS_EXIT) => bail!(Message::RemoteExists { tool, path }),
+                Some(255) => bail!(Message::RemoteSshFailed { tool, detail }),
+                _ => bail!(Message::RemoteFailed {
+                    tool,
+                    exit_code,
+                    detail
+                }),
+            }
+        }
+        let newline = stdout
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .context(Message::UnexpectedResponse)?;
+        let size = String::from_utf8_lossy(&stdout[..newline])
+            .trim()
+            .parse::<u64>()
+            .context(Message::UnexpectedResponse)?;
+        if self.mode != "read" {
+            return Ok(json!({
+                "path": self.path,
+                "mode": self.mode,
+                "bytes": self.stdin.as_ref().map_or(0, Vec::len),
+                "size": size
+            }));
+        }
+        let data = &stdout[newline + 1..];
+        let redacted = redact::redact_known(data, secrets);
+        let (encoding, content) = match std::str::from_utf8(&redacted) {
+            Ok(text) => ("utf8", text.to_string()),
+            Err(_) => ("base64", BASE64.encode(&redacted)),
+        };
+        Ok(json!({
+            "path": self.path,
+            "size": size,
+            "offset": self.offset,
+            "bytes": data.len(),
+            "truncated": self.offset + (data.len() as u64) < size,
+            "encoding": encoding,
+            "content": content
+        }))
+    }
+}
+
+pub fn readable(config: &FsConfig, tool: &'static str, path: &str) -> Result<String> {
+    check_path(path)?;
+    let roots = config.read.iter().chain(&config.write).collect::<Vec<_>>();
+    if !roots.iter().any(|root| within(path, root)) {
+        bail!(Message::PathOutsideRoots {
+            tool,
+            path: path.to_string()
+        });
+    }
+    Ok(format!(
+        "p=$(readlink -f -- {path}) && [ -f \"$p\" ] || {{ echo 'Datei nicht gefunden' >&2; exit {missing}; }}; {guard}",
+        path = shell_escape(path),
+        missing = MISSING_EXIT,
+        guard = guard(&roots)
+    ))
+}
+
+fn guard(roots: &[&String]) -> String {
+    format!(
+        "ok=; for r in {}; do r=$(readlink -f -- \"$r\") || continue; case \"$p\" in \"$r\"|\"$r\"/*) ok=1;; esac; done; \
+         [ -n \"$ok\" ] || {{ echo 'Pfad zeigt außerhalb der Freigabe' >&2; exit {}; }}; ",
+        roots
+            .iter()
+            .map(|root| shell_escape(root))
+            .collect::<Vec<_>>()
+            .join(" "),
+        DENIED_EXIT
+    )
+}
+
+fn check_path(path: &str) -> Result<()> {
+    if !path.starts_with('/') {
+        bail!(Message::PathNotAbsolute {
+            path: path.to_string()
+        });
+    }
+    if path.contains(['\0', '\n']) {
+        bail!(Message::PathInvalidChars);
+    }
+    if path
+        .trim_end_matches('/')
+        .split('/')
+        .skip(1)
+        .any(|segment| matches!(segment, "" | "." | ".."))
+    {
+        bail!(Message::PathNotNormalized {
+            path: path.to_string()
+        });
+    }
+    Ok(())
+}
+
+fn within(path: &str, root: &str) -> bool {
+    let root = root.trim_end_matches('/');
+    path == root || path.starts_with(&format!("{}/", root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(path: &str) -> FsOperation {
+        FsOperation {
+            tool: "fs.read",
+            host: None,
+            user: None,
+            path: path.to_string(),
+            script: String::new(),
+            stdin: None,
+            mode: "read",
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn read_content_is_redacted() {
+        let secrets = vec![(
+            "hunter2-secret".to_string(),
+            "{{secret:db_password}}".to_string(),
+        )];
+        let stdout = b"33\npassword=hunter2-secret\nuser=app\n";
+        let result = read("/etc/app.conf")
+            .finish(Some(0), stdout, b"", secrets)
+            .unwrap();
+        assert_eq!(
+            result["content"],
+            "password={{secret:db_password}}\nuser=app\n"
+        );
+        assert_eq!(result["bytes"], 33);
+        assert_eq!(result["encoding"], "utf8");
+    }
+}
@@ -0,0 +1,862 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use roxmltree::{Document, Node};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio_rustls::TlsConnector;
+
+use crate::findings::{Finding, Severity};
+use crate::messages::Message;
+use crate::secrets::{self, SecretsConfig};
+
+const MAX_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+const EVIDENCE_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GvmConfig {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default = "default_username")]
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub ca_file: Option<String>,
+    #[serde(default)]
+    pub insecure_tls: bool,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default = "default_scan_config_id")]
+    pub scan_config_id: String,
+    #[serde(default = "default_scanner_id")]
+    pub scanner_id: String,
+    #[serde(default = "default_port_list_id")]
+    pub port_list_id: String,
+    #[serde(default)]
+    pub credentials: BTreeMap<String, GvmCredential>,
+    #[serde(default = "default_max_results")]
+    pub max_results: usize,
+    #[serde(default = "default_min_qod")]
+    pub min_qod: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GvmCredential {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: CredentialKind,
+    #[serde(default)]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    Ssh,
+    Smb,
+    Esxi,
+    Snmp,
+}
+
+fn default_username() -> String {
+    "admin".to_string()
+}
+
+fn default_timeout_sec() -> u64 {
+    60
+}
+
+fn default_scan_config_id() -> String {
+    "daa204e8-a3d6-11e0-a2f6-406186ea4fc5".to_string()
+}
+
+fn default_scanner_id() -> String {
+    "08b69003-5fc2-4037-a479-93b440211c73".to_string()
+}
+
+fn default_port_list_id() -> String {
+    "33d0cd82-57c6-11e1-8ed1-406186ea4fc5".to_string()
+}
+
+fn default_max_results() -> usize {
+    500
+}
+
+fn default_min_qod() -> u8 {
+    70
+}
+
+impl Default for GvmConfig {
+    fn default() -> Self {
+        Self {
+            address: None,
+            username: default_username(),
+            password: None,
+            ca_file: None,
+            insecure_tls: false,
+            timeout_sec: default_timeout_sec(),
+            scan_config_id: default_scan_config_id(),
+            scanner_id: default_scanner_id(),
+            port_list_id: default_port_list_id(),
+            credentials: BTreeMap::new(),
+            max_results: default_max_results(),
+            min_qod: default_min_qod(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum GvmAction {
+    Scan {
+        targets: Vec<String>,
+        name: Option<String>,
+        #[serde(default)]
+        credentials: Vec<String>,
+        port_list_id: Option<String>,
+        scan_config_id: Option<String>,
+    },
+    Status {
+        task_id: String,
+    },
+    Results {
+        task_id: Option<String>,
+        report_id: Option<String>,
+        min_severity: Option<Severity>,
+        limit: Option<usize>,
+    },
+    Stop {
+        task_id: String,
+    },
+}
+
+pub struct GvmCall {
+    pub tool: String,
+    pub targets: Vec<String>,
+    action: GvmAction,
+}
+
+enum Endpoint {
+    #[cfg(unix)]
+    Unix(String),
+    Tls {
+        host: String,
+        port: u16,
+        connector: TlsConnector,
+    },
+}
+
+#[cfg(unix)]
+fn unix_endpoint(path: &str) -> Result<Endpoint> {
+    Ok(Endpoint::Unix(path.to_string()))
+}
+
+#[cfg(not(unix))]
+fn unix_endpoint(path: &str) -> Result<Endpoint> {
+    bail!(
+        "gvm.address unix:{} wird nur unter Unix unterstützt, tls:// verwenden",
+        path
+    )
+}
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+struct Session {
+    stream: Box<dyn Stream>,
+    buffer: Vec<u8>,
+}
+
+pub struct Gvm {
+    endpoint: Endpoint,
+    config: GvmConfig,
+}
+
+impl Gvm {
+    pub fn open(config: &GvmConfig) -> Result<Option<Self>> {
+        let Some(address) = &config.address else {
+            return Ok(None);
+        };
+        for (name, credential) in &config.credentials {
+            if !is_id(&credential.id) {
+                bail!("gvm.credentials.{}: ungültige id '{}'", name, credential.id);
+            }
+        }
+        for (field, id) in [
+            ("scan_config_id", &config.scan_config_id),
+            ("scanner_id", &config.scanner_id),
+            ("port_list_id", &config.port_list_id),
+        ] {
+            if !is_id(id) {
+                bail!("gvm.{}: ungültige id '{}'", field, id);
+            }
+        }
+        let endpoint = if let Some(path) = address.strip_prefix("unix:") {
+            unix_endpoint(path)?
+        } else if let Some(authority) = address.strip_prefix("tls://") {
+            let (host, port) = match authority.rsplit_once(':') {
+                Some((host, port)) => (
+                    host,
+                    port.parse::<u16>()
+                        .with_context(|| format!("gvm.address '{}': ungültiger Port", address))?,
+                ),
+                None => (authority, 9390),
+            };
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            if host.is_empty() {
+                bail!("gvm.address '{}' enthält keinen Host", address);
+            }
+            Endpoint::Tls {
+                host: host.to_string(),
+                port,
+                connector: connector(config)?,
+            }
+        } else {
+            bail!(
+                "gvm.address '{}' muss mit unix: oder tls:// beginnen",
+                address
+            );
+        };
+        Ok(Some(Self {
+            endpoint,
+            config: config.clone(),
+        }))
+    }
+
+    pub fn host(&self) -> &str {
+        match &self.endpoint {
+            #[cfg(unix)]
+            Endpoint::Unix(_) => "localhost",
+            Endpoint::Tls { host, .. } => host,
+        }
+    }
+
+    pub fn mcp_tools(&self) -> Vec<Value> {
+        let credentials = self.config.credentials.keys().cloned().collect::<Vec<_>>();
+        vec![
+            json!({
+                "name": "gvm.scan",
+                "description": "Creates a GVM/OpenVAS target and task for the given hosts and starts the scan; returns task_id and report_id for gvm.status/gvm.results",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["targets"],
+                    "properties": {
+                        "targets": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "IPs, CIDR networks or hostnames"},
+                        "name": {"type": "string"},
+                        "credentials": {
+                            "type": "array",
+                            "items": {"type": "string", "enum": credentials},
+                            "description": "Configured scan credentials for authenticated checks"
+                        },
+                        "port_list_id": {"type": "string"},
+                        "scan_config_id": {"type": "string"}
+                    }
+                }
+            }),
+            json!({
+                "name": "gvm.status",
+                "description": "Shows status and progress of a GVM scan task",
+                "inputSchema": {"type": "object", "required": ["task_id"], "properties": {"task_id": {"type": "string"}}}
+            }),
+            json!({
+                "name": "gvm.results",
+                "description": "Fetches results of a GVM task or report as findings, highest severity first",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "task_id": {"type": "string"},
+                        "report_id": {"type": "string"},
+                        "min_severity": {"type": "string", "enum": ["info", "low", "medium", "high", "critical"]},
+                        "limit": {"type": "integer", "minimum": 1}
+                    }
+                }
+            }),
+            json!({
+                "name": "gvm.stop",
+                "description": "Stops a running GVM scan task",
+                "inputSchema": {"type": "object", "required": ["task_id"], "properties": {"task_id": {"type": "string"}}}
+            }),
+        ]
+    }
+
+    pub fn prepare(&self, action: &str, arguments: Value) -> Result<GvmCall> {
+        let mut arguments = match arguments {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        arguments.insert("action".to_string(), Value::String(action.to_string()));
+        let tool = format!("gvm.{}", action);
+        let action =
+            serde_json::from_value::<GvmAction>(Value::Object(arguments)).map_err(|error| {
+                Message::InvalidArguments {
+                    tool,
+                    error: error.to_string(),
+                }
+            })?;
+        let tool = format!("gvm.{}", action_name(&action));
+        let targets = match &action {
+            GvmAction::Scan {
+                targets,
+                credentials,
+                port_list_id,
+                scan_config_id,
+                ..
+            } => {
+                if targets.is_empty() {
+                    bail!(Message::MissingArgument {
+                        tool: "gvm.scan".to_string(),
+                        argument: "targets"
+                    });
+                }
+                if let Some(target) = targets
+                    .iter()
+                    .find(|target| target.is_empty() || target.contains([',', ' ', '<', '>', '&']))
+                {
+                    bail!(Message::InvalidValue {
+                        field: "target",
+                        value: target.clone()
+                    });
+                }
+                for name in credentials {
+                    if !self.config.credentials.contains_key(name) {
+                        bail!(Message::NotConfigured {
+                            key: format!("gvm.credentials.{}", name)
+                        });
+                    }
+                }
+                for id in port_list_id.iter().chain(scan_config_id) {
+                    if !is_id(id) {
+                        bail!(Message::InvalidValue {
+                            field: "id",
+                            value: id.clone()
+                        });
+                    }
+                }
+                targets.clone()
+            }
+            GvmAction::Status { task_id } | GvmAction::Stop { task_id } if !is_id(task_id) => {
+                bail!(Message::InvalidValue {
+                    field: "task_id",
+                    value: task_id.clone()
+                })
+            }
+            GvmAction::Results {
+                task_id, report_id, ..
+            } => {
+                if task_id.is_some() == report_id.is_some() {
+                    bail!(Message::ExactlyOneOf {
+                        tool: "gvm.results".to_string(),
+                        options: "task_id, report_id"
+                    });
+                }
+                if let Some(id) = task_id.iter().chain(report_id).find(|id| !is_id(id)) {
+                    bail!(Message::InvalidValue {
+                        field: "id",
+                        value: id.clone()
+                    });
+                }
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+        Ok(GvmCall {
+            tool,
+            targets,
+            action,
+        })
+    }
+
+    pub async fn execute(
+        &self,
+        secrets: &SecretsConfig,
+        call: GvmCall,
+    ) -> Result<(Value, Vec<Finding>)> {
+        let limit = Duration::from_secs(self.config.timeout_sec);
+        tokio::time::timeout(limit, self.run(secrets, call.action))
+            .await
+            .map_err(|_| Message::OperationTimeout {
+                tool: call.tool,
+                timeout_sec: limit.as_secs(),
+            })?
+    }
+
+    async fn run(
+        &self,
+        secrets: &SecretsConfig,
+        action: GvmAction,
+    ) -> Result<(Value, Vec<Finding>)> {
+        let mut session = self.connect(secrets).await?;
+        match action {
+            GvmAction::Scan {
+                targets,
+                name,
+                credentials,
+                port_list_id,
+                scan_config_id,
+            } => {
+                let name = format!(
+                    "{} {}",
+                    name.as_deref().unwrap_or("ollama-kali-bridge"),
+                    SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map(|value| value.as_millis())
+                        .unwrap_or(0)
+                );
+                let mut target = format!(
+                    "<create_target><name>{}</name><hosts>{}</hosts><port_list id=\"{}\"/>",
+                    escape(&name),
+                    escape(&targets.join(",")),
+                    port_list_id.as_deref().unwrap_or(&self.config.port_list_id)
+                );
+                for credential in credentials
+                    .iter()
+                    .filter_map(|name| self.config.credentials.get(name))
+                {
+                    let tag = match credential.kind {
+                        CredentialKind::Ssh => "ssh_credential",
+                        CredentialKind::Smb => "smb_credential",
+                        CredentialKind::Esxi => "esxi_credential",
+                        CredentialKind::Snmp => "snmp_credential",
+                    };
+                    match credential.port {
+                        Some(port) => target.push_str(&format!(
+                            "<{tag} id=\"{}\"><port>{}</port></{tag}>",
+                            credential.id, port
+                        )),
+                        None => target.push_str(&format!("<{tag} id=\"{}\"/>", credential.id)),
+                    }
+                }
+                target.push_str("</create_target>");
+                let target_id = created_id(&session.command("create_target", &target).await?)?;
+                let task = format!(
+                    "<create_task><name>{}</name><config id=\"{}\"/><target id=\"{}\"/><scanner id=\"{}\"/></create_task>",
+                    escape(&name),
+                    scan_config_id
+                        .as_deref()
+                        .unwrap_or(&self.config.scan_config_id),
+                    target_id,
+                    self.config.scanner_id
+                );
+                let task_id = created_id(&session.command("create_task", &task).await?)?;
+                let started = session
+                    .command(
+                        "start_task",
+                        &format!("<start_task task_id=\"{}\"/>", task_id),
+                    )
+                    .await?;
+                let report_id = child_text(&Document::parse(&started)?.root_element(), "report_id");
+                Ok((
+                    json!({
+                        "name": name,
+                        "targets": targets,
+                        "credentials": credentials,
+                        "target_id": target_id,
+                        "task_id": task_id,
+                        "report_id": report_id
+                    }),
+                    Vec::new(),
+                ))
+            }
+            GvmAction::Status { task_id } => {
+                let response = session
+                    .command(
+                        "get_tasks",
+                        &format!("<get_tasks task_id=\"{}\"/>", task_id),
+                    )
+                    .await?;
+                let document = Document::parse(&response)?;
+                let task = document
+                    .root_element()
+                    .children()
+                    .find(|node| node.has_tag_name("task"))
+                    .ok_or_else(|| Message::GvmTaskMissing {
+                        task_id: task_id.clone(),
+                    })?;
+                let report_id = ["current_report", "last_report"].iter().find_map(|field| {
+                    task.children()
+                        .find(|node| node.has_tag_name(*field))
+                        .and_then(|node| node.children().find(|node| node.has_tag_name("report")))
+                        .and_then(|report| report.attribute("id"))
+                        .map(str::to_string)
+                });
+                let progress =
+                    child_text(&task, "progress").and_then(|value| value.parse::<i64>().ok());
+                Ok((
+                    json!({
+                        "task_id": task_id,
+                        "name": child_text(&task, "name"),
+                        "status": child_text(&task, "status"),
+                        "progress": progress,
+                        "report_id": report_id
+                    }),
+                    Vec::new(),
+                ))
+            }
+            GvmAction::Results {
+                task_id,
+                report_id,
+                min_severity,
+                limit,
+            } => {
+                let limit = limit
+                    .unwrap_or(self.config.max_results)
+                    .clamp(1, self.config.max_results);
+                let scope = match (&task_id, &report_id) {
+                    (Some(task_id), _) => format!("task_id={}", task_id),
+                    (None, Some(report_id)) => format!("report_id={}", report_id),
+                    (None, None) => bail!(Message::ExactlyOneOf {
+                        tool: "gvm.results".to_string(),
+                        options: "task_id, report_id"
+                    }),
+                };
+                let request = format!(
+                    "<get_results details=\"1\" filter=\"{} apply_overrides=1 min_qod={} sort-reverse=severity first=1 rows={}\"/>",
+                    scope,
+                    self.config.min_qod,
+                    limit + 1
+                );
+                let response = session.command("get_results", &request).await?;
+                let document = Document::parse(&response)?;
+                let mut findings = document
+                    .root_element()
+                    .children()
+                    .filter(|node| node.has_tag_name("result"))
+                    .map(|node| result_finding(&node))
+                    .collect::<Vec<_>>();
+                let truncated = findings.len() > limit;
+                findings.truncate(limit);
+                if let Some(min_severity) = min_severity {
+                    findings.retain(|finding| finding.severity >= min_severity);
+                }
+                Ok((
+                    json!({
+                        "task_id": task_id,
+                        "report_id": report_id,
+                        "count": findings.len(),
+                        "truncated": truncated
+                    }),
+                    findings,
+                ))
+            }
+            GvmAction::Stop { task_id } => {
+                session
+                    .command(
+                        "stop_task",
+                        &format!("<stop_task task_id=\"{}\"/>", task_id),
+                    )
+                    .await?;
+                Ok((json!({"task_id": task_id, "stopped": true}), Vec::new()))
+            }
+        }
+    }
+
+    async fn connect(&self, secrets: &SecretsConfig) -> Result<Session> {
+        let stream: Box<dyn Stream> = match &self.endpoint {
+            #[cfg(unix)]
+            Endpoint::Unix(path) => {
+                Box::new(UnixStream::connect(path).await.with_context(|| {
+                    Message::BackendUnreachable {
+                        service: "GVM",
+                        address: path.clone(),
+                    }
+                })?)
+            }
+            Endpoint::Tls {
+                host,
+                port,
+                connector,
+            } => {
+                let unreachable = || Message::BackendUnreachable {
+                    service: "GVM",
+                    address: format!("{}:{}", host, port),
+                };
+                let tcp = TcpStream::connect((host.as_str(), *port))
+                    .await
+                    .with_context(unreachable)?;
+                let name = ServerName::try_from(host.clone()).with_context(unreachable)?;
+                Box::new(
+                    connector
+                        .connect(name, tcp)
+                        .await
+                        .with_context(unreachable)?,
+                )
+            }
+        };
+        let password = self
+            .config
+            .password
+            .clone()
+            .or_else(|| std::env::var("GVM_PASSWORD").ok())
+            .ok_or(Message::PasswordMissing {
+                key: "gvm.password",
+                env: "GVM_PASSWORD",
+            })?;
+        let values = secrets::resolve(secrets, std::iter::once(&password)).await?;
+        let mut session = Session {
+            stream,
+            buffer: Vec::new(),
+        };
+        session
+            .command(
+                "authenticate",
+                &format!(
+                    "<authenticate><credentials><username>{}</username><password>{}</password></credentials></authenticate>",
+                    escape(&self.config.username),
+                    escape(&values.substitute(&password))
+                ),
+            )
+            .await?;
+        Ok(session)
+    }
+}
+
+impl Session {
+    async fn command(&mut self, name: &str, request: &str) -> Result<String> {
+        let failed = |detail: &str| Message::BackendFailed {
+            service: "GMP",
+            operation: name.to_string(),
+            detail: detail.to_string(),
+        };
+        self.stream
+            .write_all(request.as_bytes())
+            .await
+            .with_context(|| failed("write"))?;
+        self.stream.flush().await.with_context(|| failed("write"))?;
+        self.buffer.clear();
+        let mut chunk = [0u8; 16 * 1024];
+        let response = loop {
+            let read = self
+                .stream
+                .read(&mut chunk)
+                .await
+                .with_context(|| failed("read"))?;
+            if read == 0 {
+                bail!(Message::BackendClosed {
+                    service: "GMP",
+                    operation: name.to_string()
+                });
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+            if self.buffer.len() > MAX_RESPONSE_BYTES {
+                bail!(Message::BackendResponseTooLarge {
+                    service: "GMP",
+                    operation: name.to_string(),
+                    max: MAX_RESPONSE_BYTES
+                });
+            }
+            if !self.buffer.trim_ascii_end().ends_with(b">") {
+                continue;
+            }
+            let Ok(text) = std::str::from_utf8(&self.buffer) else {
+                continue;
+            };
+            if Document::parse(text).is_ok() {
+                break text.to_string();
+            }
+        };
+        let document = Document::parse(&response)?;
+        let root = document.root_element();
+        let status = root.attribute("status").unwrap_or_default();
+        if !status.starts_with('2') {
+            bail!(failed(&format!(
+                "{} ({})",
+                root.attribute("status_text").unwrap_or_default(),
+                status
+            )));
+        }
+        Ok(response)
+    }
+}
+
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            signature,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            signature,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn connector(config: &GvmConfig) -> Result<TlsConnector> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .context("TLS-Protokollversionen konnten nicht gesetzt werden")?;
+    let client = match (&config.ca_file, config.insecure_tls) {
+        (Some(ca_path), _) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca_path)
+                .with_context(|| format!("gvm.ca_file {} konnte nicht gelesen werden", ca_path))?
+            {
+                let cert = cert
+                    .with_context(|| format!("gvm.ca_file {} enthält ungültiges PEM", ca_path))?;
+                roots.add(cert).with_context(|| {
+                    format!("gvm.ca_file {} enthält ungültiges Zertifikat", ca_path)
+                })?;
+            }
+            if roots.is_empty() {
+                bail!("gvm.ca_file {} enthält kein Zertifikat", ca_path);
+            }
+            builder.with_root_certificates(roots).with_no_client_auth()
+        }
+        (None, true) => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+            .with_no_client_auth(),
+        (None, false) => bail!("gvm.address mit tls:// braucht gvm.ca_file oder gvm.insecure_tls"),
+    };
+    Ok(TlsConnector::from(Arc::new(client)))
+}
+
+fn action_name(action: &GvmAction) -> &'static str {
+    match action {
+        GvmAction::Scan { .. } => "scan",
+        GvmAction::Status { .. } => "status",
+        GvmAction::Results { .. } => "results",
+        GvmAction::Stop { .. } => "stop",
+    }
+}
+
+fn created_id(response: &str) -> Result<String> {
+    Document::parse(response)?
+        .root_element()
+        .attribute("id")
+        .map(str::to_string)
+        .ok_or_else(|| {
+            Message::BackendUnexpected {
+                service: "GMP",
+                operation: "create".to_string(),
+            }
+            .into()
+        })
+}
+
+fn child_text(node: &Node, tag: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.has_tag_name(tag))
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn result_finding(node: &Node) -> Finding {
+    let score = child_text(node, "severity")
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(0.0);
+    let severity = match score {
+        score if score >= 9.0 => Severity::Critical,
+        score if score >= 7.0 => Severity::High,
+        score if score >= 4.0 => Severity::Medium,
+        score if score > 0.0 => Severity::Low,
+        _ => Severity::Info,
+    };
+    let mut finding = Finding::new(
+        "gvm",
+        if score > 0.0 { "vulnerability" } else { "log" },
+        severity,
+        child_text(node, "name").unwrap_or_else(|| "unbenanntes Ergebnis".to_string()),
+    );
+    finding.host = node
+        .children()
+        .find(|child| child.has_tag_name("host"))
+        .and_then(|host| host.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    if let Some((port, protocol)) = child_text(node, "port")
+        .as_deref()
+        .and_then(|port| port.split_once('/'))
+    {
+        finding.port = port.parse().ok();
+        finding.protocol = Some(protocol.to_string());
+    }
+    let nvt = node.children().find(|child| child.has_tag_name("nvt"));
+    finding.references = nvt
+        .iter()
+        .flat_map(|nvt| nvt.descendants())
+        .filter(|child| child.has_tag_name("ref"))
+        .filter_map(|reference| reference.attribute("id"))
+        .map(str::to_string)
+        .collect();
+    finding.evidence = child_text(node, "description")
+        .map(|description| description.chars().take(EVIDENCE_CHARS).collect());
+    finding.data = json!({
+        "result_id": node.attribute("id"),
+        "oid": nvt.and_then(|nvt| nvt.attribute("oid")),
+        "score": score,
+        "threat": child_text(node, "threat"),
+        "qod": node
+            .children()
+            .find(|child| child.has_tag_name("qod"))
+            .and_then(|qod| child_text(&qod, "value"))
+            .and_then(|value| value.parse::<u64>().ok()),
+        "solution": nvt.and_then(|nvt| child_text(&nvt, "solution"))
+    });
+    finding
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn is_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 64
+        && value.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    #[serde(default = "default_circuit_failures")]
+    pub circuit_failures: u32,
+}
+
+fn default_circuit_failures() -> u32 {
+    3
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            circuit_failures: default_circuit_failures(),
+        }
+    }
+}
+
+pub fn validate(config: &HealthConfig) -> Result<()> {
+    if config.circuit_failures == 0 {
+        bail!("health.circuit_failures muss größer als 0 sein");
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct HostState {
+    consecutive_failures: u32,
+    last_success_ms: Option<u128>,
+    last_failure_ms: Option<u128>,
+}
+
+#[derive(Default)]
+pub struct Circuits {
+    hosts: Mutex<BTreeMap<String, HostState>>,
+}
+
+impl Circuits {
+    pub fn record(&self, host: &str, connect_failed: bool) {
+        let mut hosts = self.hosts.lock().expect("circuits poisoned");
+        let state = hosts.entry(host.to_string()).or_default();
+        if connect_failed {
+            state.consecutive_failures += 1;
+            state.last_failure_ms = Some(now_ms());
+        } else {
+            state.consecutive_failures = 0;
+            state.last_success_ms = Some(now_ms());
+        }
+    }
+
+    pub fn snapshot(&self, config: &HealthConfig) -> BTreeMap<String, Value> {
+        let hosts = self.hosts.lock().expect("circuits poisoned");
+        hosts
+            .iter()
+            .map(|(host, state)| {
+                let circuit = if state.consecutive_failures >= config.circuit_failures {
+                    "open"
+                } else {
+                    "closed"
+                };
+                (
+                    host.clone(),
+                    json!({
+                        "host": host,
+                        "circuit": circuit,
+                        "consecutive_failures": state.consecutive_failures,
+                        "last_success_ms": state.last_success_ms,
+                        "last_failure_ms": state.last_failure_ms
+                    }),
+                )
+            })
+            .collect()
+    }
+}
+
+pub fn summary(report: &Value) -> Value {
+    json!({
+        "status": report["status"],
+        "ready": report["ready"],
+        "reasons": report["reasons"],
+        "version": report["version"],
+        "queue_depth": report["queue"]["queue_depth"],
+        "open_circuits": report["hosts"]
+            .as_array()
+            .map_or(0, |hosts| hosts.iter().filter(|host| host["circuit"] == "open").count())
+    })
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
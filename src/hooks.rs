@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+
+use crate::{log_observation, redact};
+
+const MAX_REASON_CHARS: usize = 500;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub pre_run: Vec<HookConfig>,
+    #[serde(default)]
+    pub post_run: Vec<HookConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default)]
+    pub blocking: bool,
+}
+
+fn default_timeout_sec() -> u64 {
+    10
+}
+
+impl HookConfig {
+    fn label(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.url.as_deref())
+            .or(self.command.first().map(String::as_str))
+            .unwrap_or("hook")
+    }
+}
+
+#[derive(Debug)]
+pub struct HookRejected(pub String);
+
+impl fmt::Display for HookRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for HookRejected {}
+
+pub fn validate(config: &HooksConfig) -> Result<()> {
+    let hooks = config
+        .pre_run
+        .iter()
+        .map(|hook| ("hooks.pre_run", hook))
+        .chain(config.post_run.iter().map(|hook| ("hooks.post_run", hook)));
+    for (key, hook) in hooks {
+        match (&hook.url, hook.command.is_empty()) {
+            (Some(_), false) | (None, true) => bail!(
+                "{}: '{}' braucht genau eines von command oder url",
+                key,
+                hook.label()
+            ),
+            (Some(url), true) if !url.starts_with("http://") && !url.starts_with("https://") => {
+                bail!(
+                    "{}: ungültige URL '{}' (http:// oder https:// erwartet)",
+                    key,
+                    url
+                )
+            }
+            _ => {}
+        }
+        if hook.timeout_sec == 0 {
+            bail!(
+                "{}: timeout_sec von '{}' muss größer als 0 sein",
+                key,
+                hook.label()
+            );
+        }
+    }
+    Ok(())
+}
+
+pub struct Hooks {
+    client: reqwest::Client,
+}
+
+impl Hooks {
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!(
+                "ollama-kali-mcp-bridge/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()
+            .context("HTTP-Client für Hooks konnte nicht erstellt werden")?;
+        Ok(Self { client })
+    }
+
+    pub async fn pre_run(&self, hooks: &[HookConfig], payload: Value) -> Result<()> {
+        let body = body("pre_run", payload);
+        for hook in hooks {
+            if let Err(reason) = call(&self.client, hook, &body).await {
+                log_observation(
+                    "hook_failed",
+                    json!({"stage": "pre_run", "hook": hook.label(), "blocking": hook.blocking, "error": reason}),
+                );
+                if hook.blocking {
+                    return Err(HookRejected(format!(
+                        "Pre-Run-Hook '{}' lehnt den Run ab: {}",
+                        hook.label(),
+                        reason
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn post_run(&self, hooks: &[HookConfig], payload: Value) {
+        let body = body("post_run", payload);
+        for hook in hooks {
+            let task = {
+                let client = self.client.clone();
+                let hook = hook.clone();
+                let body = body.clone();
+                async move {
+                    if let Err(reason) = call(&client, &hook, &body).await {
+                        log_observation(
+                            "hook_failed",
+                            json!({"stage": "post_run", "hook": hook.label(), "blocking": hook.blocking, "error": reason}),
+                        );
+                    }
+                }
+            };
+            if hook.blocking {
+                task.await;
+            } else {
+                tokio::spawn(task);
+            }
+        }
+    }
+}
+
+async fn call(
+    client: &reqwest::Client,
+    hook: &HookConfig,
+    body: &Value,
+) -> std::result::Result<(), String> {
+    let timeout = Duration::from_secs(hook.timeout_sec);
+    match &hook.url {
+        Some(url) => {
+            let mut request = client.post(url).timeout(timeout).json(body);
+            for (name, value) in &hook.headers {
+                request = request.header(name, value);
+            }
+            let response = request.send().await.map_err(|error| error.to_string())?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
+            }
+            let text = response.text().await.unwrap_or_default();
+            Err(reason(&format!("HTTP {}", status.as_u16()), &text))
+        }
+        None => tokio::time::timeout(timeout, run_command(&hook.command, body))
+            .await
+            .unwrap_or_else(|_| Err(format!("Zeitüberschreitung nach {}s", hook.timeout_sec))),
+    }
+}
+
+async fn run_command(command: &[String], body: &Value) -> std::result::Result<(), String> {
+    let mut child = tokio::process::Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|error| format!("{} konnte nicht gestartet werden: {}", command[0], error))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let mut data = serde_json::to_vec(body).map_err(|error| error.to_string())?;
+        data.push(b'\n');
+        let _ = stdin.write_all(&data).await;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|error| error.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let status = match output.status.code() {
+        Some(code) => format!("Exit-Code {}", code),
+        None => "durch Signal beendet".to_string(),
+    };
+    Err(reason(&status, &String::from_utf8_lossy(&output.stderr)))
+}
+
+fn body(stage: &str, mut payload: Value) -> Value {
+    redact::redact_value(&mut payload);
+    payload["hook"] = json!(stage);
+    payload
+}
+
+fn reason(status: &str, detail: &str) -> String {
+    let detail = detail.trim();
+    if detail.is_empty() {
+        return status.to_string();
+    }
+    format!(
+        "{}: {}",
+        status,
+        detail.chars().take(MAX_REASON_CHARS).collect::<String>()
+    )
+}
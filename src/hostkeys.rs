@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::platform;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostKeysConfig {
+    #[serde(default)]
+    pub known_hosts_file: Option<String>,
+    #[serde(default)]
+    pub pins: BTreeMap<String, Vec<String>>,
+}
+
+pub struct HostKey {
+    pub key_type: String,
+    pub key: String,
+    pub fingerprint: String,
+}
+
+impl HostKeysConfig {
+    pub fn pins(&self, host: &str) -> Option<&[String]> {
+        self.pins
+            .get(host)
+            .map(Vec::as_slice)
+            .filter(|pins| !pins.is_empty())
+    }
+
+    pub fn ssh_options(&self, host: &str, strict: bool) -> Vec<String> {
+        let strict = strict || self.pins(host).is_some();
+        let mut options = vec![format!(
+            "StrictHostKeyChecking={}",
+            if strict { "yes" } else { "no" }
+        )];
+        if let Some(file) = &self.known_hosts_file {
+            options.push(format!("UserKnownHostsFile={}", file));
+            options.push(format!(
+                "GlobalKnownHostsFile={}",
+                if cfg!(windows) { "NUL" } else { "/dev/null" }
+            ));
+            options.push(format!("HostKeyAlias={}", host));
+        }
+        options
+    }
+
+    pub fn plink_args(&self, host: &str) -> Vec<String> {
+        self.pins(host)
+            .into_iter()
+            .flatten()
+            .flat_map(|pin| ["-hostkey".to_string(), pin.clone()])
+            .collect()
+    }
+}
+
+pub fn validate(config: &HostKeysConfig) -> Result<()> {
+    for (host, pins) in &config.pins {
+        if let Some(pin) = pins.iter().find(|pin| !pin.starts_with("SHA256:")) {
+            bail!(
+                "host_keys.pins.{}: '{}' ist kein SHA256-Fingerprint",
+                host,
+                pin
+            );
+        }
+    }
+    let Some(file) = config
+        .known_hosts_file
+        .as_ref()
+        .filter(|_| !config.pins.is_empty())
+    else {
+        return Ok(());
+    };
+    for (hosts, host_key) in known_keys(Path::new(file))? {
+        for host in hosts {
+            if let Some(pins) = config.pins(&host)
+                && !pins.contains(&host_key.fingerprint)
+            {
+                bail!(
+                    "{}: Schlüssel {} {} für '{}' passt nicht zu host_keys.pins",
+                    file,
+                    host_key.key_type,
+                    host_key.fingerprint,
+                    host
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn fingerprint(key: &str) -> Result<String> {
+    let blob = STANDARD
+        .decode(key)
+        .context("Host-Key ist kein gültiges Base64")?;
+    Ok(format!(
+        "SHA256:{}",
+        STANDARD_NO_PAD.encode(Sha256::digest(blob))
+    ))
+}
+
+fn parse_line(line: &str) -> Option<(Vec<String>, HostKey)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+        return None;
+    }
+    let mut fields = line.split_whitespace();
+    let hosts = fields.next()?;
+    let key_type = fields.next()?.to_string();
+    let key = fields.next()?.to_string();
+    let fingerprint = fingerprint(&key).ok()?;
+    let hosts = hosts
+        .split(',')
+        .filter(|host| !host.starts_with('|'))
+        .map(str::to_string)
+        .collect();
+    Some((
+        hosts,
+        HostKey {
+            key_type,
+            key,
+            fingerprint,
+        },
+    ))
+}
+
+fn known_keys(path: &Path) -> Result<Vec<(Vec<String>, HostKey)>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content.lines().filter_map(parse_line).collect()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(error) => {
+            Err(error).with_context(|| format!("{} konnte nicht gelesen werden", path.display()))
+        }
+    }
+}
+
+pub async fn scan(address: &str, port: Option<u16>, timeout: Duration) -> Result<Vec<HostKey>> {
+    let mut command = tokio::process::Command::new("ssh-keyscan");
+    command.arg("-T").arg(timeout.as_secs().max(1).to_string());
+    if let Some(port) = port {
+        command.arg("-p").arg(port.to_string());
+    }
+    let output = command
+        .arg("--")
+        .arg(address)
+        .output()
+        .await
+        .context("ssh-keyscan konnte nicht gestartet werden")?;
+    let keys = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_line)
+        .map(|(_, key)| key)
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        bail!(
+            "ssh-keyscan lieferte keine Schlüssel für {}: {}",
+            address,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(keys)
+}
+
+pub fn store(path: &Path, host: &str, keys: &[HostKey]) -> Result<()> {
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) => {
+            return Err(error)
+                .with_context(|| format!("{} konnte nicht gelesen werden", path.display()));
+        }
+    };
+    let mut lines = existing
+        .lines()
+        .filter(|line| {
+            parse_line(line).is_none_or(|(hosts, _)| !hosts.iter().any(|entry| entry == host))
+        })
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    lines.extend(
+        keys.iter()
+            .map(|key| format!("{} {} {}", host, key.key_type, key.key)),
+    );
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("{} konnte nicht angelegt werden", parent.display()))?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, lines.join("\n") + "\n")
+        .with_context(|| format!("{} konnte nicht geschrieben werden", tmp.display()))?;
+    platform::set_mode(&tmp, 0o600)?;
+    fs::rename(&tmp, path)
+        .with_context(|| format!("{} konnte nicht ersetzt werden", path.display()))
+}
@@ -0,0 +1,587 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use crate::log_observation;
+use crate::messages::Message;
+use crate::rest::RestConfig;
+use crate::secrets::random_bytes;
+use crate::session::SessionsConfig;
+use crate::websocket;
+
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    #[serde(default)]
+    pub listen: Option<String>,
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    #[serde(default)]
+    pub tls_cert: Option<String>,
+    #[serde(default)]
+    pub tls_key: Option<String>,
+    #[serde(default)]
+    pub tls_client_ca: Option<String>,
+    #[serde(default)]
+    pub tls_client_cert_required: bool,
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    #[serde(default)]
+    pub rest: RestConfig,
+}
+
+fn default_max_body_bytes() -> usize {
+    1024 * 1024
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            listen: None,
+            tokens: Vec::new(),
+            max_body_bytes: default_max_body_bytes(),
+            tls_cert: None,
+            tls_key: None,
+            tls_client_ca: None,
+            tls_client_cert_required: false,
+            sessions: SessionsConfig::default(),
+            rest: RestConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenConfig {
+    pub identity: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub token_sha256: Option<String>,
+    #[serde(default)]
+    pub client_cert_sha256: Option<String>,
+}
+
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+    pub identity: String,
+    pub session: Option<String>,
+    pub websocket: bool,
+}
+
+pub enum HttpResponse {
+    Json(u16, Value),
+    Session(String, Value),
+    Stream(
+        mpsc::UnboundedReceiver<Vec<u8>>,
+        Pin<Box<dyn Future<Output = Result<()>> + Send>>,
+    ),
+    Background(u16, Value, Pin<Box<dyn Future<Output = Result<()>> + Send>>),
+    WebSocket(
+        mpsc::UnboundedSender<String>,
+        mpsc::UnboundedReceiver<Vec<u8>>,
+        Pin<Box<dyn Future<Output = Result<()>> + Send>>,
+    ),
+    Accepted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Credential {
+    Token,
+    ClientCert,
+}
+
+pub struct Auth {
+    tokens: RwLock<Vec<(String, Credential, [u8; 32])>>,
+}
+
+impl Auth {
+    pub fn new(tokens: &[TokenConfig]) -> Result<Self> {
+        Ok(Self {
+            tokens: RwLock::new(digest_tokens(tokens)?),
+        })
+    }
+
+    pub fn reload(&self, tokens: &[TokenConfig]) -> Result<usize> {
+        let digests = digest_tokens(tokens)?;
+        let count = digests.len();
+        *self.tokens.write().expect("auth tokens poisoned") = digests;
+        Ok(count)
+    }
+
+    pub fn authenticate(
+        &self,
+        authorization: Option<&str>,
+        client_cert: Option<[u8; 32]>,
+    ) -> Option<String> {
+        let bearer = authorization
+            .and_then(|value| value.split_once(' '))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+            .map(|(_, presented)| <[u8; 32]>::from(Sha256::digest(presented.trim().as_bytes())));
+        self.find(Credential::Token, bearer)
+            .or_else(|| self.find(Credential::ClientCert, client_cert))
+    }
+
+    fn find(&self, kind: Credential, presented: Option<[u8; 32]>) -> Option<String> {
+        let presented = presented?;
+        let tokens = self.tokens.read().expect("auth tokens poisoned");
+        let mut matched = None;
+        for (identity, credential, digest) in tokens.iter() {
+            if *credential == kind && constant_time_eq(&presented, digest) && matched.is_none() {
+                matched = Some(identity.clone());
+            }
+        }
+        matched
+    }
+}
+
+fn digest_tokens(tokens: &[TokenConfig]) -> Result<Vec<(String, Credential, [u8; 32])>> {
+    if tokens.is_empty() {
+        bail!("http.tokens ist leer, ohne Tokens wird kein Netzwerk-Listener gestartet");
+    }
+    tokens
+        .iter()
+        .map(|token| {
+            let (credential, digest) = match (&token.token, &token.token_sha256, &token.client_cert_sha256) {
+                (Some(plain), None, None) => (Credential::Token, Sha256::digest(plain.as_bytes()).into()),
+                (None, Some(hex), None) => (
+                    Credential::Token,
+                    decode_hex_digest(hex)
+                        .with_context(|| format!("ungültiges token_sha256 für Identität '{}'", token.identity))?,
+                ),
+                (None, None, Some(hex)) => (
+                    Credential::ClientCert,
+                    decode_hex_digest(&hex.replace(':', "")).with_context(|| {
+                        format!("ungültiges client_cert_sha256 für Identität '{}'", token.identity)
+                    })?,
+                ),
+                _ => bail!(
+                    "Identität '{}': genau eines von token, token_sha256 oder client_cert_sha256 angeben",
+                    token.identity
+                ),
+            };
+            Ok((token.identity.clone(), credential, digest))
+        })
+        .collect()
+}
+
+fn decode_hex_digest(hex: &str) -> Result<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        bail!("64 Hex-Zeichen erwartet");
+    }
+    let mut digest = [0_u8; 32];
+    for (index, byte) in digest.iter_mut().enumerate() {
+        *byte =
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).context("kein gültiges Hex")?;
+    }
+    Ok(digest)
+}
+
+fn constant_time_eq(left: &[u8; 32], right: &[u8; 32]) -> bool {
+    left.iter()
+        .zip(right)
+        .fold(0_u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+pub fn generate_token(identity: &str) -> Result<Value> {
+    let token = random_bytes::<32>()?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    Ok(json!({
+        "identity": identity,
+        "token": token,
+        "token_sha256": format!("{:x}", Sha256::digest(token.as_bytes()))
+    }))
+}
+
+pub fn is_probe(method: &str, path: &str) -> bool {
+    method == "GET" && matches!(path, "/healthz" | "/readyz")
+}
+
+pub async fn bind(addr: &str) -> Result<TcpListener> {
+    TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("HTTP-Listener konnte nicht an {} gebunden werden", addr))
+}
+
+pub async fn handle_connection<S, F, Fut>(
+    mut stream: S,
+    peer: SocketAddr,
+    client_cert: Option<[u8; 32]>,
+    auth: &Auth,
+    max_body_bytes: usize,
+    handler: &F,
+) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Fn(HttpRequest) -> Fut,
+    Fut: Future<Output = HttpResponse>,
+{
+    let parsed = tokio::time::timeout(
+        REQUEST_READ_TIMEOUT,
+        read_request(&mut stream, max_body_bytes),
+    )
+    .await
+    .map_err(|_| Message::HttpReadTimeout.into())
+    .and_then(|parsed| parsed);
+    let ParsedRequest {
+        method,
+        path,
+        authorization,
+        session,
+        body,
+        upgrade,
+    } = match parsed {
+        Ok(Some(parsed)) => parsed,
+        Ok(None) => {
+            let error = Message::HttpBodyTooLarge {
+                max: max_body_bytes,
+            };
+            return write_json(&mut stream, 413, &error_body(&error), &[]).await;
+        }
+        Err(error) => {
+            return write_json(
+                &mut stream,
+                400,
+                &failure_body(&error, "E_BAD_REQUEST"),
+                &[],
+            )
+            .await;
+        }
+    };
+
+    let authorization = authorization.or_else(|| {
+        let protocols = upgrade.as_ref()?.protocols.as_deref()?;
+        websocket::offered_token(protocols).map(|token| format!("Bearer {}", token))
+    });
+    let identity = match auth.authenticate(authorization.as_deref(), client_cert) {
+        Some(identity) => identity,
+        None if is_probe(&method, &path) => String::new(),
+        None => {
+            log_observation(
+                "auth_failed",
+                json!({
+                    "peer": peer.to_string(),
+                    "path": path,
+                    "token_presented": authorization.is_some(),
+                    "client_cert_presented": client_cert.is_some()
+                }),
+            );
+            return write_json(
+                &mut stream,
+                401,
+                &error_body(&Message::AuthRequired),
+                &[("WWW-Authenticate", "Bearer")],
+            )
+            .await;
+        }
+    };
+
+    let response = handler(HttpRequest {
+        method,
+        path,
+        body,
+        identity,
+        session,
+        websocket: upgrade.is_some(),
+    })
+    .await;
+    match response {
+        HttpResponse::Json(status, value) => write_json(&mut stream, status, &value, &[]).await,
+        HttpResponse::Session(session, value) => {
+            write_json(
+                &mut stream,
+                200,
+                &value,
+                &[("Mcp-Session-Id", session.as_str())],
+            )
+            .await
+        }
+        HttpResponse::Accepted => {
+            stream
+                .write_all(
+                    b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+                .await?;
+            stream.shutdown().await?;
+            Ok(())
+        }
+        HttpResponse::Background(status, value, task) => {
+            if write_json(&mut stream, status, &value, &[]).await.is_err() {
+                log_observation(
+                    "http_client_disconnected",
+                    json!({"peer": peer.to_string()}),
+                );
+            }
+            task.await
+        }
+        HttpResponse::WebSocket(incoming, outgoing, task) => {
+            let Some(upgrade) = upgrade else {
+                return write_json(
+                    &mut stream,
+                    426,
+                    &error_body(&Message::WebSocketExpected),
+                    &[("Upgrade", "websocket")],
+                )
+                .await;
+            };
+            let key = match (
+                websocket::check_upgrade(upgrade.version.as_deref()),
+                upgrade.key,
+            ) {
+                (Ok(()), Some(key)) => key,
+                (Err(error), _) => {
+                    return write_json(
+                        &mut stream,
+                        426,
+                        &failure_body(&error, "E_WEBSOCKET"),
+                        &[("Sec-WebSocket-Version", "13")],
+                    )
+                    .await;
+                }
+                (Ok(()), None) => {
+                    return write_json(
+                        &mut stream,
+                        400,
+                        &error_body(&Message::WebSocketKeyMissing),
+                        &[],
+                    )
+                    .await;
+                }
+            };
+            let mut response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n",
+                websocket::accept_key(&key)
+            );
+            let offered = upgrade.protocols.as_deref().unwrap_or_default();
+            if offered
+                .split(',')
+                .any(|protocol| protocol.trim() == websocket::PROTOCOL)
+            {
+                response.push_str(&format!(
+                    "Sec-WebSocket-Protocol: {}\r\n",
+                    websocket::PROTOCOL
+                ));
+            }
+            response.push_str("\r\n");
+            stream.write_all(response.as_bytes()).await?;
+            log_observation("websocket_opened", json!({"peer": peer.to_string()}));
+            let (served, ran) = tokio::join!(
+                websocket::serve(stream, incoming, outgoing, max_body_bytes),
+                task
+            );
+            log_observation(
+                "websocket_closed",
+                json!({"peer": peer.to_string(), "error": served.err().map(|error| format!("{:#}", error))}),
+            );
+            ran
+        }
+        HttpResponse::Stream(rx, task) => {
+            let (streamed, ran) = tokio::join!(forward_chunks(&mut stream, rx), task);
+            if streamed.is_err() {
+                log_observation(
+                    "http_client_disconnected",
+                    json!({"peer": peer.to_string()}),
+                );
+            }
+            ran
+        }
+    }
+}
+
+async fn forward_chunks<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    mut rx: mpsc::UnboundedReceiver<Vec<u8>>,
+) -> Result<()> {
+    let mut result = stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nCache-Control: no-cache\r\n\
+              Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+    while let Some(chunk) = rx.recv().await {
+        if chunk.is_empty() || result.is_err() {
+            continue;
+        }
+        result = async {
+            stream
+                .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                .await?;
+            stream.write_all(&chunk).await?;
+            stream.write_all(b"\r\n").await?;
+            stream.flush().await
+        }
+        .await;
+    }
+    result?;
+    stream.write_all(b"0\r\n\r\n").await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+struct ParsedRequest {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    session: Option<String>,
+    body: Vec<u8>,
+    upgrade: Option<Upgrade>,
+}
+
+#[derive(Default)]
+struct Upgrade {
+    key: Option<String>,
+    version: Option<String>,
+    protocols: Option<String>,
+}
+
+async fn read_request<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    max_body_bytes: usize,
+) -> Result<Option<ParsedRequest>> {
+    let mut data = Vec::new();
+    let mut buffer = [0_u8; 4096];
+    let header_end = loop {
+        if let Some(position) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if data.len() > MAX_HEADER_BYTES {
+            bail!(Message::HttpHeaderTooLarge {
+                max: MAX_HEADER_BYTES
+            });
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            bail!(Message::HttpIncomplete);
+        }
+        data.extend_from_slice(&buffer[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line
+        .next()
+        .ok_or(Message::HttpMalformed { part: "method" })?
+        .to_string();
+    let path = request_line
+        .next()
+        .ok_or(Message::HttpMalformed { part: "path" })?
+        .to_string();
+    let mut authorization = None;
+    let mut session = None;
+    let mut content_length = 0;
+    let mut websocket = false;
+    let mut upgrade = Upgrade::default();
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "authorization" => authorization = Some(value.to_string()),
+            "mcp-session-id" => session = Some(value.to_string()),
+            "content-length" => {
+                content_length = value.parse::<usize>().map_err(|_| Message::HttpMalformed {
+                    part: "Content-Length",
+                })?
+            }
+            "transfer-encoding" => bail!(Message::HttpChunked),
+            "upgrade" => websocket = value.eq_ignore_ascii_case("websocket"),
+            "sec-websocket-key" => upgrade.key = Some(value.to_string()),
+            "sec-websocket-version" => upgrade.version = Some(value.to_string()),
+            "sec-websocket-protocol" => upgrade.protocols = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if content_length > max_body_bytes {
+        return Ok(None);
+    }
+
+    let mut body = data[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            bail!(Message::HttpIncomplete);
+        }
+        body.extend_from_slice(&buffer[..read]);
+    }
+    body.truncate(content_length);
+    Ok(Some(ParsedRequest {
+        method,
+        path,
+        authorization,
+        session,
+        body,
+        upgrade: websocket.then_some(upgrade),
+    }))
+}
+
+pub fn error_body(message: &Message) -> Value {
+    json!({"error": message.to_string(), "code": message.code()})
+}
+
+pub fn failure_body(error: &anyhow::Error, fallback: &'static str) -> Value {
+    let code = error
+        .downcast_ref::<Message>()
+        .map_or(fallback, Message::code);
+    json!({"error": format!("{:#}", error), "code": code})
+}
+
+async fn write_json<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    status: u16,
+    value: &Value,
+    headers: &[(&str, &str)],
+) -> Result<()> {
+    let body = format!("{}\n", value);
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reason(status),
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        426 => "Upgrade Required",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
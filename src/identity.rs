@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityConfig {
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    #[serde(default)]
+    pub identities_only: bool,
+    #[serde(default = "default_use_agent")]
+    pub use_agent: bool,
+    #[serde(default)]
+    pub hosts: BTreeMap<String, HostIdentity>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HostIdentity {
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    #[serde(default)]
+    pub identities_only: Option<bool>,
+    #[serde(default)]
+    pub use_agent: Option<bool>,
+}
+
+fn default_use_agent() -> bool {
+    true
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        Self {
+            identity_file: None,
+            identities_only: false,
+            use_agent: default_use_agent(),
+            hosts: BTreeMap::new(),
+        }
+    }
+}
+
+impl IdentityConfig {
+    fn resolve(&self, host: &str) -> (Option<&String>, bool, bool) {
+        let host = self.hosts.get(host);
+        (
+            host.and_then(|host| host.identity_file.as_ref())
+                .or(self.identity_file.as_ref()),
+            host.and_then(|host| host.identities_only)
+                .unwrap_or(self.identities_only),
+            host.and_then(|host| host.use_agent)
+                .unwrap_or(self.use_agent),
+        )
+    }
+
+    pub fn ssh_args(&self, host: &str) -> Vec<String> {
+        let (identity_file, identities_only, use_agent) = self.resolve(host);
+        let mut args = Vec::new();
+        if let Some(file) = identity_file {
+            args.push("-i".to_string());
+            args.push(expand_home(file).display().to_string());
+        }
+        if identities_only {
+            args.push("-o".to_string());
+            args.push("IdentitiesOnly=yes".to_string());
+        }
+        if !use_agent {
+            args.push("-o".to_string());
+            args.push("IdentityAgent=none".to_string());
+        }
+        args
+    }
+
+    pub fn plink_args(&self, host: &str) -> Vec<String> {
+        let (identity_file, _, use_agent) = self.resolve(host);
+        let mut args = Vec::new();
+        if let Some(file) = identity_file {
+            args.push("-i".to_string());
+            args.push(expand_home(file).display().to_string());
+        }
+        if !use_agent {
+            args.push("-noagent".to_string());
+        }
+        args
+    }
+}
+
+pub fn validate(config: &IdentityConfig) -> Result<()> {
+    let files = config
+        .identity_file
+        .iter()
+        .map(|file| ("ssh_identity.identity_file".to_string(), file))
+        .chain(config.hosts.iter().filter_map(|(host, identity)| {
+            identity
+                .identity_file
+                .as_ref()
+                .map(|file| (format!("ssh_identity.hosts.{}.identity_file", host), file))
+        }));
+    for (key, file) in files {
+        if !expand_home(file).is_file() {
+            bail!("{}: Schlüsseldatei '{}' existiert nicht", key, file);
+        }
+    }
+    Ok(())
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (
+        path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")),
+        home,
+    ) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
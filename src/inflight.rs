@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, bail};
+use tokio::sync::watch;
+
+use crate::messages::Message;
+
+type Shared<V> = Option<Result<V, Failure>>;
+
+#[derive(Clone)]
+enum Failure {
+    Message(Message),
+    Text(String),
+}
+
+struct Inner<K, V> {
+    runs: Mutex<HashMap<K, watch::Receiver<Shared<V>>>>,
+}
+
+#[derive(Clone)]
+pub struct InFlight<K, V> {
+    inner: Arc<Inner<K, V>>,
+}
+
+pub enum Joined<K: Hash + Eq, V> {
+    Leader(LeaderGuard<K, V>),
+    Follower(Follower<V>),
+}
+
+pub struct LeaderGuard<K: Hash + Eq, V> {
+    inner: Arc<Inner<K, V>>,
+    key: K,
+    tx: watch::Sender<Shared<V>>,
+}
+
+pub struct Follower<V> {
+    rx: watch::Receiver<Shared<V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> InFlight<K, V> {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                runs: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn join(&self, key: &K) -> Joined<K, V> {
+        let mut runs = self.inner.runs.lock().expect("in-flight state poisoned");
+        if let Some(rx) = runs.get(key) {
+            return Joined::Follower(Follower { rx: rx.clone() });
+        }
+        let (tx, rx) = watch::channel(None);
+        runs.insert(key.clone(), rx);
+        Joined::Leader(LeaderGuard {
+            inner: self.inner.clone(),
+            key: key.clone(),
+            tx,
+        })
+    }
+}
+
+impl<K: Hash + Eq, V> LeaderGuard<K, V> {
+    pub fn complete(self, result: Result<V, &anyhow::Error>) {
+        let result = result.map_err(|error| match error.downcast_ref::<Message>() {
+            Some(message) => Failure::Message(message.clone()),
+            None => Failure::Text(error.to_string()),
+        });
+        self.tx.send_replace(Some(result));
+    }
+}
+
+impl<K: Hash + Eq, V> Drop for LeaderGuard<K, V> {
+    fn drop(&mut self) {
+        self.inner
+            .runs
+            .lock()
+            .expect("in-flight state poisoned")
+            .remove(&self.key);
+    }
+}
+
+impl<V: Clone> Follower<V> {
+    pub async fn wait(mut self) -> Result<V> {
+        let shared = match self.rx.wait_for(Option::is_some).await {
+            Ok(shared) => shared.clone(),
+            Err(_) => bail!(Message::InFlightAborted),
+        };
+        match shared {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(Failure::Message(message))) => bail!(message),
+            Some(Err(Failure::Text(text))) => bail!(text),
+            None => bail!(Message::InFlightAborted),
+        }
+    }
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin};
+use tokio::sync::{Notify, oneshot};
+
+use crate::audit::AuditLog;
+use crate::messages::Message;
+use crate::sanitize::OutputFilter;
+use crate::{log_observation, redact, session};
+
+const TICK: Duration = Duration::from_secs(1);
+const READER_DRAIN: Duration = Duration::from_secs(2);
+const CLOSE_WAIT: Duration = Duration::from_secs(5);
+const MAX_WAIT_MS: u64 = 30_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InteractiveConfig {
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout_sec: u64,
+    #[serde(default = "default_max_duration")]
+    pub max_duration_sec: u64,
+    #[serde(default = "default_max_buffer")]
+    pub max_buffer_bytes: usize,
+    #[serde(default = "default_max_input")]
+    pub max_input_bytes: usize,
+}
+
+fn default_max_sessions() -> usize {
+    4
+}
+
+fn default_idle_timeout() -> u64 {
+    600
+}
+
+fn default_max_duration() -> u64 {
+    3600
+}
+
+fn default_max_buffer() -> usize {
+    256 * 1024
+}
+
+fn default_max_input() -> usize {
+    4096
+}
+
+impl Default for InteractiveConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: default_max_sessions(),
+            idle_timeout_sec: default_idle_timeout(),
+            max_duration_sec: default_max_duration(),
+            max_buffer_bytes: default_max_buffer(),
+            max_input_bytes: default_max_input(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenRequest {
+    pub tool: String,
+    pub host: Option<String>,
+    pub user: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub engagement: Option<String>,
+    pub strip_ansi: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum TerminalAction {
+    Send {
+        id: String,
+        input: String,
+        #[serde(default = "default_newline")]
+        newline: bool,
+    },
+    Read {
+        id: String,
+        #[serde(default)]
+        wait_ms: u64,
+        max_bytes: Option<usize>,
+    },
+    Close {
+        id: String,
+    },
+}
+
+fn default_newline() -> bool {
+    true
+}
+
+pub fn is_action(action: &str) -> bool {
+    matches!(action, "open" | "send" | "read" | "close")
+}
+
+struct Exit {
+    code: Option<i32>,
+    reason: &'static str,
+}
+
+struct TerminalState {
+    buffer: Vec<u8>,
+    dropped: u64,
+    bytes_in: u64,
+    bytes_out: u64,
+    last_used: Instant,
+    exit: Option<Exit>,
+    filter: OutputFilter,
+}
+
+struct Terminal {
+    id: String,
+    client: Option<String>,
+    tool: String,
+    target: String,
+    started: Instant,
+    input: tokio::sync::Mutex<ChildStdin>,
+    state: Mutex<TerminalState>,
+    changed: Notify,
+    close: Mutex<Option<oneshot::Sender<&'static str>>>,
+}
+
+impl Terminal {
+    fn append(&self, data: &[u8], max_buffer_bytes: usize) {
+        let mut state = self.state.lock().expect("terminal poisoned");
+        state.bytes_out += data.len() as u64;
+        state.buffer.extend_from_slice(data);
+        let excess = state.buffer.len().saturating_sub(max_buffer_bytes);
+        if excess > 0 {
+            state.buffer.drain(..excess);
+            state.dropped += excess as u64;
+        }
+        drop(state);
+        self.changed.notify_waiters();
+    }
+
+    fn summary(&self, state: &TerminalState) -> Value {
+        json!({
+            "id": self.id,
+            "tool": self.tool,
+            "target": self.target,
+            "running": state.exit.is_none(),
+            "exit_code": state.exit.as_ref().and_then(|exit| exit.code),
+            "reason": state.exit.as_ref().map(|exit| exit.reason),
+            "bytes_in": state.bytes_in,
+            "bytes_out": state.bytes_out,
+            "duration_ms": self.started.elapsed().as_millis()
+        })
+    }
+}
+
+pub struct Terminals {
+    config: InteractiveConfig,
+    audit: Option<Arc<AuditLog>>,
+    terminals: Mutex<HashMap<String, Arc<Terminal>>>,
+}
+
+impl Terminals {
+    pub fn new(config: &InteractiveConfig, audit: Option<Arc<AuditLog>>) -> Self {
+        Self {
+            config: config.clone(),
+            audit,
+            terminals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn open(
+        &self,
+        client: Option<&str>,
+        tool: &str,
+        target: &str,
+        mut child: Child,
+        filter: OutputFilter,
+    ) -> Result<Value> {
+        let id = {
+            let mut terminals = self.terminals.lock().expect("terminals poisoned");
+            self.prune(&mut terminals);
+            if terminals.len() >= self.config.max_sessions {
+                bail!(Message::TerminalLimit {
+                    max: self.config.max_sessions
+                });
+            }
+            let id = session::session_id()?;
+            let stdin = child
+                .stdin
+                .take()
+                .context(Message::PipeMissing { stream: "stdin" })?;
+            let (close, close_requested) = oneshot::channel();
+            let terminal = Arc::new(Terminal {
+                id: id.clone(),
+                client: client.map(str::to_string),
+                tool: tool.to_string(),
+                target: target.to_string(),
+                started: Instant::now(),
+                input: tokio::sync::Mutex::new(stdin),
+                state: Mutex::new(TerminalState {
+                    buffer: Vec::new(),
+                    dropped: 0,
+                    bytes_in: 0,
+                    bytes_out: 0,
+                    last_used: Instant::now(),
+                    exit: None,
+                    filter,
+                }),
+                changed: Notify::new(),
+                close: Mutex::new(Some(close)),
+            });
+            terminals.insert(id.clone(), terminal.clone());
+            tokio::spawn(supervise(
+                terminal,
+                child,
+                close_requested,
+                self.config.clone(),
+                self.audit.clone(),
+            ));
+            id
+        };
+        let entry = json!({"id": id, "client": client, "tool": tool, "target": target});
+        self.record("interactive_opened", entry.clone())?;
+        log_observation("interactive_opened", entry);
+        Ok(json!({
+            "id": id,
+            "tool": tool,
+            "target": target,
+            "idle_timeout_sec": self.config.idle_timeout_sec,
+            "max_duration_sec": self.config.max_duration_sec
+        }))
+    }
+
+    pub async fn mcp_call(
+        &self,
+        client: Option<&str>,
+        action: &str,
+        arguments: Value,
+    ) -> Result<Value> {
+        let mut arguments = match arguments {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        arguments.insert("action".to_string(), Value::String(action.to_string()));
+        let action = serde_json::from_value::<TerminalAction>(Value::Object(arguments)).map_err(
+            |error| Message::InvalidArguments {
+                tool: format!("session.{}", action),
+                error: error.to_string(),
+            },
+        )?;
+
+        match action {
+            TerminalAction::Send { id, input, newline } => {
+                let terminal = self.get(&id, client)?;
+                let mut data = input.into_bytes();
+                if newline {
+                    data.push(b'\n');
+                }
+                if data.len() > self.config.max_input_bytes {
+                    bail!(Message::LimitExceeded {
+                        field: "input",
+                        value: data.len(),
+                        max: self.config.max_input_bytes
+                    });
+                }
+                if terminal
+                    .state
+                    .lock()
+                    .expect("terminal poisoned")
+                    .exit
+                    .is_some()
+                {
+                    bail!(Message::TerminalClosed { id });
+                }
+                terminal
+                    .input
+                    .lock()
+                    .await
+                    .write_all(&data)
+                    .await
+                    .context(Message::TerminalInputFailed)?;
+                let mut state = terminal.state.lock().expect("terminal poisoned");
+                state.bytes_in += data.len() as u64;
+                state.last_used = Instant::now();
+                drop(state);
+                self.record(
+                    "interactive_input",
+                    json!({
+                        "id": id,
+                        "client": client,
+                        "tool": terminal.tool,
+                        "target": terminal.target,
+                        "input": String::from_utf8_lossy(&data)
+                    }),
+                )?;
+                Ok(json!({"id": id, "bytes": data.len()}))
+            }
+            TerminalAction::Read {
+                id,
+                wait_ms,
+                max_bytes,
+            } => {
+                let terminal = self.get(&id, client)?;
+                let wait = Duration::from_millis(wait_ms.min(MAX_WAIT_MS));
+                let changed = terminal.changed.notified();
+                tokio::pin!(changed);
+                changed.as_mut().enable();
+                let idle = {
+                    let state = terminal.state.lock().expect("terminal poisoned");
+                    state.buffer.is_empty() && state.exit.is_none()
+                };
+                if idle && !wait.is_zero() {
+                    let _ = tokio::time::timeout(wait, changed).await;
+                }
+
+                let mut state = terminal.state.lock().expect("terminal poisoned");
+                state.last_used = Instant::now();
+                let limit = max_bytes
+                    .unwrap_or(self.config.max_buffer_bytes)
+                    .min(state.buffer.len());
+                let mut data = state.buffer.drain(..limit).collect::<Vec<_>>();
+                if let Err(error) = std::str::from_utf8(&data)
+                    && error.error_len().is_none()
+                    && state.exit.is_none()
+                {
+                    let tail = data.split_off(error.valid_up_to());
+                    state.buffer.splice(..0, tail);
+                }
+                let mut output = state.filter.redact(true, &data);
+                output.extend(state.filter.flush_redacted(true));
+                if state.filter.is_active() {
+                    output = state.filter.apply(true, &output);
+                }
+                let dropped = std::mem::take(&mut state.dropped);
+                let mut result = terminal.summary(&state);
+                result["output"] = json!(String::from_utf8_lossy(&output));
+                result["bytes"] = json!(data.len());
+                result["pending"] = json!(state.buffer.len());
+                result["dropped"] = json!(dropped);
+                Ok(result)
+            }
+            TerminalAction::Close { id } => {
+                let terminal = self.get(&id, client)?;
+                let changed = terminal.changed.notified();
+                tokio::pin!(changed);
+                changed.as_mut().enable();
+                let close = terminal.close.lock().expect("terminal poisoned").take();
+                if let Some(close) = close {
+                    let _ = close.send("closed");
+                    let _ = tokio::time::timeout(CLOSE_WAIT, async {
+                        while terminal
+                            .state
+                            .lock()
+                            .expect("terminal poisoned")
+                            .exit
+                            .is_none()
+                        {
+                            changed.as_mut().await;
+                            changed.set(terminal.changed.notified());
+                            changed.as_mut().enable();
+                        }
+                    })
+                    .await;
+                }
+                self.terminals
+                    .lock()
+                    .expect("terminals poisoned")
+                    .remove(&id);
+                let state = terminal.state.lock().expect("terminal poisoned");
+                Ok(terminal.summary(&state))
+            }
+        }
+    }
+
+    pub fn close_all(&self, reason: &'static str) -> usize {
+        let terminals = self.terminals.lock().expect("terminals poisoned");
+        terminals
+            .values()
+            .filter_map(|terminal| terminal.close.lock().expect("terminal poisoned").take())
+            .map(|close| close.send(reason))
+            .filter(Result::is_ok)
+            .count()
+    }
+
+    fn get(&self, id: &str, client: Option<&str>) -> Result<Arc<Terminal>> {
+        let mut terminals = self.terminals.lock().expect("terminals poisoned");
+        self.prune(&mut terminals);
+        match terminals.get(id) {
+            Some(terminal) if terminal.client.as_deref() == client => Ok(terminal.clone()),
+            _ => bail!(Message::TerminalUnknown { id: id.to_string() }),
+        }
+    }
+
+    fn prune(&self, terminals: &mut HashMap<String, Arc<Terminal>>) {
+        let idle = Duration::from_secs(self.config.idle_timeout_sec);
+        terminals.retain(|_, terminal| {
+            let state = terminal.state.lock().expect("terminal poisoned");
+            state.exit.is_none() || state.last_used.elapsed() < idle
+        });
+    }
+
+    fn record(&self, kind: &str, entry: Value) -> Result<()> {
+        record(self.audit.as_deref(), kind, entry)
+    }
+}
+
+fn record(audit: Option<&AuditLog>, kind: &str, mut entry: Value) -> Result<()> {
+    match audit {
+        Some(log) => {
+            redact::redact_value(&mut entry);
+            log.append(kind, entry)
+        }
+        None => Ok(()),
+    }
+}
+
+async fn supervise(
+    terminal: Arc<Terminal>,
+    mut child: Child,
+    mut close_requested: oneshot::Receiver<&'static str>,
+    config: InteractiveConfig,
+    audit: Option<Arc<AuditLog>>,
+) {
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(tokio::spawn(pump(
+            terminal.clone(),
+            stdout,
+            config.max_buffer_bytes,
+        )));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(tokio::spawn(pump(
+            terminal.clone(),
+            stderr,
+            config.max_buffer_bytes,
+        )));
+    }
+
+    let max_duration = Duration::from_secs(config.max_duration_sec);
+    let idle = Duration::from_secs(config.idle_timeout_sec);
+    let reason = loop {
+        tokio::select! {
+            _ = child.wait() => break "exited",
+            requested = &mut close_requested => break requested.unwrap_or("closed"),
+            _ = tokio::time::sleep(TICK) => {
+                if terminal.started.elapsed() >= max_duration {
+                    break "max_duration";
+                }
+                if terminal.state.lock().expect("terminal poisoned").last_used.elapsed() >= idle {
+                    break "idle_timeout";
+                }
+            }
+        }
+    };
+    if reason != "exited" {
+        let _ = child.start_kill();
+    }
+    let code = child.wait().await.ok().and_then(|status| status.code());
+    let drained = tokio::time::Instant::now() + READER_DRAIN;
+    for reader in readers {
+        let abort = reader.abort_handle();
+        if tokio::time::timeout_at(drained, reader).await.is_err() {
+            abort.abort();
+        }
+    }
+
+    let summary = {
+        let mut state = terminal.state.lock().expect("terminal poisoned");
+        state.exit = Some(Exit { code, reason });
+        state.last_used = Instant::now();
+        terminal.summary(&state)
+    };
+    terminal.changed.notify_waiters();
+    let mut entry = summary;
+    entry["client"] = json!(terminal.client);
+    if let Err(error) = record(audit.as_deref(), "interactive_closed", entry.clone()) {
+        log_observation(
+            "interactive_audit_failed",
+            json!({"id": terminal.id, "error": format!("{:#}", error)}),
+        );
+    }
+    log_observation("interactive_closed", entry);
+}
+
+async fn pump<R: AsyncRead + Unpin>(
+    terminal: Arc<Terminal>,
+    mut reader: R,
+    max_buffer_bytes: usize,
+) {
+    let mut chunk = [0_u8; 8192];
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => terminal.append(&chunk[..read], max_buffer_bytes),
+        }
+    }
+}
+
+pub fn mcp_tools() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "session.open",
+            "description": "Opens an interactive PTY session for a tool enabled for interactive use (e.g. msfconsole) on Kali",
+            "inputSchema": {
+                "type": "object",
+                "required": ["tool"],
+                "properties": {
+                    "tool": {"type": "string"},
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "args": {"type": "array", "items": {"type": "string"}},
+                    "engagement": {"type": "string"},
+                    "strip_ansi": {"type": "boolean"}
+                }
+            }
+        }),
+        json!({
+            "name": "session.send",
+            "description": "Sends input to an interactive session; a newline is appended unless newline is false",
+            "inputSchema": {
+                "type": "object",
+                "required": ["id", "input"],
+                "properties": {
+                    "id": {"type": "string"},
+                    "input": {"type": "string"},
+                    "newline": {"type": "boolean"}
+                }
+            }
+        }),
+        json!({
+            "name": "session.read",
+            "description": "Reads buffered output of an interactive session, optionally waiting up to wait_ms for new output",
+            "inputSchema": {
+                "type": "object",
+                "required": ["id"],
+                "properties": {
+                    "id": {"type": "string"},
+                    "wait_ms": {"type": "integer", "minimum": 0, "maximum": MAX_WAIT_MS},
+                    "max_bytes": {"type": "integer", "minimum": 1}
+                }
+            }
+        }),
+        json!({
+            "name": "session.close",
+            "description": "Terminates an interactive session and returns its exit status",
+            "inputSchema": {"type": "object", "required": ["id"], "properties": {"id": {"type": "string"}}}
+        }),
+    ]
+}
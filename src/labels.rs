@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+
+pub type Labels = BTreeMap<String, String>;
+
+const MAX_KEY_CHARS: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelsConfig {
+    #[serde(default = "default_max_labels")]
+    pub max_labels: usize,
+    #[serde(default = "default_max_value_chars")]
+    pub max_value_chars: usize,
+    #[serde(default)]
+    pub metric_keys: Vec<String>,
+}
+
+fn default_max_labels() -> usize {
+    16
+}
+
+fn default_max_value_chars() -> usize {
+    128
+}
+
+impl Default for LabelsConfig {
+    fn default() -> Self {
+        Self {
+            max_labels: default_max_labels(),
+            max_value_chars: default_max_value_chars(),
+            metric_keys: Vec::new(),
+        }
+    }
+}
+
+pub fn validate(config: &LabelsConfig) -> Result<()> {
+    for (index, key) in config.metric_keys.iter().enumerate() {
+        if !valid_key(key) || key.contains(['.', '-']) {
+            bail!(
+                "labels.metric_keys: '{}' ist kein gültiger Metrik-Label-Name (a-z, 0-9, _)",
+                key
+            );
+        }
+        if config.metric_keys[..index].contains(key) {
+            bail!("labels.metric_keys: '{}' ist doppelt aufgeführt", key);
+        }
+    }
+    Ok(())
+}
+
+pub fn check(config: &LabelsConfig, labels: &Labels) -> Result<()> {
+    if labels.len() > config.max_labels {
+        bail!(Message::LimitExceeded {
+            field: "labels",
+            value: labels.len(),
+            max: config.max_labels
+        });
+    }
+    for (key, value) in labels {
+        if !valid_key(key) {
+            bail!(Message::InvalidLabelName {
+                key: key.clone(),
+                max: MAX_KEY_CHARS
+            });
+        }
+        if value.chars().count() > config.max_value_chars {
+            bail!(Message::LabelTooLong {
+                key: key.clone(),
+                max: config.max_value_chars
+            });
+        }
+        if value.chars().any(char::is_control) {
+            bail!(Message::LabelControlChars { key: key.clone() });
+        }
+    }
+    Ok(())
+}
+
+fn valid_key(key: &str) -> bool {
+    key.len() <= MAX_KEY_CHARS
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '-'))
+}
+
+pub fn merge(base: &Labels, extra: &Labels) -> Labels {
+    let mut merged = base.clone();
+    merged.extend(
+        extra
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone())),
+    );
+    merged
+}
+
+pub fn attach(payload: &mut Value, labels: &Labels) {
+    if !labels.is_empty() {
+        payload["labels"] = json!(labels);
+    }
+}
+
+pub fn parse_pair(pair: &str) -> Result<(String, String)> {
+    let Some((key, value)) = pair.split_once(['=', ':']) else {
+        bail!(Message::InvalidLabelPair {
+            pair: pair.to_string()
+        });
+    };
+    if !valid_key(key) {
+        bail!(Message::InvalidLabelName {
+            key: key.to_string(),
+            max: MAX_KEY_CHARS
+        });
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+pub fn matches(labels: &Value, filters: &[(String, String)]) -> bool {
+    filters
+        .iter()
+        .all(|(key, value)| labels[key].as_str() == Some(value.as_str()))
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+const PING_PREFIX: &str = "bridge-ping-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivenessConfig {
+    #[serde(default)]
+    pub ping_interval_sec: u64,
+    #[serde(default = "default_max_missed_pings")]
+    pub max_missed_pings: usize,
+}
+
+fn default_max_missed_pings() -> usize {
+    3
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval_sec: 0,
+            max_missed_pings: default_max_missed_pings(),
+        }
+    }
+}
+
+impl LivenessConfig {
+    pub fn interval(&self) -> Option<Duration> {
+        (self.ping_interval_sec > 0).then(|| Duration::from_secs(self.ping_interval_sec))
+    }
+}
+
+pub enum Tick {
+    Ping(Value),
+    Dead(usize),
+}
+
+pub struct Pinger {
+    max_missed: usize,
+    sent: u64,
+    outstanding: HashSet<String>,
+}
+
+impl Pinger {
+    pub fn new(config: &LivenessConfig) -> Self {
+        Self {
+            max_missed: config.max_missed_pings.max(1),
+            sent: 0,
+            outstanding: HashSet::new(),
+        }
+    }
+
+    pub fn tick(&mut self) -> Tick {
+        if self.outstanding.len() >= self.max_missed {
+            return Tick::Dead(self.outstanding.len());
+        }
+        self.sent += 1;
+        let id = format!("{}{}", PING_PREFIX, self.sent);
+        self.outstanding.insert(id.clone());
+        Tick::Ping(json!({"jsonrpc": "2.0", "id": id, "method": "ping"}))
+    }
+
+    pub fn seen(&mut self) {
+        self.outstanding.clear();
+    }
+}
+
+pub fn is_response(message: &Value) -> bool {
+    message.get("method").is_none()
+        && (message.get("result").is_some() || message.get("error").is_some())
+}
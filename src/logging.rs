@@ -0,0 +1,213 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+#[cfg(not(unix))]
+use std::net::UdpSocket as SyslogSocket;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram as SyslogSocket;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::Level;
+
+use crate::platform::OpenOptionsExt;
+
+const SYSLOG_FACILITY_USER: u8 = 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogDestination {
+    #[default]
+    Stderr,
+    File,
+    Syslog,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_tracing(self) -> Level {
+        match self {
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Error => Level::ERROR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub destination: LogDestination,
+    #[serde(default)]
+    pub level: LogLevel,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: u64,
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+    #[serde(default = "default_syslog_socket")]
+    pub syslog_socket: String,
+}
+
+fn default_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_max_files() -> usize {
+    5
+}
+
+#[cfg(unix)]
+fn default_syslog_socket() -> String {
+    "/dev/log".to_string()
+}
+
+#[cfg(not(unix))]
+fn default_syslog_socket() -> String {
+    "127.0.0.1:514".to_string()
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            destination: LogDestination::default(),
+            level: LogLevel::default(),
+            path: None,
+            max_bytes: default_max_bytes(),
+            max_files: default_max_files(),
+            syslog_socket: default_syslog_socket(),
+        }
+    }
+}
+
+pub enum LogSink {
+    Stderr,
+    File {
+        path: PathBuf,
+        file: File,
+        size: u64,
+        max_bytes: u64,
+        max_files: usize,
+    },
+    Syslog(SyslogSocket),
+}
+
+impl LogSink {
+    pub fn open(config: &LoggingConfig) -> Result<Self> {
+        match config.destination {
+            LogDestination::Stderr => Ok(LogSink::Stderr),
+            LogDestination::File => {
+                let path = PathBuf::from(
+                    config
+                        .path
+                        .as_deref()
+                        .context("logging.path fehlt für destination \"file\"")?,
+                );
+                let file = open_log_file(&path)?;
+                let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+                Ok(LogSink::File {
+                    path,
+                    file,
+                    size,
+                    max_bytes: config.max_bytes,
+                    max_files: config.max_files,
+                })
+            }
+            LogDestination::Syslog => {
+                let socket = unbound_syslog_socket()
+                    .context("syslog-Socket konnte nicht erstellt werden")?;
+                socket.connect(&config.syslog_socket).with_context(|| {
+                    format!("syslog unter {} nicht erreichbar", config.syslog_socket)
+                })?;
+                Ok(LogSink::Syslog(socket))
+            }
+        }
+    }
+
+    pub fn write(&mut self, level: Level, line: &str) {
+        match self {
+            LogSink::Stderr => eprintln!("{}", line),
+            LogSink::File {
+                path,
+                file,
+                size,
+                max_bytes,
+                max_files,
+            } => {
+                if *size > 0
+                    && *size + line.len() as u64 + 1 > *max_bytes
+                    && let Ok(rotated) = rotate_log_file(path, *max_files)
+                {
+                    *file = rotated;
+                    *size = 0;
+                }
+                if writeln!(file, "{}", line).is_ok() {
+                    *size += line.len() as u64 + 1;
+                }
+            }
+            LogSink::Syslog(socket) => {
+                let message = format!(
+                    "<{}>ollama-kali-mcp-bridge[{}]: {}",
+                    SYSLOG_FACILITY_USER * 8 + syslog_severity(level),
+                    std::process::id(),
+                    line
+                );
+                let _ = socket.send(message.as_bytes());
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn unbound_syslog_socket() -> std::io::Result<SyslogSocket> {
+    SyslogSocket::unbound()
+}
+
+#[cfg(not(unix))]
+fn unbound_syslog_socket() -> std::io::Result<SyslogSocket> {
+    SyslogSocket::bind(("0.0.0.0", 0))
+}
+
+fn syslog_severity(level: Level) -> u8 {
+    match level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        _ => 7,
+    }
+}
+
+fn open_log_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o640)
+        .open(path)
+        .with_context(|| format!("Logdatei {} konnte nicht geöffnet werden", path.display()))
+}
+
+fn rotate_log_file(path: &Path, max_files: usize) -> Result<File> {
+    let numbered = |index: usize| PathBuf::from(format!("{}.{}", path.display(), index));
+    if max_files == 0 {
+        fs::remove_file(path).ok();
+    } else {
+        fs::remove_file(numbered(max_files)).ok();
+        for index in (1..max_files).rev() {
+            fs::rename(numbered(index), numbered(index + 1)).ok();
+        }
+        fs::rename(path, numbered(1))
+            .with_context(|| format!("Logdatei {} konnte nicht rotiert werden", path.display()))?;
+    }
+    open_log_file(path)
+}
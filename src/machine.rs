@@ -0,0 +1,356 @@
+use std::sync::OnceLock;
+
+use anyhow::{Result, bail};
+use regex::Regex;
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::cve;
+use crate::findings::{Finding, Severity};
+use crate::messages::Message;
+
+pub const MAX_MACHINE_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MachineOutput {
+    NmapXml,
+    NiktoJson,
+    Gobuster,
+}
+
+pub struct Parsed {
+    pub findings: Vec<Finding>,
+    pub preview: String,
+    pub summary: Value,
+}
+
+impl MachineOutput {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MachineOutput::NmapXml => "nmap_xml",
+            MachineOutput::NiktoJson => "nikto_json",
+            MachineOutput::Gobuster => "gobuster",
+        }
+    }
+
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            MachineOutput::NmapXml => &["-oX", "-"],
+            MachineOutput::NiktoJson => &["-Format", "json", "-output", "-"],
+            MachineOutput::Gobuster => &["-q", "--no-color"],
+        }
+    }
+
+    pub fn check_args(self, tool: &str, args: &[String]) -> Result<()> {
+        let conflicts = |arg: &str| match self {
+            MachineOutput::NmapXml => arg.starts_with("-o") && arg.len() <= 3,
+            MachineOutput::NiktoJson => {
+                matches!(
+                    arg.to_ascii_lowercase().as_str(),
+                    "-o" | "-output" | "--output" | "-format" | "--format" | "-f"
+                )
+            }
+            MachineOutput::Gobuster => false,
+        };
+        if let Some(arg) = args.iter().find(|arg| conflicts(arg)) {
+            return Err(Message::OutputFlagConflict {
+                tool: tool.to_string(),
+                format: self.as_str(),
+                arg: arg.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    pub fn parse(self, stdout: &str, args: &[String]) -> Result<Parsed> {
+        let mut parsed = match self {
+            MachineOutput::NmapXml => parse_nmap(stdout)?,
+            MachineOutput::NiktoJson => parse_nikto(stdout)?,
+            MachineOutput::Gobuster => parse_gobuster(stdout, args),
+        };
+        parsed.summary["machine_output"] = json!(self.as_str());
+        parsed.summary["parsed"] = json!(true);
+        parsed.summary["raw_bytes"] = json!(stdout.len());
+        Ok(parsed)
+    }
+}
+
+fn parse_nmap(stdout: &str) -> Result<Parsed> {
+    let start = stdout
+        .find("<nmaprun")
+        .ok_or(Message::MachineOutputMissing { format: "nmap XML" })?;
+    let end = stdout
+        .rfind("</nmaprun>")
+        .ok_or(Message::MachineOutputIncomplete { format: "nmap XML" })?
+        + "</nmaprun>".len();
+    let document = Document::parse(&stdout[start..end])
+        .map_err(|_| Message::MachineOutputInvalid { format: "nmap XML" })?;
+    let mut findings = Vec::new();
+    let mut preview = Vec::new();
+    let mut hosts = 0;
+    for host in document
+        .descendants()
+        .filter(|node| node.has_tag_name("host"))
+    {
+        hosts += 1;
+        let address = host
+            .children()
+            .filter(|node| node.has_tag_name("address"))
+            .find(|node| node.attribute("addrtype") != Some("mac"))
+            .and_then(|node| node.attribute("addr"))
+            .unwrap_or("-");
+        let hostname = child(host, "hostnames")
+            .and_then(|names| child(names, "hostname"))
+            .and_then(|name| name.attribute("name"));
+        let status = child(host, "status")
+            .and_then(|status| status.attribute("state"))
+            .unwrap_or("unknown");
+        preview.push(match hostname {
+            Some(name) => format!("Host {} ({}) {}", address, name, status),
+            None => format!("Host {} {}", address, status),
+        });
+        for port in child(host, "ports")
+            .into_iter()
+            .flat_map(|ports| ports.children())
+            .filter(|node| node.has_tag_name("port"))
+        {
+            let protocol = port.attribute("protocol").unwrap_or("tcp");
+            let portid = port.attribute("portid").unwrap_or("-");
+            let state = child(port, "state")
+                .and_then(|state| state.attribute("state"))
+                .unwrap_or("unknown");
+            let service = child(port, "service");
+            let name = service.and_then(|service| service.attribute("name"));
+            let product = service
+                .map(|service| {
+                    ["product", "version", "extrainfo"]
+                        .iter()
+                        .filter_map(|key| service.attribute(*key))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .unwrap_or_default();
+            preview.push(
+                format!(
+                    "  {}/{} {} {} {}",
+                    portid,
+                    protocol,
+                    state,
+                    name.unwrap_or("-"),
+                    product
+                )
+                .trim_end()
+                .to_string(),
+            );
+            let scripts = port
+                .children()
+                .filter(|node| node.has_tag_name("script"))
+                .collect::<Vec<_>>();
+            for script in &scripts {
+                let output = script.attribute("output").unwrap_or_default().trim();
+                preview.push(format!(
+                    "  |_ {}: {}",
+                    script.attribute("id").unwrap_or("-"),
+                    output.lines().next().unwrap_or_default()
+                ));
+            }
+            if state != "open" {
+                continue;
+            }
+            let mut finding = Finding::new(
+                "nmap",
+                "service",
+                Severity::Info,
+                match name {
+                    Some(name) => format!("Port {}/{} offen ({})", portid, protocol, name),
+                    None => format!("Port {}/{} offen", portid, protocol),
+                },
+            );
+            finding.host = Some(address.to_string());
+            finding.port = portid.parse().ok();
+            finding.protocol = Some(protocol.to_string());
+            finding.evidence = Some(product.clone()).filter(|product| !product.is_empty());
+            finding.data = json!({"service": name, "hostname": hostname});
+            let mut outputs = String::new();
+            for script in scripts {
+                if let Some(id) = script.attribute("id") {
+                    finding.data["scripts"][id] =
+                        json!(script.attribute("output").unwrap_or_default());
+                    outputs.push_str(script.attribute("output").unwrap_or_default());
+                    outputs.push('\n');
+                }
+            }
+            let cves = cve::scored(&outputs);
+            if !cves.is_empty() {
+                finding.data["cves"] = json!(cves);
+            }
+            findings.push(finding);
+        }
+    }
+    if let Some(finished) = document
+        .descendants()
+        .find(|node| node.has_tag_name("finished"))
+        && let Some(summary) = finished.attribute("summary")
+    {
+        preview.push(summary.to_string());
+    }
+    let summary = json!({"hosts": hosts, "open_ports": findings.len()});
+    Ok(Parsed {
+        findings,
+        preview: lines(preview),
+        summary,
+    })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn parse_nikto(stdout: &str) -> Result<Parsed> {
+    let documents = match serde_json::from_str::<Value>(stdout.trim()) {
+        Ok(document) => vec![document],
+        Err(_) => stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('{') || line.starts_with('['))
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .collect(),
+    };
+    if documents.is_empty() {
+        bail!(Message::MachineOutputMissing {
+            format: "Nikto JSON"
+        });
+    }
+    let mut findings = Vec::new();
+    let mut preview = Vec::new();
+    let mut hosts = 0;
+    let scans = documents.iter().flat_map(|document| match document {
+        Value::Array(scans) => scans.iter().collect::<Vec<_>>(),
+        scan => vec![scan],
+    });
+    for scan in scans.filter(|scan| scan.is_object()) {
+        hosts += 1;
+        let host = scan["ip"]
+            .as_str()
+            .or_else(|| scan["host"].as_str())
+            .unwrap_or("-");
+        let port = text(&scan["port"]);
+        preview.push(
+            format!(
+                "Host {}:{} {}",
+                host,
+                port,
+                scan["banner"].as_str().unwrap_or_default()
+            )
+            .trim_end()
+            .to_string(),
+        );
+        for item in scan["vulnerabilities"].as_array().into_iter().flatten() {
+            let message = item["msg"].as_str().unwrap_or_default();
+            let url = item["url"].as_str().unwrap_or_default();
+            preview.push(format!("+ {}: {}", url, message));
+            let mut finding = Finding::new("nikto", "web", Severity::Info, message);
+            finding.host = Some(host.to_string());
+            finding.port = port.parse().ok();
+            finding.protocol = Some("tcp".to_string());
+            finding.evidence = Some(url.to_string()).filter(|url| !url.is_empty());
+            finding.references = item["references"]
+                .as_str()
+                .unwrap_or_default()
+                .split([' ', ',', '\n'])
+                .filter(|reference| !reference.is_empty())
+                .map(str::to_string)
+                .collect();
+            finding.data = json!({"id": item["id"], "method": item["method"]});
+            findings.push(finding);
+        }
+    }
+    let summary = json!({"hosts": hosts, "items": findings.len()});
+    Ok(Parsed {
+        findings,
+        preview: lines(preview),
+        summary,
+    })
+}
+
+fn parse_gobuster(stdout: &str, args: &[String]) -> Parsed {
+    static PATH: OnceLock<Regex> = OnceLock::new();
+    let path = PATH.get_or_init(|| {
+        Regex::new(r"^(\S+)\s+\(Status:\s*(\d+)\)(?:\s*\[Size:\s*(\d+)\])?(?:\s*\[-->\s*(\S+)\])?")
+            .expect("gobuster regex")
+    });
+    let base = args
+        .iter()
+        .position(|arg| arg == "-u" || arg == "--url")
+        .and_then(|index| args.get(index + 1))
+        .map(|url| url.trim_end_matches('/').to_string());
+    let host = base.as_deref().map(|url| {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let authority = rest.split('/').next().unwrap_or(rest);
+        authority
+            .rsplit_once(':')
+            .map_or(authority, |(host, _)| host)
+            .to_string()
+    });
+    let mut findings = Vec::new();
+    for line in stdout.lines().map(str::trim) {
+        if let Some(captures) = path.captures(line) {
+            let found = &captures[1];
+            let status = &captures[2];
+            let mut finding = Finding::new(
+                "gobuster",
+                "path",
+                Severity::Info,
+                format!("Pfad {} (Status {})", found, status),
+            );
+            finding.host = host.clone();
+            finding.evidence = Some(match &base {
+                Some(base) if found.starts_with('/') => format!("{}{}", base, found),
+                _ => found.to_string(),
+            });
+            finding.data = json!({
+                "status": status.parse::<u16>().ok(),
+                "size": captures.get(3).and_then(|size| size.as_str().parse::<u64>().ok()),
+                "redirect": captures.get(4).map(|redirect| redirect.as_str())
+            });
+            findings.push(finding);
+        } else if let Some(name) = line.strip_prefix("Found:") {
+            let name = name.split_whitespace().next().unwrap_or_default();
+            if name.is_empty() {
+                continue;
+            }
+            let mut finding = Finding::new(
+                "gobuster",
+                "hostname",
+                Severity::Info,
+                format!("Name {} gefunden", name),
+            );
+            finding.host = Some(name.to_string());
+            findings.push(finding);
+        }
+    }
+    let summary = json!({"items": findings.len()});
+    Parsed {
+        findings,
+        preview: stdout.to_string(),
+        summary,
+    }
+}
+
+fn lines(lines: Vec<String>) -> String {
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+fn text(value: &Value) -> String {
+    value
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| value.to_string())
+}
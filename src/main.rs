@@ -1,17 +1,160 @@
-use std::collections::HashMap;
+mod activity;
+mod approvals;
+mod artifacts;
+mod audit;
+mod bandwidth;
+mod baseline;
+mod budget;
+mod bundle;
+mod cache;
+mod connections;
+mod console;
+mod control;
+mod cracking;
+mod cve;
+mod defectdojo;
+mod encryption;
+mod engagement;
+mod events;
+mod export;
+mod findings;
+mod findings_store;
+mod framing;
+mod fs;
+mod gvm;
+mod health;
+mod hooks;
+mod hostkeys;
+mod http;
+mod identity;
+mod inflight;
+mod interactive;
+mod labels;
+mod liveness;
+mod logging;
+mod machine;
+mod manifest;
+mod mcp;
+mod messages;
+mod metasploit;
+mod metrics;
+mod monitor;
+mod notify;
+mod outputs;
+mod pipeline;
+mod platform;
+mod plugins;
+mod profiles;
+mod rbac;
+mod redact;
+mod render;
+mod rest;
+mod retention;
+mod sampling;
+mod sanitize;
+mod scheduler;
+mod scripting;
+mod secrets;
+mod session;
+mod shutdown;
+mod stats;
+mod targets;
+mod telemetry;
+mod timeline;
+mod tls;
+mod websocket;
+mod wordlists;
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context as TaskContext, Poll};
 use std::time::SystemTime;
 use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result, anyhow, bail};
-use clap::{Args, Parser, Subcommand};
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
-use tokio::process::Command;
-use tokio::sync::mpsc;
+use sha2::{Digest, Sha256};
+use tokio::io::{
+    self, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines, Stdin,
+};
+use tokio::process::{Child, Command};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::Instrument;
+
+use activity::{Activity, ActivityConfig};
+use approvals::{ApprovalEvent, Approvals, ApprovalsConfig, Decision};
+use artifacts::{ArtifactStore, ArtifactsConfig, Transcript};
+use audit::{AuditConfig, AuditLog};
+use bandwidth::{BandwidthConfig, Bucket, Throttle};
+use baseline::BaselinesConfig;
+use budget::{Budgets, ContextBudgetConfig, Metered};
+use cache::{CacheConfig, ResultCache};
+use connections::{ConnectionSharingConfig, Leases};
+use console::{ConsoleCommand, RequestSpec};
+use control::{Control, ControlCommand, ControlConfig};
+use cracking::CrackingConfig;
+use cve::{CveConfig, CveDatabase};
+use defectdojo::DefectDojoConfig;
+use encryption::{EncryptionAction, EncryptionConfig, Sealer};
+use engagement::{EngagementAction, EngagementsConfig};
+use events::EventsFormat;
+use export::{ExportFormat, ExportKind};
+use findings::Finding;
+use findings_store::{FindingsStore, FindingsStoreConfig};
+use framing::{FrameReader, Framing};
+use fs::FsConfig;
+use gvm::{Gvm, GvmConfig};
+use health::{Circuits, HealthConfig};
+use hooks::{Hooks, HooksConfig};
+use hostkeys::HostKeysConfig;
+use http::{HttpConfig, HttpRequest, HttpResponse};
+use identity::IdentityConfig;
+use inflight::{InFlight, Joined, LeaderGuard};
+use interactive::{InteractiveConfig, Terminals};
+use labels::{Labels, LabelsConfig};
+use liveness::{LivenessConfig, Pinger, Tick};
+use logging::LoggingConfig;
+use machine::{MAX_MACHINE_BYTES, MachineOutput};
+use manifest::{Manifest, ManifestConfig, ToolVersions, VersionAction};
+use mcp::{McpConfig, Peer};
+use messages::{Locale, Message};
+use metasploit::{Metasploit, MetasploitConfig};
+use metrics::{Gauges, Metrics};
+use notify::{NotificationEvent, NotificationsConfig, Notifier};
+use outputs::Outputs;
+use pipeline::{Phase, Pipeline, PipelineConfig, Stage, StageFuture, StageName};
+use plugins::{Plugins, PluginsConfig};
+use profiles::{CaptureConfig, HydraConfig, Image, ScreenshotConfig, ToolProfile};
+use rbac::RbacConfig;
+use redact::{RedactionConfig, StreamRedactor};
+use render::{OutputFormat, Renderer, WorkflowFormat, WorkflowProgress};
+use rest::{RunKind, RunStore};
+use retention::{RetentionConfig, Selection};
+use sampling::SamplingConfig;
+use sanitize::{LineFilter, OutputFilter};
+use scheduler::{Admission, Claim, Concurrency, Scheduler, SchedulerConfig, SessionPermit};
+use scripting::{ScriptingConfig, Scripts};
+use secrets::{SecretValues, SecretsAction, SecretsConfig};
+use session::Sessions;
+use shutdown::{CancelSignal, Hangup, ShutdownSignals};
+use targets::TargetsConfig;
+use telemetry::{OBSERVATION_TARGET, Telemetry, TelemetryConfig};
+use timeline::TimelineFormat;
+use wordlists::WordlistsConfig;
 
 #[derive(Parser, Debug)]
-#[command(version, about = "Ollama ↔ Kali tool bridge over SSH with strict runtime control")]
+#[command(
+    version,
+    about = "Ollama ↔ Kali tool bridge over SSH with strict runtime control"
+)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
@@ -20,9 +163,33 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Commands {
     Run(RunArgs),
-    Serve(ServeArgs),
-    McpServe(ServeArgs),
+    Serve(StdioServeArgs),
+    McpServe(McpServeArgs),
     WorkflowServe(ServeArgs),
+    Workflow(WorkflowArgs),
+    HttpServe(HttpServeArgs),
+    Console(ConsoleArgs),
+    HttpToken(HttpTokenArgs),
+    VerifyAudit(VerifyAuditArgs),
+    AuditKeygen(AuditKeygenArgs),
+    Stats(StatsArgs),
+    Timeline(TimelineArgs),
+    Tools(ToolsArgs),
+    ExportBundle(ExportBundleArgs),
+    ExportFindings(ExportFindingsArgs),
+    Export(ExportArgs),
+    Engagement(EngagementArgs),
+    #[command(alias = "ctl")]
+    Control(ControlArgs),
+    Monitor(MonitorArgs),
+    Events(EventsArgs),
+    Secrets(SecretsArgs),
+    Encryption(EncryptionArgs),
+    TrustHost(TrustHostArgs),
+    Purge(PurgeArgs),
+    Replay(ReplayArgs),
+    Baseline(BaselineArgs),
+    Findings(FindingsArgs),
     PrintSchema,
 }
 
@@ -30,205 +197,1602 @@ enum Commands {
 struct ServeArgs {
     #[arg(long, default_value = "bridge-config.json")]
     config: String,
+    #[arg(long)]
+    metrics_addr: Option<String>,
 }
 
 #[derive(Args, Debug)]
-struct RunArgs {
+struct StdioServeArgs {
+    #[command(flatten)]
+    serve: ServeArgs,
     #[arg(long)]
-    host: String,
-    #[arg(long)]
-    user: Option<String>,
+    rest: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct WorkflowArgs {
+    #[command(subcommand)]
+    action: WorkflowAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum WorkflowAction {
+    Run(WorkflowRunArgs),
+}
+
+#[derive(Args, Debug)]
+struct WorkflowRunArgs {
     #[arg(long)]
-    tool: String,
+    file: String,
     #[arg(long)]
-    args: Vec<String>,
+    host: Option<String>,
     #[arg(long)]
-    timeout_sec: Option<u64>,
+    user: Option<String>,
     #[arg(long)]
-    max_output_bytes: Option<usize>,
+    engagement: Option<String>,
+    #[arg(long = "var")]
+    vars: Vec<String>,
+    #[arg(long = "label")]
+    labels: Vec<String>,
+    #[arg(long, value_enum, default_value_t = WorkflowFormat::Pretty)]
+    format: WorkflowFormat,
     #[arg(long, default_value = "bridge-config.json")]
     config: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ToolPolicy {
-    command: String,
-    #[serde(default)]
-    default_args: Vec<String>,
-    #[serde(default = "default_max_args")]
-    max_args: usize,
+#[derive(Args, Debug)]
+struct HttpServeArgs {
+    #[command(flatten)]
+    serve: ServeArgs,
+    #[arg(long)]
+    listen: Option<String>,
 }
 
-fn default_max_args() -> usize {
-    16
+#[derive(Args, Debug)]
+struct McpServeArgs {
+    #[command(flatten)]
+    serve: ServeArgs,
+    #[arg(long, value_enum, default_value_t = Framing::Auto)]
+    framing: Framing,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct BridgeConfig {
-    #[serde(default = "default_timeout")]
-    default_timeout_sec: u64,
-    #[serde(default = "default_max_timeout")]
-    max_timeout_sec: u64,
-    #[serde(default = "default_max_output")]
-    max_output_bytes: usize,
-    #[serde(default = "default_ssh_connect_timeout")]
-    ssh_connect_timeout_sec: u64,
-    #[serde(default = "default_ssh_server_alive_interval")]
-    ssh_server_alive_interval_sec: u64,
-    #[serde(default = "default_ssh_server_alive_count_max")]
-    ssh_server_alive_count_max: u64,
-    #[serde(default = "default_strict_host_key_checking")]
-    ssh_strict_host_key_checking: bool,
-    #[serde(default = "default_max_retries")]
-    max_retries: u32,
-    #[serde(default = "default_retry_backoff_ms")]
-    retry_backoff_ms: u64,
-    #[serde(default = "default_observability_json_logs")]
-    observability_json_logs: bool,
-    #[serde(default)]
-    tools: HashMap<String, ToolPolicy>,
+#[derive(Args, Debug)]
+struct ConsoleArgs {
+    #[command(flatten)]
+    serve: ServeArgs,
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long)]
+    user: Option<String>,
+    #[arg(long)]
+    engagement: Option<String>,
 }
 
-fn default_timeout() -> u64 {
-    30
+#[derive(Args, Debug)]
+struct HttpTokenArgs {
+    #[arg(long)]
+    identity: String,
 }
 
-fn default_max_timeout() -> u64 {
-    300
+#[derive(Args, Debug)]
+struct VerifyAuditArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    public_key: Option<String>,
 }
 
-fn default_max_output() -> usize {
-    128 * 1024
+#[derive(Args, Debug)]
+struct StatsArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    since: Option<String>,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long = "label")]
+    labels: Vec<String>,
+    #[arg(long, default_value_t = 5)]
+    top_hosts: usize,
+    #[arg(long, value_enum, default_value_t = TableFormat::Table)]
+    format: TableFormat,
 }
 
-fn default_ssh_connect_timeout() -> u64 {
-    10
+#[derive(Args, Debug)]
+struct TimelineArgs {
+    engagement: String,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    since: Option<String>,
+    #[arg(long)]
+    until: Option<String>,
+    #[arg(long, value_enum, default_value_t = TimelineFormat::Table)]
+    format: TimelineFormat,
 }
 
-fn default_ssh_server_alive_interval() -> u64 {
-    15
+#[derive(Args, Debug)]
+struct ToolsArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[command(subcommand)]
+    action: ToolsAction,
 }
 
-fn default_ssh_server_alive_count_max() -> u64 {
-    2
+#[derive(Subcommand, Debug)]
+enum ToolsAction {
+    List {
+        #[arg(long)]
+        client: Option<String>,
+        #[arg(long, value_enum, default_value_t = TableFormat::Table)]
+        format: TableFormat,
+    },
+    Describe {
+        name: String,
+        #[arg(long, value_enum, default_value_t = TableFormat::Table)]
+        format: TableFormat,
+    },
 }
 
-fn default_strict_host_key_checking() -> bool {
-    true
+#[derive(Args, Debug)]
+struct ExportBundleArgs {
+    run_id: String,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    out: Option<String>,
 }
 
-fn default_max_retries() -> u32 {
-    1
+#[derive(Args, Debug)]
+struct ExportFindingsArgs {
+    #[arg(
+        long,
+        required_unless_present = "engagement",
+        conflicts_with = "engagement"
+    )]
+    run: Option<String>,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    dry_run: bool,
 }
 
-fn default_retry_backoff_ms() -> u64 {
-    750
+#[derive(Args, Debug)]
+struct ExportArgs {
+    #[arg(value_enum)]
+    kind: ExportKind,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    format: ExportFormat,
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+    #[arg(long)]
+    since: Option<String>,
+    #[arg(long)]
+    until: Option<String>,
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long)]
+    tool: Option<String>,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long)]
+    min_severity: Option<String>,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    out: Option<String>,
 }
 
-fn default_observability_json_logs() -> bool {
-    true
+#[derive(Args, Debug)]
+struct EngagementArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[command(subcommand)]
+    action: EngagementAction,
 }
 
-impl Default for BridgeConfig {
-    fn default() -> Self {
-        let mut tools = HashMap::new();
-        tools.insert(
-            "nmap".to_string(),
-            ToolPolicy {
-                command: "/usr/bin/nmap".to_string(),
-                default_args: Vec::new(),
-                max_args: 12,
-            },
-        );
-        tools.insert(
-            "nikto".to_string(),
-            ToolPolicy {
-                command: "/usr/bin/nikto".to_string(),
-                default_args: Vec::new(),
-                max_args: 12,
-            },
-        );
-        tools.insert(
-            "sqlmap".to_string(),
-            ToolPolicy {
-                command: "/usr/bin/sqlmap".to_string(),
-                default_args: Vec::new(),
-                max_args: 12,
-            },
-        );
-        Self {
-            default_timeout_sec: default_timeout(),
-            max_timeout_sec: default_max_timeout(),
-            max_output_bytes: default_max_output(),
-            ssh_connect_timeout_sec: default_ssh_connect_timeout(),
-            ssh_server_alive_interval_sec: default_ssh_server_alive_interval(),
-            ssh_server_alive_count_max: default_ssh_server_alive_count_max(),
-            ssh_strict_host_key_checking: default_strict_host_key_checking(),
-            max_retries: default_max_retries(),
-            retry_backoff_ms: default_retry_backoff_ms(),
-            observability_json_logs: default_observability_json_logs(),
-            tools,
-        }
-    }
+#[derive(Args, Debug)]
+struct ControlArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    socket: Option<String>,
+    #[command(subcommand)]
+    command: ControlCommand,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct RunRequest {
-    id: Option<String>,
-    host: String,
-    user: Option<String>,
-    tool: String,
-    #[serde(default)]
-    args: Vec<String>,
-    timeout_sec: Option<u64>,
-    max_output_bytes: Option<usize>,
+#[derive(Args, Debug)]
+struct EventsArgs {
+    #[command(subcommand)]
+    action: EventsAction,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct WorkflowRequest {
-    id: Option<String>,
-    host: String,
-    user: Option<String>,
-    #[serde(default = "default_stop_on_error")]
-    stop_on_error: bool,
-    steps: Vec<WorkflowStep>,
+#[derive(Subcommand, Debug)]
+enum EventsAction {
+    Tail(EventsTailArgs),
 }
 
-fn default_stop_on_error() -> bool {
-    true
+#[derive(Args, Debug)]
+struct EventsTailArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    run: Option<String>,
+    #[arg(long)]
+    tool: Option<String>,
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long = "kind")]
+    kinds: Vec<String>,
+    #[arg(long)]
+    last: Option<usize>,
+    #[arg(long, short)]
+    follow: bool,
+    #[arg(long, value_enum, default_value_t = EventsFormat::Jsonl)]
+    format: EventsFormat,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct WorkflowStep {
-    tool: String,
-    #[serde(default)]
-    args: Vec<String>,
-    timeout_sec: Option<u64>,
-    max_output_bytes: Option<usize>,
+#[derive(Args, Debug)]
+struct MonitorArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    socket: Option<String>,
+    #[arg(long, default_value_t = 1000)]
+    interval_ms: u64,
 }
 
-#[derive(Debug, Deserialize)]
-struct JsonRpcRequest {
-    id: Option<Value>,
-    method: String,
-    params: Option<Value>,
+#[derive(Args, Debug)]
+struct SecretsArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[command(subcommand)]
+    action: SecretsAction,
 }
 
-#[derive(Debug, Deserialize)]
-struct McpCallParams {
-    name: String,
-    #[serde(default)]
-    arguments: Value,
+#[derive(Args, Debug)]
+struct PurgeArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long)]
+    older_than: Option<String>,
+    #[arg(long)]
+    dry_run: bool,
 }
 
-#[derive(Debug, Deserialize)]
-struct McpToolArguments {
-    host: String,
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    run_id: String,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[arg(long)]
+    path: Option<String>,
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long)]
     user: Option<String>,
-    #[serde(default)]
-    args: Vec<String>,
+    #[arg(long)]
     timeout_sec: Option<u64>,
+    #[arg(long)]
     max_output_bytes: Option<usize>,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long = "label")]
+    labels: Vec<String>,
+    #[arg(long)]
+    manifest: bool,
+}
+
+#[derive(Args, Debug)]
+struct BaselineArgs {
+    name: String,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+}
+
+#[derive(Args, Debug)]
+struct FindingsArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[command(subcommand)]
+    action: FindingsAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum FindingsAction {
+    List {
+        #[command(flatten)]
+        filter: FindingsFilter,
+        #[arg(long)]
+        since: Option<String>,
+        #[arg(long, value_enum, default_value_t = TableFormat::Table)]
+        format: TableFormat,
+    },
+    Diff {
+        #[command(flatten)]
+        filter: FindingsFilter,
+        #[arg(long)]
+        since: String,
+        #[arg(long, value_enum, default_value_t = TableFormat::Table)]
+        format: TableFormat,
+    },
+}
+
+#[derive(Args, Debug)]
+struct FindingsFilter {
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long)]
+    min_severity: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct EncryptionArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    #[command(subcommand)]
+    action: EncryptionAction,
+}
+
+#[derive(Args, Debug)]
+struct TrustHostArgs {
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+    host: String,
+    #[arg(long)]
+    address: Option<String>,
+    #[arg(long)]
+    port: Option<u16>,
+    #[arg(long)]
+    yes: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum TableFormat {
+    Table,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct AuditKeygenArgs {
+    #[arg(long)]
+    out: String,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    #[arg(long)]
+    host: String,
+    #[arg(long)]
+    user: Option<String>,
+    #[arg(long)]
+    tool: String,
+    #[arg(long)]
+    args: Vec<String>,
+    #[arg(long)]
+    timeout_sec: Option<u64>,
+    #[arg(long)]
+    max_output_bytes: Option<usize>,
+    #[arg(long, value_enum, default_value_t = StreamMode::Chunks)]
+    stream_mode: StreamMode,
+    #[arg(long)]
+    strip_ansi: bool,
+    #[arg(long)]
+    strip_control_chars: bool,
+    #[arg(long)]
+    include_pattern: Option<String>,
+    #[arg(long)]
+    exclude_pattern: Option<String>,
+    #[arg(long)]
+    engagement: Option<String>,
+    #[arg(long)]
+    stdin_file: Option<String>,
+    #[arg(long)]
+    params: Option<String>,
+    #[arg(long = "label")]
+    labels: Vec<String>,
+    #[arg(long)]
+    manifest: bool,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Jsonl)]
+    format: OutputFormat,
+    #[arg(long, default_value = "bridge-config.json")]
+    config: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ToolPolicy {
+    command: String,
+    #[serde(default)]
+    default_args: Vec<String>,
+    #[serde(default = "default_max_args")]
+    max_args: usize,
+    #[serde(default)]
+    retry_policy: Option<RetryPolicy>,
+    #[serde(default)]
+    strip_ansi: bool,
+    #[serde(default)]
+    strip_control_chars: bool,
+    #[serde(default)]
+    binary: bool,
+    #[serde(default)]
+    secrets: Vec<String>,
+    #[serde(default = "default_tool_enabled")]
+    enabled: bool,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    cwd: Option<String>,
+    #[serde(default)]
+    run_as: Option<String>,
+    #[serde(default)]
+    nice_level: Option<i32>,
+    #[serde(default)]
+    io_class: Option<IoClass>,
+    #[serde(default)]
+    cpu_percent: Option<u32>,
+    #[serde(default)]
+    max_mem: Option<String>,
+    #[serde(default = "default_kill_signal")]
+    kill_signal: String,
+    #[serde(default = "default_kill_grace")]
+    kill_grace_sec: u64,
+    #[serde(default = "default_kill_escalate")]
+    kill_escalate: bool,
+    #[serde(default)]
+    workspace: bool,
+    #[serde(default)]
+    artifacts: Vec<String>,
+    #[serde(default)]
+    interactive: bool,
+    #[serde(default)]
+    profile: Option<ToolProfile>,
+    #[serde(default)]
+    max_rate: Option<u32>,
+    #[serde(default)]
+    capture: CaptureConfig,
+    #[serde(default)]
+    screenshots: ScreenshotConfig,
+    #[serde(default)]
+    hydra: HydraConfig,
+    #[serde(default)]
+    require_approval: bool,
+    #[serde(default)]
+    concurrency: Option<Concurrency>,
+    #[serde(default)]
+    machine_output: Option<MachineOutput>,
+    #[serde(default)]
+    min_version: Option<String>,
+    #[serde(default)]
+    min_version_action: VersionAction,
+    #[serde(default)]
+    version_args: Option<Vec<String>>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    examples: Vec<ToolExample>,
+    #[serde(default)]
+    arg_hints: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ToolExample {
+    #[serde(default)]
+    description: Option<String>,
+    arguments: serde_json::Map<String, Value>,
+}
+
+const KILL_SIGNALS: &[&str] = &["TERM", "INT", "HUP", "QUIT", "USR1", "USR2", "KILL"];
+
+#[derive(Debug, Clone, Copy)]
+struct Termination<'a> {
+    signal: &'a str,
+    grace_sec: u64,
+    escalate: bool,
+    run_as: Option<&'a str>,
+}
+
+impl ToolPolicy {
+    fn termination(&self) -> Termination<'_> {
+        Termination {
+            signal: &self.kill_signal,
+            grace_sec: self.kill_grace_sec,
+            escalate: self.kill_escalate,
+            run_as: self.run_as.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IoClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+impl IoClass {
+    fn ionice_class(self) -> &'static str {
+        match self {
+            IoClass::Realtime => "1",
+            IoClass::BestEffort => "2",
+            IoClass::Idle => "3",
+        }
+    }
+}
+
+fn default_max_args() -> usize {
+    16
+}
+
+fn default_tool_enabled() -> bool {
+    true
+}
+
+fn default_kill_signal() -> String {
+    "TERM".to_string()
+}
+
+fn default_kill_grace() -> u64 {
+    5
+}
+
+fn default_kill_escalate() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FailureClass {
+    Timeout,
+    SshConnect,
+    NonZeroExit,
+    ExecError,
+    PolicyViolation,
+    PrivilegeDenied,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RetryPolicy {
+    #[serde(default = "default_retry_on")]
+    retry_on: Vec<FailureClass>,
+    #[serde(default)]
+    retry_exit_codes: Vec<i32>,
+    #[serde(default)]
+    no_retry_exit_codes: Vec<i32>,
+}
+
+fn default_retry_on() -> Vec<FailureClass> {
+    vec![
+        FailureClass::Timeout,
+        FailureClass::SshConnect,
+        FailureClass::ExecError,
+    ]
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retry_on: default_retry_on(),
+            retry_exit_codes: Vec::new(),
+            no_retry_exit_codes: Vec::new(),
+        }
+    }
+}
+
+impl FailureClass {
+    fn as_str(self) -> &'static str {
+        match self {
+            FailureClass::Timeout => "timeout",
+            FailureClass::SshConnect => "ssh_connect",
+            FailureClass::NonZeroExit => "non_zero_exit",
+            FailureClass::ExecError => "exec_error",
+            FailureClass::PolicyViolation => "policy_violation",
+            FailureClass::PrivilegeDenied => "privilege_denied",
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn should_retry(&self, class: FailureClass, exit_code: Option<i32>) -> bool {
+        if class == FailureClass::PolicyViolation {
+            return false;
+        }
+        if let Some(code) = exit_code {
+            if self.no_retry_exit_codes.contains(&code) {
+                return false;
+            }
+            if class == FailureClass::NonZeroExit && self.retry_exit_codes.contains(&code) {
+                return true;
+            }
+        }
+        self.retry_on.contains(&class)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BackoffStrategy {
+    #[default]
+    Linear,
+    Exponential,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SshClient {
+    #[default]
+    Openssh,
+    Plink,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RemoteTimeout {
+    #[default]
+    Auto,
+    Coreutils,
+    Watchdog,
+    Bridge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BridgeConfig {
+    #[serde(default = "default_timeout")]
+    default_timeout_sec: u64,
+    #[serde(default = "default_max_timeout")]
+    max_timeout_sec: u64,
+    #[serde(default = "default_max_output")]
+    max_output_bytes: usize,
+    #[serde(default = "default_max_stdin")]
+    max_stdin_bytes: usize,
+    #[serde(default = "default_ssh_connect_timeout")]
+    ssh_connect_timeout_sec: u64,
+    #[serde(default = "default_ssh_server_alive_interval")]
+    ssh_server_alive_interval_sec: u64,
+    #[serde(default = "default_ssh_server_alive_count_max")]
+    ssh_server_alive_count_max: u64,
+    #[serde(default = "default_strict_host_key_checking")]
+    ssh_strict_host_key_checking: bool,
+    #[serde(default = "default_ssh_program")]
+    ssh_program: String,
+    #[serde(default)]
+    ssh_client: SshClient,
+    #[serde(default)]
+    host_keys: HostKeysConfig,
+    #[serde(default)]
+    ssh_identity: IdentityConfig,
+    #[serde(default)]
+    ssh_extra_options: Vec<String>,
+    #[serde(default)]
+    ssh_host_options: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    connection_sharing: ConnectionSharingConfig,
+    #[serde(default)]
+    bandwidth: BandwidthConfig,
+    #[serde(default)]
+    hooks: HooksConfig,
+    #[serde(default)]
+    plugins: PluginsConfig,
+    #[serde(default)]
+    scripting: ScriptingConfig,
+    #[serde(default)]
+    pipeline: PipelineConfig,
+    #[serde(default)]
+    labels: LabelsConfig,
+    #[serde(default)]
+    retention: RetentionConfig,
+    #[serde(default)]
+    encryption: EncryptionConfig,
+    #[serde(default)]
+    manifest: ManifestConfig,
+    #[serde(default)]
+    baselines: BaselinesConfig,
+    #[serde(default)]
+    findings_store: FindingsStoreConfig,
+    #[serde(default)]
+    cve: CveConfig,
+    #[serde(default)]
+    defectdojo: DefectDojoConfig,
+    #[serde(default)]
+    remote_timeout: RemoteTimeout,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+    #[serde(default = "default_retry_backoff_ms")]
+    retry_backoff_ms: u64,
+    #[serde(default)]
+    retry_backoff_strategy: BackoffStrategy,
+    #[serde(default = "default_retry_backoff_max_ms")]
+    retry_backoff_max_ms: u64,
+    #[serde(default)]
+    retry_jitter_ratio: f64,
+    #[serde(default)]
+    retry_policy: RetryPolicy,
+    #[serde(default = "default_observability_json_logs")]
+    observability_json_logs: bool,
+    #[serde(default)]
+    locale: Locale,
+    #[serde(default)]
+    logging: LoggingConfig,
+    #[serde(default)]
+    scheduler: SchedulerConfig,
+    #[serde(default)]
+    cache: CacheConfig,
+    #[serde(default = "default_dedup_in_flight")]
+    dedup_in_flight: bool,
+    #[serde(default = "default_shutdown_drain_timeout")]
+    shutdown_drain_timeout_sec: u64,
+    #[serde(default)]
+    metrics_addr: Option<String>,
+    #[serde(default)]
+    telemetry: TelemetryConfig,
+    #[serde(default)]
+    audit: AuditConfig,
+    #[serde(default)]
+    notifications: NotificationsConfig,
+    #[serde(default)]
+    artifacts: ArtifactsConfig,
+    #[serde(default)]
+    engagements: EngagementsConfig,
+    #[serde(default)]
+    control: ControlConfig,
+    #[serde(default)]
+    activity: ActivityConfig,
+    #[serde(default)]
+    liveness: LivenessConfig,
+    #[serde(default)]
+    health: HealthConfig,
+    #[serde(default)]
+    mcp: McpConfig,
+    #[serde(default)]
+    sampling: SamplingConfig,
+    #[serde(default)]
+    context_budget: ContextBudgetConfig,
+    #[serde(default)]
+    approvals: ApprovalsConfig,
+    #[serde(default)]
+    secrets: SecretsConfig,
+    #[serde(default)]
+    redaction: RedactionConfig,
+    #[serde(default)]
+    http: HttpConfig,
+    #[serde(default)]
+    rbac: RbacConfig,
+    #[serde(default)]
+    fs: FsConfig,
+    #[serde(default)]
+    interactive: InteractiveConfig,
+    #[serde(default)]
+    metasploit: MetasploitConfig,
+    #[serde(default)]
+    gvm: GvmConfig,
+    #[serde(default)]
+    wordlists: WordlistsConfig,
+    #[serde(default)]
+    targets: TargetsConfig,
+    #[serde(default)]
+    cracking: CrackingConfig,
+    #[serde(default)]
+    tools: HashMap<String, ToolPolicy>,
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+fn default_max_timeout() -> u64 {
+    300
+}
+
+fn default_max_output() -> usize {
+    128 * 1024
+}
+
+fn default_max_stdin() -> usize {
+    1024 * 1024
+}
+
+fn default_ssh_connect_timeout() -> u64 {
+    10
+}
+
+fn default_ssh_server_alive_interval() -> u64 {
+    15
+}
+
+fn default_ssh_server_alive_count_max() -> u64 {
+    2
+}
+
+fn default_strict_host_key_checking() -> bool {
+    true
+}
+
+fn default_ssh_program() -> String {
+    "ssh".to_string()
+}
+
+fn default_max_retries() -> u32 {
+    1
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    750
+}
+
+fn default_retry_backoff_max_ms() -> u64 {
+    30_000
+}
+
+fn default_observability_json_logs() -> bool {
+    true
+}
+
+fn default_dedup_in_flight() -> bool {
+    true
+}
+
+fn default_shutdown_drain_timeout() -> u64 {
+    30
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        let mut tools = HashMap::new();
+        tools.insert(
+            "nmap".to_string(),
+            ToolPolicy {
+                command: "/usr/bin/nmap".to_string(),
+                default_args: Vec::new(),
+                max_args: 12,
+                retry_policy: None,
+                strip_ansi: false,
+                strip_control_chars: false,
+                binary: false,
+                secrets: Vec::new(),
+                enabled: true,
+                env: BTreeMap::new(),
+                cwd: None,
+                run_as: None,
+                nice_level: None,
+                io_class: None,
+                cpu_percent: None,
+                max_mem: None,
+                kill_signal: default_kill_signal(),
+                kill_grace_sec: default_kill_grace(),
+                kill_escalate: default_kill_escalate(),
+                workspace: false,
+                artifacts: Vec::new(),
+                interactive: false,
+                profile: None,
+                max_rate: None,
+                capture: CaptureConfig::default(),
+                screenshots: ScreenshotConfig::default(),
+                hydra: HydraConfig::default(),
+                require_approval: false,
+                concurrency: None,
+                machine_output: None,
+                min_version: None,
+                min_version_action: VersionAction::Warn,
+                version_args: None,
+                description: None,
+                examples: Vec::new(),
+                arg_hints: BTreeMap::new(),
+            },
+        );
+        tools.insert(
+            "nikto".to_string(),
+            ToolPolicy {
+                command: "/usr/bin/nikto".to_string(),
+                default_args: Vec::new(),
+                max_args: 12,
+                retry_policy: None,
+                strip_ansi: false,
+                strip_control_chars: false,
+                binary: false,
+                secrets: Vec::new(),
+                enabled: true,
+                env: BTreeMap::new(),
+                cwd: None,
+                run_as: None,
+                nice_level: None,
+                io_class: None,
+                cpu_percent: None,
+                max_mem: None,
+                kill_signal: default_kill_signal(),
+                kill_grace_sec: default_kill_grace(),
+                kill_escalate: default_kill_escalate(),
+                workspace: false,
+                artifacts: Vec::new(),
+                interactive: false,
+                profile: None,
+                max_rate: None,
+                capture: CaptureConfig::default(),
+                screenshots: ScreenshotConfig::default(),
+                hydra: HydraConfig::default(),
+                require_approval: false,
+                concurrency: None,
+                machine_output: None,
+                min_version: None,
+                min_version_action: VersionAction::Warn,
+                version_args: None,
+                description: None,
+                examples: Vec::new(),
+                arg_hints: BTreeMap::new(),
+            },
+        );
+        tools.insert(
+            "sqlmap".to_string(),
+            ToolPolicy {
+                command: "/usr/bin/sqlmap".to_string(),
+                default_args: Vec::new(),
+                max_args: 12,
+                retry_policy: None,
+                strip_ansi: false,
+                strip_control_chars: false,
+                binary: false,
+                secrets: Vec::new(),
+                enabled: true,
+                env: BTreeMap::new(),
+                cwd: None,
+                run_as: None,
+                nice_level: None,
+                io_class: None,
+                cpu_percent: None,
+                max_mem: None,
+                kill_signal: default_kill_signal(),
+                kill_grace_sec: default_kill_grace(),
+                kill_escalate: default_kill_escalate(),
+                workspace: false,
+                artifacts: Vec::new(),
+                interactive: false,
+                profile: None,
+                max_rate: None,
+                capture: CaptureConfig::default(),
+                screenshots: ScreenshotConfig::default(),
+                hydra: HydraConfig::default(),
+                require_approval: false,
+                concurrency: None,
+                machine_output: None,
+                min_version: None,
+                min_version_action: VersionAction::Warn,
+                version_args: None,
+                description: None,
+                examples: Vec::new(),
+                arg_hints: BTreeMap::new(),
+            },
+        );
+        Self {
+            default_timeout_sec: default_timeout(),
+            max_timeout_sec: default_max_timeout(),
+            max_output_bytes: default_max_output(),
+            max_stdin_bytes: default_max_stdin(),
+            ssh_connect_timeout_sec: default_ssh_connect_timeout(),
+            ssh_server_alive_interval_sec: default_ssh_server_alive_interval(),
+            ssh_server_alive_count_max: default_ssh_server_alive_count_max(),
+            remote_timeout: RemoteTimeout::default(),
+            ssh_strict_host_key_checking: default_strict_host_key_checking(),
+            ssh_program: default_ssh_program(),
+            ssh_client: SshClient::default(),
+            host_keys: HostKeysConfig::default(),
+            ssh_identity: IdentityConfig::default(),
+            ssh_extra_options: Vec::new(),
+            ssh_host_options: BTreeMap::new(),
+            connection_sharing: ConnectionSharingConfig::default(),
+            bandwidth: BandwidthConfig::default(),
+            hooks: HooksConfig::default(),
+            plugins: PluginsConfig::default(),
+            scripting: ScriptingConfig::default(),
+            pipeline: PipelineConfig::default(),
+            labels: LabelsConfig::default(),
+            retention: RetentionConfig::default(),
+            encryption: EncryptionConfig::default(),
+            manifest: ManifestConfig::default(),
+            baselines: BaselinesConfig::default(),
+            findings_store: FindingsStoreConfig::default(),
+            cve: CveConfig::default(),
+            defectdojo: DefectDojoConfig::default(),
+            max_retries: default_max_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            retry_backoff_strategy: BackoffStrategy::default(),
+            retry_backoff_max_ms: default_retry_backoff_max_ms(),
+            retry_jitter_ratio: 0.0,
+            retry_policy: RetryPolicy::default(),
+            observability_json_logs: default_observability_json_logs(),
+            locale: Locale::default(),
+            logging: LoggingConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            cache: CacheConfig::default(),
+            dedup_in_flight: default_dedup_in_flight(),
+            shutdown_drain_timeout_sec: default_shutdown_drain_timeout(),
+            metrics_addr: None,
+            telemetry: TelemetryConfig::default(),
+            audit: AuditConfig::default(),
+            notifications: NotificationsConfig::default(),
+            artifacts: ArtifactsConfig::default(),
+            engagements: EngagementsConfig::default(),
+            control: ControlConfig::default(),
+            activity: ActivityConfig::default(),
+            liveness: LivenessConfig::default(),
+            health: HealthConfig::default(),
+            mcp: McpConfig::default(),
+            sampling: SamplingConfig::default(),
+            context_budget: ContextBudgetConfig::default(),
+            approvals: ApprovalsConfig::default(),
+            secrets: SecretsConfig::default(),
+            redaction: RedactionConfig::default(),
+            http: HttpConfig::default(),
+            rbac: RbacConfig::default(),
+            fs: FsConfig::default(),
+            interactive: InteractiveConfig::default(),
+            metasploit: MetasploitConfig::default(),
+            gvm: GvmConfig::default(),
+            wordlists: WordlistsConfig::default(),
+            targets: TargetsConfig::default(),
+            cracking: CrackingConfig::default(),
+            tools,
+        }
+    }
+}
+
+struct Runtime {
+    scheduler: Scheduler,
+    cancel: CancelSignal,
+    draining: CancelSignal,
+    cache: ResultCache<CacheKey, CollectedRun>,
+    in_flight: InFlight<CacheKey, CollectedRun>,
+    metrics: Metrics,
+    audit: Option<Arc<AuditLog>>,
+    notifier: Notifier,
+    artifacts: Option<ArtifactStore>,
+    findings_store: Option<FindingsStore>,
+    cves: Option<CveDatabase>,
+    sealer: Option<Sealer>,
+    control: Control,
+    sessions: Sessions,
+    terminals: Terminals,
+    metasploit: Option<Metasploit>,
+    gvm: Option<Gvm>,
+    approvals: Approvals,
+    outputs: Outputs,
+    budgets: Budgets,
+    connections: Leases,
+    bandwidth: Arc<Bucket>,
+    hooks: Hooks,
+    plugins: Plugins,
+    activity: Arc<Activity>,
+    tool_versions: ToolVersions,
+    circuits: Circuits,
+    config: std::sync::RwLock<Arc<BridgeConfig>>,
+    config_path: String,
+    tool_changes: watch::Sender<u64>,
+    run_prefix: String,
+}
+
+const STARTUP_SECTIONS: &[&str] = &[
+    "scheduler",
+    "cache",
+    "audit",
+    "notifications",
+    "artifacts",
+    "control",
+    "activity",
+    "liveness",
+    "secrets",
+    "redaction",
+    "http",
+    "telemetry",
+    "logging",
+    "interactive",
+    "metasploit",
+    "gvm",
+    "observability_json_logs",
+    "locale",
+    "metrics_addr",
+    "plugins",
+    "labels",
+    "encryption",
+    "baselines",
+    "findings_store",
+    "cve",
+];
+
+const TOOL_LIST_SECTIONS: &[&str] = &[
+    "tools",
+    "engagements",
+    "rbac",
+    "fs",
+    "wordlists",
+    "cracking",
+    "mcp",
+];
+
+impl Runtime {
+    fn new(config: &BridgeConfig, config_path: &str) -> Result<Self> {
+        validate_config(config)?;
+        redact::install(&config.redaction, known_secret_values(config))?;
+        messages::install(config.locale);
+        let (tool_changes, _) = watch::channel(0);
+        let audit = AuditLog::open(&config.audit)?.map(Arc::new);
+        let sealer = Sealer::open(&config.encryption)?;
+        let cves = CveDatabase::open(&config.cve)?;
+        if let Some(cves) = &cves {
+            log_observation(
+                "cve_database_loaded",
+                json!({"path": config.cve.database, "records": cves.len()}),
+            );
+        }
+        Ok(Self {
+            scheduler: Scheduler::new(&config.scheduler),
+            cancel: CancelSignal::new(),
+            draining: CancelSignal::new(),
+            cache: ResultCache::new(&config.cache),
+            in_flight: InFlight::new(),
+            metrics: Metrics::new(&config.labels.metric_keys),
+            terminals: Terminals::new(&config.interactive, audit.clone()),
+            audit,
+            metasploit: Metasploit::open(&config.metasploit)?,
+            gvm: Gvm::open(&config.gvm)?,
+            approvals: Approvals::new(),
+            outputs: Outputs::default(),
+            budgets: Budgets::default(),
+            connections: Leases::default(),
+            bandwidth: Arc::default(),
+            hooks: Hooks::new()?,
+            plugins: Plugins::load(&config.plugins)?,
+            activity: Activity::new(&config.activity),
+            tool_versions: ToolVersions::default(),
+            circuits: Circuits::default(),
+            notifier: Notifier::new(&config.notifications)?,
+            artifacts: ArtifactStore::open(&config.artifacts, sealer.clone())?,
+            findings_store: FindingsStore::open(&config.findings_store, sealer.clone()),
+            cves,
+            sealer,
+            control: Control::new(),
+            sessions: Sessions::new(&config.http.sessions),
+            config: std::sync::RwLock::new(Arc::new(config.clone())),
+            config_path: config_path.to_string(),
+            tool_changes,
+            run_prefix: session::session_id()?[..8].to_string(),
+        })
+    }
+
+    fn config(&self) -> Arc<BridgeConfig> {
+        self.config.read().expect("config poisoned").clone()
+    }
+
+    fn config_snapshot(&self) -> Result<Value> {
+        let mut config = serde_json::to_value(&*self.config())?;
+        bundle::redact_config(&mut config);
+        redact::redact_value(&mut config);
+        Ok(json!({"config_path": self.config_path, "config": config}))
+    }
+
+    fn reload_config(&self) -> Result<Value> {
+        let content = std::fs::read_to_string(&self.config_path).with_context(|| {
+            format!(
+                "Konfiguration {} konnte nicht gelesen werden",
+                self.config_path
+            )
+        })?;
+        let parsed: BridgeConfig =
+            serde_json::from_str(&content).context("config JSON konnte nicht geparst werden")?;
+        let mut loaded = serde_json::to_value(&parsed)?;
+
+        let mut current = self.config.write().expect("config poisoned");
+        let running = serde_json::to_value(&**current)?;
+        let mut restart_required = Vec::new();
+        for section in STARTUP_SECTIONS {
+            if loaded[*section] != running[*section] {
+                restart_required.push(*section);
+                loaded[*section] = running[*section].clone();
+            }
+        }
+        let tools_changed = TOOL_LIST_SECTIONS
+            .iter()
+            .any(|section| loaded[*section] != running[*section]);
+        let reloaded = serde_json::from_value::<BridgeConfig>(loaded)?;
+        validate_config(&reloaded)?;
+        rbac::validate(&reloaded.rbac, &reloaded.http.tokens)?;
+        let mut tools = reloaded.tools.keys().cloned().collect::<Vec<_>>();
+        tools.sort();
+        *current = Arc::new(reloaded);
+        drop(current);
+        if tools_changed {
+            self.notify_tool_changes();
+        }
+        Ok(json!({
+            "config_path": self.config_path,
+            "tools": tools,
+            "tools_changed": tools_changed,
+            "restart_required": restart_required
+        }))
+    }
+
+    fn notify_tool_changes(&self) {
+        self.tool_changes.send_modify(|generation| *generation += 1);
+        log_observation(
+            "tools_list_changed",
+            json!({"generation": *self.tool_changes.borrow()}),
+        );
+    }
+
+    fn set_tool_enabled(&self, tool: &str, enabled: bool) -> Result<Value> {
+        let mut current = self.config.write().expect("config poisoned");
+        let mut updated = (**current).clone();
+        let policy = updated
+            .tools
+            .get_mut(tool)
+            .ok_or_else(|| Message::NotConfigured {
+                key: format!("tools.{}", tool),
+            })?;
+        let changed = policy.enabled != enabled;
+        policy.enabled = enabled;
+        *current = Arc::new(updated);
+        drop(current);
+        if changed {
+            self.notify_tool_changes();
+        }
+        Ok(json!({"tool": tool, "enabled": enabled, "changed": changed}))
+    }
+
+    fn control_status(&self) -> Value {
+        json!({
+            "paused": self.control.is_paused(),
+            "kill_generation": self.control.kill_generation(),
+            "active_sessions": self.scheduler.active_sessions(),
+            "queue_depth": self.scheduler.queue_depth(),
+            "pending_approvals": self.approvals.count()
+        })
+    }
+
+    fn health(&self) -> Value {
+        let config = self.config();
+        let config_check = self.check_config_file();
+        let control_socket = config
+            .control
+            .socket_path
+            .as_ref()
+            .map(|path| json!({"path": path, "listening": control::listening(path)}));
+        let mut hosts = self.circuits.snapshot(&config.health);
+        for host in self.scheduler.hosts().as_array().into_iter().flatten() {
+            let name = host["host"].as_str().unwrap_or_default().to_string();
+            let entry = hosts
+                .entry(name.clone())
+                .or_insert_with(|| json!({"host": name, "circuit": "closed"}));
+            for key in ["active", "queued", "saturated", "exclusive"] {
+                entry[key] = host[key].clone();
+            }
+        }
+        let mut reasons = Vec::new();
+        if config_check.is_err() {
+            reasons.push("config_invalid");
+        }
+        if control_socket
+            .as_ref()
+            .is_some_and(|socket| socket["listening"] != true)
+        {
+            reasons.push("control_socket_down");
+        }
+        if self.control.is_paused() {
+            reasons.push("paused");
+        }
+        if self.draining.is_triggered() {
+            reasons.push("draining");
+        }
+        let open_circuits = hosts
+            .values()
+            .filter(|host| host["circuit"] == "open")
+            .count();
+        let status = if self.draining.is_triggered() {
+            "draining"
+        } else if self.control.is_paused() {
+            "paused"
+        } else if !reasons.is_empty() || open_circuits > 0 {
+            "degraded"
+        } else {
+            "ok"
+        };
+        json!({
+            "status": status,
+            "ready": reasons.is_empty(),
+            "reasons": reasons,
+            "version": env!("CARGO_PKG_VERSION"),
+            "config": {
+                "path": self.config_path,
+                "valid": config_check.is_ok(),
+                "error": config_check.err().map(|error| format!("{:#}", error))
+            },
+            "control_socket": control_socket,
+            "queue": self.control_status(),
+            "hosts": hosts.into_values().collect::<Vec<_>>()
+        })
+    }
+
+    fn check_config_file(&self) -> Result<()> {
+        let content = match std::fs::read_to_string(&self.config_path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!(
+                        "Konfiguration {} konnte nicht gelesen werden",
+                        self.config_path
+                    )
+                });
+            }
+        };
+        let parsed: BridgeConfig =
+            serde_json::from_str(&content).context("config JSON konnte nicht geparst werden")?;
+        validate_config(&parsed)?;
+        rbac::validate(&parsed.rbac, &parsed.http.tokens)
+    }
+
+    fn render_metrics(&self) -> String {
+        self.metrics.render(&Gauges {
+            active_sessions: self.scheduler.active_sessions(),
+            queue_depth: self.scheduler.queue_depth(),
+        })
+    }
+}
+
+fn known_secret_values(config: &BridgeConfig) -> Vec<(String, String)> {
+    let replacement = config.redaction.replacement.clone();
+    let mut values = secrets::known_values(&config.secrets);
+    if let Some(s3) = &config.artifacts.s3 {
+        let credentials = [
+            s3.secret_access_key
+                .clone()
+                .or_else(|| std::env::var("AWS_SECRET_ACCESS_KEY").ok()),
+            s3.session_token
+                .clone()
+                .or_else(|| std::env::var("AWS_SESSION_TOKEN").ok()),
+        ];
+        values.extend(
+            credentials
+                .into_iter()
+                .flatten()
+                .map(|value| (value, replacement.clone())),
+        );
+    }
+    for webhook in &config.notifications.webhooks {
+        values.extend(
+            webhook
+                .headers
+                .values()
+                .map(|value| (value.clone(), replacement.clone())),
+        );
+    }
+    if let Some(token) = config
+        .defectdojo
+        .token
+        .as_ref()
+        .filter(|token| !token.contains("{{"))
+    {
+        values.push((token.clone(), replacement.clone()));
+    }
+    values
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    client: Option<String>,
+    policy_sha256: String,
+    target: String,
+    tool: String,
+    args: Vec<String>,
+    timeout_sec: u64,
+    max_output_bytes: usize,
+    strip_ansi: bool,
+    strip_control_chars: bool,
+    include_pattern: Option<String>,
+    exclude_pattern: Option<String>,
+    engagement: Option<String>,
+    stdin: Option<StdinPayload>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ServeProtocol {
+    Events,
+    JsonRpc,
+}
+
+enum Incoming {
+    Line(String),
+    Closed,
+    Shutdown(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum StreamMode {
+    #[default]
+    Chunks,
+    Lines,
+}
+
+const MAX_LINE_BYTES: usize = 16 * 1024;
+const SUDO_DENIED_EXIT: i32 = 250;
+const REMOTE_KILL_SLACK_SEC: u64 = 2;
+const SSH_ERROR_HEAD_BYTES: usize = 4096;
+const REMOTE_TRANSFER_TIMEOUT_SEC: u64 = 60;
+const TOP_FINDINGS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StdinPayload {
+    Text(String),
+    Base64 { base64: String },
+}
+
+impl StdinPayload {
+    fn bytes(&self, secrets: &SecretValues) -> Result<Vec<u8>> {
+        match self {
+            StdinPayload::Text(text) => Ok(secrets.substitute(text).into_bytes()),
+            StdinPayload::Base64 { base64 } => {
+                BASE64
+                    .decode(base64.trim())
+                    .context(Message::InvalidEncoding {
+                        field: "stdin.base64",
+                    })
+            }
+        }
+    }
+
+    fn text(&self) -> Option<&String> {
+        match self {
+            StdinPayload::Text(text) => Some(text),
+            StdinPayload::Base64 { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunRequest {
+    id: Option<String>,
+    host: String,
+    user: Option<String>,
+    tool: String,
+    #[serde(default)]
+    args: Vec<String>,
+    timeout_sec: Option<u64>,
+    max_output_bytes: Option<usize>,
+    #[serde(default)]
+    stream_mode: StreamMode,
+    strip_ansi: Option<bool>,
+    strip_control_chars: Option<bool>,
+    #[serde(default)]
+    include_pattern: Option<String>,
+    #[serde(default)]
+    exclude_pattern: Option<String>,
+    #[serde(default)]
+    no_cache: bool,
+    engagement: Option<String>,
+    #[serde(default)]
+    stdin: Option<StdinPayload>,
+    #[serde(default)]
+    params: BTreeMap<String, Value>,
+    #[serde(default)]
+    labels: Labels,
+    #[serde(default)]
+    manifest: bool,
+    #[serde(skip)]
+    client: Option<String>,
+    #[serde(skip)]
+    output_root: Option<PathBuf>,
+    #[serde(skip)]
+    session: Option<String>,
+    #[serde(skip)]
+    replay_of: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct WorkflowRequest {
+    id: Option<String>,
+    host: String,
+    user: Option<String>,
+    engagement: Option<String>,
+    #[serde(default)]
+    labels: Labels,
+    #[serde(skip)]
+    client: Option<String>,
+    #[serde(default = "default_stop_on_error")]
+    stop_on_error: bool,
+    #[serde(default)]
+    vars: serde_json::Map<String, Value>,
+    steps: Vec<WorkflowStep>,
+}
+
+fn default_stop_on_error() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct WorkflowStep {
+    tool: String,
+    #[serde(default)]
+    args: Vec<String>,
+    timeout_sec: Option<u64>,
+    max_output_bytes: Option<usize>,
+    strip_ansi: Option<bool>,
+    strip_control_chars: Option<bool>,
+    #[serde(default)]
+    include_pattern: Option<String>,
+    #[serde(default)]
+    exclude_pattern: Option<String>,
+    #[serde(default)]
+    no_cache: bool,
+    #[serde(default)]
+    stdin: Option<StdinPayload>,
+    #[serde(default)]
+    params: BTreeMap<String, Value>,
+    #[serde(default)]
+    labels: Labels,
+    for_each: Option<String>,
+    when: Option<String>,
+    script: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcRequest {
+    id: Option<Value>,
+    method: String,
+    params: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct McpCallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+    #[serde(default, rename = "_meta")]
+    meta: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct McpToolArguments {
+    host: Option<String>,
+    user: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    timeout_sec: Option<u64>,
+    max_output_bytes: Option<usize>,
+    strip_ansi: Option<bool>,
+    strip_control_chars: Option<bool>,
+    #[serde(default)]
+    include_pattern: Option<String>,
+    #[serde(default)]
+    exclude_pattern: Option<String>,
+    #[serde(default)]
+    no_cache: bool,
+    engagement: Option<String>,
+    #[serde(default)]
+    stdin: Option<StdinPayload>,
+    #[serde(default)]
+    labels: Labels,
+    #[serde(default)]
+    manifest: bool,
+    #[serde(flatten)]
+    params: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -238,932 +1802,8765 @@ struct Event {
     payload: serde_json::Value,
 }
 
-#[derive(Debug)]
-struct FinalStatus {
-    exit_code: Option<i32>,
-    timed_out: bool,
-    duration_ms: u128,
+#[derive(Debug, Clone)]
+struct FinalStatus {
+    exit_code: Option<i32>,
+    timed_out: bool,
+    duration_ms: u128,
+    privileged: bool,
+    connect_failed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct CollectedRun {
+    final_status: FinalStatus,
+    stdout: String,
+    stderr: String,
+    encoding: OutputEncoding,
+    lossy: bool,
+    truncated: bool,
+    attempts: u32,
+    cached: bool,
+    deduplicated: bool,
+    artifacts: Vec<String>,
+    findings: Option<Vec<Finding>>,
+    summary: Option<Value>,
+    images: Vec<Image>,
+    manifest: Option<Manifest>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum OutputEncoding {
+    Utf8,
+    Base64,
+}
+
+#[derive(Debug)]
+enum Chunk {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+}
+
+impl Chunk {
+    fn data(&self) -> &[u8] {
+        match self {
+            Chunk::Stdout(data) | Chunk::Stderr(data) => data,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data().is_empty()
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Commands::Run(args) => {
+            let config = load_config(&args.config).await?;
+            let telemetry = telemetry::init(
+                &config.telemetry,
+                &config.logging,
+                config.observability_json_logs,
+            )?;
+            let runtime = Runtime::new(&config, &args.config)?;
+            let stdin = match &args.stdin_file {
+                Some(path) => {
+                    let data = std::fs::read(path).with_context(|| {
+                        format!("stdin-Datei {} konnte nicht gelesen werden", path)
+                    })?;
+                    Some(match String::from_utf8(data) {
+                        Ok(text) => StdinPayload::Text(text),
+                        Err(error) => StdinPayload::Base64 {
+                            base64: BASE64.encode(error.into_bytes()),
+                        },
+                    })
+                }
+                None => None,
+            };
+            let params = match &args.params {
+                Some(params) => {
+                    serde_json::from_str(params).context("--params muss ein JSON-Objekt sein")?
+                }
+                None => BTreeMap::new(),
+            };
+            let labels = args
+                .labels
+                .iter()
+                .map(|pair| labels::parse_pair(pair))
+                .collect::<Result<Labels>>()?;
+            let request = RunRequest {
+                id: Some("cli-run".to_string()),
+                host: args.host,
+                user: args.user,
+                tool: args.tool,
+                args: args.args,
+                timeout_sec: args.timeout_sec,
+                max_output_bytes: args.max_output_bytes,
+                stream_mode: args.stream_mode,
+                strip_ansi: args.strip_ansi.then_some(true),
+                strip_control_chars: args.strip_control_chars.then_some(true),
+                include_pattern: args.include_pattern,
+                exclude_pattern: args.exclude_pattern,
+                no_cache: false,
+                engagement: args.engagement,
+                stdin,
+                params,
+                labels,
+                manifest: args.manifest,
+                client: None,
+                output_root: None,
+                session: None,
+                replay_of: None,
+            };
+            let id = request.id.clone();
+            let (mut out, mut rx) = channel_writer();
+            let run = async {
+                let result = run_request(&config, &runtime, request, &mut out).await;
+                drop(out);
+                result
+            };
+            let mut renderer = Renderer::new(args.format);
+            let (result, rendered) =
+                tokio::join!(run, print_events(&mut rx, |event| renderer.event(event)));
+            rendered?;
+            let code = match &result {
+                Ok(status) => render::exit_code(status.exit_code, status.timed_out),
+                Err(error) => {
+                    render::print(renderer.event(json!({
+                        "id": id,
+                        "event": "error",
+                        "payload": {"code": error_code(error), "message": format!("{:#}", error)}
+                    })))?;
+                    if renderer.started() {
+                        render::EXIT_BRIDGE_ERROR
+                    } else {
+                        render::EXIT_REJECTED
+                    }
+                }
+            };
+            render::print(renderer.finish())?;
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Commands::Serve(args) => {
+            let config_path = args.serve.config.clone();
+            let (mut config, runtime, telemetry) = prepare_serve(args.serve).await?;
+            let result = match args.rest {
+                Some(rest) => {
+                    Arc::make_mut(&mut config).http.listen = Some(rest);
+                    let store = Arc::new(RunStore::new(&config.http.rest));
+                    serve_http(config, runtime.clone(), &config_path, HttpApi::Rest(store)).await
+                }
+                None => serve_stdio(config, runtime.clone()).await,
+            };
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            result?;
+        }
+        Commands::McpServe(args) => {
+            let (config, runtime, telemetry) = prepare_serve(args.serve).await?;
+            let result = serve_mcp_stdio(config, runtime.clone(), args.framing).await;
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            result?;
+        }
+        Commands::WorkflowServe(args) => {
+            let (config, runtime, telemetry) = prepare_serve(args).await?;
+            let result = serve_workflow_stdio(config, runtime.clone()).await;
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            result?;
+        }
+        Commands::Workflow(args) => match args.action {
+            WorkflowAction::Run(args) => {
+                let workflow = workflow_from_file(&args)?;
+                let config = load_config(&args.config).await?;
+                let telemetry = telemetry::init(
+                    &config.telemetry,
+                    &config.logging,
+                    config.observability_json_logs,
+                )?;
+                let runtime = Runtime::new(&config, &args.config)?;
+                let (mut out, mut rx) = channel_writer();
+                let run = async {
+                    let result = run_workflow(&config, &runtime, workflow, &mut out).await;
+                    drop(out);
+                    result
+                };
+                let mut progress = WorkflowProgress::new(args.format);
+                let (result, rendered) =
+                    tokio::join!(run, print_events(&mut rx, |event| progress.event(event)));
+                runtime.notifier.flush().await;
+                telemetry.shutdown();
+                result?;
+                rendered?;
+                let code = progress.exit_code();
+                render::print(progress.finish())?;
+                if code != 0 {
+                    std::process::exit(code);
+                }
+            }
+        },
+        Commands::HttpServe(args) => {
+            let config_path = args.serve.config.clone();
+            let listen = args.listen;
+            let (mut config, runtime, telemetry) = prepare_serve(args.serve).await?;
+            if listen.is_some() {
+                Arc::make_mut(&mut config).http.listen = listen;
+            }
+            let result = serve_http(config, runtime.clone(), &config_path, HttpApi::Mcp).await;
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            result?;
+        }
+        Commands::Console(args) => {
+            let defaults = console::Defaults {
+                host: args.host,
+                user: args.user,
+                engagement: args.engagement,
+                timeout_sec: None,
+            };
+            let (_, runtime, telemetry) = prepare_serve(args.serve).await?;
+            let result = serve_console(runtime.clone(), defaults).await;
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            result?;
+        }
+        Commands::HttpToken(args) => println!(
+            "{}",
+            serde_json::to_string_pretty(&http::generate_token(&args.identity)?)?
+        ),
+        Commands::VerifyAudit(args) => verify_audit(args).await?,
+        Commands::AuditKeygen(args) => {
+            let public_key = audit::generate_signing_key(Path::new(&args.out))?;
+            println!(
+                "{}",
+                json!({"signing_key_path": args.out, "public_key": audit::encode_public_key(&public_key)})
+            );
+        }
+        Commands::Stats(args) => print_stats(args).await?,
+        Commands::Timeline(args) => print_timeline(args).await?,
+        Commands::Tools(args) => print_tools(args).await?,
+        Commands::ExportBundle(args) => export_bundle(args).await?,
+        Commands::ExportFindings(args) => export_findings(args).await?,
+        Commands::Export(args) => export_records(args).await?,
+        Commands::Engagement(args) => {
+            let config = load_config(&args.config).await?;
+            let result = engagement::apply(&config.engagements, args.action)?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        Commands::Control(args) => {
+            let config = load_config(&args.config).await?;
+            let socket = args
+                .socket
+                .or(config.control.socket_path)
+                .context("kein Control-Socket angegeben (--socket oder control.socket_path)")?;
+            if let ControlCommand::Tail { after, follow } = args.command {
+                return tail_events(&socket, after, follow).await;
+            }
+            let status = control::send(&socket, &args.command).await?;
+            println!("{}", serde_json::to_string_pretty(&status)?);
+            if matches!(args.command, ControlCommand::Health) && status["ready"] != true {
+                std::process::exit(1);
+            }
+        }
+        Commands::Monitor(args) => {
+            let config = load_config(&args.config).await?;
+            let socket = args
+                .socket
+                .or(config.control.socket_path)
+                .context("kein Control-Socket angegeben (--socket oder control.socket_path)")?;
+            control::send(&socket, &ControlCommand::Status).await?;
+            monitor::run(&socket, Duration::from_millis(args.interval_ms.max(100))).await?;
+        }
+        Commands::Events(args) => match args.action {
+            EventsAction::Tail(args) => {
+                let config = load_config(&args.config).await?;
+                let path = args
+                    .path
+                    .or(config.audit.path)
+                    .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+                let filter = events::Filter {
+                    run: args.run,
+                    tool: args.tool,
+                    host: args.host,
+                    kinds: args.kinds,
+                };
+                events::tail(
+                    Path::new(&path),
+                    &filter,
+                    args.last,
+                    args.follow,
+                    args.format,
+                )
+                .await?;
+            }
+        },
+        Commands::Secrets(args) => {
+            let config = load_config(&args.config).await?;
+            let result = secrets::apply(&config.secrets, args.action)?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        Commands::Encryption(args) => {
+            let config = load_config(&args.config).await?;
+            let result = encryption::apply(&config.encryption, args.action)?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        Commands::TrustHost(args) => trust_host(args).await?,
+        Commands::Purge(args) => purge(args).await?,
+        Commands::Replay(args) => replay(args).await?,
+        Commands::Baseline(args) => {
+            let config = load_config(&args.config).await?;
+            let telemetry = telemetry::init(
+                &config.telemetry,
+                &config.logging,
+                config.observability_json_logs,
+            )?;
+            let runtime = Runtime::new(&config, &args.config)?;
+            let result = run_baseline(&runtime, &args.name).await;
+            runtime.notifier.flush().await;
+            telemetry.shutdown();
+            println!("{}", serde_json::to_string_pretty(&result?)?);
+        }
+        Commands::Findings(args) => print_findings(args).await?,
+        Commands::PrintSchema => print_schema()?,
+    }
+    Ok(())
+}
+
+async fn tail_events(socket: &str, mut after: Option<u64>, follow: bool) -> Result<()> {
+    loop {
+        let status = control::send(
+            socket,
+            &ControlCommand::Tail {
+                after,
+                follow: false,
+            },
+        )
+        .await?;
+        for event in status["events"].as_array().into_iter().flatten() {
+            println!("{}", event);
+        }
+        after = status["last_seq"].as_u64();
+        if !follow {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+fn workflow_from_file(args: &WorkflowRunArgs) -> Result<WorkflowRequest> {
+    let content = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("Workflow-Datei {} konnte nicht gelesen werden", args.file))?;
+    let mut workflow: Value = if args.file.ends_with(".json") {
+        serde_json::from_str(&content)
+            .with_context(|| format!("Workflow-Datei {} ist kein gültiges JSON", args.file))?
+    } else {
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Workflow-Datei {} ist kein gültiges YAML", args.file))?
+    };
+    let Some(fields) = workflow.as_object_mut() else {
+        bail!(
+            "Workflow-Datei {} muss ein Objekt mit steps enthalten",
+            args.file
+        );
+    };
+    fields
+        .entry("id")
+        .or_insert_with(|| json!(format!("workflow-{}", manifest::now_ms())));
+    for (key, value) in [
+        ("host", &args.host),
+        ("user", &args.user),
+        ("engagement", &args.engagement),
+    ] {
+        if let Some(value) = value {
+            fields.insert(key.to_string(), json!(value));
+        }
+    }
+    let vars = fields.entry("vars").or_insert_with(|| json!({}));
+    for var in &args.vars {
+        let Some((name, value)) = var.split_once('=') else {
+            bail!("ungültige Variable '{}', erwartet name=wert", var);
+        };
+        let Some(vars) = vars.as_object_mut() else {
+            bail!("vars in {} muss ein Objekt sein", args.file);
+        };
+        vars.insert(name.to_string(), json!(value));
+    }
+    let labels = fields.entry("labels").or_insert_with(|| json!({}));
+    for label in &args.labels {
+        let (key, value) = labels::parse_pair(label)?;
+        let Some(labels) = labels.as_object_mut() else {
+            bail!("labels in {} muss ein Objekt sein", args.file);
+        };
+        labels.insert(key, json!(value));
+    }
+    if !fields.contains_key("host") {
+        bail!(
+            "Workflow-Datei {} enthält keinen host, --host angeben",
+            args.file
+        );
+    }
+    serde_json::from_value(workflow)
+        .with_context(|| format!("Workflow-Datei {} ist ungültig", args.file))
+}
+
+async fn print_events(
+    rx: &mut mpsc::UnboundedReceiver<Vec<u8>>,
+    mut render: impl FnMut(Value) -> Vec<render::Output>,
+) -> Result<()> {
+    let mut pending = Vec::new();
+    while let Some(chunk) = rx.recv().await {
+        pending.extend(chunk);
+        while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+            let line = pending.drain(..=end).collect::<Vec<_>>();
+            if let Ok(event) = serde_json::from_slice::<Value>(&line) {
+                render::print(render(event))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn load_config(path: &str) -> Result<BridgeConfig> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => {
+            let cfg: BridgeConfig = serde_json::from_str(&content)
+                .context("config JSON konnte nicht geparst werden")?;
+            Ok(cfg)
+        }
+        Err(_) => Ok(BridgeConfig::default()),
+    }
+}
+
+async fn verify_audit(args: VerifyAuditArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .or(config.audit.path)
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let public_key = match (&args.public_key, &config.audit.signing_key_path) {
+        (Some(encoded), _) => Some(audit::decode_public_key(encoded)?),
+        (None, Some(key_path)) => Some(audit::load_signing_key(key_path)?.verifying_key()),
+        (None, None) => None,
+    };
+
+    let report = audit::verify(Path::new(&path), public_key.as_ref())?;
+    println!(
+        "{}",
+        json!({
+            "valid": true,
+            "signatures_checked": public_key.is_some(),
+            "report": report
+        })
+    );
+    Ok(())
+}
+
+async fn trust_host(args: TrustHostArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let file = match (&config.host_keys.known_hosts_file, config.ssh_client) {
+        (Some(file), _) => Some(file.clone()),
+        (None, SshClient::Plink) => None,
+        (None, SshClient::Openssh) => bail!("host_keys.known_hosts_file ist nicht gesetzt"),
+    };
+    let address = args.address.as_deref().unwrap_or(&args.host);
+    let keys = hostkeys::scan(
+        address,
+        args.port,
+        Duration::from_secs(config.ssh_connect_timeout_sec),
+    )
+    .await?;
+    let pins = config.host_keys.pins(&args.host);
+    for key in &keys {
+        let state = match pins {
+            Some(pins) if pins.contains(&key.fingerprint) => "passt zum Pin",
+            Some(_) => "passt NICHT zum Pin",
+            None => "kein Pin",
+        };
+        eprintln!(
+            "{} {} {} ({})",
+            args.host, key.key_type, key.fingerprint, state
+        );
+    }
+    let keys = keys
+        .into_iter()
+        .filter(|key| pins.is_none_or(|pins| pins.contains(&key.fingerprint)))
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        bail!(
+            "kein Schlüssel von {} passt zu host_keys.pins.{}",
+            address,
+            args.host
+        );
+    }
+    if let Some(file) = &file {
+        if !args.yes {
+            eprint!(
+                "{} Schlüssel für '{}' in {} übernehmen? [j/N] ",
+                keys.len(),
+                args.host,
+                file
+            );
+            let mut answer = String::new();
+            BufReader::new(io::stdin()).read_line(&mut answer).await?;
+            if !matches!(
+                answer.trim().to_lowercase().as_str(),
+                "j" | "ja" | "y" | "yes"
+            ) {
+                bail!("abgebrochen, {} bleibt unverändert", file);
+            }
+        }
+        hostkeys::store(Path::new(file), &args.host, &keys)?;
+    }
+    let fingerprints = keys
+        .iter()
+        .map(|key| key.fingerprint.clone())
+        .collect::<Vec<_>>();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({
+            "host": args.host,
+            "known_hosts_file": file,
+            "keys": keys.iter().map(|key| json!({"type": key.key_type, "fingerprint": key.fingerprint})).collect::<Vec<_>>(),
+            "pin": {"host_keys": {"pins": {args.host.clone(): fingerprints}}}
+        }))?
+    );
+    Ok(())
+}
+
+async fn print_stats(args: StatsArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .or(config.audit.path)
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let since_ms = match &args.since {
+        Some(window) => {
+            let now_ms = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|value| value.as_millis())
+                .unwrap_or(0);
+            Some(now_ms.saturating_sub(stats::parse_window(window)?))
+        }
+        None => None,
+    };
+
+    let label_filters = args
+        .labels
+        .iter()
+        .map(|pair| labels::parse_pair(pair))
+        .collect::<Result<Vec<_>>>()?;
+    let records = audit::read_records(Path::new(&path))?;
+    let summary = stats::summarize(
+        &records,
+        since_ms,
+        args.engagement.as_deref(),
+        &label_filters,
+        args.top_hosts,
+    );
+    match args.format {
+        TableFormat::Table => print!("{}", stats::render_table(&summary)),
+        TableFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+    }
+    Ok(())
+}
+
+async fn print_timeline(args: TimelineArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .or(config.audit.path.clone())
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let now_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0);
+    let since_ms = args
+        .since
+        .as_deref()
+        .map(|value| export::parse_instant(value, now_ms))
+        .transpose()?;
+    let until_ms = args
+        .until
+        .as_deref()
+        .map(|value| export::parse_instant(value, now_ms))
+        .transpose()?;
+    let registry = engagement::Registry::load(&config.engagements.path)?;
+    let sealer = Sealer::open(&config.encryption)?;
+    let records = audit::read_records(Path::new(&path))?;
+    let timeline = timeline::build(
+        &records,
+        &args.engagement,
+        registry.engagements.get(&args.engagement),
+        since_ms,
+        until_ms,
+        sealer.as_ref(),
+    )?;
+    match args.format {
+        TimelineFormat::Table => print!("{}", timeline::render_table(&timeline)),
+        TimelineFormat::Markdown => print!("{}", timeline::render_markdown(&timeline)),
+        TimelineFormat::Json => println!("{}", serde_json::to_string_pretty(&timeline)?),
+    }
+    Ok(())
+}
+
+async fn print_findings(args: FindingsArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = config
+        .findings_store
+        .path
+        .clone()
+        .context("kein Findings-Store konfiguriert (findings_store.path)")?;
+    let sealer = Sealer::open(&config.encryption)?;
+    let query = |filter: FindingsFilter| -> Result<findings_store::Query> {
+        let min_severity = match &filter.min_severity {
+            Some(value) => Some(
+                findings::Severity::parse(value)
+                    .with_context(|| format!("unbekannter Schweregrad '{}'", value))?,
+            ),
+            None => None,
+        };
+        Ok(findings_store::Query {
+            host: filter.host,
+            engagement: filter.engagement,
+            min_severity,
+        })
+    };
+    let cutoff = |window: &str| -> Result<u128> {
+        let now_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|value| value.as_millis())
+            .unwrap_or(0);
+        Ok(now_ms.saturating_sub(stats::parse_window(window)?))
+    };
+    match args.action {
+        FindingsAction::List {
+            filter,
+            since,
+            format,
+        } => {
+            let since_ms = since.as_deref().map(cutoff).transpose()?;
+            let entries =
+                findings_store::list(Path::new(&path), sealer.as_ref(), &query(filter)?, since_ms)?;
+            match format {
+                TableFormat::Table => print!("{}", findings_store::render_table(&entries)),
+                TableFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+            }
+        }
+        FindingsAction::Diff {
+            filter,
+            since,
+            format,
+        } => {
+            let entries =
+                findings_store::list(Path::new(&path), sealer.as_ref(), &query(filter)?, None)?;
+            let diff = findings_store::diff(entries, cutoff(&since)?);
+            match format {
+                TableFormat::Table => {
+                    for (title, entries) in [
+                        ("Neu", &diff.new),
+                        ("Wiederkehrend", &diff.recurring),
+                        ("Nicht mehr gesehen", &diff.stale),
+                    ] {
+                        println!("{} ({})", title, entries.len());
+                        if !entries.is_empty() {
+                            print!("{}", findings_store::render_table(entries));
+                        }
+                        println!();
+                    }
+                }
+                TableFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn print_tools(args: ToolsArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    match args.action {
+        ToolsAction::List { client, format } => {
+            let mut tools = config
+                .tools
+                .iter()
+                .filter(|(name, _)| rbac::allows_tool(&config.rbac, client.as_deref(), name))
+                .collect::<Vec<_>>();
+            tools.sort_by_key(|(name, _)| *name);
+            let tools = tools
+                .into_iter()
+                .map(|(name, policy)| describe_tool(&config, name, policy))
+                .collect::<Vec<_>>();
+            match format {
+                TableFormat::Table => {
+                    println!(
+                        "{:<16} {:<24} {:<6} {:<10} {:>8}  {:<24} HINWEISE",
+                        "TOOL", "COMMAND", "AKTIV", "PROFIL", "MAX_ARGS", "DEFAULT_ARGS"
+                    );
+                    for tool in &tools {
+                        println!(
+                            "{:<16} {:<24} {:<6} {:<10} {:>8}  {:<24} {}",
+                            tool["name"].as_str().unwrap_or_default(),
+                            tool["command"].as_str().unwrap_or_default(),
+                            if tool["enabled"] == true {
+                                "ja"
+                            } else {
+                                "nein"
+                            },
+                            tool["policy"]["profile"].as_str().unwrap_or("-"),
+                            tool["max_args"].as_u64().unwrap_or_default(),
+                            join_strings(&tool["default_args"], " "),
+                            tool_hints(tool)
+                        );
+                    }
+                }
+                TableFormat::Json => println!("{}", serde_json::to_string_pretty(&tools)?),
+            }
+        }
+        ToolsAction::Describe { name, format } => {
+            let policy = config
+                .tools
+                .get(&name)
+                .with_context(|| format!("tool '{}' ist nicht konfiguriert", name))?;
+            let tool = describe_tool(&config, &name, policy);
+            match format {
+                TableFormat::Table => {
+                    println!("Tool:          {}", name);
+                    println!(
+                        "Beschreibung:  {}",
+                        tool["description"].as_str().unwrap_or_default()
+                    );
+                    println!("Befehl:        {}", policy.command);
+                    println!(
+                        "Aktiv:         {}",
+                        if policy.enabled { "ja" } else { "nein" }
+                    );
+                    println!(
+                        "Profil:        {}",
+                        policy.profile.map_or("-", ToolProfile::as_str)
+                    );
+                    println!(
+                        "Default-Args:  {}",
+                        join_strings(&tool["default_args"], " ")
+                    );
+                    println!("Max. Args:     {}", policy.max_args);
+                    println!("Hinweise:      {}", tool_hints(&tool));
+                    println!("Limits:");
+                    for (key, value) in tool["limits"].as_object().into_iter().flatten() {
+                        if !value.is_null() {
+                            println!(
+                                "  {:<22} {}",
+                                key,
+                                value
+                                    .as_str()
+                                    .map_or_else(|| value.to_string(), str::to_string)
+                            );
+                        }
+                    }
+                    println!("Parameter:");
+                    let required = &tool["inputSchema"]["required"];
+                    for (key, schema) in tool["inputSchema"]["properties"]
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                    {
+                        let kind = schema["type"].as_str().unwrap_or("beliebig");
+                        let mark = if required
+                            .as_array()
+                            .is_some_and(|required| required.contains(&json!(key)))
+                        {
+                            ", Pflicht"
+                        } else {
+                            ""
+                        };
+                        println!("  {:<22} {}{}", key, kind, mark);
+                    }
+                }
+                TableFormat::Json => println!("{}", serde_json::to_string_pretty(&tool)?),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn describe_tool(config: &BridgeConfig, name: &str, policy: &ToolPolicy) -> Value {
+    let definition = tool_definition(name, policy, &json!(["host"]), Vec::new());
+    json!({
+        "name": name,
+        "description": definition["description"],
+        "command": policy.command,
+        "default_args": policy.default_args,
+        "max_args": policy.max_args,
+        "enabled": policy.enabled,
+        "limits": {
+            "default_timeout_sec": config.default_timeout_sec,
+            "max_timeout_sec": config.max_timeout_sec,
+            "max_output_bytes": config.max_output_bytes,
+            "max_stdin_bytes": config.max_stdin_bytes,
+            "max_rate": policy.max_rate,
+            "concurrency": policy.concurrency,
+            "nice_level": policy.nice_level,
+            "io_class": policy.io_class,
+            "cpu_percent": policy.cpu_percent,
+            "max_mem": policy.max_mem,
+            "kill_signal": policy.kill_signal,
+            "kill_grace_sec": policy.kill_grace_sec
+        },
+        "annotations": {
+            "requires_approval": needs_approval(policy),
+            "privileged": policy.run_as.is_some(),
+            "interactive": policy.interactive,
+            "binary": policy.binary,
+            "workspace": policy.workspace,
+            "secrets": !policy.secrets.is_empty()
+        },
+        "policy": policy,
+        "inputSchema": definition["inputSchema"]
+    })
+}
+
+fn tool_hints(tool: &Value) -> String {
+    let hints = tool["annotations"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, value)| **value == true)
+        .map(|(key, _)| match key.as_str() {
+            "privileged" => format!(
+                "run_as={}",
+                tool["policy"]["run_as"].as_str().unwrap_or_default()
+            ),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>();
+    if hints.is_empty() {
+        "-".to_string()
+    } else {
+        hints.join(", ")
+    }
+}
+
+fn join_strings(values: &Value, separator: &str) -> String {
+    let values = values
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        "-".to_string()
+    } else {
+        values.join(separator)
+    }
+}
+
+async fn export_bundle(args: ExportBundleArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .clone()
+        .or(config.audit.path.clone())
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let out = args
+        .out
+        .unwrap_or_else(|| format!("bundle-{}.zip", artifacts::key_segment(&args.run_id)));
+    let sealer = Sealer::open(&config.encryption)?;
+    let store = ArtifactStore::open(&config.artifacts, sealer.clone())?;
+
+    let records = audit::read_records(Path::new(&path))?;
+    let report = bundle::export(
+        &args.run_id,
+        &records,
+        serde_json::to_value(&config)?,
+        &engagement::Registry::load(&config.engagements.path)?,
+        store.as_ref(),
+        sealer.as_ref(),
+        Path::new(&out),
+    )
+    .await?;
+    println!("{}", json!({"bundle": out, "report": report}));
+    Ok(())
+}
+
+async fn export_findings(args: ExportFindingsArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .or(config.audit.path.clone())
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let selection = match (args.run, args.engagement) {
+        (Some(run), _) => defectdojo::Selection::Run(run),
+        (None, Some(engagement)) => defectdojo::Selection::Engagement(engagement),
+        (None, None) => unreachable!("clap verlangt --run oder --engagement"),
+    };
+    let sealer = Sealer::open(&config.encryption)?;
+    let records = audit::read_records(Path::new(&path))?;
+    let report = defectdojo::export(
+        &config.defectdojo,
+        &config.secrets,
+        &records,
+        &selection,
+        sealer.as_ref(),
+        args.dry_run,
+    )
+    .await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+async fn export_records(args: ExportArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .or(config.audit.path.clone())
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let columns = export::columns(args.kind, &args.columns)?;
+    let now_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0);
+    let min_severity = match &args.min_severity {
+        Some(value) => Some(
+            findings::Severity::parse(value)
+                .with_context(|| format!("unbekannter Schweregrad '{}'", value))?,
+        ),
+        None => None,
+    };
+    let filter = export::Filter {
+        since_ms: args
+            .since
+            .as_deref()
+            .map(|value| export::parse_instant(value, now_ms))
+            .transpose()?,
+        until_ms: args
+            .until
+            .as_deref()
+            .map(|value| export::parse_instant(value, now_ms))
+            .transpose()?,
+        host: args.host,
+        tool: args.tool,
+        engagement: args.engagement,
+        min_severity,
+    };
+    let sealer = Sealer::open(&config.encryption)?;
+    let records = audit::read_records(Path::new(&path))?;
+    match &args.out {
+        Some(out) => {
+            let mut file =
+                std::io::BufWriter::new(std::fs::File::create(out).with_context(|| {
+                    format!("Exportdatei {} konnte nicht angelegt werden", out)
+                })?);
+            let rows = export::export(
+                &records,
+                args.kind,
+                &filter,
+                &columns,
+                args.format,
+                sealer.as_ref(),
+                &mut file,
+            )?;
+            eprintln!("{} Zeilen nach {} exportiert", rows, out);
+        }
+        None => {
+            export::export(
+                &records,
+                args.kind,
+                &filter,
+                &columns,
+                args.format,
+                sealer.as_ref(),
+                &mut std::io::stdout().lock(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+async fn purge(args: PurgeArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    retention::validate(&config.retention, config.artifacts.local_dir.as_deref())?;
+    let dir = config
+        .artifacts
+        .local_dir
+        .as_deref()
+        .context("kein lokaler Artefakt-Speicher konfiguriert (artifacts.local_dir)")?;
+    let older_than_ms = args
+        .older_than
+        .as_deref()
+        .map(stats::parse_window)
+        .transpose()?;
+    let selection = (args.engagement.is_some() || older_than_ms.is_some()).then_some(Selection {
+        engagement: args.engagement,
+        older_than_ms,
+    });
+    let report = retention::purge(
+        &config.retention,
+        Path::new(dir),
+        selection.as_ref(),
+        args.dry_run,
+    )?;
+    if !args.dry_run
+        && let Some(log) = AuditLog::open(&config.audit)?
+    {
+        log.append("retention_purge", retention_audit(&report, "cli"))?;
+    }
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+async fn replay(args: ReplayArgs) -> Result<()> {
+    let config = load_config(&args.config).await?;
+    let path = args
+        .path
+        .clone()
+        .or(config.audit.path.clone())
+        .context("kein Audit-Log angegeben (--path oder audit.path)")?;
+    let records = audit::read_records(Path::new(&path))?;
+    let original = records
+        .iter()
+        .rev()
+        .filter(|record| record["kind"] == "run_manifest")
+        .map(|record| &record["entry"])
+        .find(|entry| {
+            entry["correlation_id"].as_str() == Some(args.run_id.as_str())
+                || entry["attempts"].as_array().is_some_and(|attempts| {
+                    attempts
+                        .iter()
+                        .any(|attempt| attempt["run_token"] == args.run_id.as_str())
+                })
+        })
+        .with_context(|| {
+            format!(
+                "kein Run-Manifest für '{}' im Audit-Log {} gefunden",
+                args.run_id, path
+            )
+        })?;
+    let mut request = serde_json::from_value::<RunRequest>(original["request"].clone())
+        .with_context(|| {
+            format!(
+                "Request aus dem Run-Manifest von '{}' ist ungültig",
+                args.run_id
+            )
+        })?;
+    if config
+        .tools
+        .get(&request.tool)
+        .is_some_and(|policy| policy.profile.is_some())
+    {
+        request.args.clear();
+        request.stdin = None;
+    }
+    let replay_of = original["correlation_id"]
+        .as_str()
+        .unwrap_or(&args.run_id)
+        .to_string();
+    request.id = Some(format!("{}-replay-{}", replay_of, manifest::now_ms()));
+    request.host = args.host.unwrap_or(request.host);
+    request.user = args.user.or(request.user);
+    request.timeout_sec = args.timeout_sec.or(request.timeout_sec);
+    request.max_output_bytes = args.max_output_bytes.or(request.max_output_bytes);
+    request.engagement = args.engagement.or(request.engagement);
+    for pair in &args.labels {
+        let (key, value) = labels::parse_pair(pair)?;
+        request.labels.insert(key, value);
+    }
+    request.manifest = args.manifest;
+    request.replay_of = Some(replay_of.clone());
+
+    let telemetry = telemetry::init(
+        &config.telemetry,
+        &config.logging,
+        config.observability_json_logs,
+    )?;
+    let runtime = Runtime::new(&config, &args.config)?;
+    let id = request.id.clone();
+    let result = execute_request_collect(&config, &runtime, request).await;
+    runtime.notifier.flush().await;
+    telemetry.shutdown();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(
+            &json!({"id": id, "replay_of": replay_of, "result": rest_result(&result?)})
+        )?
+    );
+    Ok(())
+}
+
+fn retention_audit(report: &Value, origin: &str) -> Value {
+    json!({
+        "origin": origin,
+        "operator": audit_operator(),
+        "dir": report["dir"],
+        "purged": report["purged"],
+        "purged_bytes": report["purged_bytes"],
+        "secure_delete": report["secure_delete"],
+        "runs": report["runs"]
+    })
+}
+
+async fn purge_periodically(runtime: Arc<Runtime>) {
+    loop {
+        let config = runtime.config();
+        let interval = Duration::from_secs(config.retention.interval_sec);
+        if config.retention.enabled
+            && let Some(dir) = &config.artifacts.local_dir
+        {
+            let retention = config.retention.clone();
+            let dir = PathBuf::from(dir);
+            let purged = tokio::task::spawn_blocking(move || {
+                retention::purge(&retention, &dir, None, false)
+            })
+            .await;
+            match purged
+                .map_err(anyhow::Error::from)
+                .and_then(|purged| purged)
+            {
+                Ok(report) if report["purged"].as_u64().unwrap_or(0) > 0 => {
+                    log_observation(
+                        "retention_purged",
+                        json!({"purged": report["purged"], "purged_bytes": report["purged_bytes"], "kept": report["kept"]}),
+                    );
+                    if let Err(error) = audit(
+                        &runtime,
+                        "retention_purge",
+                        retention_audit(&report, "schedule"),
+                    ) {
+                        log_observation(
+                            "retention_audit_failed",
+                            json!({"message": error.to_string()}),
+                        );
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => log_observation(
+                    "retention_failed",
+                    json!({"message": format!("{:#}", error)}),
+                ),
+            }
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = runtime.cancel.cancelled() => return,
+        }
+    }
+}
+
+fn validate_config(config: &BridgeConfig) -> Result<()> {
+    validate_tool_policies(config)?;
+    hostkeys::validate(&config.host_keys)?;
+    identity::validate(&config.ssh_identity)?;
+    validate_ssh_options(config)?;
+    fs::validate(&config.fs)?;
+    wordlists::validate(&config.wordlists)?;
+    cracking::validate(&config.cracking)?;
+    hooks::validate(&config.hooks)?;
+    pipeline::validate(&config.pipeline)?;
+    labels::validate(&config.labels)?;
+    retention::validate(&config.retention, config.artifacts.local_dir.as_deref())?;
+    manifest::validate(&config.manifest)?;
+    health::validate(&config.health)?;
+    budget::validate(&config.context_budget)?;
+    validate_baselines(config)
+}
+
+fn validate_baselines(config: &BridgeConfig) -> Result<()> {
+    baseline::validate(&config.baselines)?;
+    defectdojo::validate(&config.defectdojo)?;
+    for (name, job) in &config.baselines.jobs {
+        serde_json::from_value::<WorkflowRequest>(job.workflow.clone())
+            .with_context(|| format!("baselines.jobs.{}.workflow ist ungültig", name))?;
+    }
+    Ok(())
+}
+
+async fn run_baseline(runtime: &Runtime, name: &str) -> Result<Value> {
+    let config = runtime.config();
+    let job = config
+        .baselines
+        .jobs
+        .get(name)
+        .with_context(|| format!("Baseline '{}' ist nicht konfiguriert", name))?;
+    let mut workflow = serde_json::from_value::<WorkflowRequest>(job.workflow.clone())
+        .with_context(|| format!("baselines.jobs.{}.workflow ist ungültig", name))?;
+    for step in &mut workflow.steps {
+        if step.for_each.is_none() && step_uses_target(step) {
+            step.for_each = Some(job.group.clone());
+        }
+    }
+    let run_id = format!("baseline-{}-{}", name, manifest::now_ms());
+    workflow.id = Some(run_id.clone());
+    workflow
+        .labels
+        .entry("baseline".to_string())
+        .or_insert_with(|| name.to_string());
+
+    let mut events = Vec::new();
+    run_workflow(&config, runtime, workflow, &mut events).await?;
+    let events = events
+        .split(|byte| *byte == b'\n')
+        .filter_map(|line| serde_json::from_slice::<Value>(line).ok())
+        .collect::<Vec<_>>();
+    let current = baseline::snapshot(&run_id, &events)?;
+    let dir = Path::new(&config.baselines.state_dir);
+    let previous = baseline::load(dir, name, runtime.sealer.as_ref())?;
+    baseline::save(dir, name, &current, runtime.sealer.as_ref())?;
+
+    let Some(previous) = previous else {
+        log_observation(
+            "baseline_established",
+            json!({"baseline": name, "run_id": run_id, "findings": current.findings.len()}),
+        );
+        return Ok(
+            json!({"baseline": name, "run_id": run_id, "state": "established", "findings": current.findings.len()}),
+        );
+    };
+    let delta = baseline::diff(&previous.findings, &current.findings);
+    if delta.is_empty() {
+        return Ok(
+            json!({"baseline": name, "run_id": run_id, "state": "unchanged", "previous_run_id": previous.run_id}),
+        );
+    }
+    let report = json!({
+        "baseline": name,
+        "group": job.group,
+        "run_id": run_id,
+        "previous_run_id": previous.run_id,
+        "added": delta.added.len(),
+        "removed": delta.removed.len(),
+        "summary": findings::summary(&delta.added)
+    });
+    log_observation("baseline_changed", report.clone());
+    audit(
+        runtime,
+        "baseline_changed",
+        json!({
+            "baseline": name,
+            "group": job.group,
+            "correlation_id": run_id,
+            "previous_run_id": previous.run_id,
+            "added": stored_findings(runtime, &delta.added)?,
+            "removed": stored_findings(runtime, &delta.removed)?
+        }),
+    )?;
+    let mut payload = report.clone();
+    payload["delta"] = json!(delta);
+    runtime
+        .notifier
+        .notify(NotificationEvent::BaselineChanged, &run_id, payload.clone());
+    payload["state"] = json!("changed");
+    Ok(payload)
+}
+
+async fn baseline_periodically(runtime: Arc<Runtime>, name: String) {
+    let config = runtime.config();
+    let previous = baseline::load(
+        Path::new(&config.baselines.state_dir),
+        &name,
+        runtime.sealer.as_ref(),
+    );
+    let mut wait = match (&previous, config.baselines.jobs.get(&name)) {
+        (Ok(previous), Some(job)) => baseline::due_in(previous.as_ref(), job.interval_sec),
+        _ => Duration::ZERO,
+    };
+    drop(config);
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = runtime.cancel.cancelled() => return,
+        }
+        if let Err(error) = run_baseline(&runtime, &name).await {
+            let message = format!("{:#}", error);
+            log_observation(
+                "baseline_failed",
+                json!({"baseline": name, "message": message}),
+            );
+            runtime.notifier.notify(
+                NotificationEvent::Error,
+                &format!("baseline-{}", name),
+                json!({"code": error_code(&error), "message": message, "baseline": name}),
+            );
+        }
+        let Some(job) = runtime.config().baselines.jobs.get(&name).cloned() else {
+            return;
+        };
+        wait = Duration::from_secs(job.interval_sec);
+    }
+}
+
+async fn prepare_serve(args: ServeArgs) -> Result<(Arc<BridgeConfig>, Arc<Runtime>, Telemetry)> {
+    let mut config = load_config(&args.config).await?;
+    if args.metrics_addr.is_some() {
+        config.metrics_addr = args.metrics_addr;
+    }
+    let telemetry = telemetry::init(
+        &config.telemetry,
+        &config.logging,
+        config.observability_json_logs,
+    )?;
+    let config = Arc::new(config);
+    let runtime = Arc::new(Runtime::new(&config, &args.config)?);
+
+    if let Some(addr) = &config.metrics_addr {
+        let listener = metrics::bind(addr).await?;
+        log_observation("metrics_listening", json!({"addr": addr}));
+        let metrics_runtime = runtime.clone();
+        tokio::spawn(metrics::serve(
+            listener,
+            Arc::new(move || metrics_runtime.render_metrics()),
+        ));
+    }
+
+    if let Some(path) = &config.control.socket_path {
+        let listener = control::bind(path).await?;
+        log_observation("control_listening", json!({"socket_path": path}));
+        let control_runtime = runtime.clone();
+        tokio::spawn(control::serve(
+            listener,
+            Arc::new(move |command| apply_control(&control_runtime, command, "socket")),
+        ));
+    }
+
+    tokio::spawn(purge_periodically(runtime.clone()));
+    for name in config.baselines.jobs.keys() {
+        tokio::spawn(baseline_periodically(runtime.clone(), name.clone()));
+    }
+
+    Ok((config, runtime, telemetry))
+}
+
+fn apply_control(runtime: &Runtime, command: ControlCommand, origin: &str) -> Result<Value> {
+    let status = match &command {
+        ControlCommand::Pause { kill } => {
+            runtime.control.pause();
+            if *kill {
+                runtime.control.kill_all();
+                runtime.terminals.close_all("killed");
+            }
+            runtime.control_status()
+        }
+        ControlCommand::Resume => {
+            runtime.control.resume();
+            runtime.control_status()
+        }
+        ControlCommand::Status => return Ok(runtime.control_status()),
+        ControlCommand::Health => return Ok(runtime.health()),
+        ControlCommand::Jobs => {
+            return Ok(json!({
+                "active": runtime.activity.jobs(),
+                "hosts": runtime.scheduler.hosts(),
+                "status": runtime.control_status()
+            }));
+        }
+        ControlCommand::Cancel { run } => runtime
+            .activity
+            .cancel(run)
+            .ok_or_else(|| Message::RunUnknown { id: run.clone() })?,
+        ControlCommand::Tail { after, .. } => return Ok(runtime.activity.events_after(*after)),
+        ControlCommand::Activity { run } => {
+            let mut activity = runtime.activity.snapshot(run.as_deref());
+            activity["status"] = runtime.control_status();
+            activity["hosts"] = runtime.scheduler.hosts();
+            return Ok(activity);
+        }
+        ControlCommand::GetConfig => return runtime.config_snapshot(),
+        ControlCommand::ReloadConfig => runtime.reload_config()?,
+        ControlCommand::SetToolEnabled { tool, enabled } => {
+            runtime.set_tool_enabled(tool, *enabled)?
+        }
+        ControlCommand::Approvals => return Ok(json!({"pending": runtime.approvals.list()})),
+        ControlCommand::Approve { approval_id } => {
+            runtime.approvals.decide(approval_id, Decision::Approved)?
+        }
+        ControlCommand::Deny {
+            approval_id,
+            reason,
+        } => runtime.approvals.decide(
+            approval_id,
+            Decision::Denied {
+                reason: reason.clone(),
+            },
+        )?,
+        ControlCommand::ContextBudget { session } => {
+            runtime.budgets.retain(&runtime.sessions.ids());
+            return Ok(runtime
+                .budgets
+                .snapshot(&runtime.config().context_budget, session.as_deref()));
+        }
+    };
+    audit(
+        runtime,
+        "control",
+        json!({"operator": audit_operator(), "origin": origin, "command": command, "status": status}),
+    )?;
+    log_observation(
+        "control",
+        json!({"command": command, "origin": origin, "status": status}),
+    );
+    runtime
+        .activity
+        .record("control", json!({"command": command, "origin": origin}));
+    Ok(status)
+}
+
+async fn serve_stdio(config: Arc<BridgeConfig>, runtime: Arc<Runtime>) -> Result<()> {
+    let stdin = io::stdin();
+    let mut lines = BufReader::new(stdin).lines();
+    let mut signals = ShutdownSignals::new()?;
+    let (mut out, writer_task) = spawn_stdout_writer();
+    let mut tasks = JoinSet::new();
+
+    let shutdown_signal = loop {
+        let line = match next_incoming(&mut lines, &mut signals).await? {
+            Incoming::Line(line) => line,
+            Incoming::Closed => break None,
+            Incoming::Shutdown(signal) => break Some(signal),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RunRequest>(&line) {
+            Ok(request) => {
+                let config = runtime.config();
+                let runtime = runtime.clone();
+                let mut out = out.clone();
+                tasks.spawn(async move { serve_run(&config, &runtime, request, &mut out).await });
+            }
+            Err(error) => {
+                runtime.notifier.notify(
+                    NotificationEvent::Error,
+                    "unknown",
+                    json!({"code": "E_PARSE", "message": error.to_string()}),
+                );
+                emit(
+                    &mut out,
+                    Event {
+                        id: "unknown".to_string(),
+                        event: "error".to_string(),
+                        payload: json!({
+                            "code": "E_PARSE",
+                            "message": error.to_string()
+                        }),
+                    },
+                )
+                .await?;
+            }
+        }
+    };
+
+    finish_serve(
+        &config,
+        &runtime,
+        ServeProtocol::Events,
+        tasks,
+        out,
+        writer_task,
+        shutdown_signal,
+    )
+    .await
+}
+
+async fn serve_run(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: RunRequest,
+    out: &mut ChannelWriter,
+) -> Result<()> {
+    let id = request.id.clone().unwrap_or_else(|| "request".to_string());
+    let (tool, host) = (request.tool.clone(), request.host.clone());
+    if let Err(error) = run_request(config, runtime, request, out).await {
+        runtime.notifier.notify(
+            NotificationEvent::Error,
+            &id,
+            json!({"code": error_code(&error), "message": error.to_string(), "tool": tool, "host": host}),
+        );
+        emit(
+            out,
+            Event {
+                id: "unknown".to_string(),
+                event: "error".to_string(),
+                payload: json!({
+                    "code": error_code(&error),
+                    "message": error.to_string()
+                }),
+            },
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn serve_mcp_stdio(
+    config: Arc<BridgeConfig>,
+    runtime: Arc<Runtime>,
+    framing: Framing,
+) -> Result<()> {
+    let mut reader = FrameReader::new(BufReader::new(io::stdin()), framing);
+    let mut signals = ShutdownSignals::new()?;
+    let (mut out, rx) = channel_writer();
+    let writer_task = tokio::spawn(framing::write_stdout(rx, reader.detected()));
+    let peer = Peer::new(out.tx.clone());
+    let elicitation = tokio::spawn(elicit_approvals(runtime.clone(), peer.clone()));
+    let mut tasks = JoinSet::new();
+    let mut tool_changes = runtime.tool_changes.subscribe();
+    let mut changes_out = out.clone();
+    let list_changed = tokio::spawn(async move {
+        while tool_changes.changed().await.is_ok() {
+            let notification =
+                json!({"jsonrpc": "2.0", "method": "notifications/tools/list_changed"});
+            if write_json_line(&mut changes_out, notification)
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let mut pinger = Pinger::new(&config.liveness);
+    let mut ping_timer = config.liveness.interval().map(|interval| {
+        let mut timer = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        timer
+    });
+
+    let shutdown_signal = loop {
+        let line = tokio::select! {
+            message = reader.next_message() => match message? {
+                Some(line) => line,
+                None => break None,
+            },
+            signal = signals.recv() => break Some(signal),
+            _ = async { ping_timer.as_mut().expect("ping timer").tick().await }, if ping_timer.is_some() => {
+                match pinger.tick() {
+                    Tick::Ping(ping) => write_json_line(&mut out, ping).await?,
+                    Tick::Dead(missed) => {
+                        log_observation("client_unresponsive", json!({"missed_pings": missed, "in_flight": tasks.len()}));
+                        runtime.cancel.trigger();
+                        break Some("ping_timeout");
+                    }
+                }
+                continue;
+            }
+        };
+        pinger.seen();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let parsed = serde_json::from_str::<Value>(&line);
+        if let Ok(response) = &parsed
+            && liveness::is_response(response)
+        {
+            peer.resolve(response);
+            continue;
+        }
+        let parsed = parsed.and_then(serde_json::from_value::<JsonRpcRequest>);
+        let request = match parsed {
+            Ok(req) => req,
+            Err(error) => {
+                write_json_line(
+                    &mut out,
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": Value::Null,
+                        "error": {
+                            "code": -32700,
+                            "message": format!("parse error: {}", error)
+                        }
+                    }),
+                )
+                .await?;
+                continue;
+            }
+        };
+
+        if request.method == "initialize" {
+            peer.set_capabilities(
+                request
+                    .params
+                    .as_ref()
+                    .map_or(Value::Null, |params| params["capabilities"].clone()),
+            );
+        }
+        if request.method == "notifications/roots/list_changed" {
+            peer.forget_roots();
+        }
+
+        let config = runtime.config();
+        let runtime = runtime.clone();
+        let mut out = out.clone();
+        let peer = peer.clone();
+        tasks.spawn(async move {
+            handle_mcp_request(
+                &config,
+                &runtime,
+                request,
+                None,
+                None,
+                Some(&peer),
+                &mut out,
+            )
+            .await
+        });
+    };
+
+    list_changed.abort();
+    elicitation.abort();
+    drop(peer);
+
+    finish_serve(
+        &config,
+        &runtime,
+        ServeProtocol::JsonRpc,
+        tasks,
+        out,
+        writer_task,
+        shutdown_signal,
+    )
+    .await
+}
+
+async fn elicit_approvals(runtime: Arc<Runtime>, peer: Arc<Peer>) {
+    let mut events = runtime.approvals.subscribe();
+    loop {
+        let (id, request) = match events.recv().await {
+            Ok(ApprovalEvent::Requested { id, request }) => (id, request),
+            Ok(ApprovalEvent::Resolved { .. }) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+        if runtime.config().approvals.elicitation && peer.supports("elicitation") {
+            tokio::spawn(elicit_approval(runtime.clone(), peer.clone(), id, request));
+            continue;
+        }
+        let _ = peer.notify(
+            "notifications/message",
+            json!({
+                "level": "warning",
+                "logger": "ollama-kali-mcp-bridge",
+                "data": {"event": "approval_pending", "approval_id": id, "request": request}
+            }),
+        );
+    }
+}
+
+async fn elicit_approval(runtime: Arc<Runtime>, peer: Arc<Peer>, id: String, request: Value) {
+    let mut events = runtime.approvals.subscribe();
+    if !runtime.approvals.is_pending(&id) {
+        return;
+    }
+    let resolved = async {
+        loop {
+            match events.recv().await {
+                Ok(ApprovalEvent::Resolved { id: resolved }) if resolved == id => return,
+                Err(broadcast::error::RecvError::Closed) => return,
+                _ => {}
+            }
+        }
+    };
+    let message = format!(
+        "Freigabe {}: {} auf {} mit {} (Client {}, Engagement {}). Soll der Run starten?",
+        id,
+        request["tool"].as_str().unwrap_or("?"),
+        request["target"].as_str().unwrap_or("?"),
+        if request["params"]
+            .as_object()
+            .is_some_and(|params| !params.is_empty())
+        {
+            &request["params"]
+        } else {
+            &request["args"]
+        },
+        request["client"].as_str().unwrap_or("-"),
+        request["engagement"].as_str().unwrap_or("-")
+    );
+    let params = json!({
+        "message": message,
+        "requestedSchema": {
+            "type": "object",
+            "required": ["approve"],
+            "properties": {
+                "approve": {"type": "boolean", "title": "Run freigeben", "description": "Nein lehnt den Run ab"},
+                "reason": {"type": "string", "title": "Begründung", "description": "Wird bei Ablehnung an den Client gemeldet"}
+            }
+        }
+    });
+    let answer = tokio::select! {
+        answer = peer.request("elicitation/create", params) => answer,
+        _ = resolved => return,
+    };
+    let command = match answer {
+        Ok(result) => match (
+            result["action"].as_str(),
+            result["content"]["approve"].as_bool(),
+        ) {
+            (Some("accept"), Some(true)) => ControlCommand::Approve {
+                approval_id: id.clone(),
+            },
+            (Some("accept"), _) => ControlCommand::Deny {
+                approval_id: id.clone(),
+                reason: result["content"]["reason"].as_str().map(str::to_string),
+            },
+            (Some("decline"), _) => ControlCommand::Deny {
+                approval_id: id.clone(),
+                reason: Some("per Elicitation abgelehnt".to_string()),
+            },
+            _ => return,
+        },
+        Err(error) => {
+            log_observation(
+                "elicitation_failed",
+                json!({"approval_id": id, "error": format!("{:#}", error)}),
+            );
+            return;
+        }
+    };
+    if let Err(error) = apply_control(&runtime, command, "elicitation") {
+        log_observation(
+            "elicitation_failed",
+            json!({"approval_id": id, "error": format!("{:#}", error)}),
+        );
+    }
+}
+
+fn mcp_tool_list(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+) -> Vec<Value> {
+    let default_host = session.and_then(|session| runtime.sessions.default_host(session));
+    let required = if default_host.is_some() {
+        json!([])
+    } else {
+        json!(["host"])
+    };
+    config
+        .tools
+        .iter()
+        .filter(|(_, policy)| policy.enabled)
+        .map(|(name, policy)| {
+            tool_definition(
+                name,
+                policy,
+                &required,
+                version_notes(config, runtime, policy),
+            )
+        })
+        .chain(engagement::mcp_tools(&config.engagements))
+        .chain(control::mcp_tools(&config.control))
+        .chain(fs::mcp_tools(&config.fs))
+        .chain(wordlists::mcp_tools(&config.wordlists))
+        .chain(targets::mcp_tools())
+        .chain(
+            output_store_enabled(config)
+                .then(outputs::mcp_tools)
+                .unwrap_or_default(),
+        )
+        .chain(cracking::mcp_tools(&config.cracking))
+        .chain(runtime.metasploit.iter().flat_map(Metasploit::mcp_tools))
+        .chain(runtime.gvm.iter().flat_map(Gvm::mcp_tools))
+        .filter(|tool| {
+            rbac::allows_tool(
+                &config.rbac,
+                client,
+                tool["name"].as_str().unwrap_or_default(),
+            )
+        })
+        .chain(session.map(|_| session::mcp_tools()).unwrap_or_default())
+        .chain(
+            config
+                .tools
+                .iter()
+                .any(|(name, policy)| {
+                    policy.interactive
+                        && policy.enabled
+                        && rbac::allows_tool(&config.rbac, client, name)
+                })
+                .then(interactive::mcp_tools)
+                .unwrap_or_default(),
+        )
+        .collect::<Vec<_>>()
+}
+
+fn version_notes(config: &BridgeConfig, runtime: &Runtime, policy: &ToolPolicy) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(min_version) = &policy.min_version {
+        notes.push(format!("Requires version {} or newer.", min_version));
+    }
+    let versions = runtime
+        .tool_versions
+        .for_command(&policy.command, config.manifest.version_ttl_sec);
+    if !versions.is_empty() {
+        let versions = versions
+            .iter()
+            .map(|(target, version)| format!("{}: {}", target, version))
+            .collect::<Vec<_>>();
+        notes.push(format!("Installed: {}.", versions.join("; ")));
+    }
+    notes
+}
+
+fn tool_definition(name: &str, policy: &ToolPolicy, required: &Value, notes: Vec<String>) -> Value {
+    let mut tool = json!({
+        "name": name,
+        "description": format!("Executes {} on Kali via SSH with timeout enforcement", policy.command),
+        "inputSchema": {
+            "type": "object",
+            "required": required,
+            "properties": {
+                "host": {"type": "string"},
+                "user": {"type": "string"},
+                "args": {"type": "array", "items": {"type": "string"}},
+                "timeout_sec": {"type": "integer", "minimum": 1},
+                "max_output_bytes": {"type": "integer", "minimum": 1024},
+                "strip_ansi": {"type": "boolean"},
+                "strip_control_chars": {"type": "boolean"},
+                "include_pattern": {
+                    "type": "string",
+                    "description": "Regex; only stdout lines matching it are returned (applied before max_output_bytes)"
+                },
+                "exclude_pattern": {
+                    "type": "string",
+                    "description": "Regex; stdout lines matching it are dropped (applied before max_output_bytes)"
+                },
+                "no_cache": {"type": "boolean"},
+                "engagement": {"type": "string"},
+                "labels": {
+                    "type": "object",
+                    "additionalProperties": {"type": "string"},
+                    "description": "Key/value labels such as {\"phase\": \"recon\"}, echoed in results and used for reporting"
+                },
+                "manifest": {
+                    "type": "boolean",
+                    "description": "Return the run manifest (resolved config, remote command, SSH options, versions, attempts) in structuredContent"
+                },
+                "stdin": {
+                    "description": "Data for the tool's standard input: a string or {\"base64\": \"...\"}",
+                    "oneOf": [
+                        {"type": "string"},
+                        {"type": "object", "required": ["base64"], "properties": {"base64": {"type": "string"}}}
+                    ]
+                }
+            }
+        }
+    });
+    if let Some(profile) = policy.profile {
+        tool["description"] = json!(format!(
+            "Runs {} ({} profile) on Kali via SSH with structured parameters and returns structured results",
+            policy.command,
+            profile.as_str()
+        ));
+        if let (Some(properties), Value::Object(extra)) = (
+            tool["inputSchema"]["properties"].as_object_mut(),
+            profile.mcp_properties(),
+        ) {
+            properties.remove("args");
+            properties.remove("stdin");
+            properties.extend(extra);
+        }
+    }
+    if let Some(description) = &policy.description {
+        tool["description"] = json!(description.trim());
+    }
+    describe_usage(policy, &mut tool, notes);
+    tool
+}
+
+fn describe_usage(policy: &ToolPolicy, tool: &mut Value, notes: Vec<String>) {
+    let mut flags = Vec::new();
+    for (key, hint) in &policy.arg_hints {
+        match tool["inputSchema"]["properties"].get_mut(key) {
+            Some(property) => property["description"] = json!(hint),
+            None => flags.push(format!("{}: {}", key, hint)),
+        }
+    }
+    if !flags.is_empty()
+        && let Some(args) = tool["inputSchema"]["properties"].get_mut("args")
+    {
+        let hints = format!("Useful arguments: {}", flags.join("; "));
+        args["description"] = json!(match args["description"].as_str() {
+            Some(description) => format!("{}. {}", description.trim_end_matches('.'), hints),
+            None => hints,
+        });
+    }
+    let mut description = tool["description"].as_str().unwrap_or_default().to_string();
+    if !notes.is_empty() {
+        description = format!("{}. {}", description.trim_end_matches('.'), notes.join(" "));
+    }
+    if !policy.examples.is_empty() {
+        description.push_str("\n\nExamples:");
+        for example in &policy.examples {
+            let arguments = Value::Object(example.arguments.clone());
+            match &example.description {
+                Some(label) => description.push_str(&format!("\n- {}: {}", label, arguments)),
+                None => description.push_str(&format!("\n- {}", arguments)),
+            }
+        }
+        tool["inputSchema"]["examples"] = json!(
+            policy
+                .examples
+                .iter()
+                .map(|example| &example.arguments)
+                .collect::<Vec<_>>()
+        );
+    }
+    tool["description"] = json!(description);
+}
+
+fn complete_argument(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+    params: &Value,
+) -> Result<Value> {
+    let missing = |argument| Message::MissingArgument {
+        tool: "completion/complete".to_string(),
+        argument,
+    };
+    let argument = params["argument"]["name"]
+        .as_str()
+        .ok_or_else(|| missing("argument.name"))?;
+    let prefix = params["argument"]["value"].as_str().unwrap_or_default();
+    let tool = match params["ref"]["type"].as_str() {
+        Some("ref/tool") => params["ref"]["name"]
+            .as_str()
+            .ok_or_else(|| missing("ref.name"))?,
+        Some("ref/prompt" | "ref/resource") => return Ok(mcp::completion(Vec::new(), prefix)),
+        other => bail!(Message::InvalidValue {
+            field: "ref.type",
+            value: other.unwrap_or_default().to_string(),
+        }),
+    };
+    let Some(schema) = mcp_tool_list(config, runtime, client, session)
+        .into_iter()
+        .find(|listed| listed["name"] == tool)
+    else {
+        return Ok(mcp::completion(Vec::new(), prefix));
+    };
+    let policy = config.tools.get(tool);
+    let mut candidates = mcp::schema_values(&schema, argument);
+    match argument {
+        "host" => {
+            candidates.extend(config.mcp.hosts.iter().cloned());
+            candidates.extend(session.and_then(|session| runtime.sessions.default_host(session)));
+            let activity = runtime.activity.snapshot(None);
+            for run in ["active", "finished"]
+                .iter()
+                .flat_map(|key| activity[*key].as_array().cloned().unwrap_or_default())
+            {
+                candidates.extend(run["host"].as_str().map(str::to_string));
+            }
+        }
+        "engagement" => {
+            candidates.extend(
+                engagement::Registry::load(&config.engagements.path)?
+                    .engagements
+                    .into_keys(),
+            );
+        }
+        "wordlist" | "login_list" | "password_list" => {
+            candidates.extend(config.wordlists.lists.keys().cloned())
+        }
+        "name" if tool == "wordlists.info" => {
+            candidates.extend(config.wordlists.lists.keys().cloned())
+        }
+        "list" => candidates.extend(
+            targets::Store::load(&config.targets.path)?
+                .lists
+                .into_keys(),
+        ),
+        "name" if tool == "targets.expand" => candidates.extend(
+            targets::Store::load(&config.targets.path)?
+                .lists
+                .into_keys(),
+        ),
+        "service" if policy.is_some_and(|policy| policy.profile == Some(ToolProfile::Hydra)) => {
+            candidates.extend(
+                policy
+                    .into_iter()
+                    .flat_map(|policy| policy.hydra.services.iter().cloned()),
+            );
+        }
+        "interface"
+            if policy.is_some_and(|policy| policy.profile == Some(ToolProfile::Capture)) =>
+        {
+            candidates.extend(
+                policy
+                    .into_iter()
+                    .flat_map(|policy| policy.capture.interfaces.iter().cloned()),
+            );
+        }
+        "args"
+            if policy.is_some_and(|policy| {
+                Path::new(&policy.command).file_name() == Some("nmap".as_ref())
+            }) =>
+        {
+            candidates.extend(mcp::NMAP_ARGS.iter().map(|arg| arg.to_string()));
+        }
+        _ => {}
+    }
+    Ok(mcp::completion(candidates, prefix))
+}
+
+fn timeline_resources(config: &BridgeConfig, client: Option<&str>) -> Result<Vec<Value>> {
+    if config.audit.path.is_none()
+        || !rbac::allows_tool(&config.rbac, client, "engagement.timeline")
+    {
+        return Ok(Vec::new());
+    }
+    let registry = engagement::Registry::load(&config.engagements.path)?;
+    Ok(registry
+        .engagements
+        .keys()
+        .map(|name| {
+            json!({
+                "uri": timeline::resource_uri(name),
+                "name": format!("{}-timeline", name),
+                "description": format!("Chronological activity log of engagement {}", name),
+                "mimeType": "application/json"
+            })
+        })
+        .collect())
+}
+
+fn read_timeline(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    name: &str,
+) -> Result<Value> {
+    if !rbac::allows_tool(&config.rbac, client, "engagement.timeline") {
+        return Err(Message::ToolForbidden {
+            client: client.unwrap_or_default().to_string(),
+            tool: "engagement.timeline".to_string(),
+        }
+        .into());
+    }
+    let path = config
+        .audit
+        .path
+        .as_deref()
+        .ok_or_else(|| Message::NotConfigured {
+            key: "audit.path".to_string(),
+        })?;
+    let registry = engagement::Registry::load(&config.engagements.path)?;
+    let records = audit::read_records(Path::new(path))?;
+    let timeline = timeline::build(
+        &records,
+        name,
+        registry.engagements.get(name),
+        None,
+        None,
+        runtime.sealer.as_ref(),
+    )?;
+    Ok(serde_json::to_value(timeline)?)
+}
+
+async fn handle_mcp_request<W: AsyncWrite + Unpin>(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: JsonRpcRequest,
+    client: Option<&str>,
+    session: Option<&str>,
+    peer: Option<&Peer>,
+    writer: &mut W,
+) -> Result<()> {
+    if request.id.is_none() && request.method.starts_with("notifications/") {
+        return Ok(());
+    }
+    let method = request.method.clone();
+    let tool = request
+        .params
+        .as_ref()
+        .and_then(|params| params["name"].as_str())
+        .filter(|_| method == "tools/call")
+        .map(str::to_string);
+    let request_bytes = method.len()
+        + request
+            .params
+            .as_ref()
+            .map_or(0, |params| params.to_string().len());
+    let mut metered = Metered::new(writer);
+    let handled = dispatch_mcp_request(
+        config,
+        runtime,
+        request,
+        client,
+        session,
+        peer,
+        &mut metered,
+    )
+    .await;
+    runtime.budgets.record(
+        &config.context_budget,
+        session.unwrap_or(budget::STDIO_SESSION),
+        client,
+        &method,
+        tool.as_deref(),
+        request_bytes as u64,
+        metered.bytes,
+    );
+    handled
+}
+
+async fn dispatch_mcp_request<W: AsyncWrite + Unpin>(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: JsonRpcRequest,
+    client: Option<&str>,
+    session: Option<&str>,
+    peer: Option<&Peer>,
+    writer: &mut W,
+) -> Result<()> {
+    let id = request.id.unwrap_or(Value::Null);
+    match request.method.as_str() {
+        "ping" => {
+            write_json_line(writer, json!({"jsonrpc": "2.0", "id": id, "result": {}})).await?;
+        }
+        "completion/complete" => {
+            let params = request.params.unwrap_or_else(|| json!({}));
+            let response = match complete_argument(config, runtime, client, session, &params) {
+                Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                Err(error) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32602, "message": format!("invalid params: {:#}", error)}
+                }),
+            };
+            write_json_line(writer, response).await?;
+        }
+        "initialize" => {
+            write_json_line(
+                writer,
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": {
+                        "protocolVersion": "2025-01-01",
+                        "capabilities": {
+                            "tools": {"listChanged": true},
+                            "resources": {},
+                            "completions": {}
+                        },
+                        "serverInfo": {
+                            "name": "ollama-kali-mcp-bridge",
+                            "version": env!("CARGO_PKG_VERSION")
+                        }
+                    }
+                }),
+            )
+            .await?;
+        }
+        "resources/list" => {
+            let cursor = request
+                .params
+                .as_ref()
+                .and_then(|params| params["cursor"].as_str());
+            let response = match timeline_resources(config, client) {
+                Ok(resources) => match mcp::paginate(resources, cursor, config.mcp.page_size) {
+                    Ok((resources, Some(next))) => {
+                        json!({"jsonrpc": "2.0", "id": id, "result": {"resources": resources, "nextCursor": next}})
+                    }
+                    Ok((resources, None)) => {
+                        json!({"jsonrpc": "2.0", "id": id, "result": {"resources": resources}})
+                    }
+                    Err(error) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32602, "message": format!("invalid params: {:#}", error)}
+                    }),
+                },
+                Err(error) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32000, "message": format!("{:#}", error), "data": {"code": error_code(&error)}}
+                }),
+            };
+            write_json_line(writer, response).await?;
+        }
+        "resources/templates/list" => {
+            let templates = if config.audit.path.is_some()
+                && rbac::allows_tool(&config.rbac, client, "engagement.timeline")
+            {
+                vec![json!({
+                    "uriTemplate": timeline::resource_uri("{engagement}"),
+                    "name": "engagement-timeline",
+                    "description": "Chronological activity log of an engagement: every run with tool, target, operator/client identity, duration and result summary",
+                    "mimeType": "application/json"
+                })]
+            } else {
+                Vec::new()
+            };
+            write_json_line(
+                writer,
+                json!({"jsonrpc": "2.0", "id": id, "result": {"resourceTemplates": templates}}),
+            )
+            .await?;
+        }
+        "resources/read" => {
+            let uri = request
+                .params
+                .as_ref()
+                .and_then(|params| params["uri"].as_str())
+                .unwrap_or_default();
+            let response = match timeline::parse_resource_uri(uri) {
+                None => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32002, "message": "resource not found", "data": {"uri": uri}}
+                }),
+                Some(name) => match read_timeline(config, runtime, client, name) {
+                    Ok(timeline) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {"contents": [{"uri": uri, "mimeType": "application/json", "text": timeline.to_string()}]}
+                    }),
+                    Err(error) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32000, "message": format!("{:#}", error), "data": {"code": error_code(&error)}}
+                    }),
+                },
+            };
+            write_json_line(writer, response).await?;
+        }
+        "tools/list" => {
+            let tools = mcp_tool_list(config, runtime, client, session);
+
+            let cursor = request
+                .params
+                .as_ref()
+                .and_then(|params| params["cursor"].as_str());
+            let response = match mcp::paginate(tools, cursor, config.mcp.page_size) {
+                Ok((tools, Some(next))) => {
+                    json!({"jsonrpc": "2.0", "id": id, "result": {"tools": tools, "nextCursor": next}})
+                }
+                Ok((tools, None)) => {
+                    json!({"jsonrpc": "2.0", "id": id, "result": {"tools": tools}})
+                }
+                Err(error) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32602, "message": format!("invalid params: {:#}", error)}
+                }),
+            };
+            write_json_line(writer, response).await?;
+        }
+        "tools/call" => {
+            let params_value = request.params.unwrap_or_else(|| json!({}));
+            let params: McpCallParams = match serde_json::from_value(params_value) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    write_json_line(
+                        writer,
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": {
+                                "code": -32602,
+                                "message": format!("invalid params: {}", error)
+                            }
+                        }),
+                    )
+                    .await?;
+                    return Ok(());
+                }
+            };
+
+            let is_builtin = ["engagement.", "admin.", "session."]
+                .iter()
+                .any(|prefix| params.name.starts_with(prefix))
+                && params.name != "session.open";
+            let is_run = config.tools.contains_key(&params.name);
+            let admitted = match session.filter(|_| !is_builtin && !is_run) {
+                Some(session) => runtime.sessions.admit_call(session),
+                None => Ok(()),
+            };
+            let restricted = (params.name.starts_with("engagement.")
+                || params.name.starts_with("admin."))
+                && !rbac::allows_tool(&config.rbac, client, &params.name);
+            let builtin = if let Err(error) = admitted {
+                Some(Err(error))
+            } else if restricted {
+                Some(Err(Message::ToolForbidden {
+                    client: client.unwrap_or_default().to_string(),
+                    tool: params.name.clone(),
+                }
+                .into()))
+            } else if let Some(action) = params.name.strip_prefix("engagement.") {
+                Some(engagement::mcp_call(
+                    &config.engagements,
+                    action,
+                    params.arguments.clone(),
+                ))
+            } else if let Some(action) = params.name.strip_prefix("msf.") {
+                Some(execute_msf(config, runtime, client, action, params.arguments.clone()).await)
+            } else if let Some(action) = params.name.strip_prefix("gvm.") {
+                Some(execute_gvm(config, runtime, client, action, params.arguments.clone()).await)
+            } else if let Some(action) = params.name.strip_prefix("fs.") {
+                Some(
+                    execute_fs(
+                        config,
+                        runtime,
+                        client,
+                        session,
+                        action,
+                        params.arguments.clone(),
+                    )
+                    .await,
+                )
+            } else if let Some(action) = params.name.strip_prefix("wordlists.") {
+                Some(
+                    execute_wordlists(
+                        config,
+                        runtime,
+                        client,
+                        session,
+                        action,
+                        params.arguments.clone(),
+                    )
+                    .await,
+                )
+            } else if let Some(action) = params.name.strip_prefix("targets.") {
+                Some(
+                    execute_targets(
+                        config,
+                        runtime,
+                        client,
+                        session,
+                        action,
+                        params.arguments.clone(),
+                    )
+                    .await,
+                )
+            } else if let Some(action) = params.name.strip_prefix("output.") {
+                Some(
+                    match rbac::allows_tool(&config.rbac, client, &params.name) {
+                        true => {
+                            let max_read_bytes = runtime
+                                .budgets
+                                .output_limit(
+                                    &config.context_budget,
+                                    session.unwrap_or(budget::STDIO_SESSION),
+                                    config.mcp.max_read_bytes,
+                                )
+                                .map_or(config.mcp.max_read_bytes, |limit| {
+                                    limit.min(config.mcp.max_read_bytes)
+                                });
+                            runtime.outputs.mcp_call(
+                                client,
+                                action,
+                                params.arguments.clone(),
+                                max_read_bytes,
+                            )
+                        }
+                        false => Err(Message::ToolForbidden {
+                            client: client.unwrap_or_default().to_string(),
+                            tool: params.name.clone(),
+                        }
+                        .into()),
+                    },
+                )
+            } else if let Some(action) = params.name.strip_prefix("crack.") {
+                Some(
+                    execute_cracking(
+                        config,
+                        runtime,
+                        client,
+                        session,
+                        action,
+                        params.arguments.clone(),
+                    )
+                    .await,
+                )
+            } else if let Some(action) = params
+                .name
+                .strip_prefix("session.")
+                .filter(|action| interactive::is_action(action))
+            {
+                Some(match action {
+                    "open" => {
+                        open_terminal(config, runtime, client, session, params.arguments.clone())
+                            .await
+                    }
+                    _ => {
+                        runtime
+                            .terminals
+                            .mcp_call(client, action, params.arguments.clone())
+                            .await
+                    }
+                })
+            } else if let Some(action) = params.name.strip_prefix("session.") {
+                Some(match session {
+                    Some(session) => {
+                        runtime
+                            .sessions
+                            .mcp_call(session, action, params.arguments.clone())
+                    }
+                    None => Err(Message::SessionOnly {
+                        tool: params.name.clone(),
+                    }
+                    .into()),
+                })
+            } else {
+                params.name.strip_prefix("admin.").map(|action| {
+                    control::mcp_command(&config.control, action, params.arguments.clone())
+                        .and_then(|command| apply_control(runtime, command, "mcp"))
+                })
+            };
+            if let Some(builtin) = builtin {
+                let response = match builtin {
+                    Ok(result) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "result": {
+                            "content": [{"type": "text", "text": result.to_string()}],
+                            "isError": false,
+                            "structuredContent": result
+                        }
+                    }),
+                    Err(error) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": -32000, "message": format!("{:#}", error), "data": {"code": error_code(&error)}}
+                    }),
+                };
+                write_json_line(writer, response).await?;
+                return Ok(());
+            }
+
+            let arguments: McpToolArguments = match serde_json::from_value(params.arguments) {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    write_json_line(
+                        writer,
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": {
+                                "code": -32602,
+                                "message": format!("invalid tool arguments: {}", error)
+                            }
+                        }),
+                    )
+                    .await?;
+                    return Ok(());
+                }
+            };
+
+            let Some(host) = arguments
+                .host
+                .or_else(|| session.and_then(|session| runtime.sessions.default_host(session)))
+            else {
+                write_json_line(
+                    writer,
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {
+                            "code": -32602,
+                            "message": format!("invalid tool arguments: {}", Message::HostMissing)
+                        }
+                    }),
+                )
+                .await?;
+                return Ok(());
+            };
+            let correlation_id = mcp_correlation_id(runtime, &id, session);
+            let requested_output_bytes = arguments.max_output_bytes;
+            let run = RunRequest {
+                id: Some(correlation_id.clone()),
+                host: host.clone(),
+                user: arguments.user,
+                tool: params.name.clone(),
+                args: arguments.args,
+                timeout_sec: arguments.timeout_sec,
+                max_output_bytes: requested_output_bytes
+                    .or(output_store_enabled(config).then_some(config.mcp.output_store_bytes)),
+                stream_mode: StreamMode::default(),
+                strip_ansi: arguments.strip_ansi,
+                strip_control_chars: arguments.strip_control_chars,
+                include_pattern: arguments.include_pattern,
+                exclude_pattern: arguments.exclude_pattern,
+                no_cache: arguments.no_cache,
+                engagement: arguments.engagement,
+                stdin: arguments.stdin,
+                params: arguments.params,
+                labels: arguments.labels,
+                manifest: arguments.manifest,
+                client: client.map(str::to_string),
+                output_root: client_output_root(config, peer).await,
+                session: session.map(str::to_string),
+                replay_of: None,
+            };
+            let run_labels = run.labels.clone();
+
+            let progress_token = params.meta.get("progressToken").cloned();
+            let mut queued = runtime.scheduler.subscribe();
+            let mut updates = 0_u64;
+            let execution = execute_request_collect(config, runtime, run);
+            tokio::pin!(execution);
+            let result = loop {
+                tokio::select! {
+                    result = &mut execution => break result,
+                    Ok((queued_id, progress)) = queued.recv(), if progress_token.is_some() => {
+                        if queued_id != correlation_id {
+                            continue;
+                        }
+                        updates += 1;
+                        let message = match progress["eta_sec"].as_u64() {
+                            Some(eta) => format!("queued at position {} on {}, estimated start in {}s", progress["position"], host, eta),
+                            None => format!("queued at position {} on {}", progress["position"], host),
+                        };
+                        write_json_line(
+                            writer,
+                            json!({
+                                "jsonrpc": "2.0",
+                                "method": "notifications/progress",
+                                "params": {"progressToken": progress_token, "progress": updates, "message": message, "_meta": {"queued": progress}}
+                            }),
+                        )
+                        .await?;
+                    }
+                }
+            };
+            if let Some(session) = session {
+                let mut entry =
+                    json!({"correlation_id": correlation_id, "tool": params.name, "host": host});
+                match &result {
+                    Ok(collected) => {
+                        entry["exit_code"] = json!(collected.final_status.exit_code);
+                        entry["timed_out"] = json!(collected.final_status.timed_out);
+                        entry["duration_ms"] = json!(collected.final_status.duration_ms);
+                        entry["cached"] = json!(collected.cached);
+                    }
+                    Err(error) => entry["error"] = json!(error.to_string()),
+                }
+                runtime.sessions.record(session, entry);
+            }
+            match result {
+                Ok(collected) => {
+                    let budget_session = session.unwrap_or(budget::STDIO_SESSION);
+                    let mut summary = format!(
+                        "exit_code={:?}, timed_out={}, duration_ms={}, attempts={}, cached={}, deduplicated={}",
+                        collected.final_status.exit_code,
+                        collected.final_status.timed_out,
+                        collected.final_status.duration_ms,
+                        collected.attempts,
+                        collected.cached,
+                        collected.deduplicated
+                    );
+                    let budget = (config.context_budget.max_tokens > 0).then(|| {
+                        runtime
+                            .budgets
+                            .status(&config.context_budget, budget_session)
+                    });
+                    if let Some(budget) = &budget {
+                        summary.push_str(&format!(
+                            ", context_tokens_left={}",
+                            budget["remaining_tokens"]
+                        ));
+                    }
+                    let mut content = match collected.encoding {
+                        OutputEncoding::Utf8 => json!([
+                            {"type": "text", "text": summary},
+                            {"type": "text", "text": collected.stdout},
+                            {"type": "text", "text": collected.stderr}
+                        ]),
+                        OutputEncoding::Base64 => json!([
+                            {"type": "text", "text": summary},
+                            {
+                                "type": "resource",
+                                "resource": {
+                                    "uri": format!("bridge://{}/stdout", params.name),
+                                    "mimeType": "application/octet-stream",
+                                    "blob": collected.stdout
+                                }
+                            },
+                            {
+                                "type": "resource",
+                                "resource": {
+                                    "uri": format!("bridge://{}/stderr", params.name),
+                                    "mimeType": "application/octet-stream",
+                                    "blob": collected.stderr
+                                }
+                            }
+                        ]),
+                    };
+                    if let Value::Array(blocks) = &mut content {
+                        blocks.extend(collected.images.iter().map(|image| {
+                            json!({"type": "image", "data": BASE64.encode(&image.data), "mimeType": image.mime_type})
+                        }));
+                    }
+                    let mut structured = collected_json(&collected);
+                    structured["run_id"] = json!(correlation_id);
+                    labels::attach(&mut structured, &run_labels);
+                    let configured = config.mcp.inline_output_bytes;
+                    let base = if configured > 0 {
+                        configured
+                    } else {
+                        requested_output_bytes.unwrap_or(config.max_output_bytes)
+                    };
+                    let inline = match runtime.budgets.output_limit(
+                        &config.context_budget,
+                        budget_session,
+                        base,
+                    ) {
+                        Some(limit) if limit < base => limit,
+                        _ => base,
+                    };
+                    if output_store_enabled(config)
+                        && collected.encoding == OutputEncoding::Utf8
+                        && (collected.stdout.len() > inline || collected.stderr.len() > inline)
+                        && let Value::Array(blocks) = &mut content
+                    {
+                        runtime.outputs.store(
+                            &correlation_id,
+                            client,
+                            &collected.stdout,
+                            &collected.stderr,
+                            config.mcp.stored_outputs,
+                        );
+                        let mut partial = json!({});
+                        for (index, stream, text) in [
+                            (1, "stdout", &collected.stdout),
+                            (2, "stderr", &collected.stderr),
+                        ] {
+                            let shown = outputs::trim(text, inline);
+                            partial[stream] =
+                                json!({"total_bytes": text.len(), "inline_bytes": shown.len()});
+                            if shown.len() == text.len() {
+                                continue;
+                            }
+                            blocks[index] = json!({
+                                "type": "text",
+                                "text": format!(
+                                    "{}\n[{} of {} bytes shown; call output.read with run_id \"{}\", stream \"{}\" and offset {} for the rest]",
+                                    shown, shown.len(), text.len(), correlation_id, stream, shown.len()
+                                )
+                            });
+                            blocks.push(json!({
+                                "type": "resource_link",
+                                "uri": format!("bridge://runs/{}/{}", correlation_id, stream),
+                                "name": format!("{}-{}", correlation_id, stream),
+                                "description": format!("Full {} of {} ({} bytes), readable via output.read", stream, params.name, text.len()),
+                                "mimeType": "text/plain",
+                                "size": text.len()
+                            }));
+                        }
+                        structured["partial"] = partial;
+                        if inline < base {
+                            runtime.budgets.note_trimmed(budget_session);
+                        }
+                    }
+                    if let Some(budget) = budget {
+                        structured["context_budget"] = budget;
+                    }
+                    let sampler = peer.filter(|peer| {
+                        collected.encoding == OutputEncoding::Utf8
+                            && config.sampling.applies(&collected.stdout)
+                            && peer.supports("sampling")
+                    });
+                    if let Some(peer) = sampler
+                        && let Some(mut sampled) = sample_output(
+                            config,
+                            runtime,
+                            peer,
+                            &correlation_id,
+                            &params.name,
+                            &collected.stdout,
+                        )
+                        .await
+                        && let Value::Array(blocks) = &mut content
+                    {
+                        let text = format!(
+                            "summary by client model: {}",
+                            sampled["text"].as_str().unwrap_or_default()
+                        );
+                        if config.sampling.replace_output {
+                            blocks[1] = json!({"type": "text", "text": text});
+                            sampled["replaced_output"] = json!(true);
+                        } else {
+                            blocks.insert(1, json!({"type": "text", "text": text}));
+                        }
+                        structured["sampled_summary"] = sampled;
+                    }
+                    write_json_line(
+                        writer,
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "result": {
+                                "content": content,
+                                "isError": collected.final_status.exit_code.unwrap_or(1) != 0 || collected.final_status.timed_out,
+                                "structuredContent": structured
+                            }
+                        }),
+                    )
+                    .await?;
+                }
+                Err(error) => {
+                    write_json_line(
+                        writer,
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": id,
+                            "error": {
+                                "code": -32000,
+                                "message": error.to_string(),
+                                "data": {"run_id": correlation_id, "code": error_code(&error)}
+                            }
+                        }),
+                    )
+                    .await?;
+                }
+            }
+        }
+        _ => {
+            write_json_line(
+                writer,
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {
+                        "code": -32601,
+                        "message": format!("method not found: {}", request.method)
+                    }
+                }),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn client_output_root(config: &BridgeConfig, peer: Option<&Peer>) -> Option<PathBuf> {
+    let peer =
+        peer.filter(|peer| config.artifacts.client_roots.enabled && peer.supports("roots"))?;
+    match peer.roots().await {
+        Ok(uris) => {
+            let root = config.artifacts.client_roots.select(&uris);
+            if root.is_none() {
+                log_observation("client_roots_rejected", json!({"roots": uris}));
+            }
+            root
+        }
+        Err(error) => {
+            log_observation(
+                "client_roots_failed",
+                json!({"error": format!("{:#}", error)}),
+            );
+            None
+        }
+    }
+}
+
+async fn sample_output(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    peer: &Peer,
+    correlation_id: &str,
+    tool: &str,
+    output: &str,
+) -> Option<Value> {
+    let request = peer.request(
+        "sampling/createMessage",
+        config.sampling.request(tool, output),
+    );
+    let result = match tokio::time::timeout(
+        Duration::from_secs(config.sampling.timeout_sec),
+        request,
+    )
+    .await
+    {
+        Ok(Ok(result)) => result,
+        Ok(Err(error)) => {
+            log_observation(
+                "sampling_failed",
+                json!({"correlation_id": correlation_id, "error": format!("{:#}", error)}),
+            );
+            return None;
+        }
+        Err(_) => {
+            log_observation(
+                "sampling_failed",
+                json!({"correlation_id": correlation_id, "error": format!("keine Antwort innerhalb von {}s", config.sampling.timeout_sec)}),
+            );
+            return None;
+        }
+    };
+    let Some(text) = sampling::summary(&result) else {
+        log_observation(
+            "sampling_failed",
+            json!({"correlation_id": correlation_id, "error": "Antwort ohne Text"}),
+        );
+        return None;
+    };
+    let sampled = json!({"text": text, "model": result["model"], "input_bytes": output.len()});
+    let entry = json!({"correlation_id": correlation_id, "tool": tool, "summary": sampled});
+    if let Err(error) = audit(runtime, "output_summarized", entry) {
+        log_observation(
+            "sampling_failed",
+            json!({"correlation_id": correlation_id, "error": format!("{:#}", error)}),
+        );
+        return None;
+    }
+    log_observation(
+        "output_summarized",
+        json!({"correlation_id": correlation_id, "tool": tool, "model": result["model"], "input_bytes": output.len()}),
+    );
+    Some(sampled)
+}
+
+fn mcp_correlation_id(runtime: &Runtime, id: &Value, session: Option<&str>) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let namespace = format!(
+        "mcp-{}-{}",
+        session.unwrap_or(&runtime.run_prefix),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    match id {
+        Value::String(value) => format!("{}-{}", namespace, value),
+        Value::Null => format!("{}-call", namespace),
+        other => format!("{}-{}", namespace, other),
+    }
+}
+
+async fn serve_console(runtime: Arc<Runtime>, mut defaults: console::Defaults) -> Result<()> {
+    let mut lines = BufReader::new(io::stdin()).lines();
+    let mut signals = ShutdownSignals::new()?;
+    let (out, mut rx) = channel_writer();
+    let printer = tokio::spawn(async move {
+        let mut pending = Vec::new();
+        while let Some(chunk) = rx.recv().await {
+            pending.extend(chunk);
+            while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+                let line = pending.drain(..=end).collect::<Vec<_>>();
+                if let Some(text) = serde_json::from_slice::<Value>(&line)
+                    .ok()
+                    .as_ref()
+                    .and_then(console::format_event)
+                {
+                    print!("{}", text);
+                    let _ = std::io::Write::flush(&mut std::io::stdout());
+                }
+            }
+        }
+    });
+    let history = Arc::new(console::History::default());
+    let mut tasks = JoinSet::new();
+    println!("Bridge-Konsole, 'help' zeigt die Befehle.");
+
+    let signal = loop {
+        print!("bridge> ");
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+        let line = match next_incoming(&mut lines, &mut signals).await? {
+            Incoming::Line(line) => line,
+            Incoming::Closed => break None,
+            Incoming::Shutdown(signal) => break Some(signal),
+        };
+        while tasks.try_join_next().is_some() {}
+        let command = match console::parse(&line) {
+            Ok(Some(ConsoleCommand::Quit)) => break None,
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(error) => {
+                println!("{:#}", error);
+                continue;
+            }
+        };
+        let config = runtime.config();
+        let handled = match command {
+            ConsoleCommand::Help => {
+                println!("{}", console::HELP);
+                Ok(())
+            }
+            ConsoleCommand::Tools => {
+                let mut tools = config.tools.iter().collect::<Vec<_>>();
+                tools.sort_by_key(|(name, _)| *name);
+                for (name, policy) in tools {
+                    let mut line = format!(
+                        "{:<20} {}",
+                        name,
+                        policy.profile.map_or("-", ToolProfile::as_str)
+                    );
+                    if !policy.enabled {
+                        line.push_str("  [deaktiviert]");
+                    }
+                    if needs_approval(policy) {
+                        line.push_str("  [Freigabe]");
+                    }
+                    println!("{}", line);
+                }
+                Ok(())
+            }
+            ConsoleCommand::Show(tool) => runtime.config_snapshot().and_then(|snapshot| {
+                let policy = config
+                    .tools
+                    .get(&tool)
+                    .with_context(|| format!("tool '{}' ist nicht konfiguriert", tool))?;
+                let mut shown = json!({"tool": tool, "policy": snapshot["config"]["tools"][&tool]});
+                if let Some(profile) = policy.profile {
+                    shown["params"] = profile.mcp_properties();
+                }
+                println!("{}", serde_json::to_string_pretty(&shown)?);
+                Ok(())
+            }),
+            ConsoleCommand::Set(None) => {
+                println!("{}", serde_json::to_string_pretty(&defaults.show())?);
+                Ok(())
+            }
+            ConsoleCommand::Set(Some((key, value))) => defaults.set(&key, &value),
+            ConsoleCommand::Run { dry, spec } => {
+                console_request(&config, &defaults, &history, spec).and_then(|request| {
+                    if dry {
+                        return console_dry_run(&config, &runtime, request);
+                    }
+                    spawn_console_run(&runtime, &history, &mut tasks, &out, request)
+                })
+            }
+            ConsoleCommand::History(None) => {
+                println!("{}", history.overview());
+                Ok(())
+            }
+            ConsoleCommand::History(Some(index)) => history.detail(index).and_then(|detail| {
+                println!("{}", serde_json::to_string_pretty(&detail)?);
+                Ok(())
+            }),
+            ConsoleCommand::Rerun(index) => history
+                .request(index)
+                .and_then(|mut request| {
+                    request["id"] = json!(history.next_id());
+                    serde_json::from_value::<RunRequest>(request)
+                        .context("Request aus der Historie ist ungültig")
+                })
+                .and_then(|request| {
+                    spawn_console_run(&runtime, &history, &mut tasks, &out, request)
+                }),
+            ConsoleCommand::Control(command) => serde_json::from_value::<ControlCommand>(command)
+                .context("ungültiges Control-Kommando")
+                .and_then(|command| apply_control(&runtime, command, "console"))
+                .and_then(|status| {
+                    println!("{}", serde_json::to_string_pretty(&status)?);
+                    Ok(())
+                }),
+            ConsoleCommand::Quit => unreachable!("quit beendet die Schleife vorher"),
+        };
+        if let Err(error) = handled {
+            println!("{}: {:#}", messages::error_prefix(), error);
+        }
+    };
+
+    let config = runtime.config();
+    match signal {
+        Some(signal) => {
+            drain_tasks(&config, &runtime, &mut tasks, signal).await;
+        }
+        None if tasks.is_empty() => {}
+        None => {
+            println!("warte auf {} laufende Runs (Strg+C bricht ab)", tasks.len());
+            tokio::select! {
+                joined = join_tasks(&mut tasks) => joined?,
+                signal = signals.recv() => {
+                    drain_tasks(&config, &runtime, &mut tasks, signal).await;
+                }
+            }
+        }
+    }
+    drop(out);
+    printer
+        .await
+        .context("Konsolenausgabe konnte nicht beendet werden")?;
+    Ok(())
+}
+
+fn console_request(
+    config: &BridgeConfig,
+    defaults: &console::Defaults,
+    history: &console::History,
+    spec: RequestSpec,
+) -> Result<RunRequest> {
+    let mut request = match spec {
+        RequestSpec::Json(text) => {
+            match serde_json::from_str::<Value>(&text).context("Request ist kein gültiges JSON")? {
+                Value::Object(request) => request,
+                _ => bail!("Request muss ein JSON-Objekt sein"),
+            }
+        }
+        RequestSpec::Words(tool, words) => {
+            let mut request = serde_json::Map::new();
+            let profile = config.tools.get(&tool).and_then(|policy| policy.profile);
+            match profile {
+                Some(_) => request.insert(
+                    "params".to_string(),
+                    Value::Object(console::params(&words)?),
+                ),
+                None => request.insert("args".to_string(), json!(words)),
+            };
+            request.insert("tool".to_string(), json!(tool));
+            request
+        }
+    };
+    defaults.apply(&mut request);
+    request
+        .entry("id")
+        .or_insert_with(|| json!(history.next_id()));
+    if !request.contains_key("host") {
+        bail!("kein Host angegeben: set host <host>, --host oder \"host\" im JSON");
+    }
+    serde_json::from_value(Value::Object(request)).context("ungültiger RunRequest")
+}
+
+fn console_dry_run(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    mut request: RunRequest,
+) -> Result<()> {
+    let policy = admit_request(config, runtime, &mut request)?;
+    let sudo = sudo_prefix(policy);
+    let stdin = request
+        .stdin
+        .as_ref()
+        .map(|stdin| stdin.bytes(&SecretValues::default()))
+        .transpose()?;
+    let plan = json!({
+        "tool": request.tool,
+        "target": format_target(&request.user, &request.host)?,
+        "args": request.args,
+        "stdin_bytes": stdin.as_ref().map(Vec::len),
+        "timeout_sec": request.timeout_sec.unwrap_or(config.default_timeout_sec).min(config.max_timeout_sec),
+        "engagement": request.engagement,
+        "requires_approval": needs_approval(policy),
+        "run_as": policy.run_as,
+        "command": command_line(policy, sudo.as_deref(), &request.args, &SecretValues::default(), false)
+    });
+    println!("{}", serde_json::to_string_pretty(&plan)?);
+    Ok(())
+}
+
+fn spawn_console_run(
+    runtime: &Arc<Runtime>,
+    history: &Arc<console::History>,
+    tasks: &mut JoinSet<Result<()>>,
+    out: &ChannelWriter,
+    request: RunRequest,
+) -> Result<()> {
+    let id = request.id.clone().unwrap_or_else(|| "request".to_string());
+    let index = history.push(&id, serde_json::to_value(&request)?);
+    let (config, runtime, history, mut out) = (
+        runtime.config(),
+        runtime.clone(),
+        history.clone(),
+        out.clone(),
+    );
+    let streams = config
+        .tools
+        .get(&request.tool)
+        .and_then(|policy| policy.profile)
+        .is_none_or(ToolProfile::streams);
+    tasks.spawn(async move {
+        let result = if streams {
+            run_request(&config, &runtime, request, &mut out).await.map(|status| {
+                json!({"exit_code": status.exit_code, "timed_out": status.timed_out, "duration_ms": status.duration_ms})
+            })
+        } else {
+            emit(
+                &mut out,
+                Event {
+                    id: id.clone(),
+                    event: "collecting".to_string(),
+                    payload: json!({"hint": "Profil ohne Live-Ausgabe, Freigaben ggf. über approvals"}),
+                },
+            )
+            .await?;
+            execute_request_collect(&config, &runtime, request).await.map(|collected| rest_result(&collected))
+        };
+        match &result {
+            Ok(status) if !streams => {
+                emit(&mut out, Event { id: id.clone(), event: "result".to_string(), payload: status.clone() }).await?
+            }
+            Ok(_) => {}
+            Err(error) => {
+                emit(
+                    &mut out,
+                    Event {
+                        id: id.clone(),
+                        event: "error".to_string(),
+                        payload: json!({"code": error_code(error), "message": format!("{:#}", error)}),
+                    },
+                )
+                .await?
+            }
+        }
+        history.finish(index, result.map_err(|error| format!("{:#}", error)));
+        Ok(())
+    });
+    Ok(())
+}
+
+async fn serve_workflow_stdio(config: Arc<BridgeConfig>, runtime: Arc<Runtime>) -> Result<()> {
+    let stdin = io::stdin();
+    let mut lines = BufReader::new(stdin).lines();
+    let mut signals = ShutdownSignals::new()?;
+    let (mut out, writer_task) = spawn_stdout_writer();
+    let mut tasks = JoinSet::new();
+
+    let shutdown_signal = loop {
+        let line = match next_incoming(&mut lines, &mut signals).await? {
+            Incoming::Line(line) => line,
+            Incoming::Closed => break None,
+            Incoming::Shutdown(signal) => break Some(signal),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let workflow: WorkflowRequest = match serde_json::from_str(&line) {
+            Ok(req) => req,
+            Err(error) => {
+                runtime.notifier.notify(
+                    NotificationEvent::Error,
+                    "workflow",
+                    json!({"code": "E_PARSE", "message": error.to_string()}),
+                );
+                emit(
+                    &mut out,
+                    Event {
+                        id: "workflow".to_string(),
+                        event: "error".to_string(),
+                        payload: json!({"code": "E_PARSE", "message": error.to_string()}),
+                    },
+                )
+                .await?;
+                continue;
+            }
+        };
+
+        let config = runtime.config();
+        let runtime = runtime.clone();
+        let mut out = out.clone();
+        tasks.spawn(async move { run_workflow(&config, &runtime, workflow, &mut out).await });
+    };
+
+    finish_serve(
+        &config,
+        &runtime,
+        ServeProtocol::Events,
+        tasks,
+        out,
+        writer_task,
+        shutdown_signal,
+    )
+    .await
+}
+
+#[derive(Clone)]
+enum HttpApi {
+    Mcp,
+    Rest(Arc<RunStore>),
+}
+
+async fn serve_http(
+    config: Arc<BridgeConfig>,
+    runtime: Arc<Runtime>,
+    config_path: &str,
+    api: HttpApi,
+) -> Result<()> {
+    let addr = config
+        .http
+        .listen
+        .clone()
+        .context("kein HTTP-Listener angegeben (--listen oder http.listen)")?;
+    let auth = Arc::new(http::Auth::new(&config.http.tokens)?);
+    rbac::validate(&config.rbac, &config.http.tokens)?;
+    let mut acceptor = tls::acceptor(&config.http)?;
+    let listener = http::bind(&addr).await?;
+    log_observation(
+        "http_listening",
+        json!({
+            "addr": addr,
+            "api": match api {
+                HttpApi::Mcp => "mcp",
+                HttpApi::Rest(_) => "rest",
+            },
+            "identities": config.http.tokens.len(),
+            "tls": acceptor.is_some(),
+            "client_cert_required": config.http.tls_client_cert_required
+        }),
+    );
+
+    let mut signals = ShutdownSignals::new()?;
+    let mut hangup = Hangup::new()?;
+    let mut connections = JoinSet::new();
+
+    let signal = loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, peer)) = accepted else {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                };
+                let (config, runtime, auth, acceptor, api) =
+                    (config.clone(), runtime.clone(), auth.clone(), acceptor.clone(), api.clone());
+                connections.spawn(async move {
+                    let handler = |request: HttpRequest| match &api {
+                        _ if http::is_probe(&request.method, &request.path) => {
+                            Box::pin(std::future::ready(health_probe(&runtime, &request.path)))
+                                as Pin<Box<dyn Future<Output = HttpResponse> + Send>>
+                        }
+                        HttpApi::Mcp => Box::pin(handle_http_request(runtime.config(), runtime.clone(), request))
+                            as Pin<Box<dyn Future<Output = HttpResponse> + Send>>,
+                        HttpApi::Rest(store) => Box::pin(handle_rest_request(runtime.config(), runtime.clone(), store.clone(), request)),
+                    };
+                    let max_body_bytes = config.http.max_body_bytes;
+                    match acceptor {
+                        Some(acceptor) => match tls::accept(&acceptor, stream).await {
+                            Ok((stream, client_cert)) => {
+                                http::handle_connection(stream, peer, client_cert, &auth, max_body_bytes, &handler).await
+                            }
+                            Err(error) => {
+                                log_observation(
+                                    "tls_handshake_failed",
+                                    json!({"peer": peer.to_string(), "message": format!("{:#}", error)}),
+                                );
+                                Ok(())
+                            }
+                        },
+                        None => http::handle_connection(stream, peer, None, &auth, max_body_bytes, &handler).await,
+                    }
+                });
+            }
+            _ = hangup.recv() => {
+                let reloaded = load_config(config_path).await.and_then(|reloaded| {
+                    let reloaded_acceptor = match acceptor {
+                        Some(_) => Some(tls::acceptor(&reloaded.http)?.context("TLS kann per SIGHUP nicht deaktiviert werden")?),
+                        None => None,
+                    };
+                    let identities = auth.reload(&reloaded.http.tokens)?;
+                    if reloaded_acceptor.is_some() {
+                        acceptor = reloaded_acceptor;
+                    }
+                    Ok(identities)
+                });
+                match reloaded {
+                    Ok(identities) => {
+                        log_observation("auth_reloaded", json!({"identities": identities, "tls": acceptor.is_some()}))
+                    }
+                    Err(error) => log_observation("auth_reload_failed", json!({"message": format!("{:#}", error)})),
+                }
+            }
+            signal = signals.recv() => break signal,
+        }
+        while connections.try_join_next().is_some() {}
+    };
+
+    drop(listener);
+    drain_tasks(&config, &runtime, &mut connections, signal).await;
+    Ok(())
+}
+
+async fn handle_http_request(
+    config: Arc<BridgeConfig>,
+    runtime: Arc<Runtime>,
+    request: HttpRequest,
+) -> HttpResponse {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/run") => match serde_json::from_slice::<RunRequest>(&request.body) {
+            Ok(mut run) => {
+                run.client = Some(request.identity);
+                let (mut out, rx) = channel_writer();
+                HttpResponse::Stream(
+                    rx,
+                    Box::pin(async move { serve_run(&config, &runtime, run, &mut out).await }),
+                )
+            }
+            Err(error) => HttpResponse::Json(
+                400,
+                json!({"code": "E_PARSE", "message": error.to_string()}),
+            ),
+        },
+        ("POST", "/workflow") => match serde_json::from_slice::<WorkflowRequest>(&request.body) {
+            Ok(mut workflow) => {
+                workflow.client = Some(request.identity);
+                let (mut out, rx) = channel_writer();
+                HttpResponse::Stream(
+                    rx,
+                    Box::pin(
+                        async move { run_workflow(&config, &runtime, workflow, &mut out).await },
+                    ),
+                )
+            }
+            Err(error) => HttpResponse::Json(
+                400,
+                json!({"code": "E_PARSE", "message": error.to_string()}),
+            ),
+        },
+        ("POST", "/mcp") => {
+            let rpc = match serde_json::from_slice::<JsonRpcRequest>(&request.body) {
+                Ok(rpc) => rpc,
+                Err(error) => {
+                    return HttpResponse::Json(
+                        400,
+                        json!({
+                            "jsonrpc": "2.0",
+                            "id": Value::Null,
+                            "error": {"code": -32700, "message": format!("parse error: {}", error)}
+                        }),
+                    );
+                }
+            };
+            let initialize = rpc.method == "initialize";
+            let session = match (initialize, request.session) {
+                (true, _) => match runtime.sessions.create(&request.identity) {
+                    Ok(session) => {
+                        runtime.budgets.retain(&runtime.sessions.ids());
+                        session
+                    }
+                    Err(error) => {
+                        return HttpResponse::Json(
+                            503,
+                            http::failure_body(&error, "E_SESSION_LIMIT"),
+                        );
+                    }
+                },
+                (false, Some(session)) => match runtime.sessions.touch(&session, &request.identity)
+                {
+                    Ok(()) => session,
+                    Err(error) => {
+                        return HttpResponse::Json(
+                            404,
+                            http::failure_body(&error, "E_SESSION_NOT_FOUND"),
+                        );
+                    }
+                },
+                (false, None) => {
+                    return HttpResponse::Json(400, http::error_body(&Message::SessionRequired));
+                }
+            };
+            let mut out = Vec::new();
+            let handled = handle_mcp_request(
+                &config,
+                &runtime,
+                rpc,
+                Some(&request.identity),
+                Some(&session),
+                None,
+                &mut out,
+            )
+            .await;
+            if let Err(error) = handled {
+                return HttpResponse::Json(500, http::failure_body(&error, "E_EXEC"));
+            }
+            match out
+                .split(|byte| *byte == b'\n')
+                .rfind(|line| !line.is_empty())
+            {
+                Some(line) => match serde_json::from_slice::<Value>(line) {
+                    Ok(response) if initialize => HttpResponse::Session(session, response),
+                    Ok(response) => HttpResponse::Json(200, response),
+                    Err(_) => {
+                        HttpResponse::Json(500, http::error_body(&Message::UnexpectedResponse))
+                    }
+                },
+                None => HttpResponse::Accepted,
+            }
+        }
+        ("DELETE", "/mcp") => match request.session {
+            Some(session) if runtime.sessions.close(&session, &request.identity) => {
+                runtime.budgets.retain(&runtime.sessions.ids());
+                HttpResponse::Json(200, json!({"session": session, "closed": true}))
+            }
+            session => HttpResponse::Json(
+                404,
+                http::error_body(&Message::SessionUnknown {
+                    id: session.unwrap_or_default(),
+                }),
+            ),
+        },
+        ("GET", "/ws") => websocket_session(runtime, request),
+        (_, "/run" | "/workflow") => HttpResponse::Json(
+            405,
+            http::error_body(&Message::MethodNotAllowed { allowed: "POST" }),
+        ),
+        (_, "/ws") => HttpResponse::Json(
+            405,
+            http::error_body(&Message::MethodNotAllowed { allowed: "GET" }),
+        ),
+        (_, "/mcp") => HttpResponse::Json(
+            405,
+            http::error_body(&Message::MethodNotAllowed {
+                allowed: "POST, DELETE",
+            }),
+        ),
+        _ => HttpResponse::Json(404, http::error_body(&Message::UnknownPath)),
+    }
+}
+
+fn health_probe(runtime: &Runtime, path: &str) -> HttpResponse {
+    let report = runtime.health();
+    let status = if path == "/readyz" && report["ready"] != true {
+        503
+    } else {
+        200
+    };
+    HttpResponse::Json(status, health::summary(&report))
+}
+
+async fn handle_rest_request(
+    config: Arc<BridgeConfig>,
+    runtime: Arc<Runtime>,
+    store: Arc<RunStore>,
+    request: HttpRequest,
+) -> HttpResponse {
+    let path = request
+        .path
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match (request.method.as_str(), path.as_str()) {
+        ("GET", "/health") => {
+            let mut health = runtime.health();
+            health["runs"] = store.counts();
+            health["bridge"] = runtime.control_status();
+            HttpResponse::Json(200, health)
+        }
+        ("GET", "/openapi.json") => {
+            let mut tools = config
+                .tools
+                .iter()
+                .filter(|(name, policy)| {
+                    policy.enabled && rbac::allows_tool(&config.rbac, Some(&request.identity), name)
+                })
+                .map(|(name, policy)| {
+                    (
+                        name.clone(),
+                        policy.profile.map(ToolProfile::mcp_properties),
+                    )
+                })
+                .collect::<Vec<_>>();
+            tools.sort_by(|left, right| left.0.cmp(&right.0));
+            HttpResponse::Json(200, rest::openapi(&tools))
+        }
+        ("POST", "/runs") => {
+            let mut run = match serde_json::from_slice::<RunRequest>(&request.body) {
+                Ok(run) => run,
+                Err(error) => {
+                    return HttpResponse::Json(
+                        400,
+                        json!({"error": error.to_string(), "code": "E_PARSE"}),
+                    );
+                }
+            };
+            if let Err(error) = validate_request(&config, &run) {
+                return HttpResponse::Json(
+                    400,
+                    json!({"error": error.to_string(), "code": error_code(&error)}),
+                );
+            }
+            let id = match store.create(RunKind::Run, run.id.take(), &request.identity, &run.labels)
+            {
+                Ok(id) => id,
+                Err((status, error)) => {
+                    return HttpResponse::Json(status, http::error_body(&error));
+                }
+            };
+            run.id = Some(id.clone());
+            run.client = Some(request.identity);
+            let accepted = json!({"id": id, "kind": "run", "state": "running", "location": format!("/runs/{}", id)});
+            HttpResponse::Background(
+                202,
+                accepted,
+                Box::pin(async move {
+                    let result = execute_request_collect(&config, &runtime, run).await;
+                    store.finish(&id, result.map(|collected| rest_result(&collected)));
+                    Ok(())
+                }),
+            )
+        }
+        ("POST", "/workflows") => {
+            let mut workflow = match serde_json::from_slice::<WorkflowRequest>(&request.body) {
+                Ok(workflow) => workflow,
+                Err(error) => {
+                    return HttpResponse::Json(
+                        400,
+                        json!({"error": error.to_string(), "code": "E_PARSE"}),
+                    );
+                }
+            };
+            if let Err(error) = workflow_runs(&config, &workflow.steps) {
+                return HttpResponse::Json(
+                    400,
+                    json!({"error": error.to_string(), "code": error_code(&error)}),
+                );
+            }
+            let id = match store.create(
+                RunKind::Workflow,
+                workflow.id.take(),
+                &request.identity,
+                &workflow.labels,
+            ) {
+                Ok(id) => id,
+                Err((status, error)) => {
+                    return HttpResponse::Json(status, http::error_body(&error));
+                }
+            };
+            workflow.id = Some(id.clone());
+            workflow.client = Some(request.identity);
+            let accepted = json!({"id": id, "kind": "workflow", "state": "running", "location": format!("/workflows/{}", id)});
+            HttpResponse::Background(
+                202,
+                accepted,
+                Box::pin(async move {
+                    let (mut out, mut rx) = channel_writer();
+                    let run = async move {
+                        let result = run_workflow(&config, &runtime, workflow, &mut out).await;
+                        drop(out);
+                        result
+                    };
+                    let collect = async {
+                        let mut pending = Vec::new();
+                        let mut last = None;
+                        while let Some(chunk) = rx.recv().await {
+                            pending.extend(chunk);
+                            while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+                                let line = pending.drain(..=end).collect::<Vec<_>>();
+                                if let Ok(event) = serde_json::from_slice::<Value>(&line) {
+                                    last = Some(event.clone());
+                                    store.push_event(&id, event);
+                                }
+                            }
+                        }
+                        last
+                    };
+                    let (ran, last) = tokio::join!(run, collect);
+                    match (ran, last) {
+                        (Ok(()), Some(event)) if event["event"] == "error" => {
+                            let failed = Message::WorkflowFailed;
+                            store.fail(
+                                &id,
+                                event["payload"]["code"].as_str().unwrap_or(failed.code()),
+                                event["payload"]["message"]
+                                    .as_str()
+                                    .unwrap_or(&failed.to_string()),
+                            );
+                        }
+                        (ran, last) => store.finish(
+                            &id,
+                            ran.map(|()| {
+                                last.map_or(Value::Null, |event| event["payload"].clone())
+                            }),
+                        ),
+                    }
+                    Ok(())
+                }),
+            )
+        }
+        ("GET", "/runs") => {
+            let filters = request
+                .path
+                .split_once('?')
+                .map(|(_, query)| {
+                    query
+                        .split('&')
+                        .filter_map(|pair| pair.strip_prefix("label="))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            match filters
+                .into_iter()
+                .map(labels::parse_pair)
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(filters) => HttpResponse::Json(200, store.list(&request.identity, &filters)),
+                Err(error) => {
+                    HttpResponse::Json(400, http::failure_body(&error, "E_INVALID_ARGUMENTS"))
+                }
+            }
+        }
+        ("GET", path) if path.starts_with("/runs/") || path.starts_with("/workflows/") => {
+            let (prefix, id) = path[1..].split_once('/').unwrap_or_default();
+            match store.get(id, &request.identity) {
+                Some(run) if prefix == "runs" || run["kind"] == "workflow" => {
+                    HttpResponse::Json(200, run)
+                }
+                _ => HttpResponse::Json(
+                    404,
+                    http::error_body(&Message::RunUnknown { id: id.to_string() }),
+                ),
+            }
+        }
+        ("GET", "/ws") => websocket_session(runtime, request),
+        (_, "/runs") => HttpResponse::Json(
+            405,
+            http::error_body(&Message::MethodNotAllowed {
+                allowed: "GET, POST",
+            }),
+        ),
+        (_, "/workflows") => HttpResponse::Json(
+            405,
+            http::error_body(&Message::MethodNotAllowed { allowed: "POST" }),
+        ),
+        (_, "/health" | "/openapi.json" | "/ws") => HttpResponse::Json(
+            405,
+            http::error_body(&Message::MethodNotAllowed { allowed: "GET" }),
+        ),
+        _ => HttpResponse::Json(404, http::error_body(&Message::UnknownPath)),
+    }
+}
+
+enum SocketRequest {
+    Run(Box<RunRequest>),
+    Workflow(WorkflowRequest),
+}
+
+fn websocket_session(runtime: Arc<Runtime>, request: HttpRequest) -> HttpResponse {
+    if !request.websocket {
+        return HttpResponse::Json(426, http::error_body(&Message::WebSocketExpected));
+    }
+    let (incoming, mut messages) = mpsc::unbounded_channel::<String>();
+    let (out, rx) = channel_writer();
+    let identity = request.identity;
+    HttpResponse::WebSocket(
+        incoming,
+        rx,
+        Box::pin(async move {
+            let mut tasks = JoinSet::new();
+            loop {
+                let message = tokio::select! {
+                    message = messages.recv() => message,
+                    _ = runtime.draining.cancelled() => None,
+                };
+                let Some(message) = message else {
+                    break;
+                };
+                let parsed = serde_json::from_str::<Value>(&message).and_then(|value| match value
+                    .get("steps")
+                {
+                    Some(_) => serde_json::from_value(value).map(SocketRequest::Workflow),
+                    None => {
+                        serde_json::from_value(value).map(|run| SocketRequest::Run(Box::new(run)))
+                    }
+                });
+                let (config, runtime, mut out) = (runtime.config(), runtime.clone(), out.clone());
+                match parsed {
+                    Ok(SocketRequest::Run(mut run)) => {
+                        run.client = Some(identity.clone());
+                        tasks.spawn(
+                            async move { serve_run(&config, &runtime, *run, &mut out).await },
+                        );
+                    }
+                    Ok(SocketRequest::Workflow(mut workflow)) => {
+                        workflow.client = Some(identity.clone());
+                        tasks.spawn(async move {
+                            run_workflow(&config, &runtime, workflow, &mut out).await
+                        });
+                    }
+                    Err(error) => {
+                        emit(
+                            &mut out,
+                            Event {
+                                id: "unknown".to_string(),
+                                event: "error".to_string(),
+                                payload: json!({
+                                    "code": "E_PARSE",
+                                    "message": error.to_string()
+                                }),
+                            },
+                        )
+                        .await?;
+                    }
+                }
+                while tasks.try_join_next().is_some() {}
+            }
+            drop(out);
+            join_tasks(&mut tasks).await
+        }),
+    )
+}
+
+fn rest_result(collected: &CollectedRun) -> Value {
+    let mut result = collected_json(collected);
+    result["stdout"] = json!(collected.stdout);
+    result["stderr"] = json!(collected.stderr);
+    if !collected.images.is_empty() {
+        result["images"] = collected
+            .images
+            .iter()
+            .map(|image| json!({"name": image.name, "mime_type": image.mime_type, "data": BASE64.encode(&image.data)}))
+            .collect();
+    }
+    result
+}
+
+fn output_store_enabled(config: &BridgeConfig) -> bool {
+    config.mcp.inline_output_bytes > 0 || config.context_budget.max_tokens > 0
+}
+
+fn collected_json(collected: &CollectedRun) -> Value {
+    let mut structured = json!({
+        "exit_code": collected.final_status.exit_code,
+        "timed_out": collected.final_status.timed_out,
+        "duration_ms": collected.final_status.duration_ms,
+        "failure_class": classify_failure(&collected.final_status),
+        "truncated": collected.truncated,
+        "encoding": collected.encoding,
+        "lossy_utf8": collected.lossy,
+        "attempts": collected.attempts,
+        "cached": collected.cached,
+        "deduplicated": collected.deduplicated,
+        "artifacts": collected.artifacts
+    });
+    if let Some(findings) = &collected.findings {
+        structured["findings_summary"] = findings::summary(findings);
+        structured["findings"] = json!(findings);
+    }
+    if let Some(summary) = &collected.summary {
+        structured["summary"] = summary.clone();
+    }
+    if let Some(manifest) = &collected.manifest {
+        structured["manifest"] = json!(manifest);
+    }
+    structured
+}
+
+async fn next_incoming(
+    lines: &mut Lines<BufReader<Stdin>>,
+    signals: &mut ShutdownSignals,
+) -> Result<Incoming> {
+    tokio::select! {
+        line = lines.next_line() => Ok(match line? {
+            Some(line) => Incoming::Line(line),
+            None => Incoming::Closed,
+        }),
+        signal = signals.recv() => Ok(Incoming::Shutdown(signal)),
+    }
+}
+
+async fn join_tasks(tasks: &mut JoinSet<Result<()>>) -> Result<()> {
+    let mut first_error = None;
+    while let Some(joined) = tasks.join_next().await {
+        let result = joined
+            .context("request task join fehlgeschlagen")
+            .and_then(|result| result);
+        if let Err(error) = result {
+            first_error.get_or_insert(error);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+async fn drain_tasks(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    tasks: &mut JoinSet<Result<()>>,
+    signal: &'static str,
+) -> Value {
+    let in_flight = tasks.len();
+    runtime.draining.trigger();
+    log_observation(
+        "shutdown_draining",
+        json!({
+            "signal": signal,
+            "in_flight": in_flight,
+            "drain_timeout_sec": config.shutdown_drain_timeout_sec
+        }),
+    );
+
+    let drain_limit = Duration::from_secs(config.shutdown_drain_timeout_sec);
+    let drained = tokio::time::timeout(drain_limit, join_tasks(tasks)).await;
+    let cancelled = tasks.len();
+    if drained.is_err() {
+        runtime.cancel.trigger();
+        let cleanup_limit = Duration::from_secs(config.ssh_connect_timeout_sec.saturating_add(15));
+        if tokio::time::timeout(cleanup_limit, join_tasks(tasks))
+            .await
+            .is_err()
+        {
+            tasks.abort_all();
+            let _ = join_tasks(tasks).await;
+        }
+    }
+
+    let payload = json!({
+        "signal": signal,
+        "in_flight": in_flight,
+        "drained": in_flight - cancelled,
+        "cancelled": cancelled
+    });
+    log_observation("shutdown_complete", payload.clone());
+    payload
+}
+
+async fn finish_serve(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    protocol: ServeProtocol,
+    mut tasks: JoinSet<Result<()>>,
+    mut out: ChannelWriter,
+    writer_task: JoinHandle<Result<()>>,
+    shutdown_signal: Option<&'static str>,
+) -> Result<()> {
+    let Some(signal) = shutdown_signal else {
+        let joined = join_tasks(&mut tasks).await;
+        drop(out);
+        writer_task
+            .await
+            .context("stdout writer join fehlgeschlagen")??;
+        return joined;
+    };
+
+    let payload = drain_tasks(config, runtime, &mut tasks, signal).await;
+    match protocol {
+        ServeProtocol::Events => {
+            emit(
+                &mut out,
+                Event {
+                    id: "bridge".to_string(),
+                    event: "shutdown".to_string(),
+                    payload,
+                },
+            )
+            .await?;
+        }
+        ServeProtocol::JsonRpc => {
+            write_json_line(
+                &mut out,
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/message",
+                    "params": {
+                        "level": "notice",
+                        "logger": "ollama-kali-mcp-bridge",
+                        "data": {"event": "shutdown", "payload": payload}
+                    }
+                }),
+            )
+            .await?;
+        }
+    }
+
+    drop(out);
+    writer_task
+        .await
+        .context("stdout writer join fehlgeschlagen")?
+}
+
+#[tracing::instrument(
+    name = "workflow",
+    skip_all,
+    fields(correlation_id = workflow.id.as_deref().unwrap_or("workflow"), steps = workflow.steps.len())
+)]
+async fn run_workflow<W: AsyncWrite + Unpin>(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    workflow: WorkflowRequest,
+    writer: &mut W,
+) -> Result<()> {
+    let id = workflow.id.unwrap_or_else(|| "workflow".to_string());
+    let stop_on_error = workflow.stop_on_error;
+    let mut last_status = json!({"state": "empty"});
+
+    let prepared = workflow_runs(config, &workflow.steps)
+        .and_then(|runs| Ok((runs, workflow_scripts(config, &id, &workflow.steps)?)));
+    let (runs, scripts) = match prepared {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            runtime.notifier.notify(
+                NotificationEvent::Error,
+                &id,
+                json!({"code": error_code(&error), "message": error.to_string()}),
+            );
+            return emit(
+                writer,
+                Event {
+                    id,
+                    event: "error".to_string(),
+                    payload: json!({"code": error_code(&error), "message": error.to_string()}),
+                },
+            )
+            .await;
+        }
+    };
+
+    let lease = workflow_connection(config, runtime, &workflow.user, &workflow.host).await;
+    let mut started = json!({
+                "shared_connection": lease.is_some(),
+                "steps": workflow.steps.len(),
+                "runs": runs.iter().filter(|(_, item, step)| item.is_some() || step.for_each.is_none()).count(),
+                "deferred": runs
+                    .iter()
+                    .filter(|(_, item, step)| item.is_none() && step.for_each.is_some())
+                    .map(|(index, _, _)| index)
+                    .collect::<Vec<_>>()
+    });
+    labels::attach(&mut started, &workflow.labels);
+    emit(
+        writer,
+        Event {
+            id: id.clone(),
+            event: "workflow_started".to_string(),
+            payload: started,
+        },
+    )
+    .await?;
+
+    let mut vars = workflow.vars;
+    let mut runs = VecDeque::from(runs);
+    while let Some((index, item, step)) = runs.pop_front() {
+        if runtime.cancel.is_triggered() {
+            break;
+        }
+        if let (Some(list), None) = (&step.for_each, &item) {
+            match targets::lookup(&config.targets, list) {
+                Ok(hosts) => {
+                    for (item, target) in hosts.into_iter().enumerate().rev() {
+                        runs.push_front((index, Some((item, target)), step));
+                    }
+                    continue;
+                }
+                Err(error) => {
+                    last_status = json!({"index": index, "error": error.to_string()});
+                    emit(
+                        writer,
+                        Event {
+                            id: id.clone(),
+                            event: "step_failed".to_string(),
+                            payload: last_status.clone(),
+                        },
+                    )
+                    .await?;
+                    if stop_on_error {
+                        break;
+                    }
+                    continue;
+                }
+            }
+        }
+        let target = item.as_ref().map(|(_, target)| target.as_str());
+        if let Some((scripts, compiled)) = &scripts
+            && let Some(when) = &compiled[index].when
+        {
+            match scripts.condition(when, &vars, &last_status) {
+                Ok(true) => {}
+                Ok(false) => {
+                    let mut skipped = json!({"index": index, "tool": step.tool});
+                    if let Some(target) = target {
+                        skipped["target"] = json!(target);
+                    }
+                    emit(
+                        writer,
+                        Event {
+                            id: id.clone(),
+                            event: "step_skipped".to_string(),
+                            payload: skipped,
+                        },
+                    )
+                    .await?;
+                    continue;
+                }
+                Err(error) => {
+                    last_status = json!({"index": index, "error": error.to_string()});
+                    emit(
+                        writer,
+                        Event {
+                            id: id.clone(),
+                            event: "step_failed".to_string(),
+                            payload: last_status.clone(),
+                        },
+                    )
+                    .await?;
+                    if stop_on_error {
+                        break;
+                    }
+                    continue;
+                }
+            }
+        }
+        if let Some(lease) = &lease {
+            match open_shared_connection(config, lease).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    emit(
+                        writer,
+                        Event {
+                            id: id.clone(),
+                            event: "connection_reestablished".to_string(),
+                            payload: json!({"index": index, "host": lease.target}),
+                        },
+                    )
+                    .await?;
+                }
+                Err(error) => log_observation(
+                    "connection_sharing_failed",
+                    json!({"workflow": id, "index": index, "host": lease.target, "error": error.to_string()}),
+                ),
+            }
+        }
+        let mut started = json!({"index": index, "tool": step.tool});
+        if let Some(target) = target {
+            started["target"] = json!(target);
+        }
+        emit(
+            writer,
+            Event {
+                id: id.clone(),
+                event: "step_started".to_string(),
+                payload: started,
+            },
+        )
+        .await?;
+
+        let (run_id, args, params) = match &item {
+            Some((item, target)) => (
+                format!("{}-step-{}-{}", id, index, item),
+                step.args
+                    .iter()
+                    .map(|arg| arg.replace(targets::TARGET_PLACEHOLDER, target))
+                    .collect(),
+                step.params
+                    .iter()
+                    .map(|(key, value)| (key.clone(), targets::substitute(value, target)))
+                    .collect(),
+            ),
+            None => (
+                format!("{}-step-{}", id, index),
+                step.args.clone(),
+                step.params.clone(),
+            ),
+        };
+        let rendered = args
+            .iter()
+            .map(|arg| scripting::render(arg, &vars))
+            .collect::<Result<Vec<_>>>()
+            .and_then(|args| {
+                let params = params
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), scripting::render_value(value, &vars)?)))
+                    .collect::<Result<BTreeMap<_, _>>>()?;
+                Ok((args, params))
+            });
+        let collected = match rendered {
+            Ok((args, params)) => {
+                let run = RunRequest {
+                    id: Some(run_id),
+                    host: workflow.host.clone(),
+                    user: workflow.user.clone(),
+                    tool: step.tool.clone(),
+                    args,
+                    timeout_sec: step.timeout_sec,
+                    max_output_bytes: step.max_output_bytes,
+                    stream_mode: StreamMode::default(),
+                    strip_ansi: step.strip_ansi,
+                    strip_control_chars: step.strip_control_chars,
+                    include_pattern: step.include_pattern.clone(),
+                    exclude_pattern: step.exclude_pattern.clone(),
+                    no_cache: step.no_cache,
+                    engagement: workflow.engagement.clone(),
+                    stdin: step.stdin.clone(),
+                    params,
+                    labels: labels::merge(&workflow.labels, &step.labels),
+                    manifest: false,
+                    client: workflow.client.clone(),
+                    output_root: None,
+                    session: None,
+                    replay_of: None,
+                };
+                execute_request_collect(config, runtime, run).await
+            }
+            Err(error) => Err(error),
+        };
+        match collected {
+            Ok(result) => {
+                let mut failed = result.final_status.timed_out
+                    || result.final_status.exit_code.unwrap_or(1) != 0;
+                last_status = json!({
+                    "index": index,
+                    "exit_code": result.final_status.exit_code,
+                    "timed_out": result.final_status.timed_out,
+                    "duration_ms": result.final_status.duration_ms,
+                    "truncated": result.truncated,
+                    "encoding": result.encoding,
+                    "lossy_utf8": result.lossy,
+                    "attempts": result.attempts,
+                    "cached": result.cached,
+                    "deduplicated": result.deduplicated,
+                    "artifacts": result.artifacts,
+                    "stdout_preview": result.stdout.chars().take(240).collect::<String>(),
+                    "stderr_preview": result.stderr.chars().take(240).collect::<String>()
+                });
+                if let Some(findings) = &result.findings {
+                    last_status["findings_summary"] = findings::summary(findings);
+                    last_status["findings"] = json!(findings);
+                }
+                if let Some(summary) = &result.summary {
+                    last_status["summary"] = summary.clone();
+                }
+                if let Some(target) = target {
+                    last_status["target"] = json!(target);
+                }
+                if let Some((scripts, compiled)) = &scripts
+                    && let Some(script) = &compiled[index].script
+                {
+                    let mut input = last_status.clone();
+                    input["stdout"] = json!(result.stdout);
+                    input["stderr"] = json!(result.stderr);
+                    match scripts.run(script, &mut vars, &input) {
+                        Ok(Value::Null) => {}
+                        Ok(value) => last_status["script_result"] = value,
+                        Err(error) => {
+                            last_status["script_error"] = json!(error.to_string());
+                            failed = true;
+                        }
+                    }
+                }
+
+                emit(
+                    writer,
+                    Event {
+                        id: id.clone(),
+                        event: "step_finished".to_string(),
+                        payload: last_status.clone(),
+                    },
+                )
+                .await?;
+
+                if failed && stop_on_error {
+                    break;
+                }
+            }
+            Err(error) => {
+                last_status = json!({
+                    "index": index,
+                    "error": error.to_string()
+                });
+                if let Some(target) = target {
+                    last_status["target"] = json!(target);
+                }
+                emit(
+                    writer,
+                    Event {
+                        id: id.clone(),
+                        event: "step_failed".to_string(),
+                        payload: last_status.clone(),
+                    },
+                )
+                .await?;
+
+                if stop_on_error {
+                    break;
+                }
+            }
+        }
+    }
+
+    runtime.notifier.notify(
+        NotificationEvent::WorkflowFinished,
+        &id,
+        json!({"host": workflow.host, "steps": workflow.steps.len(), "last_status": last_status.clone()}),
+    );
+    drop(lease);
+    emit(
+        writer,
+        Event {
+            id,
+            event: "workflow_finished".to_string(),
+            payload: last_status,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn workflow_connection<'a>(
+    config: &BridgeConfig,
+    runtime: &'a Runtime,
+    user: &Option<String>,
+    host: &str,
+) -> Option<connections::Lease<'a>> {
+    if !config.connection_sharing.enabled {
+        return None;
+    }
+    let opened = async {
+        let target = format_target(user, host)?;
+        let lease = runtime.connections.acquire(
+            &config.connection_sharing,
+            &config.ssh_program,
+            &target,
+        )?;
+        open_shared_connection(config, &lease).await?;
+        Ok::<_, anyhow::Error>(lease)
+    };
+    match opened.await {
+        Ok(lease) => Some(lease),
+        Err(error) => {
+            log_observation(
+                "connection_sharing_failed",
+                json!({"host": host, "error": error.to_string()}),
+            );
+            None
+        }
+    }
+}
+
+struct StepScripts {
+    when: Option<rhai::AST>,
+    script: Option<rhai::AST>,
+}
+
+fn workflow_scripts(
+    config: &BridgeConfig,
+    id: &str,
+    steps: &[WorkflowStep],
+) -> Result<Option<(Scripts, Vec<StepScripts>)>> {
+    if steps
+        .iter()
+        .all(|step| step.when.is_none() && step.script.is_none())
+    {
+        return Ok(None);
+    }
+    if !config.scripting.enabled {
+        bail!(Message::Disabled {
+            tool: "when, script".to_string(),
+            setting: "scripting.enabled=false",
+        });
+    }
+    let scripts = Scripts::new(&config.scripting, id);
+    let compiled = steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            let compile = |source: &Option<String>, field| {
+                source
+                    .as_deref()
+                    .map(|source| scripts.compile(source))
+                    .transpose()
+                    .with_context(|| Message::StepScriptInvalid { index, field })
+            };
+            Ok(StepScripts {
+                when: compile(&step.when, "when")?,
+                script: compile(&step.script, "script")?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some((scripts, compiled)))
+}
+
+type WorkflowRun<'a> = (usize, Option<(usize, String)>, &'a WorkflowStep);
+
+fn step_uses_target(step: &WorkflowStep) -> bool {
+    step.args
+        .iter()
+        .any(|arg| arg.contains(targets::TARGET_PLACEHOLDER))
+        || step
+            .params
+            .values()
+            .any(|value| value.to_string().contains(targets::TARGET_PLACEHOLDER))
+}
+
+fn workflow_runs<'a>(
+    config: &BridgeConfig,
+    steps: &'a [WorkflowStep],
+) -> Result<Vec<WorkflowRun<'a>>> {
+    let mut runs = Vec::new();
+    let mut produced = Vec::new();
+    for (index, step) in steps.iter().enumerate() {
+        let dns_list = config
+            .tools
+            .get(&step.tool)
+            .and_then(|policy| policy.profile)
+            .filter(|profile| profile.dns())
+            .and_then(|_| step.params.get("list"))
+            .and_then(Value::as_str);
+        let Some(list) = &step.for_each else {
+            produced.extend(dns_list);
+            runs.push((index, None, step));
+            continue;
+        };
+        if !step_uses_target(step) {
+            bail!(Message::ForEachWithoutTarget {
+                index,
+                placeholder: targets::TARGET_PLACEHOLDER,
+            });
+        }
+        if produced.contains(&list.as_str()) {
+            runs.push((index, None, step));
+        } else {
+            for (item, target) in targets::lookup(&config.targets, list)?
+                .into_iter()
+                .enumerate()
+            {
+                runs.push((index, Some((item, target)), step));
+            }
+        }
+        produced.extend(dns_list);
+    }
+    Ok(runs)
+}
+
+#[tracing::instrument(
+    name = "request",
+    skip_all,
+    fields(correlation_id = request.id.as_deref().unwrap_or("request"), tool = %request.tool, host = %request.host)
+)]
+async fn run_request<W: AsyncWrite + Unpin>(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: RunRequest,
+    writer: &mut W,
+) -> Result<FinalStatus> {
+    let pipeline = request_pipeline(&config.pipeline);
+    let mut context = RunContext::new(config, runtime, request, true);
+    let (events, mut pending) = mpsc::unbounded_channel();
+    context.events = Some(events);
+    let admitted = {
+        let admission = pipeline.run(Phase::Admission, &mut context);
+        tokio::pin!(admission);
+        loop {
+            tokio::select! {
+                admitted = &mut admission => break admitted,
+                Some(event) = pending.recv() => emit(writer, event).await?,
+            }
+        }
+    };
+    while let Ok(event) = pending.try_recv() {
+        emit(writer, event).await?;
+    }
+    if let Err(error) = admitted {
+        audit_request_rejected(runtime, &context.correlation_id, &context.request, &error)?;
+        return Err(error);
+    }
+    let id = context.correlation_id.clone();
+    let request = context.request.clone();
+    let policy = context.policy()?;
+
+    let timeout_sec = request
+        .timeout_sec
+        .unwrap_or(config.default_timeout_sec)
+        .min(config.max_timeout_sec);
+    let max_output_bytes = request.max_output_bytes.unwrap_or(config.max_output_bytes);
+    let target = format_target(&request.user, &request.host)?;
+    let kill_generation = runtime.control.kill_generation();
+
+    let claim = Claim::tool(
+        &request.host,
+        &request.tool,
+        policy.concurrency,
+        timeout_sec,
+    );
+    let _permit = match runtime.scheduler.enqueue(claim)? {
+        Admission::Granted(permit) => permit,
+        Admission::Queued(mut ticket) => loop {
+            let mut payload = ticket.progress();
+            payload["host"] = json!(request.host);
+            payload["tool"] = json!(request.tool);
+            emit(
+                writer,
+                Event {
+                    id: id.clone(),
+                    event: "queued".to_string(),
+                    payload,
+                },
+            )
+            .await?;
+            let next = tokio::select! {
+                next = ticket.next() => next?,
+                _ = runtime.cancel.cancelled() => return Err(Message::Shutdown.into()),
+                _ = runtime.control.killed_since(kill_generation) => return Err(Message::Killed.into()),
+            };
+            if let Some(permit) = next {
+                break permit;
+            }
+        },
+    };
+
+    log_observation(
+        "stream_run_started",
+        json!({
+            "correlation_id": id.clone(),
+            "tool": request.tool.clone(),
+            "target": target.clone(),
+            "timeout_sec": timeout_sec,
+            "max_output_bytes": max_output_bytes,
+            "labels": request.labels
+        }),
+    );
+
+    let mut started_payload = json!({
+        "target": target,
+        "tool": request.tool,
+        "timeout_sec": timeout_sec,
+        "max_output_bytes": max_output_bytes,
+        "stream_mode": request.stream_mode
+    });
+    labels::attach(&mut started_payload, &request.labels);
+    emit(
+        writer,
+        Event {
+            id: id.clone(),
+            event: "started".to_string(),
+            payload: started_payload,
+        },
+    )
+    .await?;
+
+    let run_token = new_run_token();
+    let stdin_template = request
+        .stdin
+        .as_ref()
+        .map(|stdin| stdin.bytes(&SecretValues::default()))
+        .transpose()?;
+    let remote_command = build_remote_command(
+        config.remote_timeout,
+        policy,
+        &request.args,
+        timeout_sec,
+        &run_token,
+        &SecretValues::default(),
+        stdin_template.as_ref().map(Vec::len),
+    );
+    let stdin_text = request.stdin.as_ref().and_then(StdinPayload::text);
+    let secret_values = secrets::resolve(
+        &config.secrets,
+        policy
+            .default_args
+            .iter()
+            .chain(policy.env.values())
+            .chain(&request.args)
+            .chain(stdin_text),
+    )
+    .await?
+    .with_expansions(wordlists::expansions(
+        &config.wordlists,
+        policy
+            .default_args
+            .iter()
+            .chain(policy.env.values())
+            .chain(&request.args),
+    )?);
+    let stdin = request
+        .stdin
+        .as_ref()
+        .map(|stdin| stdin.bytes(&secret_values))
+        .transpose()?;
+    let tool_version = probe_tool_version(config, runtime, policy, &target).await;
+    let mut manifest = run_manifest(config, policy, &request, &id, &target, tool_version)?;
+    let mut record = manifest::Attempt::new(1);
+    record.command(&run_token, &remote_command);
+    audit_run_started(
+        runtime,
+        &id,
+        &target,
+        &request,
+        &remote_command,
+        &run_token,
+        1,
+        policy.run_as.as_deref(),
+    )?;
+    let active = runtime.activity.start(
+        &id,
+        &run_token,
+        &request.tool,
+        &request.host,
+        request.client.as_deref(),
+    );
+
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_sec);
+    let mut sink = EventStreamSink {
+        writer: &mut *writer,
+        id: &id,
+        max_output_bytes,
+        written_bytes: 0,
+        truncated: false,
+        stream_mode: if policy.binary {
+            StreamMode::Chunks
+        } else {
+            request.stream_mode
+        },
+        binary: policy.binary,
+        started,
+        seq: 0,
+        stdout_line: Vec::new(),
+        stderr_line: Vec::new(),
+    };
+    let filter = output_filter(policy, &request)?.with_redactor(StreamRedactor::new(
+        secret_values.redaction_pairs(),
+        policy.binary,
+    ));
+    let outcome = async {
+        let child = spawn_ssh(
+            config,
+            &target,
+            &build_remote_command(
+                config.remote_timeout,
+                policy,
+                &request.args,
+                timeout_sec,
+                &run_token,
+                &secret_values,
+                stdin.as_ref().map(Vec::len),
+            ),
+        )?;
+        let input = ProcessInput {
+            stdin: stdin.as_deref(),
+            deadline,
+            kill_generation,
+            termination: policy.termination(),
+        };
+        supervise_process(
+            config, runtime, child, &target, &run_token, input, filter, &mut sink,
+        )
+        .await
+    }
+    .await;
+    let summary = match (policy.profile, &outcome) {
+        (Some(ToolProfile::Capture), Ok(_)) => {
+            summarize_capture(config, policy, &target, &run_token).await
+        }
+        _ => None,
+    };
+    let results = match (
+        policy.profile.and_then(ToolProfile::results_command),
+        &outcome,
+    ) {
+        (Some(command), Ok(_)) => profile_results(config, &command, &target, &run_token).await,
+        _ => None,
+    };
+    let workspace_files = finish_workspace(
+        config,
+        runtime,
+        policy,
+        &target,
+        &run_token,
+        outcome.is_ok(),
+    )
+    .await;
+    let summary = screenshot_results(policy, &workspace_files)
+        .map(|screenshots| screenshots.summary)
+        .or(summary);
+    let outcome = match audit_run_failed(runtime, &id, &run_token, outcome) {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            record.fail(&error);
+            manifest.attempts.push(record);
+            store_manifest(config, runtime, &mut manifest)?;
+            return Err(error);
+        }
+    };
+
+    let final_status = FinalStatus {
+        exit_code: outcome.exit_code,
+        timed_out: outcome.timed_out,
+        duration_ms: started.elapsed().as_millis(),
+        privileged: policy.run_as.is_some(),
+        connect_failed: outcome.connect_failed,
+    };
+    active.finish(final_status.exit_code, final_status.timed_out);
+    record.finish(
+        final_status.exit_code,
+        final_status.timed_out,
+        classify_failure(&final_status).map(FailureClass::as_str),
+    );
+    manifest.attempts.push(record);
+    audit_run_finished(
+        runtime,
+        &id,
+        &run_token,
+        &final_status,
+        &outcome,
+        sink.truncated,
+    )?;
+    store_manifest(config, runtime, &mut manifest)?;
+    let summary = match (policy.profile, &results) {
+        (Some(profile), Some(data)) => {
+            dns_results(config, runtime, &request, &id, profile, data)?.or(summary)
+        }
+        _ => summary,
+    };
+    let mut artifacts = upload_artifacts(
+        runtime,
+        request.engagement.as_deref(),
+        &id,
+        &run_token,
+        &outcome,
+    )
+    .await?;
+    artifacts.extend(
+        store_workspace_files(
+            runtime,
+            request.engagement.as_deref(),
+            &id,
+            &run_token,
+            request.output_root.as_deref(),
+            workspace_files,
+        )
+        .await?,
+    );
+    runtime.circuits.record(
+        &request.host,
+        classify_failure(&final_status) == Some(FailureClass::SshConnect),
+    );
+    runtime.metrics.record_run(
+        &request.tool,
+        &request.host,
+        classify_failure(&final_status).map_or("success", FailureClass::as_str),
+        &request.labels,
+        started.elapsed(),
+        sink.truncated,
+    );
+
+    log_observation(
+        "stream_run_finished",
+        json!({
+            "correlation_id": id.clone(),
+            "exit_code": final_status.exit_code,
+            "timed_out": final_status.timed_out,
+            "duration_ms": final_status.duration_ms,
+            "labels": request.labels
+        }),
+    );
+
+    context.result = Some(Ok(CollectedRun {
+        final_status: final_status.clone(),
+        stdout: String::new(),
+        stderr: String::new(),
+        encoding: OutputEncoding::Utf8,
+        lossy: false,
+        truncated: sink.truncated,
+        attempts: 1,
+        cached: false,
+        deduplicated: false,
+        artifacts: artifacts.clone(),
+        findings: None,
+        summary: None,
+        images: Vec::new(),
+        manifest: None,
+    }));
+    pipeline.run(Phase::Completion, &mut context).await?;
+    let mut payload = json!({
+        "exit_code": final_status.exit_code,
+        "timed_out": final_status.timed_out,
+        "duration_ms": final_status.duration_ms,
+        "artifacts": artifacts,
+        "next_action_hint": next_action_hint(&final_status)
+    });
+    if let Some(summary) = summary {
+        payload["summary"] = summary;
+    }
+    if request.manifest && config.manifest.enabled {
+        payload["manifest"] = json!(manifest);
+    }
+    labels::attach(&mut payload, &request.labels);
+    emit(
+        writer,
+        Event {
+            id,
+            event: "finished".to_string(),
+            payload,
+        },
+    )
+    .await?;
+
+    Ok(final_status)
+}
+
+struct RunContext<'a> {
+    config: &'a BridgeConfig,
+    runtime: &'a Runtime,
+    correlation_id: String,
+    request: RunRequest,
+    streaming: bool,
+    policy: Option<&'a ToolPolicy>,
+    scope_hosts: Vec<String>,
+    events: Option<mpsc::UnboundedSender<Event>>,
+    cache_key: Option<CacheKey>,
+    leader: Option<LeaderGuard<CacheKey, CollectedRun>>,
+    shared: bool,
+    credentials: Vec<profiles::Credential>,
+    result: Option<Result<CollectedRun>>,
+}
+
+impl<'a> RunContext<'a> {
+    fn new(
+        config: &'a BridgeConfig,
+        runtime: &'a Runtime,
+        request: RunRequest,
+        streaming: bool,
+    ) -> Self {
+        Self {
+            config,
+            runtime,
+            correlation_id: request.id.clone().unwrap_or_else(|| "request".to_string()),
+            request,
+            streaming,
+            policy: None,
+            scope_hosts: Vec::new(),
+            events: None,
+            cache_key: None,
+            leader: None,
+            shared: false,
+            credentials: Vec::new(),
+            result: None,
+        }
+    }
+
+    fn policy(&self) -> Result<&'a ToolPolicy> {
+        self.policy
+            .context("Request hat die policy-Stufe nicht durchlaufen")
+    }
+}
+
+fn request_pipeline<'a>(config: &PipelineConfig) -> Pipeline<RunContext<'a>> {
+    Pipeline::new(
+        config,
+        vec![
+            Box::new(PolicyStage),
+            Box::new(ScopeStage),
+            Box::new(RateLimitStage),
+            Box::new(PluginsStage),
+            Box::new(ApprovalStage),
+            Box::new(PreHooksStage),
+            Box::new(ExecutorStage),
+            Box::new(ParsersStage),
+            Box::new(RedactionStage),
+            Box::new(StoreStage),
+            Box::new(NotifyStage),
+            Box::new(PostHooksStage),
+        ],
+    )
+}
+
+struct PolicyStage;
+
+impl<'c> Stage<RunContext<'c>> for PolicyStage {
+    fn name(&self) -> StageName {
+        StageName::Policy
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            check_policy(context)?;
+            let policy = context.policy()?;
+            check_tool_version(context.config, context.runtime, &context.request, policy).await
+        })
+    }
+}
+
+struct ScopeStage;
+
+impl<'c> Stage<RunContext<'c>> for ScopeStage {
+    fn name(&self) -> StageName {
+        StageName::Scope
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move { check_scope(context) })
+    }
+}
+
+struct RateLimitStage;
+
+impl<'c> Stage<RunContext<'c>> for RateLimitStage {
+    fn name(&self) -> StageName {
+        StageName::RateLimit
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            match &context.request.session {
+                Some(session) => context.runtime.sessions.admit_call(session),
+                None => Ok(()),
+            }
+        })
+    }
+}
+
+struct PluginsStage;
+
+impl<'c> Stage<RunContext<'c>> for PluginsStage {
+    fn name(&self) -> StageName {
+        StageName::Plugins
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move { check_plugins(context) })
+    }
+}
+
+struct ApprovalStage;
+
+impl<'c> Stage<RunContext<'c>> for ApprovalStage {
+    fn name(&self) -> StageName {
+        StageName::Approval
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            let policy = context.policy()?;
+            approve_request(
+                context.config,
+                context.runtime,
+                &context.correlation_id,
+                &context.request,
+                policy,
+                context.events.as_ref(),
+            )
+            .await
+        })
+    }
+}
+
+struct PreHooksStage;
+
+impl<'c> Stage<RunContext<'c>> for PreHooksStage {
+    fn name(&self) -> StageName {
+        StageName::PreHooks
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            context
+                .runtime
+                .hooks
+                .pre_run(
+                    &context.config.hooks.pre_run,
+                    hook_payload(&context.correlation_id, &context.request),
+                )
+                .await
+        })
+    }
+}
+
+fn check_policy(context: &mut RunContext<'_>) -> Result<()> {
+    let config = context.config;
+    let request = &mut context.request;
+    if context.runtime.control.is_paused() {
+        return Err(Message::Paused.into());
+    }
+    request.host = targets::normalize_host(&request.host)?;
+    labels::check(&config.labels, &request.labels)?;
+    let policy = validate_request(config, request)?;
+    if let Some(profile) = policy.profile {
+        if context.streaming && !profile.streams() {
+            return Err(Message::ProfileNotStreaming {
+                tool: request.tool.clone(),
+                profile: profile.as_str().to_string(),
+            }
+            .into());
+        }
+        if !request.args.is_empty() || request.stdin.is_some() {
+            return Err(Message::ProfileParamsOnly {
+                tool: request.tool.clone(),
+                profile: profile.as_str().to_string(),
+            }
+            .into());
+        }
+        let run = profile.build_args(
+            policy.max_rate,
+            &policy.capture,
+            &policy.screenshots,
+            &policy.hydra,
+            &request.params,
+        )?;
+        request.args = run.args;
+        request.stdin = run.stdin.map(StdinPayload::Text);
+        context.scope_hosts = run.hosts;
+        if let Some(min_timeout_sec) = run.timeout_sec {
+            request.timeout_sec = Some(
+                request
+                    .timeout_sec
+                    .map_or(min_timeout_sec, |timeout| timeout.max(min_timeout_sec)),
+            );
+        }
+        validate_request(config, request)?;
+    }
+    wordlists::expansions(&config.wordlists, &request.args)?;
+    let timeout_sec = request.timeout_sec.unwrap_or(config.default_timeout_sec);
+    let allowed_timeout_sec = rbac::authorize(
+        &config.rbac,
+        request.client.as_deref(),
+        &request.tool,
+        &request.host,
+        timeout_sec,
+    )?;
+    if allowed_timeout_sec < timeout_sec {
+        request.timeout_sec = Some(allowed_timeout_sec);
+    }
+    context.policy = Some(policy);
+    Ok(())
+}
+
+fn check_scope(context: &mut RunContext<'_>) -> Result<()> {
+    let policy = context.policy()?;
+    let request = &mut context.request;
+    let engagement =
+        engagement::resolve(&context.config.engagements, request.engagement.as_deref())?;
+    if let Some(profile) = policy.profile.filter(|profile| profile.requires_scope())
+        && engagement
+            .as_ref()
+            .is_none_or(|engagement| engagement.scope.is_empty())
+    {
+        return Err(Message::ScopeRequired {
+            tool: request.tool.clone(),
+            profile: profile.as_str().to_string(),
+        }
+        .into());
+    }
+    if let Some(engagement) = engagement {
+        request.engagement = Some(engagement.name.clone());
+        engagement::check_scope(&engagement, &request.args)?;
+        engagement::check_hosts(&engagement, &context.scope_hosts)?;
+    }
+    Ok(())
+}
+
+fn check_plugins(context: &RunContext<'_>) -> Result<()> {
+    let request = &context.request;
+    context.runtime.plugins.validate_args(
+        &request.tool,
+        json!({
+            "tool": request.tool,
+            "host": request.host,
+            "user": request.user,
+            "args": request.args,
+            "params": request.params,
+            "engagement": request.engagement
+        }),
+    )
+}
+
+fn admit_request<'a>(
+    config: &'a BridgeConfig,
+    runtime: &'a Runtime,
+    request: &mut RunRequest,
+) -> Result<&'a ToolPolicy> {
+    let mut context = RunContext::new(config, runtime, request.clone(), false);
+    check_policy(&mut context)?;
+    check_scope(&mut context)?;
+    if config.pipeline.enabled(StageName::Plugins) {
+        check_plugins(&context)?;
+    }
+    *request = context.request.clone();
+    context.policy()
+}
+
+fn validate_request<'a>(config: &'a BridgeConfig, request: &RunRequest) -> Result<&'a ToolPolicy> {
+    let policy = config
+        .tools
+        .get(&request.tool)
+        .ok_or_else(|| Message::ToolNotAllowed {
+            tool: request.tool.clone(),
+        })?;
+    if !policy.enabled {
+        return Err(Message::ToolDisabled {
+            tool: request.tool.clone(),
+        }
+        .into());
+    }
+    format_target(&request.user, &request.host)?;
+
+    if policy.profile.is_none() && request.args.len() > policy.max_args {
+        return Err(Message::TooManyArgs {
+            tool: request.tool.clone(),
+            count: request.args.len(),
+            max: policy.max_args,
+        }
+        .into());
+    }
+    secrets::check_args(
+        &config.secrets,
+        &request.tool,
+        &policy.secrets,
+        &request.args,
+    )?;
+    if let Some(stdin) = &request.stdin {
+        let size = stdin.bytes(&SecretValues::default())?.len();
+        if size > config.max_stdin_bytes {
+            return Err(Message::StdinTooLarge {
+                tool: request.tool.clone(),
+                size,
+                max: config.max_stdin_bytes,
+            }
+            .into());
+        }
+        if let Some(text) = stdin.text() {
+            secrets::check_args(
+                &config.secrets,
+                &request.tool,
+                &policy.secrets,
+                std::slice::from_ref(text),
+            )?;
+        }
+    }
+    if let Some(machine) = policy.machine_output {
+        machine.check_args(&request.tool, &request.args)?;
+    }
+    if line_filter(request)?.is_some()
+        && (policy.binary || policy.profile.is_some() || policy.machine_output.is_some())
+    {
+        return Err(Message::FilterUnsupported {
+            tool: request.tool.clone(),
+        }
+        .into());
+    }
+    Ok(policy)
+}
+
+#[tracing::instrument(
+    name = "request",
+    skip_all,
+    fields(correlation_id = request.id.as_deref().unwrap_or("request"), tool = %request.tool, host = %request.host)
+)]
+async fn execute_request_collect(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: RunRequest,
+) -> Result<CollectedRun> {
+    let pipeline = request_pipeline(&config.pipeline);
+    let mut context = RunContext::new(config, runtime, request, false);
+    if let Err(error) = pipeline.run(Phase::Admission, &mut context).await {
+        let (correlation_id, request) = (&context.correlation_id, &context.request);
+        log_observation(
+            "attempt_rejected",
+            json!({
+                "correlation_id": correlation_id,
+                "failure_class": FailureClass::PolicyViolation,
+                "message": error.to_string(),
+                "labels": request.labels
+            }),
+        );
+        audit_request_rejected(runtime, correlation_id, request, &error)?;
+        if config.pipeline.enabled(StageName::Notify) {
+            runtime.notifier.notify(
+                NotificationEvent::Error,
+                correlation_id,
+                json!({
+                    "code": error_code(&error),
+                    "message": error.to_string(),
+                    "tool": request.tool,
+                    "host": request.host,
+                    "labels": request.labels
+                }),
+            );
+        }
+        return Err(error);
+    }
+    pipeline.run(Phase::Execution, &mut context).await?;
+    pipeline.run(Phase::Completion, &mut context).await?;
+    let result = context
+        .result
+        .take()
+        .context("executor-Stufe hat kein Ergebnis geliefert")?;
+    if let Some(leader) = context.leader.take() {
+        leader.complete(result.as_ref().map(CollectedRun::clone));
+    }
+    result.map(|mut collected| {
+        if !context.request.manifest {
+            collected.manifest = None;
+        }
+        collected
+    })
+}
+
+struct ExecutorStage;
+
+impl<'c> Stage<RunContext<'c>> for ExecutorStage {
+    fn name(&self) -> StageName {
+        StageName::Executor
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(execute_stage(context))
+    }
+}
+
+async fn execute_stage(context: &mut RunContext<'_>) -> Result<()> {
+    let (config, runtime, policy) = (context.config, context.runtime, context.policy()?);
+    let (correlation_id, request) = (&context.correlation_id, &context.request);
+    let cache_key = (!request.no_cache)
+        .then(|| cache_key(config, policy, request))
+        .transpose()?;
+    if let Some((mut cached, age)) = cache_key.as_ref().and_then(|key| runtime.cache.get(key)) {
+        log_observation(
+            "cache_hit",
+            json!({
+                "correlation_id": correlation_id,
+                "tool": request.tool.clone(),
+                "host": request.host.clone(),
+                "age_ms": age.as_millis(),
+                "labels": request.labels
+            }),
+        );
+        runtime.metrics.record_cache_hit();
+        audit_request_shared(runtime, "cache_hit", correlation_id, request)?;
+        cached.cached = true;
+        context.shared = true;
+        context.result = Some(Ok(cached));
+        return Ok(());
+    }
+
+    match cache_key
+        .as_ref()
+        .filter(|_| config.dedup_in_flight)
+        .map(|key| runtime.in_flight.join(key))
+    {
+        Some(Joined::Leader(guard)) => context.leader = Some(guard),
+        Some(Joined::Follower(follower)) => {
+            log_observation(
+                "in_flight_joined",
+                json!({
+                    "correlation_id": correlation_id,
+                    "client": request.client,
+                    "tool": request.tool.clone(),
+                    "host": request.host.clone()
+                }),
+            );
+            runtime.metrics.record_in_flight_joined();
+            audit_request_shared(runtime, "in_flight_joined", correlation_id, request)?;
+            let shared = tokio::select! {
+                shared = follower.wait() => shared,
+                _ = runtime.cancel.cancelled() => return Err(Message::Shutdown.into()),
+            };
+            context.shared = true;
+            context.result = Some(shared.map(|mut shared| {
+                shared.deduplicated = true;
+                shared
+            }));
+            return Ok(());
+        }
+        None => {}
+    }
+
+    match execute_request_with_retries(config, runtime, request, policy, correlation_id).await {
+        Ok((collected, credentials)) => {
+            context.credentials = credentials;
+            context.result = Some(Ok(collected));
+        }
+        Err(error) => context.result = Some(Err(error)),
+    }
+    context.cache_key = cache_key;
+    Ok(())
+}
+
+struct ParsersStage;
+
+impl<'c> Stage<RunContext<'c>> for ParsersStage {
+    fn name(&self) -> StageName {
+        StageName::Parsers
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            let policy = context.policy()?;
+            if context.shared || context.streaming {
+                return Ok(());
+            }
+            let Some(Ok(collected)) = &mut context.result else {
+                return Ok(());
+            };
+            if collected.encoding != OutputEncoding::Utf8 {
+                return Ok(());
+            }
+            let request = &context.request;
+            let findings = policy
+                .profile
+                .and_then(|profile| profile.parse_findings(&collected.stdout))
+                .or_else(|| collected.findings.take());
+            let output = json!({
+                "tool": request.tool,
+                "args": request.args,
+                "exit_code": collected.final_status.exit_code,
+                "timed_out": collected.final_status.timed_out,
+                "stdout": collected.stdout,
+                "stderr": collected.stderr
+            });
+            collected.findings = tokio::task::block_in_place(|| {
+                context
+                    .runtime
+                    .plugins
+                    .findings(&request.tool, output, findings)
+            });
+            if let Some(findings) = collected.findings.as_mut() {
+                cve::enrich(findings, context.runtime.cves.as_ref());
+            }
+            Ok(())
+        })
+    }
+}
+
+struct RedactionStage;
+
+impl<'c> Stage<RunContext<'c>> for RedactionStage {
+    fn name(&self) -> StageName {
+        StageName::Redaction
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            let Some(Ok(collected)) = &mut context.result else {
+                return Ok(());
+            };
+            if context.credentials.is_empty() {
+                return Ok(());
+            }
+            let replacement = &context.config.redaction.replacement;
+            for credential in context
+                .credentials
+                .iter()
+                .filter(|credential| !credential.password.is_empty())
+            {
+                collected.stdout = collected.stdout.replace(&credential.password, replacement);
+                collected.stderr = collected.stderr.replace(&credential.password, replacement);
+            }
+            collected.findings = Some(store_credentials(
+                context.config,
+                &context.correlation_id,
+                &context.credentials,
+            ));
+            context.credentials.clear();
+            Ok(())
+        })
+    }
+}
+
+struct StoreStage;
+
+impl<'c> Stage<RunContext<'c>> for StoreStage {
+    fn name(&self) -> StageName {
+        StageName::Store
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            let policy = context.policy()?;
+            if context.shared || context.streaming {
+                return Ok(());
+            }
+            let Some(Ok(collected)) = &context.result else {
+                return Ok(());
+            };
+            let (runtime, request) = (context.runtime, &context.request);
+            if let Some(findings) = collected
+                .findings
+                .as_ref()
+                .filter(|findings| !findings.is_empty())
+            {
+                let source = policy
+                    .profile
+                    .map(ToolProfile::as_str)
+                    .or(policy.machine_output.map(MachineOutput::as_str))
+                    .unwrap_or("plugin");
+                let dedup = observe_findings(
+                    runtime,
+                    Some(&context.correlation_id),
+                    request.engagement.as_deref(),
+                    findings,
+                );
+                audit(
+                    runtime,
+                    "findings",
+                    json!({
+                        "source": source,
+                        "tool": request.tool,
+                        "correlation_id": context.correlation_id,
+                        "engagement": request.engagement,
+                        "labels": request.labels,
+                        "findings": stored_findings(runtime, findings)?,
+                        "dedup": dedup
+                    }),
+                )?;
+                log_observation(
+                    "findings",
+                    json!({"source": source, "tool": request.tool, "summary": findings::summary(findings), "dedup": dedup}),
+                );
+            }
+            if classify_failure(&collected.final_status).is_none()
+                && let Some(key) = context.cache_key.take()
+            {
+                runtime.cache.insert(key, collected.clone());
+            }
+            Ok(())
+        })
+    }
+}
+
+struct NotifyStage;
+
+impl<'c> Stage<RunContext<'c>> for NotifyStage {
+    fn name(&self) -> StageName {
+        StageName::Notify
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            let (runtime, correlation_id, request) =
+                (context.runtime, &context.correlation_id, &context.request);
+            match context.result.as_ref().filter(|_| !context.shared) {
+                Some(Ok(collected)) => runtime.notifier.notify(
+                    NotificationEvent::Finished,
+                    correlation_id,
+                    json!({
+                        "tool": request.tool,
+                        "target": format_target(&request.user, &request.host)?,
+                        "exit_code": collected.final_status.exit_code,
+                        "timed_out": collected.final_status.timed_out,
+                        "duration_ms": collected.final_status.duration_ms,
+                        "truncated": collected.truncated,
+                        "attempts": collected.attempts,
+                        "artifacts": collected.artifacts,
+                        "findings_summary": collected.findings.as_deref().map(findings::summary),
+                        "top_findings": collected.findings.as_deref().map(|found| findings::highest(found, TOP_FINDINGS)),
+                        "labels": request.labels
+                    }),
+                ),
+                Some(Err(error)) => runtime.notifier.notify(
+                    NotificationEvent::Error,
+                    correlation_id,
+                    json!({
+                        "code": error_code(error),
+                        "message": error.to_string(),
+                        "tool": request.tool,
+                        "host": request.host,
+                        "labels": request.labels
+                    }),
+                ),
+                None => {}
+            }
+            Ok(())
+        })
+    }
+}
+
+struct PostHooksStage;
+
+impl<'c> Stage<RunContext<'c>> for PostHooksStage {
+    fn name(&self) -> StageName {
+        StageName::PostHooks
+    }
+
+    fn run<'a>(&'a self, context: &'a mut RunContext<'c>) -> StageFuture<'a> {
+        Box::pin(async move {
+            let hooks = &context.config.hooks.post_run;
+            if context.shared || hooks.is_empty() {
+                return Ok(());
+            }
+            let mut payload = hook_payload(&context.correlation_id, &context.request);
+            payload["result"] = match &context.result {
+                Some(Ok(collected)) => json!({
+                    "exit_code": collected.final_status.exit_code,
+                    "timed_out": collected.final_status.timed_out,
+                    "duration_ms": collected.final_status.duration_ms,
+                    "truncated": collected.truncated,
+                    "attempts": collected.attempts,
+                    "artifacts": collected.artifacts
+                }),
+                Some(Err(error)) => json!({"code": error_code(error), "error": error.to_string()}),
+                None => return Ok(()),
+            };
+            context.runtime.hooks.post_run(hooks, payload).await;
+            Ok(())
+        })
+    }
+}
+
+fn hook_payload(correlation_id: &str, request: &RunRequest) -> Value {
+    json!({
+        "correlation_id": correlation_id,
+        "tool": request.tool,
+        "host": request.host,
+        "user": request.user,
+        "args": request.args,
+        "engagement": request.engagement,
+        "client": request.client,
+        "timeout_sec": request.timeout_sec,
+        "labels": request.labels
+    })
+}
+
+fn needs_approval(policy: &ToolPolicy) -> bool {
+    policy.require_approval || policy.profile.is_some_and(ToolProfile::requires_approval)
+}
+
+async fn approve_request(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    correlation_id: &str,
+    request: &RunRequest,
+    policy: &ToolPolicy,
+    events: Option<&mpsc::UnboundedSender<Event>>,
+) -> Result<()> {
+    if !needs_approval(policy) {
+        return Ok(());
+    }
+    let budget = attempt_budget(config, policy, request).await?;
+    let (approval_id, decision) = request_approval(runtime, correlation_id, request, budget)?;
+    if let Some(events) = events {
+        let _ = events.send(Event {
+            id: correlation_id.to_string(),
+            event: "approval_pending".to_string(),
+            payload: json!({"approval_id": approval_id, "timeout_sec": config.approvals.timeout_sec}),
+        });
+    }
+    await_approval(config, runtime, correlation_id, &approval_id, decision).await
+}
+
+async fn attempt_budget(
+    config: &BridgeConfig,
+    policy: &ToolPolicy,
+    request: &RunRequest,
+) -> Result<Value> {
+    let Some(budget) = policy
+        .profile
+        .map(|profile| profile.attempt_budget(&policy.hydra, &request.params))
+        .transpose()?
+        .flatten()
+    else {
+        return Ok(Value::Null);
+    };
+    let lists = budget
+        .login_list
+        .iter()
+        .chain([&budget.password_list])
+        .map(|name| wordlists::resolve(&config.wordlists, name))
+        .collect::<Result<Vec<_>>>()?;
+    let script = lists
+        .iter()
+        .map(|path| {
+            format!(
+                "p={}; [ -f \"$p\" ] || {{ echo \"Wordlist $p nicht gefunden\" >&2; exit 4; }}; awk 'END {{ print NR }}' \"$p\"",
+                shell_escape(path)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let target = format_target(&request.user, &request.host)?;
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC),
+    );
+    let output = tokio::time::timeout(
+        limit,
+        build_ssh_command(config, &target, &script)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| Message::OperationTimeout {
+        tool: "wordlists".to_string(),
+        timeout_sec: limit.as_secs(),
+    })?
+    .context(Message::SshStatusFailed)?;
+    if !output.status.success() {
+        bail!(Message::RemoteFailed {
+            tool: "wordlists".to_string(),
+            exit_code: output.status.code(),
+            detail: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let counts = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .context(Message::UnexpectedResponse)?;
+    let (logins, passwords) = match (budget.login_list.is_some(), counts.as_slice()) {
+        (true, [logins, passwords]) => (*logins, *passwords),
+        (false, [passwords]) => (1, *passwords),
+        _ => return Err(Message::UnexpectedResponse.into()),
+    };
+    let attempts = logins.saturating_mul(passwords);
+    if attempts > budget.max_attempts {
+        bail!(Message::AttemptsExceeded {
+            attempts,
+            logins,
+            passwords,
+            max: budget.max_attempts,
+        });
+    }
+    Ok(
+        json!({"attempts": attempts, "logins": logins, "passwords": passwords, "max_attempts": budget.max_attempts}),
+    )
+}
+
+fn request_approval(
+    runtime: &Runtime,
+    correlation_id: &str,
+    request: &RunRequest,
+    details: Value,
+) -> Result<(String, tokio::sync::oneshot::Receiver<Decision>)> {
+    let summary = json!({
+        "correlation_id": correlation_id,
+        "tool": request.tool,
+        "target": format_target(&request.user, &request.host)?,
+        "args": request.args,
+        "params": request.params,
+        "client": request.client,
+        "engagement": request.engagement,
+        "details": details
+    });
+    let (approval_id, decision) = runtime.approvals.request(summary.clone());
+    if let Err(error) = audit(
+        runtime,
+        "approval_requested",
+        json!({"approval_id": approval_id, "request": summary}),
+    ) {
+        runtime.approvals.withdraw(&approval_id);
+        return Err(error);
+    }
+    log_observation(
+        "approval_requested",
+        json!({"approval_id": approval_id, "correlation_id": correlation_id, "tool": request.tool}),
+    );
+    runtime.notifier.notify(
+        NotificationEvent::ApprovalRequested,
+        correlation_id,
+        json!({"approval_id": approval_id, "tool": request.tool, "host": request.host, "details": summary["details"]}),
+    );
+    Ok((approval_id, decision))
+}
+
+async fn await_approval(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    correlation_id: &str,
+    approval_id: &str,
+    decision: tokio::sync::oneshot::Receiver<Decision>,
+) -> Result<()> {
+    let kill_generation = runtime.control.kill_generation();
+    let timeout_sec = config.approvals.timeout_sec;
+    let outcome = tokio::select! {
+        decision = tokio::time::timeout(Duration::from_secs(timeout_sec), decision) => match decision {
+            Ok(Ok(decision)) => Ok(decision),
+            Ok(Err(_)) => Err(Message::ApprovalWithdrawn),
+            Err(_) => Err(Message::ApprovalTimeout { timeout_sec }),
+        },
+        _ = runtime.cancel.cancelled() => Err(Message::Shutdown),
+        _ = runtime.control.killed_since(kill_generation) => Err(Message::Killed),
+    };
+    runtime.approvals.withdraw(approval_id);
+    let (kind, result) = match outcome {
+        Ok(Decision::Approved) => ("approval_granted", Ok(())),
+        Ok(Decision::Denied { reason }) => (
+            "approval_denied",
+            Err(Message::ApprovalDenied { reason }.into()),
+        ),
+        Err(message) => ("approval_expired", Err(message.into())),
+    };
+    let error = result.as_ref().err().map(ToString::to_string);
+    audit(
+        runtime,
+        kind,
+        json!({"approval_id": approval_id, "correlation_id": correlation_id, "error": error}),
+    )?;
+    log_observation(
+        kind,
+        json!({"approval_id": approval_id, "correlation_id": correlation_id, "error": error}),
+    );
+    result
+}
+
+async fn execute_request_with_retries(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: &RunRequest,
+    policy: &ToolPolicy,
+    correlation_id: &str,
+) -> Result<(CollectedRun, Vec<profiles::Credential>)> {
+    let target = format_target(&request.user, &request.host)?;
+    let tool_version = probe_tool_version(config, runtime, policy, &target).await;
+    let mut manifest = run_manifest(
+        config,
+        policy,
+        request,
+        correlation_id,
+        &target,
+        tool_version,
+    )?;
+    let result = run_attempts(
+        config,
+        runtime,
+        request,
+        policy,
+        correlation_id,
+        &mut manifest,
+    )
+    .await;
+    store_manifest(config, runtime, &mut manifest)?;
+    result.map(|(mut collected, credentials)| {
+        collected.manifest = config.manifest.enabled.then_some(manifest);
+        (collected, credentials)
+    })
+}
+
+async fn run_attempts(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: &RunRequest,
+    policy: &ToolPolicy,
+    correlation_id: &str,
+    manifest: &mut Manifest,
+) -> Result<(CollectedRun, Vec<profiles::Credential>)> {
+    let retry_policy = policy.retry_policy.as_ref().unwrap_or(&config.retry_policy);
+    let max_attempts = config.max_retries.saturating_add(1);
+    let mut attempt: u32 = 1;
+
+    loop {
+        if runtime.cancel.is_triggered() {
+            return Err(Message::Shutdown.into());
+        }
+        if attempt > 1 && runtime.control.is_paused() {
+            return Err(Message::PausedRetry.into());
+        }
+
+        log_observation(
+            "attempt_started",
+            json!({
+                "correlation_id": correlation_id,
+                "attempt": attempt,
+                "max_attempts": max_attempts,
+                "tool": request.tool.clone(),
+                "host": request.host.clone(),
+                "labels": request.labels
+            }),
+        );
+
+        let mut record = manifest::Attempt::new(attempt);
+        let outcome =
+            execute_request_collect_once(config, runtime, request.clone(), attempt, &mut record)
+                .instrument(tracing::info_span!("attempt", attempt))
+                .await;
+        match outcome {
+            Ok((mut collected, credentials)) => {
+                collected.attempts = attempt;
+                let failure_class = classify_failure(&collected.final_status);
+                record.finish(
+                    collected.final_status.exit_code,
+                    collected.final_status.timed_out,
+                    failure_class.map(FailureClass::as_str),
+                );
+                manifest.attempts.push(record);
+                runtime.circuits.record(
+                    &request.host,
+                    failure_class == Some(FailureClass::SshConnect),
+                );
+                runtime.metrics.record_run(
+                    &request.tool,
+                    &request.host,
+                    failure_class.map_or("success", FailureClass::as_str),
+                    &request.labels,
+                    Duration::from_millis(collected.final_status.duration_ms as u64),
+                    collected.truncated,
+                );
+
+                log_observation(
+                    "attempt_finished",
+                    json!({
+                        "correlation_id": correlation_id,
+                        "attempt": attempt,
+                        "success": failure_class.is_none(),
+                        "failure_class": failure_class,
+                        "exit_code": collected.final_status.exit_code,
+                        "timed_out": collected.final_status.timed_out,
+                        "duration_ms": collected.final_status.duration_ms,
+                        "truncated": collected.truncated
+                    }),
+                );
+
+                let Some(failure_class) = failure_class else {
+                    return Ok((collected, credentials));
+                };
+                if attempt >= max_attempts
+                    || !retry_policy.should_retry(failure_class, collected.final_status.exit_code)
+                {
+                    return Ok((collected, credentials));
+                }
+
+                let backoff_ms = retry_backoff_ms(config, attempt);
+                if let Some(record) = manifest.attempts.last_mut() {
+                    record.backoff_ms = Some(backoff_ms);
+                }
+                runtime.metrics.record_retry(&request.tool);
+                log_observation(
+                    "retry_scheduled",
+                    json!({
+                        "correlation_id": correlation_id,
+                        "attempt": attempt,
+                        "next_attempt": attempt + 1,
+                        "backoff_ms": backoff_ms
+                    }),
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(backoff_ms)) => {}
+                    _ = runtime.cancel.cancelled() => return Err(Message::Shutdown.into()),
+                }
+            }
+            Err(error) => {
+                record.fail(&error);
+                manifest.attempts.push(record);
+                runtime
+                    .metrics
+                    .record_error(&request.tool, &request.host, &request.labels);
+                let message = error.to_string();
+                log_observation(
+                    "attempt_error",
+                    json!({
+                        "correlation_id": correlation_id,
+                        "attempt": attempt,
+                        "failure_class": FailureClass::ExecError,
+                        "message": message
+                    }),
+                );
+
+                if attempt >= max_attempts
+                    || error
+                        .downcast_ref::<Message>()
+                        .is_some_and(Message::is_cancelled)
+                    || !retry_policy.should_retry(FailureClass::ExecError, None)
+                {
+                    return Err(error);
+                }
+
+                let backoff_ms = retry_backoff_ms(config, attempt);
+                if let Some(record) = manifest.attempts.last_mut() {
+                    record.backoff_ms = Some(backoff_ms);
+                }
+                runtime.metrics.record_retry(&request.tool);
+                log_observation(
+                    "retry_scheduled",
+                    json!({
+                        "correlation_id": correlation_id,
+                        "attempt": attempt,
+                        "next_attempt": attempt + 1,
+                        "backoff_ms": backoff_ms
+                    }),
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(backoff_ms)) => {}
+                    _ = runtime.cancel.cancelled() => return Err(Message::Shutdown.into()),
+                }
+            }
+        }
+
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+fn run_manifest(
+    config: &BridgeConfig,
+    policy: &ToolPolicy,
+    request: &RunRequest,
+    correlation_id: &str,
+    target: &str,
+    tool_version: Option<String>,
+) -> Result<Manifest> {
+    Ok(Manifest {
+        bridge_version: env!("CARGO_PKG_VERSION"),
+        correlation_id: correlation_id.to_string(),
+        replay_of: request.replay_of.clone(),
+        tool: request.tool.clone(),
+        target: target.to_string(),
+        request: serde_json::to_value(request)?,
+        config: json!({
+            "timeout_sec": request.timeout_sec.unwrap_or(config.default_timeout_sec).min(config.max_timeout_sec),
+            "max_output_bytes": request.max_output_bytes.unwrap_or(config.max_output_bytes),
+            "strip_ansi": request.strip_ansi.unwrap_or(policy.strip_ansi),
+            "strip_control_chars": request.strip_control_chars.unwrap_or(policy.strip_control_chars),
+            "include_pattern": request.include_pattern,
+            "exclude_pattern": request.exclude_pattern,
+            "remote_timeout": config.remote_timeout,
+            "max_retries": config.max_retries,
+            "retry_policy": policy.retry_policy.as_ref().unwrap_or(&config.retry_policy),
+            "retry_backoff_ms": config.retry_backoff_ms,
+            "retry_backoff_strategy": config.retry_backoff_strategy,
+            "retry_backoff_max_ms": config.retry_backoff_max_ms,
+            "retry_jitter_ratio": config.retry_jitter_ratio,
+            "ssh_client": config.ssh_client,
+            "tool": policy
+        }),
+        ssh: ssh_argv(config, target),
+        tool_version,
+        started_ms: manifest::now_ms(),
+        finished_ms: None,
+        attempts: Vec::new(),
+    })
+}
+
+fn store_manifest(config: &BridgeConfig, runtime: &Runtime, manifest: &mut Manifest) -> Result<()> {
+    manifest.finish();
+    if !config.manifest.enabled {
+        return Ok(());
+    }
+    audit(runtime, "run_manifest", serde_json::to_value(&*manifest)?)
+}
+
+async fn check_tool_version(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: &RunRequest,
+    policy: &ToolPolicy,
+) -> Result<()> {
+    let Some(min_version) = &policy.min_version else {
+        return Ok(());
+    };
+    let target = format_target(&request.user, &request.host)?;
+    let version = probe_tool_version(config, runtime, policy, &target).await;
+    let details = json!({"tool": request.tool, "target": target, "version": version, "min_version": min_version});
+    match version
+        .as_deref()
+        .and_then(|version| manifest::satisfies(version, min_version))
+    {
+        Some(true) => Ok(()),
+        Some(false) if policy.min_version_action == VersionAction::Fail => {
+            Err(Message::ToolVersionTooOld {
+                tool: request.tool.clone(),
+                version: version
+                    .as_deref()
+                    .and_then(manifest::version_number)
+                    .unwrap_or_default()
+                    .to_string(),
+                min_version: min_version.clone(),
+            }
+            .into())
+        }
+        Some(false) => {
+            log_observation("tool_version_outdated", details);
+            Ok(())
+        }
+        None => {
+            log_observation("tool_version_unknown", details);
+            Ok(())
+        }
+    }
+}
+
+async fn probe_tool_version(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    policy: &ToolPolicy,
+    target: &str,
+) -> Option<String> {
+    let wanted = config.manifest.enabled && config.manifest.tool_version;
+    if !wanted && policy.min_version.is_none() {
+        return None;
+    }
+    if let Some(version) =
+        runtime
+            .tool_versions
+            .get(target, &policy.command, config.manifest.version_ttl_sec)
+    {
+        return Some(version);
+    }
+    let remote_command = format!(
+        "{} 2>&1 | head -n 1",
+        std::iter::once(&policy.command)
+            .chain(
+                policy
+                    .version_args
+                    .as_ref()
+                    .unwrap_or(&config.manifest.version_args)
+            )
+            .map(|part| shell_escape(part))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let output = tokio::time::timeout(
+        Duration::from_secs(config.manifest.version_timeout_sec),
+        build_ssh_command(config, target, &remote_command)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    let error = match output {
+        Ok(Ok(output)) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !version.is_empty() {
+                runtime
+                    .tool_versions
+                    .insert(target, &policy.command, version.clone());
+                return Some(version);
+            }
+            "keine Ausgabe".to_string()
+        }
+        Ok(Ok(output)) => format!("Exit-Code {:?}", output.status.code()),
+        Ok(Err(error)) => error.to_string(),
+        Err(_) => "Zeitüberschreitung".to_string(),
+    };
+    log_observation(
+        "tool_version_failed",
+        json!({"target": target, "command": policy.command, "error": error}),
+    );
+    None
+}
+
+fn ssh_argv(config: &BridgeConfig, target: &str) -> Vec<String> {
+    let command = build_ssh_command(config, target, "");
+    let command = command.as_std();
+    let mut argv = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    argv.pop();
+    argv
+}
+
+fn cache_key(config: &BridgeConfig, policy: &ToolPolicy, request: &RunRequest) -> Result<CacheKey> {
+    let resolved = serde_json::to_vec(&json!({
+        "policy": policy,
+        "ssh_host_options": config.ssh_host_options.get(&request.host)
+    }))?;
+    Ok(CacheKey {
+        client: request.client.clone(),
+        policy_sha256: format!("{:x}", Sha256::digest(&resolved)),
+        target: format_target(&request.user, &request.host)?,
+        tool: request.tool.clone(),
+        args: request.args.clone(),
+        timeout_sec: request
+            .timeout_sec
+            .unwrap_or(config.default_timeout_sec)
+            .min(config.max_timeout_sec),
+        max_output_bytes: request.max_output_bytes.unwrap_or(config.max_output_bytes),
+        strip_ansi: request.strip_ansi.unwrap_or(policy.strip_ansi),
+        strip_control_chars: request
+            .strip_control_chars
+            .unwrap_or(policy.strip_control_chars),
+        include_pattern: request.include_pattern.clone(),
+        exclude_pattern: request.exclude_pattern.clone(),
+        engagement: request.engagement.clone(),
+        stdin: request.stdin.clone(),
+    })
+}
+
+async fn execute_request_collect_once(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: RunRequest,
+    attempt: u32,
+    record: &mut manifest::Attempt,
+) -> Result<(CollectedRun, Vec<profiles::Credential>)> {
+    let policy = validate_request(config, &request)?;
+    let correlation_id = request.id.clone().unwrap_or_else(|| "request".to_string());
+    let kill_generation = runtime.control.kill_generation();
+    let timeout_sec = request
+        .timeout_sec
+        .unwrap_or(config.default_timeout_sec)
+        .min(config.max_timeout_sec);
+    let claim = Claim::tool(
+        &request.host,
+        &request.tool,
+        policy.concurrency,
+        timeout_sec,
+    );
+    let _permit =
+        acquire_session(runtime, &correlation_id, &request, claim, kill_generation).await?;
+
+    let max_output_bytes = request.max_output_bytes.unwrap_or(config.max_output_bytes);
+    let target = format_target(&request.user, &request.host)?;
+    let run_token = new_run_token();
+    let stdin_template = request
+        .stdin
+        .as_ref()
+        .map(|stdin| stdin.bytes(&SecretValues::default()))
+        .transpose()?;
+    let remote_command = build_remote_command(
+        config.remote_timeout,
+        policy,
+        &request.args,
+        timeout_sec,
+        &run_token,
+        &SecretValues::default(),
+        stdin_template.as_ref().map(Vec::len),
+    );
+    let stdin_text = request.stdin.as_ref().and_then(StdinPayload::text);
+    let secret_values = secrets::resolve(
+        &config.secrets,
+        policy
+            .default_args
+            .iter()
+            .chain(policy.env.values())
+            .chain(&request.args)
+            .chain(stdin_text),
+    )
+    .await?
+    .with_expansions(wordlists::expansions(
+        &config.wordlists,
+        policy
+            .default_args
+            .iter()
+            .chain(policy.env.values())
+            .chain(&request.args),
+    )?);
+    let stdin = request
+        .stdin
+        .as_ref()
+        .map(|stdin| stdin.bytes(&secret_values))
+        .transpose()?;
+    record.command(&run_token, &remote_command);
+    audit_run_started(
+        runtime,
+        &correlation_id,
+        &target,
+        &request,
+        &remote_command,
+        &run_token,
+        attempt,
+        policy.run_as.as_deref(),
+    )?;
+    let active = runtime.activity.start(
+        &correlation_id,
+        &run_token,
+        &request.tool,
+        &request.host,
+        request.client.as_deref(),
+    );
+
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_sec);
+    let mut sink = CollectSink {
+        max_output_bytes,
+        written_bytes: 0,
+        truncated: false,
+        machine: policy.machine_output.is_some() && !policy.binary,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    };
+    let filter = output_filter(policy, &request)?.with_redactor(StreamRedactor::new(
+        secret_values.redaction_pairs(),
+        policy.binary,
+    ));
+    let outcome = async {
+        let child = spawn_ssh(
+            config,
+            &target,
+            &build_remote_command(
+                config.remote_timeout,
+                policy,
+                &request.args,
+                timeout_sec,
+                &run_token,
+                &secret_values,
+                stdin.as_ref().map(Vec::len),
+            ),
+        )?;
+        let input = ProcessInput {
+            stdin: stdin.as_deref(),
+            deadline,
+            kill_generation,
+            termination: policy.termination(),
+        };
+        supervise_process(
+            config, runtime, child, &target, &run_token, input, filter, &mut sink,
+        )
+        .await
+    }
+    .await;
+    let summary = match (policy.profile, &outcome) {
+        (Some(ToolProfile::Capture), Ok(_)) => {
+            summarize_capture(config, policy, &target, &run_token).await
+        }
+        _ => None,
+    };
+    let results = match (
+        policy.profile.and_then(ToolProfile::results_command),
+        &outcome,
+    ) {
+        (Some(command), Ok(_)) => profile_results(config, &command, &target, &run_token).await,
+        _ => None,
+    };
+    let credentials = match (policy.profile, &results) {
+        (Some(ToolProfile::Hydra), Some(data)) => profiles::hydra_credentials(data),
+        _ => Vec::new(),
+    };
+    let workspace_files = finish_workspace(
+        config,
+        runtime,
+        policy,
+        &target,
+        &run_token,
+        outcome.is_ok(),
+    )
+    .await;
+    let (images, summary) = match screenshot_results(policy, &workspace_files) {
+        Some(screenshots) => (screenshots.images, Some(screenshots.summary)),
+        None => (Vec::new(), summary),
+    };
+    let outcome = audit_run_failed(runtime, &correlation_id, &run_token, outcome)?;
+    let final_status = FinalStatus {
+        exit_code: outcome.exit_code,
+        timed_out: outcome.timed_out,
+        duration_ms: started.elapsed().as_millis(),
+        privileged: policy.run_as.is_some(),
+        connect_failed: outcome.connect_failed,
+    };
+    active.finish(final_status.exit_code, final_status.timed_out);
+    audit_run_finished(
+        runtime,
+        &correlation_id,
+        &run_token,
+        &final_status,
+        &outcome,
+        sink.truncated,
+    )?;
+    let summary = match (policy.profile, &results) {
+        (Some(profile), Some(data)) => {
+            dns_results(config, runtime, &request, &correlation_id, profile, data)?.or(summary)
+        }
+        _ => summary,
+    };
+    let mut artifacts = upload_artifacts(
+        runtime,
+        request.engagement.as_deref(),
+        &correlation_id,
+        &run_token,
+        &outcome,
+    )
+    .await?;
+    artifacts.extend(
+        store_workspace_files(
+            runtime,
+            request.engagement.as_deref(),
+            &correlation_id,
+            &run_token,
+            request.output_root.as_deref(),
+            workspace_files,
+        )
+        .await?,
+    );
+
+    let _parse = tracing::info_span!("parse_output", binary = policy.binary).entered();
+    let (stdout, stderr, encoding, lossy) = if policy.binary {
+        (
+            BASE64.encode(&sink.stdout),
+            BASE64.encode(&sink.stderr),
+            OutputEncoding::Base64,
+            false,
+        )
+    } else {
+        (
+            String::from_utf8_lossy(&sink.stdout).to_string(),
+            String::from_utf8_lossy(&sink.stderr).to_string(),
+            OutputEncoding::Utf8,
+            has_invalid_utf8(&sink.stdout) || has_invalid_utf8(&sink.stderr),
+        )
+    };
+    let (stdout, findings, summary, truncated) = match policy.machine_output {
+        Some(machine) if encoding == OutputEncoding::Utf8 => {
+            let budget = max_output_bytes.saturating_sub(stderr.len());
+            let (preview, findings, summary, cut) =
+                machine_result(machine, &request.args, stdout, budget);
+            (preview, findings, Some(summary), sink.truncated || cut)
+        }
+        _ => (stdout, None, summary, sink.truncated),
+    };
+    drop(_parse);
+
+    Ok((
+        CollectedRun {
+            final_status,
+            stdout,
+            stderr,
+            encoding,
+            lossy,
+            truncated,
+            attempts: 1,
+            cached: false,
+            deduplicated: false,
+            artifacts,
+            findings,
+            summary,
+            images,
+            manifest: None,
+        },
+        credentials,
+    ))
+}
+
+trait ChunkSink {
+    async fn accept(&mut self, chunk: Chunk) -> Result<()>;
+
+    async fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
-struct CollectedRun {
-    final_status: FinalStatus,
-    stdout: String,
-    stderr: String,
+struct EventStreamSink<'a, W> {
+    writer: &'a mut W,
+    id: &'a str,
+    max_output_bytes: usize,
+    written_bytes: usize,
     truncated: bool,
-    attempts: u32,
+    stream_mode: StreamMode,
+    binary: bool,
+    started: Instant,
+    seq: u64,
+    stdout_line: Vec<u8>,
+    stderr_line: Vec<u8>,
 }
 
-#[derive(Debug)]
-enum Chunk {
-    Stdout(Vec<u8>),
-    Stderr(Vec<u8>),
+impl<W: AsyncWrite + Unpin> EventStreamSink<'_, W> {
+    async fn emit_output(&mut self, event_name: &str, bytes: &[u8], extra: Value) -> Result<()> {
+        if self.written_bytes < self.max_output_bytes {
+            let remaining = self.max_output_bytes - self.written_bytes;
+            let part = if bytes.len() > remaining {
+                &bytes[..remaining]
+            } else {
+                bytes
+            };
+            self.written_bytes += part.len();
+            let mut payload = if self.binary {
+                json!({"data": BASE64.encode(part), "encoding": "base64"})
+            } else {
+                let text = String::from_utf8_lossy(part).to_string();
+                if has_invalid_utf8(part) {
+                    json!({"data": text, "lossy_utf8": true})
+                } else {
+                    json!({"data": text})
+                }
+            };
+            if let (Some(target), Value::Object(fields)) = (payload.as_object_mut(), extra) {
+                target.extend(fields);
+            }
+            emit(
+                self.writer,
+                Event {
+                    id: self.id.to_string(),
+                    event: event_name.to_string(),
+                    payload,
+                },
+            )
+            .await?;
+        } else if !self.truncated {
+            self.truncated = true;
+            emit(
+                self.writer,
+                Event {
+                    id: self.id.to_string(),
+                    event: "output_truncated".to_string(),
+                    payload: json!({"max_output_bytes": self.max_output_bytes}),
+                },
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn emit_line(&mut self, is_stdout: bool, mut line: Vec<u8>, partial: bool) -> Result<()> {
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        self.seq += 1;
+        let extra = json!({
+            "seq": self.seq,
+            "t_ms": self.started.elapsed().as_millis(),
+            "partial": partial
+        });
+        let event_name = if is_stdout {
+            "stdout_line"
+        } else {
+            "stderr_line"
+        };
+        self.emit_output(event_name, &line, extra).await
+    }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
-    match cli.command {
-        Commands::Run(args) => {
-            let config = load_config(&args.config).await?;
-            let request = RunRequest {
-                id: Some("cli-run".to_string()),
-                host: args.host,
-                user: args.user,
-                tool: args.tool,
-                args: args.args,
-                timeout_sec: args.timeout_sec,
-                max_output_bytes: args.max_output_bytes,
+impl<W: AsyncWrite + Unpin> ChunkSink for EventStreamSink<'_, W> {
+    async fn accept(&mut self, chunk: Chunk) -> Result<()> {
+        let (is_stdout, data) = match chunk {
+            Chunk::Stdout(data) => (true, data),
+            Chunk::Stderr(data) => (false, data),
+        };
+
+        if self.stream_mode == StreamMode::Chunks {
+            let event_name = if is_stdout {
+                "stdout_chunk"
+            } else {
+                "stderr_chunk"
             };
-            let mut out = io::stdout();
-            run_request(&config, request, &mut out).await?;
+            return self.emit_output(event_name, &data, Value::Null).await;
         }
-        Commands::Serve(args) => {
-            let config = load_config(&args.config).await?;
-            serve_stdio(&config).await?;
+
+        let buffer = if is_stdout {
+            &mut self.stdout_line
+        } else {
+            &mut self.stderr_line
+        };
+        buffer.extend_from_slice(&data);
+        let mut complete = Vec::new();
+        while let Some(pos) = buffer.iter().position(|byte| *byte == b'\n') {
+            complete.push(buffer.drain(..=pos).collect::<Vec<u8>>());
         }
-        Commands::McpServe(args) => {
-            let config = load_config(&args.config).await?;
-            serve_mcp_stdio(&config).await?;
+        let overflow = if buffer.len() > MAX_LINE_BYTES {
+            Some(std::mem::take(buffer))
+        } else {
+            None
+        };
+
+        for line in complete {
+            self.emit_line(is_stdout, line, false).await?;
         }
-        Commands::WorkflowServe(args) => {
-            let config = load_config(&args.config).await?;
-            serve_workflow_stdio(&config).await?;
+        if let Some(line) = overflow {
+            self.emit_line(is_stdout, line, true).await?;
         }
-        Commands::PrintSchema => print_schema()?,
+        Ok(())
     }
-    Ok(())
-}
 
-async fn load_config(path: &str) -> Result<BridgeConfig> {
-    match tokio::fs::read_to_string(path).await {
-        Ok(content) => {
-            let cfg: BridgeConfig =
-                serde_json::from_str(&content).context("config JSON konnte nicht geparst werden")?;
-            Ok(cfg)
+    async fn finish(&mut self) -> Result<()> {
+        if !self.stdout_line.is_empty() {
+            let line = std::mem::take(&mut self.stdout_line);
+            self.emit_line(true, line, true).await?;
         }
-        Err(_) => Ok(BridgeConfig::default()),
+        if !self.stderr_line.is_empty() {
+            let line = std::mem::take(&mut self.stderr_line);
+            self.emit_line(false, line, true).await?;
+        }
+        Ok(())
     }
 }
 
-async fn serve_stdio(config: &BridgeConfig) -> Result<()> {
-    let stdin = io::stdin();
-    let mut lines = BufReader::new(stdin).lines();
-    let mut out = io::stdout();
+struct CollectSink {
+    max_output_bytes: usize,
+    written_bytes: usize,
+    truncated: bool,
+    machine: bool,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
 
-    while let Some(line) = lines.next_line().await? {
-        if line.trim().is_empty() {
-            continue;
+impl ChunkSink for CollectSink {
+    async fn accept(&mut self, chunk: Chunk) -> Result<()> {
+        if let (true, Chunk::Stdout(data)) = (self.machine, &chunk) {
+            let remaining = MAX_MACHINE_BYTES.saturating_sub(self.stdout.len());
+            self.truncated |= data.len() > remaining;
+            self.stdout
+                .extend_from_slice(&data[..data.len().min(remaining)]);
+            return Ok(());
         }
-        match serde_json::from_str::<RunRequest>(&line) {
-            Ok(request) => {
-                if let Err(error) = run_request(config, request, &mut out).await {
-                    emit(
-                        &mut out,
-                        Event {
-                            id: "unknown".to_string(),
-                            event: "error".to_string(),
-                            payload: json!({
-                                "code": "E_EXEC",
-                                "message": error.to_string()
-                            }),
-                        },
-                    )
-                    .await?;
-                }
-            }
-            Err(error) => {
-                emit(
-                    &mut out,
-                    Event {
-                        id: "unknown".to_string(),
-                        event: "error".to_string(),
-                        payload: json!({
-                            "code": "E_PARSE",
-                            "message": error.to_string()
-                        }),
-                    },
-                )
-                .await?;
-            }
+        if self.written_bytes >= self.max_output_bytes {
+            self.truncated = true;
+            return Ok(());
         }
+
+        let (data, is_stdout) = match chunk {
+            Chunk::Stdout(bytes) => (bytes, true),
+            Chunk::Stderr(bytes) => (bytes, false),
+        };
+        let remaining = self.max_output_bytes - self.written_bytes;
+        let part = if data.len() > remaining {
+            &data[..remaining]
+        } else {
+            &data[..]
+        };
+        self.written_bytes += part.len();
+        if part.len() < data.len() {
+            self.truncated = true;
+        }
+        if is_stdout {
+            self.stdout.extend_from_slice(part);
+        } else {
+            self.stderr.extend_from_slice(part);
+        }
+        Ok(())
     }
-    Ok(())
 }
 
-async fn serve_mcp_stdio(config: &BridgeConfig) -> Result<()> {
-    let stdin = io::stdin();
-    let mut lines = BufReader::new(stdin).lines();
-    let mut out = io::stdout();
-
-    while let Some(line) = lines.next_line().await? {
-        if line.trim().is_empty() {
-            continue;
+fn machine_result(
+    machine: MachineOutput,
+    args: &[String],
+    raw: String,
+    budget: usize,
+) -> (String, Option<Vec<Finding>>, Value, bool) {
+    let (preview, findings, summary) = match machine.parse(&raw, args) {
+        Ok(parsed) => (parsed.preview, Some(parsed.findings), parsed.summary),
+        Err(error) => {
+            let summary = json!({
+                "machine_output": machine.as_str(),
+                "parsed": false,
+                "raw_bytes": raw.len(),
+                "error": format!("{:#}", error)
+            });
+            log_observation("machine_output_unparsed", summary.clone());
+            (raw, None, summary)
         }
+    };
+    let shown = outputs::trim(&preview, budget);
+    let cut = shown.len() < preview.len();
+    (shown.to_string(), findings, summary, cut)
+}
 
-        let parsed = serde_json::from_str::<JsonRpcRequest>(&line);
-        let request = match parsed {
-            Ok(req) => req,
-            Err(error) => {
-                write_json_line(
-                    &mut out,
-                    json!({
-                        "jsonrpc": "2.0",
-                        "id": Value::Null,
-                        "error": {
-                            "code": -32700,
-                            "message": format!("parse error: {}", error)
-                        }
-                    }),
-                )
-                .await?;
-                continue;
-            }
-        };
-
-        handle_mcp_request(config, request, &mut out).await?;
+fn has_invalid_utf8(bytes: &[u8]) -> bool {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => false,
+        Err(error) => error.error_len().is_some(),
     }
+}
 
-    Ok(())
+struct ProcessOutcome {
+    exit_code: Option<i32>,
+    timed_out: bool,
+    connect_failed: bool,
+    stdout_sha256: String,
+    stderr_sha256: String,
+    transcript: Option<Transcript>,
 }
 
-async fn handle_mcp_request<W: AsyncWrite + Unpin>(
+fn output_filter(policy: &ToolPolicy, request: &RunRequest) -> Result<OutputFilter> {
+    Ok(OutputFilter::new(
+        request.strip_ansi.unwrap_or(policy.strip_ansi),
+        request
+            .strip_control_chars
+            .unwrap_or(policy.strip_control_chars),
+    )
+    .with_lines(line_filter(request)?))
+}
+
+fn line_filter(request: &RunRequest) -> Result<Option<LineFilter>> {
+    let compile = |field: &'static str, pattern: &Option<String>| {
+        pattern
+            .as_deref()
+            .map(|pattern| {
+                regex::bytes::RegexBuilder::new(pattern)
+                    .size_limit(1 << 20)
+                    .build()
+                    .map_err(|error| Message::InvalidPattern {
+                        field,
+                        error: error.to_string(),
+                    })
+            })
+            .transpose()
+    };
+    Ok(LineFilter::new(
+        compile("include_pattern", &request.include_pattern)?,
+        compile("exclude_pattern", &request.exclude_pattern)?,
+    ))
+}
+
+struct ProcessInput<'a> {
+    stdin: Option<&'a [u8]>,
+    deadline: tokio::time::Instant,
+    kill_generation: u64,
+    termination: Termination<'a>,
+}
+
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(name = "ssh_exec", skip_all, fields(target = %target))]
+async fn supervise_process<S: ChunkSink>(
     config: &BridgeConfig,
-    request: JsonRpcRequest,
-    writer: &mut W,
-) -> Result<()> {
-    let id = request.id.unwrap_or(Value::Null);
-    match request.method.as_str() {
-        "initialize" => {
-            write_json_line(
-                writer,
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": {
-                        "protocolVersion": "2025-01-01",
-                        "capabilities": {
-                            "tools": {}
-                        },
-                        "serverInfo": {
-                            "name": "ollama-kali-mcp-bridge",
-                            "version": env!("CARGO_PKG_VERSION")
+    runtime: &Runtime,
+    mut child: Child,
+    target: &str,
+    run_token: &str,
+    input: ProcessInput<'_>,
+    mut filter: OutputFilter,
+    sink: &mut S,
+) -> Result<ProcessOutcome> {
+    let ProcessInput {
+        stdin,
+        deadline,
+        kill_generation,
+        termination,
+    } = input;
+    let mut remote_stdin = child.stdin.take();
+    if let (Some(data), Some(pipe)) = (stdin, remote_stdin.as_mut()) {
+        let written = tokio::time::timeout_at(deadline, async {
+            pipe.write_all(data).await?;
+            pipe.flush().await
+        })
+        .await;
+        if !matches!(written, Ok(Ok(()))) {
+            log_observation(
+                "stdin_write_failed",
+                json!({"target": target, "run_token": run_token, "bytes": data.len()}),
+            );
+        }
+    }
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or(Message::PipeMissing { stream: "stdout" })?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or(Message::PipeMissing { stream: "stderr" })?;
+    let (tx, mut rx) = mpsc::channel::<Chunk>(64);
+    let throttle = Throttle::output(&config.bandwidth, &runtime.bandwidth);
+    let out_task = spawn_pipe_reader(stdout, tx.clone(), Chunk::Stdout, throttle.clone());
+    let err_task = spawn_pipe_reader(stderr, tx, Chunk::Stderr, throttle);
+
+    let mut process_done = false;
+    let mut streams_open = true;
+    let mut timed_out = false;
+    let mut cancelled = false;
+    let mut killed = false;
+    let mut aborted = false;
+    let mut exit_code = None;
+    let run_cancel = runtime
+        .activity
+        .cancel_signal(run_token)
+        .unwrap_or_else(|| Arc::new(CancelSignal::new()));
+    let mut stdout_digest = Sha256::new();
+    let mut stderr_digest = Sha256::new();
+    let mut transcript = runtime.artifacts.as_ref().map(|_| Transcript::default());
+    let remote_kill = kill_remote_process_group(config, target, run_token, termination);
+    tokio::pin!(remote_kill);
+    let mut kill_at = deadline;
+    let mut kill_started = false;
+    let mut remote_kill_done = false;
+    let mut stderr_head = Vec::new();
+
+    while !process_done || streams_open || (timed_out && !remote_kill_done) {
+        tokio::select! {
+            chunk = rx.recv(), if streams_open => {
+                if let Some(Chunk::Stderr(data)) = &chunk {
+                    let room = SSH_ERROR_HEAD_BYTES.saturating_sub(stderr_head.len());
+                    stderr_head.extend_from_slice(&data[..room.min(data.len())]);
+                }
+                let chunks = match chunk {
+                    Some(chunk) if filter.redacts() => vec![match chunk {
+                        Chunk::Stdout(data) => Chunk::Stdout(filter.redact(true, &data)),
+                        Chunk::Stderr(data) => Chunk::Stderr(filter.redact(false, &data)),
+                    }],
+                    Some(chunk) => vec![chunk],
+                    None => {
+                        streams_open = false;
+                        vec![Chunk::Stdout(filter.flush_redacted(true)), Chunk::Stderr(filter.flush_redacted(false))]
+                    }
+                };
+                for chunk in chunks {
+                    match &chunk {
+                        Chunk::Stdout(data) => stdout_digest.update(data),
+                        Chunk::Stderr(data) => stderr_digest.update(data),
+                    }
+                    if let (Some(transcript), Some(store)) = (transcript.as_mut(), runtime.artifacts.as_ref()) {
+                        match &chunk {
+                            Chunk::Stdout(data) => transcript.push(true, data, store.max_transcript_bytes),
+                            Chunk::Stderr(data) => transcript.push(false, data, store.max_transcript_bytes),
                         }
                     }
-                }),
-            )
-            .await?;
-        }
-        "tools/list" => {
-            let tools = config
-                .tools
-                .iter()
-                .map(|(name, policy)| {
-                    json!({
-                        "name": name,
-                        "description": format!("Executes {} on Kali via SSH with timeout enforcement", policy.command),
-                        "inputSchema": {
-                            "type": "object",
-                            "required": ["host"],
-                            "properties": {
-                                "host": {"type": "string"},
-                                "user": {"type": "string"},
-                                "args": {"type": "array", "items": {"type": "string"}},
-                                "timeout_sec": {"type": "integer", "minimum": 1},
-                                "max_output_bytes": {"type": "integer", "minimum": 1024}
-                            }
+                    let chunk = if filter.is_active() {
+                        match chunk {
+                            Chunk::Stdout(data) => Chunk::Stdout(filter.apply(true, &data)),
+                            Chunk::Stderr(data) => Chunk::Stderr(filter.apply(false, &data)),
                         }
-                    })
-                })
-                .collect::<Vec<_>>();
-
-            write_json_line(
-                writer,
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": {"tools": tools}
-                }),
-            )
-            .await?;
-        }
-        "tools/call" => {
-            let params_value = request.params.unwrap_or_else(|| json!({}));
-            let params: McpCallParams = match serde_json::from_value(params_value) {
-                Ok(parsed) => parsed,
-                Err(error) => {
-                    write_json_line(
-                        writer,
-                        json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "error": {
-                                "code": -32602,
-                                "message": format!("invalid params: {}", error)
-                            }
-                        }),
-                    )
-                    .await?;
-                    return Ok(());
+                    } else {
+                        chunk
+                    };
+                    if !chunk.is_empty() {
+                        runtime.activity.output(run_token, chunk.data());
+                        sink.accept(chunk).await?;
+                    }
                 }
-            };
+                let rest = if streams_open { Vec::new() } else { filter.finish() };
+                if !rest.is_empty() {
+                    runtime.activity.output(run_token, &rest);
+                    sink.accept(Chunk::Stdout(rest)).await?;
+                }
+            }
+            status = child.wait(), if !process_done => {
+                exit_code = status.context(Message::SshStatusFailed)?.code();
+                process_done = true;
+                kill_started |= timed_out;
+            }
+            _ = tokio::time::sleep_until(deadline), if !process_done && !timed_out => {
+                timed_out = true;
+                if config.remote_timeout != RemoteTimeout::Bridge {
+                    kill_at = deadline + Duration::from_secs(termination.grace_sec.saturating_add(REMOTE_KILL_SLACK_SEC));
+                }
+            }
+            _ = tokio::time::sleep_until(kill_at), if timed_out && !kill_started => {
+                kill_started = true;
+            }
+            _ = &mut remote_kill, if kill_started && !remote_kill_done => {
+                remote_kill_done = true;
+                remote_stdin.take();
+                if !process_done {
+                    let _ = child.kill().await;
+                    let status = child.wait().await.context(Message::KillFailed { trigger: "timeout" })?;
+                    exit_code = status.code();
+                    process_done = true;
+                }
+            }
+            _ = runtime.cancel.cancelled(), if !process_done => {
+                cancelled = true;
+                let _ = child.kill().await;
+                remote_stdin.take();
+                let status = child.wait().await.context(Message::KillFailed { trigger: "shutdown" })?;
+                exit_code = status.code();
+                process_done = true;
+                kill_remote_process_group(config, target, run_token, termination).await;
+            }
+            _ = runtime.control.killed_since(kill_generation), if !process_done => {
+                killed = true;
+                let _ = child.kill().await;
+                remote_stdin.take();
+                let status = child.wait().await.context(Message::KillFailed { trigger: "kill_switch" })?;
+                exit_code = status.code();
+                process_done = true;
+                kill_remote_process_group(config, target, run_token, termination).await;
+            }
+            _ = run_cancel.cancelled(), if !process_done => {
+                aborted = true;
+                let _ = child.kill().await;
+                remote_stdin.take();
+                let status = child.wait().await.context(Message::KillFailed { trigger: "cancel" })?;
+                exit_code = status.code();
+                process_done = true;
+                kill_remote_process_group(config, target, run_token, termination).await;
+            }
+        }
+    }
+
+    out_task
+        .await
+        .context(Message::OutputTaskFailed { stream: "stdout" })??;
+    err_task
+        .await
+        .context(Message::OutputTaskFailed { stream: "stderr" })??;
+    sink.finish().await?;
+    if cancelled {
+        return Err(Message::RunShutdown { exit_code }.into());
+    }
+    if killed {
+        return Err(Message::RunKilled { exit_code }.into());
+    }
+    if aborted {
+        return Err(Message::RunCancelled { exit_code }.into());
+    }
+
+    Ok(ProcessOutcome {
+        exit_code,
+        timed_out,
+        connect_failed: exit_code == Some(255) && ssh_connect_error(&stderr_head),
+        stdout_sha256: format!("{:x}", stdout_digest.finalize()),
+        stderr_sha256: format!("{:x}", stderr_digest.finalize()),
+        transcript,
+    })
+}
+
+fn spawn_pipe_reader<R>(
+    pipe: R,
+    tx: mpsc::Sender<Chunk>,
+    wrap: fn(Vec<u8>) -> Chunk,
+    throttle: Throttle,
+) -> JoinHandle<Result<()>>
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(pipe);
+        let mut buf = [0_u8; 4096];
+        loop {
+            let read = reader.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            throttle.pace(read).await;
+            if tx.send(wrap(buf[..read].to_vec())).await.is_err() {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
 
-            let arguments: McpToolArguments = match serde_json::from_value(params.arguments) {
-                Ok(parsed) => parsed,
-                Err(error) => {
-                    write_json_line(
-                        writer,
-                        json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "error": {
-                                "code": -32602,
-                                "message": format!("invalid tool arguments: {}", error)
-                            }
-                        }),
-                    )
-                    .await?;
-                    return Ok(());
-                }
+async fn acquire_session(
+    runtime: &Runtime,
+    correlation_id: &str,
+    request: &RunRequest,
+    claim: Claim,
+    kill_generation: u64,
+) -> Result<SessionPermit> {
+    match runtime.scheduler.enqueue(claim)? {
+        Admission::Granted(permit) => Ok(permit),
+        Admission::Queued(mut ticket) => loop {
+            let mut progress = ticket.progress();
+            progress["host"] = json!(request.host);
+            progress["tool"] = json!(request.tool);
+            runtime.scheduler.announce(correlation_id, &progress);
+            progress["correlation_id"] = json!(correlation_id);
+            log_observation("queued", progress);
+            let next = tokio::select! {
+                next = ticket.next() => next?,
+                _ = runtime.cancel.cancelled() => return Err(Message::Shutdown.into()),
+                _ = runtime.control.killed_since(kill_generation) => return Err(Message::Killed.into()),
             };
+            if let Some(permit) = next {
+                return Ok(permit);
+            }
+        },
+    }
+}
 
-            let run = RunRequest {
-                id: Some("mcp-call".to_string()),
-                host: arguments.host,
-                user: arguments.user,
-                tool: params.name,
-                args: arguments.args,
-                timeout_sec: arguments.timeout_sec,
-                max_output_bytes: arguments.max_output_bytes,
-            };
+fn retry_backoff_ms(config: &BridgeConfig, attempt: u32) -> u64 {
+    let base = match config.retry_backoff_strategy {
+        BackoffStrategy::Linear => config.retry_backoff_ms.saturating_mul(attempt as u64),
+        BackoffStrategy::Exponential => {
+            let factor = 1_u64
+                .checked_shl(attempt.saturating_sub(1))
+                .unwrap_or(u64::MAX);
+            config.retry_backoff_ms.saturating_mul(factor)
+        }
+    };
+    let capped = base.min(config.retry_backoff_max_ms);
+    let ratio = config.retry_jitter_ratio.clamp(0.0, 1.0);
+    if ratio == 0.0 {
+        return capped;
+    }
+    let jitter = (capped as f64 * ratio * random_unit()) as u64;
+    capped - jitter
+}
 
-            let result = execute_request_collect(config, run).await;
-            match result {
-                Ok(collected) => {
-                    let summary = format!(
-                        "exit_code={:?}, timed_out={}, duration_ms={}, attempts={}",
-                        collected.final_status.exit_code,
-                        collected.final_status.timed_out,
-                        collected.final_status.duration_ms,
-                        collected.attempts
-                    );
-                    write_json_line(
-                        writer,
-                        json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "result": {
-                                "content": [
-                                    {"type": "text", "text": summary},
-                                    {"type": "text", "text": collected.stdout},
-                                    {"type": "text", "text": collected.stderr}
-                                ],
-                                "isError": collected.final_status.exit_code.unwrap_or(1) != 0 || collected.final_status.timed_out,
-                                "structuredContent": {
-                                    "exit_code": collected.final_status.exit_code,
-                                    "timed_out": collected.final_status.timed_out,
-                                    "duration_ms": collected.final_status.duration_ms,
-                                    "truncated": collected.truncated,
-                                    "attempts": collected.attempts
-                                }
-                            }
-                        }),
-                    )
-                    .await?;
-                }
-                Err(error) => {
-                    write_json_line(
-                        writer,
-                        json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "error": {
-                                "code": -32000,
-                                "message": error.to_string()
-                            }
-                        }),
-                    )
-                    .await?;
-                }
-            }
+fn random_unit() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_nanos())
+        .unwrap_or(0);
+    hasher.write_u128(nanos);
+    (hasher.finish() >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+fn next_action_hint(status: &FinalStatus) -> &'static str {
+    match classify_failure(status) {
+        Some(FailureClass::Timeout) => "reduce scope or increase timeout",
+        Some(FailureClass::PrivilegeDenied) => {
+            "configure passwordless sudo for run_as on the Kali host"
         }
-        _ => {
-            write_json_line(
-                writer,
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "error": {
-                        "code": -32601,
-                        "message": format!("method not found: {}", request.method)
-                    }
-                }),
-            )
-            .await?;
+        Some(FailureClass::SshConnect) => {
+            "check SSH reachability, host key and ssh_identity for this host"
         }
+        _ => "analyze output and schedule next tool",
     }
+}
 
-    Ok(())
+fn classify_failure(status: &FinalStatus) -> Option<FailureClass> {
+    if status.timed_out {
+        return Some(FailureClass::Timeout);
+    }
+    match status.exit_code {
+        Some(0) => None,
+        Some(255) if status.connect_failed => Some(FailureClass::SshConnect),
+        Some(SUDO_DENIED_EXIT) if status.privileged => Some(FailureClass::PrivilegeDenied),
+        Some(_) => Some(FailureClass::NonZeroExit),
+        None => Some(FailureClass::ExecError),
+    }
 }
 
-async fn serve_workflow_stdio(config: &BridgeConfig) -> Result<()> {
-    let stdin = io::stdin();
-    let mut lines = BufReader::new(stdin).lines();
-    let mut out = io::stdout();
+fn ssh_connect_error(stderr: &[u8]) -> bool {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .map(str::trim)
+        .any(|line| {
+            [
+                "ssh: ",
+                "kex_exchange_identification:",
+                "ssh_exchange_identification:",
+                "Host key verification failed",
+                "Connection closed by ",
+                "Connection reset by ",
+                "Connection timed out during banner exchange",
+                "Received disconnect from ",
+                "Too many authentication failures",
+                "Permission denied (",
+                "FATAL ERROR: ",
+                "Cannot confirm a host key in batch mode",
+            ]
+            .iter()
+            .any(|prefix| line.starts_with(prefix))
+                || line.contains(": Permission denied (")
+        })
+}
 
-    while let Some(line) = lines.next_line().await? {
-        if line.trim().is_empty() {
-            continue;
+fn audit(runtime: &Runtime, kind: &str, mut entry: Value) -> Result<()> {
+    match &runtime.audit {
+        Some(log) => {
+            redact::redact_value(&mut entry);
+            log.append(kind, entry)
         }
+        None => Ok(()),
+    }
+}
 
-        let workflow: WorkflowRequest = match serde_json::from_str(&line) {
-            Ok(req) => req,
-            Err(error) => {
-                emit(
-                    &mut out,
-                    Event {
-                        id: "workflow".to_string(),
-                        event: "error".to_string(),
-                        payload: json!({"code": "E_PARSE", "message": error.to_string()}),
-                    },
-                )
-                .await?;
-                continue;
-            }
-        };
+fn stored_findings(runtime: &Runtime, findings: &[Finding]) -> Result<Value> {
+    match &runtime.sealer {
+        Some(sealer) => sealer.seal_value(&json!(findings)),
+        None => Ok(json!(findings)),
+    }
+}
 
-        run_workflow(config, workflow, &mut out).await?;
+fn observe_findings(
+    runtime: &Runtime,
+    run_id: Option<&str>,
+    engagement: Option<&str>,
+    findings: &[Finding],
+) -> Value {
+    let Some(store) = &runtime.findings_store else {
+        return Value::Null;
+    };
+    match store.observe(run_id, engagement, findings) {
+        Ok(dedup) => dedup,
+        Err(error) => {
+            log_observation(
+                "findings_store_failed",
+                json!({"run_id": run_id, "error": format!("{:#}", error)}),
+            );
+            Value::Null
+        }
     }
+}
 
-    Ok(())
+fn audit_operator() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
 }
 
-async fn run_workflow<W: AsyncWrite + Unpin>(
-    config: &BridgeConfig,
-    workflow: WorkflowRequest,
-    writer: &mut W,
+fn audit_request_rejected(
+    runtime: &Runtime,
+    correlation_id: &str,
+    request: &RunRequest,
+    error: &anyhow::Error,
 ) -> Result<()> {
-    let id = workflow.id.unwrap_or_else(|| "workflow".to_string());
-    let stop_on_error = workflow.stop_on_error;
-    let mut last_status = json!({"state": "empty"});
-
-    emit(
-        writer,
-        Event {
-            id: id.clone(),
-            event: "workflow_started".to_string(),
-            payload: json!({"steps": workflow.steps.len()}),
-        },
+    audit(
+        runtime,
+        "request_rejected",
+        json!({
+            "correlation_id": correlation_id,
+            "operator": audit_operator(),
+            "client": request.client,
+            "target": format_target(&request.user, &request.host)?,
+            "tool": request.tool,
+            "args": request.args,
+            "engagement": request.engagement,
+            "labels": request.labels,
+            "error": error.to_string()
+        }),
     )
-    .await?;
+}
 
-    for (index, step) in workflow.steps.iter().enumerate() {
-        emit(
-            writer,
-            Event {
-                id: id.clone(),
-                event: "step_started".to_string(),
-                payload: json!({"index": index, "tool": step.tool}),
-            },
-        )
-        .await?;
+fn audit_request_shared(
+    runtime: &Runtime,
+    kind: &str,
+    correlation_id: &str,
+    request: &RunRequest,
+) -> Result<()> {
+    audit(
+        runtime,
+        kind,
+        json!({
+            "correlation_id": correlation_id,
+            "operator": audit_operator(),
+            "client": request.client,
+            "target": format_target(&request.user, &request.host)?,
+            "tool": request.tool,
+            "args": request.args,
+            "labels": request.labels
+        }),
+    )
+}
 
-        let run = RunRequest {
-            id: Some(format!("{}-step-{}", id, index)),
-            host: workflow.host.clone(),
-            user: workflow.user.clone(),
-            tool: step.tool.clone(),
-            args: step.args.clone(),
-            timeout_sec: step.timeout_sec,
-            max_output_bytes: step.max_output_bytes,
-        };
+#[allow(clippy::too_many_arguments)]
+fn audit_run_started(
+    runtime: &Runtime,
+    correlation_id: &str,
+    target: &str,
+    request: &RunRequest,
+    remote_command: &str,
+    run_token: &str,
+    attempt: u32,
+    run_as: Option<&str>,
+) -> Result<()> {
+    let stdin = request
+        .stdin
+        .as_ref()
+        .and_then(|stdin| stdin.bytes(&SecretValues::default()).ok());
+    audit(
+        runtime,
+        "run_started",
+        json!({
+            "correlation_id": correlation_id,
+            "run_token": run_token,
+            "attempt": attempt,
+            "operator": audit_operator(),
+            "client": request.client,
+            "target": target,
+            "host": request.host,
+            "tool": request.tool,
+            "args": request.args,
+            "engagement": request.engagement,
+            "labels": request.labels,
+            "stdin_bytes": stdin.as_ref().map(Vec::len),
+            "stdin_sha256": stdin.as_ref().map(|data| format!("{:x}", Sha256::digest(data))),
+            "privileged": run_as.is_some(),
+            "run_as": run_as,
+            "remote_command": remote_command,
+            "replay_of": request.replay_of
+        }),
+    )
+}
 
-        let collected = execute_request_collect(config, run).await;
-        match collected {
-            Ok(result) => {
-                let failed = result.final_status.timed_out || result.final_status.exit_code.unwrap_or(1) != 0;
-                last_status = json!({
-                    "index": index,
-                    "exit_code": result.final_status.exit_code,
-                    "timed_out": result.final_status.timed_out,
-                    "duration_ms": result.final_status.duration_ms,
-                    "truncated": result.truncated,
-                    "attempts": result.attempts,
-                    "stdout_preview": result.stdout.chars().take(240).collect::<String>(),
-                    "stderr_preview": result.stderr.chars().take(240).collect::<String>()
-                });
+fn audit_run_failed(
+    runtime: &Runtime,
+    correlation_id: &str,
+    run_token: &str,
+    outcome: Result<ProcessOutcome>,
+) -> Result<ProcessOutcome> {
+    if let Err(error) = &outcome {
+        audit(
+            runtime,
+            "run_failed",
+            json!({
+                "correlation_id": correlation_id,
+                "run_token": run_token,
+                "error": error.to_string()
+            }),
+        )?;
+    }
+    outcome
+}
 
-                emit(
-                    writer,
-                    Event {
-                        id: id.clone(),
-                        event: "step_finished".to_string(),
-                        payload: last_status.clone(),
-                    },
-                )
-                .await?;
+fn audit_run_finished(
+    runtime: &Runtime,
+    correlation_id: &str,
+    run_token: &str,
+    status: &FinalStatus,
+    outcome: &ProcessOutcome,
+    truncated: bool,
+) -> Result<()> {
+    audit(
+        runtime,
+        "run_finished",
+        json!({
+            "correlation_id": correlation_id,
+            "run_token": run_token,
+            "exit_code": status.exit_code,
+            "timed_out": status.timed_out,
+            "duration_ms": status.duration_ms,
+            "truncated": truncated,
+            "stdout_sha256": outcome.stdout_sha256,
+            "stderr_sha256": outcome.stderr_sha256
+        }),
+    )
+}
 
-                if failed && stop_on_error {
-                    break;
-                }
-            }
-            Err(error) => {
-                last_status = json!({
-                    "index": index,
-                    "error": error.to_string()
-                });
-                emit(
-                    writer,
-                    Event {
-                        id: id.clone(),
-                        event: "step_failed".to_string(),
-                        payload: last_status.clone(),
-                    },
-                )
-                .await?;
+async fn upload_artifacts(
+    runtime: &Runtime,
+    engagement: Option<&str>,
+    correlation_id: &str,
+    run_token: &str,
+    outcome: &ProcessOutcome,
+) -> Result<Vec<String>> {
+    let (Some(store), Some(transcript)) = (&runtime.artifacts, &outcome.transcript) else {
+        return Ok(Vec::new());
+    };
+    match store
+        .upload_transcript(engagement, correlation_id, run_token, transcript)
+        .await
+    {
+        Ok(urls) => {
+            audit(
+                runtime,
+                "artifacts_uploaded",
+                json!({
+                    "correlation_id": correlation_id,
+                    "run_token": run_token,
+                    "urls": urls,
+                    "transcript_truncated": transcript.truncated
+                }),
+            )?;
+            log_observation(
+                "artifacts_uploaded",
+                json!({"correlation_id": correlation_id, "urls": urls, "transcript_truncated": transcript.truncated}),
+            );
+            Ok(urls)
+        }
+        Err(error) => {
+            log_observation(
+                "artifact_upload_failed",
+                json!({"correlation_id": correlation_id, "run_token": run_token, "error": format!("{:#}", error)}),
+            );
+            Ok(Vec::new())
+        }
+    }
+}
 
-                if stop_on_error {
-                    break;
-                }
-            }
+fn log_observation(event: &str, mut payload: Value) {
+    redact::redact_value(&mut payload);
+    match event {
+        "attempt_error"
+        | "attempt_rejected"
+        | "remote_kill"
+        | "notification_failed"
+        | "artifact_upload_failed"
+        | "workspace_collect_failed"
+        | "workspace_artifacts_skipped"
+        | "interactive_audit_failed"
+        | "auth_failed"
+        | "auth_reload_failed"
+        | "tls_handshake_failed"
+        | "connection_sharing_failed"
+        | "hook_failed"
+        | "plugin_failed"
+        | "findings_store_failed" => {
+            tracing::warn!(target: OBSERVATION_TARGET, observation = event, payload = %payload)
         }
+        "attempt_started"
+        | "queued"
+        | "cache_hit"
+        | "in_flight_joined"
+        | "notification_delivered" => {
+            tracing::debug!(target: OBSERVATION_TARGET, observation = event, payload = %payload)
+        }
+        _ => tracing::info!(target: OBSERVATION_TARGET, observation = event, payload = %payload),
     }
+}
 
-    emit(
-        writer,
-        Event {
-            id,
-            event: "workflow_finished".to_string(),
-            payload: last_status,
-        },
+fn new_run_token() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let timestamp_ms = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0);
+    format!(
+        "{}-{}-{}",
+        std::process::id(),
+        timestamp_ms,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
     )
-    .await?;
+}
 
-    Ok(())
+const REMOTE_RUN_BASE: &str = "\"${XDG_RUNTIME_DIR:-$HOME/.cache}/ollama-kali-bridge\"";
+const REMOTE_PID_FILE: &str = "\"$d/pid\"";
+const REMOTE_STDIN_FILE: &str = "\"$d/stdin\"";
+const REMOTE_WORKSPACE: &str = "\"$d/work\"";
+
+fn create_remote_run_dir(run_token: &str) -> String {
+    format!(
+        "b={base}; mkdir -p -m 700 \"$b\" && [ ! -L \"$b\" ] && chmod 700 \"$b\" && \
+         d=$(mktemp -d \"$b/{token}.XXXXXX\") || exit 126; ",
+        base = REMOTE_RUN_BASE,
+        token = run_token
+    )
+}
+
+fn find_remote_run_dir(run_token: &str) -> String {
+    format!(
+        "d=; for x in {}/{}.*; do [ -d \"$x\" ] && d=$x; done; [ -n \"$d\" ] || exit 0; ",
+        REMOTE_RUN_BASE, run_token
+    )
+}
+
+async fn execute_fs(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+    action: &str,
+    arguments: Value,
+) -> Result<Value> {
+    if runtime.control.is_paused() {
+        return Err(Message::Paused.into());
+    }
+    let operation = fs::prepare(&config.fs, action, arguments)?;
+    let host = operation
+        .host
+        .clone()
+        .or_else(|| session.and_then(|session| runtime.sessions.default_host(session)))
+        .ok_or(Message::HostMissing)?;
+    rbac::authorize(&config.rbac, client, operation.tool, &host, 0)?;
+    if let Some(engagement) = engagement::resolve(&config.engagements, None)? {
+        engagement::check_scope(&engagement, std::slice::from_ref(&host))?;
+    }
+
+    let target = format_target(&operation.user, &host)?;
+    let mut child = build_ssh_command(config, &target, &operation.script)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(Message::SshSpawnFailed)?;
+    if let (Some(data), Some(mut stdin)) = (&operation.stdin, child.stdin.take()) {
+        let _ = stdin.write_all(data).await;
+    }
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC),
+    );
+    let output = tokio::time::timeout(limit, child.wait_with_output())
+        .await
+        .map_err(|_| Message::OperationTimeout {
+            tool: operation.tool.to_string(),
+            timeout_sec: limit.as_secs(),
+        })?
+        .context(Message::SshStatusFailed)?;
+    let result = operation.finish(
+        output.status.code(),
+        &output.stdout,
+        &output.stderr,
+        secrets::known_values(&config.secrets),
+    );
+
+    let digest = match &operation.stdin {
+        Some(data) => Some(format!("{:x}", Sha256::digest(data))),
+        None => result
+            .as_ref()
+            .ok()
+            .and_then(|value| value["content"].as_str())
+            .map(|content| format!("{:x}", Sha256::digest(content.as_bytes()))),
+    };
+    let entry = json!({
+        "operator": audit_operator(),
+        "client": client,
+        "session": session,
+        "tool": operation.tool,
+        "target": target,
+        "host": host,
+        "path": operation.path,
+        "bytes": result.as_ref().ok().map(|value| value["bytes"].clone()),
+        "sha256": digest,
+        "error": result.as_ref().err().map(|error| format!("{:#}", error))
+    });
+    audit(runtime, "fs_access", entry.clone())?;
+    log_observation("fs_access", entry);
+    result
 }
 
-async fn run_request<W: AsyncWrite + Unpin>(
+async fn execute_cracking(
     config: &BridgeConfig,
-    request: RunRequest,
-    writer: &mut W,
-) -> Result<FinalStatus> {
-    let id = request.id.unwrap_or_else(|| "request".to_string());
-    let policy = config
-        .tools
-        .get(&request.tool)
-        .ok_or_else(|| anyhow!("tool '{}' ist nicht freigegeben", request.tool))?;
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+    action: &str,
+    arguments: Value,
+) -> Result<Value> {
+    let job = cracking::prepare(
+        &config.cracking,
+        &config.wordlists,
+        &config.fs,
+        action,
+        arguments,
+    )?;
+    if job.tool == "crack.submit" && runtime.control.is_paused() {
+        return Err(Message::Paused.into());
+    }
+    let host = job
+        .host
+        .clone()
+        .or_else(|| session.and_then(|session| runtime.sessions.default_host(session)))
+        .ok_or(Message::HostMissing)?;
+    rbac::authorize(&config.rbac, client, job.tool, &host, 0)?;
+    let mut engagement_name = None;
+    if let Some(engagement) = engagement::resolve(&config.engagements, None)? {
+        engagement::check_scope(&engagement, std::slice::from_ref(&host))?;
+        engagement_name = Some(engagement.name);
+    }
 
-    if request.args.len() > policy.max_args {
-        bail!(
-            "zu viele args für tool '{}': {} > {}",
-            request.tool,
-            request.args.len(),
-            policy.max_args
+    let target = format_target(&job.user, &host)?;
+    let mut child = build_ssh_command(config, &target, &job.script)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(Message::SshSpawnFailed)?;
+    if let Some(mut stdin) = child.stdin.take()
+        && let Some(data) = &job.stdin
+    {
+        let _ = stdin.write_all(data).await;
+    }
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC),
+    );
+    let output = tokio::time::timeout(limit, child.wait_with_output())
+        .await
+        .map_err(|_| Message::OperationTimeout {
+            tool: job.tool.to_string(),
+            timeout_sec: limit.as_secs(),
+        })?
+        .context(Message::SshStatusFailed)?;
+    let result = job.finish(
+        &config.secrets,
+        output.status.code(),
+        &output.stdout,
+        &output.stderr,
+    );
+
+    let entry = json!({
+        "operator": audit_operator(),
+        "client": client,
+        "session": session,
+        "tool": job.tool,
+        "target": target,
+        "job_id": job.job_id,
+        "engagement": engagement_name,
+        "hashes_sha256": job.stdin.as_ref().map(|data| format!("{:x}", Sha256::digest(data))),
+        "state": result.as_ref().ok().map(|(value, _)| value["state"].clone()),
+        "error": result.as_ref().err().map(|error| format!("{:#}", error))
+    });
+    audit(runtime, "crack_job", entry.clone())?;
+    log_observation("crack_job", entry);
+    let (mut value, findings) = result?;
+    if !findings.is_empty() {
+        let source = value["engine"].as_str().unwrap_or("cracking").to_string();
+        let dedup = observe_findings(
+            runtime,
+            Some(&job.job_id),
+            engagement_name.as_deref(),
+            &findings,
+        );
+        audit(
+            runtime,
+            "findings",
+            json!({"source": source, "tool": job.tool, "engagement": engagement_name, "findings": stored_findings(runtime, &findings)?, "dedup": dedup}),
+        )?;
+        log_observation(
+            "findings",
+            json!({"source": source, "tool": job.tool, "summary": findings::summary(&findings), "dedup": dedup}),
         );
     }
+    if job.tool == "crack.results" {
+        value["findings_summary"] = findings::summary(&findings);
+        value["findings"] = json!(findings);
+    }
+    Ok(value)
+}
 
-    let timeout_sec = request
-        .timeout_sec
-        .unwrap_or(config.default_timeout_sec)
-        .min(config.max_timeout_sec);
-    let max_output_bytes = request.max_output_bytes.unwrap_or(config.max_output_bytes);
-    let target = format_target(&request.user, &request.host);
+async fn execute_wordlists(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+    action: &str,
+    arguments: Value,
+) -> Result<Value> {
+    let query = wordlists::prepare(&config.wordlists, action, arguments)?;
+    let host = query
+        .host
+        .clone()
+        .or_else(|| session.and_then(|session| runtime.sessions.default_host(session)))
+        .ok_or(Message::HostMissing)?;
+    rbac::authorize(&config.rbac, client, query.tool, &host, 0)?;
+    if let Some(engagement) = engagement::resolve(&config.engagements, None)? {
+        engagement::check_scope(&engagement, std::slice::from_ref(&host))?;
+    }
 
+    let target = format_target(&query.user, &host)?;
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC),
+    );
+    let output = tokio::time::timeout(
+        limit,
+        build_ssh_command(config, &target, &query.script)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| Message::OperationTimeout {
+        tool: query.tool.to_string(),
+        timeout_sec: limit.as_secs(),
+    })?
+    .context(Message::SshStatusFailed)?;
+    let result = query.finish(output.status.code(), &output.stdout, &output.stderr);
     log_observation(
-        config,
-        "stream_run_started",
+        "wordlists_query",
         json!({
-            "correlation_id": id.clone(),
-            "tool": request.tool.clone(),
-            "target": target.clone(),
-            "timeout_sec": timeout_sec,
-            "max_output_bytes": max_output_bytes
+            "client": client,
+            "tool": query.tool,
+            "target": target,
+            "error": result.as_ref().err().map(|error| format!("{:#}", error))
         }),
     );
+    result
+}
 
-    emit(
-        writer,
-        Event {
-            id: id.clone(),
-            event: "started".to_string(),
-            payload: json!({
-                "target": target,
-                "tool": request.tool,
-                "timeout_sec": timeout_sec,
-                "max_output_bytes": max_output_bytes
-            }),
-        },
-    )
-    .await?;
-
-    let remote_command = build_remote_command(policy, &request.args, timeout_sec);
-    let mut child = build_ssh_command(config, &target, &remote_command)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .context("SSH-Prozess konnte nicht gestartet werden")?;
-
-    let stdout = child.stdout.take().context("stdout pipe fehlt")?;
-    let stderr = child.stderr.take().context("stderr pipe fehlt")?;
-    let (tx, mut rx) = mpsc::channel::<Chunk>(64);
-
-    let tx_out = tx.clone();
-    let out_task = tokio::spawn(async move {
-        let mut reader = BufReader::new(stdout);
-        let mut buf = [0_u8; 4096];
-        loop {
-            let read = reader.read(&mut buf).await?;
-            if read == 0 {
-                break;
-            }
-            if tx_out.send(Chunk::Stdout(buf[..read].to_vec())).await.is_err() {
-                break;
-            }
+async fn execute_targets(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+    action: &str,
+    arguments: Value,
+) -> Result<Value> {
+    let request = targets::parse_request(action, arguments)?;
+    if !rbac::allows_tool(&config.rbac, client, "targets.expand") {
+        return Err(Message::ToolForbidden {
+            client: client.unwrap_or_default().to_string(),
+            tool: "targets.expand".to_string(),
         }
-        Result::<()>::Ok(())
-    });
+        .into());
+    }
+    let mut hosts = targets::expand(&request.targets, config.targets.max_hosts)?;
+    let engagement = engagement::resolve(&config.engagements, request.engagement.as_deref())?;
+    if let Some(engagement) = &engagement {
+        engagement::check_scope(engagement, &hosts)?;
+    }
 
-    let err_task = tokio::spawn(async move {
-        let mut reader = BufReader::new(stderr);
-        let mut buf = [0_u8; 4096];
-        loop {
-            let read = reader.read(&mut buf).await?;
-            if read == 0 {
-                break;
-            }
-            if tx.send(Chunk::Stderr(buf[..read].to_vec())).await.is_err() {
-                break;
-            }
+    let ips = hosts
+        .iter()
+        .filter(|host| host.parse::<std::net::IpAddr>().is_ok())
+        .cloned()
+        .collect::<Vec<_>>();
+    if request.ping && !ips.is_empty() {
+        let host = request
+            .host
+            .clone()
+            .or_else(|| session.and_then(|session| runtime.sessions.default_host(session)))
+            .ok_or(Message::HostMissing)?;
+        let run = RunRequest {
+            id: Some("targets-expand".to_string()),
+            host,
+            user: request.user.clone(),
+            tool: config.targets.ping_tool.clone(),
+            args: ["-sn", "-n", "-oG", "-", "-iL", "-"]
+                .map(str::to_string)
+                .to_vec(),
+            timeout_sec: None,
+            max_output_bytes: None,
+            stream_mode: StreamMode::default(),
+            strip_ansi: None,
+            strip_control_chars: None,
+            include_pattern: None,
+            exclude_pattern: None,
+            no_cache: true,
+            engagement: request.engagement.clone(),
+            stdin: Some(StdinPayload::Text(format!("{}\n", ips.join("\n")))),
+            params: BTreeMap::new(),
+            labels: Labels::new(),
+            manifest: false,
+            client: client.map(str::to_string),
+            output_root: None,
+            session: None,
+            replay_of: None,
+        };
+        let collected = execute_request_collect(config, runtime, run).await?;
+        if collected.final_status.timed_out || collected.final_status.exit_code != Some(0) {
+            bail!(Message::PingSweepFailed {
+                tool: config.targets.ping_tool.clone(),
+                exit_code: collected.final_status.exit_code,
+                timed_out: collected.final_status.timed_out,
+                detail: collected.stderr.trim().to_string(),
+            });
         }
-        Result::<()>::Ok(())
-    });
+        if collected.truncated {
+            bail!(Message::PingSweepTruncated {
+                tool: config.targets.ping_tool.clone(),
+            });
+        }
+        let up = targets::parse_ping(&collected.stdout);
+        hosts.retain(|host| up.contains(host) || !ips.contains(host));
+    }
 
-    let started = Instant::now();
-    let deadline = started + Duration::from_secs(timeout_sec);
+    let engagement_name = engagement.map(|engagement| engagement.name);
+    if let Some(name) = &request.name {
+        targets::store(
+            &config.targets,
+            name,
+            &hosts,
+            &request.targets,
+            engagement_name.as_deref(),
+            request.ping,
+        )?;
+    }
+    let entry = json!({
+        "operator": audit_operator(),
+        "client": client,
+        "sources": request.targets,
+        "name": request.name,
+        "engagement": engagement_name,
+        "pinged": request.ping,
+        "count": hosts.len()
+    });
+    audit(runtime, "targets_expanded", entry.clone())?;
+    log_observation("targets_expanded", entry);
+    Ok(json!({
+        "name": request.name,
+        "stored": request.name.is_some(),
+        "engagement": engagement_name,
+        "pinged": request.ping,
+        "count": hosts.len(),
+        "hosts": hosts
+    }))
+}
 
-    let mut process_done = false;
-    let mut timed_out = false;
-    let mut exit_code = None;
-    let mut written_bytes = 0_usize;
-    let mut truncated = false;
+async fn execute_msf(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    action: &str,
+    arguments: Value,
+) -> Result<Value> {
+    let metasploit = runtime
+        .metasploit
+        .as_ref()
+        .ok_or_else(|| Message::NotConfigured {
+            key: "metasploit.url".to_string(),
+        })?;
+    let call = metasploit.prepare(action, arguments)?;
+    if call.tool == "msf.execute" && runtime.control.is_paused() {
+        return Err(Message::Paused.into());
+    }
+    rbac::authorize(&config.rbac, client, &call.tool, metasploit.host(), 0)?;
+    let mut engagement_name = None;
+    if let Some(engagement) = engagement::resolve(&config.engagements, None)? {
+        engagement::check_scope(&engagement, &call.targets)?;
+        engagement_name = Some(engagement.name);
+    }
 
-    while !process_done || !rx.is_closed() {
-        tokio::select! {
-            chunk = rx.recv() => {
-                if let Some(chunk) = chunk {
-                    let (event_name, bytes) = match chunk {
-                        Chunk::Stdout(data) => ("stdout_chunk", data),
-                        Chunk::Stderr(data) => ("stderr_chunk", data),
-                    };
+    let tool = call.tool.clone();
+    let module = call.module.clone();
+    let targets = call.targets.clone();
+    let result = metasploit.execute(&config.secrets, call).await;
+    let entry = json!({
+        "operator": audit_operator(),
+        "client": client,
+        "tool": tool,
+        "module": module,
+        "targets": targets,
+        "engagement": engagement_name,
+        "error": result.as_ref().err().map(|error| format!("{:#}", error))
+    });
+    audit(runtime, "msf_call", entry.clone())?;
+    log_observation("msf_call", entry);
+    let (mut value, mut findings) = result?;
+    cve::enrich(&mut findings, runtime.cves.as_ref());
+    if !findings.is_empty() {
+        let dedup = observe_findings(runtime, None, engagement_name.as_deref(), &findings);
+        audit(
+            runtime,
+            "findings",
+            json!({"source": "metasploit", "tool": tool, "engagement": engagement_name, "findings": stored_findings(runtime, &findings)?, "dedup": dedup}),
+        )?;
+        log_observation(
+            "findings",
+            json!({"source": "metasploit", "tool": tool, "summary": findings::summary(&findings), "dedup": dedup}),
+        );
+    }
+    if !findings.is_empty() || matches!(tool.as_str(), "msf.status" | "msf.vulns") {
+        value["findings_summary"] = findings::summary(&findings);
+        value["findings"] = json!(findings);
+    }
+    Ok(value)
+}
 
-                    if written_bytes < max_output_bytes {
-                        let remaining = max_output_bytes - written_bytes;
-                        let part = if bytes.len() > remaining { &bytes[..remaining] } else { &bytes[..] };
-                        written_bytes += part.len();
-                        let text = String::from_utf8_lossy(part).to_string();
-                        emit(
-                            writer,
-                            Event {
-                                id: id.clone(),
-                                event: event_name.to_string(),
-                                payload: json!({"data": text}),
-                            },
-                        ).await?;
-                    } else if !truncated {
-                        truncated = true;
-                        emit(
-                            writer,
-                            Event {
-                                id: id.clone(),
-                                event: "output_truncated".to_string(),
-                                payload: json!({"max_output_bytes": max_output_bytes}),
-                            },
-                        ).await?;
-                    }
-                }
-            }
-            _ = tokio::time::sleep(Duration::from_millis(100)), if !process_done => {
-                if let Some(status) = child.try_wait().context("Statusprüfung des SSH-Prozesses fehlgeschlagen")? {
-                    exit_code = status.code();
-                    process_done = true;
-                } else if Instant::now() >= deadline {
-                    timed_out = true;
-                    let _ = child.kill().await;
-                    let status = child.wait().await.context("Timeout und kill fehlgeschlagen")?;
-                    exit_code = status.code();
-                    process_done = true;
-                }
-            }
-            else => {
-                if process_done {
-                    break;
-                }
-            }
-        }
+async fn execute_gvm(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    action: &str,
+    arguments: Value,
+) -> Result<Value> {
+    let gvm = runtime.gvm.as_ref().ok_or_else(|| Message::NotConfigured {
+        key: "gvm.address".to_string(),
+    })?;
+    let call = gvm.prepare(action, arguments)?;
+    if call.tool == "gvm.scan" && runtime.control.is_paused() {
+        return Err(Message::Paused.into());
+    }
+    rbac::authorize(&config.rbac, client, &call.tool, gvm.host(), 0)?;
+    let mut engagement_name = None;
+    if let Some(engagement) = engagement::resolve(&config.engagements, None)? {
+        engagement::check_scope(&engagement, &call.targets)?;
+        engagement_name = Some(engagement.name);
     }
 
-    out_task.await.context("stdout task join fehlgeschlagen")??;
-    err_task.await.context("stderr task join fehlgeschlagen")??;
+    let tool = call.tool.clone();
+    let targets = call.targets.clone();
+    let result = gvm.execute(&config.secrets, call).await;
+    let entry = json!({
+        "operator": audit_operator(),
+        "client": client,
+        "tool": tool,
+        "targets": targets,
+        "engagement": engagement_name,
+        "task_id": result.as_ref().ok().map(|(value, _)| value["task_id"].clone()),
+        "error": result.as_ref().err().map(|error| format!("{:#}", error))
+    });
+    audit(runtime, "gvm_call", entry.clone())?;
+    log_observation("gvm_call", entry);
+    let (mut value, mut findings) = result?;
+    cve::enrich(&mut findings, runtime.cves.as_ref());
+    if !findings.is_empty() {
+        let dedup = observe_findings(runtime, None, engagement_name.as_deref(), &findings);
+        audit(
+            runtime,
+            "findings",
+            json!({"source": "gvm", "tool": tool, "engagement": engagement_name, "findings": stored_findings(runtime, &findings)?, "dedup": dedup}),
+        )?;
+        log_observation(
+            "findings",
+            json!({"source": "gvm", "tool": tool, "summary": findings::summary(&findings), "dedup": dedup}),
+        );
+    }
+    if tool == "gvm.results" {
+        value["findings_summary"] = findings::summary(&findings);
+        value["findings"] = json!(findings);
+    }
+    Ok(value)
+}
 
-    let final_status = FinalStatus {
-        exit_code,
-        timed_out,
-        duration_ms: started.elapsed().as_millis(),
+async fn open_terminal(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    client: Option<&str>,
+    session: Option<&str>,
+    arguments: Value,
+) -> Result<Value> {
+    let open = serde_json::from_value::<interactive::OpenRequest>(arguments).map_err(|error| {
+        Message::InvalidArguments {
+            tool: "session.open".to_string(),
+            error: error.to_string(),
+        }
+    })?;
+    let host = open
+        .host
+        .or_else(|| session.and_then(|session| runtime.sessions.default_host(session)))
+        .ok_or(Message::HostMissing)?;
+    let mut request = RunRequest {
+        id: None,
+        host,
+        user: open.user,
+        tool: open.tool,
+        args: open.args,
+        timeout_sec: None,
+        max_output_bytes: None,
+        stream_mode: StreamMode::default(),
+        strip_ansi: open.strip_ansi,
+        strip_control_chars: None,
+        include_pattern: None,
+        exclude_pattern: None,
+        no_cache: true,
+        engagement: open.engagement,
+        stdin: None,
+        params: BTreeMap::new(),
+        labels: Labels::new(),
+        manifest: false,
+        client: client.map(str::to_string),
+        output_root: None,
+        session: None,
+        replay_of: None,
     };
+    let policy = admit_request(config, runtime, &mut request)?;
+    if !policy.interactive {
+        bail!(Message::Disabled {
+            tool: request.tool.clone(),
+            setting: "interactive=false",
+        });
+    }
+    check_tool_version(config, runtime, &request, policy).await?;
+    approve_request(config, runtime, "session.open", &request, policy, None).await?;
+    let secret_values = secrets::resolve(
+        &config.secrets,
+        policy
+            .default_args
+            .iter()
+            .chain(policy.env.values())
+            .chain(&request.args),
+    )
+    .await?
+    .with_expansions(wordlists::expansions(
+        &config.wordlists,
+        policy
+            .default_args
+            .iter()
+            .chain(policy.env.values())
+            .chain(&request.args),
+    )?);
+    let target = format_target(&request.user, &request.host)?;
+    let child = ssh_command(config, &request.host)
+        .arg(if config.ssh_client == SshClient::Plink {
+            "-t"
+        } else {
+            "-tt"
+        })
+        .arg(&target)
+        .arg(build_interactive_command(
+            policy,
+            &request.args,
+            &secret_values,
+        ))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(Message::SshSpawnFailed)?;
+    let filter = output_filter(policy, &request)?
+        .with_redactor(StreamRedactor::new(secret_values.redaction_pairs(), false));
+    let mut opened = runtime
+        .terminals
+        .open(client, &request.tool, &target, child, filter)?;
+    opened["engagement"] = json!(request.engagement);
+    Ok(opened)
+}
 
+async fn summarize_capture(
+    config: &BridgeConfig,
+    policy: &ToolPolicy,
+    target: &str,
+    run_token: &str,
+) -> Option<Value> {
+    let command = policy.capture.summary_command()?;
+    let script = format!(
+        "{}cd {} && {}",
+        find_remote_run_dir(run_token),
+        REMOTE_WORKSPACE,
+        command
+    );
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC),
+    );
+    let result = tokio::time::timeout(
+        limit,
+        build_ssh_command(config, target, &script)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    let error = match result {
+        Ok(Ok(output)) if output.status.success() => {
+            let summary = policy
+                .capture
+                .parse_summary(&String::from_utf8_lossy(&output.stdout));
+            if summary.is_some() {
+                return summary;
+            }
+            "leere Ausgabe".to_string()
+        }
+        Ok(Ok(output)) => String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .chars()
+            .take(500)
+            .collect(),
+        Ok(Err(error)) => error.to_string(),
+        Err(_) => "Timeout".to_string(),
+    };
     log_observation(
-        config,
-        "stream_run_finished",
-        json!({
-            "correlation_id": id.clone(),
-            "exit_code": final_status.exit_code,
-            "timed_out": final_status.timed_out,
-            "duration_ms": final_status.duration_ms
-        }),
+        "capture_summary_failed",
+        json!({"target": target, "run_token": run_token, "error": error}),
     );
+    None
+}
 
-    emit(
-        writer,
-        Event {
-            id,
-            event: "finished".to_string(),
-            payload: json!({
-                "exit_code": final_status.exit_code,
-                "timed_out": final_status.timed_out,
-                "duration_ms": final_status.duration_ms,
-                "next_action_hint": if final_status.timed_out { "reduce scope or increase timeout" } else { "analyze output and schedule next tool" }
-            }),
-        },
+async fn profile_results(
+    config: &BridgeConfig,
+    command: &str,
+    target: &str,
+    run_token: &str,
+) -> Option<String> {
+    let script = format!(
+        "{}cd {} && {}",
+        find_remote_run_dir(run_token),
+        REMOTE_WORKSPACE,
+        command
+    );
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC),
+    );
+    let result = tokio::time::timeout(
+        limit,
+        build_ssh_command(config, target, &script)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
     )
-    .await?;
-
-    Ok(final_status)
+    .await;
+    let error = match result {
+        Ok(Ok(output)) if output.status.success() => {
+            return Some(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+        Ok(Ok(output)) => String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .chars()
+            .take(500)
+            .collect(),
+        Ok(Err(error)) => error.to_string(),
+        Err(_) => "Timeout".to_string(),
+    };
+    log_observation(
+        "profile_results_failed",
+        json!({"target": target, "run_token": run_token, "error": error}),
+    );
+    None
 }
 
-async fn execute_request_collect(config: &BridgeConfig, request: RunRequest) -> Result<CollectedRun> {
-    let correlation_id = request.id.clone().unwrap_or_else(|| "request".to_string());
-    let max_attempts = config.max_retries.saturating_add(1);
-    let mut attempt: u32 = 1;
-
-    loop {
-        log_observation(
-            config,
-            "attempt_started",
-            json!({
-                "correlation_id": correlation_id.clone(),
-                "attempt": attempt,
-                "max_attempts": max_attempts,
-                "tool": request.tool.clone(),
-                "host": request.host.clone()
-            }),
-        );
-
-        match execute_request_collect_once(config, request.clone()).await {
-            Ok(mut collected) => {
-                collected.attempts = attempt;
-                let success = run_success(&collected.final_status);
-
-                log_observation(
-                    config,
-                    "attempt_finished",
-                    json!({
-                        "correlation_id": correlation_id.clone(),
-                        "attempt": attempt,
-                        "success": success,
-                        "exit_code": collected.final_status.exit_code,
-                        "timed_out": collected.final_status.timed_out,
-                        "duration_ms": collected.final_status.duration_ms,
-                        "truncated": collected.truncated
-                    }),
-                );
-
-                if success || attempt >= max_attempts {
-                    return Ok(collected);
-                }
-
-                let backoff_ms = config.retry_backoff_ms.saturating_mul(attempt as u64);
-                log_observation(
-                    config,
-                    "retry_scheduled",
+fn dns_results(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    request: &RunRequest,
+    correlation_id: &str,
+    profile: ToolProfile,
+    data: &str,
+) -> Result<Option<Value>> {
+    let Some(names) = profile.dns_names(data) else {
+        return Ok(None);
+    };
+    let engagement = match &request.engagement {
+        Some(name) => Some(
+            engagement::Registry::load(&config.engagements.path)?
+                .get(name)?
+                .clone(),
+        ),
+        None => None,
+    };
+    let (names, out_of_scope): (BTreeMap<_, _>, BTreeMap<_, _>) =
+        names.into_iter().partition(|(name, _)| {
+            engagement
+                .as_ref()
+                .is_none_or(|engagement| engagement::covers_host(engagement, name))
+        });
+    let mut summary = json!({
+        "names": names,
+        "out_of_scope": out_of_scope.keys().collect::<Vec<_>>()
+    });
+    if let Some(list) = request.params.get("list").and_then(Value::as_str) {
+        summary["list"] = match targets::merge_dns(
+            &config.targets,
+            list,
+            &names,
+            profile.as_str(),
+            request.engagement.as_deref(),
+        ) {
+            Ok(merged) => {
+                audit(
+                    runtime,
+                    "targets_merged",
                     json!({
-                        "correlation_id": correlation_id.clone(),
-                        "attempt": attempt,
-                        "next_attempt": attempt + 1,
-                        "backoff_ms": backoff_ms
+                        "correlation_id": correlation_id,
+                        "tool": request.tool,
+                        "engagement": request.engagement,
+                        "list": merged
                     }),
-                );
-                tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+                )?;
+                merged
             }
             Err(error) => {
-                let message = error.to_string();
                 log_observation(
-                    config,
-                    "attempt_error",
-                    json!({
-                        "correlation_id": correlation_id.clone(),
-                        "attempt": attempt,
-                        "message": message
-                    }),
+                    "targets_merge_failed",
+                    json!({"correlation_id": correlation_id, "list": list, "error": format!("{:#}", error)}),
                 );
+                json!({"name": list, "error": format!("{:#}", error)})
+            }
+        };
+    }
+    Ok(Some(summary))
+}
 
-                if attempt >= max_attempts {
-                    return Err(error);
+fn store_credentials(
+    config: &BridgeConfig,
+    correlation_id: &str,
+    credentials: &[profiles::Credential],
+) -> Vec<Finding> {
+    credentials
+        .iter()
+        .map(|credential| {
+            let name = format!(
+                "hydra.{}.{}.{}",
+                credential.service, credential.host, credential.login
+            )
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                    c
+                } else {
+                    '_'
                 }
-
-                let backoff_ms = config.retry_backoff_ms.saturating_mul(attempt as u64);
+            })
+            .collect::<String>();
+            let stored = match config.secrets.file {
+                Some(_) => secrets::store(&config.secrets, &name, &credential.password)
+                    .map_err(|error| format!("{:#}", error)),
+                None => Err("keine Secrets-Datei konfiguriert".to_string()),
+            };
+            if let Err(error) = &stored {
                 log_observation(
-                    config,
-                    "retry_scheduled",
-                    json!({
-                        "correlation_id": correlation_id.clone(),
-                        "attempt": attempt,
-                        "next_attempt": attempt + 1,
-                        "backoff_ms": backoff_ms
-                    }),
+                    "credential_store_failed",
+                    json!({"correlation_id": correlation_id, "secret": name, "error": error}),
                 );
-                tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
             }
-        }
+            credential.finding(stored.is_ok().then_some(name.as_str()))
+        })
+        .collect()
+}
+
+fn screenshot_results(
+    policy: &ToolPolicy,
+    files: &[WorkspaceFile],
+) -> Option<profiles::Screenshots> {
+    let files = files
+        .iter()
+        .map(|file| (file.name.as_str(), file.data.as_slice()))
+        .collect::<Vec<_>>();
+    policy.profile?.screenshots(&policy.screenshots, &files)
+}
+
+struct WorkspaceFile {
+    name: String,
+    data: Vec<u8>,
+}
+
+async fn finish_workspace(
+    config: &BridgeConfig,
+    runtime: &Runtime,
+    policy: &ToolPolicy,
+    target: &str,
+    run_token: &str,
+    collect: bool,
+) -> Vec<WorkspaceFile> {
+    if !policy.workspace {
+        return Vec::new();
+    }
+    let patterns = policy
+        .artifacts
+        .iter()
+        .map(String::as_str)
+        .chain(
+            policy
+                .profile
+                .iter()
+                .flat_map(|profile| profile.artifacts().iter().copied()),
+        )
+        .collect::<Vec<_>>();
+    let mut script = find_remote_run_dir(run_token);
+    if collect && !patterns.is_empty() {
+        script.push_str(&format!(
+            "cd {dir} 2>/dev/null && {{ nl=$(printf '\\nx'); nl=${{nl%x}}; left={limit}; for f in {patterns}; do \
+             [ -f \"$f\" ] || continue; case \"$f\" in *\"$nl\"*) continue;; esac; n=$(($(wc -c < \"$f\"))); \
+             if [ \"$n\" -gt \"$left\" ]; then printf 'skip %s %s\\n' \"$n\" \"$f\"; continue; fi; \
+             left=$((left-n)); printf 'file %s %s\\n' \"$n\" \"$f\"; cat < \"$f\"; done; }}; cd /; ",
+            dir = REMOTE_WORKSPACE,
+            limit = config.artifacts.max_workspace_bytes,
+            patterns = patterns.join(" ")
+        ));
+    }
+    script.push_str("rm -rf \"$d\"");
 
-        attempt = attempt.saturating_add(1);
+    let throttle = Throttle::artifacts(&config.bandwidth, &runtime.bandwidth);
+    let transfer_sec = throttle
+        .slowest_rate()
+        .map_or(0, |rate| config.artifacts.max_workspace_bytes as u64 / rate);
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(REMOTE_TRANSFER_TIMEOUT_SEC)
+            .saturating_add(transfer_sec),
+    );
+    let result = tokio::time::timeout(
+        limit,
+        download(build_ssh_command(config, target, &script), &throttle),
+    )
+    .await;
+    let stdout = match result {
+        Ok(Ok(stdout)) => stdout,
+        Ok(Err(error)) => {
+            log_observation(
+                "workspace_collect_failed",
+                json!({"target": target, "run_token": run_token, "error": error.to_string()}),
+            );
+            return Vec::new();
+        }
+        Err(_) => {
+            log_observation(
+                "workspace_collect_failed",
+                json!({"target": target, "run_token": run_token, "error": "workspace collect timeout"}),
+            );
+            return Vec::new();
+        }
+    };
+    let (files, skipped) = parse_workspace_listing(&stdout);
+    if !skipped.is_empty() {
+        log_observation(
+            "workspace_artifacts_skipped",
+            json!({"target": target, "run_token": run_token, "skipped": skipped, "max_workspace_bytes": config.artifacts.max_workspace_bytes}),
+        );
     }
+    files
 }
 
-async fn execute_request_collect_once(config: &BridgeConfig, request: RunRequest) -> Result<CollectedRun> {
-    let policy = config
-        .tools
-        .get(&request.tool)
-        .ok_or_else(|| anyhow!("tool '{}' ist nicht freigegeben", request.tool))?;
+async fn download(mut command: Command, throttle: &Throttle) -> std::io::Result<Vec<u8>> {
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout piped");
+    let mut data = Vec::new();
+    let mut buf = [0_u8; 16 * 1024];
+    loop {
+        let read = stdout.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        throttle.pace(read).await;
+        data.extend_from_slice(&buf[..read]);
+    }
+    child.wait().await?;
+    Ok(data)
+}
 
-    if request.args.len() > policy.max_args {
-        bail!(
-            "zu viele args für tool '{}': {} > {}",
-            request.tool,
-            request.args.len(),
-            policy.max_args
-        );
+fn parse_workspace_listing(mut data: &[u8]) -> (Vec<WorkspaceFile>, Vec<String>) {
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    while let Some(end) = data.iter().position(|byte| *byte == b'\n') {
+        let header = String::from_utf8_lossy(&data[..end]).to_string();
+        data = &data[end + 1..];
+        let mut parts = header.splitn(3, ' ');
+        let (Some(kind), Some(Ok(size)), Some(name)) = (
+            parts.next(),
+            parts.next().map(str::parse::<usize>),
+            parts.next(),
+        ) else {
+            break;
+        };
+        if kind == "skip" {
+            skipped.push(name.to_string());
+            continue;
+        }
+        if kind != "file" || size > data.len() {
+            break;
+        }
+        let name = name.trim_start_matches("./").to_string();
+        if name
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."))
+        {
+            files.push(WorkspaceFile {
+                name,
+                data: data[..size].to_vec(),
+            });
+        }
+        data = &data[size..];
     }
+    (files, skipped)
+}
 
-    let timeout_sec = request
-        .timeout_sec
-        .unwrap_or(config.default_timeout_sec)
-        .min(config.max_timeout_sec);
-    let max_output_bytes = request.max_output_bytes.unwrap_or(config.max_output_bytes);
-    let target = format_target(&request.user, &request.host);
-    let remote_command = build_remote_command(policy, &request.args, timeout_sec);
+async fn store_workspace_files(
+    runtime: &Runtime,
+    engagement: Option<&str>,
+    correlation_id: &str,
+    run_token: &str,
+    output_root: Option<&Path>,
+    files: Vec<WorkspaceFile>,
+) -> Result<Vec<String>> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let config = runtime.config();
+    let mut urls = Vec::new();
+    let mut entries = Vec::new();
+    for file in files {
+        let stored = match (&runtime.artifacts, &config.artifacts.local_dir) {
+            (Some(store), _) => Some(
+                store
+                    .upload_workspace_file(
+                        engagement,
+                        correlation_id,
+                        run_token,
+                        &file.name,
+                        &file.data,
+                    )
+                    .await,
+            ),
+            (None, Some(dir)) => Some(artifacts::store_local(
+                dir,
+                engagement,
+                correlation_id,
+                run_token,
+                &file.name,
+                &file.data,
+                runtime.sealer.as_ref(),
+            )),
+            (None, None) => None,
+        };
+        let placed = output_root.map(|root| {
+            artifacts::store_local(
+                &root.to_string_lossy(),
+                engagement,
+                correlation_id,
+                run_token,
+                &file.name,
+                &file.data,
+                None,
+            )
+        });
+        let stored = match (stored, placed) {
+            (Some(stored), placed) => stored.map(|url| (Some(url), placed)),
+            (None, Some(placed)) => Ok((None, Some(placed))),
+            (None, None) => continue,
+        };
+        match stored {
+            Ok((url, placed)) => {
+                let mut entry = json!({
+                    "name": file.name,
+                    "bytes": file.data.len(),
+                    "sha256": format!("{:x}", Sha256::digest(&file.data))
+                });
+                if let Some(url) = url {
+                    entry["url"] = json!(url);
+                    urls.push(url);
+                }
+                match placed {
+                    Some(Ok(placed)) => {
+                        let path = placed.trim_start_matches("file://").to_string();
+                        entry["client_path"] = json!(path);
+                        urls.push(placed);
+                    }
+                    Some(Err(error)) => log_observation(
+                        "client_root_write_failed",
+                        json!({"correlation_id": correlation_id, "name": file.name, "error": format!("{:#}", error)}),
+                    ),
+                    None => {}
+                }
+                entries.push(entry);
+            }
+            Err(error) => log_observation(
+                "artifact_upload_failed",
+                json!({"correlation_id": correlation_id, "run_token": run_token, "name": file.name, "error": format!("{:#}", error)}),
+            ),
+        }
+    }
+    audit(
+        runtime,
+        "workspace_artifacts",
+        json!({"correlation_id": correlation_id, "run_token": run_token, "files": entries}),
+    )?;
+    log_observation(
+        "workspace_artifacts_stored",
+        json!({"correlation_id": correlation_id, "urls": urls}),
+    );
+    Ok(urls)
+}
 
-    let mut child = build_ssh_command(config, &target, &remote_command)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .context("SSH-Prozess konnte nicht gestartet werden")?;
+async fn kill_remote_process_group(
+    config: &BridgeConfig,
+    target: &str,
+    run_token: &str,
+    termination: Termination<'_>,
+) {
+    let kill_command = format!(
+        "{find}if [ -f {f} ]; then read pid < {f}; \
+         sig() {{ {kill}; }} 2>/dev/null; \
+         sig {signal}; i=0; while [ \"$i\" -lt {grace} ] && sig 0; do sleep 1; i=$((i+1)); done; \
+         {escalate}rm -f {f}; fi; rm -f {s}; rmdir \"$d\" 2>/dev/null; true",
+        find = find_remote_run_dir(run_token),
+        f = REMOTE_PID_FILE,
+        s = REMOTE_STDIN_FILE,
+        kill = group_kill(termination.run_as, "\"$1\""),
+        signal = termination.signal,
+        grace = termination.grace_sec,
+        escalate = if termination.escalate {
+            "sig KILL; "
+        } else {
+            ""
+        }
+    );
+    let limit = Duration::from_secs(
+        config
+            .ssh_connect_timeout_sec
+            .saturating_add(termination.grace_sec)
+            .saturating_add(5),
+    );
+    let result = tokio::time::timeout(
+        limit,
+        build_ssh_command(config, target, &kill_command)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .status(),
+    )
+    .await;
+    let outcome = match result {
+        Ok(Ok(status)) => json!({"exit_code": status.code()}),
+        Ok(Err(error)) => json!({"error": error.to_string()}),
+        Err(_) => json!({"error": "remote kill timeout"}),
+    };
+    log_observation(
+        "remote_kill",
+        json!({"target": target, "run_token": run_token, "outcome": outcome}),
+    );
+}
 
-    let stdout = child.stdout.take().context("stdout pipe fehlt")?;
-    let stderr = child.stderr.take().context("stderr pipe fehlt")?;
-    let (tx, mut rx) = mpsc::channel::<Chunk>(64);
+fn validate_ssh_options(config: &BridgeConfig) -> Result<()> {
+    if config.ssh_program.trim().is_empty() {
+        bail!("ssh_program darf nicht leer sein");
+    }
+    if config.ssh_client == SshClient::Plink {
+        if config.host_keys.known_hosts_file.is_some() {
+            bail!(
+                "ssh_client plink: host_keys.known_hosts_file wird nicht unterstützt, Host-Keys über host_keys.pins festlegen"
+            );
+        }
+        if !config.ssh_extra_options.is_empty() || !config.ssh_host_options.is_empty() {
+            bail!(
+                "ssh_client plink: ssh_extra_options und ssh_host_options werden nicht unterstützt"
+            );
+        }
+    } else if !config.host_keys.pins.is_empty() && config.host_keys.known_hosts_file.is_none() {
+        bail!("host_keys.pins braucht host_keys.known_hosts_file");
+    }
+    if config.connection_sharing.enabled && config.ssh_client == SshClient::Plink {
+        bail!("ssh_client plink: connection_sharing wird nicht unterstützt");
+    }
+    let options = config
+        .ssh_extra_options
+        .iter()
+        .map(|option| ("ssh_extra_options".to_string(), option))
+        .chain(config.ssh_host_options.iter().flat_map(|(host, options)| {
+            options
+                .iter()
+                .map(move |option| (format!("ssh_host_options.{}", host), option))
+        }));
+    for (key, option) in options {
+        let Some((name, value)) = option.split_once('=') else {
+            bail!("{}: '{}' hat nicht die Form Name=Wert", key, option);
+        };
+        if name.is_empty()
+            || !name.chars().all(|c| c.is_ascii_alphanumeric())
+            || value.trim().is_empty()
+        {
+            bail!("{}: ungültige SSH-Option '{}'", key, option);
+        }
+        if value.contains(['\0', '\n', '\r']) {
+            bail!("{}: Option '{}' enthält Steuerzeichen", key, name);
+        }
+        if [
+            "batchmode",
+            "remotecommand",
+            "localcommand",
+            "permitlocalcommand",
+        ]
+        .contains(&name.to_ascii_lowercase().as_str())
+        {
+            bail!(
+                "{}: Option '{}' setzt die Bridge selbst oder ist nicht erlaubt",
+                key,
+                name
+            );
+        }
+        if config.connection_sharing.enabled && name.to_ascii_lowercase().starts_with("control") {
+            bail!(
+                "{}: Option '{}' kollidiert mit connection_sharing",
+                key,
+                name
+            );
+        }
+    }
+    Ok(())
+}
 
-    let tx_out = tx.clone();
-    let out_task = tokio::spawn(async move {
-        let mut reader = BufReader::new(stdout);
-        let mut buf = [0_u8; 4096];
-        loop {
-            let read = reader.read(&mut buf).await?;
-            if read == 0 {
-                break;
+fn validate_usage_hints(tool: &str, policy: &ToolPolicy) -> Result<()> {
+    if policy
+        .description
+        .as_ref()
+        .is_some_and(|description| description.trim().is_empty())
+    {
+        bail!("tools.{}.description darf nicht leer sein", tool);
+    }
+    let definition = tool_definition(tool, policy, &json!([]), Vec::new());
+    let properties = &definition["inputSchema"]["properties"];
+    for (key, hint) in &policy.arg_hints {
+        if hint.trim().is_empty() {
+            bail!("tools.{}.arg_hints.{} darf nicht leer sein", tool, key);
+        }
+        if let Some(profile) = policy.profile
+            && properties.get(key).is_none()
+        {
+            bail!(
+                "tools.{}.arg_hints: '{}' ist kein Parameter des Profils {}",
+                tool,
+                key,
+                profile.as_str()
+            );
+        }
+    }
+    for (index, example) in policy.examples.iter().enumerate() {
+        if example.arguments.is_empty() {
+            bail!(
+                "tools.{}.examples[{}]: arguments darf nicht leer sein",
+                tool,
+                index
+            );
+        }
+        for key in example.arguments.keys() {
+            if properties.get(key).is_none() {
+                bail!(
+                    "tools.{}.examples[{}]: unbekannter Parameter '{}'",
+                    tool,
+                    index,
+                    key
+                );
             }
-            if tx_out.send(Chunk::Stdout(buf[..read].to_vec())).await.is_err() {
-                break;
+        }
+        if let Some(args) = example.arguments.get("args") {
+            let Some(args) = args
+                .as_array()
+                .filter(|args| args.iter().all(Value::is_string))
+            else {
+                bail!(
+                    "tools.{}.examples[{}]: args muss eine Liste von Strings sein",
+                    tool,
+                    index
+                );
+            };
+            if args.len() > policy.max_args {
+                bail!(
+                    "tools.{}.examples[{}]: {} Argumente, erlaubt sind max_args={}",
+                    tool,
+                    index,
+                    args.len(),
+                    policy.max_args
+                );
             }
         }
-        Result::<()>::Ok(())
-    });
+    }
+    Ok(())
+}
 
-    let err_task = tokio::spawn(async move {
-        let mut reader = BufReader::new(stderr);
-        let mut buf = [0_u8; 4096];
-        loop {
-            let read = reader.read(&mut buf).await?;
-            if read == 0 {
-                break;
+fn validate_tool_policies(config: &BridgeConfig) -> Result<()> {
+    for (tool, policy) in &config.tools {
+        for (name, value) in &policy.env {
+            let mut chars = name.chars();
+            let valid_name = chars
+                .next()
+                .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_name {
+                bail!("tools.{}.env: ungültiger Variablenname '{}'", tool, name);
             }
-            if tx.send(Chunk::Stderr(buf[..read].to_vec())).await.is_err() {
-                break;
+            if value.contains('\0') {
+                bail!("tools.{}.env.{}: Wert enthält ein NUL-Byte", tool, name);
             }
         }
-        Result::<()>::Ok(())
-    });
+        if let Some(cwd) = &policy.cwd
+            && (cwd.is_empty() || cwd.contains('\0'))
+        {
+            bail!("tools.{}.cwd: ungültiges Arbeitsverzeichnis", tool);
+        }
+        if let Some(user) = &policy.run_as {
+            let valid_user = !user.is_empty()
+                && user.len() <= 32
+                && !user.starts_with('-')
+                && user
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+            if !valid_user {
+                bail!("tools.{}.run_as: ungültiger Benutzername '{}'", tool, user);
+            }
+        }
+        if let Some(level) = policy.nice_level
+            && !(-20..=19).contains(&level)
+        {
+            bail!("tools.{}.nice_level muss zwischen -20 und 19 liegen", tool);
+        }
+        if policy.max_rate == Some(0) {
+            bail!("tools.{}.max_rate muss größer als 0 sein", tool);
+        }
+        if policy.machine_output.is_some() && (policy.binary || policy.profile.is_some()) {
+            bail!(
+                "tools.{}.machine_output ist nicht mit binary oder profile kombinierbar",
+                tool
+            );
+        }
+        if let Some(min_version) = &policy.min_version
+            && manifest::parse_version(min_version).is_none()
+        {
+            bail!(
+                "tools.{}.min_version: '{}' ist keine Versionsnummer (z. B. 7.94)",
+                tool,
+                min_version
+            );
+        }
+        if policy.version_args.as_ref().is_some_and(Vec::is_empty) {
+            bail!("tools.{}.version_args darf nicht leer sein", tool);
+        }
+        validate_usage_hints(tool, policy)?;
+        if policy.concurrency == Some(Concurrency::Shared(0)) {
+            bail!("tools.{}.concurrency: shared muss größer als 0 sein", tool);
+        }
+        if policy.cpu_percent == Some(0) {
+            bail!("tools.{}.cpu_percent muss größer als 0 sein", tool);
+        }
+        if !KILL_SIGNALS.contains(&policy.kill_signal.as_str()) {
+            bail!(
+                "tools.{}.kill_signal '{}' ist nicht erlaubt (erlaubt: {})",
+                tool,
+                policy.kill_signal,
+                KILL_SIGNALS.join(", ")
+            );
+        }
+        if !policy.artifacts.is_empty() && !policy.workspace {
+            bail!(
+                "tools.{}.artifacts setzt tools.{}.workspace voraus",
+                tool,
+                tool
+            );
+        }
+        let profile_artifacts = policy
+            .profile
+            .is_some_and(|profile| !profile.artifacts().is_empty());
+        if profile_artifacts && !policy.workspace {
+            bail!(
+                "tools.{}: Profil mit Artefakten setzt tools.{}.workspace voraus",
+                tool,
+                tool
+            );
+        }
+        if (!policy.artifacts.is_empty() || profile_artifacts)
+            && config.artifacts.s3.is_none()
+            && config.artifacts.local_dir.is_none()
+        {
+            bail!(
+                "tools.{}.artifacts setzt artifacts.s3 oder artifacts.local_dir voraus",
+                tool
+            );
+        }
+        if policy.profile == Some(ToolProfile::Capture) {
+            policy
+                .capture
+                .validate()
+                .with_context(|| format!("tools.{}.capture ist ungültig", tool))?;
+        }
+        if policy.profile == Some(ToolProfile::Hydra) {
+            policy
+                .hydra
+                .validate()
+                .with_context(|| format!("tools.{}.hydra ist ungültig", tool))?;
+        }
+        if let Some(profile) = policy
+            .profile
+            .filter(|profile| profile.results_command().is_some())
+            && !policy.workspace
+        {
+            bail!(
+                "tools.{}: Profil {} setzt tools.{}.workspace voraus",
+                tool,
+                profile.as_str(),
+                tool
+            );
+        }
+        if policy.interactive && policy.workspace {
+            bail!(
+                "tools.{}: interactive und workspace sind nicht kombinierbar",
+                tool
+            );
+        }
+        if policy.workspace && policy.run_as.is_some() {
+            bail!(
+                "tools.{}: workspace und run_as sind nicht kombinierbar",
+                tool
+            );
+        }
+        for pattern in &policy.artifacts {
+            let valid_pattern = !pattern.is_empty()
+                && !pattern.starts_with('/')
+                && !pattern.starts_with('-')
+                && pattern.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/' | '*' | '?')
+                })
+                && pattern
+                    .split('/')
+                    .all(|segment| !matches!(segment, "" | "." | ".."));
+            if !valid_pattern {
+                bail!("tools.{}.artifacts: ungültiges Muster '{}'", tool, pattern);
+            }
+        }
+        if let Some(max_mem) = &policy.max_mem {
+            parse_mem_limit(max_mem)
+                .with_context(|| format!("tools.{}.max_mem ist ungültig", tool))?;
+        }
+        wordlists::expansions(
+            &config.wordlists,
+            policy.default_args.iter().chain(policy.env.values()),
+        )
+        .with_context(|| format!("tools.{}: Wordlist-Platzhalter nicht auflösbar", tool))?;
+    }
+    Ok(())
+}
 
-    let started = Instant::now();
-    let deadline = started + Duration::from_secs(timeout_sec);
-    let mut process_done = false;
-    let mut timed_out = false;
-    let mut exit_code = None;
-    let mut written_bytes = 0_usize;
-    let mut truncated = false;
-    let mut stdout_text = String::new();
-    let mut stderr_text = String::new();
+fn parse_mem_limit(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let (digits, factor) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 1_u64 << 10),
+        Some('M') => (&value[..value.len() - 1], 1 << 20),
+        Some('G') => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    let amount = digits
+        .parse::<u64>()
+        .with_context(|| format!("'{}' ist keine Größe (z. B. 512M, 2G)", value))?;
+    match amount.checked_mul(factor) {
+        Some(bytes) if bytes > 0 => Ok(bytes),
+        _ => bail!("'{}' ist keine gültige Speichergrenze", value),
+    }
+}
 
-    while !process_done || !rx.is_closed() {
-        tokio::select! {
-            chunk = rx.recv() => {
-                if let Some(chunk) = chunk {
-                    if written_bytes >= max_output_bytes {
-                        truncated = true;
-                        continue;
-                    }
+fn resource_wrapper(policy: &ToolPolicy) -> Vec<String> {
+    let mut wrapper = Vec::new();
+    if let Some(bytes) = policy
+        .max_mem
+        .as_deref()
+        .and_then(|value| parse_mem_limit(value).ok())
+    {
+        wrapper.extend([
+            "prlimit".to_string(),
+            format!("--as={}", bytes),
+            "--".to_string(),
+        ]);
+    }
+    if let Some(level) = policy.nice_level {
+        wrapper.extend(["nice".to_string(), "-n".to_string(), level.to_string()]);
+    }
+    if let Some(class) = policy.io_class {
+        wrapper.extend([
+            "ionice".to_string(),
+            "-c".to_string(),
+            class.ionice_class().to_string(),
+        ]);
+    }
+    if let Some(percent) = policy.cpu_percent {
+        wrapper.extend([
+            "cpulimit".to_string(),
+            "-l".to_string(),
+            percent.to_string(),
+            "-f".to_string(),
+            "--".to_string(),
+        ]);
+    }
+    wrapper
+}
 
-                    let (data, is_stdout) = match chunk {
-                        Chunk::Stdout(bytes) => (bytes, true),
-                        Chunk::Stderr(bytes) => (bytes, false),
-                    };
-                    let remaining = max_output_bytes - written_bytes;
-                    let part = if data.len() > remaining { &data[..remaining] } else { &data[..] };
-                    written_bytes += part.len();
-                    if part.len() < data.len() {
-                        truncated = true;
-                    }
-                    let text = String::from_utf8_lossy(part).to_string();
-                    if is_stdout {
-                        stdout_text.push_str(&text);
-                    } else {
-                        stderr_text.push_str(&text);
-                    }
-                }
-            }
-            _ = tokio::time::sleep(Duration::from_millis(100)), if !process_done => {
-                if let Some(status) = child.try_wait().context("Statusprüfung des SSH-Prozesses fehlgeschlagen")? {
-                    exit_code = status.code();
-                    process_done = true;
-                } else if Instant::now() >= deadline {
-                    timed_out = true;
-                    let _ = child.kill().await;
-                    let status = child.wait().await.context("Timeout und kill fehlgeschlagen")?;
-                    exit_code = status.code();
-                    process_done = true;
-                }
-            }
-            else => {
-                if process_done {
-                    break;
-                }
-            }
+fn build_remote_command(
+    remote_timeout: RemoteTimeout,
+    policy: &ToolPolicy,
+    args: &[String],
+    timeout_sec: u64,
+    run_token: &str,
+    secrets: &SecretValues,
+    stdin_len: Option<usize>,
+) -> String {
+    let sudo = sudo_prefix(policy);
+    let escaped = command_line(policy, sudo.as_deref(), args, secrets, policy.workspace);
+    let (prepare, input) = match stdin_len {
+        Some(len) => (
+            format!("umask 077; head -c {} <&3 > {}; ", len, REMOTE_STDIN_FILE),
+            format!(" < {}", REMOTE_STDIN_FILE),
+        ),
+        None => (String::new(), String::new()),
+    };
+    let mut chdir = create_remote_run_dir(run_token);
+    if policy.workspace {
+        chdir.push_str(&format!("mkdir -m 700 {} || exit 126; ", REMOTE_WORKSPACE));
+    }
+    match &policy.cwd {
+        Some(cwd) => chdir.push_str(&format!("cd {} || exit 126; ", shell_escape(cwd))),
+        None if policy.workspace => {
+            chdir.push_str(&format!("cd {} || exit 126; ", REMOTE_WORKSPACE))
         }
+        None => {}
     }
+    let check = sudo_check(policy, sudo.as_deref());
+    let termination = policy.termination();
+    let coreutils = if termination.escalate {
+        format!(
+            "timeout --signal={} --kill-after={}s {}s",
+            termination.signal, termination.grace_sec, timeout_sec
+        )
+    } else {
+        format!("timeout --signal={} {}s", termination.signal, timeout_sec)
+    };
+    let escalate = if termination.escalate {
+        format!(
+            "; sleep {}; {} 2>/dev/null",
+            termination.grace_sec,
+            group_kill(termination.run_as, "KILL")
+        )
+    } else {
+        String::new()
+    };
+    let watchdog = format!(
+        "( trap 'kill \"$s\" 2>/dev/null; exit 0' TERM; sleep {} & s=$!; wait \"$s\"; trap '' TERM; \
+         {} 2>/dev/null{} ) >/dev/null 2>&1 & dog=$!; ",
+        timeout_sec,
+        group_kill(termination.run_as, termination.signal),
+        escalate
+    );
+    let reap = format!(
+        "[ \"$rc\" != 124 ] || {} 2>/dev/null; ",
+        group_kill(termination.run_as, "KILL")
+    );
+    let (probe, timer, start_watchdog, stop_watchdog) = match remote_timeout {
+        RemoteTimeout::Auto => (
+            format!(
+                "if timeout --signal=TERM --kill-after=5s 1s true 2>/dev/null; then t='{}'; else t=; fi; ",
+                coreutils
+            ),
+            "$t ".to_string(),
+            format!("if [ -z \"$t\" ]; then {}fi; ", watchdog),
+            format!(
+                "[ -z \"$dog\" ] || kill \"$dog\" 2>/dev/null; [ -z \"$t\" ] || {}",
+                reap
+            ),
+        ),
+        RemoteTimeout::Coreutils => (
+            String::new(),
+            format!("{} ", coreutils),
+            String::new(),
+            reap,
+        ),
+        RemoteTimeout::Watchdog => (
+            String::new(),
+            String::new(),
+            watchdog,
+            "kill \"$dog\" 2>/dev/null; ".to_string(),
+        ),
+        RemoteTimeout::Bridge => (String::new(), String::new(), String::new(), String::new()),
+    };
+    format!(
+        "exec 3<&0; {probe}{check}{chdir}{prepare}setsid {timer}{command}{input} 3<&- & pid=$!; \
+         echo \"$pid\" > {pid_file}; {start_watchdog}\
+         {{ cat <&3 >/dev/null 2>&1; {hangup} 2>/dev/null; }} >/dev/null 2>&1 & watcher=$!; \
+         wait \"$pid\"; rc=$?; kill \"$watcher\" 2>/dev/null; {stop_watchdog}rm -f {pid_file} {stdin_file}; \
+         rmdir \"$d\" 2>/dev/null; exit \"$rc\"",
+        hangup = group_kill(termination.run_as, termination.signal),
+        command = escaped,
+        pid_file = REMOTE_PID_FILE,
+        stdin_file = REMOTE_STDIN_FILE
+    )
+}
 
-    out_task.await.context("stdout task join fehlgeschlagen")??;
-    err_task.await.context("stderr task join fehlgeschlagen")??;
+fn build_interactive_command(
+    policy: &ToolPolicy,
+    args: &[String],
+    secrets: &SecretValues,
+) -> String {
+    let sudo = sudo_prefix(policy);
+    let chdir = match &policy.cwd {
+        Some(cwd) => format!("cd {} || exit 126; ", shell_escape(cwd)),
+        None => String::new(),
+    };
+    format!(
+        "{}{}exec {}",
+        sudo_check(policy, sudo.as_deref()),
+        chdir,
+        command_line(policy, sudo.as_deref(), args, secrets, false)
+    )
+}
 
-    Ok(CollectedRun {
-        final_status: FinalStatus {
-            exit_code,
-            timed_out,
-            duration_ms: started.elapsed().as_millis(),
-        },
-        stdout: stdout_text,
-        stderr: stderr_text,
-        truncated,
-        attempts: 1,
-    })
+fn sudo_prefix(policy: &ToolPolicy) -> Option<Vec<String>> {
+    sudo_for(policy.run_as.as_deref())
 }
 
-fn run_success(status: &FinalStatus) -> bool {
-    !status.timed_out && status.exit_code.unwrap_or(1) == 0
+fn sudo_for(run_as: Option<&str>) -> Option<Vec<String>> {
+    run_as.map(|user| {
+        let mut sudo = vec!["sudo".to_string(), "-n".to_string()];
+        if user != "root" {
+            sudo.extend(["-u".to_string(), user.to_string()]);
+        }
+        sudo
+    })
 }
 
-fn log_observation(config: &BridgeConfig, event: &str, payload: Value) {
-    if !config.observability_json_logs {
-        return;
+fn group_kill(run_as: Option<&str>, signal: &str) -> String {
+    match sudo_for(run_as) {
+        Some(sudo) => format!(
+            "{} kill -{} -- -\"$pid\"",
+            sudo.iter()
+                .map(|part| shell_escape(part))
+                .collect::<Vec<_>>()
+                .join(" "),
+            signal
+        ),
+        None => format!("kill -{} -\"$pid\"", signal),
     }
-    let timestamp_ms = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .map(|value| value.as_millis())
-        .unwrap_or(0);
-    let line = json!({
-        "ts_ms": timestamp_ms,
-        "event": event,
-        "payload": payload
-    });
-    eprintln!("{}", line);
 }
 
-fn build_remote_command(policy: &ToolPolicy, args: &[String], timeout_sec: u64) -> String {
+fn sudo_check(policy: &ToolPolicy, sudo: Option<&[String]>) -> String {
+    let (Some(sudo), Some(user)) = (sudo, &policy.run_as) else {
+        return String::new();
+    };
+    let first = resource_wrapper(policy)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| {
+            if policy.env.is_empty() {
+                policy.command.clone()
+            } else {
+                "env".to_string()
+            }
+        });
+    format!(
+        "{} -l -- {} >/dev/null 2>&1 || {{ echo {} >&2; exit {}; }}; ",
+        sudo.iter()
+            .map(|part| shell_escape(part))
+            .collect::<Vec<_>>()
+            .join(" "),
+        shell_escape(&first),
+        shell_escape(&format!(
+            "bridge: sudo verlangt ein Passwort für run_as '{}'",
+            user
+        )),
+        SUDO_DENIED_EXIT
+    )
+}
+
+fn command_line(
+    policy: &ToolPolicy,
+    sudo: Option<&[String]>,
+    args: &[String],
+    secrets: &SecretValues,
+    workspace: bool,
+) -> String {
     let mut full_args = Vec::new();
+    if let Some(sudo) = sudo {
+        full_args.extend(sudo.iter().cloned());
+        full_args.push("--".to_string());
+    }
+    full_args.extend(resource_wrapper(policy));
+    let mut workspace_at = None;
+    if !policy.env.is_empty() || workspace {
+        full_args.push("env".to_string());
+        full_args.extend(
+            policy
+                .env
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, secrets.substitute(value))),
+        );
+        if workspace {
+            workspace_at = Some(full_args.len());
+        }
+    }
     full_args.push(policy.command.clone());
-    full_args.extend(policy.default_args.iter().cloned());
-    full_args.extend(args.iter().cloned());
-    let escaped = full_args
+    full_args.extend(
+        policy
+            .default_args
+            .iter()
+            .map(|arg| secrets.substitute(arg)),
+    );
+    full_args.extend(args.iter().map(|arg| secrets.substitute(arg)));
+    if let Some(machine) = policy.machine_output {
+        full_args.extend(machine.args().iter().map(|arg| arg.to_string()));
+    }
+    let mut escaped = full_args
         .iter()
         .map(|part| shell_escape(part))
-        .collect::<Vec<_>>()
-        .join(" ");
-    format!(
-        "timeout --signal=TERM --kill-after=5s {}s {}",
-        timeout_sec, escaped
-    )
+        .collect::<Vec<_>>();
+    if let Some(index) = workspace_at {
+        escaped.insert(index, format!("BRIDGE_WORKSPACE={}", REMOTE_WORKSPACE));
+    }
+    escaped.join(" ")
+}
+
+fn spawn_ssh(config: &BridgeConfig, target: &str, remote_command: &str) -> Result<Child> {
+    build_ssh_command(config, target, remote_command)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context(Message::SshSpawnFailed)
 }
 
 fn build_ssh_command(config: &BridgeConfig, target: &str, remote_command: &str) -> Command {
-    let mut command = Command::new("ssh");
+    let host = target.rsplit_once('@').map_or(target, |(_, host)| host);
+    let mut command = ssh_command(config, host);
+    for option in config.connection_sharing.ssh_options(target) {
+        command.arg("-o").arg(option);
+    }
+    command.arg(target).arg(remote_command);
+    command
+}
+
+async fn open_shared_connection(
+    config: &BridgeConfig,
+    lease: &connections::Lease<'_>,
+) -> Result<bool> {
+    let _gate = lease.lock().await;
+    if lease.alive().await {
+        return Ok(false);
+    }
+    let _ = std::fs::remove_file(&lease.path);
+    let host = lease
+        .target
+        .rsplit_once('@')
+        .map_or(lease.target.as_str(), |(_, host)| host);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let status = tokio::time::timeout(
+            Duration::from_secs(config.ssh_connect_timeout_sec + 5),
+            ssh_command(config, host)
+                .arg("-o")
+                .arg("ControlMaster=yes")
+                .arg("-o")
+                .arg(format!("ControlPath={}", lease.path.display()))
+                .arg("-o")
+                .arg(format!(
+                    "ControlPersist={}",
+                    config.connection_sharing.persist_sec.max(1)
+                ))
+                .arg("-N")
+                .arg("-f")
+                .arg(&lease.target)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status(),
+        )
+        .await;
+        match status {
+            Ok(Ok(status)) if status.success() && lease.alive().await => return Ok(true),
+            _ if attempts <= config.connection_sharing.reconnect_attempts => {
+                tokio::time::sleep(Duration::from_secs(u64::from(attempts))).await;
+            }
+            Ok(Ok(status)) => bail!(Message::MasterConnectionFailed {
+                target: lease.target.clone(),
+                exit_code: status.code(),
+            }),
+            Ok(Err(error)) => return Err(error).context(Message::SshSpawnFailed),
+            Err(_) => bail!(Message::MasterConnectionTimeout {
+                target: lease.target.clone(),
+            }),
+        }
+    }
+}
+
+fn ssh_command(config: &BridgeConfig, host: &str) -> Command {
+    let mut command = Command::new(&config.ssh_program);
+    if config.ssh_client == SshClient::Plink {
+        command.arg("-ssh").arg("-batch");
+        command.args(config.host_keys.plink_args(host));
+        command.args(config.ssh_identity.plink_args(host));
+        return command;
+    }
     command
         .arg("-o")
         .arg("BatchMode=yes")
@@ -1178,18 +10575,18 @@ fn build_ssh_command(config: &BridgeConfig, target: &str, remote_command: &str)
         .arg(format!(
             "ServerAliveCountMax={}",
             config.ssh_server_alive_count_max
-        ))
-        .arg("-o")
-        .arg(format!(
-            "StrictHostKeyChecking={}",
-            if config.ssh_strict_host_key_checking {
-                "yes"
-            } else {
-                "no"
-            }
-        ))
-        .arg(target)
-        .arg(remote_command);
+        ));
+    for option in config
+        .host_keys
+        .ssh_options(host, config.ssh_strict_host_key_checking)
+    {
+        command.arg("-o").arg(option);
+    }
+    command.args(config.ssh_identity.ssh_args(host));
+    let host_options = config.ssh_host_options.get(host).into_iter().flatten();
+    for option in host_options.chain(&config.ssh_extra_options) {
+        command.arg("-o").arg(option);
+    }
     command
 }
 
@@ -1201,10 +10598,26 @@ fn shell_escape(input: &str) -> String {
     format!("'{}'", escaped)
 }
 
-fn format_target(user: &Option<String>, host: &str) -> String {
+fn format_target(user: &Option<String>, host: &str) -> Result<String> {
+    let host = targets::normalize_host(host)?;
     match user {
-        Some(user) => format!("{}@{}", user, host),
-        None => host.to_string(),
+        Some(user) => {
+            targets::check_user(user)?;
+            Ok(format!("{}@{}", user, host))
+        }
+        None => Ok(host),
+    }
+}
+
+fn error_code(error: &anyhow::Error) -> &'static str {
+    if error.is::<targets::InvalidHost>() {
+        "E_INVALID_HOST"
+    } else if error.is::<hooks::HookRejected>() {
+        "E_HOOK_REJECTED"
+    } else if let Some(message) = error.downcast_ref::<Message>() {
+        message.code()
+    } else {
+        "E_EXEC"
     }
 }
 
@@ -1216,6 +10629,71 @@ async fn emit<W: AsyncWrite + Unpin>(writer: &mut W, event: Event) -> Result<()>
     Ok(())
 }
 
+#[derive(Clone)]
+struct ChannelWriter {
+    tx: mpsc::UnboundedSender<Vec<u8>>,
+    buf: Vec<u8>,
+}
+
+impl AsyncWrite for ChannelWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut TaskContext<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().buf.extend_from_slice(data);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.buf.is_empty() {
+            let chunk = std::mem::take(&mut this.buf);
+            if this.tx.send(chunk).is_err() {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "stdout writer beendet",
+                )));
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+fn channel_writer() -> (ChannelWriter, mpsc::UnboundedReceiver<Vec<u8>>) {
+    let (tx, rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    (
+        ChannelWriter {
+            tx,
+            buf: Vec::new(),
+        },
+        rx,
+    )
+}
+
+fn spawn_stdout_writer() -> (ChannelWriter, JoinHandle<Result<()>>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let task = tokio::spawn(async move {
+        let mut out = io::stdout();
+        while let Some(chunk) = rx.recv().await {
+            out.write_all(&chunk).await?;
+            out.flush().await?;
+        }
+        Ok(())
+    });
+    (
+        ChannelWriter {
+            tx,
+            buf: Vec::new(),
+        },
+        task,
+    )
+}
+
 async fn write_json_line<W: AsyncWrite + Unpin>(writer: &mut W, value: Value) -> Result<()> {
     let line = serde_json::to_string(&value)?;
     writer.write_all(line.as_bytes()).await?;
@@ -1233,15 +10711,22 @@ fn print_schema() -> Result<()> {
         "tool": "whitelisted-tool-name",
         "args": ["arg1", "arg2"],
         "timeout_sec": 30,
-        "max_output_bytes": 131072
+        "max_output_bytes": 131072,
+        "stream_mode": "chunks|lines",
+        "engagement": "optional-engagement-name",
+        "stdin": "optional-text | {\"base64\": \"...\"}"
       },
       "events": [
+        "queued",
         "started",
         "stdout_chunk",
         "stderr_chunk",
+        "stdout_line",
+        "stderr_line",
         "output_truncated",
         "finished",
-        "error"
+        "error",
+        "shutdown"
       ]
     });
     println!("{}", serde_json::to_string_pretty(&schema)?);
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub tool_version: bool,
+    #[serde(default = "default_version_args")]
+    pub version_args: Vec<String>,
+    #[serde(default = "default_version_timeout_sec")]
+    pub version_timeout_sec: u64,
+    #[serde(default = "default_version_ttl_sec")]
+    pub version_ttl_sec: u64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_version_args() -> Vec<String> {
+    vec!["--version".to_string()]
+}
+
+fn default_version_timeout_sec() -> u64 {
+    10
+}
+
+fn default_version_ttl_sec() -> u64 {
+    3600
+}
+
+impl Default for ManifestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            tool_version: false,
+            version_args: default_version_args(),
+            version_timeout_sec: default_version_timeout_sec(),
+            version_ttl_sec: default_version_ttl_sec(),
+        }
+    }
+}
+
+pub fn validate(config: &ManifestConfig) -> Result<()> {
+    if config.version_timeout_sec == 0 {
+        bail!("manifest.version_timeout_sec muss größer als 0 sein");
+    }
+    if config.tool_version && config.version_args.is_empty() {
+        bail!("manifest.version_args darf nicht leer sein, wenn tool_version aktiv ist");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionAction {
+    #[default]
+    Warn,
+    Fail,
+}
+
+#[derive(Default)]
+pub struct ToolVersions {
+    versions: Mutex<HashMap<(String, String), (String, Instant)>>,
+}
+
+impl ToolVersions {
+    pub fn get(&self, target: &str, command: &str, ttl_sec: u64) -> Option<String> {
+        let versions = self.versions.lock().expect("tool versions poisoned");
+        versions
+            .get(&(target.to_string(), command.to_string()))
+            .filter(|(_, probed)| fresh(*probed, ttl_sec))
+            .map(|(version, _)| version.clone())
+    }
+
+    pub fn insert(&self, target: &str, command: &str, version: String) {
+        let mut versions = self.versions.lock().expect("tool versions poisoned");
+        versions.insert(
+            (target.to_string(), command.to_string()),
+            (version, Instant::now()),
+        );
+    }
+
+    pub fn for_command(&self, command: &str, ttl_sec: u64) -> Vec<(String, String)> {
+        let versions = self.versions.lock().expect("tool versions poisoned");
+        let mut found = versions
+            .iter()
+            .filter(|((_, cached), (_, probed))| cached == command && fresh(*probed, ttl_sec))
+            .map(|((target, _), (version, _))| (target.clone(), version.clone()))
+            .collect::<Vec<_>>();
+        found.sort();
+        found
+    }
+}
+
+fn fresh(probed: Instant, ttl_sec: u64) -> bool {
+    ttl_sec == 0 || probed.elapsed() < Duration::from_secs(ttl_sec)
+}
+
+pub fn version_number(text: &str) -> Option<&str> {
+    static VERSION: OnceLock<Regex> = OnceLock::new();
+    let version = VERSION.get_or_init(|| Regex::new(r"\d+(?:\.\d+)*").expect("version regex"));
+    version.find(text).map(|found| found.as_str())
+}
+
+pub fn parse_version(text: &str) -> Option<Vec<u64>> {
+    version_number(text)?
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+pub fn satisfies(version: &str, min_version: &str) -> Option<bool> {
+    let (mut found, mut required) = (parse_version(version)?, parse_version(min_version)?);
+    let len = found.len().max(required.len());
+    found.resize(len, 0);
+    required.resize(len, 0);
+    Some(found >= required)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    pub bridge_version: &'static str,
+    pub correlation_id: String,
+    pub replay_of: Option<String>,
+    pub tool: String,
+    pub target: String,
+    pub request: Value,
+    pub config: Value,
+    pub ssh: Vec<String>,
+    pub tool_version: Option<String>,
+    pub started_ms: u128,
+    pub finished_ms: Option<u128>,
+    pub attempts: Vec<Attempt>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attempt {
+    pub attempt: u32,
+    pub run_token: Option<String>,
+    pub remote_command: Option<String>,
+    pub started_ms: u128,
+    pub finished_ms: Option<u128>,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub failure_class: Option<String>,
+    pub error: Option<String>,
+    pub backoff_ms: Option<u64>,
+}
+
+impl Attempt {
+    pub fn new(attempt: u32) -> Self {
+        Self {
+            attempt,
+            run_token: None,
+            remote_command: None,
+            started_ms: now_ms(),
+            finished_ms: None,
+            exit_code: None,
+            timed_out: false,
+            failure_class: None,
+            error: None,
+            backoff_ms: None,
+        }
+    }
+
+    pub fn command(&mut self, run_token: &str, remote_command: &str) {
+        self.run_token = Some(run_token.to_string());
+        self.remote_command = Some(remote_command.to_string());
+    }
+
+    pub fn finish(&mut self, exit_code: Option<i32>, timed_out: bool, failure_class: Option<&str>) {
+        self.finished_ms = Some(now_ms());
+        self.exit_code = exit_code;
+        self.timed_out = timed_out;
+        self.failure_class = failure_class.map(str::to_string);
+    }
+
+    pub fn fail(&mut self, error: &anyhow::Error) {
+        self.finished_ms = Some(now_ms());
+        self.failure_class = Some("exec_error".to_string());
+        self.error = Some(error.to_string());
+    }
+}
+
+impl Manifest {
+    pub fn finish(&mut self) {
+        self.finished_ms = Some(now_ms());
+    }
+}
+
+pub fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
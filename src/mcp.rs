@@ -0,0 +1,292 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::messages::Message;
+
+const MAX_COMPLETIONS: usize = 100;
+const REQUEST_PREFIX: &str = "bridge-req-";
+
+pub const NMAP_ARGS: &[&str] = &[
+    "-sS",
+    "-sT",
+    "-sU",
+    "-sA",
+    "-sW",
+    "-sN",
+    "-sF",
+    "-sX",
+    "-sn",
+    "-sV",
+    "-sC",
+    "-O",
+    "-A",
+    "-Pn",
+    "-n",
+    "-p",
+    "-p-",
+    "--top-ports",
+    "-F",
+    "-T0",
+    "-T1",
+    "-T2",
+    "-T3",
+    "-T4",
+    "-T5",
+    "--open",
+    "--reason",
+    "--script",
+    "-oX",
+    "-oN",
+    "-oG",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpConfig {
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub inline_output_bytes: usize,
+    #[serde(default = "default_max_read_bytes")]
+    pub max_read_bytes: usize,
+    #[serde(default = "default_stored_outputs")]
+    pub stored_outputs: usize,
+    #[serde(default = "default_output_store_bytes")]
+    pub output_store_bytes: usize,
+}
+
+fn default_page_size() -> usize {
+    100
+}
+
+fn default_max_read_bytes() -> usize {
+    64 * 1024
+}
+
+fn default_stored_outputs() -> usize {
+    32
+}
+
+fn default_output_store_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            page_size: default_page_size(),
+            hosts: Vec::new(),
+            inline_output_bytes: 0,
+            max_read_bytes: default_max_read_bytes(),
+            stored_outputs: default_stored_outputs(),
+            output_store_bytes: default_output_store_bytes(),
+        }
+    }
+}
+
+pub fn paginate(
+    mut items: Vec<Value>,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<(Vec<Value>, Option<String>)> {
+    items.sort_by(|left, right| name(left).cmp(name(right)));
+    let start = match cursor {
+        Some(cursor) => {
+            let after = decode_cursor(cursor)?;
+            items.partition_point(|item| name(item) <= after.as_str())
+        }
+        None => 0,
+    };
+    let mut page = items.split_off(start);
+    if page_size == 0 || page.len() <= page_size {
+        return Ok((page, None));
+    }
+    page.truncate(page_size);
+    let next = page
+        .last()
+        .map(|item| BASE64.encode(json!({"after": name(item)}).to_string()));
+    Ok((page, next))
+}
+
+fn decode_cursor(cursor: &str) -> Result<String> {
+    let decoded = BASE64
+        .decode(cursor.trim())
+        .map_err(|_| Message::InvalidCursor)?;
+    let value = serde_json::from_slice::<Value>(&decoded).map_err(|_| Message::InvalidCursor)?;
+    match value["after"].as_str() {
+        Some(after) => Ok(after.to_string()),
+        None => bail!(Message::InvalidCursor),
+    }
+}
+
+fn name(item: &Value) -> &str {
+    item["name"].as_str().unwrap_or_default()
+}
+
+pub fn schema_values(tool: &Value, argument: &str) -> Vec<String> {
+    let property = &tool["inputSchema"]["properties"][argument];
+    let values = property["enum"]
+        .as_array()
+        .or_else(|| property["items"]["enum"].as_array());
+    values
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn completion(candidates: impl IntoIterator<Item = String>, prefix: &str) -> Value {
+    let prefix = prefix.to_lowercase();
+    let matches = candidates
+        .into_iter()
+        .filter(|candidate| candidate.to_lowercase().starts_with(&prefix))
+        .collect::<BTreeSet<_>>();
+    let total = matches.len();
+    let values = matches
+        .into_iter()
+        .take(MAX_COMPLETIONS)
+        .collect::<Vec<_>>();
+    json!({"completion": {"values": values, "total": total, "hasMore": total > MAX_COMPLETIONS}})
+}
+
+pub struct Peer {
+    out: mpsc::UnboundedSender<Vec<u8>>,
+    next: AtomicU64,
+    pending: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+    capabilities: Mutex<Value>,
+    roots: Mutex<Option<Vec<String>>>,
+    roots_generation: AtomicU64,
+}
+
+struct PendingRequest<'a> {
+    peer: &'a Peer,
+    id: String,
+    answered: bool,
+}
+
+impl Peer {
+    pub fn new(out: mpsc::UnboundedSender<Vec<u8>>) -> Arc<Self> {
+        Arc::new(Self {
+            out,
+            next: AtomicU64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(Value::Null),
+            roots: Mutex::new(None),
+            roots_generation: AtomicU64::new(0),
+        })
+    }
+
+    pub fn set_capabilities(&self, capabilities: Value) {
+        *self.capabilities.lock().expect("peer poisoned") = capabilities;
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities
+            .lock()
+            .expect("peer poisoned")
+            .get(capability)
+            .is_some_and(Value::is_object)
+    }
+
+    pub fn notify(&self, method: &str, params: Value) -> Result<()> {
+        self.send(json!({"jsonrpc": "2.0", "method": method, "params": params}))
+    }
+
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = format!(
+            "{}{}",
+            REQUEST_PREFIX,
+            self.next.fetch_add(1, Ordering::Relaxed)
+        );
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .expect("peer poisoned")
+            .insert(id.clone(), tx);
+        let mut guard = PendingRequest {
+            peer: self,
+            id: id.clone(),
+            answered: false,
+        };
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))?;
+        let response = rx.await.context("Client-Verbindung wurde beendet")?;
+        guard.answered = true;
+        match response.get("error") {
+            Some(error) => Err(anyhow!(
+                "Client lehnte {} ab: {}",
+                method,
+                error["message"].as_str().unwrap_or("unbekannter Fehler")
+            )),
+            None => Ok(response["result"].clone()),
+        }
+    }
+
+    pub async fn roots(&self) -> Result<Vec<String>> {
+        if let Some(roots) = self.roots.lock().expect("peer poisoned").as_ref() {
+            return Ok(roots.clone());
+        }
+        let generation = self.roots_generation.load(Ordering::Acquire);
+        let result = self.request("roots/list", json!({})).await?;
+        let uris = result["roots"]
+            .as_array()
+            .context("roots/list lieferte keine roots")?
+            .iter()
+            .filter_map(|root| root["uri"].as_str())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if self.roots_generation.load(Ordering::Acquire) == generation {
+            *self.roots.lock().expect("peer poisoned") = Some(uris.clone());
+        }
+        Ok(uris)
+    }
+
+    pub fn forget_roots(&self) {
+        self.roots_generation.fetch_add(1, Ordering::AcqRel);
+        *self.roots.lock().expect("peer poisoned") = None;
+    }
+
+    pub fn resolve(&self, response: &Value) -> bool {
+        let Some(id) = response["id"].as_str() else {
+            return false;
+        };
+        match self.pending.lock().expect("peer poisoned").remove(id) {
+            Some(tx) => tx.send(response.clone()).is_ok(),
+            None => false,
+        }
+    }
+
+    fn send(&self, message: Value) -> Result<()> {
+        let mut line = serde_json::to_vec(&message)?;
+        line.push(b'\n');
+        self.out
+            .send(line)
+            .map_err(|_| anyhow!("stdout writer beendet"))
+    }
+}
+
+impl Drop for PendingRequest<'_> {
+    fn drop(&mut self) {
+        if self.answered {
+            return;
+        }
+        self.peer
+            .pending
+            .lock()
+            .expect("peer poisoned")
+            .remove(&self.id);
+        let _ = self.peer.notify(
+            "notifications/cancelled",
+            json!({"requestId": self.id, "reason": "Anfrage wird nicht mehr benötigt"}),
+        );
+    }
+}
@@ -0,0 +1,1735 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+pub fn install(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+pub fn error_prefix() -> &'static str {
+    match locale() {
+        Locale::En => "Error",
+        Locale::De => "Fehler",
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ToolNotAllowed {
+        tool: String,
+    },
+    ToolDisabled {
+        tool: String,
+    },
+    TooManyArgs {
+        tool: String,
+        count: usize,
+        max: usize,
+    },
+    StdinTooLarge {
+        tool: String,
+        size: usize,
+        max: usize,
+    },
+    InvalidHost {
+        host: String,
+    },
+    InvalidUser {
+        user: String,
+    },
+    ProfileNotStreaming {
+        tool: String,
+        profile: String,
+    },
+    ProfileParamsOnly {
+        tool: String,
+        profile: String,
+    },
+    ScopeRequired {
+        tool: String,
+        profile: String,
+    },
+    InvalidPattern {
+        field: &'static str,
+        error: String,
+    },
+    FilterUnsupported {
+        tool: String,
+    },
+    OutputFlagConflict {
+        tool: String,
+        format: &'static str,
+        arg: String,
+    },
+    ToolVersionTooOld {
+        tool: String,
+        version: String,
+        min_version: String,
+    },
+    OutOfScope {
+        target: String,
+        engagement: String,
+    },
+    UnrecognizedTarget {
+        target: String,
+        engagement: String,
+    },
+    EngagementClosed {
+        engagement: String,
+    },
+    NoRole {
+        client: String,
+    },
+    ToolForbidden {
+        client: String,
+        tool: String,
+    },
+    HostForbidden {
+        client: String,
+        tool: String,
+        host: String,
+    },
+    Paused,
+    PausedRetry,
+    Shutdown,
+    Killed,
+    RunShutdown {
+        exit_code: Option<i32>,
+    },
+    RunKilled {
+        exit_code: Option<i32>,
+    },
+    RunCancelled {
+        exit_code: Option<i32>,
+    },
+    ApprovalDenied {
+        reason: Option<String>,
+    },
+    ApprovalWithdrawn,
+    ApprovalTimeout {
+        timeout_sec: u64,
+    },
+    HostMissing,
+    SshSpawnFailed,
+    SshStatusFailed,
+    PipeMissing {
+        stream: &'static str,
+    },
+    OutputTaskFailed {
+        stream: &'static str,
+    },
+    KillFailed {
+        trigger: &'static str,
+    },
+    OperationTimeout {
+        tool: String,
+        timeout_sec: u64,
+    },
+    UnexpectedResponse,
+    InvalidArguments {
+        tool: String,
+        error: String,
+    },
+    MissingArgument {
+        tool: String,
+        argument: &'static str,
+    },
+    ExactlyOneOf {
+        tool: String,
+        options: &'static str,
+    },
+    ArgumentNotApplicable {
+        tool: String,
+        argument: &'static str,
+    },
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+    InvalidEncoding {
+        field: &'static str,
+    },
+    PlaceholderIncomplete {
+        arg: String,
+    },
+    UnknownVariable {
+        name: String,
+    },
+    LimitExceeded {
+        field: &'static str,
+        value: usize,
+        max: usize,
+    },
+    TooManyHosts {
+        target: String,
+        max: usize,
+    },
+    AttemptsExceeded {
+        attempts: u64,
+        logins: u64,
+        passwords: u64,
+        max: u64,
+    },
+    NotReleased {
+        key: String,
+        name: String,
+    },
+    Disabled {
+        tool: String,
+        setting: &'static str,
+    },
+    NotConfigured {
+        key: String,
+    },
+    PasswordMissing {
+        key: &'static str,
+        env: &'static str,
+    },
+    InvalidLabelName {
+        key: String,
+        max: usize,
+    },
+    LabelTooLong {
+        key: String,
+        max: usize,
+    },
+    LabelControlChars {
+        key: String,
+    },
+    InvalidLabelPair {
+        pair: String,
+    },
+    InvalidCursor,
+    PathNotAbsolute {
+        path: String,
+    },
+    PathInvalidChars,
+    PathNotNormalized {
+        path: String,
+    },
+    PathOutsideRoots {
+        tool: &'static str,
+        path: String,
+    },
+    NotAFile {
+        tool: &'static str,
+        path: String,
+    },
+    RemoteDenied {
+        tool: String,
+        path: String,
+    },
+    RemoteNotFound {
+        tool: String,
+        path: String,
+    },
+    RemoteExists {
+        tool: String,
+        path: String,
+    },
+    RemoteSshFailed {
+        tool: String,
+        detail: String,
+    },
+    MasterConnectionFailed {
+        target: String,
+        exit_code: Option<i32>,
+    },
+    MasterConnectionTimeout {
+        target: String,
+    },
+    RemoteFailed {
+        tool: String,
+        exit_code: Option<i32>,
+        detail: String,
+    },
+    PingSweepFailed {
+        tool: String,
+        exit_code: Option<i32>,
+        timed_out: bool,
+        detail: String,
+    },
+    PingSweepTruncated {
+        tool: String,
+    },
+    WordlistMissing {
+        name: String,
+        path: String,
+    },
+    CrackJobMissing {
+        job_id: String,
+    },
+    CrackBusy {
+        max_jobs: usize,
+    },
+    OutputNotStored {
+        run_id: String,
+    },
+    TargetListMissing {
+        name: String,
+    },
+    TargetListForeign {
+        name: String,
+        owner: String,
+        engagement: String,
+    },
+    EngagementMissing {
+        name: String,
+    },
+    EngagementExists {
+        name: String,
+    },
+    EngagementRequired,
+    RunUnknown {
+        id: String,
+    },
+    RunExists {
+        id: String,
+    },
+    InvalidRunId {
+        id: String,
+    },
+    TooManyRuns {
+        max: usize,
+    },
+    GvmTaskMissing {
+        task_id: String,
+    },
+    QueueFull {
+        max: usize,
+    },
+    QueueTimeout {
+        timeout_sec: u64,
+    },
+    InFlightAborted,
+    SecretNotConfigured {
+        name: String,
+    },
+    SecretEnvMissing {
+        name: String,
+        var: String,
+    },
+    SecretFileEntryMissing {
+        name: String,
+        key: String,
+    },
+    SecretEmpty {
+        name: String,
+    },
+    SecretCommandFailed {
+        name: String,
+    },
+    SecretCommandExit {
+        name: String,
+        status: String,
+    },
+    SecretCommandTimeout {
+        name: String,
+        timeout_sec: u64,
+    },
+    SecretNotUtf8 {
+        name: String,
+    },
+    SecretFileNotConfigured,
+    SecretFileUnreadable {
+        path: String,
+    },
+    SecretFileDecryptFailed {
+        path: String,
+    },
+    SecretKeyInvalid {
+        path: String,
+    },
+    SecretFileWriteFailed {
+        path: String,
+    },
+    SecretValueMissing,
+    SecretNotStored {
+        name: String,
+    },
+    KeyFileWriteFailed {
+        path: String,
+    },
+    RandomFailed,
+    StateFileUnreadable {
+        kind: &'static str,
+        path: String,
+    },
+    StateFileInvalid {
+        kind: &'static str,
+        path: String,
+    },
+    StateFileWriteFailed {
+        kind: &'static str,
+        path: String,
+    },
+    SessionLimit {
+        max: usize,
+    },
+    SessionClientLimit {
+        client: String,
+        count: usize,
+    },
+    SessionUnknown {
+        id: String,
+    },
+    SessionRequired,
+    SessionOnly {
+        tool: String,
+    },
+    SessionRateLimited {
+        limit: usize,
+    },
+    TerminalLimit {
+        max: usize,
+    },
+    TerminalUnknown {
+        id: String,
+    },
+    TerminalClosed {
+        id: String,
+    },
+    TerminalInputFailed,
+    BackendUnreachable {
+        service: &'static str,
+        address: String,
+    },
+    BackendFailed {
+        service: &'static str,
+        operation: String,
+        detail: String,
+    },
+    BackendClosed {
+        service: &'static str,
+        operation: String,
+    },
+    BackendResponseTooLarge {
+        service: &'static str,
+        operation: String,
+        max: usize,
+    },
+    BackendAuthRejected {
+        service: &'static str,
+        operation: String,
+    },
+    BackendUnexpected {
+        service: &'static str,
+        operation: String,
+    },
+    ScriptFailed {
+        stage: &'static str,
+        error: String,
+    },
+    StepScriptInvalid {
+        index: usize,
+        field: &'static str,
+    },
+    ForEachWithoutTarget {
+        index: usize,
+        placeholder: &'static str,
+    },
+    WorkflowFailed,
+    MachineOutputMissing {
+        format: &'static str,
+    },
+    MachineOutputIncomplete {
+        format: &'static str,
+    },
+    MachineOutputInvalid {
+        format: &'static str,
+    },
+    HttpReadTimeout,
+    HttpHeaderTooLarge {
+        max: usize,
+    },
+    HttpIncomplete,
+    HttpMalformed {
+        part: &'static str,
+    },
+    HttpChunked,
+    HttpBodyTooLarge {
+        max: usize,
+    },
+    AuthRequired,
+    MethodNotAllowed {
+        allowed: &'static str,
+    },
+    UnknownPath,
+    WebSocketExpected,
+    WebSocketVersion,
+    WebSocketKeyMissing,
+}
+
+impl Message {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Message::ToolNotAllowed { .. } => "E_TOOL_NOT_ALLOWED",
+            Message::ToolDisabled { .. } => "E_TOOL_DISABLED",
+            Message::TooManyArgs { .. } => "E_TOO_MANY_ARGS",
+            Message::StdinTooLarge { .. } => "E_STDIN_TOO_LARGE",
+            Message::InvalidHost { .. } | Message::InvalidUser { .. } => "E_INVALID_HOST",
+            Message::ProfileNotStreaming { .. }
+            | Message::ProfileParamsOnly { .. }
+            | Message::InvalidPattern { .. }
+            | Message::FilterUnsupported { .. }
+            | Message::OutputFlagConflict { .. }
+            | Message::HostMissing => "E_INVALID_REQUEST",
+            Message::ToolVersionTooOld { .. } => "E_TOOL_VERSION",
+            Message::ScopeRequired { .. } => "E_SCOPE_REQUIRED",
+            Message::OutOfScope { .. }
+            | Message::UnrecognizedTarget { .. }
+            | Message::TargetListForeign { .. } => "E_OUT_OF_SCOPE",
+            Message::EngagementClosed { .. } => "E_ENGAGEMENT_CLOSED",
+            Message::NoRole { .. }
+            | Message::ToolForbidden { .. }
+            | Message::HostForbidden { .. } => "E_FORBIDDEN",
+            Message::Paused | Message::PausedRetry => "E_PAUSED",
+            Message::Shutdown | Message::RunShutdown { .. } => "E_SHUTDOWN",
+            Message::Killed | Message::RunKilled { .. } => "E_KILLED",
+            Message::RunCancelled { .. } => "E_CANCELLED",
+            Message::ApprovalDenied { .. } => "E_APPROVAL_DENIED",
+            Message::ApprovalWithdrawn | Message::ApprovalTimeout { .. } => "E_APPROVAL_EXPIRED",
+            Message::SshSpawnFailed
+            | Message::SshStatusFailed
+            | Message::PipeMissing { .. }
+            | Message::OutputTaskFailed { .. }
+            | Message::KillFailed { .. } => "E_SSH",
+            Message::OperationTimeout { .. } => "E_TIMEOUT",
+            Message::UnexpectedResponse => "E_UNEXPECTED_RESPONSE",
+            Message::InvalidArguments { .. }
+            | Message::MissingArgument { .. }
+            | Message::ExactlyOneOf { .. }
+            | Message::ArgumentNotApplicable { .. }
+            | Message::InvalidValue { .. }
+            | Message::InvalidEncoding { .. }
+            | Message::PlaceholderIncomplete { .. }
+            | Message::InvalidLabelName { .. }
+            | Message::LabelTooLong { .. }
+            | Message::LabelControlChars { .. }
+            | Message::InvalidLabelPair { .. }
+            | Message::InvalidCursor => "E_INVALID_ARGUMENTS",
+            Message::LimitExceeded { .. }
+            | Message::TooManyHosts { .. }
+            | Message::AttemptsExceeded { .. }
+            | Message::TooManyRuns { .. } => "E_LIMIT_EXCEEDED",
+            Message::NotReleased { .. } => "E_NOT_ALLOWED",
+            Message::Disabled { .. } => "E_TOOL_DISABLED",
+            Message::NotConfigured { .. } | Message::PasswordMissing { .. } => "E_NOT_CONFIGURED",
+            Message::PathNotAbsolute { .. }
+            | Message::PathInvalidChars
+            | Message::PathNotNormalized { .. }
+            | Message::NotAFile { .. } => "E_INVALID_PATH",
+            Message::PathOutsideRoots { .. } | Message::RemoteDenied { .. } => "E_PATH_DENIED",
+            Message::RemoteNotFound { .. }
+            | Message::WordlistMissing { .. }
+            | Message::CrackJobMissing { .. }
+            | Message::OutputNotStored { .. }
+            | Message::TargetListMissing { .. }
+            | Message::EngagementMissing { .. }
+            | Message::RunUnknown { .. }
+            | Message::GvmTaskMissing { .. }
+            | Message::SecretNotStored { .. }
+            | Message::UnknownPath => "E_NOT_FOUND",
+            Message::RemoteExists { .. }
+            | Message::EngagementExists { .. }
+            | Message::RunExists { .. } => "E_EXISTS",
+            Message::RemoteSshFailed { .. }
+            | Message::MasterConnectionFailed { .. }
+            | Message::MasterConnectionTimeout { .. }
+            | Message::TerminalInputFailed => "E_SSH",
+            Message::RemoteFailed { .. }
+            | Message::PingSweepFailed { .. }
+            | Message::PingSweepTruncated { .. } => "E_REMOTE",
+            Message::CrackBusy { .. } => "E_BUSY",
+            Message::EngagementRequired => "E_ENGAGEMENT_REQUIRED",
+            Message::InvalidRunId { .. } => "E_INVALID_REQUEST",
+            Message::QueueFull { .. } => "E_QUEUE_FULL",
+            Message::QueueTimeout { .. } => "E_QUEUE_TIMEOUT",
+            Message::InFlightAborted => "E_CANCELLED",
+            Message::SecretNotConfigured { .. }
+            | Message::SecretEnvMissing { .. }
+            | Message::SecretFileEntryMissing { .. }
+            | Message::SecretEmpty { .. }
+            | Message::SecretCommandFailed { .. }
+            | Message::SecretCommandExit { .. }
+            | Message::SecretCommandTimeout { .. }
+            | Message::SecretNotUtf8 { .. }
+            | Message::SecretFileNotConfigured
+            | Message::SecretFileUnreadable { .. }
+            | Message::SecretFileDecryptFailed { .. }
+            | Message::SecretKeyInvalid { .. }
+            | Message::SecretFileWriteFailed { .. }
+            | Message::SecretValueMissing
+            | Message::KeyFileWriteFailed { .. } => "E_SECRET",
+            Message::RandomFailed
+            | Message::StateFileUnreadable { .. }
+            | Message::StateFileInvalid { .. }
+            | Message::StateFileWriteFailed { .. } => "E_INTERNAL",
+            Message::SessionLimit { .. }
+            | Message::SessionClientLimit { .. }
+            | Message::TerminalLimit { .. } => "E_SESSION_LIMIT",
+            Message::SessionUnknown { .. } | Message::TerminalUnknown { .. } => {
+                "E_SESSION_NOT_FOUND"
+            }
+            Message::SessionRequired | Message::SessionOnly { .. } => "E_SESSION_REQUIRED",
+            Message::TerminalClosed { .. } => "E_SESSION_CLOSED",
+            Message::SessionRateLimited { .. } => "E_RATE_LIMITED",
+            Message::BackendUnreachable { .. }
+            | Message::BackendFailed { .. }
+            | Message::BackendClosed { .. }
+            | Message::BackendResponseTooLarge { .. }
+            | Message::BackendAuthRejected { .. } => "E_BACKEND",
+            Message::BackendUnexpected { .. } => "E_UNEXPECTED_RESPONSE",
+            Message::ScriptFailed { .. } => "E_SCRIPT",
+            Message::UnknownVariable { .. }
+            | Message::StepScriptInvalid { .. }
+            | Message::ForEachWithoutTarget { .. }
+            | Message::WorkflowFailed => "E_WORKFLOW",
+            Message::MachineOutputMissing { .. }
+            | Message::MachineOutputIncomplete { .. }
+            | Message::MachineOutputInvalid { .. } => "E_MACHINE_OUTPUT",
+            Message::HttpReadTimeout
+            | Message::HttpHeaderTooLarge { .. }
+            | Message::HttpIncomplete
+            | Message::HttpMalformed { .. }
+            | Message::HttpChunked => "E_BAD_REQUEST",
+            Message::HttpBodyTooLarge { .. } => "E_BODY_TOO_LARGE",
+            Message::AuthRequired => "E_UNAUTHORIZED",
+            Message::MethodNotAllowed { .. } => "E_METHOD_NOT_ALLOWED",
+            Message::WebSocketExpected
+            | Message::WebSocketVersion
+            | Message::WebSocketKeyMissing => "E_WEBSOCKET",
+        }
+    }
+
+    pub fn text(&self, locale: Locale) -> String {
+        match (self, locale) {
+            (Message::ToolNotAllowed { tool }, Locale::En) => {
+                format!("tool '{}' is not allowed", tool)
+            }
+            (Message::ToolNotAllowed { tool }, Locale::De) => {
+                format!("tool '{}' ist nicht freigegeben", tool)
+            }
+            (Message::ToolDisabled { tool }, Locale::En) => {
+                format!("tool '{}' is disabled at runtime", tool)
+            }
+            (Message::ToolDisabled { tool }, Locale::De) => {
+                format!("tool '{}' ist zur Laufzeit deaktiviert", tool)
+            }
+            (Message::TooManyArgs { tool, count, max }, Locale::En) => {
+                format!("too many args for tool '{}': {} > {}", tool, count, max)
+            }
+            (Message::TooManyArgs { tool, count, max }, Locale::De) => {
+                format!("zu viele args für tool '{}': {} > {}", tool, count, max)
+            }
+            (Message::StdinTooLarge { tool, size, max }, Locale::En) => {
+                format!(
+                    "stdin for tool '{}' too large: {} > {} bytes",
+                    tool, size, max
+                )
+            }
+            (Message::StdinTooLarge { tool, size, max }, Locale::De) => {
+                format!(
+                    "stdin für tool '{}' zu groß: {} > {} Bytes",
+                    tool, size, max
+                )
+            }
+            (Message::InvalidHost { host }, Locale::En) => {
+                format!("invalid host '{}'", host.escape_debug())
+            }
+            (Message::InvalidHost { host }, Locale::De) => {
+                format!("ungültiger Host '{}'", host.escape_debug())
+            }
+            (Message::InvalidUser { user }, Locale::En) => {
+                format!("invalid SSH user '{}'", user.escape_debug())
+            }
+            (Message::InvalidUser { user }, Locale::De) => {
+                format!("ungültiger SSH-Benutzer '{}'", user.escape_debug())
+            }
+            (Message::ProfileNotStreaming { tool, profile }, Locale::En) => format!(
+                "tool '{}' (profile {}) is only available via tools/call or workflows, not in streaming mode",
+                tool, profile
+            ),
+            (Message::ProfileNotStreaming { tool, profile }, Locale::De) => format!(
+                "tool '{}' (Profil {}) ist nur über tools/call bzw. Workflows nutzbar, nicht im Streaming-Modus",
+                tool, profile
+            ),
+            (Message::ProfileParamsOnly { tool, profile }, Locale::En) => {
+                format!(
+                    "tool '{}' (profile {}) takes structured params instead of args and stdin",
+                    tool, profile
+                )
+            }
+            (Message::ProfileParamsOnly { tool, profile }, Locale::De) => {
+                format!(
+                    "tool '{}' (Profil {}) nimmt strukturierte Parameter statt args und stdin",
+                    tool, profile
+                )
+            }
+            (Message::ScopeRequired { tool, profile }, Locale::En) => {
+                format!(
+                    "tool '{}' (profile {}) requires an engagement with a scope",
+                    tool, profile
+                )
+            }
+            (Message::ScopeRequired { tool, profile }, Locale::De) => {
+                format!(
+                    "tool '{}' (Profil {}) braucht ein Engagement mit Scope",
+                    tool, profile
+                )
+            }
+            (Message::InvalidPattern { field, error }, Locale::En) => {
+                format!("{} is not a valid regex: {}", field, error)
+            }
+            (Message::InvalidPattern { field, error }, Locale::De) => {
+                format!("{} ist keine gültige Regex: {}", field, error)
+            }
+            (Message::FilterUnsupported { tool }, Locale::En) => {
+                format!(
+                    "tool '{}' produces binary or parsed output, include_pattern and exclude_pattern are not available",
+                    tool
+                )
+            }
+            (Message::FilterUnsupported { tool }, Locale::De) => {
+                format!(
+                    "tool '{}' liefert binäre oder geparste Ausgabe, include_pattern und exclude_pattern sind nicht verfügbar",
+                    tool
+                )
+            }
+            (Message::OutputFlagConflict { tool, format, arg }, Locale::En) => {
+                format!(
+                    "tool '{}' sets its output format itself (machine_output {}), '{}' is not allowed",
+                    tool, format, arg
+                )
+            }
+            (Message::OutputFlagConflict { tool, format, arg }, Locale::De) => {
+                format!(
+                    "tool '{}' setzt das Ausgabeformat selbst (machine_output {}), '{}' ist nicht erlaubt",
+                    tool, format, arg
+                )
+            }
+            (
+                Message::ToolVersionTooOld {
+                    tool,
+                    version,
+                    min_version,
+                },
+                Locale::En,
+            ) => {
+                format!(
+                    "tool '{}' on the Kali host reports version {}, at least {} is required",
+                    tool, version, min_version
+                )
+            }
+            (
+                Message::ToolVersionTooOld {
+                    tool,
+                    version,
+                    min_version,
+                },
+                Locale::De,
+            ) => {
+                format!(
+                    "tool '{}' meldet auf dem Kali-Host Version {}, mindestens {} ist erforderlich",
+                    tool, version, min_version
+                )
+            }
+            (Message::OutOfScope { target, engagement }, Locale::En) => {
+                format!(
+                    "target '{}' is outside the scope of engagement '{}'",
+                    target, engagement
+                )
+            }
+            (Message::OutOfScope { target, engagement }, Locale::De) => {
+                format!(
+                    "Ziel '{}' liegt außerhalb des Scopes von Engagement '{}'",
+                    target, engagement
+                )
+            }
+            (Message::UnrecognizedTarget { target, engagement }, Locale::En) => {
+                format!(
+                    "argument '{}' looks like a target but cannot be checked against the scope of engagement '{}'",
+                    target, engagement
+                )
+            }
+            (Message::UnrecognizedTarget { target, engagement }, Locale::De) => {
+                format!(
+                    "Argument '{}' sieht wie ein Ziel aus, lässt sich aber nicht gegen den Scope von Engagement '{}' prüfen",
+                    target, engagement
+                )
+            }
+            (Message::EngagementClosed { engagement }, Locale::En) => {
+                format!("engagement '{}' is outside its time window", engagement)
+            }
+            (Message::EngagementClosed { engagement }, Locale::De) => {
+                format!(
+                    "Engagement '{}' ist außerhalb seines Zeitfensters",
+                    engagement
+                )
+            }
+            (Message::NoRole { client }, Locale::En) => {
+                format!("client '{}' has no RBAC role", client)
+            }
+            (Message::NoRole { client }, Locale::De) => {
+                format!("Client '{}' hat keine RBAC-Rolle", client)
+            }
+            (Message::ToolForbidden { client, tool }, Locale::En) => {
+                format!("client '{}' may not run tool '{}'", client, tool)
+            }
+            (Message::ToolForbidden { client, tool }, Locale::De) => {
+                format!("Client '{}' darf Tool '{}' nicht ausführen", client, tool)
+            }
+            (Message::HostForbidden { client, tool, host }, Locale::En) => {
+                format!(
+                    "client '{}' may not run tool '{}' on host '{}'",
+                    client, tool, host
+                )
+            }
+            (Message::HostForbidden { client, tool, host }, Locale::De) => {
+                format!(
+                    "Client '{}' darf Tool '{}' nicht auf Host '{}' ausführen",
+                    client, tool, host
+                )
+            }
+            (Message::Paused, Locale::En) => "bridge is paused, new runs are rejected".to_string(),
+            (Message::Paused, Locale::De) => {
+                "Bridge ist pausiert, neue Runs werden abgelehnt".to_string()
+            }
+            (Message::PausedRetry, Locale::En) => {
+                "bridge is paused, further attempts are rejected".to_string()
+            }
+            (Message::PausedRetry, Locale::De) => {
+                "Bridge ist pausiert, weitere Versuche werden abgelehnt".to_string()
+            }
+            (Message::Shutdown, Locale::En) => "request was aborted during shutdown".to_string(),
+            (Message::Shutdown, Locale::De) => {
+                "Request wurde beim Shutdown abgebrochen".to_string()
+            }
+            (Message::Killed, Locale::En) => "request was aborted by the kill switch".to_string(),
+            (Message::Killed, Locale::De) => {
+                "Request wurde per Kill-Switch abgebrochen".to_string()
+            }
+            (Message::RunShutdown { exit_code }, Locale::En) => {
+                format!(
+                    "run was aborted during shutdown (exit_code={:?})",
+                    exit_code
+                )
+            }
+            (Message::RunShutdown { exit_code }, Locale::De) => {
+                format!(
+                    "Run wurde beim Shutdown abgebrochen (exit_code={:?})",
+                    exit_code
+                )
+            }
+            (Message::RunKilled { exit_code }, Locale::En) => {
+                format!(
+                    "run was aborted by the kill switch (exit_code={:?})",
+                    exit_code
+                )
+            }
+            (Message::RunKilled { exit_code }, Locale::De) => {
+                format!(
+                    "Run wurde per Kill-Switch abgebrochen (exit_code={:?})",
+                    exit_code
+                )
+            }
+            (Message::RunCancelled { exit_code }, Locale::En) => {
+                format!(
+                    "run was cancelled by the operator (exit_code={:?})",
+                    exit_code
+                )
+            }
+            (Message::RunCancelled { exit_code }, Locale::De) => {
+                format!(
+                    "Run wurde vom Operator abgebrochen (exit_code={:?})",
+                    exit_code
+                )
+            }
+            (
+                Message::ApprovalDenied {
+                    reason: Some(reason),
+                },
+                Locale::En,
+            ) => format!("run was denied by the operator: {}", reason),
+            (
+                Message::ApprovalDenied {
+                    reason: Some(reason),
+                },
+                Locale::De,
+            ) => format!("Run wurde vom Operator abgelehnt: {}", reason),
+            (Message::ApprovalDenied { reason: None }, Locale::En) => {
+                "run was denied by the operator".to_string()
+            }
+            (Message::ApprovalDenied { reason: None }, Locale::De) => {
+                "Run wurde vom Operator abgelehnt".to_string()
+            }
+            (Message::ApprovalWithdrawn, Locale::En) => "approval was withdrawn".to_string(),
+            (Message::ApprovalWithdrawn, Locale::De) => "Freigabe wurde zurückgezogen".to_string(),
+            (Message::ApprovalTimeout { timeout_sec }, Locale::En) => {
+                format!("no approval within {}s", timeout_sec)
+            }
+            (Message::ApprovalTimeout { timeout_sec }, Locale::De) => {
+                format!("keine Freigabe innerhalb von {}s", timeout_sec)
+            }
+            (Message::HostMissing, Locale::En) => {
+                "host is missing and the session has no default host".to_string()
+            }
+            (Message::HostMissing, Locale::De) => {
+                "host fehlt und die Session hat keinen Default-Host".to_string()
+            }
+            (Message::SshSpawnFailed, Locale::En) => "SSH process could not be started".to_string(),
+            (Message::SshSpawnFailed, Locale::De) => {
+                "SSH-Prozess konnte nicht gestartet werden".to_string()
+            }
+            (Message::SshStatusFailed, Locale::En) => {
+                "status check of the SSH process failed".to_string()
+            }
+            (Message::SshStatusFailed, Locale::De) => {
+                "Statusprüfung des SSH-Prozesses fehlgeschlagen".to_string()
+            }
+            (Message::PipeMissing { stream }, Locale::En) => {
+                format!("{} pipe of the SSH process is missing", stream)
+            }
+            (Message::PipeMissing { stream }, Locale::De) => {
+                format!("{}-Pipe des SSH-Prozesses fehlt", stream)
+            }
+            (Message::OutputTaskFailed { stream }, Locale::En) => {
+                format!("reading {} of the SSH process failed", stream)
+            }
+            (Message::OutputTaskFailed { stream }, Locale::De) => {
+                format!("Lesen von {} des SSH-Prozesses fehlgeschlagen", stream)
+            }
+            (Message::KillFailed { trigger }, Locale::En) => {
+                format!("SSH process could not be killed ({})", trigger)
+            }
+            (Message::KillFailed { trigger }, Locale::De) => {
+                format!("SSH-Prozess konnte nicht beendet werden ({})", trigger)
+            }
+            (Message::OperationTimeout { tool, timeout_sec }, Locale::En) => {
+                format!("{}: timeout after {}s", tool, timeout_sec)
+            }
+            (Message::OperationTimeout { tool, timeout_sec }, Locale::De) => {
+                format!("{}: Timeout nach {}s", tool, timeout_sec)
+            }
+            (Message::UnexpectedResponse, Locale::En) => {
+                "unexpected response from the Kali host".to_string()
+            }
+            (Message::UnexpectedResponse, Locale::De) => {
+                "unerwartete Antwort vom Kali-Host".to_string()
+            }
+            (Message::InvalidArguments { tool, error }, Locale::En) => {
+                format!("invalid arguments for {}: {}", tool, error)
+            }
+            (Message::InvalidArguments { tool, error }, Locale::De) => {
+                format!("ungültige Argumente für {}: {}", tool, error)
+            }
+            (Message::MissingArgument { tool, argument }, Locale::En) => {
+                format!("{} requires {}", tool, argument)
+            }
+            (Message::MissingArgument { tool, argument }, Locale::De) => {
+                format!("{} braucht {}", tool, argument)
+            }
+            (Message::ExactlyOneOf { tool, options }, Locale::En) => {
+                format!("{} needs exactly one of {}", tool, options)
+            }
+            (Message::ExactlyOneOf { tool, options }, Locale::De) => {
+                format!("{} braucht genau eines von {}", tool, options)
+            }
+            (Message::ArgumentNotApplicable { tool, argument }, Locale::En) => {
+                format!("{} does not apply to {}", argument, tool)
+            }
+            (Message::ArgumentNotApplicable { tool, argument }, Locale::De) => {
+                format!("{} gilt nicht für {}", argument, tool)
+            }
+            (Message::InvalidValue { field, value }, Locale::En) => {
+                format!("invalid {} '{}'", field, value.escape_debug())
+            }
+            (Message::InvalidValue { field, value }, Locale::De) => {
+                format!("ungültiger Wert für {}: '{}'", field, value.escape_debug())
+            }
+            (Message::InvalidEncoding { field }, Locale::En) => {
+                format!("{} is not valid base64", field)
+            }
+            (Message::InvalidEncoding { field }, Locale::De) => {
+                format!("{} ist kein gültiges Base64", field)
+            }
+            (Message::PlaceholderIncomplete { arg }, Locale::En) => {
+                format!("incomplete placeholder in '{}'", arg)
+            }
+            (Message::PlaceholderIncomplete { arg }, Locale::De) => {
+                format!("unvollständiger Platzhalter in '{}'", arg)
+            }
+            (Message::UnknownVariable { name }, Locale::En) => {
+                format!("unknown variable '{}'", name)
+            }
+            (Message::UnknownVariable { name }, Locale::De) => {
+                format!("unbekannte Variable '{}'", name)
+            }
+            (Message::LimitExceeded { field, value, max }, Locale::En) => {
+                format!("{} exceeds the limit: {} > {}", field, value, max)
+            }
+            (Message::LimitExceeded { field, value, max }, Locale::De) => {
+                format!("{} überschreitet das Limit: {} > {}", field, value, max)
+            }
+            (Message::TooManyHosts { target, max }, Locale::En) => {
+                format!("'{}' expands to too many hosts (max_hosts={})", target, max)
+            }
+            (Message::TooManyHosts { target, max }, Locale::De) => {
+                format!("'{}' ergibt zu viele Hosts (max_hosts={})", target, max)
+            }
+            (
+                Message::AttemptsExceeded {
+                    attempts,
+                    logins,
+                    passwords,
+                    max,
+                },
+                Locale::En,
+            ) => format!(
+                "{} attempts ({} logins x {} passwords) exceed max_attempts={}",
+                attempts, logins, passwords, max
+            ),
+            (
+                Message::AttemptsExceeded {
+                    attempts,
+                    logins,
+                    passwords,
+                    max,
+                },
+                Locale::De,
+            ) => format!(
+                "{} Versuche ({} Logins x {} Passwörter) überschreiten max_attempts={}",
+                attempts, logins, passwords, max
+            ),
+            (Message::NotReleased { key, name }, Locale::En) => {
+                format!("'{}' is not allowed by {}", name, key)
+            }
+            (Message::NotReleased { key, name }, Locale::De) => {
+                format!("'{}' ist in {} nicht freigegeben", name, key)
+            }
+            (Message::Disabled { tool, setting }, Locale::En) => {
+                format!("{} is disabled ({})", tool, setting)
+            }
+            (Message::Disabled { tool, setting }, Locale::De) => {
+                format!("{} ist deaktiviert ({})", tool, setting)
+            }
+            (Message::NotConfigured { key }, Locale::En) => format!("{} is not configured", key),
+            (Message::NotConfigured { key }, Locale::De) => {
+                format!("{} ist nicht konfiguriert", key)
+            }
+            (Message::PasswordMissing { key, env }, Locale::En) => {
+                format!("{} is missing (config or {})", key, env)
+            }
+            (Message::PasswordMissing { key, env }, Locale::De) => {
+                format!("{} fehlt (Config oder {})", key, env)
+            }
+            (Message::InvalidLabelName { key, max }, Locale::En) => {
+                format!(
+                    "invalid label name '{}' (a-z, 0-9, _, ., -, at most {} characters)",
+                    key, max
+                )
+            }
+            (Message::InvalidLabelName { key, max }, Locale::De) => {
+                format!(
+                    "ungültiger Label-Name '{}' (a-z, 0-9, _, ., -, höchstens {} Zeichen)",
+                    key, max
+                )
+            }
+            (Message::LabelTooLong { key, max }, Locale::En) => {
+                format!("label '{}' is too long (at most {} characters)", key, max)
+            }
+            (Message::LabelTooLong { key, max }, Locale::De) => {
+                format!("Label '{}' ist zu lang (höchstens {} Zeichen)", key, max)
+            }
+            (Message::LabelControlChars { key }, Locale::En) => {
+                format!("label '{}' contains control characters", key)
+            }
+            (Message::LabelControlChars { key }, Locale::De) => {
+                format!("Label '{}' enthält Steuerzeichen", key)
+            }
+            (Message::InvalidLabelPair { pair }, Locale::En) => {
+                format!("invalid label '{}', expected key=value", pair)
+            }
+            (Message::InvalidLabelPair { pair }, Locale::De) => {
+                format!("ungültiges Label '{}', erwartet key=value", pair)
+            }
+            (Message::InvalidCursor, Locale::En) => "invalid cursor".to_string(),
+            (Message::InvalidCursor, Locale::De) => "ungültiger Cursor".to_string(),
+            (Message::PathNotAbsolute { path }, Locale::En) => {
+                format!("path '{}' must be absolute", path)
+            }
+            (Message::PathNotAbsolute { path }, Locale::De) => {
+                format!("Pfad '{}' muss absolut sein", path)
+            }
+            (Message::PathInvalidChars, Locale::En) => {
+                "path contains invalid characters".to_string()
+            }
+            (Message::PathInvalidChars, Locale::De) => {
+                "Pfad enthält unzulässige Zeichen".to_string()
+            }
+            (Message::PathNotNormalized { path }, Locale::En) => {
+                format!("path '{}' is not normalized", path)
+            }
+            (Message::PathNotNormalized { path }, Locale::De) => {
+                format!("Pfad '{}' ist nicht normalisiert", path)
+            }
+            (Message::PathOutsideRoots { tool, path }, Locale::En) => {
+                format!("{}: {} is outside the allowed directories", tool, path)
+            }
+            (Message::PathOutsideRoots { tool, path }, Locale::De) => {
+                format!(
+                    "{}: {} liegt außerhalb der freigegebenen Verzeichnisse",
+                    tool, path
+                )
+            }
+            (Message::NotAFile { tool, path }, Locale::En) => {
+                format!("{}: {} does not name a file", tool, path)
+            }
+            (Message::NotAFile { tool, path }, Locale::De) => {
+                format!("{}: {} nennt keine Datei", tool, path)
+            }
+            (Message::RemoteDenied { tool, path }, Locale::En) => {
+                format!(
+                    "{}: {} resolves outside the allowed directories or is a symlink",
+                    tool, path
+                )
+            }
+            (Message::RemoteDenied { tool, path }, Locale::De) => {
+                format!(
+                    "{}: {} zeigt außerhalb der Freigabe oder ist ein Symlink",
+                    tool, path
+                )
+            }
+            (Message::RemoteNotFound { tool, path }, Locale::En) => {
+                format!("{}: {} not found", tool, path)
+            }
+            (Message::RemoteNotFound { tool, path }, Locale::De) => {
+                format!("{}: {} nicht gefunden", tool, path)
+            }
+            (Message::RemoteExists { tool, path }, Locale::En) => {
+                format!(
+                    "{}: {} already exists (set overwrite or append)",
+                    tool, path
+                )
+            }
+            (Message::RemoteExists { tool, path }, Locale::De) => {
+                format!(
+                    "{}: {} existiert bereits (overwrite oder append setzen)",
+                    tool, path
+                )
+            }
+            (Message::RemoteSshFailed { tool, detail }, Locale::En) => {
+                format!("{}: SSH connection failed: {}", tool, detail)
+            }
+            (Message::RemoteSshFailed { tool, detail }, Locale::De) => {
+                format!("{}: SSH-Verbindung fehlgeschlagen: {}", tool, detail)
+            }
+            (Message::MasterConnectionFailed { target, exit_code }, Locale::En) => {
+                format!(
+                    "master connection to {} could not be established (exit_code={:?})",
+                    target, exit_code
+                )
+            }
+            (Message::MasterConnectionFailed { target, exit_code }, Locale::De) => {
+                format!(
+                    "Master-Verbindung zu {} konnte nicht aufgebaut werden (exit_code={:?})",
+                    target, exit_code
+                )
+            }
+            (Message::MasterConnectionTimeout { target }, Locale::En) => {
+                format!("master connection to {}: timeout", target)
+            }
+            (Message::MasterConnectionTimeout { target }, Locale::De) => {
+                format!("Master-Verbindung zu {}: Zeitüberschreitung", target)
+            }
+            (
+                Message::RemoteFailed {
+                    tool,
+                    exit_code,
+                    detail,
+                },
+                Locale::En,
+            ) => {
+                format!("{} failed (exit_code={:?}): {}", tool, exit_code, detail)
+            }
+            (
+                Message::RemoteFailed {
+                    tool,
+                    exit_code,
+                    detail,
+                },
+                Locale::De,
+            ) => {
+                format!(
+                    "{} fehlgeschlagen (exit_code={:?}): {}",
+                    tool, exit_code, detail
+                )
+            }
+            (
+                Message::PingSweepFailed {
+                    tool,
+                    exit_code,
+                    timed_out,
+                    detail,
+                },
+                Locale::En,
+            ) => format!(
+                "ping sweep with '{}' failed (exit_code={:?}, timed_out={}): {}",
+                tool, exit_code, timed_out, detail
+            ),
+            (
+                Message::PingSweepFailed {
+                    tool,
+                    exit_code,
+                    timed_out,
+                    detail,
+                },
+                Locale::De,
+            ) => format!(
+                "Ping-Sweep mit '{}' fehlgeschlagen (exit_code={:?}, timed_out={}): {}",
+                tool, exit_code, timed_out, detail
+            ),
+            (Message::PingSweepTruncated { tool }, Locale::En) => {
+                format!(
+                    "output of the ping sweep was truncated, increase max_output_bytes for '{}'",
+                    tool
+                )
+            }
+            (Message::PingSweepTruncated { tool }, Locale::De) => {
+                format!(
+                    "Ausgabe des Ping-Sweeps wurde abgeschnitten, max_output_bytes für '{}' erhöhen",
+                    tool
+                )
+            }
+            (Message::WordlistMissing { name, path }, Locale::En) => {
+                format!("wordlist '{}' ({}) not found", name, path)
+            }
+            (Message::WordlistMissing { name, path }, Locale::De) => {
+                format!("Wordlist '{}' ({}) nicht gefunden", name, path)
+            }
+            (Message::CrackJobMissing { job_id }, Locale::En) => {
+                format!("cracking job '{}' not found", job_id)
+            }
+            (Message::CrackJobMissing { job_id }, Locale::De) => {
+                format!("Cracking-Job '{}' nicht gefunden", job_id)
+            }
+            (Message::CrackBusy { max_jobs }, Locale::En) => {
+                format!("{} cracking jobs are already running", max_jobs)
+            }
+            (Message::CrackBusy { max_jobs }, Locale::De) => {
+                format!("bereits {} Cracking-Jobs aktiv", max_jobs)
+            }
+            (Message::OutputNotStored { run_id }, Locale::En) => {
+                format!("no output is stored (any more) for run '{}'", run_id)
+            }
+            (Message::OutputNotStored { run_id }, Locale::De) => {
+                format!("für Run '{}' ist keine Ausgabe (mehr) gespeichert", run_id)
+            }
+            (Message::TargetListMissing { name }, Locale::En) => {
+                format!("target list '{}' does not exist", name)
+            }
+            (Message::TargetListMissing { name }, Locale::De) => {
+                format!("Target-Liste '{}' existiert nicht", name)
+            }
+            (
+                Message::TargetListForeign {
+                    name,
+                    owner,
+                    engagement,
+                },
+                Locale::En,
+            ) => format!(
+                "target list '{}' belongs to engagement '{}', the run to '{}'",
+                name, owner, engagement
+            ),
+            (
+                Message::TargetListForeign {
+                    name,
+                    owner,
+                    engagement,
+                },
+                Locale::De,
+            ) => format!(
+                "Target-Liste '{}' gehört zu Engagement '{}', der Run zu '{}'",
+                name, owner, engagement
+            ),
+            (Message::EngagementMissing { name }, Locale::En) => {
+                format!("engagement '{}' does not exist", name)
+            }
+            (Message::EngagementMissing { name }, Locale::De) => {
+                format!("Engagement '{}' existiert nicht", name)
+            }
+            (Message::EngagementExists { name }, Locale::En) => {
+                format!("engagement '{}' already exists", name)
+            }
+            (Message::EngagementExists { name }, Locale::De) => {
+                format!("Engagement '{}' existiert bereits", name)
+            }
+            (Message::EngagementRequired, Locale::En) => {
+                "no engagement given and none is active (engagements.require=true)".to_string()
+            }
+            (Message::EngagementRequired, Locale::De) => {
+                "kein Engagement angegeben und keines aktiv (engagements.require=true)".to_string()
+            }
+            (Message::RunUnknown { id }, Locale::En) => {
+                format!("no run with id or run_token '{}'", id)
+            }
+            (Message::RunUnknown { id }, Locale::De) => {
+                format!("kein Run mit id oder run_token '{}'", id)
+            }
+            (Message::RunExists { id }, Locale::En) => format!("run '{}' already exists", id),
+            (Message::RunExists { id }, Locale::De) => format!("Run '{}' existiert bereits", id),
+            (Message::InvalidRunId { id }, Locale::En) => {
+                format!("invalid run id '{}'", id.escape_debug())
+            }
+            (Message::InvalidRunId { id }, Locale::De) => {
+                format!("ungültige Run-ID '{}'", id.escape_debug())
+            }
+            (Message::TooManyRuns { max }, Locale::En) => {
+                format!("too many running REST runs (max_runs={})", max)
+            }
+            (Message::TooManyRuns { max }, Locale::De) => {
+                format!("zu viele laufende REST-Runs (max_runs={})", max)
+            }
+            (Message::GvmTaskMissing { task_id }, Locale::En) => {
+                format!("GVM task '{}' not found", task_id)
+            }
+            (Message::GvmTaskMissing { task_id }, Locale::De) => {
+                format!("GVM-Task '{}' nicht gefunden", task_id)
+            }
+            (Message::QueueFull { max }, Locale::En) => {
+                format!("scheduler queue is full ({} waiting requests)", max)
+            }
+            (Message::QueueFull { max }, Locale::De) => {
+                format!("scheduler-queue voll ({} wartende Requests)", max)
+            }
+            (Message::QueueTimeout { timeout_sec }, Locale::En) => {
+                format!(
+                    "waiting time in the scheduler queue exceeded ({}s)",
+                    timeout_sec
+                )
+            }
+            (Message::QueueTimeout { timeout_sec }, Locale::De) => {
+                format!(
+                    "Wartezeit in der scheduler-queue überschritten ({}s)",
+                    timeout_sec
+                )
+            }
+            (Message::InFlightAborted, Locale::En) => {
+                "the running identical request was aborted".to_string()
+            }
+            (Message::InFlightAborted, Locale::De) => {
+                "laufender identischer Request wurde abgebrochen".to_string()
+            }
+            (Message::SecretNotConfigured { name }, Locale::En) => {
+                format!("secret '{}' is not configured", name)
+            }
+            (Message::SecretNotConfigured { name }, Locale::De) => {
+                format!("Secret '{}' ist nicht konfiguriert", name)
+            }
+            (Message::SecretEnvMissing { name, var }, Locale::En) => {
+                format!("secret '{}': environment variable {} is missing", name, var)
+            }
+            (Message::SecretEnvMissing { name, var }, Locale::De) => {
+                format!("Secret '{}': Umgebungsvariable {} fehlt", name, var)
+            }
+            (Message::SecretFileEntryMissing { name, key }, Locale::En) => {
+                format!(
+                    "secret '{}': entry '{}' is missing in the secrets file",
+                    name, key
+                )
+            }
+            (Message::SecretFileEntryMissing { name, key }, Locale::De) => {
+                format!(
+                    "Secret '{}': Eintrag '{}' fehlt in der Secrets-Datei",
+                    name, key
+                )
+            }
+            (Message::SecretEmpty { name }, Locale::En) => format!("secret '{}' is empty", name),
+            (Message::SecretEmpty { name }, Locale::De) => format!("Secret '{}' ist leer", name),
+            (Message::SecretCommandFailed { name }, Locale::En) => {
+                format!("secret '{}': command could not be started", name)
+            }
+            (Message::SecretCommandFailed { name }, Locale::De) => {
+                format!("Secret '{}': command konnte nicht gestartet werden", name)
+            }
+            (Message::SecretCommandExit { name, status }, Locale::En) => {
+                format!("secret '{}': command ended with {}", name, status)
+            }
+            (Message::SecretCommandExit { name, status }, Locale::De) => {
+                format!("Secret '{}': command endete mit {}", name, status)
+            }
+            (Message::SecretCommandTimeout { name, timeout_sec }, Locale::En) => {
+                format!(
+                    "secret '{}': command exceeded the timeout of {}s",
+                    name, timeout_sec
+                )
+            }
+            (Message::SecretCommandTimeout { name, timeout_sec }, Locale::De) => {
+                format!(
+                    "Secret '{}': command hat das Timeout von {}s überschritten",
+                    name, timeout_sec
+                )
+            }
+            (Message::SecretNotUtf8 { name }, Locale::En) => {
+                format!("secret '{}': output is not UTF-8", name)
+            }
+            (Message::SecretNotUtf8 { name }, Locale::De) => {
+                format!("Secret '{}': Ausgabe ist kein UTF-8", name)
+            }
+            (Message::SecretFileNotConfigured, Locale::En) => {
+                "no secrets file configured (secrets.file.path and secrets.file.key_path)"
+                    .to_string()
+            }
+            (Message::SecretFileNotConfigured, Locale::De) => {
+                "keine Secrets-Datei konfiguriert (secrets.file.path und secrets.file.key_path)"
+                    .to_string()
+            }
+            (Message::SecretFileUnreadable { path }, Locale::En) => {
+                format!("secrets file {} could not be read", path)
+            }
+            (Message::SecretFileUnreadable { path }, Locale::De) => {
+                format!("Secrets-Datei {} konnte nicht gelesen werden", path)
+            }
+            (Message::SecretFileDecryptFailed { path }, Locale::En) => {
+                format!("secrets file {} could not be decrypted (wrong key?)", path)
+            }
+            (Message::SecretFileDecryptFailed { path }, Locale::De) => {
+                format!(
+                    "Secrets-Datei {} konnte nicht entschlüsselt werden (falscher Schlüssel?)",
+                    path
+                )
+            }
+            (Message::SecretKeyInvalid { path }, Locale::En) => {
+                format!("secrets key {} is not 32 bytes of base64", path)
+            }
+            (Message::SecretKeyInvalid { path }, Locale::De) => {
+                format!("Secrets-Schlüssel {} ist kein base64 mit 32 Bytes", path)
+            }
+            (Message::SecretFileWriteFailed { path }, Locale::En) => {
+                format!("secrets file {} could not be written", path)
+            }
+            (Message::SecretFileWriteFailed { path }, Locale::De) => {
+                format!("Secrets-Datei {} konnte nicht geschrieben werden", path)
+            }
+            (Message::SecretValueMissing, Locale::En) => {
+                "empty secret value (pass the value on stdin)".to_string()
+            }
+            (Message::SecretValueMissing, Locale::De) => {
+                "leerer Secret-Wert (Wert über stdin übergeben)".to_string()
+            }
+            (Message::SecretNotStored { name }, Locale::En) => {
+                format!("secret '{}' is not in the secrets file", name)
+            }
+            (Message::SecretNotStored { name }, Locale::De) => {
+                format!("Secret '{}' ist nicht in der Secrets-Datei", name)
+            }
+            (Message::KeyFileWriteFailed { path }, Locale::En) => {
+                format!("key file {} could not be written", path)
+            }
+            (Message::KeyFileWriteFailed { path }, Locale::De) => {
+                format!("Schlüsseldatei {} konnte nicht geschrieben werden", path)
+            }
+            (Message::RandomFailed, Locale::En) => "random data could not be generated".to_string(),
+            (Message::RandomFailed, Locale::De) => {
+                "Zufallsdaten konnten nicht erzeugt werden".to_string()
+            }
+            (Message::StateFileUnreadable { kind, path }, Locale::En) => {
+                format!("{} file {} could not be read", kind, path)
+            }
+            (Message::StateFileUnreadable { kind, path }, Locale::De) => {
+                format!("{}-Datei {} konnte nicht gelesen werden", kind, path)
+            }
+            (Message::StateFileInvalid { kind, path }, Locale::En) => {
+                format!("{} file {} could not be parsed", kind, path)
+            }
+            (Message::StateFileInvalid { kind, path }, Locale::De) => {
+                format!("{}-Datei {} konnte nicht geparst werden", kind, path)
+            }
+            (Message::StateFileWriteFailed { kind, path }, Locale::En) => {
+                format!("{} file {} could not be written", kind, path)
+            }
+            (Message::StateFileWriteFailed { kind, path }, Locale::De) => {
+                format!("{}-Datei {} konnte nicht geschrieben werden", kind, path)
+            }
+            (Message::SessionLimit { max }, Locale::En) => {
+                format!("maximum number of MCP sessions ({}) reached", max)
+            }
+            (Message::SessionLimit { max }, Locale::De) => {
+                format!("maximale Anzahl MCP-Sessions ({}) erreicht", max)
+            }
+            (Message::SessionClientLimit { client, count }, Locale::En) => {
+                format!(
+                    "client '{}' already has {} open MCP sessions",
+                    client, count
+                )
+            }
+            (Message::SessionClientLimit { client, count }, Locale::De) => {
+                format!(
+                    "Client '{}' hat bereits {} offene MCP-Sessions",
+                    client, count
+                )
+            }
+            (Message::SessionUnknown { id }, Locale::En) => {
+                format!("unknown or expired MCP session '{}'", id)
+            }
+            (Message::SessionUnknown { id }, Locale::De) => {
+                format!("unbekannte oder abgelaufene MCP-Session '{}'", id)
+            }
+            (Message::SessionRequired, Locale::En) => {
+                "Mcp-Session-Id is missing, send initialize first".to_string()
+            }
+            (Message::SessionRequired, Locale::De) => {
+                "Mcp-Session-Id fehlt, zuerst initialize senden".to_string()
+            }
+            (Message::SessionOnly { tool }, Locale::En) => {
+                format!("{} is only available inside an MCP session over HTTP", tool)
+            }
+            (Message::SessionOnly { tool }, Locale::De) => format!(
+                "{} ist nur innerhalb einer MCP-Session über HTTP verfügbar",
+                tool
+            ),
+            (Message::SessionRateLimited { limit }, Locale::En) => {
+                format!(
+                    "rate limit of the MCP session exceeded ({} runs per minute)",
+                    limit
+                )
+            }
+            (Message::SessionRateLimited { limit }, Locale::De) => {
+                format!(
+                    "Rate-Limit der MCP-Session überschritten ({} Runs pro Minute)",
+                    limit
+                )
+            }
+            (Message::TerminalLimit { max }, Locale::En) => {
+                format!("maximum number of interactive sessions ({}) reached", max)
+            }
+            (Message::TerminalLimit { max }, Locale::De) => {
+                format!("maximale Anzahl interaktiver Sessions ({}) erreicht", max)
+            }
+            (Message::TerminalUnknown { id }, Locale::En) => {
+                format!("unknown or expired interactive session '{}'", id)
+            }
+            (Message::TerminalUnknown { id }, Locale::De) => {
+                format!("unbekannte oder abgelaufene interaktive Session '{}'", id)
+            }
+            (Message::TerminalClosed { id }, Locale::En) => {
+                format!("interactive session '{}' has already ended", id)
+            }
+            (Message::TerminalClosed { id }, Locale::De) => {
+                format!("interaktive Session '{}' ist bereits beendet", id)
+            }
+            (Message::TerminalInputFailed, Locale::En) => {
+                "input could not be passed to the session".to_string()
+            }
+            (Message::TerminalInputFailed, Locale::De) => {
+                "Eingabe konnte nicht an die Session übergeben werden".to_string()
+            }
+            (Message::BackendUnreachable { service, address }, Locale::En) => {
+                format!("{} {} is not reachable", service, address)
+            }
+            (Message::BackendUnreachable { service, address }, Locale::De) => {
+                format!("{} {} nicht erreichbar", service, address)
+            }
+            (
+                Message::BackendFailed {
+                    service,
+                    operation,
+                    detail,
+                },
+                Locale::En,
+            ) => {
+                format!("{} {} failed: {}", service, operation, detail)
+            }
+            (
+                Message::BackendFailed {
+                    service,
+                    operation,
+                    detail,
+                },
+                Locale::De,
+            ) => {
+                format!("{} {} fehlgeschlagen: {}", service, operation, detail)
+            }
+            (Message::BackendClosed { service, operation }, Locale::En) => {
+                format!(
+                    "{} {}: connection closed before the end of the response",
+                    service, operation
+                )
+            }
+            (Message::BackendClosed { service, operation }, Locale::De) => {
+                format!(
+                    "{} {}: Verbindung wurde vor Ende der Antwort geschlossen",
+                    service, operation
+                )
+            }
+            (
+                Message::BackendResponseTooLarge {
+                    service,
+                    operation,
+                    max,
+                },
+                Locale::En,
+            ) => {
+                format!(
+                    "{} {}: response larger than {} bytes",
+                    service, operation, max
+                )
+            }
+            (
+                Message::BackendResponseTooLarge {
+                    service,
+                    operation,
+                    max,
+                },
+                Locale::De,
+            ) => {
+                format!(
+                    "{} {}: Antwort größer als {} Bytes",
+                    service, operation, max
+                )
+            }
+            (Message::BackendAuthRejected { service, operation }, Locale::En) => {
+                format!("{} {}: login is rejected", service, operation)
+            }
+            (Message::BackendAuthRejected { service, operation }, Locale::De) => {
+                format!("{} {}: Anmeldung wird abgelehnt", service, operation)
+            }
+            (Message::BackendUnexpected { service, operation }, Locale::En) => {
+                format!("{} {}: unexpected response", service, operation)
+            }
+            (Message::BackendUnexpected { service, operation }, Locale::De) => {
+                format!("{} {}: unerwartete Antwort", service, operation)
+            }
+            (Message::ScriptFailed { stage, error }, Locale::En) => {
+                format!("{} failed: {}", stage, error)
+            }
+            (Message::ScriptFailed { stage, error }, Locale::De) => {
+                format!("{} fehlgeschlagen: {}", stage, error)
+            }
+            (Message::StepScriptInvalid { index, field }, Locale::En) => {
+                format!("steps[{}].{} is invalid", index, field)
+            }
+            (Message::StepScriptInvalid { index, field }, Locale::De) => {
+                format!("steps[{}].{} ist ungültig", index, field)
+            }
+            (Message::ForEachWithoutTarget { index, placeholder }, Locale::En) => {
+                format!(
+                    "workflow step {} uses for_each, but neither args nor params contain {}",
+                    index, placeholder
+                )
+            }
+            (Message::ForEachWithoutTarget { index, placeholder }, Locale::De) => {
+                format!(
+                    "Workflow-Schritt {} nutzt for_each, aber weder args noch params enthalten {}",
+                    index, placeholder
+                )
+            }
+            (Message::WorkflowFailed, Locale::En) => "workflow failed".to_string(),
+            (Message::WorkflowFailed, Locale::De) => "Workflow fehlgeschlagen".to_string(),
+            (Message::MachineOutputMissing { format }, Locale::En) => {
+                format!("no {} output found", format)
+            }
+            (Message::MachineOutputMissing { format }, Locale::De) => {
+                format!("keine {}-Ausgabe gefunden", format)
+            }
+            (Message::MachineOutputIncomplete { format }, Locale::En) => {
+                format!("{} output is incomplete", format)
+            }
+            (Message::MachineOutputIncomplete { format }, Locale::De) => {
+                format!("{}-Ausgabe ist unvollständig", format)
+            }
+            (Message::MachineOutputInvalid { format }, Locale::En) => {
+                format!("{} output is invalid", format)
+            }
+            (Message::MachineOutputInvalid { format }, Locale::De) => {
+                format!("{}-Ausgabe ist ungültig", format)
+            }
+            (Message::HttpReadTimeout, Locale::En) => {
+                "timeout while reading the HTTP request".to_string()
+            }
+            (Message::HttpReadTimeout, Locale::De) => {
+                "Timeout beim Lesen des HTTP-Requests".to_string()
+            }
+            (Message::HttpHeaderTooLarge { max }, Locale::En) => {
+                format!("HTTP headers longer than {} bytes", max)
+            }
+            (Message::HttpHeaderTooLarge { max }, Locale::De) => {
+                format!("HTTP-Header länger als {} Bytes", max)
+            }
+            (Message::HttpIncomplete, Locale::En) => {
+                "connection closed before the end of the HTTP request".to_string()
+            }
+            (Message::HttpIncomplete, Locale::De) => {
+                "Verbindung vor Ende des HTTP-Requests geschlossen".to_string()
+            }
+            (Message::HttpMalformed { part }, Locale::En) => {
+                format!("HTTP request has a missing or invalid {}", part)
+            }
+            (Message::HttpMalformed { part }, Locale::De) => {
+                format!("HTTP-Request hat fehlende oder ungültige {}", part)
+            }
+            (Message::HttpChunked, Locale::En) => {
+                "Transfer-Encoding in the request is not supported".to_string()
+            }
+            (Message::HttpChunked, Locale::De) => {
+                "Transfer-Encoding im Request wird nicht unterstützt".to_string()
+            }
+            (Message::HttpBodyTooLarge { max }, Locale::En) => {
+                format!("HTTP body larger than {} bytes", max)
+            }
+            (Message::HttpBodyTooLarge { max }, Locale::De) => {
+                format!("HTTP-Body größer als {} Bytes", max)
+            }
+            (Message::AuthRequired, Locale::En) => "authentication required".to_string(),
+            (Message::AuthRequired, Locale::De) => "Authentifizierung erforderlich".to_string(),
+            (Message::MethodNotAllowed { allowed }, Locale::En) => {
+                format!("only {} allowed", allowed)
+            }
+            (Message::MethodNotAllowed { allowed }, Locale::De) => {
+                format!("nur {} erlaubt", allowed)
+            }
+            (Message::UnknownPath, Locale::En) => "unknown path".to_string(),
+            (Message::UnknownPath, Locale::De) => "unbekannter Pfad".to_string(),
+            (Message::WebSocketExpected, Locale::En) => "WebSocket upgrade expected".to_string(),
+            (Message::WebSocketExpected, Locale::De) => "WebSocket-Upgrade erwartet".to_string(),
+            (Message::WebSocketVersion, Locale::En) => {
+                "only WebSocket version 13 is supported".to_string()
+            }
+            (Message::WebSocketVersion, Locale::De) => {
+                "nur WebSocket-Version 13 wird unterstützt".to_string()
+            }
+            (Message::WebSocketKeyMissing, Locale::En) => {
+                "Sec-WebSocket-Key is missing".to_string()
+            }
+            (Message::WebSocketKeyMissing, Locale::De) => "Sec-WebSocket-Key fehlt".to_string(),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Message::RunCancelled { .. })
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text(locale()))
+    }
+}
+
+impl std::error::Error for Message {}
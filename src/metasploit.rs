@@ -0,0 +1,636 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::findings::{Finding, Severity};
+use crate::messages::Message;
+use crate::secrets::{self, SecretsConfig};
+
+const MODULE_TYPES: &[&str] = &[
+    "exploit",
+    "auxiliary",
+    "post",
+    "payload",
+    "encoder",
+    "nop",
+    "evasion",
+];
+const TARGET_OPTIONS: &[&str] = &["RHOSTS", "RHOST"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetasploitConfig {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default = "default_username")]
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub insecure_tls: bool,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default)]
+    pub modules: Vec<String>,
+}
+
+fn default_username() -> String {
+    "msf".to_string()
+}
+
+fn default_timeout_sec() -> u64 {
+    30
+}
+
+impl Default for MetasploitConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            username: default_username(),
+            password: None,
+            insecure_tls: false,
+            timeout_sec: default_timeout_sec(),
+            modules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum MsfAction {
+    Search {
+        query: String,
+    },
+    Info {
+        module: String,
+    },
+    Execute {
+        module: String,
+        #[serde(default)]
+        options: BTreeMap<String, Value>,
+    },
+    Status {
+        uuid: Option<String>,
+    },
+    Vulns {
+        workspace: Option<String>,
+    },
+    Stop {
+        job_id: Value,
+    },
+}
+
+pub struct MsfCall {
+    pub tool: String,
+    pub module: Option<String>,
+    pub targets: Vec<String>,
+    action: MsfAction,
+}
+
+pub struct Metasploit {
+    client: reqwest::Client,
+    url: Url,
+    config: MetasploitConfig,
+    token: Mutex<Option<String>>,
+}
+
+impl Metasploit {
+    pub fn open(config: &MetasploitConfig) -> Result<Option<Self>> {
+        let Some(url) = &config.url else {
+            return Ok(None);
+        };
+        let url = Url::parse(url).with_context(|| format!("ungültige metasploit.url '{}'", url))?;
+        if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+            bail!(
+                "metasploit.url '{}' muss eine http(s)-URL mit Host sein",
+                url
+            );
+        }
+        for module in &config.modules {
+            let pattern = module.strip_suffix('*').unwrap_or(module);
+            if !MODULE_TYPES
+                .iter()
+                .any(|kind| pattern.starts_with(&format!("{}/", kind)) || pattern == *kind)
+            {
+                bail!("metasploit.modules: ungültiges Modulmuster '{}'", module);
+            }
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_sec))
+            .danger_accept_invalid_certs(config.insecure_tls)
+            .build()
+            .context("HTTP-Client für Metasploit-RPC konnte nicht erstellt werden")?;
+        Ok(Some(Self {
+            client,
+            url,
+            config: config.clone(),
+            token: Mutex::new(None),
+        }))
+    }
+
+    pub fn host(&self) -> &str {
+        self.url.host_str().unwrap_or_default()
+    }
+
+    pub fn mcp_tools(&self) -> Vec<Value> {
+        let mut tools = vec![
+            json!({
+                "name": "msf.search",
+                "description": "Searches Metasploit modules via msfrpcd (e.g. 'type:auxiliary smb')",
+                "inputSchema": {"type": "object", "required": ["query"], "properties": {"query": {"type": "string"}}}
+            }),
+            json!({
+                "name": "msf.info",
+                "description": "Shows description and options of a Metasploit module, e.g. auxiliary/scanner/smb/smb_version",
+                "inputSchema": {"type": "object", "required": ["module"], "properties": {"module": {"type": "string"}}}
+            }),
+            json!({
+                "name": "msf.status",
+                "description": "Lists running Metasploit jobs and open sessions as findings; with uuid also the result of an executed module",
+                "inputSchema": {"type": "object", "properties": {"uuid": {"type": "string"}}}
+            }),
+            json!({
+                "name": "msf.vulns",
+                "description": "Returns vulnerabilities recorded in the Metasploit database as findings",
+                "inputSchema": {"type": "object", "properties": {"workspace": {"type": "string"}}}
+            }),
+        ];
+        if !self.config.modules.is_empty() {
+            tools.push(json!({
+                "name": "msf.execute",
+                "description": format!(
+                    "Runs an allowed Metasploit module as background job with datastore options (allowed: {})",
+                    self.config.modules.join(", ")
+                ),
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["module"],
+                    "properties": {
+                        "module": {"type": "string"},
+                        "options": {"type": "object", "additionalProperties": {"type": ["string", "integer", "boolean"]}}
+                    }
+                }
+            }));
+            tools.push(json!({
+                "name": "msf.stop",
+                "description": "Stops a Metasploit job",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["job_id"],
+                    "properties": {"job_id": {"type": ["string", "integer"]}}
+                }
+            }));
+        }
+        tools
+    }
+
+    pub fn prepare(&self, action: &str, arguments: Value) -> Result<MsfCall> {
+        let mut arguments = match arguments {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        arguments.insert("action".to_string(), Value::String(action.to_string()));
+        let tool = format!("msf.{}", action);
+        let action =
+            serde_json::from_value::<MsfAction>(Value::Object(arguments)).map_err(|error| {
+                Message::InvalidArguments {
+                    tool,
+                    error: error.to_string(),
+                }
+            })?;
+        let tool = format!("msf.{}", action_name(&action));
+        let (module, targets) = match &action {
+            MsfAction::Info { module } => {
+                split_module(module)?;
+                (Some(module.clone()), Vec::new())
+            }
+            MsfAction::Execute { module, options } => {
+                split_module(module)?;
+                if self.config.modules.is_empty() {
+                    bail!(Message::Disabled {
+                        tool: "msf.execute".to_string(),
+                        setting: "metasploit.modules=[]"
+                    });
+                }
+                if !self
+                    .config
+                    .modules
+                    .iter()
+                    .any(|pattern| matches(pattern, module))
+                {
+                    bail!(Message::NotReleased {
+                        key: "metasploit.modules".to_string(),
+                        name: module.clone()
+                    });
+                }
+                let mut targets = Vec::new();
+                for (name, value) in options {
+                    if !matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_)) {
+                        bail!(Message::InvalidValue {
+                            field: "option",
+                            value: name.clone()
+                        });
+                    }
+                    if TARGET_OPTIONS.contains(&name.to_ascii_uppercase().as_str()) {
+                        targets.extend(option_text(value).split_whitespace().map(str::to_string));
+                    }
+                }
+                (Some(module.clone()), targets)
+            }
+            MsfAction::Stop { job_id }
+                if !matches!(job_id, Value::String(_) | Value::Number(_)) =>
+            {
+                bail!(Message::InvalidValue {
+                    field: "job_id",
+                    value: job_id.to_string()
+                })
+            }
+            _ => (None, Vec::new()),
+        };
+        Ok(MsfCall {
+            tool,
+            module,
+            targets,
+            action,
+        })
+    }
+
+    pub async fn execute(
+        &self,
+        secrets: &SecretsConfig,
+        call: MsfCall,
+    ) -> Result<(Value, Vec<Finding>)> {
+        match call.action {
+            MsfAction::Search { query } => {
+                let modules = self
+                    .call(secrets, "module.search", vec![json!(query)])
+                    .await?;
+                Ok((json!({"modules": modules}), Vec::new()))
+            }
+            MsfAction::Info { module } => {
+                let (kind, name) = split_module(&module)?;
+                let info = self
+                    .call(secrets, "module.info", vec![json!(kind), json!(name)])
+                    .await?;
+                let options = self
+                    .call(secrets, "module.options", vec![json!(kind), json!(name)])
+                    .await?;
+                Ok((
+                    json!({"module": module, "info": info, "options": options}),
+                    Vec::new(),
+                ))
+            }
+            MsfAction::Execute { module, options } => {
+                let (kind, name) = split_module(&module)?;
+                let options = options
+                    .iter()
+                    .map(|(name, value)| (name.clone(), json!(option_text(value))))
+                    .collect::<serde_json::Map<_, _>>();
+                let started = self
+                    .call(
+                        secrets,
+                        "module.execute",
+                        vec![json!(kind), json!(name), Value::Object(options)],
+                    )
+                    .await?;
+                Ok((
+                    json!({"module": module, "job_id": started["job_id"], "uuid": started["uuid"]}),
+                    Vec::new(),
+                ))
+            }
+            MsfAction::Status { uuid } => {
+                let jobs = self.call(secrets, "job.list", Vec::new()).await?;
+                let sessions = self.call(secrets, "session.list", Vec::new()).await?;
+                let mut findings = session_findings(&sessions);
+                let mut status = json!({"jobs": jobs, "sessions": sessions});
+                if let Some(uuid) = uuid {
+                    let result = self
+                        .call(secrets, "module.results", vec![json!(uuid)])
+                        .await?;
+                    findings.extend(result_finding(&uuid, &result));
+                    status["result"] = result;
+                }
+                Ok((status, findings))
+            }
+            MsfAction::Vulns { workspace } => {
+                let mut filter = serde_json::Map::new();
+                if let Some(workspace) = workspace {
+                    filter.insert("workspace".to_string(), json!(workspace));
+                }
+                let vulns = self
+                    .call(secrets, "db.vulns", vec![Value::Object(filter)])
+                    .await?;
+                let findings = vuln_findings(&vulns["vulns"]);
+                Ok((json!({"count": findings.len()}), findings))
+            }
+            MsfAction::Stop { job_id } => {
+                let stopped = self
+                    .call(secrets, "job.stop", vec![json!(option_text(&job_id))])
+                    .await?;
+                Ok((
+                    json!({"job_id": job_id, "result": stopped["result"]}),
+                    Vec::new(),
+                ))
+            }
+        }
+    }
+
+    async fn call(&self, secrets: &SecretsConfig, method: &str, args: Vec<Value>) -> Result<Value> {
+        for attempt in 0..2 {
+            let cached = self
+                .token
+                .lock()
+                .expect("metasploit token poisoned")
+                .clone();
+            let token = match cached {
+                Some(token) => token,
+                None => self.login(secrets).await?,
+            };
+            let mut request = vec![json!(method), json!(token)];
+            request.extend(args.iter().cloned());
+            let (status, response) = self.post(&request).await?;
+            if status == StatusCode::UNAUTHORIZED && attempt == 0 {
+                *self.token.lock().expect("metasploit token poisoned") = None;
+                continue;
+            }
+            return check_response(method, status, response);
+        }
+        Err(Message::BackendAuthRejected {
+            service: "Metasploit-RPC",
+            operation: method.to_string(),
+        }
+        .into())
+    }
+
+    async fn login(&self, secrets: &SecretsConfig) -> Result<String> {
+        let password = self
+            .config
+            .password
+            .clone()
+            .or_else(|| std::env::var("MSFRPC_PASSWORD").ok())
+            .ok_or(Message::PasswordMissing {
+                key: "metasploit.password",
+                env: "MSFRPC_PASSWORD",
+            })?;
+        let values = secrets::resolve(secrets, std::iter::once(&password)).await?;
+        let request = [
+            json!("auth.login"),
+            json!(self.config.username),
+            json!(values.substitute(&password)),
+        ];
+        let (status, response) = self.post(&request).await?;
+        let response = check_response("auth.login", status, response)?;
+        let token = response["token"]
+            .as_str()
+            .ok_or_else(|| Message::BackendUnexpected {
+                service: "Metasploit-RPC",
+                operation: "auth.login".to_string(),
+            })?
+            .to_string();
+        *self.token.lock().expect("metasploit token poisoned") = Some(token.clone());
+        Ok(token)
+    }
+
+    async fn post(&self, request: &[Value]) -> Result<(StatusCode, Value)> {
+        let mut body = Vec::new();
+        rmpv::encode::write_value(&mut body, &to_msgpack(&Value::Array(request.to_vec())))
+            .with_context(|| Message::BackendFailed {
+                service: "Metasploit-RPC",
+                operation: request
+                    .first()
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                detail: "encode".to_string(),
+            })?;
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "binary/message-pack")
+            .body(body)
+            .send()
+            .await
+            .with_context(|| Message::BackendUnreachable {
+                service: "Metasploit-RPC",
+                address: self.url.to_string(),
+            })?;
+        let status = response.status();
+        let bytes = response
+            .bytes()
+            .await
+            .with_context(|| Message::BackendFailed {
+                service: "Metasploit-RPC",
+                operation: request
+                    .first()
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                detail: "read".to_string(),
+            })?;
+        let value = rmpv::decode::read_value(&mut bytes.as_ref())
+            .map(|value| from_msgpack(&value))
+            .unwrap_or(Value::Null);
+        Ok((status, value))
+    }
+}
+
+fn check_response(method: &str, status: StatusCode, response: Value) -> Result<Value> {
+    if response["error"].as_bool() == Some(true) {
+        bail!(Message::BackendFailed {
+            service: "Metasploit-RPC",
+            operation: method.to_string(),
+            detail: format!(
+                "{} ({})",
+                response["error_message"].as_str().unwrap_or_default(),
+                response["error_class"].as_str().unwrap_or_default()
+            )
+        });
+    }
+    if !status.is_success() {
+        bail!(Message::BackendFailed {
+            service: "Metasploit-RPC",
+            operation: method.to_string(),
+            detail: format!("HTTP {}", status)
+        });
+    }
+    Ok(response)
+}
+
+fn action_name(action: &MsfAction) -> &'static str {
+    match action {
+        MsfAction::Search { .. } => "search",
+        MsfAction::Info { .. } => "info",
+        MsfAction::Execute { .. } => "execute",
+        MsfAction::Status { .. } => "status",
+        MsfAction::Vulns { .. } => "vulns",
+        MsfAction::Stop { .. } => "stop",
+    }
+}
+
+fn split_module(module: &str) -> Result<(&str, &str)> {
+    match module.split_once('/') {
+        Some((kind, name)) if MODULE_TYPES.contains(&kind) && !name.is_empty() => Ok((kind, name)),
+        _ => bail!(Message::InvalidValue {
+            field: "module",
+            value: module.to_string()
+        }),
+    }
+}
+
+fn matches(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+fn option_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn session_findings(sessions: &Value) -> Vec<Finding> {
+    let Some(sessions) = sessions.as_object() else {
+        return Vec::new();
+    };
+    sessions
+        .iter()
+        .map(|(id, session)| {
+            let kind = session["type"].as_str().unwrap_or("shell");
+            let mut finding = Finding::new(
+                "metasploit",
+                "session",
+                Severity::Critical,
+                format!(
+                    "{}-Session {} über {}",
+                    kind,
+                    id,
+                    session["via_exploit"]
+                        .as_str()
+                        .unwrap_or("unbekanntes Modul")
+                ),
+            );
+            finding.host = session["session_host"]
+                .as_str()
+                .or_else(|| session["target_host"].as_str())
+                .filter(|host| !host.is_empty())
+                .map(str::to_string);
+            finding.port = session["session_port"]
+                .as_u64()
+                .and_then(|port| u16::try_from(port).ok());
+            finding.evidence = session["info"]
+                .as_str()
+                .filter(|info| !info.is_empty())
+                .map(str::to_string);
+            finding.data = json!({"session_id": id, "session": session});
+            finding
+        })
+        .collect()
+}
+
+fn result_finding(uuid: &str, result: &Value) -> Option<Finding> {
+    let code = result["result"]["code"].as_str()?;
+    let severity = match code {
+        "vulnerable" => Severity::High,
+        "appears" => Severity::Medium,
+        _ => return None,
+    };
+    let mut finding = Finding::new(
+        "metasploit",
+        "vulnerability",
+        severity,
+        format!("Modul-Check meldet '{}'", code),
+    );
+    finding.evidence = result["result"]["message"].as_str().map(str::to_string);
+    finding.data = json!({"uuid": uuid, "result": result});
+    Some(finding)
+}
+
+fn vuln_findings(vulns: &Value) -> Vec<Finding> {
+    vulns
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|vuln| {
+            let mut finding = Finding::new(
+                "metasploit",
+                "vulnerability",
+                Severity::High,
+                vuln["name"].as_str().unwrap_or("unbenannte Schwachstelle"),
+            );
+            finding.host = vuln["host"].as_str().map(str::to_string);
+            finding.port = vuln["port"]
+                .as_u64()
+                .and_then(|port| u16::try_from(port).ok());
+            finding.protocol = vuln["proto"].as_str().map(str::to_string);
+            finding.references = vuln["refs"]
+                .as_str()
+                .map(|refs| {
+                    refs.split(',')
+                        .map(|reference| reference.trim().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            finding.data = vuln.clone();
+            finding
+        })
+        .collect()
+}
+
+fn to_msgpack(value: &Value) -> rmpv::Value {
+    match value {
+        Value::Null => rmpv::Value::Nil,
+        Value::Bool(flag) => rmpv::Value::Boolean(*flag),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(signed), _) => rmpv::Value::from(signed),
+            (None, Some(unsigned)) => rmpv::Value::from(unsigned),
+            _ => rmpv::Value::F64(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => rmpv::Value::from(text.as_str()),
+        Value::Array(items) => rmpv::Value::Array(items.iter().map(to_msgpack).collect()),
+        Value::Object(map) => rmpv::Value::Map(
+            map.iter()
+                .map(|(key, value)| (rmpv::Value::from(key.as_str()), to_msgpack(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn from_msgpack(value: &rmpv::Value) -> Value {
+    match value {
+        rmpv::Value::Nil => Value::Null,
+        rmpv::Value::Boolean(flag) => json!(flag),
+        rmpv::Value::Integer(number) => number
+            .as_i64()
+            .map(Value::from)
+            .or_else(|| number.as_u64().map(Value::from))
+            .unwrap_or(Value::Null),
+        rmpv::Value::F32(number) => json!(number),
+        rmpv::Value::F64(number) => json!(number),
+        rmpv::Value::String(text) => json!(String::from_utf8_lossy(text.as_bytes())),
+        rmpv::Value::Binary(bytes) => json!(String::from_utf8_lossy(bytes)),
+        rmpv::Value::Array(items) => Value::Array(items.iter().map(from_msgpack).collect()),
+        rmpv::Value::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let key = match key {
+                        rmpv::Value::String(text) => {
+                            String::from_utf8_lossy(text.as_bytes()).into_owned()
+                        }
+                        rmpv::Value::Binary(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                        other => from_msgpack(other).to_string(),
+                    };
+                    (key, from_msgpack(value))
+                })
+                .collect(),
+        ),
+        rmpv::Value::Ext(_, _) => Value::Null,
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::labels::Labels;
+
+const DURATION_BUCKETS: [f64; 10] = [0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0];
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct State {
+    runs: BTreeMap<(String, String, String, Vec<String>), u64>,
+    durations: BTreeMap<String, Histogram>,
+    retries: BTreeMap<String, u64>,
+    truncations: BTreeMap<String, u64>,
+    cache_hits: u64,
+    in_flight_joined: u64,
+}
+
+pub struct Metrics {
+    state: Mutex<State>,
+    label_keys: Vec<String>,
+}
+
+pub struct Gauges {
+    pub active_sessions: usize,
+    pub queue_depth: usize,
+}
+
+impl Metrics {
+    pub fn new(label_keys: &[String]) -> Self {
+        Self {
+            state: Mutex::default(),
+            label_keys: label_keys.to_vec(),
+        }
+    }
+
+    fn label_values(&self, labels: &Labels) -> Vec<String> {
+        self.label_keys
+            .iter()
+            .map(|key| labels.get(key).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    pub fn record_run(
+        &self,
+        tool: &str,
+        host: &str,
+        status: &str,
+        labels: &Labels,
+        duration: Duration,
+        truncated: bool,
+    ) {
+        let values = self.label_values(labels);
+        let mut state = self.state.lock().expect("metrics state poisoned");
+        *state
+            .runs
+            .entry((
+                tool.to_string(),
+                host.to_string(),
+                status.to_string(),
+                values,
+            ))
+            .or_insert(0) += 1;
+
+        let seconds = duration.as_secs_f64();
+        let histogram = state.durations.entry(tool.to_string()).or_default();
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+
+        if truncated {
+            *state.truncations.entry(tool.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    pub fn record_error(&self, tool: &str, host: &str, labels: &Labels) {
+        let values = self.label_values(labels);
+        let mut state = self.state.lock().expect("metrics state poisoned");
+        *state
+            .runs
+            .entry((
+                tool.to_string(),
+                host.to_string(),
+                "exec_error".to_string(),
+                values,
+            ))
+            .or_insert(0) += 1;
+    }
+
+    pub fn record_retry(&self, tool: &str) {
+        let mut state = self.state.lock().expect("metrics state poisoned");
+        *state.retries.entry(tool.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn record_cache_hit(&self) {
+        self.state
+            .lock()
+            .expect("metrics state poisoned")
+            .cache_hits += 1;
+    }
+
+    pub fn record_in_flight_joined(&self) {
+        self.state
+            .lock()
+            .expect("metrics state poisoned")
+            .in_flight_joined += 1;
+    }
+
+    pub fn render(&self, gauges: &Gauges) -> String {
+        let state = self.state.lock().expect("metrics state poisoned");
+        let mut out = String::new();
+
+        out.push_str("# HELP bridge_runs_total Abgeschlossene Tool-Läufe nach Tool, Host, Status und Request-Labels.\n");
+        out.push_str("# TYPE bridge_runs_total counter\n");
+        for ((tool, host, status, values), value) in &state.runs {
+            let labels = self
+                .label_keys
+                .iter()
+                .zip(values)
+                .map(|(key, label)| format!(",label_{}=\"{}\"", key, escape_label(label)))
+                .collect::<String>();
+            let _ = writeln!(
+                out,
+                "bridge_runs_total{{tool=\"{}\",host=\"{}\",status=\"{}\"{}}} {}",
+                escape_label(tool),
+                escape_label(host),
+                escape_label(status),
+                labels,
+                value
+            );
+        }
+
+        out.push_str("# HELP bridge_run_duration_seconds Laufzeit der Tool-Läufe.\n");
+        out.push_str("# TYPE bridge_run_duration_seconds histogram\n");
+        for (tool, histogram) in &state.durations {
+            let tool = escape_label(tool);
+            for (bound, value) in DURATION_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    out,
+                    "bridge_run_duration_seconds_bucket{{tool=\"{}\",le=\"{}\"}} {}",
+                    tool, bound, value
+                );
+            }
+            let _ = writeln!(
+                out,
+                "bridge_run_duration_seconds_bucket{{tool=\"{}\",le=\"+Inf\"}} {}",
+                tool, histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "bridge_run_duration_seconds_sum{{tool=\"{}\"}} {}",
+                tool, histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "bridge_run_duration_seconds_count{{tool=\"{}\"}} {}",
+                tool, histogram.count
+            );
+        }
+
+        out.push_str("# HELP bridge_retries_total Geplante Wiederholungen nach Tool.\n");
+        out.push_str("# TYPE bridge_retries_total counter\n");
+        for (tool, value) in &state.retries {
+            let _ = writeln!(
+                out,
+                "bridge_retries_total{{tool=\"{}\"}} {}",
+                escape_label(tool),
+                value
+            );
+        }
+
+        out.push_str(
+            "# HELP bridge_truncations_total Läufe mit abgeschnittener Ausgabe nach Tool.\n",
+        );
+        out.push_str("# TYPE bridge_truncations_total counter\n");
+        for (tool, value) in &state.truncations {
+            let _ = writeln!(
+                out,
+                "bridge_truncations_total{{tool=\"{}\"}} {}",
+                escape_label(tool),
+                value
+            );
+        }
+
+        out.push_str(
+            "# HELP bridge_cache_hits_total Aus dem Ergebnis-Cache beantwortete Requests.\n",
+        );
+        out.push_str("# TYPE bridge_cache_hits_total counter\n");
+        let _ = writeln!(out, "bridge_cache_hits_total {}", state.cache_hits);
+
+        out.push_str("# HELP bridge_in_flight_joined_total An laufende identische Läufe angehängte Requests.\n");
+        out.push_str("# TYPE bridge_in_flight_joined_total counter\n");
+        let _ = writeln!(
+            out,
+            "bridge_in_flight_joined_total {}",
+            state.in_flight_joined
+        );
+
+        out.push_str("# HELP bridge_active_ssh_sessions Aktuell offene SSH-Sessions.\n");
+        out.push_str("# TYPE bridge_active_ssh_sessions gauge\n");
+        let _ = writeln!(out, "bridge_active_ssh_sessions {}", gauges.active_sessions);
+
+        out.push_str("# HELP bridge_queue_depth Wartende Requests in der scheduler-queue.\n");
+        out.push_str("# TYPE bridge_queue_depth gauge\n");
+        let _ = writeln!(out, "bridge_queue_depth {}", gauges.queue_depth);
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub async fn bind(addr: &str) -> Result<TcpListener> {
+    TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("metrics-Endpoint konnte nicht an {} gebunden werden", addr))
+}
+
+pub async fn serve<F>(listener: TcpListener, render: Arc<F>)
+where
+    F: Fn() -> String + Send + Sync + 'static,
+{
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            continue;
+        };
+        let render = render.clone();
+        tokio::spawn(async move {
+            let _ = tokio::time::timeout(
+                Duration::from_secs(5),
+                handle_connection(stream, render.as_ref()),
+            )
+            .await;
+        });
+    }
+}
+
+async fn handle_connection<F: Fn() -> String>(mut stream: TcpStream, render: &F) -> Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0_u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n")
+        && request.len() < MAX_REQUEST_BYTES
+    {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", render()),
+        (Some("GET"), Some(_)) => ("404 Not Found", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "method not allowed\n".to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
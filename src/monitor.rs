@@ -0,0 +1,376 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, List, ListItem, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::Value;
+
+use crate::control::{self, ControlCommand};
+
+struct Monitor {
+    socket: String,
+    snapshot: Value,
+    error: Option<String>,
+    selected: Option<String>,
+    follow: bool,
+    table: TableState,
+}
+
+pub async fn run(socket: &str, interval: Duration) -> Result<()> {
+    let mut terminal = ratatui::try_init().context("Terminal konnte nicht initialisiert werden")?;
+    let mut monitor = Monitor {
+        socket: socket.to_string(),
+        snapshot: Value::Null,
+        error: None,
+        selected: None,
+        follow: true,
+        table: TableState::default(),
+    };
+    let result = monitor.run(&mut terminal, interval).await;
+    ratatui::try_restore().context("Terminal konnte nicht wiederhergestellt werden")?;
+    result
+}
+
+impl Monitor {
+    async fn run(&mut self, terminal: &mut DefaultTerminal, interval: Duration) -> Result<()> {
+        loop {
+            self.refresh().await;
+            terminal.draw(|frame| self.draw(frame))?;
+            let next = Instant::now() + interval;
+            while let Some(remaining) = next.checked_duration_since(Instant::now()) {
+                let pressed = tokio::task::block_in_place(|| -> Result<Option<Event>> {
+                    if event::poll(remaining)? {
+                        return Ok(Some(event::read()?));
+                    }
+                    Ok(None)
+                })?;
+                let Some(Event::Key(key)) = pressed else {
+                    if pressed.is_some() {
+                        terminal.draw(|frame| self.draw(frame))?;
+                    }
+                    continue;
+                };
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(());
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                    KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                    KeyCode::Char('f') => self.follow = !self.follow,
+                    _ => continue,
+                }
+                break;
+            }
+        }
+    }
+
+    async fn refresh(&mut self) {
+        for _ in 0..2 {
+            let requested = self.selected.clone();
+            let command = ControlCommand::Activity {
+                run: requested.clone(),
+            };
+            match control::send(&self.socket, &command).await {
+                Ok(snapshot) => {
+                    self.snapshot = snapshot;
+                    self.error = None;
+                }
+                Err(error) => {
+                    self.error = Some(format!("{:#}", error));
+                    return;
+                }
+            }
+            let runs = self.runs();
+            let newest = self.snapshot["active"]
+                .as_array()
+                .and_then(|active| active.last());
+            if let (true, Some(newest)) = (self.follow, newest) {
+                self.selected = newest["id"].as_str().map(str::to_string);
+            }
+            if self.selected.is_none() {
+                self.selected = runs
+                    .first()
+                    .and_then(|run| run["id"].as_str())
+                    .map(str::to_string);
+            }
+            let index = self.selected.as_ref().and_then(|selected| {
+                runs.iter()
+                    .position(|run| run["id"].as_str() == Some(selected))
+            });
+            self.table.select(index);
+            if self.selected == requested {
+                return;
+            }
+        }
+    }
+
+    fn runs(&self) -> Vec<Value> {
+        let list = |key: &str| self.snapshot[key].as_array().cloned().unwrap_or_default();
+        list("active").into_iter().chain(list("finished")).collect()
+    }
+
+    fn select(&mut self, offset: isize) {
+        let runs = self.runs();
+        if runs.is_empty() {
+            return;
+        }
+        let current = self.table.selected().unwrap_or(0) as isize;
+        let index = (current + offset).clamp(0, runs.len() as isize - 1) as usize;
+        self.table.select(Some(index));
+        self.selected = runs[index]["id"].as_str().map(str::to_string);
+        self.follow = false;
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, middle, bottom, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Percentage(40),
+            Constraint::Min(6),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [runs, hosts] =
+            Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .areas(middle);
+        let [events, tail] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(bottom);
+
+        self.draw_header(frame, header);
+        self.draw_runs(frame, runs);
+        self.draw_hosts(frame, hosts);
+        self.draw_events(frame, events);
+        self.draw_tail(frame, tail);
+        let help = format!(
+            "↑/↓ Run wählen · f neuesten Run folgen ({}) · q beenden",
+            if self.follow { "an" } else { "aus" }
+        );
+        frame.render_widget(
+            Paragraph::new(help).style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn draw_header(&self, frame: &mut Frame, area: Rect) {
+        let status = &self.snapshot["status"];
+        let line = match &self.error {
+            Some(error) => Line::from(Span::styled(
+                format!("Control-Socket {}: {}", self.socket, error),
+                Style::default().fg(Color::Red),
+            )),
+            None => {
+                let (state, color) = if status["paused"] == true {
+                    ("pausiert", Color::Yellow)
+                } else {
+                    ("aktiv", Color::Green)
+                };
+                Line::from(vec![
+                    Span::styled(
+                        state,
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!(
+                        "  Sessions {}  Queue {}  Freigaben offen {}  Runs aktiv {}",
+                        status["active_sessions"],
+                        status["queue_depth"],
+                        status["pending_approvals"],
+                        self.snapshot["active"].as_array().map_or(0, Vec::len)
+                    )),
+                ])
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(line).block(Block::bordered().title(" Bridge ")),
+            area,
+        );
+    }
+
+    fn draw_runs(&mut self, frame: &mut Frame, area: Rect) {
+        let now = now_ms();
+        let rows = self.runs().into_iter().map(|run| {
+            let started = run["started_ms"].as_u64().unwrap_or(0);
+            let end = run["finished_ms"].as_u64().unwrap_or(now);
+            let (state, color) = match run["outcome"].as_str() {
+                None => ("läuft".to_string(), Color::Cyan),
+                Some("finished") if run["exit_code"] == 0 => ("fertig".to_string(), Color::Green),
+                Some("finished") => (format!("exit {}", run["exit_code"]), Color::Yellow),
+                Some("timed_out") => ("Timeout".to_string(), Color::Red),
+                Some(_) => ("fehlgeschlagen".to_string(), Color::Red),
+            };
+            Row::new(vec![
+                Cell::from(state).style(Style::default().fg(color)),
+                Cell::from(text(&run["id"])),
+                Cell::from(text(&run["tool"])),
+                Cell::from(text(&run["host"])),
+                Cell::from(text(&run["client"])),
+                Cell::from(duration(end.saturating_sub(started))),
+                Cell::from(bytes(run["output_bytes"].as_u64().unwrap_or(0))),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(14),
+                Constraint::Fill(2),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(9),
+            ],
+        )
+        .header(
+            Row::new(["Status", "ID", "Tool", "Host", "Client", "Dauer", "Ausgabe"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(" Runs "));
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_hosts(&self, frame: &mut Frame, area: Rect) {
+        let hosts = self.snapshot["hosts"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let rows = hosts.iter().map(|host| {
+            let style = if host["saturated"] == true {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                text(&host["host"]),
+                host["active"].to_string(),
+                host["queued"].to_string(),
+            ])
+            .style(style)
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(6),
+                Constraint::Length(9),
+            ],
+        )
+        .header(
+            Row::new(["Host", "Aktiv", "Wartend"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title(" Hosts "));
+        frame.render_widget(table, area);
+    }
+
+    fn draw_events(&self, frame: &mut Frame, area: Rect) {
+        let now = now_ms();
+        let events = self.snapshot["events"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let items = events.iter().rev().take(area.height as usize).map(|event| {
+            let age = now.saturating_sub(event["ts_ms"].as_u64().unwrap_or(now));
+            let color = match event["event"].as_str() {
+                Some("run_started") => Color::Cyan,
+                Some("run_finished") => Color::Green,
+                _ => Color::Red,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:>6} ", duration(age)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(text(&event["event"]), Style::default().fg(color)),
+                Span::raw(format!(
+                    " {} {}@{}",
+                    text(&event["id"]),
+                    text(&event["tool"]),
+                    text(&event["host"])
+                )),
+            ]))
+        });
+        frame.render_widget(
+            List::new(items).block(Block::bordered().title(" Ereignisse ")),
+            area,
+        );
+    }
+
+    fn draw_tail(&self, frame: &mut Frame, area: Rect) {
+        let tail = &self.snapshot["tail"];
+        let title = match tail["id"].as_str() {
+            Some(id) if tail["running"] == true => format!(" Ausgabe {} (live) ", id),
+            Some(id) => format!(" Ausgabe {} ", id),
+            None => " Ausgabe ".to_string(),
+        };
+        let data = tail["data"].as_str().unwrap_or_default();
+        let visible = area.height.saturating_sub(2) as usize;
+        let lines = data.lines().map(printable).collect::<Vec<_>>();
+        let lines = lines[lines.len().saturating_sub(visible)..]
+            .iter()
+            .map(|line| Line::raw(line.clone()))
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        Value::Null => "-".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn printable(line: &str) -> String {
+    let line = line
+        .rsplit('\r')
+        .find(|part| !part.is_empty())
+        .unwrap_or_default();
+    let mut out = String::with_capacity(line.len());
+    let mut escape = false;
+    for character in line.chars() {
+        match character {
+            '\u{1b}' => escape = true,
+            character if escape => escape = !character.is_ascii_alphabetic(),
+            '\t' => out.push_str("    "),
+            character if character.is_control() => {}
+            character => out.push(character),
+        }
+    }
+    out
+}
+
+fn duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+fn bytes(count: u64) -> String {
+    match count {
+        0..1024 => format!("{} B", count),
+        1024..1_048_576 => format!("{:.1} KiB", count as f64 / 1024.0),
+        _ => format!("{:.1} MiB", count as f64 / 1_048_576.0),
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::task::JoinSet;
+
+use crate::findings::Severity;
+use crate::{log_observation, redact};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Finished,
+    WorkflowFinished,
+    Error,
+    ApprovalRequested,
+    BaselineChanged,
+}
+
+impl NotificationEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NotificationEvent::Finished => "finished",
+            NotificationEvent::WorkflowFinished => "workflow_finished",
+            NotificationEvent::Error => "error",
+            NotificationEvent::ApprovalRequested => "approval_requested",
+            NotificationEvent::BaselineChanged => "baseline_changed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    #[default]
+    Json,
+    Slack,
+    Discord,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<NotificationEvent>,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+}
+
+fn default_timeout_sec() -> u64 {
+    5
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    1000
+}
+
+impl WebhookConfig {
+    fn accepts(&self, event: NotificationEvent, severity: Option<Severity>) -> bool {
+        let threshold = match (self.min_severity, event) {
+            (Some(min), NotificationEvent::Finished | NotificationEvent::BaselineChanged) => {
+                severity.is_some_and(|severity| severity >= min)
+            }
+            _ => true,
+        };
+        (self.events.is_empty() || self.events.contains(&event)) && threshold
+    }
+}
+
+fn highest_severity(event: NotificationEvent, payload: &Value) -> Option<Severity> {
+    let summary = match event {
+        NotificationEvent::BaselineChanged => &payload["summary"],
+        _ => &payload["findings_summary"],
+    };
+    summary["by_severity"]
+        .as_object()?
+        .keys()
+        .filter_map(|severity| Severity::parse(severity))
+        .max()
+}
+
+pub struct Notifier {
+    client: reqwest::Client,
+    webhooks: Vec<Arc<WebhookConfig>>,
+    deliveries: Mutex<JoinSet<()>>,
+}
+
+impl Notifier {
+    pub fn new(config: &NotificationsConfig) -> Result<Self> {
+        for webhook in &config.webhooks {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                bail!(
+                    "ungültige Webhook-URL '{}' (http:// oder https:// erwartet)",
+                    webhook.url
+                );
+            }
+        }
+        let client = reqwest::Client::builder()
+            .user_agent(concat!(
+                "ollama-kali-mcp-bridge/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()
+            .context("HTTP-Client für Webhooks konnte nicht erstellt werden")?;
+        Ok(Self {
+            client,
+            webhooks: config.webhooks.iter().cloned().map(Arc::new).collect(),
+            deliveries: Mutex::new(JoinSet::new()),
+        })
+    }
+
+    pub fn notify(&self, event: NotificationEvent, id: &str, mut payload: Value) {
+        let severity = highest_severity(event, &payload);
+        let targets = self
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.accepts(event, severity))
+            .cloned()
+            .collect::<Vec<_>>();
+        if targets.is_empty() {
+            return;
+        }
+
+        redact::redact_value(&mut payload);
+        let body = json!({
+            "source": "ollama-kali-mcp-bridge",
+            "event": event.as_str(),
+            "id": id,
+            "ts_ms": SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|value| value.as_millis())
+                .unwrap_or(0),
+            "payload": payload
+        });
+        let body = Arc::new(body);
+        let mut deliveries = self
+            .deliveries
+            .lock()
+            .expect("notification deliveries poisoned");
+        while deliveries.try_join_next().is_some() {}
+        for webhook in targets {
+            let body = match webhook.format {
+                WebhookFormat::Json => body.clone(),
+                WebhookFormat::Slack => {
+                    Arc::new(json!({"text": render_text(&body, webhook.min_severity)}))
+                }
+                WebhookFormat::Discord => {
+                    Arc::new(json!({"content": render_text(&body, webhook.min_severity)}))
+                }
+            };
+            deliveries.spawn(deliver(self.client.clone(), webhook, event, body));
+        }
+    }
+
+    pub async fn flush(&self) {
+        let mut deliveries = std::mem::take(
+            &mut *self
+                .deliveries
+                .lock()
+                .expect("notification deliveries poisoned"),
+        );
+        while deliveries.join_next().await.is_some() {}
+    }
+}
+
+async fn deliver(
+    client: reqwest::Client,
+    webhook: Arc<WebhookConfig>,
+    event: NotificationEvent,
+    body: Arc<Value>,
+) {
+    let max_attempts = webhook.max_retries.saturating_add(1);
+    let mut attempt = 1;
+    loop {
+        let mut request = client
+            .post(&webhook.url)
+            .timeout(Duration::from_secs(webhook.timeout_sec))
+            .json(&*body);
+        for (name, value) in &webhook.headers {
+            request = request.header(name, value);
+        }
+
+        let (retryable, error) = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                log_observation(
+                    "notification_delivered",
+                    json!({"url": webhook.url, "event": event.as_str(), "attempt": attempt}),
+                );
+                return;
+            }
+            Ok(response) => {
+                let status = response.status();
+                (
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS,
+                    format!("HTTP {}", status.as_u16()),
+                )
+            }
+            Err(error) => (true, error.to_string()),
+        };
+
+        if !retryable || attempt >= max_attempts {
+            log_observation(
+                "notification_failed",
+                json!({
+                    "url": webhook.url,
+                    "event": event.as_str(),
+                    "attempts": attempt,
+                    "error": error
+                }),
+            );
+            return;
+        }
+        let backoff = webhook
+            .retry_backoff_ms
+            .saturating_mul(1 << (attempt - 1).min(6));
+        tokio::time::sleep(Duration::from_millis(backoff)).await;
+        attempt += 1;
+    }
+}
+
+fn render_text(body: &Value, min_severity: Option<Severity>) -> String {
+    let mut text = format!(
+        "[ollama-kali-mcp-bridge] {} `{}`",
+        body["event"].as_str().unwrap_or("event"),
+        body["id"].as_str().unwrap_or("-")
+    );
+    if let Some(payload) = body["payload"].as_object() {
+        let fields = payload
+            .iter()
+            .filter(|(_, value)| !value.is_object() && !value.is_array())
+            .map(|(key, value)| match value {
+                Value::String(value) => format!("{}={}", key, value),
+                other => format!("{}={}", key, other),
+            })
+            .collect::<Vec<_>>();
+        if !fields.is_empty() {
+            text.push_str(": ");
+            text.push_str(&fields.join(", "));
+        }
+    }
+    let findings = body["payload"]["top_findings"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|finding| {
+            let severity = finding["severity"].as_str().and_then(Severity::parse);
+            min_severity.is_none_or(|min| severity.is_some_and(|severity| severity >= min))
+        });
+    for finding in findings {
+        text.push_str(&format!(
+            "\n- [{}] {}",
+            finding["severity"].as_str().unwrap_or("info"),
+            finding["title"].as_str().unwrap_or("-")
+        ));
+        match (finding["host"].as_str(), finding["port"].as_u64()) {
+            (Some(host), Some(port)) => text.push_str(&format!(" ({}:{})", host, port)),
+            (Some(host), None) => text.push_str(&format!(" ({})", host)),
+            _ => {}
+        }
+    }
+    text
+}
@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+
+struct Stored {
+    run_id: String,
+    client: Option<String>,
+    stdout: String,
+    stderr: String,
+}
+
+#[derive(Default)]
+pub struct Outputs {
+    entries: Mutex<VecDeque<Stored>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReadRequest {
+    run_id: String,
+    #[serde(default = "default_stream")]
+    stream: String,
+    #[serde(default)]
+    offset: usize,
+    length: Option<usize>,
+}
+
+fn default_stream() -> String {
+    "stdout".to_string()
+}
+
+impl Outputs {
+    pub fn store(
+        &self,
+        run_id: &str,
+        client: Option<&str>,
+        stdout: &str,
+        stderr: &str,
+        max_entries: usize,
+    ) {
+        let mut entries = self.entries.lock().expect("outputs poisoned");
+        entries.retain(|entry| entry.run_id != run_id);
+        entries.push_back(Stored {
+            run_id: run_id.to_string(),
+            client: client.map(str::to_string),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        });
+        while entries.len() > max_entries.max(1) {
+            entries.pop_front();
+        }
+    }
+
+    pub fn mcp_call(
+        &self,
+        client: Option<&str>,
+        action: &str,
+        arguments: Value,
+        max_read_bytes: usize,
+    ) -> Result<Value> {
+        if action != "read" {
+            bail!(Message::ToolNotAllowed {
+                tool: format!("output.{}", action)
+            });
+        }
+        let request = serde_json::from_value::<ReadRequest>(arguments).map_err(|error| {
+            Message::InvalidArguments {
+                tool: "output.read".to_string(),
+                error: error.to_string(),
+            }
+        })?;
+        let entries = self.entries.lock().expect("outputs poisoned");
+        let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.run_id == request.run_id && entry.client.as_deref() == client)
+        else {
+            bail!(Message::OutputNotStored {
+                run_id: request.run_id
+            });
+        };
+        let text = match request.stream.as_str() {
+            "stdout" => &entry.stdout,
+            "stderr" => &entry.stderr,
+            other => bail!(Message::InvalidValue {
+                field: "stream",
+                value: other.to_string()
+            }),
+        };
+        let length = request.length.unwrap_or(max_read_bytes).min(max_read_bytes);
+        let start = floor_boundary(text, request.offset.min(text.len()));
+        let mut end = floor_boundary(text, start.saturating_add(length).min(text.len()));
+        if end == start && start < text.len() {
+            end = (start + 1..=text.len())
+                .find(|index| text.is_char_boundary(*index))
+                .unwrap_or(text.len());
+        }
+        Ok(json!({
+            "run_id": entry.run_id,
+            "stream": request.stream,
+            "offset": start,
+            "length": end - start,
+            "total_bytes": text.len(),
+            "next_offset": (end < text.len()).then_some(end),
+            "data": &text[start..end]
+        }))
+    }
+}
+
+pub fn trim(text: &str, limit: usize) -> &str {
+    &text[..floor_boundary(text, limit.min(text.len()))]
+}
+
+fn floor_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+pub fn mcp_tools() -> Vec<Value> {
+    vec![json!({
+        "name": "output.read",
+        "description": "Reads a slice of the full stdout or stderr of an earlier tool call whose inline output was trimmed; page through it with next_offset",
+        "inputSchema": {
+            "type": "object",
+            "required": ["run_id"],
+            "properties": {
+                "run_id": {"type": "string", "description": "structuredContent.run_id of the earlier call"},
+                "stream": {"type": "string", "enum": ["stdout", "stderr"]},
+                "offset": {"type": "integer", "minimum": 0, "description": "Byte offset to start reading at"},
+                "length": {"type": "integer", "minimum": 1, "description": "Maximum number of bytes to return"}
+            }
+        }
+    })]
+}
@@ -0,0 +1,199 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use tracing::Instrument;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageName {
+    Policy,
+    Scope,
+    RateLimit,
+    Plugins,
+    Approval,
+    PreHooks,
+    Executor,
+    Parsers,
+    Redaction,
+    Store,
+    Notify,
+    PostHooks,
+}
+
+const REQUIRED_STAGES: [StageName; 5] = [
+    StageName::Policy,
+    StageName::Scope,
+    StageName::Approval,
+    StageName::Executor,
+    StageName::Redaction,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Admission,
+    Execution,
+    Completion,
+}
+
+impl StageName {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StageName::Policy => "policy",
+            StageName::Scope => "scope",
+            StageName::RateLimit => "rate_limit",
+            StageName::Plugins => "plugins",
+            StageName::Approval => "approval",
+            StageName::PreHooks => "pre_hooks",
+            StageName::Executor => "executor",
+            StageName::Parsers => "parsers",
+            StageName::Redaction => "redaction",
+            StageName::Store => "store",
+            StageName::Notify => "notify",
+            StageName::PostHooks => "post_hooks",
+        }
+    }
+
+    fn phase(self) -> Phase {
+        match self {
+            StageName::Executor => Phase::Execution,
+            StageName::Parsers
+            | StageName::Redaction
+            | StageName::Store
+            | StageName::Notify
+            | StageName::PostHooks => Phase::Completion,
+            _ => Phase::Admission,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    #[serde(default = "default_stages")]
+    pub stages: Vec<StageName>,
+}
+
+fn default_stages() -> Vec<StageName> {
+    vec![
+        StageName::Policy,
+        StageName::Scope,
+        StageName::RateLimit,
+        StageName::Plugins,
+        StageName::Approval,
+        StageName::PreHooks,
+        StageName::Executor,
+        StageName::Parsers,
+        StageName::Redaction,
+        StageName::Store,
+        StageName::Notify,
+        StageName::PostHooks,
+    ]
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            stages: default_stages(),
+        }
+    }
+}
+
+impl PipelineConfig {
+    pub fn enabled(&self, name: StageName) -> bool {
+        self.stages.contains(&name)
+    }
+}
+
+pub fn validate(config: &PipelineConfig) -> Result<()> {
+    for (index, stage) in config.stages.iter().enumerate() {
+        if config.stages[..index].contains(stage) {
+            bail!(
+                "pipeline.stages: '{}' ist doppelt aufgeführt",
+                stage.as_str()
+            );
+        }
+    }
+    for required in REQUIRED_STAGES {
+        if !config.enabled(required) {
+            bail!(
+                "pipeline.stages: '{}' kann nicht deaktiviert werden",
+                required.as_str()
+            );
+        }
+    }
+    if config.stages.first() != Some(&StageName::Policy) {
+        bail!(
+            "pipeline.stages: 'policy' muss die erste Stufe sein, sie löst Tool und Profil-Argumente auf"
+        );
+    }
+    let position = |name| config.stages.iter().position(|stage| *stage == name);
+    let executor = position(StageName::Executor).unwrap_or_default();
+    for (index, stage) in config.stages.iter().enumerate() {
+        match stage.phase() {
+            Phase::Admission if index > executor => {
+                bail!(
+                    "pipeline.stages: '{}' muss vor 'executor' stehen",
+                    stage.as_str()
+                )
+            }
+            Phase::Completion if index < executor => {
+                bail!(
+                    "pipeline.stages: '{}' muss nach 'executor' stehen",
+                    stage.as_str()
+                )
+            }
+            _ => {}
+        }
+    }
+    if let (Some(store), Some(redaction)) =
+        (position(StageName::Store), position(StageName::Redaction))
+        && store < redaction
+    {
+        bail!(
+            "pipeline.stages: 'redaction' muss vor 'store' stehen, sonst landen Zugangsdaten im Cache"
+        );
+    }
+    Ok(())
+}
+
+pub type StageFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+pub trait Stage<C>: Send + Sync {
+    fn name(&self) -> StageName;
+
+    fn run<'a>(&'a self, context: &'a mut C) -> StageFuture<'a>;
+}
+
+pub struct Pipeline<C> {
+    stages: Vec<Box<dyn Stage<C>>>,
+}
+
+impl<C: Send> Pipeline<C> {
+    pub fn new(config: &PipelineConfig, mut available: Vec<Box<dyn Stage<C>>>) -> Self {
+        let stages = config
+            .stages
+            .iter()
+            .filter_map(|name| {
+                let index = available.iter().position(|stage| stage.name() == *name)?;
+                Some(available.swap_remove(index))
+            })
+            .collect();
+        Self { stages }
+    }
+
+    pub async fn run(&self, phase: Phase, context: &mut C) -> Result<()> {
+        for stage in self
+            .stages
+            .iter()
+            .filter(|stage| stage.name().phase() == phase)
+        {
+            let name = stage.name().as_str();
+            stage
+                .run(context)
+                .instrument(tracing::info_span!("stage", name))
+                .await?;
+        }
+        Ok(())
+    }
+}
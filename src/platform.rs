@@ -0,0 +1,43 @@
+use std::fs::Metadata;
+use std::io;
+use std::path::Path;
+
+#[cfg(unix)]
+pub use std::os::unix::fs::OpenOptionsExt;
+
+#[cfg(not(unix))]
+pub trait OpenOptionsExt {
+    fn mode(&mut self, mode: u32) -> &mut Self;
+}
+
+#[cfg(not(unix))]
+impl OpenOptionsExt for std::fs::OpenOptions {
+    fn mode(&mut self, _mode: u32) -> &mut Self {
+        self
+    }
+}
+
+#[cfg(unix)]
+pub fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+pub fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn file_id(meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.ino()
+}
+
+#[cfg(not(unix))]
+pub fn file_id(meta: &Metadata) -> u64 {
+    meta.created()
+        .ok()
+        .and_then(|created| created.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |created| created.as_nanos() as u64)
+}
@@ -0,0 +1,227 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::findings::Finding;
+use crate::log_observation;
+
+const VALIDATE_ARGS: &str = "validate_args";
+const PARSE_OUTPUT: &str = "parse_output";
+const ENRICH_FINDINGS: &str = "enrich_findings";
+const ENTRY_POINTS: [&str; 3] = [VALIDATE_ARGS, PARSE_OUTPUT, ENRICH_FINDINGS];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginsConfig {
+    #[serde(default)]
+    pub modules: Vec<PluginConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default = "default_fuel")]
+    pub fuel: u64,
+    #[serde(default = "default_max_memory_bytes")]
+    pub max_memory_bytes: usize,
+}
+
+fn default_fuel() -> u64 {
+    1_000_000_000
+}
+
+fn default_max_memory_bytes() -> usize {
+    64 * 1024 * 1024
+}
+
+struct Plugin {
+    config: PluginConfig,
+    module: Module,
+    entry_points: Vec<&'static str>,
+}
+
+impl Plugin {
+    fn handles(&self, tool: &str, entry_point: &str) -> bool {
+        self.entry_points.contains(&entry_point)
+            && (self.config.tools.is_empty() || self.config.tools.iter().any(|name| name == tool))
+    }
+}
+
+pub struct Plugins {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+}
+
+impl Plugins {
+    pub fn load(config: &PluginsConfig) -> Result<Self> {
+        let mut engine_config = Config::new();
+        engine_config.consume_fuel(true);
+        let engine =
+            Engine::new(&engine_config).context("WASM-Engine konnte nicht erstellt werden")?;
+        let mut plugins = Vec::new();
+        for plugin in &config.modules {
+            if plugins
+                .iter()
+                .any(|loaded: &Plugin| loaded.config.name == plugin.name)
+            {
+                bail!("plugins: Name '{}' ist doppelt vergeben", plugin.name);
+            }
+            let module = Module::from_file(&engine, &plugin.path).with_context(|| {
+                format!(
+                    "plugins.{}: {} konnte nicht geladen werden",
+                    plugin.name, plugin.path
+                )
+            })?;
+            if let Some(import) = module.imports().next() {
+                bail!(
+                    "plugins.{}: Modul importiert '{}::{}', erlaubt sind nur Module ohne Imports",
+                    plugin.name,
+                    import.module(),
+                    import.name()
+                );
+            }
+            let exports = module
+                .exports()
+                .map(|export| export.name().to_string())
+                .collect::<Vec<_>>();
+            for required in ["memory", "alloc"] {
+                if !exports.iter().any(|name| name == required) {
+                    bail!("plugins.{}: Export '{}' fehlt", plugin.name, required);
+                }
+            }
+            let entry_points = ENTRY_POINTS
+                .into_iter()
+                .filter(|entry_point| exports.iter().any(|name| name == entry_point))
+                .collect::<Vec<_>>();
+            if entry_points.is_empty() {
+                bail!(
+                    "plugins.{}: Modul exportiert keinen Einstiegspunkt ({})",
+                    plugin.name,
+                    ENTRY_POINTS.join(", ")
+                );
+            }
+            plugins.push(Plugin {
+                config: plugin.clone(),
+                module,
+                entry_points,
+            });
+        }
+        Ok(Self { engine, plugins })
+    }
+
+    pub fn validate_args(&self, tool: &str, request: Value) -> Result<()> {
+        for plugin in self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.handles(tool, VALIDATE_ARGS))
+        {
+            let verdict = self
+                .call(plugin, VALIDATE_ARGS, &request)
+                .with_context(|| {
+                    format!(
+                        "Plugin '{}' konnte die Argumente nicht prüfen",
+                        plugin.config.name
+                    )
+                })?;
+            if verdict["allow"] != json!(true) {
+                bail!(
+                    "Plugin '{}' lehnt den Run ab: {}",
+                    plugin.config.name,
+                    verdict["reason"].as_str().unwrap_or("ohne Begründung")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn findings(
+        &self,
+        tool: &str,
+        output: Value,
+        mut findings: Option<Vec<Finding>>,
+    ) -> Option<Vec<Finding>> {
+        for plugin in self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.handles(tool, PARSE_OUTPUT))
+        {
+            match self
+                .call(plugin, PARSE_OUTPUT, &output)
+                .and_then(|result| parse_findings(plugin, result))
+            {
+                Ok(parsed) => findings.get_or_insert_with(Vec::new).extend(parsed),
+                Err(error) => plugin_failed(plugin, PARSE_OUTPUT, tool, &error),
+            }
+        }
+        for plugin in self
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.handles(tool, ENRICH_FINDINGS))
+        {
+            let Some(current) = findings.as_ref().filter(|findings| !findings.is_empty()) else {
+                break;
+            };
+            let input = json!({"tool": tool, "findings": current});
+            match self
+                .call(plugin, ENRICH_FINDINGS, &input)
+                .and_then(|result| parse_findings(plugin, result))
+            {
+                Ok(enriched) => findings = Some(enriched),
+                Err(error) => plugin_failed(plugin, ENRICH_FINDINGS, tool, &error),
+            }
+        }
+        findings
+    }
+
+    fn call(&self, plugin: &Plugin, entry_point: &str, input: &Value) -> Result<Value> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(plugin.config.max_memory_bytes)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(plugin.config.fuel)?;
+        let instance = Instance::new(&mut store, &plugin.module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("Export 'memory' ist kein Speicher")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let entry = instance.get_typed_func::<(i32, i32), i64>(&mut store, entry_point)?;
+
+        let data = serde_json::to_vec(input)?;
+        let len = i32::try_from(data.len()).context("Eingabe zu groß für das Plugin")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory
+            .write(&mut store, ptr as u32 as usize, &data)
+            .context("alloc lieferte einen ungültigen Zeiger")?;
+        let packed = entry.call(&mut store, (ptr, len))? as u64;
+        let (out_ptr, out_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut out = vec![0_u8; out_len];
+        memory
+            .read(&store, out_ptr, &mut out)
+            .context("Ergebnis liegt außerhalb des Plugin-Speichers")?;
+        serde_json::from_slice(&out).context("Ergebnis ist kein gültiges JSON")
+    }
+}
+
+fn parse_findings(plugin: &Plugin, mut result: Value) -> Result<Vec<Finding>> {
+    let Some(findings) = result["findings"].as_array_mut() else {
+        bail!("Ergebnis enthält kein Feld 'findings'");
+    };
+    for finding in findings.iter_mut().filter_map(Value::as_object_mut) {
+        finding
+            .entry("source")
+            .or_insert_with(|| json!(plugin.config.name));
+    }
+    serde_json::from_value(result["findings"].take()).context("ungültige Findings")
+}
+
+fn plugin_failed(plugin: &Plugin, entry_point: &str, tool: &str, error: &anyhow::Error) {
+    log_observation(
+        "plugin_failed",
+        json!({"plugin": plugin.config.name, "entry_point": entry_point, "tool": tool, "error": format!("{:#}", error)}),
+    );
+}
@@ -0,0 +1,1513 @@
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use anyhow::{Result, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::findings::{Finding, Severity};
+use crate::log_observation;
+use crate::messages::Message;
+use crate::targets::{self, DnsName};
+
+const CAPTURE_FILE: &str = "capture.pcap";
+const DEFAULT_CAPTURE_SEC: u64 = 60;
+const CAPTURE_TIMEOUT_SLACK_SEC: u64 = 30;
+const GOWITNESS_RESULTS: &str = "gowitness.jsonl";
+const HYDRA_RESULTS: &str = "hydra.json";
+const DNSRECON_RESULTS: &str = "dnsrecon.json";
+const DNSX_RESULTS: &str = "dnsx.jsonl";
+const SUBFINDER_RESULTS: &str = "subfinder.jsonl";
+const DNS_RECORD_TYPES: [&str; 9] = ["a", "aaaa", "cname", "mx", "ns", "txt", "ptr", "srv", "soa"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolProfile {
+    Nuclei,
+    Masscan,
+    Capture,
+    Gowitness,
+    Eyewitness,
+    Hydra,
+    Dnsrecon,
+    Dnsx,
+    Subfinder,
+}
+
+impl ToolProfile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ToolProfile::Nuclei => "nuclei",
+            ToolProfile::Masscan => "masscan",
+            ToolProfile::Capture => "capture",
+            ToolProfile::Gowitness => "gowitness",
+            ToolProfile::Eyewitness => "eyewitness",
+            ToolProfile::Hydra => "hydra",
+            ToolProfile::Dnsrecon => "dnsrecon",
+            ToolProfile::Dnsx => "dnsx",
+            ToolProfile::Subfinder => "subfinder",
+        }
+    }
+
+    pub fn default_max_rate(self) -> u32 {
+        match self {
+            ToolProfile::Nuclei => 50,
+            ToolProfile::Masscan => 1000,
+            ToolProfile::Dnsx => 100,
+            ToolProfile::Capture
+            | ToolProfile::Gowitness
+            | ToolProfile::Eyewitness
+            | ToolProfile::Hydra
+            | ToolProfile::Dnsrecon
+            | ToolProfile::Subfinder => 0,
+        }
+    }
+
+    pub fn requires_scope(self) -> bool {
+        matches!(self, ToolProfile::Masscan | ToolProfile::Hydra)
+    }
+
+    pub fn requires_approval(self) -> bool {
+        matches!(self, ToolProfile::Hydra)
+    }
+
+    pub fn streams(self) -> bool {
+        !matches!(self, ToolProfile::Hydra)
+    }
+
+    pub fn dns(self) -> bool {
+        matches!(
+            self,
+            ToolProfile::Dnsrecon | ToolProfile::Dnsx | ToolProfile::Subfinder
+        )
+    }
+
+    pub fn results_command(self) -> Option<String> {
+        let file = match self {
+            ToolProfile::Hydra => HYDRA_RESULTS,
+            ToolProfile::Dnsrecon => DNSRECON_RESULTS,
+            ToolProfile::Dnsx => DNSX_RESULTS,
+            ToolProfile::Subfinder => SUBFINDER_RESULTS,
+            _ => return None,
+        };
+        Some(format!("cat {} 2>/dev/null || true", file))
+    }
+
+    pub fn artifacts(self) -> &'static [&'static str] {
+        match self {
+            ToolProfile::Capture => &[CAPTURE_FILE],
+            ToolProfile::Gowitness => &["screenshots/*", GOWITNESS_RESULTS],
+            ToolProfile::Eyewitness => &["eyewitness/screens/*"],
+            _ => &[],
+        }
+    }
+
+    pub fn mcp_properties(self) -> Value {
+        match self {
+            ToolProfile::Nuclei => json!({
+                "targets": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "URLs, hosts or IPs to scan"},
+                "tags": {"type": "array", "items": {"type": "string"}, "description": "Template tags to include, e.g. cve, exposure"},
+                "exclude_tags": {"type": "array", "items": {"type": "string"}},
+                "template_ids": {"type": "array", "items": {"type": "string"}, "description": "Run only these template IDs"},
+                "severity": {
+                    "type": "array",
+                    "items": {"type": "string", "enum": ["info", "low", "medium", "high", "critical"]}
+                },
+                "rate_limit": {"type": "integer", "minimum": 1, "description": "Requests per second, capped by the bridge"}
+            }),
+            ToolProfile::Masscan => json!({
+                "targets": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "minItems": 1,
+                    "description": "IPs, CIDR networks or ranges (a.b.c.d-e.f.g.h) inside the engagement scope"
+                },
+                "ports": {"type": "string", "description": "Port list, e.g. 80,443,8000-8100"},
+                "rate": {"type": "integer", "minimum": 1, "description": "Packets per second, capped by the bridge"},
+                "banners": {"type": "boolean", "description": "Grab service banners"}
+            }),
+            ToolProfile::Capture => json!({
+                "interface": {"type": "string", "description": "Capture interface (must be allowed by policy)"},
+                "duration_sec": {"type": "integer", "minimum": 1, "description": "Capture duration, capped by policy"},
+                "max_packets": {"type": "integer", "minimum": 1, "description": "Stop after this many packets, capped by policy"},
+                "snaplen": {"type": "integer", "minimum": 64, "description": "Bytes captured per packet, capped by policy"},
+                "hosts": {"type": "array", "items": {"type": "string"}, "description": "Only traffic to/from these IPs or CIDR networks"},
+                "ports": {"type": "array", "items": {"type": "integer", "minimum": 1, "maximum": 65535}},
+                "protocol": {"type": "string", "enum": ["tcp", "udp", "icmp", "icmp6", "arp"]}
+            }),
+            ToolProfile::Gowitness | ToolProfile::Eyewitness => json!({
+                "targets": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "http(s) URLs to screenshot"},
+                "page_timeout_sec": {"type": "integer", "minimum": 1, "description": "Per-page timeout, capped by policy"}
+            }),
+            ToolProfile::Hydra => json!({
+                "service": {"type": "string", "description": "Hydra service module allowed by policy, e.g. ssh, ftp, smb, http-post-form"},
+                "target": {"type": "string", "description": "Single IP or hostname inside the engagement scope"},
+                "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+                "login": {"type": "string", "description": "Single login name (alternative to login_list)"},
+                "login_list": {"type": "string", "description": "Logical wordlist name with login names"},
+                "password_list": {"type": "string", "description": "Logical wordlist name with passwords"},
+                "threads": {"type": "integer", "minimum": 1, "description": "Parallel tasks, capped by policy"},
+                "max_attempts": {"type": "integer", "minimum": 1, "description": "Abort before starting if user x password combinations exceed this, capped by policy"},
+                "stop_on_first": {"type": "boolean", "description": "Stop after the first valid credential (default true)"},
+                "options": {"type": "string", "description": "Module options, e.g. /login:user=^USER^&pass=^PASS^:F=failed for http-post-form"}
+            }),
+            ToolProfile::Dnsrecon => json!({
+                "domain": {"type": "string", "description": "Domain to enumerate, inside the engagement scope"},
+                "types": {
+                    "type": "array",
+                    "items": {"type": "string", "enum": ["std", "brt", "axfr", "srv", "crt", "zonewalk"]},
+                    "description": "Enumeration types (default std)"
+                },
+                "wordlist": {"type": "string", "description": "Logical wordlist name for brt"},
+                "list": {"type": "string", "description": "Merge discovered names into this stored target list"}
+            }),
+            ToolProfile::Dnsx => json!({
+                "names": {"type": "array", "items": {"type": "string"}, "description": "Host names to resolve"},
+                "domain": {"type": "string", "description": "Domain to brute-force with wordlist (instead of names)"},
+                "wordlist": {"type": "string", "description": "Logical wordlist name with subdomain labels"},
+                "record_types": {
+                    "type": "array",
+                    "items": {"type": "string", "enum": DNS_RECORD_TYPES},
+                    "description": "Record types to query (default a, aaaa, cname)"
+                },
+                "rate_limit": {"type": "integer", "minimum": 1, "description": "Queries per second, capped by the bridge"},
+                "list": {"type": "string", "description": "Merge resolved names into this stored target list"}
+            }),
+            ToolProfile::Subfinder => json!({
+                "domains": {"type": "array", "items": {"type": "string"}, "minItems": 1, "description": "Root domains inside the engagement scope"},
+                "all": {"type": "boolean", "description": "Use all passive sources (slower)"},
+                "recursive": {"type": "boolean", "description": "Only use sources that support recursive enumeration"},
+                "list": {"type": "string", "description": "Merge discovered names into this stored target list"}
+            }),
+        }
+    }
+
+    pub fn build_args(
+        self,
+        max_rate: Option<u32>,
+        capture: &CaptureConfig,
+        screenshots: &ScreenshotConfig,
+        hydra: &HydraConfig,
+        params: &BTreeMap<String, Value>,
+    ) -> Result<ProfileRun> {
+        let params = Value::Object(params.clone().into_iter().collect());
+        let cap = max_rate.unwrap_or(self.default_max_rate());
+        let run = match self {
+            ToolProfile::Nuclei => {
+                let params = parse_params::<NucleiParams>("nuclei", params)?;
+                if params.targets.is_empty() {
+                    bail!(missing("nuclei", "targets"));
+                }
+                for target in &params.targets {
+                    if target.is_empty()
+                        || target.starts_with('-')
+                        || target.contains([',', ' ', '\n', '\t'])
+                    {
+                        bail!(invalid("target", target));
+                    }
+                }
+                let mut args = vec![
+                    "-jsonl".to_string(),
+                    "-silent".to_string(),
+                    "-no-color".to_string(),
+                    "-disable-update-check".to_string(),
+                    format!("-rate-limit={}", rate(self, params.rate_limit, cap)),
+                    format!("-u={}", params.targets.join(",")),
+                ];
+                for (flag, values) in [
+                    ("tags", &params.tags),
+                    ("exclude-tags", &params.exclude_tags),
+                    ("id", &params.template_ids),
+                ] {
+                    if values.is_empty() {
+                        continue;
+                    }
+                    if let Some(value) = values.iter().find(|value| !is_identifier(value)) {
+                        bail!(invalid(flag, value));
+                    }
+                    args.push(format!("-{}={}", flag, values.join(",")));
+                }
+                if !params.severity.is_empty() {
+                    let severity = params
+                        .severity
+                        .iter()
+                        .map(|severity| severity.as_str())
+                        .collect::<Vec<_>>();
+                    args.push(format!("-severity={}", severity.join(",")));
+                }
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin: None,
+                    hosts: Vec::new(),
+                }
+            }
+            ToolProfile::Masscan => {
+                let params = parse_params::<MasscanParams>("masscan", params)?;
+                if params.targets.is_empty() {
+                    bail!(missing("masscan", "targets"));
+                }
+                for target in &params.targets {
+                    if !is_address_range(target) {
+                        bail!(invalid("target", target));
+                    }
+                }
+                if params.ports.is_empty()
+                    || !params
+                        .ports
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || matches!(c, ',' | '-'))
+                {
+                    bail!(invalid("ports", &params.ports));
+                }
+                let mut args = vec![
+                    "-oL".to_string(),
+                    "-".to_string(),
+                    format!("--rate={}", rate(self, params.rate, cap)),
+                    format!("--ports={}", params.ports),
+                ];
+                if params.banners {
+                    args.push("--banners".to_string());
+                }
+                args.extend(params.targets);
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin: None,
+                    hosts: Vec::new(),
+                }
+            }
+            ToolProfile::Capture => {
+                let params = parse_params::<CaptureParams>("capture", params)?;
+                let interface = match params.interface {
+                    Some(interface) if capture.interfaces.contains(&interface) => interface,
+                    Some(interface) => bail!(Message::NotReleased {
+                        key: "capture.interfaces".to_string(),
+                        name: interface
+                    }),
+                    None => capture.interfaces.first().cloned().ok_or_else(|| {
+                        Message::NotConfigured {
+                            key: "capture.interfaces".to_string(),
+                        }
+                    })?,
+                };
+                let duration = params
+                    .duration_sec
+                    .unwrap_or(DEFAULT_CAPTURE_SEC)
+                    .clamp(1, capture.max_duration_sec);
+                let packets = params
+                    .max_packets
+                    .unwrap_or(capture.max_packets)
+                    .clamp(1, capture.max_packets);
+                let snaplen = params
+                    .snaplen
+                    .unwrap_or(capture.max_snaplen)
+                    .clamp(64, capture.max_snaplen);
+                let mut args = vec![
+                    "-i".to_string(),
+                    interface,
+                    "-n".to_string(),
+                    "-U".to_string(),
+                    "-s".to_string(),
+                    snaplen.to_string(),
+                    "-c".to_string(),
+                    packets.to_string(),
+                    "-G".to_string(),
+                    duration.to_string(),
+                    "-W".to_string(),
+                    "1".to_string(),
+                    "-w".to_string(),
+                    CAPTURE_FILE.to_string(),
+                ];
+                let mut clauses = Vec::new();
+                if !params.hosts.is_empty() {
+                    let mut hosts = Vec::new();
+                    for host in &params.hosts {
+                        let keyword = match host.split_once('/') {
+                            Some(_) if is_address_range(host) => "net",
+                            None if host.parse::<IpAddr>().is_ok() => "host",
+                            _ => bail!(invalid("host", host)),
+                        };
+                        hosts.push(vec![keyword.to_string(), host.clone()]);
+                    }
+                    clauses.push(hosts);
+                }
+                if !params.ports.is_empty() {
+                    clauses.push(
+                        params
+                            .ports
+                            .iter()
+                            .map(|port| vec!["port".to_string(), port.to_string()])
+                            .collect(),
+                    );
+                }
+                if let Some(protocol) = params.protocol {
+                    clauses.push(vec![vec![protocol.as_str().to_string()]]);
+                }
+                let base = capture
+                    .base_filter
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                if !base.is_empty() {
+                    clauses.push(vec![base]);
+                }
+                for (index, clause) in clauses.into_iter().enumerate() {
+                    if index > 0 {
+                        args.push("and".to_string());
+                    }
+                    args.push("(".to_string());
+                    for (index, term) in clause.into_iter().enumerate() {
+                        if index > 0 {
+                            args.push("or".to_string());
+                        }
+                        args.extend(term);
+                    }
+                    args.push(")".to_string());
+                }
+                ProfileRun {
+                    args,
+                    timeout_sec: Some(duration + CAPTURE_TIMEOUT_SLACK_SEC),
+                    stdin: None,
+                    hosts: Vec::new(),
+                }
+            }
+            ToolProfile::Gowitness | ToolProfile::Eyewitness => {
+                let params = parse_params::<ScreenshotParams>(self.as_str(), params)?;
+                if params.targets.is_empty() {
+                    bail!(missing(self.as_str(), "targets"));
+                }
+                if params.targets.len() > screenshots.max_targets {
+                    bail!(Message::LimitExceeded {
+                        field: "targets",
+                        value: params.targets.len(),
+                        max: screenshots.max_targets
+                    });
+                }
+                for target in &params.targets {
+                    let valid = (target.starts_with("http://") || target.starts_with("https://"))
+                        && !target.chars().any(|c| c.is_whitespace() || c.is_control());
+                    if !valid {
+                        bail!(invalid("target", target));
+                    }
+                }
+                let page_timeout = params
+                    .page_timeout_sec
+                    .unwrap_or(screenshots.page_timeout_sec)
+                    .clamp(1, screenshots.page_timeout_sec);
+                let args = match self {
+                    ToolProfile::Gowitness => vec![
+                        "scan".to_string(),
+                        "file".to_string(),
+                        "--file=-".to_string(),
+                        "--screenshot-path=screenshots".to_string(),
+                        "--screenshot-format=png".to_string(),
+                        "--write-jsonl".to_string(),
+                        format!("--write-jsonl-file={}", GOWITNESS_RESULTS),
+                        format!("--timeout={}", page_timeout),
+                    ],
+                    _ => vec![
+                        "--web".to_string(),
+                        "--no-prompt".to_string(),
+                        "-f".to_string(),
+                        "/dev/stdin".to_string(),
+                        "-d".to_string(),
+                        "eyewitness".to_string(),
+                        "--timeout".to_string(),
+                        page_timeout.to_string(),
+                    ],
+                };
+                let mut stdin = params.targets.join("\n");
+                stdin.push('\n');
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin: Some(stdin),
+                    hosts: Vec::new(),
+                }
+            }
+            ToolProfile::Hydra => {
+                let params = hydra.params(params)?;
+                let threads = params
+                    .threads
+                    .unwrap_or(hydra.max_threads)
+                    .clamp(1, hydra.max_threads);
+                let mut args = vec![
+                    "-I".to_string(),
+                    "-o".to_string(),
+                    HYDRA_RESULTS.to_string(),
+                    "-b".to_string(),
+                    "jsonv1".to_string(),
+                    "-t".to_string(),
+                    threads.to_string(),
+                ];
+                if params.stop_on_first {
+                    args.push("-f".to_string());
+                }
+                match (&params.login, &params.login_list) {
+                    (Some(login), None) => args.extend(["-l".to_string(), login.clone()]),
+                    (None, Some(list)) => {
+                        args.extend(["-L".to_string(), crate::wordlists::placeholder(list)])
+                    }
+                    _ => bail!(Message::ExactlyOneOf {
+                        tool: "hydra".to_string(),
+                        options: "login, login_list"
+                    }),
+                }
+                args.extend([
+                    "-P".to_string(),
+                    crate::wordlists::placeholder(&params.password_list),
+                ]);
+                if let Some(port) = params.port {
+                    args.extend(["-s".to_string(), port.to_string()]);
+                }
+                if params.target.parse::<IpAddr>().is_ok_and(|ip| ip.is_ipv6()) {
+                    args.push("-6".to_string());
+                }
+                args.push(params.target);
+                args.push(params.service);
+                args.extend(params.options);
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin: None,
+                    hosts: Vec::new(),
+                }
+            }
+            ToolProfile::Dnsrecon => {
+                let params = parse_params::<DnsreconParams>("dnsrecon", params)?;
+                check_domain(&params.domain)?;
+                check_list(params.list.as_deref())?;
+                let types = match params.types.is_empty() {
+                    true => vec![DnsreconType::Std],
+                    false => params.types,
+                };
+                let mut args = vec![
+                    "-d".to_string(),
+                    params.domain.clone(),
+                    "-t".to_string(),
+                    types
+                        .iter()
+                        .map(|kind| kind.as_str())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    "-j".to_string(),
+                    DNSRECON_RESULTS.to_string(),
+                ];
+                match (types.contains(&DnsreconType::Brt), &params.wordlist) {
+                    (true, Some(wordlist)) => {
+                        check_wordlist(wordlist)?;
+                        args.extend(["-D".to_string(), crate::wordlists::placeholder(wordlist)]);
+                    }
+                    (true, None) => bail!(missing("dnsrecon -t brt", "wordlist")),
+                    (false, Some(_)) => bail!(Message::ArgumentNotApplicable {
+                        tool: "dnsrecon without -t brt".to_string(),
+                        argument: "wordlist"
+                    }),
+                    (false, None) => {}
+                }
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin: None,
+                    hosts: vec![params.domain],
+                }
+            }
+            ToolProfile::Dnsx => {
+                let params = parse_params::<DnsxParams>("dnsx", params)?;
+                check_list(params.list.as_deref())?;
+                let mut args = vec![
+                    "-silent".to_string(),
+                    "-json".to_string(),
+                    "-o".to_string(),
+                    DNSX_RESULTS.to_string(),
+                    "-disable-update-check".to_string(),
+                    format!("-rate-limit={}", rate(self, params.rate_limit, cap)),
+                ];
+                let types = match params.record_types.is_empty() {
+                    true => vec!["a".to_string(), "aaaa".to_string(), "cname".to_string()],
+                    false => params.record_types,
+                };
+                for kind in &types {
+                    if !DNS_RECORD_TYPES.contains(&kind.as_str()) {
+                        bail!(invalid("record_type", kind));
+                    }
+                    args.push(format!("-{}", kind));
+                }
+                let (stdin, hosts) = match (params.names.is_empty(), params.domain, params.wordlist)
+                {
+                    (false, None, None) => {
+                        for name in &params.names {
+                            check_domain(name)?;
+                        }
+                        let mut stdin = params.names.join("\n");
+                        stdin.push('\n');
+                        (Some(stdin), params.names)
+                    }
+                    (true, Some(domain), Some(wordlist)) => {
+                        check_domain(&domain)?;
+                        check_wordlist(&wordlist)?;
+                        args.extend([
+                            "-d".to_string(),
+                            domain.clone(),
+                            "-w".to_string(),
+                            crate::wordlists::placeholder(&wordlist),
+                        ]);
+                        (None, vec![domain])
+                    }
+                    _ => bail!(Message::ExactlyOneOf {
+                        tool: "dnsx".to_string(),
+                        options: "names, domain with wordlist"
+                    }),
+                };
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin,
+                    hosts,
+                }
+            }
+            ToolProfile::Subfinder => {
+                let params = parse_params::<SubfinderParams>("subfinder", params)?;
+                check_list(params.list.as_deref())?;
+                if params.domains.is_empty() {
+                    bail!(missing("subfinder", "domains"));
+                }
+                let mut args = vec![
+                    "-silent".to_string(),
+                    "-oJ".to_string(),
+                    "-o".to_string(),
+                    SUBFINDER_RESULTS.to_string(),
+                    "-disable-update-check".to_string(),
+                ];
+                for domain in &params.domains {
+                    check_domain(domain)?;
+                    args.extend(["-d".to_string(), domain.clone()]);
+                }
+                if params.all {
+                    args.push("-all".to_string());
+                }
+                if params.recursive {
+                    args.push("-recursive".to_string());
+                }
+                ProfileRun {
+                    args,
+                    timeout_sec: None,
+                    stdin: None,
+                    hosts: params.domains,
+                }
+            }
+        };
+        Ok(run)
+    }
+
+    pub fn attempt_budget(
+        self,
+        hydra: &HydraConfig,
+        params: &BTreeMap<String, Value>,
+    ) -> Result<Option<AttemptBudget>> {
+        if self != ToolProfile::Hydra {
+            return Ok(None);
+        }
+        let params = hydra.params(Value::Object(params.clone().into_iter().collect()))?;
+        Ok(Some(AttemptBudget {
+            login_list: params.login_list,
+            password_list: params.password_list,
+            max_attempts: params
+                .max_attempts
+                .unwrap_or(hydra.max_attempts)
+                .clamp(1, hydra.max_attempts),
+        }))
+    }
+
+    pub fn parse_findings(self, stdout: &str) -> Option<Vec<Finding>> {
+        match self {
+            ToolProfile::Nuclei => Some(
+                stdout
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+                    .filter(|record| record.get("template-id").is_some())
+                    .map(nuclei_finding)
+                    .collect(),
+            ),
+            ToolProfile::Masscan => Some(masscan_findings(stdout)),
+            _ => None,
+        }
+    }
+
+    pub fn dns_names(self, data: &str) -> Option<BTreeMap<String, DnsName>> {
+        let source = self.as_str();
+        let mut names = BTreeMap::new();
+        let lines = || {
+            data.lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line.trim()).ok())
+        };
+        match self {
+            ToolProfile::Subfinder => {
+                for record in lines() {
+                    if let Some(entry) = record["host"]
+                        .as_str()
+                        .and_then(|host| targets::dns_entry(&mut names, host, source))
+                        && let Some(ip) = record["ip"].as_str()
+                    {
+                        entry.add("a", ip);
+                    }
+                }
+            }
+            ToolProfile::Dnsx => {
+                for record in lines() {
+                    let values = DNS_RECORD_TYPES
+                        .iter()
+                        .flat_map(|kind| {
+                            record[*kind]
+                                .as_array()
+                                .into_iter()
+                                .flatten()
+                                .filter_map(Value::as_str)
+                                .map(move |value| (*kind, value))
+                        })
+                        .collect::<Vec<_>>();
+                    if values.is_empty() {
+                        continue;
+                    }
+                    if let Some(entry) = record["host"]
+                        .as_str()
+                        .and_then(|host| targets::dns_entry(&mut names, host, source))
+                    {
+                        for (kind, value) in values {
+                            entry.add(kind, value);
+                        }
+                    }
+                }
+            }
+            ToolProfile::Dnsrecon => {
+                for record in serde_json::from_str::<Vec<Value>>(data).unwrap_or_default() {
+                    dnsrecon_record(&mut names, &record);
+                }
+            }
+            _ => return None,
+        }
+        Some(names)
+    }
+
+    pub fn screenshots(
+        self,
+        config: &ScreenshotConfig,
+        files: &[(&str, &[u8])],
+    ) -> Option<Screenshots> {
+        if !matches!(self, ToolProfile::Gowitness | ToolProfile::Eyewitness) {
+            return None;
+        }
+        let mut images = Vec::new();
+        let mut skipped = Vec::new();
+        for (name, data) in files {
+            let Some(mime_type) = image_mime_type(data) else {
+                continue;
+            };
+            if images.len() >= config.max_images || data.len() > config.max_image_bytes {
+                skipped.push(name.to_string());
+                continue;
+            }
+            images.push(Image {
+                name: name.to_string(),
+                mime_type,
+                data: data.to_vec(),
+            });
+        }
+        let pages = files
+            .iter()
+            .filter(|(name, _)| *name == GOWITNESS_RESULTS)
+            .flat_map(|(_, data)| {
+                String::from_utf8_lossy(data)
+                    .lines()
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+            .map(|record| {
+                json!({
+                    "url": record["url"],
+                    "final_url": record["final_url"],
+                    "status": record["response_code"],
+                    "title": record["title"],
+                    "failed": record["failed"],
+                    "screenshot": record["file_name"]
+                })
+            })
+            .collect::<Vec<_>>();
+        let summary = json!({
+            "pages": pages,
+            "images": images
+                .iter()
+                .map(|image| json!({"name": image.name, "mime_type": image.mime_type, "bytes": image.data.len()}))
+                .collect::<Vec<_>>(),
+            "skipped_images": skipped
+        });
+        Some(Screenshots { images, summary })
+    }
+}
+
+pub struct Screenshots {
+    pub images: Vec<Image>,
+    pub summary: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub name: String,
+    pub mime_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+fn image_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenshotConfig {
+    #[serde(default = "default_screenshot_targets")]
+    pub max_targets: usize,
+    #[serde(default = "default_screenshot_images")]
+    pub max_images: usize,
+    #[serde(default = "default_screenshot_image_bytes")]
+    pub max_image_bytes: usize,
+    #[serde(default = "default_page_timeout")]
+    pub page_timeout_sec: u64,
+}
+
+fn default_screenshot_targets() -> usize {
+    20
+}
+
+fn default_screenshot_images() -> usize {
+    8
+}
+
+fn default_screenshot_image_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_page_timeout() -> u64 {
+    30
+}
+
+impl Default for ScreenshotConfig {
+    fn default() -> Self {
+        Self {
+            max_targets: default_screenshot_targets(),
+            max_images: default_screenshot_images(),
+            max_image_bytes: default_screenshot_image_bytes(),
+            page_timeout_sec: default_page_timeout(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScreenshotParams {
+    #[serde(default)]
+    targets: Vec<String>,
+    page_timeout_sec: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HydraConfig {
+    #[serde(default = "default_hydra_services")]
+    pub services: Vec<String>,
+    #[serde(default = "default_hydra_attempts")]
+    pub max_attempts: u64,
+    #[serde(default = "default_hydra_threads")]
+    pub max_threads: u32,
+}
+
+fn default_hydra_services() -> Vec<String> {
+    [
+        "ssh",
+        "ftp",
+        "telnet",
+        "smb",
+        "rdp",
+        "mysql",
+        "postgres",
+        "mssql",
+        "vnc",
+        "pop3",
+        "imap",
+        "smtp",
+        "http-get",
+        "https-get",
+        "http-post-form",
+        "https-post-form",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+fn default_hydra_attempts() -> u64 {
+    500
+}
+
+fn default_hydra_threads() -> u32 {
+    4
+}
+
+impl Default for HydraConfig {
+    fn default() -> Self {
+        Self {
+            services: default_hydra_services(),
+            max_attempts: default_hydra_attempts(),
+            max_threads: default_hydra_threads(),
+        }
+    }
+}
+
+impl HydraConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(service) = self.services.iter().find(|service| !is_identifier(service)) {
+            bail!("ungültiger hydra-Dienst '{}'", service);
+        }
+        if self.max_attempts == 0 || self.max_threads == 0 {
+            bail!("max_attempts und max_threads müssen größer als 0 sein");
+        }
+        Ok(())
+    }
+
+    fn params(&self, params: Value) -> Result<HydraParams> {
+        let params = parse_params::<HydraParams>("hydra", params)?;
+        if !self.services.contains(&params.service) {
+            bail!(Message::NotReleased {
+                key: "hydra.services".to_string(),
+                name: params.service
+            });
+        }
+        if !is_identifier(&params.target) || params.target.contains('/') {
+            bail!(invalid("target", &params.target));
+        }
+        if let Some(login) = &params.login
+            && (login.is_empty() || login.starts_with('-') || login.chars().any(char::is_control))
+        {
+            bail!(invalid("login", login));
+        }
+        for list in params.login_list.iter().chain([&params.password_list]) {
+            if list.is_empty() || list.contains(['{', '}']) || list.chars().any(char::is_control) {
+                bail!(invalid("wordlist", list));
+            }
+        }
+        let form = params.service.ends_with("-form");
+        match &params.options {
+            Some(options) if options.starts_with('-') || options.chars().any(char::is_control) => {
+                bail!(invalid("options", options))
+            }
+            None if form => bail!(missing(&params.service, "options")),
+            _ => {}
+        }
+        Ok(params)
+    }
+}
+
+pub fn hydra_credentials(data: &str) -> Vec<Credential> {
+    let Ok(report) = serde_json::from_str::<Value>(data) else {
+        return Vec::new();
+    };
+    report["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|result| {
+            Some(Credential {
+                host: result["host"].as_str()?.to_string(),
+                port: result["port"]
+                    .as_u64()
+                    .and_then(|port| u16::try_from(port).ok()),
+                service: result["service"].as_str().unwrap_or_default().to_string(),
+                login: result["login"].as_str()?.to_string(),
+                password: result["password"].as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub struct AttemptBudget {
+    pub login_list: Option<String>,
+    pub password_list: String,
+    pub max_attempts: u64,
+}
+
+pub struct Credential {
+    pub host: String,
+    pub port: Option<u16>,
+    pub service: String,
+    pub login: String,
+    pub password: String,
+}
+
+impl Credential {
+    pub fn finding(&self, secret: Option<&str>) -> Finding {
+        let mut finding = Finding::new(
+            "hydra",
+            "credential",
+            Severity::Critical,
+            format!("Gültige Zugangsdaten für {} ({})", self.service, self.login),
+        );
+        finding.host = Some(self.host.clone());
+        finding.port = self.port;
+        finding.protocol = Some(self.service.clone());
+        finding.data = json!({
+            "login": self.login,
+            "service": self.service,
+            "secret": secret,
+            "stored": secret.is_some()
+        });
+        finding
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HydraParams {
+    service: String,
+    target: String,
+    port: Option<u16>,
+    login: Option<String>,
+    login_list: Option<String>,
+    password_list: String,
+    threads: Option<u32>,
+    max_attempts: Option<u64>,
+    #[serde(default = "default_stop_on_first")]
+    stop_on_first: bool,
+    options: Option<String>,
+}
+
+fn default_stop_on_first() -> bool {
+    true
+}
+
+pub struct ProfileRun {
+    pub args: Vec<String>,
+    pub timeout_sec: Option<u64>,
+    pub stdin: Option<String>,
+    pub hosts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureConfig {
+    #[serde(default = "default_capture_interfaces")]
+    pub interfaces: Vec<String>,
+    #[serde(default = "default_capture_duration")]
+    pub max_duration_sec: u64,
+    #[serde(default = "default_capture_packets")]
+    pub max_packets: u64,
+    #[serde(default = "default_capture_snaplen")]
+    pub max_snaplen: u32,
+    #[serde(default = "default_capture_filter")]
+    pub base_filter: String,
+    #[serde(default)]
+    pub summarizer: Option<CaptureSummarizer>,
+    #[serde(default = "default_tshark_fields")]
+    pub tshark_fields: Vec<String>,
+    #[serde(default = "default_summary_rows")]
+    pub max_rows: usize,
+}
+
+fn default_capture_interfaces() -> Vec<String> {
+    vec!["eth0".to_string()]
+}
+
+fn default_capture_duration() -> u64 {
+    300
+}
+
+fn default_capture_packets() -> u64 {
+    100_000
+}
+
+fn default_capture_snaplen() -> u32 {
+    65_535
+}
+
+fn default_capture_filter() -> String {
+    "not port 22".to_string()
+}
+
+fn default_tshark_fields() -> Vec<String> {
+    [
+        "frame.time_epoch",
+        "ip.src",
+        "ip.dst",
+        "_ws.col.Protocol",
+        "frame.len",
+        "_ws.col.Info",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+fn default_summary_rows() -> usize {
+    200
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            interfaces: default_capture_interfaces(),
+            max_duration_sec: default_capture_duration(),
+            max_packets: default_capture_packets(),
+            max_snaplen: default_capture_snaplen(),
+            base_filter: default_capture_filter(),
+            summarizer: None,
+            tshark_fields: default_tshark_fields(),
+            max_rows: default_summary_rows(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureSummarizer {
+    Capinfos,
+    Tshark,
+}
+
+impl CaptureConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(interface) = self
+            .interfaces
+            .iter()
+            .find(|interface| !is_identifier(interface))
+        {
+            bail!("ungültiges Interface '{}'", interface);
+        }
+        if self.max_duration_sec == 0 || self.max_packets == 0 || self.max_snaplen < 64 {
+            bail!(
+                "max_duration_sec und max_packets müssen größer als 0 sein, max_snaplen mindestens 64"
+            );
+        }
+        if self.base_filter.chars().any(|c| {
+            !(c.is_ascii_alphanumeric() || matches!(c, ' ' | '.' | '/' | ':' | '(' | ')' | '!'))
+        }) {
+            bail!("base_filter enthält unzulässige Zeichen");
+        }
+        if let Some(field) = self
+            .tshark_fields
+            .iter()
+            .find(|field| !is_identifier(field))
+        {
+            bail!("ungültiges tshark-Feld '{}'", field);
+        }
+        if self.max_rows == 0 {
+            bail!("max_rows muss größer als 0 sein");
+        }
+        Ok(())
+    }
+
+    pub fn summary_command(&self) -> Option<String> {
+        match self.summarizer? {
+            CaptureSummarizer::Capinfos => Some(format!("capinfos -M -T {}", CAPTURE_FILE)),
+            CaptureSummarizer::Tshark => Some(format!(
+                "tshark -n -r {} -c {} -T fields -E header=y -E separator=/t -E occurrence=f {}",
+                CAPTURE_FILE,
+                self.max_rows,
+                self.tshark_fields
+                    .iter()
+                    .map(|field| format!("-e {}", field))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )),
+        }
+    }
+
+    pub fn parse_summary(&self, stdout: &str) -> Option<Value> {
+        let mut lines = stdout.lines().filter(|line| !line.trim().is_empty());
+        let header = lines.next()?.split('\t').map(str::trim).collect::<Vec<_>>();
+        let rows = lines
+            .map(|line| {
+                header
+                    .iter()
+                    .zip(line.split('\t'))
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(name, value)| (name.to_string(), typed(value)))
+                    .collect::<serde_json::Map<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        match self.summarizer? {
+            CaptureSummarizer::Capinfos => rows.into_iter().next().map(Value::Object),
+            CaptureSummarizer::Tshark => Some(json!({
+                "fields": header,
+                "rows": rows,
+                "truncated": rows.len() >= self.max_rows
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CaptureProtocol {
+    Tcp,
+    Udp,
+    Icmp,
+    Icmp6,
+    Arp,
+}
+
+impl CaptureProtocol {
+    fn as_str(self) -> &'static str {
+        match self {
+            CaptureProtocol::Tcp => "tcp",
+            CaptureProtocol::Udp => "udp",
+            CaptureProtocol::Icmp => "icmp",
+            CaptureProtocol::Icmp6 => "icmp6",
+            CaptureProtocol::Arp => "arp",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CaptureParams {
+    interface: Option<String>,
+    duration_sec: Option<u64>,
+    max_packets: Option<u64>,
+    snaplen: Option<u32>,
+    #[serde(default)]
+    hosts: Vec<String>,
+    #[serde(default)]
+    ports: Vec<u16>,
+    protocol: Option<CaptureProtocol>,
+}
+
+fn typed(value: &str) -> Value {
+    let value = value.trim();
+    match value.parse::<i64>() {
+        Ok(number) => json!(number),
+        Err(_) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .map_or_else(|| json!(value), |number| json!(number)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DnsreconType {
+    Std,
+    Brt,
+    Axfr,
+    Srv,
+    Crt,
+    Zonewalk,
+}
+
+impl DnsreconType {
+    fn as_str(self) -> &'static str {
+        match self {
+            DnsreconType::Std => "std",
+            DnsreconType::Brt => "brt",
+            DnsreconType::Axfr => "axfr",
+            DnsreconType::Srv => "srv",
+            DnsreconType::Crt => "crt",
+            DnsreconType::Zonewalk => "zonewalk",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DnsreconParams {
+    domain: String,
+    #[serde(default)]
+    types: Vec<DnsreconType>,
+    wordlist: Option<String>,
+    list: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DnsxParams {
+    #[serde(default)]
+    names: Vec<String>,
+    domain: Option<String>,
+    wordlist: Option<String>,
+    #[serde(default)]
+    record_types: Vec<String>,
+    rate_limit: Option<u32>,
+    list: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SubfinderParams {
+    #[serde(default)]
+    domains: Vec<String>,
+    #[serde(default)]
+    all: bool,
+    #[serde(default)]
+    recursive: bool,
+    list: Option<String>,
+}
+
+fn check_domain(domain: &str) -> Result<()> {
+    if !targets::is_hostname(domain) || domain.parse::<IpAddr>().is_ok() {
+        bail!(invalid("domain", domain));
+    }
+    Ok(())
+}
+
+fn check_list(list: Option<&str>) -> Result<()> {
+    match list {
+        Some(list) if !targets::is_name(list) => bail!(invalid("list", list)),
+        _ => Ok(()),
+    }
+}
+
+fn check_wordlist(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(['{', '}']) || name.chars().any(char::is_control) {
+        bail!(invalid("wordlist", name));
+    }
+    Ok(())
+}
+
+fn parse_params<T: DeserializeOwned>(tool: &str, params: Value) -> Result<T> {
+    serde_json::from_value(params).map_err(|error| {
+        Message::InvalidArguments {
+            tool: tool.to_string(),
+            error: error.to_string(),
+        }
+        .into()
+    })
+}
+
+fn missing(tool: &str, argument: &'static str) -> Message {
+    Message::MissingArgument {
+        tool: tool.to_string(),
+        argument,
+    }
+}
+
+fn invalid(field: &'static str, value: &str) -> Message {
+    Message::InvalidValue {
+        field,
+        value: value.to_string(),
+    }
+}
+
+fn dnsrecon_record(names: &mut BTreeMap<String, DnsName>, record: &Value) {
+    let source = ToolProfile::Dnsrecon.as_str();
+    let kind = record["type"]
+        .as_str()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let address = record["address"].as_str();
+    let field = |name: &str| record[name].as_str();
+    match kind.as_str() {
+        "a" | "aaaa" | "ptr" | "cname" | "txt" | "spf" => {
+            let value = match kind.as_str() {
+                "cname" => field("target"),
+                "txt" | "spf" => field("strings").or(field("text")),
+                _ => address,
+            };
+            if let (Some(entry), Some(value)) = (
+                field("name").and_then(|name| targets::dns_entry(names, name, source)),
+                value,
+            ) {
+                entry.add(if kind == "spf" { "txt" } else { &kind }, value);
+            }
+        }
+        "ns" | "mx" | "soa" | "srv" => {
+            let host = match kind.as_str() {
+                "mx" => field("exchange"),
+                "soa" => field("mname"),
+                _ => field("target"),
+            };
+            let Some(host) = host else {
+                return;
+            };
+            if let Some(entry) = targets::dns_entry(names, host, source) {
+                if let Some(address) = address {
+                    entry.add("a", address);
+                }
+                if kind == "srv" {
+                    let service = field("name").unwrap_or_default();
+                    let port = record["port"]
+                        .as_str()
+                        .map(str::to_string)
+                        .or(record["port"].as_u64().map(|port| port.to_string()));
+                    entry.add("srv", &format!("{}:{}", service, port.unwrap_or_default()));
+                }
+            }
+            if kind != "srv"
+                && let Some(entry) =
+                    field("domain").and_then(|domain| targets::dns_entry(names, domain, source))
+            {
+                entry.add(&kind, host);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NucleiParams {
+    #[serde(default)]
+    targets: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    exclude_tags: Vec<String>,
+    #[serde(default)]
+    template_ids: Vec<String>,
+    #[serde(default)]
+    severity: Vec<Severity>,
+    rate_limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MasscanParams {
+    #[serde(default)]
+    targets: Vec<String>,
+    #[serde(default)]
+    ports: String,
+    rate: Option<u32>,
+    #[serde(default)]
+    banners: bool,
+}
+
+fn rate(profile: ToolProfile, requested: Option<u32>, cap: u32) -> u32 {
+    let requested = requested.unwrap_or(cap);
+    if requested > cap {
+        log_observation(
+            "rate_clamped",
+            json!({"profile": profile.as_str(), "requested": requested, "max_rate": cap}),
+        );
+    }
+    requested.clamp(1, cap.max(1))
+}
+
+fn is_identifier(value: &str) -> bool {
+    !value.is_empty()
+        && !value.starts_with('-')
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+fn is_address_range(value: &str) -> bool {
+    let parts = match value.split_once('/') {
+        Some((ip, prefix)) => {
+            return ip.parse::<IpAddr>().is_ok()
+                && prefix.parse::<u8>().is_ok_and(|prefix| prefix <= 128);
+        }
+        None => value
+            .split_once('-')
+            .map_or((value, None), |(first, last)| (first, Some(last))),
+    };
+    match parts {
+        (first, None) => first.parse::<IpAddr>().is_ok(),
+        (first, Some(last)) => {
+            first.parse::<IpAddr>().is_ok()
+                && (last.parse::<IpAddr>().is_ok() || last.parse::<u8>().is_ok())
+        }
+    }
+}
+
+fn nuclei_finding(record: Value) -> Finding {
+    let info = &record["info"];
+    let template = record["template-id"].as_str().unwrap_or_default();
+    let mut finding = Finding::new(
+        "nuclei",
+        "vulnerability",
+        info["severity"]
+            .as_str()
+            .and_then(Severity::parse)
+            .unwrap_or_default(),
+        info["name"].as_str().unwrap_or(template),
+    );
+    finding.host = record["host"]
+        .as_str()
+        .map(strip_url)
+        .or_else(|| record["ip"].as_str())
+        .map(str::to_string);
+    finding.port = match &record["port"] {
+        Value::String(port) => port.parse().ok(),
+        Value::Number(port) => port.as_u64().and_then(|port| u16::try_from(port).ok()),
+        _ => None,
+    };
+    finding.protocol = record["type"].as_str().map(str::to_string);
+    finding.references = info["classification"]["cve-id"]
+        .as_array()
+        .into_iter()
+        .chain(info["reference"].as_array())
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect();
+    finding.evidence = record["matched-at"].as_str().map(str::to_string);
+    finding.data = record;
+    finding
+}
+
+fn strip_url(value: &str) -> &str {
+    if value.parse::<IpAddr>().is_ok() {
+        return value;
+    }
+    let rest = value.split_once("://").map_or(value, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or(bracketed),
+        None => authority
+            .rsplit_once(':')
+            .map_or(authority, |(host, _)| host),
+    }
+}
+
+fn masscan_findings(stdout: &str) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+    for line in stdout.lines() {
+        let fields = line.trim_end().splitn(7, ' ').collect::<Vec<_>>();
+        let [state @ ("open" | "banner"), protocol, port, host, ..] = fields.as_slice() else {
+            continue;
+        };
+        let Ok(port) = port.parse::<u16>() else {
+            continue;
+        };
+        let index = match findings.iter().position(|finding| {
+            finding.host.as_deref() == Some(*host)
+                && finding.port == Some(port)
+                && finding.protocol.as_deref() == Some(*protocol)
+        }) {
+            Some(index) => index,
+            None => {
+                let mut finding = Finding::new(
+                    "masscan",
+                    "service",
+                    Severity::Info,
+                    format!("Port {}/{} offen", port, protocol),
+                );
+                finding.host = Some(host.to_string());
+                finding.port = Some(port);
+                finding.protocol = Some(protocol.to_string());
+                findings.push(finding);
+                findings.len() - 1
+            }
+        };
+        if *state == "banner"
+            && let [.., service, banner] = fields.as_slice()
+            && fields.len() == 7
+        {
+            let finding = &mut findings[index];
+            finding.data["banners"][*service] = json!(banner);
+            finding.evidence.get_or_insert_with(|| banner.to_string());
+        }
+    }
+    findings
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::http::TokenConfig;
+use crate::log_observation;
+use crate::messages::Message;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RbacConfig {
+    #[serde(default)]
+    pub roles: BTreeMap<String, Role>,
+    #[serde(default)]
+    pub clients: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub host_groups: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Role {
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub max_timeout_sec: Option<u64>,
+}
+
+impl RbacConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.roles.is_empty()
+    }
+
+    fn roles_of<'a>(&'a self, client: &str) -> impl Iterator<Item = &'a Role> {
+        self.clients
+            .get(client)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.roles.get(name))
+    }
+
+    fn allows_host(&self, role: &Role, host: &str) -> bool {
+        role.hosts
+            .iter()
+            .any(|entry| match entry.strip_prefix('@') {
+                Some(group) => self
+                    .host_groups
+                    .get(group)
+                    .is_some_and(|members| members.iter().any(|member| matches(member, host))),
+                None => matches(entry, host),
+            })
+    }
+}
+
+pub fn validate(config: &RbacConfig, tokens: &[TokenConfig]) -> Result<()> {
+    if !config.is_enabled() {
+        return Ok(());
+    }
+    for (client, roles) in &config.clients {
+        for role in roles {
+            if !config.roles.contains_key(role) {
+                bail!("rbac.clients.{}: unbekannte Rolle '{}'", client, role);
+            }
+        }
+    }
+    for (name, role) in &config.roles {
+        for group in role
+            .hosts
+            .iter()
+            .filter_map(|entry| entry.strip_prefix('@'))
+        {
+            if !config.host_groups.contains_key(group) {
+                bail!("rbac.roles.{}: unbekannte Host-Gruppe '{}'", name, group);
+            }
+        }
+    }
+    for token in tokens {
+        if !config.clients.contains_key(&token.identity) {
+            log_observation(
+                "rbac_client_without_role",
+                json!({"identity": token.identity}),
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn authorize(
+    config: &RbacConfig,
+    client: Option<&str>,
+    tool: &str,
+    host: &str,
+    timeout_sec: u64,
+) -> Result<u64> {
+    let Some(client) = client.filter(|_| config.is_enabled()) else {
+        return Ok(timeout_sec);
+    };
+    let roles = config.roles_of(client).collect::<Vec<_>>();
+    if roles.is_empty() {
+        return Err(Message::NoRole {
+            client: client.to_string(),
+        }
+        .into());
+    }
+    let for_tool = roles
+        .into_iter()
+        .filter(|role| role.tools.iter().any(|pattern| matches(pattern, tool)))
+        .collect::<Vec<_>>();
+    if for_tool.is_empty() {
+        return Err(Message::ToolForbidden {
+            client: client.to_string(),
+            tool: tool.to_string(),
+        }
+        .into());
+    }
+    let max_timeout_sec = for_tool
+        .into_iter()
+        .filter(|role| config.allows_host(role, host))
+        .map(|role| role.max_timeout_sec.unwrap_or(u64::MAX))
+        .max();
+    match max_timeout_sec {
+        Some(max_timeout_sec) => Ok(timeout_sec.min(max_timeout_sec)),
+        None => Err(Message::HostForbidden {
+            client: client.to_string(),
+            tool: tool.to_string(),
+            host: host.to_string(),
+        }
+        .into()),
+    }
+}
+
+pub fn allows_tool(config: &RbacConfig, client: Option<&str>, tool: &str) -> bool {
+    match client.filter(|_| config.is_enabled()) {
+        Some(client) => config
+            .roles_of(client)
+            .any(|role| role.tools.iter().any(|pattern| matches(pattern, tool))),
+        None => true,
+    }
+}
+
+fn matches(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
@@ -0,0 +1,344 @@
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use regex::bytes::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const MAX_PENDING_BYTES: usize = 64 * 1024;
+const MIN_KNOWN_VALUE_LEN: usize = 4;
+
+const BUILTIN_PATTERNS: &[&str] = &[
+    r#"(?i)\b(?:password|passwd|pwd|pass|secret|token|api[_-]?key|access[_-]?key)\b\s*[:=]\s*"?(?P<secret>[^\s"',;]+)"#,
+    r"(?i)\bbearer\s+(?P<secret>[A-Za-z0-9._~+/-]{8,}=*)",
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+    r"\bgh[pousr]_[A-Za-z0-9]{36}\b",
+    r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+    r"\beyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",
+    r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?-----END [A-Z ]*PRIVATE KEY-----",
+];
+
+static GLOBAL: OnceLock<Redactor> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_builtin_patterns")]
+    pub builtin_patterns: bool,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default = "default_replacement")]
+    pub replacement: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_builtin_patterns() -> bool {
+    true
+}
+
+fn default_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            builtin_patterns: default_builtin_patterns(),
+            patterns: Vec::new(),
+            replacement: default_replacement(),
+        }
+    }
+}
+
+pub struct Redactor {
+    patterns: Vec<Regex>,
+    values: Vec<(Vec<u8>, Vec<u8>)>,
+    replacement: Vec<u8>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig, known_values: Vec<(String, String)>) -> Result<Self> {
+        if !config.enabled {
+            return Ok(Self {
+                patterns: Vec::new(),
+                values: Vec::new(),
+                replacement: Vec::new(),
+            });
+        }
+        let builtin = if config.builtin_patterns {
+            BUILTIN_PATTERNS
+        } else {
+            &[]
+        };
+        let patterns = builtin
+            .iter()
+            .copied()
+            .chain(config.patterns.iter().map(String::as_str))
+            .map(|pattern| {
+                Regex::new(pattern)
+                    .with_context(|| format!("ungültiges Redaction-Pattern '{}'", pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            patterns,
+            values: known_value_pairs(known_values),
+            replacement: config.replacement.clone().into_bytes(),
+        })
+    }
+
+    pub fn redact_bytes<'a>(
+        &self,
+        data: &'a [u8],
+        extra: &[(Vec<u8>, Vec<u8>)],
+        patterns: bool,
+    ) -> Cow<'a, [u8]> {
+        let mut out = Cow::Borrowed(data);
+        for (value, replacement) in extra.iter().chain(&self.values) {
+            if contains(&out, value) {
+                out = Cow::Owned(replace(&out, value, replacement));
+            }
+        }
+        if patterns {
+            for pattern in &self.patterns {
+                if let Some(replaced) = self.replace_pattern(pattern, &out) {
+                    out = Cow::Owned(replaced);
+                }
+            }
+        }
+        out
+    }
+
+    pub fn redact_str<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.redact_bytes(text.as_bytes(), &[], true) {
+            Cow::Borrowed(_) => Cow::Borrowed(text),
+            Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => {
+                if let Cow::Owned(redacted) = self.redact_str(text) {
+                    *text = redacted;
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        !self.patterns.is_empty() || !self.values.is_empty()
+    }
+
+    fn replace_pattern(&self, pattern: &Regex, data: &[u8]) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut last = 0;
+        for caps in pattern.captures_iter(data) {
+            let whole = caps.get(0).expect("Regex-Match ohne Gruppe 0");
+            if data[..whole.start()].ends_with(b"{{")
+                && data[whole.start()..].starts_with(b"secret:")
+            {
+                continue;
+            }
+            out.extend_from_slice(&data[last..whole.start()]);
+            out.extend_from_slice(&self.replace_match(&caps));
+            last = whole.end();
+        }
+        if last == 0 && out.is_empty() {
+            return None;
+        }
+        out.extend_from_slice(&data[last..]);
+        Some(out)
+    }
+
+    fn replace_match(&self, caps: &Captures) -> Vec<u8> {
+        let whole = caps.get(0).expect("Regex-Match ohne Gruppe 0");
+        match caps.name("secret") {
+            Some(secret) => {
+                let mut out = whole.as_bytes()[..secret.start() - whole.start()].to_vec();
+                out.extend_from_slice(&self.replacement);
+                out.extend_from_slice(&whole.as_bytes()[secret.end() - whole.start()..]);
+                out
+            }
+            None => self.replacement.clone(),
+        }
+    }
+}
+
+pub fn install(config: &RedactionConfig, known_values: Vec<(String, String)>) -> Result<()> {
+    let redactor = Redactor::new(config, known_values)?;
+    let _ = GLOBAL.set(redactor);
+    Ok(())
+}
+
+pub fn redact_value(value: &mut Value) {
+    if let Some(redactor) = GLOBAL.get() {
+        redactor.redact_value(value);
+    }
+}
+
+pub fn redact_known(data: &[u8], known_values: Vec<(String, String)>) -> Vec<u8> {
+    let values = known_value_pairs(known_values);
+    match GLOBAL.get() {
+        Some(redactor) => redactor.redact_bytes(data, &values, true).into_owned(),
+        None => redact_values(data, &values),
+    }
+}
+
+#[derive(Debug, Default)]
+struct StreamState {
+    pending: Vec<u8>,
+    in_private_key: bool,
+}
+
+#[derive(Default)]
+pub struct StreamRedactor {
+    redactor: Option<&'static Redactor>,
+    values: Vec<(Vec<u8>, Vec<u8>)>,
+    patterns: bool,
+    stdout: StreamState,
+    stderr: StreamState,
+}
+
+impl std::fmt::Debug for StreamRedactor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamRedactor")
+            .field("active", &self.is_active())
+            .finish()
+    }
+}
+
+impl StreamRedactor {
+    pub fn new(values: Vec<(Vec<u8>, Vec<u8>)>, binary: bool) -> Self {
+        let redactor = GLOBAL.get().filter(|redactor| redactor.is_active());
+        Self {
+            redactor,
+            values: values
+                .into_iter()
+                .filter(|(value, _)| !value.is_empty())
+                .collect(),
+            patterns: !binary,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.redactor.is_some() || !self.values.is_empty()
+    }
+
+    pub fn apply(&mut self, is_stdout: bool, data: &[u8]) -> Vec<u8> {
+        let state = if is_stdout {
+            &mut self.stdout
+        } else {
+            &mut self.stderr
+        };
+        state.pending.extend_from_slice(data);
+        let ready = match state.pending.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => end + 1,
+            None if state.pending.len() >= MAX_PENDING_BYTES => state.pending.len(),
+            None => return Vec::new(),
+        };
+        let lines = state.pending.drain(..ready).collect::<Vec<_>>();
+        self.redact_lines(is_stdout, &lines)
+    }
+
+    pub fn flush(&mut self, is_stdout: bool) -> Vec<u8> {
+        let state = if is_stdout {
+            &mut self.stdout
+        } else {
+            &mut self.stderr
+        };
+        let rest = std::mem::take(&mut state.pending);
+        self.redact_lines(is_stdout, &rest)
+    }
+
+    fn redact_lines(&mut self, is_stdout: bool, data: &[u8]) -> Vec<u8> {
+        let redactor = self.redactor;
+        let patterns =
+            self.patterns && redactor.is_some_and(|redactor| !redactor.patterns.is_empty());
+        let state = if is_stdout {
+            &mut self.stdout
+        } else {
+            &mut self.stderr
+        };
+        let mut out = Vec::with_capacity(data.len());
+        for line in data.split_inclusive(|byte| *byte == b'\n') {
+            if patterns {
+                if state.in_private_key {
+                    if contains(line, b"PRIVATE KEY-----") && contains(line, b"-----END") {
+                        state.in_private_key = false;
+                    }
+                    continue;
+                }
+                if contains(line, b"PRIVATE KEY-----") && contains(line, b"-----BEGIN") {
+                    state.in_private_key = !contains(line, b"-----END");
+                    if let Some(redactor) = redactor {
+                        out.extend_from_slice(&redactor.replacement);
+                    }
+                    if line.ends_with(b"\n") {
+                        out.push(b'\n');
+                    }
+                    continue;
+                }
+            }
+            match redactor {
+                Some(redactor) => {
+                    out.extend_from_slice(&redactor.redact_bytes(line, &self.values, patterns))
+                }
+                None => out.extend_from_slice(&redact_values(line, &self.values)),
+            }
+        }
+        out
+    }
+}
+
+fn known_value_pairs(known_values: Vec<(String, String)>) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut pairs = known_values
+        .into_iter()
+        .filter(|(value, _)| value.len() >= MIN_KNOWN_VALUE_LEN)
+        .map(|(value, replacement)| (value.into_bytes(), replacement.into_bytes()))
+        .collect::<Vec<_>>();
+    pairs.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+    pairs.dedup_by(|left, right| left.0 == right.0);
+    pairs
+}
+
+fn redact_values(data: &[u8], values: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+    let mut out = data.to_vec();
+    for (value, replacement) in values {
+        if contains(&out, value) {
+            out = replace(&out, value, replacement);
+        }
+    }
+    out
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+fn replace(haystack: &[u8], needle: &[u8], replacement: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(haystack.len());
+    let mut index = 0;
+    while index < haystack.len() {
+        if haystack[index..].starts_with(needle) {
+            out.extend_from_slice(replacement);
+            index += needle.len();
+        } else {
+            out.push(haystack[index]);
+            index += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,403 @@
+use std::io::Write;
+use std::time::Instant;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use crate::console;
+use crate::messages;
+
+pub const EXIT_REJECTED: i32 = 77;
+pub const EXIT_BRIDGE_ERROR: i32 = 70;
+pub const EXIT_TIMEOUT: i32 = 124;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Jsonl,
+    Json,
+    Text,
+    Pretty,
+}
+
+pub enum Output {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+}
+
+pub struct Renderer {
+    format: OutputFormat,
+    id: Option<Value>,
+    started: bool,
+    finished: Option<Value>,
+    error: Option<Value>,
+    events: Vec<Value>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    binary: bool,
+}
+
+impl Renderer {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            id: None,
+            started: false,
+            finished: None,
+            error: None,
+            events: Vec::new(),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            binary: false,
+        }
+    }
+
+    pub fn started(&self) -> bool {
+        self.started
+    }
+
+    pub fn event(&mut self, event: Value) -> Vec<Output> {
+        let name = event["event"].as_str().unwrap_or_default().to_string();
+        self.id.get_or_insert_with(|| event["id"].clone());
+        match name.as_str() {
+            "started" => self.started = true,
+            "finished" => self.finished = Some(event["payload"].clone()),
+            "error" => self.error = Some(event["payload"].clone()),
+            _ => {}
+        }
+        let payload = &event["payload"];
+        let stream = match name.as_str() {
+            "stdout_chunk" | "stdout_line" => Some(true),
+            "stderr_chunk" | "stderr_line" => Some(false),
+            _ => None,
+        };
+        match (self.format, stream) {
+            (OutputFormat::Jsonl, _) => {
+                let mut line = event.to_string().into_bytes();
+                line.push(b'\n');
+                vec![Output::Stdout(line)]
+            }
+            (OutputFormat::Pretty, _) => console::format_event(&event)
+                .map(|text| vec![Output::Stdout(text.into_bytes())])
+                .unwrap_or_default(),
+            (OutputFormat::Json, Some(stdout)) => {
+                self.binary |= payload["encoding"] == "base64";
+                let bytes = output_bytes(payload);
+                if stdout {
+                    &mut self.stdout
+                } else {
+                    &mut self.stderr
+                }
+                .extend_from_slice(&bytes);
+                Vec::new()
+            }
+            (OutputFormat::Json, None) => {
+                if name != "finished" && name != "error" {
+                    self.events.push(event);
+                }
+                Vec::new()
+            }
+            (OutputFormat::Text, Some(true)) => vec![Output::Stdout(output_bytes(payload))],
+            (OutputFormat::Text, Some(false)) => vec![Output::Stderr(output_bytes(payload))],
+            (OutputFormat::Text, None) if name == "error" => {
+                let message = payload["message"].as_str().unwrap_or_default();
+                vec![Output::Stderr(
+                    format!("{}: {}\n", messages::error_prefix(), message).into_bytes(),
+                )]
+            }
+            (OutputFormat::Text, None) => Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> Vec<Output> {
+        match self.format {
+            OutputFormat::Json => {
+                let encode = |bytes: &[u8]| {
+                    if self.binary {
+                        BASE64.encode(bytes)
+                    } else {
+                        String::from_utf8_lossy(bytes).to_string()
+                    }
+                };
+                let mut document = json!({
+                    "id": self.id,
+                    "result": self.finished,
+                    "error": self.error,
+                    "stdout": encode(&self.stdout),
+                    "stderr": encode(&self.stderr),
+                    "events": self.events
+                });
+                if self.binary {
+                    document["encoding"] = json!("base64");
+                }
+                let mut text = serde_json::to_string_pretty(&document)
+                    .unwrap_or_default()
+                    .into_bytes();
+                text.push(b'\n');
+                vec![Output::Stdout(text)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn output_bytes(payload: &Value) -> Vec<u8> {
+    let data = payload["data"].as_str().unwrap_or_default();
+    let mut bytes = if payload["encoding"] == "base64" {
+        BASE64.decode(data).unwrap_or_default()
+    } else {
+        data.as_bytes().to_vec()
+    };
+    if payload.get("seq").is_some() && payload["partial"] != true {
+        bytes.push(b'\n');
+    }
+    bytes
+}
+
+pub fn print(outputs: Vec<Output>) -> std::io::Result<()> {
+    for output in outputs {
+        match output {
+            Output::Stdout(bytes) => {
+                let mut out = std::io::stdout().lock();
+                out.write_all(&bytes)?;
+                out.flush()?;
+            }
+            Output::Stderr(bytes) => {
+                let mut err = std::io::stderr().lock();
+                err.write_all(&bytes)?;
+                err.flush()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn exit_code(exit_code: Option<i32>, timed_out: bool) -> i32 {
+    match exit_code {
+        _ if timed_out => EXIT_TIMEOUT,
+        Some(code) => code,
+        None => EXIT_BRIDGE_ERROR,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WorkflowFormat {
+    #[default]
+    Pretty,
+    Jsonl,
+    Json,
+}
+
+pub struct WorkflowProgress {
+    format: WorkflowFormat,
+    started: Instant,
+    id: Option<Value>,
+    step: String,
+    succeeded: usize,
+    failed: usize,
+    skipped: usize,
+    findings: u64,
+    exit_code: Option<i32>,
+    completed: bool,
+    events: Vec<Value>,
+}
+
+impl WorkflowProgress {
+    pub fn new(format: WorkflowFormat) -> Self {
+        Self {
+            format,
+            started: Instant::now(),
+            id: None,
+            step: String::new(),
+            succeeded: 0,
+            failed: 0,
+            skipped: 0,
+            findings: 0,
+            exit_code: None,
+            completed: false,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn event(&mut self, event: Value) -> Vec<Output> {
+        self.id.get_or_insert_with(|| event["id"].clone());
+        let payload = &event["payload"];
+        let line = match event["event"].as_str().unwrap_or_default() {
+            "workflow_started" => Some(format!(
+                "Workflow {}: {} Schritte, {} Runs",
+                text(&event["id"]),
+                payload["steps"],
+                payload["runs"]
+            )),
+            "step_started" => {
+                self.step = step_label(payload, payload["tool"].as_str());
+                Some(format!("{} gestartet", self.step))
+            }
+            "step_finished" => {
+                self.findings += payload["findings_summary"]["total"].as_u64().unwrap_or(0);
+                let duration = format!(
+                    "{:.1} s",
+                    payload["duration_ms"].as_f64().unwrap_or_default() / 1000.0
+                );
+                let code = exit_code(
+                    payload["exit_code"].as_i64().map(|code| code as i32),
+                    payload["timed_out"] == true,
+                );
+                let outcome = if let Some(error) = payload["script_error"].as_str() {
+                    self.fail(EXIT_BRIDGE_ERROR);
+                    format!("Skriptfehler nach {}: {}", duration, error)
+                } else if code != 0 {
+                    self.fail(code);
+                    format!(
+                        "fehlgeschlagen mit Exit-Code {} nach {}",
+                        payload["exit_code"], duration
+                    )
+                } else {
+                    self.succeeded += 1;
+                    format!("beendet nach {}", duration)
+                };
+                let mut line = format!("{} {}", self.step, outcome);
+                if let Some(total) = payload["findings_summary"]["total"]
+                    .as_u64()
+                    .filter(|total| *total > 0)
+                {
+                    line.push_str(&format!(", {} Findings", total));
+                }
+                if payload["timed_out"] == true {
+                    line.push_str(" (Timeout)");
+                }
+                Some(line)
+            }
+            "step_skipped" => {
+                self.skipped += 1;
+                Some(format!(
+                    "{} übersprungen",
+                    step_label(payload, payload["tool"].as_str())
+                ))
+            }
+            "step_failed" => {
+                self.fail(EXIT_BRIDGE_ERROR);
+                Some(format!(
+                    "{} fehlgeschlagen: {}",
+                    step_label(payload, None),
+                    text(&payload["error"])
+                ))
+            }
+            "connection_reestablished" => Some(format!(
+                "SSH-Verbindung zu {} neu aufgebaut",
+                text(&payload["host"])
+            )),
+            "workflow_finished" => {
+                self.completed = true;
+                None
+            }
+            "error" => {
+                self.exit_code.get_or_insert(EXIT_REJECTED);
+                Some(format!(
+                    "{}: {}",
+                    messages::error_prefix(),
+                    text(&payload["message"])
+                ))
+            }
+            _ => None,
+        };
+        match self.format {
+            WorkflowFormat::Pretty => line
+                .map(|line| vec![Output::Stdout(format!("{}\n", line).into_bytes())])
+                .unwrap_or_default(),
+            WorkflowFormat::Jsonl => {
+                let mut line = event.to_string().into_bytes();
+                line.push(b'\n');
+                vec![Output::Stdout(line)]
+            }
+            WorkflowFormat::Json => {
+                self.events.push(event);
+                Vec::new()
+            }
+        }
+    }
+
+    fn fail(&mut self, code: i32) {
+        self.failed += 1;
+        self.exit_code.get_or_insert(code);
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self.exit_code {
+            Some(code) => code,
+            None if self.completed => 0,
+            None => EXIT_BRIDGE_ERROR,
+        }
+    }
+
+    pub fn finish(self) -> Vec<Output> {
+        let summary = json!({
+            "id": self.id,
+            "completed": self.completed,
+            "succeeded": self.succeeded,
+            "failed": self.failed,
+            "skipped": self.skipped,
+            "findings": self.findings,
+            "duration_ms": self.started.elapsed().as_millis() as u64,
+            "exit_code": self.exit_code()
+        });
+        match self.format {
+            WorkflowFormat::Pretty => {
+                let line = format!(
+                    "Workflow {} {}: {} erfolgreich, {} fehlgeschlagen, {} übersprungen, {} Findings, {:.1} s\n",
+                    text(&summary["id"]),
+                    if self.completed {
+                        "beendet"
+                    } else {
+                        "abgebrochen"
+                    },
+                    self.succeeded,
+                    self.failed,
+                    self.skipped,
+                    self.findings,
+                    self.started.elapsed().as_secs_f64()
+                );
+                vec![Output::Stdout(line.into_bytes())]
+            }
+            WorkflowFormat::Jsonl => vec![Output::Stdout(
+                format!(
+                    "{}\n",
+                    json!({"event": "summary", "id": summary["id"], "payload": summary})
+                )
+                .into_bytes(),
+            )],
+            WorkflowFormat::Json => {
+                let mut document = summary;
+                document["events"] = json!(self.events);
+                let mut text = serde_json::to_string_pretty(&document)
+                    .unwrap_or_default()
+                    .into_bytes();
+                text.push(b'\n');
+                vec![Output::Stdout(text)]
+            }
+        }
+    }
+}
+
+fn step_label(payload: &Value, tool: Option<&str>) -> String {
+    let mut label = format!("Schritt {}", payload["index"]);
+    if let Some(tool) = tool {
+        label.push_str(&format!(" ({}", tool));
+        if let Some(target) = payload["target"].as_str() {
+            label.push_str(&format!(" auf {}", target));
+        }
+        label.push(')');
+    } else if let Some(target) = payload["target"].as_str() {
+        label.push_str(&format!(" ({})", target));
+    }
+    label
+}
+
+fn text(value: &Value) -> String {
+    value
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| value.to_string())
+}
@@ -0,0 +1,436 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::labels::{self, Labels};
+use crate::messages::Message;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestConfig {
+    #[serde(default = "default_max_runs")]
+    pub max_runs: usize,
+    #[serde(default = "default_max_events")]
+    pub max_events: usize,
+}
+
+fn default_max_runs() -> usize {
+    500
+}
+
+fn default_max_events() -> usize {
+    1000
+}
+
+impl Default for RestConfig {
+    fn default() -> Self {
+        Self {
+            max_runs: default_max_runs(),
+            max_events: default_max_events(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunKind {
+    Run,
+    Workflow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RunState {
+    Running,
+    Finished,
+    Failed,
+}
+
+struct StoredRun {
+    id: String,
+    kind: RunKind,
+    client: String,
+    labels: Labels,
+    state: RunState,
+    created_ms: u128,
+    finished_ms: Option<u128>,
+    result: Option<Value>,
+    error: Option<String>,
+    code: Option<String>,
+    events: Vec<Value>,
+    dropped_events: usize,
+}
+
+pub struct RunStore {
+    config: RestConfig,
+    next: AtomicU64,
+    runs: Mutex<VecDeque<StoredRun>>,
+}
+
+impl RunStore {
+    pub fn new(config: &RestConfig) -> Self {
+        Self {
+            config: config.clone(),
+            next: AtomicU64::new(1),
+            runs: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn create(
+        &self,
+        kind: RunKind,
+        id: Option<String>,
+        client: &str,
+        labels: &Labels,
+    ) -> Result<String, (u16, Message)> {
+        let id = id.unwrap_or_else(|| {
+            format!(
+                "rest-{}-{}",
+                now_ms(),
+                self.next.fetch_add(1, Ordering::Relaxed)
+            )
+        });
+        if id.is_empty()
+            || id.len() > 128
+            || id
+                .chars()
+                .any(|c| c.is_control() || matches!(c, '/' | '?' | '#'))
+        {
+            return Err((400, Message::InvalidRunId { id }));
+        }
+        let mut runs = self.runs.lock().expect("rest runs poisoned");
+        if runs.iter().any(|run| run.id == id) {
+            return Err((409, Message::RunExists { id }));
+        }
+        if runs.len() >= self.config.max_runs {
+            let Some(index) = runs.iter().position(|run| run.state != RunState::Running) else {
+                return Err((
+                    503,
+                    Message::TooManyRuns {
+                        max: self.config.max_runs,
+                    },
+                ));
+            };
+            runs.remove(index);
+        }
+        runs.push_back(StoredRun {
+            id: id.clone(),
+            kind,
+            client: client.to_string(),
+            labels: labels.clone(),
+            state: RunState::Running,
+            created_ms: now_ms(),
+            finished_ms: None,
+            result: None,
+            error: None,
+            code: None,
+            events: Vec::new(),
+            dropped_events: 0,
+        });
+        Ok(id)
+    }
+
+    pub fn push_event(&self, id: &str, event: Value) {
+        self.update(id, |run| {
+            if run.events.len() >= self.config.max_events {
+                run.dropped_events += 1;
+            } else {
+                run.events.push(event);
+            }
+        });
+    }
+
+    pub fn finish(&self, id: &str, outcome: Result<Value>) {
+        self.update(id, |run| {
+            run.finished_ms = Some(now_ms());
+            match outcome {
+                Ok(result) => {
+                    run.state = RunState::Finished;
+                    run.result = Some(result);
+                }
+                Err(error) => {
+                    run.state = RunState::Failed;
+                    run.code = Some(crate::error_code(&error).to_string());
+                    run.error = Some(format!("{:#}", error));
+                }
+            }
+        });
+    }
+
+    pub fn fail(&self, id: &str, code: &str, error: &str) {
+        self.update(id, |run| {
+            run.finished_ms = Some(now_ms());
+            run.state = RunState::Failed;
+            run.code = Some(code.to_string());
+            run.error = Some(error.to_string());
+        });
+    }
+
+    pub fn get(&self, id: &str, client: &str) -> Option<Value> {
+        let runs = self.runs.lock().expect("rest runs poisoned");
+        let run = runs
+            .iter()
+            .find(|run| run.id == id && run.client == client)?;
+        let mut value = summary(run);
+        if let Some(result) = &run.result {
+            value["result"] = result.clone();
+        }
+        if let Some(error) = &run.error {
+            value["error"] = json!(error);
+            value["code"] = json!(run.code);
+        }
+        if run.kind == RunKind::Workflow {
+            value["events"] = json!(run.events);
+            value["dropped_events"] = json!(run.dropped_events);
+        }
+        Some(value)
+    }
+
+    pub fn list(&self, client: &str, filters: &[(String, String)]) -> Value {
+        let runs = self.runs.lock().expect("rest runs poisoned");
+        let runs = runs
+            .iter()
+            .rev()
+            .filter(|run| run.client == client && labels::matches(&json!(run.labels), filters))
+            .map(summary)
+            .collect::<Vec<_>>();
+        json!({"runs": runs})
+    }
+
+    pub fn counts(&self) -> Value {
+        let runs = self.runs.lock().expect("rest runs poisoned");
+        let count = |state| runs.iter().filter(|run| run.state == state).count();
+        json!({
+            "running": count(RunState::Running),
+            "finished": count(RunState::Finished),
+            "failed": count(RunState::Failed)
+        })
+    }
+
+    fn update(&self, id: &str, apply: impl FnOnce(&mut StoredRun)) {
+        if let Some(run) = self
+            .runs
+            .lock()
+            .expect("rest runs poisoned")
+            .iter_mut()
+            .find(|run| run.id == id)
+        {
+            apply(run);
+        }
+    }
+}
+
+fn summary(run: &StoredRun) -> Value {
+    json!({
+        "id": run.id,
+        "kind": run.kind,
+        "state": run.state,
+        "labels": run.labels,
+        "created_ms": run.created_ms,
+        "finished_ms": run.finished_ms
+    })
+}
+
+pub fn openapi(tools: &[(String, Option<Value>)]) -> Value {
+    let params = tools
+        .iter()
+        .filter_map(|(name, properties)| {
+            let properties = properties.as_ref()?;
+            Some((
+                format!("Params.{}", name),
+                json!({"type": "object", "properties": properties}),
+            ))
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let labels = json!({
+        "type": "object",
+        "additionalProperties": {"type": "string"},
+        "description": "Free-form key/value labels carried into events, logs, audit and metrics"
+    });
+    let mut schemas = json!({
+        "RunRequest": {
+            "type": "object",
+            "required": ["host", "tool"],
+            "properties": {
+                "id": {"type": "string", "description": "Optional run id, generated if missing"},
+                "host": {"type": "string"},
+                "user": {"type": "string"},
+                "tool": {"type": "string", "enum": tools.iter().map(|(name, _)| name).collect::<Vec<_>>()},
+                "args": {"type": "array", "items": {"type": "string"}},
+                "timeout_sec": {"type": "integer", "minimum": 1},
+                "max_output_bytes": {"type": "integer", "minimum": 1},
+                "strip_ansi": {"type": "boolean"},
+                "strip_control_chars": {"type": "boolean"},
+                "include_pattern": {"type": "string", "description": "Regex; only stdout lines matching it are kept"},
+                "exclude_pattern": {"type": "string", "description": "Regex; stdout lines matching it are dropped"},
+                "no_cache": {"type": "boolean"},
+                "engagement": {"type": "string"},
+                "stdin": {
+                    "oneOf": [
+                        {"type": "string"},
+                        {"type": "object", "required": ["base64"], "properties": {"base64": {"type": "string"}}}
+                    ]
+                },
+                "params": {
+                    "type": "object",
+                    "description": "Structured parameters for profile tools, see the Params.<tool> schemas"
+                },
+                "labels": labels,
+                "manifest": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Include the run manifest (resolved config, remote command, SSH options, versions, attempts) in the result"
+                }
+            }
+        },
+        "WorkflowRequest": {
+            "type": "object",
+            "required": ["host", "steps"],
+            "properties": {
+                "id": {"type": "string"},
+                "host": {"type": "string"},
+                "user": {"type": "string"},
+                "engagement": {"type": "string"},
+                "labels": labels,
+                "stop_on_error": {"type": "boolean", "default": true},
+                "steps": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "type": "object",
+                        "required": ["tool"],
+                        "properties": {
+                            "tool": {"type": "string"},
+                            "args": {"type": "array", "items": {"type": "string"}},
+                            "params": {"type": "object"},
+                            "labels": {"type": "object", "additionalProperties": {"type": "string"}, "description": "Merged over the workflow labels"},
+                            "for_each": {"type": "string", "description": "Stored target list, {{target}} is replaced per host"},
+                            "timeout_sec": {"type": "integer", "minimum": 1},
+                            "max_output_bytes": {"type": "integer", "minimum": 1},
+                            "no_cache": {"type": "boolean"}
+                        }
+                    }
+                }
+            }
+        },
+        "Accepted": {
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "kind": {"type": "string"}, "state": {"type": "string"}, "location": {"type": "string"}}
+        },
+        "RunStatus": {
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "kind": {"type": "string", "enum": ["run", "workflow"]},
+                "state": {"type": "string", "enum": ["running", "finished", "failed"]},
+                "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                "created_ms": {"type": "integer"},
+                "finished_ms": {"type": "integer", "nullable": true},
+                "result": {"type": "object", "description": "Run result (exit_code, stdout, stderr, findings, summary) or the last workflow status"},
+                "error": {"type": "string"},
+                "code": {"type": "string", "description": "Stable error code of a failed run"},
+                "events": {"type": "array", "items": {"type": "object"}, "description": "Workflow events"},
+                "dropped_events": {"type": "integer"}
+            }
+        },
+        "RunList": {
+            "type": "object",
+            "properties": {"runs": {"type": "array", "items": {"$ref": "#/components/schemas/RunStatus"}}}
+        },
+        "Error": {"type": "object", "properties": {"error": {"type": "string"}, "code": {"type": "string"}}}
+    });
+    if let Value::Object(schemas) = &mut schemas {
+        schemas.extend(params);
+    }
+    let error = |description: &str| json!({"description": description, "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Error"}}}});
+    let accepted = json!({
+        "description": "Run accepted and started",
+        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/Accepted"}}}
+    });
+    let status = json!({
+        "description": "Current state and, once finished, the result",
+        "content": {"application/json": {"schema": {"$ref": "#/components/schemas/RunStatus"}}}
+    });
+    let id_parameter =
+        json!([{"name": "id", "in": "path", "required": true, "schema": {"type": "string"}}]);
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "ollama-kali-mcp-bridge REST API",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "security": [{"bearer": []}],
+        "paths": {
+            "/health": {
+                "get": {
+                    "summary": "Health report (config validity, control socket, queue, per-host circuit state) and run counters",
+                    "responses": {"200": {"description": "Bridge is up"}}
+                }
+            },
+            "/runs": {
+                "get": {
+                    "summary": "Runs and workflows of the caller, newest first",
+                    "parameters": [{
+                        "name": "label",
+                        "in": "query",
+                        "description": "Label filter as key:value, repeatable, all must match",
+                        "schema": {"type": "array", "items": {"type": "string"}},
+                        "style": "form",
+                        "explode": true
+                    }],
+                    "responses": {
+                        "200": {
+                            "description": "Run summaries without results",
+                            "content": {"application/json": {"schema": {"$ref": "#/components/schemas/RunList"}}}
+                        },
+                        "400": error("Invalid label filter")
+                    }
+                },
+                "post": {
+                    "summary": "Start a tool run",
+                    "requestBody": {"required": true, "content": {"application/json": {"schema": {"$ref": "#/components/schemas/RunRequest"}}}},
+                    "responses": {"202": accepted, "400": error("Invalid request"), "409": error("Run id already exists"), "503": error("Too many running runs")}
+                }
+            },
+            "/runs/{id}": {
+                "get": {
+                    "summary": "State and result of a run or workflow",
+                    "parameters": id_parameter,
+                    "responses": {"200": status, "404": error("Unknown run")}
+                }
+            },
+            "/workflows": {
+                "post": {
+                    "summary": "Start a workflow",
+                    "requestBody": {"required": true, "content": {"application/json": {"schema": {"$ref": "#/components/schemas/WorkflowRequest"}}}},
+                    "responses": {"202": accepted, "400": error("Invalid request"), "409": error("Run id already exists"), "503": error("Too many running runs")}
+                }
+            },
+            "/workflows/{id}": {
+                "get": {
+                    "summary": "State, events and last status of a workflow",
+                    "parameters": id_parameter,
+                    "responses": {"200": status, "404": error("Unknown workflow")}
+                }
+            },
+            "/openapi.json": {
+                "get": {"summary": "This document", "responses": {"200": {"description": "OpenAPI document"}}}
+            }
+        },
+        "components": {
+            "securitySchemes": {"bearer": {"type": "http", "scheme": "bearer"}},
+            "schemas": schemas
+        }
+    })
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::artifacts::key_segment;
+use crate::stats::parse_window;
+
+const WIPE_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_interval_sec")]
+    pub interval_sec: u64,
+    #[serde(default)]
+    pub max_age: Option<String>,
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+    #[serde(default)]
+    pub engagements: BTreeMap<String, RetentionLimits>,
+    #[serde(default = "default_secure_delete")]
+    pub secure_delete: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionLimits {
+    #[serde(default)]
+    pub max_age: Option<String>,
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
+}
+
+fn default_interval_sec() -> u64 {
+    3600
+}
+
+fn default_secure_delete() -> bool {
+    true
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_sec: default_interval_sec(),
+            max_age: None,
+            max_bytes: None,
+            engagements: BTreeMap::new(),
+            secure_delete: default_secure_delete(),
+        }
+    }
+}
+
+pub fn validate(config: &RetentionConfig, local_dir: Option<&str>) -> Result<()> {
+    if config.interval_sec == 0 {
+        bail!("retention.interval_sec muss größer als 0 sein");
+    }
+    if let Some(max_age) = &config.max_age {
+        parse_window(max_age).with_context(|| format!("retention.max_age '{}'", max_age))?;
+    }
+    for (name, limits) in &config.engagements {
+        if let Some(max_age) = &limits.max_age {
+            parse_window(max_age)
+                .with_context(|| format!("retention.engagements.{}.max_age '{}'", name, max_age))?;
+        }
+    }
+    if config.enabled && local_dir.is_none() {
+        bail!("retention.enabled setzt artifacts.local_dir voraus");
+    }
+    Ok(())
+}
+
+pub struct Selection {
+    pub engagement: Option<String>,
+    pub older_than_ms: Option<u128>,
+}
+
+struct StoredRun {
+    path: PathBuf,
+    engagement: Option<String>,
+    modified_ms: u128,
+    bytes: u64,
+}
+
+pub fn purge(
+    config: &RetentionConfig,
+    dir: &Path,
+    selection: Option<&Selection>,
+    dry_run: bool,
+) -> Result<Value> {
+    let now = now_ms();
+    let mut runs = Vec::new();
+    if dir.is_dir() {
+        scan(dir, &mut Vec::new(), &mut runs)?;
+    }
+    runs.sort_by_key(|run| run.modified_ms);
+
+    let mut purged = Vec::new();
+    match selection {
+        Some(selection) => {
+            let engagement = selection.engagement.as_deref().map(key_segment);
+            let (matching, kept): (Vec<_>, Vec<_>) = runs.into_iter().partition(|run| {
+                engagement
+                    .as_ref()
+                    .is_none_or(|name| run.engagement.as_ref() == Some(name))
+                    && selection
+                        .older_than_ms
+                        .is_none_or(|window| now.saturating_sub(run.modified_ms) > window)
+            });
+            runs = kept;
+            purged.extend(matching.into_iter().map(|run| (run, "manual")));
+        }
+        None => {
+            let limits = config
+                .engagements
+                .iter()
+                .map(|(name, limits)| (key_segment(name), limits))
+                .collect::<BTreeMap<_, _>>();
+            let limits_of = |run: &StoredRun| {
+                run.engagement
+                    .as_ref()
+                    .and_then(|name| limits.get(name))
+                    .copied()
+            };
+
+            let mut kept = Vec::new();
+            for run in runs {
+                let max_age = limits_of(&run)
+                    .and_then(|limits| limits.max_age.as_ref())
+                    .or(config.max_age.as_ref());
+                match max_age {
+                    Some(max_age)
+                        if now.saturating_sub(run.modified_ms) > parse_window(max_age)? =>
+                    {
+                        purged.push((run, "max_age"))
+                    }
+                    _ => kept.push(run),
+                }
+            }
+            runs = kept;
+
+            for (name, limits) in &limits {
+                if let Some(max_bytes) = limits.max_bytes {
+                    let (group, rest): (Vec<_>, Vec<_>) = runs
+                        .into_iter()
+                        .partition(|run| run.engagement.as_ref() == Some(name));
+                    runs = rest;
+                    runs.extend(trim_to(group, max_bytes, &mut purged));
+                    runs.sort_by_key(|run| run.modified_ms);
+                }
+            }
+            if let Some(max_bytes) = config.max_bytes {
+                runs = trim_to(runs, max_bytes, &mut purged);
+            }
+        }
+    }
+
+    let mut report = Vec::new();
+    let mut bytes = 0;
+    for (run, reason) in &purged {
+        if !dry_run {
+            remove_run(dir, &run.path, config.secure_delete)?;
+        }
+        bytes += run.bytes;
+        report.push(json!({
+            "path": run.path.display().to_string(),
+            "engagement": run.engagement,
+            "bytes": run.bytes,
+            "age_ms": now.saturating_sub(run.modified_ms),
+            "reason": reason
+        }));
+    }
+    Ok(json!({
+        "dir": dir.display().to_string(),
+        "dry_run": dry_run,
+        "secure_delete": config.secure_delete,
+        "purged": report.len(),
+        "purged_bytes": bytes,
+        "kept": runs.len(),
+        "kept_bytes": runs.iter().map(|run| run.bytes).sum::<u64>(),
+        "runs": report
+    }))
+}
+
+fn trim_to(
+    runs: Vec<StoredRun>,
+    max_bytes: u64,
+    purged: &mut Vec<(StoredRun, &'static str)>,
+) -> Vec<StoredRun> {
+    let mut total = runs.iter().map(|run| run.bytes).sum::<u64>();
+    let mut kept = Vec::new();
+    for run in runs {
+        if total > max_bytes {
+            total -= run.bytes;
+            purged.push((run, "max_bytes"));
+        } else {
+            kept.push(run);
+        }
+    }
+    kept
+}
+
+fn scan(dir: &Path, components: &mut Vec<String>, runs: &mut Vec<StoredRun>) -> Result<()> {
+    if dir.join("workspace").is_dir() && components.len() >= 2 {
+        let (bytes, modified_ms) = usage(dir)?;
+        runs.push(StoredRun {
+            path: dir.to_path_buf(),
+            engagement: (components.len() == 3).then(|| components[0].clone()),
+            modified_ms,
+            bytes,
+        });
+        return Ok(());
+    }
+    if components.len() >= 3 {
+        return Ok(());
+    }
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Verzeichnis {} konnte nicht gelesen werden", dir.display()))?;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            components.push(entry.file_name().to_string_lossy().to_string());
+            scan(&entry.path(), components, runs)?;
+            components.pop();
+        }
+    }
+    Ok(())
+}
+
+fn usage(dir: &Path) -> Result<(u64, u128)> {
+    let (mut bytes, mut modified_ms) = (0, modified(dir)?);
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("Verzeichnis {} konnte nicht gelesen werden", dir.display()))?
+    {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let (inner_bytes, inner_modified) = usage(&entry.path())?;
+            bytes += inner_bytes;
+            modified_ms = modified_ms.max(inner_modified);
+        } else if file_type.is_file() {
+            bytes += entry.metadata()?.len();
+            modified_ms = modified_ms.max(modified(&entry.path())?);
+        }
+    }
+    Ok((bytes, modified_ms))
+}
+
+fn modified(path: &Path) -> Result<u128> {
+    let modified = fs::metadata(path)?.modified()?;
+    Ok(modified
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0))
+}
+
+fn remove_run(root: &Path, path: &Path, secure: bool) -> Result<()> {
+    if secure {
+        wipe(path)?;
+    }
+    fs::remove_dir_all(path)
+        .with_context(|| format!("{} konnte nicht gelöscht werden", path.display()))?;
+    let mut parent = path.parent();
+    while let Some(dir) = parent.filter(|dir| *dir != root && dir.starts_with(root)) {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+        parent = dir.parent();
+    }
+    Ok(())
+}
+
+fn wipe(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("Verzeichnis {} konnte nicht gelesen werden", dir.display()))?
+    {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            wipe(&entry.path())?;
+        } else if file_type.is_file() {
+            let path = entry.path();
+            let mut remaining = entry.metadata()?.len() as usize;
+            let mut file = OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .with_context(|| format!("{} konnte nicht überschrieben werden", path.display()))?;
+            let zeros = [0_u8; WIPE_CHUNK_BYTES];
+            while remaining > 0 {
+                let chunk = remaining.min(WIPE_CHUNK_BYTES);
+                file.write_all(&zeros[..chunk])?;
+                remaining -= chunk;
+            }
+            file.sync_all()
+                .with_context(|| format!("{} konnte nicht überschrieben werden", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_min_output_bytes")]
+    pub min_output_bytes: usize,
+    #[serde(default = "default_max_input_bytes")]
+    pub max_input_bytes: usize,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,
+    #[serde(default)]
+    pub replace_output: bool,
+    #[serde(default)]
+    pub model_hint: Option<String>,
+    #[serde(default = "default_prompt")]
+    pub prompt: String,
+}
+
+fn default_min_output_bytes() -> usize {
+    32 * 1024
+}
+
+fn default_max_input_bytes() -> usize {
+    128 * 1024
+}
+
+fn default_max_tokens() -> u32 {
+    800
+}
+
+fn default_timeout_sec() -> u64 {
+    120
+}
+
+fn default_prompt() -> String {
+    "Summarize the output of the security tool below for a penetration tester. List discovered hosts, open services, \
+     findings with their severity and errors. Be concise and do not invent results that are not in the output."
+        .to_string()
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_output_bytes: default_min_output_bytes(),
+            max_input_bytes: default_max_input_bytes(),
+            max_tokens: default_max_tokens(),
+            timeout_sec: default_timeout_sec(),
+            replace_output: false,
+            model_hint: None,
+            prompt: default_prompt(),
+        }
+    }
+}
+
+impl SamplingConfig {
+    pub fn applies(&self, output: &str) -> bool {
+        self.enabled && output.len() >= self.min_output_bytes
+    }
+
+    pub fn request(&self, tool: &str, output: &str) -> Value {
+        let mut end = output.len().min(self.max_input_bytes);
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut text = format!("Tool: {}\n\n{}", tool, &output[..end]);
+        if end < output.len() {
+            text.push_str(&format!(
+                "\n\n[output truncated after {} of {} bytes]",
+                end,
+                output.len()
+            ));
+        }
+        let mut params = json!({
+            "messages": [{"role": "user", "content": {"type": "text", "text": text}}],
+            "systemPrompt": self.prompt,
+            "includeContext": "none",
+            "maxTokens": self.max_tokens
+        });
+        if let Some(model) = &self.model_hint {
+            params["modelPreferences"] = json!({"hints": [{"name": model}], "speedPriority": 0.5});
+        }
+        params
+    }
+}
+
+pub fn summary(result: &Value) -> Option<String> {
+    let content = &result["content"];
+    let text = match content {
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        block if block["type"] == "text" => block["text"].as_str()?.to_string(),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
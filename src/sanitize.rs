@@ -0,0 +1,186 @@
+use regex::bytes::Regex;
+
+use crate::redact::StreamRedactor;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum EscapeState {
+    #[default]
+    Text,
+    Escape,
+    Csi,
+    Osc,
+    OscEscape,
+}
+
+#[derive(Debug)]
+pub struct LineFilter {
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    pending: Vec<u8>,
+}
+
+impl LineFilter {
+    pub fn new(include: Option<Regex>, exclude: Option<Regex>) -> Option<Self> {
+        if include.is_none() && exclude.is_none() {
+            return None;
+        }
+        Some(Self {
+            include,
+            exclude,
+            pending: Vec::new(),
+        })
+    }
+
+    fn keeps(&self, line: &[u8]) -> bool {
+        let text = line.strip_suffix(b"\n").unwrap_or(line);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_match(text))
+            && self
+                .exclude
+                .as_ref()
+                .is_none_or(|exclude| !exclude.is_match(text))
+    }
+
+    fn push(&mut self, data: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(data);
+        let Some(end) = self.pending.iter().rposition(|byte| *byte == b'\n') else {
+            return Vec::new();
+        };
+        let complete = self.pending.drain(..=end).collect::<Vec<_>>();
+        complete
+            .split_inclusive(|byte| *byte == b'\n')
+            .filter(|line| self.keeps(line))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn finish(&mut self) -> Vec<u8> {
+        let rest = std::mem::take(&mut self.pending);
+        if !rest.is_empty() && self.keeps(&rest) {
+            rest
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct OutputFilter {
+    strip_ansi: bool,
+    strip_control: bool,
+    stdout_state: EscapeState,
+    stderr_state: EscapeState,
+    redactor: StreamRedactor,
+    lines: Option<LineFilter>,
+}
+
+impl OutputFilter {
+    pub fn new(strip_ansi: bool, strip_control: bool) -> Self {
+        Self {
+            strip_ansi,
+            strip_control,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_redactor(mut self, redactor: StreamRedactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    pub fn with_lines(mut self, lines: Option<LineFilter>) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.strip_ansi || self.strip_control || self.lines.is_some()
+    }
+
+    pub fn redacts(&self) -> bool {
+        self.redactor.is_active()
+    }
+
+    pub fn redact(&mut self, is_stdout: bool, data: &[u8]) -> Vec<u8> {
+        self.redactor.apply(is_stdout, data)
+    }
+
+    pub fn flush_redacted(&mut self, is_stdout: bool) -> Vec<u8> {
+        self.redactor.flush(is_stdout)
+    }
+
+    pub fn apply(&mut self, is_stdout: bool, data: &[u8]) -> Vec<u8> {
+        let out = self.strip(is_stdout, data);
+        match self.lines.as_mut() {
+            Some(lines) if is_stdout => lines.push(&out),
+            _ => out,
+        }
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        self.lines
+            .as_mut()
+            .map(LineFilter::finish)
+            .unwrap_or_default()
+    }
+
+    fn strip(&mut self, is_stdout: bool, data: &[u8]) -> Vec<u8> {
+        if !self.strip_ansi && !self.strip_control {
+            return data.to_vec();
+        }
+        let strip_ansi = self.strip_ansi;
+        let strip_control = self.strip_control;
+        let state = if is_stdout {
+            &mut self.stdout_state
+        } else {
+            &mut self.stderr_state
+        };
+        let mut out = Vec::with_capacity(data.len());
+
+        for &byte in data {
+            match *state {
+                EscapeState::Text => {
+                    if strip_ansi && byte == 0x1b {
+                        *state = EscapeState::Escape;
+                    } else if strip_control && is_control(byte) {
+                        continue;
+                    } else {
+                        out.push(byte);
+                    }
+                }
+                EscapeState::Escape => {
+                    *state = match byte {
+                        b'[' => EscapeState::Csi,
+                        b']' => EscapeState::Osc,
+                        _ => EscapeState::Text,
+                    };
+                }
+                EscapeState::Csi => {
+                    if (0x40..=0x7e).contains(&byte) {
+                        *state = EscapeState::Text;
+                    }
+                }
+                EscapeState::Osc => match byte {
+                    0x07 => *state = EscapeState::Text,
+                    0x1b => *state = EscapeState::OscEscape,
+                    _ => {}
+                },
+                EscapeState::OscEscape => {
+                    *state = if byte == b'\\' {
+                        EscapeState::Text
+                    } else {
+                        EscapeState::Osc
+                    };
+                }
+            }
+        }
+        out
+    }
+}
+
+fn is_control(byte: u8) -> bool {
+    (byte < 0x20 && byte != b'\n' && byte != b'\t') || byte == 0x7f
+}
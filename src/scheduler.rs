@@ -0,0 +1,456 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::{broadcast, watch};
+use tokio::time::Instant;
+
+use crate::messages::Message;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+    #[serde(default = "default_max_sessions_per_host")]
+    pub max_sessions_per_host: usize,
+    #[serde(default = "default_max_queue")]
+    pub max_queue: usize,
+    #[serde(default = "default_queue_timeout")]
+    pub queue_timeout_sec: u64,
+}
+
+fn default_max_sessions() -> usize {
+    4
+}
+
+fn default_max_sessions_per_host() -> usize {
+    2
+}
+
+fn default_max_queue() -> usize {
+    32
+}
+
+fn default_queue_timeout() -> u64 {
+    120
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: default_max_sessions(),
+            max_sessions_per_host: default_max_sessions_per_host(),
+            max_queue: default_max_queue(),
+            queue_timeout_sec: default_queue_timeout(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Concurrency {
+    Exclusive,
+    Shared(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Claim {
+    host: String,
+    tool: Option<String>,
+    concurrency: Option<Concurrency>,
+    timeout: Duration,
+}
+
+impl Claim {
+    pub fn tool(
+        host: &str,
+        tool: &str,
+        concurrency: Option<Concurrency>,
+        timeout_sec: u64,
+    ) -> Self {
+        Self {
+            host: host.to_string(),
+            tool: Some(tool.to_string()),
+            concurrency,
+            timeout: Duration::from_secs(timeout_sec),
+        }
+    }
+
+    fn exclusive(&self) -> bool {
+        self.concurrency == Some(Concurrency::Exclusive)
+    }
+
+    fn tool_key(&self) -> Option<(String, String)> {
+        self.tool
+            .as_ref()
+            .map(|tool| (self.host.clone(), tool.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Waiting(usize),
+    Admitted,
+}
+
+struct Waiter {
+    ticket: u64,
+    claim: Claim,
+    slot: watch::Sender<Slot>,
+}
+
+#[derive(Default)]
+struct State {
+    active_total: usize,
+    active_per_host: HashMap<String, usize>,
+    active_per_tool: HashMap<(String, String), usize>,
+    exclusive_hosts: HashSet<String>,
+    deadlines: HashMap<u64, (String, Instant)>,
+    queue: VecDeque<Waiter>,
+    next_ticket: u64,
+}
+
+struct Inner {
+    config: SchedulerConfig,
+    state: Mutex<State>,
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+    queued: broadcast::Sender<(String, Value)>,
+}
+
+pub enum Admission {
+    Granted(SessionPermit),
+    Queued(Ticket),
+}
+
+pub struct SessionPermit {
+    inner: Arc<Inner>,
+    session: u64,
+    claim: Claim,
+}
+
+pub struct Ticket {
+    inner: Arc<Inner>,
+    ticket: u64,
+    claim: Claim,
+    slot: watch::Receiver<Slot>,
+    position: usize,
+    deadline: Instant,
+    done: bool,
+}
+
+impl Scheduler {
+    pub fn new(config: &SchedulerConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                config: config.clone(),
+                state: Mutex::new(State::default()),
+            }),
+            queued: broadcast::channel(64).0,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<(String, Value)> {
+        self.queued.subscribe()
+    }
+
+    pub fn announce(&self, id: &str, progress: &Value) {
+        let _ = self.queued.send((id.to_string(), progress.clone()));
+    }
+
+    pub fn enqueue(&self, claim: Claim) -> Result<Admission> {
+        let inner = &self.inner;
+        let mut state = inner.state.lock().expect("scheduler state poisoned");
+
+        let exclusive_waiting = state
+            .queue
+            .iter()
+            .any(|waiter| waiter.claim.host == claim.host && waiter.claim.exclusive());
+        if !exclusive_waiting && inner.has_capacity(&state, &claim) {
+            state.next_ticket += 1;
+            let session = state.next_ticket;
+            inner.admit(&mut state, session, &claim);
+            return Ok(Admission::Granted(SessionPermit {
+                inner: inner.clone(),
+                session,
+                claim,
+            }));
+        }
+
+        if state.queue.len() >= inner.config.max_queue {
+            bail!(Message::QueueFull {
+                max: inner.config.max_queue
+            });
+        }
+
+        state.next_ticket += 1;
+        let ticket = state.next_ticket;
+        let position = state.queue.len() + 1;
+        let (tx, rx) = watch::channel(Slot::Waiting(position));
+        state.queue.push_back(Waiter {
+            ticket,
+            claim: claim.clone(),
+            slot: tx,
+        });
+
+        Ok(Admission::Queued(Ticket {
+            inner: inner.clone(),
+            ticket,
+            claim,
+            slot: rx,
+            position,
+            deadline: Instant::now() + Duration::from_secs(inner.config.queue_timeout_sec),
+            done: false,
+        }))
+    }
+}
+
+impl Scheduler {
+    pub fn active_sessions(&self) -> usize {
+        self.inner
+            .state
+            .lock()
+            .expect("scheduler state poisoned")
+            .active_total
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.inner
+            .state
+            .lock()
+            .expect("scheduler state poisoned")
+            .queue
+            .len()
+    }
+
+    pub fn hosts(&self) -> Value {
+        let state = self.inner.state.lock().expect("scheduler state poisoned");
+        let mut hosts = BTreeMap::<&str, (usize, usize)>::new();
+        for (host, active) in &state.active_per_host {
+            hosts.entry(host).or_default().0 = *active;
+        }
+        for waiter in &state.queue {
+            hosts.entry(&waiter.claim.host).or_default().1 += 1;
+        }
+        hosts
+            .into_iter()
+            .map(|(host, (active, queued))| {
+                let exclusive = state.exclusive_hosts.contains(host);
+                let saturated = exclusive || active >= self.inner.config.max_sessions_per_host;
+                json!({"host": host, "active": active, "queued": queued, "saturated": saturated, "exclusive": exclusive})
+            })
+            .collect()
+    }
+}
+
+impl Inner {
+    fn has_capacity(&self, state: &State, claim: &Claim) -> bool {
+        let host_active = state.active_per_host.get(&claim.host).copied().unwrap_or(0);
+        if state.active_total >= self.config.max_sessions
+            || host_active >= self.config.max_sessions_per_host
+            || state.exclusive_hosts.contains(&claim.host)
+        {
+            return false;
+        }
+        match claim.concurrency {
+            Some(Concurrency::Exclusive) => host_active == 0,
+            Some(Concurrency::Shared(limit)) => claim
+                .tool_key()
+                .is_none_or(|key| state.active_per_tool.get(&key).copied().unwrap_or(0) < limit),
+            None => true,
+        }
+    }
+
+    fn admit(&self, state: &mut State, session: u64, claim: &Claim) {
+        state.active_total += 1;
+        state.deadlines.insert(
+            session,
+            (claim.host.clone(), Instant::now() + claim.timeout),
+        );
+        *state.active_per_host.entry(claim.host.clone()).or_insert(0) += 1;
+        if let Some(key) = claim.tool_key() {
+            *state.active_per_tool.entry(key).or_insert(0) += 1;
+        }
+        if claim.exclusive() {
+            state.exclusive_hosts.insert(claim.host.clone());
+        }
+    }
+
+    fn release(&self, session: u64, claim: &Claim) {
+        let mut state = self.state.lock().expect("scheduler state poisoned");
+        state.active_total = state.active_total.saturating_sub(1);
+        state.deadlines.remove(&session);
+        if let Some(count) = state.active_per_host.get_mut(&claim.host) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.active_per_host.remove(&claim.host);
+            }
+        }
+        if let Some(key) = claim.tool_key()
+            && let Some(count) = state.active_per_tool.get_mut(&key)
+        {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                state.active_per_tool.remove(&key);
+            }
+        }
+        if claim.exclusive() {
+            state.exclusive_hosts.remove(&claim.host);
+        }
+        self.dispatch(&mut state);
+    }
+
+    fn dispatch(&self, state: &mut State) {
+        let mut index = 0;
+        let mut blocked = HashSet::new();
+        while index < state.queue.len() && state.active_total < self.config.max_sessions {
+            let claim = state.queue[index].claim.clone();
+            if !blocked.contains(&claim.host) && self.has_capacity(state, &claim) {
+                let waiter = state.queue.remove(index).expect("queue index in range");
+                self.admit(state, waiter.ticket, &claim);
+                let _ = waiter.slot.send(Slot::Admitted);
+            } else {
+                if claim.exclusive() {
+                    blocked.insert(claim.host);
+                }
+                index += 1;
+            }
+        }
+        for (offset, waiter) in state.queue.iter().enumerate() {
+            let position = offset + 1;
+            waiter.slot.send_if_modified(|slot| {
+                if *slot == Slot::Waiting(position) {
+                    false
+                } else {
+                    *slot = Slot::Waiting(position);
+                    true
+                }
+            });
+        }
+    }
+
+    fn estimate(&self, ticket: u64) -> Option<Instant> {
+        let state = self.state.lock().expect("scheduler state poisoned");
+        let index = state
+            .queue
+            .iter()
+            .position(|waiter| waiter.ticket == ticket)?;
+        let claim = &state.queue[index].claim;
+        let host_bound = state.active_total < self.config.max_sessions;
+        let competes = |host: &String| !host_bound || *host == claim.host;
+        let mut free = state
+            .deadlines
+            .values()
+            .filter(|(host, _)| competes(host))
+            .map(|(_, deadline)| *deadline)
+            .collect::<Vec<_>>();
+        for waiter in state
+            .queue
+            .iter()
+            .take(index)
+            .filter(|waiter| competes(&waiter.claim.host))
+        {
+            let start = take_slot(&mut free, waiter.claim.exclusive())?;
+            free.push(start + waiter.claim.timeout);
+        }
+        let start = take_slot(&mut free, claim.exclusive())?;
+        Some(start.max(Instant::now()))
+    }
+
+    fn withdraw(&self, ticket: u64) -> bool {
+        let mut state = self.state.lock().expect("scheduler state poisoned");
+        let Some(index) = state
+            .queue
+            .iter()
+            .position(|waiter| waiter.ticket == ticket)
+        else {
+            return false;
+        };
+        state.queue.remove(index);
+        self.dispatch(&mut state);
+        true
+    }
+}
+
+fn take_slot(free: &mut Vec<Instant>, exclusive: bool) -> Option<Instant> {
+    if exclusive {
+        let last = free.iter().max().copied()?;
+        free.clear();
+        return Some(last);
+    }
+    let (index, _) = free
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, deadline)| **deadline)?;
+    Some(free.swap_remove(index))
+}
+
+impl Ticket {
+    pub fn progress(&self) -> Value {
+        let eta = self
+            .inner
+            .estimate(self.ticket)
+            .map(|start| start.saturating_duration_since(Instant::now()));
+        let now_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|value| value.as_millis())
+            .unwrap_or(0);
+        json!({
+            "position": self.position,
+            "eta_sec": eta.map(|eta| eta.as_secs_f64().ceil() as u64),
+            "estimated_start_ms": eta.map(|eta| now_ms + eta.as_millis())
+        })
+    }
+
+    pub async fn next(&mut self) -> Result<Option<SessionPermit>> {
+        match tokio::time::timeout_at(self.deadline, self.slot.changed()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) | Err(_) => {
+                if self.inner.withdraw(self.ticket) {
+                    self.done = true;
+                    bail!(Message::QueueTimeout {
+                        timeout_sec: self.inner.config.queue_timeout_sec
+                    });
+                }
+            }
+        }
+
+        match *self.slot.borrow_and_update() {
+            Slot::Admitted => {
+                self.done = true;
+                Ok(Some(SessionPermit {
+                    inner: self.inner.clone(),
+                    session: self.ticket,
+                    claim: self.claim.clone(),
+                }))
+            }
+            Slot::Waiting(position) => {
+                self.position = position;
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        if self.done {
+            return;
+        }
+        if !self.inner.withdraw(self.ticket) {
+            self.inner.release(self.ticket, &self.claim);
+        }
+    }
+}
+
+impl Drop for SessionPermit {
+    fn drop(&mut self) {
+        self.inner.release(self.session, &self.claim);
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::{Result, bail};
+use regex::Regex;
+use rhai::{AST, Array, Dynamic, Engine, EvalAltResult, Scope};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::log_observation;
+use crate::messages::Message;
+
+const VAR_PREFIX: &str = "{{vars.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_max_operations")]
+    pub max_operations: u64,
+    #[serde(default = "default_max_string_bytes")]
+    pub max_string_bytes: usize,
+    #[serde(default = "default_max_collection_len")]
+    pub max_collection_len: usize,
+}
+
+fn default_max_operations() -> u64 {
+    1_000_000
+}
+
+fn default_max_string_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_max_collection_len() -> usize {
+    10_000
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_operations: default_max_operations(),
+            max_string_bytes: default_max_string_bytes(),
+            max_collection_len: default_max_collection_len(),
+        }
+    }
+}
+
+pub struct Scripts {
+    engine: Engine,
+}
+
+impl Scripts {
+    pub fn new(config: &ScriptingConfig, workflow: &str) -> Self {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(config.max_operations)
+            .set_max_string_size(config.max_string_bytes)
+            .set_max_array_size(config.max_collection_len)
+            .set_max_map_size(config.max_collection_len)
+            .set_max_expr_depths(64, 32)
+            .set_max_call_levels(32);
+        let id = workflow.to_string();
+        engine.on_print(move |text| {
+            log_observation("script_print", json!({"workflow": id, "text": text}))
+        });
+        engine.on_debug(|_, _, _| {});
+        engine.register_fn("regex_all", regex_all);
+        engine.register_fn("join", |items: Array, separator: &str| {
+            items
+                .iter()
+                .map(Dynamic::to_string)
+                .collect::<Vec<_>>()
+                .join(separator)
+        });
+        Self { engine }
+    }
+
+    pub fn compile(&self, script: &str) -> Result<AST> {
+        self.engine.compile(script).map_err(|error| {
+            Message::ScriptFailed {
+                stage: "compile",
+                error: error.to_string(),
+            }
+            .into()
+        })
+    }
+
+    pub fn condition(&self, ast: &AST, vars: &Map<String, Value>, last: &Value) -> Result<bool> {
+        let mut scope = scope(vars, last)?;
+        self.engine
+            .eval_ast_with_scope::<bool>(&mut scope, ast)
+            .map_err(|error| {
+                Message::ScriptFailed {
+                    stage: "when",
+                    error: error.to_string(),
+                }
+                .into()
+            })
+    }
+
+    pub fn run(&self, ast: &AST, vars: &mut Map<String, Value>, result: &Value) -> Result<Value> {
+        let mut scope = scope(vars, result)?;
+        let returned = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, ast)
+            .map_err(|error| Message::ScriptFailed {
+                stage: "script",
+                error: error.to_string(),
+            })?;
+        let updated = scope.get_value::<Dynamic>("vars").unwrap_or_default();
+        *vars = rhai::serde::from_dynamic(&updated).map_err(|error| Message::ScriptFailed {
+            stage: "vars",
+            error: error.to_string(),
+        })?;
+        if returned.is_unit() {
+            return Ok(Value::Null);
+        }
+        rhai::serde::from_dynamic(&returned).map_err(|error| {
+            Message::ScriptFailed {
+                stage: "result",
+                error: error.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+fn scope(vars: &Map<String, Value>, result: &Value) -> Result<Scope<'static>> {
+    let mut scope = Scope::new();
+    let failed = |stage, error: Box<EvalAltResult>| Message::ScriptFailed {
+        stage,
+        error: error.to_string(),
+    };
+    scope.push(
+        "vars",
+        rhai::serde::to_dynamic(vars).map_err(|error| failed("vars", error))?,
+    );
+    scope.push_constant(
+        "result",
+        rhai::serde::to_dynamic(result).map_err(|error| failed("result", error))?,
+    );
+    Ok(scope)
+}
+
+fn regex_all(text: &str, pattern: &str) -> std::result::Result<Array, Box<EvalAltResult>> {
+    let regex = Regex::new(pattern).map_err(|error| error.to_string())?;
+    Ok(regex
+        .captures_iter(text)
+        .filter_map(|captures| captures.get(1).or_else(|| captures.get(0)))
+        .map(|found| Dynamic::from(found.as_str().to_string()))
+        .collect())
+}
+
+pub fn render(text: &str, vars: &Map<String, Value>) -> Result<String> {
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(VAR_PREFIX) {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + VAR_PREFIX.len()..];
+        let Some(end) = after.find("}}") else {
+            bail!(Message::PlaceholderIncomplete {
+                arg: text.to_string()
+            });
+        };
+        let name = after[..end].trim();
+        let value = vars.get(name).ok_or_else(|| Message::UnknownVariable {
+            name: name.to_string(),
+        })?;
+        rendered.push_str(&plain(value));
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+pub fn render_value(value: &Value, vars: &Map<String, Value>) -> Result<Value> {
+    Ok(match value {
+        Value::String(text) => Value::String(render(text, vars)?),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_value(item, vars))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), render_value(item, vars)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(plain).collect::<Vec<_>>().join(","),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+use crate::platform::OpenOptionsExt;
+
+const PLACEHOLDER_PREFIX: &str = "{{secret:";
+const PLACEHOLDER_SUFFIX: &str = "}}";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    #[serde(default)]
+    pub file: Option<SecretFileConfig>,
+    #[serde(default)]
+    pub entries: BTreeMap<String, SecretSource>,
+    #[serde(default = "default_command_timeout_sec")]
+    pub command_timeout_sec: u64,
+}
+
+fn default_command_timeout_sec() -> u64 {
+    10
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            entries: BTreeMap::new(),
+            command_timeout_sec: default_command_timeout_sec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretFileConfig {
+    pub path: String,
+    pub key_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum SecretSource {
+    Env {
+        var: String,
+    },
+    File {
+        #[serde(default)]
+        key: Option<String>,
+    },
+    Command {
+        command: Vec<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SecretsAction {
+    Keygen {
+        #[arg(long)]
+        out: String,
+    },
+    Set {
+        name: String,
+    },
+    Remove {
+        name: String,
+    },
+    List,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SealedFile {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Default)]
+pub struct SecretValues {
+    values: Vec<(String, String)>,
+    expansions: Vec<(String, String)>,
+}
+
+impl SecretValues {
+    pub fn with_expansions(mut self, expansions: Vec<(String, String)>) -> Self {
+        self.expansions = expansions;
+        self
+    }
+
+    pub fn substitute(&self, arg: &str) -> String {
+        let mut out = arg.to_string();
+        for (name, value) in &self.values {
+            out = out.replace(&placeholder(name), value);
+        }
+        for (placeholder, value) in &self.expansions {
+            out = out.replace(placeholder, value);
+        }
+        out
+    }
+
+    pub fn redaction_pairs(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (value.as_bytes().to_vec(), placeholder(name).into_bytes()))
+            .collect()
+    }
+}
+
+pub fn placeholder(name: &str) -> String {
+    format!("{}{}{}", PLACEHOLDER_PREFIX, name, PLACEHOLDER_SUFFIX)
+}
+
+pub fn placeholders(arg: &str) -> Result<Vec<&str>> {
+    let mut names = Vec::new();
+    let mut rest = arg;
+    while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+        let after = &rest[start + PLACEHOLDER_PREFIX.len()..];
+        let end = after
+            .find(PLACEHOLDER_SUFFIX)
+            .ok_or_else(|| Message::PlaceholderIncomplete {
+                arg: arg.to_string(),
+            })?;
+        let name = &after[..end];
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.'))
+        {
+            bail!(Message::InvalidValue {
+                field: "secret",
+                value: name.to_string()
+            });
+        }
+        names.push(name);
+        rest = &after[end + PLACEHOLDER_SUFFIX.len()..];
+    }
+    Ok(names)
+}
+
+pub fn check_args(
+    config: &SecretsConfig,
+    tool: &str,
+    allowed: &[String],
+    args: &[String],
+) -> Result<()> {
+    for arg in args {
+        for name in placeholders(arg)? {
+            if !allowed.iter().any(|allowed| allowed == name) {
+                bail!(Message::NotReleased {
+                    key: format!("tools.{}.secrets", tool),
+                    name: name.to_string()
+                });
+            }
+            if !config.entries.contains_key(name) {
+                bail!(Message::SecretNotConfigured {
+                    name: name.to_string()
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn resolve<'a>(
+    config: &SecretsConfig,
+    args: impl IntoIterator<Item = &'a String>,
+) -> Result<SecretValues> {
+    let mut names = Vec::new();
+    for arg in args {
+        for name in placeholders(arg)? {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+
+    let mut values = Vec::new();
+    let mut sealed = None;
+    for name in names {
+        let source = config
+            .entries
+            .get(name)
+            .ok_or_else(|| Message::SecretNotConfigured {
+                name: name.to_string(),
+            })?;
+        let value = match source {
+            SecretSource::Env { var } => {
+                std::env::var(var).map_err(|_| Message::SecretEnvMissing {
+                    name: name.to_string(),
+                    var: var.clone(),
+                })?
+            }
+            SecretSource::File { key } => {
+                if sealed.is_none() {
+                    sealed = Some(read_file(config)?);
+                }
+                let key = key.as_deref().unwrap_or(name);
+                sealed
+                    .as_ref()
+                    .and_then(|entries: &BTreeMap<String, String>| entries.get(key))
+                    .cloned()
+                    .ok_or_else(|| Message::SecretFileEntryMissing {
+                        name: name.to_string(),
+                        key: key.to_string(),
+                    })?
+            }
+            SecretSource::Command { command } => {
+                run_command(name, command, config.command_timeout_sec).await?
+            }
+        };
+        if value.is_empty() {
+            bail!(Message::SecretEmpty {
+                name: name.to_string()
+            });
+        }
+        values.push((name.to_string(), value));
+    }
+    Ok(SecretValues {
+        values,
+        expansions: Vec::new(),
+    })
+}
+
+pub fn known_values(config: &SecretsConfig) -> Vec<(String, String)> {
+    let sealed = config.file.as_ref().and_then(|_| read_file(config).ok());
+    config
+        .entries
+        .iter()
+        .filter_map(|(name, source)| {
+            let value = match source {
+                SecretSource::Env { var } => std::env::var(var).ok(),
+                SecretSource::File { key } => sealed
+                    .as_ref()
+                    .and_then(|entries| entries.get(key.as_deref().unwrap_or(name)))
+                    .cloned(),
+                SecretSource::Command { .. } => None,
+            };
+            value.map(|value| (value, placeholder(name)))
+        })
+        .collect()
+}
+
+async fn run_command(name: &str, command: &[String], timeout_sec: u64) -> Result<String> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| Message::SecretCommandFailed {
+            name: name.to_string(),
+        })?;
+    let output = tokio::time::timeout(
+        Duration::from_secs(timeout_sec),
+        tokio::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| Message::SecretCommandTimeout {
+        name: name.to_string(),
+        timeout_sec,
+    })?
+    .with_context(|| Message::SecretCommandFailed {
+        name: name.to_string(),
+    })?;
+    if !output.status.success() {
+        bail!(Message::SecretCommandExit {
+            name: name.to_string(),
+            status: output.status.to_string()
+        });
+    }
+    let value = String::from_utf8(output.stdout).map_err(|_| Message::SecretNotUtf8 {
+        name: name.to_string(),
+    })?;
+    Ok(value.trim_end_matches(['\r', '\n']).to_string())
+}
+
+pub fn apply(config: &SecretsConfig, action: SecretsAction) -> Result<Value> {
+    match action {
+        SecretsAction::Keygen { out } => {
+            let mut file = OpenOptions::new()
+                .create_new(true)
+                .write(true)
+                .mode(0o600)
+                .open(&out)
+                .with_context(|| Message::KeyFileWriteFailed { path: out.clone() })?;
+            writeln!(file, "{}", BASE64.encode(random_bytes::<32>()?))
+                .with_context(|| Message::KeyFileWriteFailed { path: out.clone() })?;
+            Ok(json!({"key_path": out}))
+        }
+        SecretsAction::Set { name } => {
+            placeholders(&placeholder(&name))?;
+            let mut value = String::new();
+            std::io::stdin()
+                .read_to_string(&mut value)
+                .context(Message::SecretValueMissing)?;
+            let value = value.trim_end_matches(['\r', '\n']).to_string();
+            if value.is_empty() {
+                bail!(Message::SecretValueMissing);
+            }
+            let mut entries = read_file_or_empty(config)?;
+            entries.insert(name.clone(), value);
+            write_file(config, &entries)?;
+            Ok(json!({"set": name}))
+        }
+        SecretsAction::Remove { name } => {
+            let mut entries = read_file_or_empty(config)?;
+            if entries.remove(&name).is_none() {
+                bail!(Message::SecretNotStored { name });
+            }
+            write_file(config, &entries)?;
+            Ok(json!({"removed": name}))
+        }
+        SecretsAction::List => {
+            let stored = match &config.file {
+                Some(file) if Path::new(&file.path).exists() => {
+                    read_file(config)?.into_keys().collect()
+                }
+                _ => Vec::new(),
+            };
+            let entries = config
+                .entries
+                .iter()
+                .map(|(name, source)| {
+                    let provider = match source {
+                        SecretSource::Env { .. } => "env",
+                        SecretSource::File { .. } => "file",
+                        SecretSource::Command { .. } => "command",
+                    };
+                    json!({"name": name, "provider": provider, "placeholder": placeholder(name)})
+                })
+                .collect::<Vec<_>>();
+            Ok(json!({"entries": entries, "file_entries": stored}))
+        }
+    }
+}
+
+pub fn store(config: &SecretsConfig, name: &str, value: &str) -> Result<()> {
+    static WRITER: Mutex<()> = Mutex::new(());
+    placeholders(&placeholder(name))?;
+    let _guard = WRITER.lock().expect("secrets writer poisoned");
+    let mut entries = read_file_or_empty(config)?;
+    entries.insert(name.to_string(), value.to_string());
+    write_file(config, &entries)
+}
+
+fn file_config(config: &SecretsConfig) -> Result<&SecretFileConfig> {
+    config
+        .file
+        .as_ref()
+        .ok_or_else(|| Message::SecretFileNotConfigured.into())
+}
+
+fn load_key(path: &str) -> Result<Key> {
+    let invalid = || Message::SecretKeyInvalid {
+        path: path.to_string(),
+    };
+    let content = fs::read_to_string(path).with_context(invalid)?;
+    let bytes = BASE64.decode(content.trim()).map_err(|_| invalid())?;
+    if bytes.len() != 32 {
+        bail!(invalid());
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+fn read_file(config: &SecretsConfig) -> Result<BTreeMap<String, String>> {
+    let file = file_config(config)?;
+    let unreadable = || Message::SecretFileUnreadable {
+        path: file.path.clone(),
+    };
+    let content = fs::read_to_string(&file.path).with_context(unreadable)?;
+    let sealed: SealedFile = serde_json::from_str(&content).map_err(|_| unreadable())?;
+    let nonce = BASE64.decode(&sealed.nonce).map_err(|_| unreadable())?;
+    if nonce.len() != 12 {
+        bail!(unreadable());
+    }
+    let ciphertext = BASE64
+        .decode(&sealed.ciphertext)
+        .map_err(|_| unreadable())?;
+    let plaintext = ChaCha20Poly1305::new(&load_key(&file.key_path)?)
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| Message::SecretFileDecryptFailed {
+            path: file.path.clone(),
+        })?;
+    serde_json::from_slice(&plaintext).map_err(|_| unreadable().into())
+}
+
+fn read_file_or_empty(config: &SecretsConfig) -> Result<BTreeMap<String, String>> {
+    if Path::new(&file_config(config)?.path).exists() {
+        read_file(config)
+    } else {
+        Ok(BTreeMap::new())
+    }
+}
+
+fn write_file(config: &SecretsConfig, entries: &BTreeMap<String, String>) -> Result<()> {
+    let file = file_config(config)?;
+    let nonce = random_bytes::<12>()?;
+    let ciphertext = ChaCha20Poly1305::new(&load_key(&file.key_path)?)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            serde_json::to_vec(entries)?.as_slice(),
+        )
+        .map_err(|_| Message::SecretFileWriteFailed {
+            path: file.path.clone(),
+        })?;
+    let sealed = SealedFile {
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+
+    let tmp = format!("{}.tmp", file.path);
+    let mut out = OpenOptions::new()
+        .create(true)
+        .truncate(true)
+        .write(true)
+        .mode(0o600)
+        .open(&tmp)
+        .with_context(|| Message::SecretFileWriteFailed { path: tmp.clone() })?;
+    out.write_all(serde_json::to_string_pretty(&sealed)?.as_bytes())
+        .and_then(|()| out.sync_all())
+        .with_context(|| Message::SecretFileWriteFailed { path: tmp.clone() })?;
+    fs::rename(&tmp, &file.path).with_context(|| Message::SecretFileWriteFailed {
+        path: file.path.clone(),
+    })
+}
+
+pub fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0_u8; N];
+    OsRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|_| Message::RandomFailed)?;
+    Ok(bytes)
+}
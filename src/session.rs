@@ -0,0 +1,295 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::log_observation;
+use crate::messages::Message;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout_sec: u64,
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+    #[serde(default = "default_max_per_client")]
+    pub max_per_client: usize,
+    #[serde(default)]
+    pub rate_limit_per_min: Option<usize>,
+    #[serde(default = "default_history_limit")]
+    pub history_limit: usize,
+}
+
+fn default_idle_timeout() -> u64 {
+    1800
+}
+
+fn default_max_sessions() -> usize {
+    64
+}
+
+fn default_max_per_client() -> usize {
+    8
+}
+
+fn default_history_limit() -> usize {
+    100
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout_sec: default_idle_timeout(),
+            max_sessions: default_max_sessions(),
+            max_per_client: default_max_per_client(),
+            rate_limit_per_min: None,
+            history_limit: default_history_limit(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum SessionAction {
+    Info,
+    SetDefaultHost { host: Option<String> },
+    History { limit: Option<usize> },
+}
+
+struct Session {
+    client: String,
+    created_ms: u128,
+    last_seen: Instant,
+    default_host: Option<String>,
+    calls: VecDeque<Instant>,
+    runs: u64,
+    history: VecDeque<Value>,
+}
+
+pub struct Sessions {
+    config: SessionsConfig,
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl Sessions {
+    pub fn new(config: &SessionsConfig) -> Self {
+        Self {
+            config: config.clone(),
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn create(&self, client: &str) -> Result<String> {
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        self.expire(&mut sessions);
+        if sessions.len() >= self.config.max_sessions {
+            bail!(Message::SessionLimit {
+                max: self.config.max_sessions
+            });
+        }
+        let per_client = sessions
+            .values()
+            .filter(|session| session.client == client)
+            .count();
+        if per_client >= self.config.max_per_client {
+            bail!(Message::SessionClientLimit {
+                client: client.to_string(),
+                count: per_client
+            });
+        }
+        let id = session_id()?;
+        sessions.insert(
+            id.clone(),
+            Session {
+                client: client.to_string(),
+                created_ms: now_ms(),
+                last_seen: Instant::now(),
+                default_host: None,
+                calls: VecDeque::new(),
+                runs: 0,
+                history: VecDeque::new(),
+            },
+        );
+        log_observation(
+            "mcp_session_started",
+            json!({"session": id, "client": client}),
+        );
+        Ok(id)
+    }
+
+    pub fn touch(&self, id: &str, client: &str) -> Result<()> {
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        self.expire(&mut sessions);
+        match sessions.get_mut(id) {
+            Some(session) if session.client == client => {
+                session.last_seen = Instant::now();
+                Ok(())
+            }
+            _ => bail!(Message::SessionUnknown { id: id.to_string() }),
+        }
+    }
+
+    pub fn close(&self, id: &str, client: &str) -> bool {
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        if sessions
+            .get(id)
+            .is_none_or(|session| session.client != client)
+        {
+            return false;
+        }
+        sessions.remove(id);
+        log_observation(
+            "mcp_session_closed",
+            json!({"session": id, "client": client}),
+        );
+        true
+    }
+
+    pub fn admit_call(&self, id: &str) -> Result<()> {
+        let Some(limit) = self.config.rate_limit_per_min else {
+            return Ok(());
+        };
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Message::SessionUnknown { id: id.to_string() })?;
+        let now = Instant::now();
+        while session
+            .calls
+            .front()
+            .is_some_and(|call| now.duration_since(*call) >= RATE_WINDOW)
+        {
+            session.calls.pop_front();
+        }
+        if session.calls.len() >= limit {
+            bail!(Message::SessionRateLimited { limit });
+        }
+        session.calls.push_back(now);
+        Ok(())
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        self.expire(&mut sessions);
+        sessions.keys().cloned().collect()
+    }
+
+    pub fn default_host(&self, id: &str) -> Option<String> {
+        let sessions = self.sessions.lock().expect("sessions poisoned");
+        sessions
+            .get(id)
+            .and_then(|session| session.default_host.clone())
+    }
+
+    pub fn record(&self, id: &str, mut entry: Value) {
+        entry["ts_ms"] = json!(now_ms());
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        if let Some(session) = sessions.get_mut(id) {
+            session.runs += 1;
+            session.history.push_back(entry);
+            while session.history.len() > self.config.history_limit {
+                session.history.pop_front();
+            }
+        }
+    }
+
+    pub fn mcp_call(&self, id: &str, action: &str, arguments: Value) -> Result<Value> {
+        let mut arguments = match arguments {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        arguments.insert("action".to_string(), Value::String(action.to_string()));
+        let action =
+            serde_json::from_value::<SessionAction>(Value::Object(arguments)).map_err(|error| {
+                Message::InvalidArguments {
+                    tool: format!("session.{}", action),
+                    error: error.to_string(),
+                }
+            })?;
+
+        let mut sessions = self.sessions.lock().expect("sessions poisoned");
+        let session = sessions
+            .get_mut(id)
+            .ok_or_else(|| Message::SessionUnknown { id: id.to_string() })?;
+        match action {
+            SessionAction::Info => Ok(json!({
+                "session": id,
+                "client": session.client,
+                "created_ms": session.created_ms,
+                "default_host": session.default_host,
+                "runs": session.runs,
+                "rate_limit_per_min": self.config.rate_limit_per_min,
+                "calls_last_minute": session
+                    .calls
+                    .iter()
+                    .filter(|call| call.elapsed() < RATE_WINDOW)
+                    .count()
+            })),
+            SessionAction::SetDefaultHost { host } => {
+                session.default_host = host.filter(|host| !host.trim().is_empty());
+                Ok(json!({"session": id, "default_host": session.default_host}))
+            }
+            SessionAction::History { limit } => {
+                let limit = limit.unwrap_or(self.config.history_limit);
+                let skip = session.history.len().saturating_sub(limit);
+                Ok(
+                    json!({"session": id, "runs": session.history.iter().skip(skip).collect::<Vec<_>>()}),
+                )
+            }
+        }
+    }
+
+    fn expire(&self, sessions: &mut HashMap<String, Session>) {
+        let idle = Duration::from_secs(self.config.idle_timeout_sec);
+        sessions.retain(|id, session| {
+            let alive = session.last_seen.elapsed() < idle;
+            if !alive {
+                log_observation(
+                    "mcp_session_expired",
+                    json!({"session": id, "client": session.client}),
+                );
+            }
+            alive
+        });
+    }
+}
+
+pub fn mcp_tools() -> Vec<Value> {
+    vec![
+        json!({
+            "name": "session.info",
+            "description": "Shows this MCP session: client identity, default host, run count and rate limit usage",
+            "inputSchema": {"type": "object", "properties": {}}
+        }),
+        json!({
+            "name": "session.set_default_host",
+            "description": "Sets the Kali host used by tool calls of this session that omit host; empty clears it",
+            "inputSchema": {"type": "object", "properties": {"host": {"type": "string"}}}
+        }),
+        json!({
+            "name": "session.history",
+            "description": "Lists the runs started in this session, newest last",
+            "inputSchema": {"type": "object", "properties": {"limit": {"type": "integer", "minimum": 1}}}
+        }),
+    ]
+}
+
+pub fn session_id() -> Result<String> {
+    let mut bytes = [0_u8; 16];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| std::io::Read::read_exact(&mut random, &mut bytes))
+        .map_err(|_| Message::RandomFailed)?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
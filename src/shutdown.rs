@@ -0,0 +1,102 @@
+#[cfg(unix)]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(unix)]
+use tokio::signal::unix::{Signal, SignalKind, signal};
+use tokio::sync::watch;
+
+pub struct CancelSignal {
+    tx: watch::Sender<bool>,
+}
+
+impl CancelSignal {
+    pub fn new() -> Self {
+        let (tx, _) = watch::channel(false);
+        Self { tx }
+    }
+
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.tx.borrow()
+    }
+
+    pub async fn cancelled(&self) {
+        let mut rx = self.tx.subscribe();
+        let _ = rx.wait_for(|triggered| *triggered).await;
+    }
+}
+
+#[cfg(unix)]
+pub struct ShutdownSignals {
+    terminate: Signal,
+    interrupt: Signal,
+}
+
+#[cfg(unix)]
+impl ShutdownSignals {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())
+                .context("SIGTERM-Handler konnte nicht registriert werden")?,
+            interrupt: signal(SignalKind::interrupt())
+                .context("SIGINT-Handler konnte nicht registriert werden")?,
+        })
+    }
+
+    pub async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = self.terminate.recv() => "SIGTERM",
+            _ = self.interrupt.recv() => "SIGINT",
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub struct ShutdownSignals;
+
+#[cfg(not(unix))]
+impl ShutdownSignals {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub async fn recv(&mut self) -> &'static str {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        "CTRL_C"
+    }
+}
+
+#[cfg(unix)]
+pub struct Hangup(Signal);
+
+#[cfg(unix)]
+impl Hangup {
+    pub fn new() -> Result<Self> {
+        Ok(Self(signal(SignalKind::hangup()).context(
+            "SIGHUP-Handler konnte nicht registriert werden",
+        )?))
+    }
+
+    pub async fn recv(&mut self) {
+        self.0.recv().await;
+    }
+}
+
+#[cfg(not(unix))]
+pub struct Hangup;
+
+#[cfg(not(unix))]
+impl Hangup {
+    pub fn new() -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub async fn recv(&mut self) {
+        std::future::pending::<()>().await;
+    }
+}
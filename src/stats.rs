@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::labels::{self, Labels};
+
+#[derive(Debug, Default, Serialize)]
+pub struct ToolStats {
+    pub runs: u64,
+    pub succeeded: u64,
+    pub success_rate: f64,
+    pub timed_out: u64,
+    pub truncated: u64,
+    pub truncation_rate: f64,
+    pub retries: u64,
+    pub failed_to_start: u64,
+    pub p50_duration_ms: Option<u64>,
+    pub p95_duration_ms: Option<u64>,
+    #[serde(skip)]
+    durations: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HostStats {
+    pub host: String,
+    pub runs: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub since_ms: Option<u128>,
+    pub engagement: Option<String>,
+    #[serde(skip_serializing_if = "Labels::is_empty")]
+    pub labels: Labels,
+    pub runs: u64,
+    pub tools: BTreeMap<String, ToolStats>,
+    pub busiest_hosts: Vec<HostStats>,
+}
+
+pub fn summarize(
+    records: &[Value],
+    since_ms: Option<u128>,
+    engagement: Option<&str>,
+    label_filters: &[(String, String)],
+    top_hosts: usize,
+) -> Summary {
+    let mut started: HashMap<String, String> = HashMap::new();
+    let mut tools: BTreeMap<String, ToolStats> = BTreeMap::new();
+    let mut hosts: HashMap<String, u64> = HashMap::new();
+    let mut runs = 0;
+
+    for record in records {
+        let ts_ms = record["ts_ms"].as_u64().map(u128::from).unwrap_or(0);
+        if since_ms.is_some_and(|since| ts_ms < since) {
+            continue;
+        }
+        let entry = &record["entry"];
+        let Some(run_token) = entry["run_token"].as_str() else {
+            continue;
+        };
+
+        match record["kind"].as_str() {
+            Some("run_started") => {
+                if engagement.is_some_and(|name| entry["engagement"].as_str() != Some(name))
+                    || !labels::matches(&entry["labels"], label_filters)
+                {
+                    continue;
+                }
+                let tool = entry["tool"].as_str().unwrap_or("unknown").to_string();
+                let host = entry["host"]
+                    .as_str()
+                    .or_else(|| {
+                        entry["target"]
+                            .as_str()
+                            .map(|target| target.rsplit('@').next().unwrap_or(target))
+                    })
+                    .unwrap_or("unknown")
+                    .to_string();
+                let stats = tools.entry(tool.clone()).or_default();
+                stats.runs += 1;
+                if entry["attempt"].as_u64().unwrap_or(1) > 1 {
+                    stats.retries += 1;
+                }
+                *hosts.entry(host).or_insert(0) += 1;
+                runs += 1;
+                started.insert(run_token.to_string(), tool);
+            }
+            Some("run_finished") => {
+                let Some(tool) = started.get(run_token) else {
+                    continue;
+                };
+                let stats = tools.entry(tool.clone()).or_default();
+                let timed_out = entry["timed_out"].as_bool().unwrap_or(false);
+                if entry["exit_code"].as_i64() == Some(0) && !timed_out {
+                    stats.succeeded += 1;
+                }
+                if timed_out {
+                    stats.timed_out += 1;
+                }
+                if entry["truncated"].as_bool().unwrap_or(false) {
+                    stats.truncated += 1;
+                }
+                if let Some(duration) = entry["duration_ms"].as_u64() {
+                    stats.durations.push(duration);
+                }
+            }
+            Some("run_failed") => {
+                if let Some(tool) = started.get(run_token) {
+                    tools.entry(tool.clone()).or_default().failed_to_start += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for stats in tools.values_mut() {
+        stats.durations.sort_unstable();
+        stats.p50_duration_ms = percentile(&stats.durations, 50);
+        stats.p95_duration_ms = percentile(&stats.durations, 95);
+        if stats.runs > 0 {
+            stats.success_rate = stats.succeeded as f64 / stats.runs as f64;
+            stats.truncation_rate = stats.truncated as f64 / stats.runs as f64;
+        }
+    }
+
+    let mut busiest_hosts = hosts
+        .into_iter()
+        .map(|(host, runs)| HostStats { host, runs })
+        .collect::<Vec<_>>();
+    busiest_hosts.sort_by(|left, right| {
+        right
+            .runs
+            .cmp(&left.runs)
+            .then_with(|| left.host.cmp(&right.host))
+    });
+    busiest_hosts.truncate(top_hosts);
+
+    Summary {
+        since_ms,
+        engagement: engagement.map(str::to_string),
+        labels: label_filters.iter().cloned().collect(),
+        runs,
+        tools,
+        busiest_hosts,
+    }
+}
+
+fn percentile(sorted: &[u64], percent: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percent * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+pub fn parse_window(input: &str) -> Result<u128> {
+    let input = input.trim();
+    let split = input
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (value, unit) = input.split_at(split);
+    let Ok(value) = value.parse::<u128>() else {
+        bail!("ungültiges Zeitfenster '{}' (z. B. 30m, 24h, 7d)", input);
+    };
+    let factor = match unit {
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" | "" => 3_600_000,
+        "d" => 86_400_000,
+        _ => bail!("ungültige Einheit in '{}' (s, m, h, d)", input),
+    };
+    Ok(value * factor)
+}
+
+pub fn render_table(summary: &Summary) -> String {
+    let mut out = String::new();
+    if let Some(engagement) = &summary.engagement {
+        let _ = writeln!(out, "Engagement: {}", engagement);
+    }
+    if !summary.labels.is_empty() {
+        let labels = summary
+            .labels
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "Labels: {}", labels.join(", "));
+    }
+    let _ = writeln!(out, "Runs gesamt: {}", summary.runs);
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "{:<16} {:>6} {:>8} {:>8} {:>8} {:>10} {:>10} {:>8}",
+        "TOOL", "RUNS", "SUCCESS", "TIMEOUT", "TRUNC", "P50_MS", "P95_MS", "RETRIES"
+    );
+    for (tool, stats) in &summary.tools {
+        let _ = writeln!(
+            out,
+            "{:<16} {:>6} {:>7.1}% {:>8} {:>7.1}% {:>10} {:>10} {:>8}",
+            tool,
+            stats.runs,
+            stats.success_rate * 100.0,
+            stats.timed_out,
+            stats.truncation_rate * 100.0,
+            format_duration(stats.p50_duration_ms),
+            format_duration(stats.p95_duration_ms),
+            stats.retries
+        );
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{:<32} {:>6}", "HOST", "RUNS");
+    for host in &summary.busiest_hosts {
+        let _ = writeln!(out, "{:<32} {:>6}", host.host, host.runs);
+    }
+    out
+}
+
+fn format_duration(value: Option<u64>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
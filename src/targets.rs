@@ -0,0 +1,554 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+
+pub const TARGET_PLACEHOLDER: &str = "{{target}}";
+
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetsConfig {
+    #[serde(default = "default_path")]
+    pub path: String,
+    #[serde(default = "default_max_hosts")]
+    pub max_hosts: usize,
+    #[serde(default = "default_ping_tool")]
+    pub ping_tool: String,
+}
+
+fn default_path() -> String {
+    "target-lists.json".to_string()
+}
+
+fn default_max_hosts() -> usize {
+    4096
+}
+
+fn default_ping_tool() -> String {
+    "nmap".to_string()
+}
+
+impl Default for TargetsConfig {
+    fn default() -> Self {
+        Self {
+            path: default_path(),
+            max_hosts: default_max_hosts(),
+            ping_tool: default_ping_tool(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Store {
+    #[serde(default)]
+    pub lists: BTreeMap<String, TargetList>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetList {
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub engagement: Option<String>,
+    #[serde(default)]
+    pub pinged: bool,
+    pub created_ms: u128,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dns: BTreeMap<String, DnsName>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DnsName {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub records: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+impl DnsName {
+    pub fn add(&mut self, kind: &str, value: &str) {
+        let value = value.trim().trim_end_matches('.');
+        if value.is_empty() {
+            return;
+        }
+        let values = match kind {
+            "a" | "aaaa" if value.parse::<IpAddr>().is_ok() => &mut self.addresses,
+            "a" | "aaaa" => return,
+            _ => self.records.entry(kind.to_string()).or_default(),
+        };
+        if !values.iter().any(|known| known == value) {
+            values.push(value.to_string());
+        }
+    }
+
+    fn merge(&mut self, other: &DnsName) {
+        for address in &other.addresses {
+            self.add("a", address);
+        }
+        for (kind, values) in &other.records {
+            for value in values {
+                self.add(kind, value);
+            }
+        }
+        for source in &other.sources {
+            if !self.sources.contains(source) {
+                self.sources.push(source.clone());
+            }
+        }
+    }
+}
+
+pub fn dns_entry<'a>(
+    names: &'a mut BTreeMap<String, DnsName>,
+    name: &str,
+    source: &str,
+) -> Option<&'a mut DnsName> {
+    let name = name.trim().trim_end_matches('.').to_ascii_lowercase();
+    if !is_hostname(&name) || name.parse::<IpAddr>().is_ok() {
+        return None;
+    }
+    let entry = names.entry(name).or_default();
+    if !entry.sources.iter().any(|known| known == source) {
+        entry.sources.push(source.to_string());
+    }
+    Some(entry)
+}
+
+impl Store {
+    pub fn load(path: &str) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                serde_json::from_str(&content).with_context(|| Message::StateFileInvalid {
+                    kind: "Target-Listen",
+                    path: path.to_string(),
+                })
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| Message::StateFileUnreadable {
+                kind: "Target-Listen",
+                path: path.to_string(),
+            }),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let tmp = format!("{}.tmp", path);
+        fs::write(&tmp, serde_json::to_string_pretty(self)?).with_context(|| {
+            Message::StateFileWriteFailed {
+                kind: "Target-Listen",
+                path: tmp.clone(),
+            }
+        })?;
+        fs::rename(&tmp, path).with_context(|| Message::StateFileWriteFailed {
+            kind: "Target-Listen",
+            path: path.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpandRequest {
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub targets: Vec<String>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub ping: bool,
+    pub engagement: Option<String>,
+}
+
+pub fn mcp_tools() -> Vec<Value> {
+    vec![json!({
+        "name": "targets.expand",
+        "description": "Expands CIDRs and IP ranges into a host list (optionally only hosts answering an nmap ping sweep), checks them against the engagement scope and stores the list by name for workflow for_each loops",
+        "inputSchema": {
+            "type": "object",
+            "required": ["targets"],
+            "properties": {
+                "targets": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "minItems": 1,
+                    "description": "IPs, CIDR networks, ranges (a.b.c.d-e.f.g.h or a.b.c.d-e) or hostnames"
+                },
+                "name": {"type": "string", "description": "Store the list under this name"},
+                "ping": {"type": "boolean", "description": "Keep only IPs that answer an nmap -sn sweep on the Kali host; hostnames are kept as given"},
+                "host": {"type": "string", "description": "Kali host for the ping sweep"},
+                "user": {"type": "string"},
+                "engagement": {"type": "string"}
+            }
+        }
+    })]
+}
+
+pub fn parse_request(action: &str, arguments: Value) -> Result<ExpandRequest> {
+    if action != "expand" {
+        bail!(Message::ToolNotAllowed {
+            tool: format!("targets.{}", action)
+        });
+    }
+    let request = serde_json::from_value::<ExpandRequest>(arguments).map_err(|error| {
+        Message::InvalidArguments {
+            tool: "targets.expand".to_string(),
+            error: error.to_string(),
+        }
+    })?;
+    if request.targets.is_empty() {
+        bail!(Message::MissingArgument {
+            tool: "targets.expand".to_string(),
+            argument: "targets"
+        });
+    }
+    if let Some(name) = &request.name
+        && !is_name(name)
+    {
+        bail!(Message::InvalidValue {
+            field: "list",
+            value: name.clone()
+        });
+    }
+    Ok(request)
+}
+
+pub fn expand(inputs: &[String], max_hosts: usize) -> Result<Vec<String>> {
+    let mut hosts = Vec::new();
+    let mut seen = HashSet::new();
+    for input in inputs {
+        let input = input.trim();
+        let Some((first, last)) = bounds(input)? else {
+            if !is_hostname(input) {
+                bail!(Message::InvalidValue {
+                    field: "target",
+                    value: input.to_string()
+                });
+            }
+            if seen.insert(input.to_ascii_lowercase()) {
+                hosts.push(input.to_ascii_lowercase());
+            }
+            continue;
+        };
+        let count = match (first, last) {
+            (IpAddr::V4(first), IpAddr::V4(last)) if first <= last => {
+                u128::from(u32::from(last) - u32::from(first)) + 1
+            }
+            (IpAddr::V6(first), IpAddr::V6(last)) if first <= last => {
+                (u128::from(last) - u128::from(first)).saturating_add(1)
+            }
+            _ => bail!(Message::InvalidValue {
+                field: "range",
+                value: input.to_string()
+            }),
+        };
+        if count > (max_hosts - hosts.len().min(max_hosts)) as u128 {
+            bail!(Message::TooManyHosts {
+                target: input.to_string(),
+                max: max_hosts
+            });
+        }
+        for offset in 0..count {
+            let host = match first {
+                IpAddr::V4(first) => IpAddr::V4(Ipv4Addr::from(u32::from(first) + offset as u32)),
+                IpAddr::V6(first) => IpAddr::V6(Ipv6Addr::from(u128::from(first) + offset)),
+            };
+            if seen.insert(host.to_string()) {
+                hosts.push(host.to_string());
+            }
+        }
+    }
+    Ok(hosts)
+}
+
+pub fn parse_ping(stdout: &str) -> HashSet<String> {
+    stdout
+        .lines()
+        .filter(|line| line.contains("Status: Up"))
+        .filter_map(|line| line.strip_prefix("Host: "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn store(
+    config: &TargetsConfig,
+    name: &str,
+    hosts: &[String],
+    sources: &[String],
+    engagement: Option<&str>,
+    pinged: bool,
+) -> Result<()> {
+    let _guard = STORE_LOCK.lock().expect("target store poisoned");
+    let mut store = Store::load(&config.path)?;
+    store.lists.insert(
+        name.to_string(),
+        TargetList {
+            hosts: hosts.to_vec(),
+            sources: sources.to_vec(),
+            engagement: engagement.map(str::to_string),
+            pinged,
+            created_ms: now_ms(),
+            dns: BTreeMap::new(),
+        },
+    );
+    store.save(&config.path)
+}
+
+pub fn merge_dns(
+    config: &TargetsConfig,
+    name: &str,
+    names: &BTreeMap<String, DnsName>,
+    source: &str,
+    engagement: Option<&str>,
+) -> Result<Value> {
+    if !is_name(name) {
+        bail!(Message::InvalidValue {
+            field: "list",
+            value: name.to_string()
+        });
+    }
+    let _guard = STORE_LOCK.lock().expect("target store poisoned");
+    let mut store = Store::load(&config.path)?;
+    let list = store
+        .lists
+        .entry(name.to_string())
+        .or_insert_with(|| TargetList {
+            hosts: Vec::new(),
+            sources: Vec::new(),
+            engagement: engagement.map(str::to_string),
+            pinged: false,
+            created_ms: now_ms(),
+            dns: BTreeMap::new(),
+        });
+    if list.engagement.as_deref() != engagement {
+        bail!(Message::TargetListForeign {
+            name: name.to_string(),
+            owner: list.engagement.as_deref().unwrap_or("-").to_string(),
+            engagement: engagement.unwrap_or("-").to_string()
+        });
+    }
+    let mut added = 0;
+    let mut dropped = 0;
+    for (host, entry) in names {
+        if !list.hosts.contains(host) {
+            if list.hosts.len() >= config.max_hosts {
+                dropped += 1;
+                continue;
+            }
+            list.hosts.push(host.clone());
+            added += 1;
+        }
+        list.dns.entry(host.clone()).or_default().merge(entry);
+    }
+    if !list.sources.iter().any(|known| known == source) {
+        list.sources.push(source.to_string());
+    }
+    if added > 0 {
+        list.pinged = false;
+    }
+    let merged =
+        json!({"name": name, "added": added, "dropped": dropped, "total": list.hosts.len()});
+    store.save(&config.path)?;
+    Ok(merged)
+}
+
+pub fn lookup(config: &TargetsConfig, name: &str) -> Result<Vec<String>> {
+    Store::load(&config.path)?
+        .lists
+        .remove(name)
+        .map(|list| list.hosts)
+        .ok_or_else(|| {
+            Message::TargetListMissing {
+                name: name.to_string(),
+            }
+            .into()
+        })
+}
+
+pub fn substitute(value: &Value, target: &str) -> Value {
+    match value {
+        Value::String(text) => Value::String(text.replace(TARGET_PLACEHOLDER, target)),
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|item| substitute(item, target)).collect())
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, item)| (key.clone(), substitute(item, target)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|value| value.as_millis())
+        .unwrap_or(0)
+}
+
+fn bounds(input: &str) -> Result<Option<(IpAddr, IpAddr)>> {
+    if let Some((ip, prefix)) = input.split_once('/') {
+        let invalid = || Message::InvalidValue {
+            field: "network",
+            value: input.to_string(),
+        };
+        let ip = ip.parse::<IpAddr>().map_err(|_| invalid())?;
+        let prefix = prefix.parse::<u8>().map_err(|_| invalid())?;
+        return match ip {
+            IpAddr::V4(ip) if prefix <= 32 => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+                let network = u32::from(ip) & mask;
+                let broadcast = network | !mask;
+                let (first, last) = if prefix <= 30 {
+                    (network + 1, broadcast - 1)
+                } else {
+                    (network, broadcast)
+                };
+                Ok(Some((IpAddr::V4(first.into()), IpAddr::V4(last.into()))))
+            }
+            IpAddr::V6(ip) if prefix <= 128 => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+                let network = u128::from(ip) & mask;
+                Ok(Some((
+                    IpAddr::V6(network.into()),
+                    IpAddr::V6((network | !mask).into()),
+                )))
+            }
+            _ => bail!(invalid()),
+        };
+    }
+    if let Ok(ip) = input.parse::<IpAddr>() {
+        return Ok(Some((ip, ip)));
+    }
+    if let Some((first, last)) = input.split_once('-')
+        && let Ok(first) = first.parse::<IpAddr>()
+    {
+        if let Ok(last) = last.parse::<IpAddr>() {
+            return Ok(Some((first, last)));
+        }
+        if let (IpAddr::V4(first), Ok(last_octet)) = (first, last.parse::<u8>()) {
+            let [a, b, c, _] = first.octets();
+            return Ok(Some((
+                IpAddr::V4(first),
+                IpAddr::V4(Ipv4Addr::new(a, b, c, last_octet)),
+            )));
+        }
+        bail!(Message::InvalidValue {
+            field: "range",
+            value: input.to_string()
+        });
+    }
+    Ok(None)
+}
+
+#[derive(Debug)]
+pub struct InvalidHost(String);
+
+impl std::fmt::Display for InvalidHost {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidHost {}
+
+pub fn normalize_host(host: &str) -> Result<String, InvalidHost> {
+    let invalid = || {
+        InvalidHost(
+            Message::InvalidHost {
+                host: host.to_string(),
+            }
+            .to_string(),
+        )
+    };
+    let inner = host
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+        .unwrap_or(host);
+    let (address, zone) = inner
+        .split_once('%')
+        .map_or((inner, None), |(address, zone)| (address, Some(zone)));
+    if let Ok(ip) = address.parse::<Ipv6Addr>() {
+        return match zone {
+            None => Ok(ip.to_string()),
+            Some(zone)
+                if !zone.is_empty()
+                    && zone
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')) =>
+            {
+                Ok(format!("{}%{}", ip, zone))
+            }
+            Some(_) => Err(invalid()),
+        };
+    }
+    if inner != host || zone.is_some() {
+        return Err(invalid());
+    }
+    if let Ok(ip) = host.parse::<Ipv4Addr>() {
+        return Ok(ip.to_string());
+    }
+    let labels = host.split('.').collect::<Vec<_>>();
+    let valid = host.len() <= 253
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+        })
+        && !labels
+            .iter()
+            .all(|label| label.chars().all(|c| c.is_ascii_digit()));
+    if !valid {
+        return Err(invalid());
+    }
+    Ok(host.to_string())
+}
+
+pub fn check_user(user: &str) -> Result<(), InvalidHost> {
+    let valid = (1..=64).contains(&user.len())
+        && !user.starts_with('-')
+        && user
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    match valid {
+        true => Ok(()),
+        false => Err(InvalidHost(
+            Message::InvalidUser {
+                user: user.to_string(),
+            }
+            .to_string(),
+        )),
+    }
+}
+
+pub fn is_hostname(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 253
+        && !value.starts_with(['-', '.'])
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+}
+
+pub fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Context as _, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::logging::{LogSink, LoggingConfig};
+
+pub const OBSERVATION_TARGET: &str = "ollama_kali_mcp_bridge::observation";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+fn default_service_name() -> String {
+    "ollama-kali-mcp-bridge".to_string()
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: default_service_name(),
+        }
+    }
+}
+
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            let _ = provider.shutdown();
+        }
+    }
+}
+
+pub fn init(
+    config: &TelemetryConfig,
+    logging: &LoggingConfig,
+    json_logs: bool,
+) -> Result<Telemetry> {
+    let observations = if json_logs {
+        Some(ObservationLayer {
+            max_level: logging.level.as_tracing(),
+            sink: Mutex::new(LogSink::open(logging)?),
+        })
+    } else {
+        None
+    };
+
+    let provider = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let exporter = SpanExporter::builder()
+                .with_http()
+                .with_endpoint(endpoint)
+                .build()
+                .context("OTLP-Exporter konnte nicht erstellt werden")?;
+            Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(
+                        Resource::builder()
+                            .with_service_name(config.service_name.clone())
+                            .build(),
+                    )
+                    .build(),
+            )
+        }
+        None => None,
+    };
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("ollama-kali-mcp-bridge"))
+            .with_filter(Targets::new().with_target("ollama_kali_mcp_bridge", Level::INFO))
+    });
+
+    tracing_subscriber::registry()
+        .with(observations)
+        .with(otel)
+        .try_init()
+        .context("tracing konnte nicht initialisiert werden")?;
+
+    Ok(Telemetry { provider })
+}
+
+struct ObservationLayer {
+    max_level: Level,
+    sink: Mutex<LogSink>,
+}
+
+impl<S> Layer<S> for ObservationLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if metadata.target() != OBSERVATION_TARGET || *metadata.level() > self.max_level {
+            return;
+        }
+        let mut visitor = ObservationVisitor::default();
+        event.record(&mut visitor);
+
+        let timestamp_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|value| value.as_millis())
+            .unwrap_or(0);
+        let payload = serde_json::from_str::<Value>(&visitor.payload)
+            .unwrap_or(Value::String(visitor.payload));
+        let line = json!({
+            "ts_ms": timestamp_ms,
+            "event": visitor.observation,
+            "payload": payload
+        });
+        self.sink
+            .lock()
+            .expect("log sink poisoned")
+            .write(*metadata.level(), &line.to_string());
+    }
+}
+
+#[derive(Default)]
+struct ObservationVisitor {
+    observation: String,
+    payload: String,
+}
+
+impl Visit for ObservationVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "observation" => self.observation = value.to_string(),
+            "payload" => self.payload = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "observation" => self.observation = format!("{:?}", value),
+            "payload" => self.payload = format!("{:?}", value),
+            _ => {}
+        }
+    }
+}
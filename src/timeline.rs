@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::artifacts;
+use crate::encryption::{self, Sealer};
+use crate::engagement::Engagement;
+use crate::findings::{self, Finding, Severity};
+
+pub const RESOURCE_PREFIX: &str = "bridge://engagements/";
+pub const RESOURCE_SUFFIX: &str = "/timeline";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TimelineFormat {
+    #[default]
+    Table,
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Timeline {
+    pub engagement: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scope: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ends_ms: Option<u128>,
+    pub totals: Totals,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Totals {
+    pub runs: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    pub rejected: u64,
+    pub findings: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Entry {
+    pub ts_ms: u128,
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    pub status: String,
+    pub result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub findings: Option<Value>,
+    #[serde(skip)]
+    findings_list: Vec<Finding>,
+}
+
+impl Entry {
+    fn new(ts_ms: u128, kind: &'static str, entry: &Value) -> Self {
+        Self {
+            ts_ms,
+            kind,
+            run_id: text(&entry["correlation_id"]),
+            attempt: None,
+            tool: text(&entry["tool"]),
+            target: text(&entry["target"]),
+            args: entry["args"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|arg| arg.as_str().map(str::to_string))
+                .collect(),
+            operator: text(&entry["operator"]),
+            client: text(&entry["client"]),
+            duration_ms: None,
+            status: String::new(),
+            result: String::new(),
+            findings: None,
+            findings_list: Vec::new(),
+        }
+    }
+}
+
+pub fn resource_uri(engagement: &str) -> String {
+    format!("{}{}{}", RESOURCE_PREFIX, engagement, RESOURCE_SUFFIX)
+}
+
+pub fn parse_resource_uri(uri: &str) -> Option<&str> {
+    uri.strip_prefix(RESOURCE_PREFIX)?
+        .strip_suffix(RESOURCE_SUFFIX)
+        .filter(|name| !name.is_empty() && !name.contains('/'))
+}
+
+pub fn build(
+    records: &[Value],
+    name: &str,
+    engagement: Option<&Engagement>,
+    since_ms: Option<u128>,
+    until_ms: Option<u128>,
+    sealer: Option<&Sealer>,
+) -> Result<Timeline> {
+    let mut entries = Vec::<Entry>::new();
+    let mut runs = HashMap::<String, usize>::new();
+    let mut latest = HashMap::<String, usize>::new();
+    let belongs = |entry: &Value| entry["engagement"].as_str() == Some(name);
+
+    for record in records {
+        let ts_ms = record["ts_ms"].as_u64().map(u128::from).unwrap_or(0);
+        let entry = &record["entry"];
+        match record["kind"].as_str() {
+            Some("run_started") if belongs(entry) => {
+                let Some(run_token) = entry["run_token"].as_str() else {
+                    continue;
+                };
+                let attempt = entry["attempt"].as_u64().unwrap_or(1);
+                let mut item = Entry::new(ts_ms, "run", entry);
+                item.attempt = Some(attempt);
+                item.status = "unfinished".to_string();
+                if let Some(run_id) = &item.run_id {
+                    latest.insert(run_id.clone(), entries.len());
+                }
+                runs.insert(run_token.to_string(), entries.len());
+                entries.push(item);
+            }
+            Some("run_finished") => {
+                let Some(item) = entry["run_token"]
+                    .as_str()
+                    .and_then(|token| runs.get(token))
+                    .map(|at| &mut entries[*at])
+                else {
+                    continue;
+                };
+                let exit_code = entry["exit_code"].as_i64();
+                let timed_out = entry["timed_out"].as_bool().unwrap_or(false);
+                item.duration_ms = entry["duration_ms"].as_u64();
+                item.status = match exit_code {
+                    _ if timed_out => "timed_out",
+                    Some(0) => "succeeded",
+                    _ => "failed",
+                }
+                .to_string();
+                item.result = match exit_code {
+                    _ if timed_out => "Timeout".to_string(),
+                    Some(code) => format!("Exit {}", code),
+                    None => "ohne Exit-Code beendet".to_string(),
+                };
+                if entry["truncated"].as_bool().unwrap_or(false) {
+                    item.result.push_str(", Ausgabe gekürzt");
+                }
+            }
+            Some("run_failed") => {
+                let Some(item) = entry["run_token"]
+                    .as_str()
+                    .and_then(|token| runs.get(token))
+                    .map(|at| &mut entries[*at])
+                else {
+                    continue;
+                };
+                item.status = "error".to_string();
+                item.result = format!(
+                    "Startfehler: {}",
+                    entry["error"].as_str().unwrap_or("unbekannt")
+                );
+            }
+            Some("findings") if belongs(entry) => {
+                let Some((run_id, at)) = entry["correlation_id"]
+                    .as_str()
+                    .and_then(|id| latest.get_key_value(id))
+                else {
+                    continue;
+                };
+                let findings =
+                    encryption::unseal_value(sealer, &entry["findings"]).with_context(|| {
+                        format!("Findings von {} konnten nicht entschlüsselt werden", run_id)
+                    })?;
+                let findings = serde_json::from_value::<Vec<Finding>>(findings)
+                    .context("findings-Record ist ungültig")?;
+                entries[*at].findings_list.extend(findings);
+            }
+            Some("request_rejected") if belongs(entry) => {
+                let mut item = Entry::new(ts_ms, "rejected", entry);
+                item.status = "rejected".to_string();
+                item.result = format!(
+                    "Abgelehnt: {}",
+                    entry["error"].as_str().unwrap_or("unbekannt")
+                );
+                entries.push(item);
+            }
+            Some("approval_requested") if belongs(&entry["request"]) => {
+                let mut item = Entry::new(ts_ms, "approval", &entry["request"]);
+                item.status = "requested".to_string();
+                item.result = format!(
+                    "Freigabe angefordert ({})",
+                    entry["approval_id"].as_str().unwrap_or("-")
+                );
+                entries.push(item);
+            }
+            Some(kind @ ("crack_job" | "msf_call" | "gvm_call")) if belongs(entry) => {
+                let mut item = Entry::new(ts_ms, action_kind(kind), entry);
+                if item.target.is_none() {
+                    let targets = entry["targets"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>();
+                    item.target = Some(targets.join(", ")).filter(|targets| !targets.is_empty());
+                }
+                let detail = [
+                    &entry["module"],
+                    &entry["state"],
+                    &entry["task_id"],
+                    &entry["job_id"],
+                ]
+                .into_iter()
+                .find_map(text);
+                match entry["error"].as_str() {
+                    Some(error) => {
+                        item.status = "failed".to_string();
+                        item.result = format!("Fehler: {}", error);
+                    }
+                    None => {
+                        item.status = "succeeded".to_string();
+                        item.result = detail.unwrap_or_else(|| "ok".to_string());
+                    }
+                }
+                entries.push(item);
+            }
+            _ => {}
+        }
+    }
+
+    for item in entries
+        .iter_mut()
+        .filter(|item| !item.findings_list.is_empty())
+    {
+        item.findings = Some(findings::summary(&item.findings_list));
+        let _ = write!(item.result, "; {}", describe_findings(&item.findings_list));
+    }
+    if let Some(engagement) = engagement {
+        for note in &engagement.notes {
+            entries.push(Entry {
+                status: "note".to_string(),
+                result: note.text.clone(),
+                ..Entry::new(note.ts_ms, "note", &Value::Null)
+            });
+        }
+    }
+    entries.retain(|item| {
+        since_ms.is_none_or(|since| item.ts_ms >= since)
+            && until_ms.is_none_or(|until| item.ts_ms < until)
+    });
+    entries.sort_by_key(|item| item.ts_ms);
+
+    let mut totals = Totals::default();
+    for item in &entries {
+        match (item.kind, item.status.as_str()) {
+            ("run", "succeeded") => totals.succeeded += 1,
+            ("run", "failed" | "timed_out" | "error") => totals.failed += 1,
+            ("rejected", _) => totals.rejected += 1,
+            _ => {}
+        }
+        totals.runs += u64::from(item.kind == "run");
+        totals.findings += item.findings_list.len() as u64;
+    }
+    Ok(Timeline {
+        engagement: name.to_string(),
+        scope: engagement
+            .map(|engagement| engagement.scope.clone())
+            .unwrap_or_default(),
+        starts_ms: engagement.map(|engagement| engagement.starts_ms),
+        ends_ms: engagement.and_then(|engagement| engagement.ends_ms),
+        totals,
+        entries,
+    })
+}
+
+fn action_kind(kind: &str) -> &'static str {
+    match kind {
+        "crack_job" => "crack",
+        "msf_call" => "metasploit",
+        _ => "gvm",
+    }
+}
+
+fn describe_findings(findings: &[Finding]) -> String {
+    let counts = Severity::ALL
+        .iter()
+        .rev()
+        .filter_map(|severity| {
+            let count = findings
+                .iter()
+                .filter(|finding| finding.severity == *severity)
+                .count();
+            (count > 0).then(|| format!("{} {}", count, severity.as_str()))
+        })
+        .collect::<Vec<_>>();
+    format!("{} Findings ({})", findings.len(), counts.join(", "))
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) if !text.is_empty() => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+pub fn render_table(timeline: &Timeline) -> String {
+    let mut out = header(timeline, "");
+    let _ = writeln!(
+        out,
+        "{:<20} {:<10} {:<22} {:<14} {:<24} {:<16} {:>9}  ERGEBNIS",
+        "ZEIT (UTC)", "ART", "RUN", "TOOL", "ZIEL", "OPERATOR/CLIENT", "DAUER"
+    );
+    for item in &timeline.entries {
+        let _ = writeln!(
+            out,
+            "{:<20} {:<10} {:<22} {:<14} {:<24} {:<16} {:>9}  {}",
+            timestamp(item.ts_ms),
+            item.kind,
+            run_label(item),
+            item.tool.as_deref().unwrap_or("-"),
+            item.target.as_deref().unwrap_or("-"),
+            identity(item),
+            duration(item.duration_ms),
+            item.result
+        );
+    }
+    out
+}
+
+pub fn render_markdown(timeline: &Timeline) -> String {
+    let mut out = format!("# Aktivitätsprotokoll {}\n\n", timeline.engagement);
+    out.push_str(&header(timeline, "- "));
+    let _ = writeln!(
+        out,
+        "| Zeit (UTC) | Art | Run | Tool | Ziel | Argumente | Operator/Client | Dauer | Ergebnis |"
+    );
+    let _ = writeln!(out, "|---|---|---|---|---|---|---|---|---|");
+    for item in &timeline.entries {
+        let args = if item.args.is_empty() {
+            String::new()
+        } else {
+            format!("`{}`", item.args.join(" ").replace('`', "'"))
+        };
+        let cells = [
+            timestamp(item.ts_ms),
+            item.kind.to_string(),
+            run_label(item),
+            item.tool.clone().unwrap_or_else(|| "-".to_string()),
+            item.target.clone().unwrap_or_else(|| "-".to_string()),
+            args,
+            identity(item),
+            duration(item.duration_ms),
+            item.result.clone(),
+        ];
+        let cells = cells
+            .iter()
+            .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+            .collect::<Vec<_>>();
+        let _ = writeln!(out, "| {} |", cells.join(" | "));
+    }
+    out
+}
+
+fn header(timeline: &Timeline, bullet: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{}Engagement: {}", bullet, timeline.engagement);
+    if !timeline.scope.is_empty() {
+        let _ = writeln!(out, "{}Scope: {}", bullet, timeline.scope.join(", "));
+    }
+    if let Some(starts_ms) = timeline.starts_ms {
+        let ends = timeline
+            .ends_ms
+            .map_or_else(|| "offen".to_string(), timestamp);
+        let _ = writeln!(
+            out,
+            "{}Zeitraum: {} bis {}",
+            bullet,
+            timestamp(starts_ms),
+            ends
+        );
+    }
+    let totals = &timeline.totals;
+    let _ = writeln!(
+        out,
+        "{}Runs: {} ({} erfolgreich, {} fehlgeschlagen), abgelehnt: {}, Findings: {}",
+        bullet, totals.runs, totals.succeeded, totals.failed, totals.rejected, totals.findings
+    );
+    out.push('\n');
+    out
+}
+
+fn run_label(item: &Entry) -> String {
+    match (&item.run_id, item.attempt) {
+        (Some(run_id), Some(attempt)) if attempt > 1 => format!("{} #{}", run_id, attempt),
+        (Some(run_id), _) => run_id.clone(),
+        (None, _) => "-".to_string(),
+    }
+}
+
+fn identity(item: &Entry) -> String {
+    match (&item.operator, &item.client) {
+        (Some(operator), Some(client)) => format!("{}/{}", operator, client),
+        (Some(name), None) | (None, Some(name)) => name.clone(),
+        (None, None) => "-".to_string(),
+    }
+}
+
+fn duration(value: Option<u64>) -> String {
+    match value {
+        Some(ms) if ms >= 60_000 => format!("{}m{:02}s", ms / 60_000, ms / 1000 % 60),
+        Some(ms) => format!("{:.1}s", ms as f64 / 1000.0),
+        None => "-".to_string(),
+    }
+}
+
+fn timestamp(ms: u128) -> String {
+    let secs = (ms / 1000) as u64;
+    let (year, month, day) = artifacts::civil_from_days((secs / 86_400) as i64);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow, bail};
+use rustls::RootCertStore;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+
+use crate::http::HttpConfig;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn acceptor(config: &HttpConfig) -> Result<Option<TlsAcceptor>> {
+    let (cert_path, key_path) = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => {
+            if config.tls_client_ca.is_some() {
+                bail!("http.tls_client_ca setzt http.tls_cert und http.tls_key voraus");
+            }
+            return Ok(None);
+        }
+        _ => bail!("http.tls_cert und http.tls_key müssen gemeinsam angegeben werden"),
+    };
+
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("TLS-Zertifikat {} konnte nicht gelesen werden", cert_path))?;
+    if certs.is_empty() {
+        bail!("TLS-Zertifikat {} enthält kein Zertifikat", cert_path);
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .with_context(|| format!("TLS-Schlüssel {} konnte nicht gelesen werden", key_path))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .context("TLS-Protokollversionen konnten nicht gesetzt werden")?;
+    let builder = match &config.tls_client_ca {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca_path)
+                .with_context(|| format!("Client-CA {} konnte nicht gelesen werden", ca_path))?
+            {
+                let cert =
+                    cert.with_context(|| format!("Client-CA {} enthält ungültiges PEM", ca_path))?;
+                roots.add(cert).with_context(|| {
+                    format!("Client-CA {} enthält ungültiges Zertifikat", ca_path)
+                })?;
+            }
+            if roots.is_empty() {
+                bail!("Client-CA {} enthält kein Zertifikat", ca_path);
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            let verifier = if config.tls_client_cert_required {
+                verifier.build()
+            } else {
+                verifier.allow_unauthenticated().build()
+            }
+            .context("Client-Zertifikatsprüfung konnte nicht eingerichtet werden")?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None if config.tls_client_cert_required => {
+            bail!("http.tls_client_cert_required setzt http.tls_client_ca voraus")
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut server = builder.with_single_cert(certs, key).with_context(|| {
+        format!(
+            "TLS-Zertifikat {} passt nicht zum Schlüssel {}",
+            cert_path, key_path
+        )
+    })?;
+    server.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Some(TlsAcceptor::from(Arc::new(server))))
+}
+
+pub async fn accept(
+    acceptor: &TlsAcceptor,
+    stream: TcpStream,
+) -> Result<(TlsStream<TcpStream>, Option<[u8; 32]>)> {
+    let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+        .await
+        .map_err(|_| anyhow!("Timeout beim TLS-Handshake"))?
+        .context("TLS-Handshake fehlgeschlagen")?;
+    let fingerprint = peer_fingerprint(&stream);
+    Ok((stream, fingerprint))
+}
+
+fn peer_fingerprint(stream: &TlsStream<TcpStream>) -> Option<[u8; 32]> {
+    let (_, connection) = stream.get_ref();
+    let leaf = connection.peer_certificates()?.first()?;
+    Some(Sha256::digest(leaf.as_ref()).into())
+}
@@ -0,0 +1,247 @@
+use anyhow::{Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+use crate::messages;
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+pub const PROTOCOL: &str = "bridge-events";
+
+const CLOSE_NORMAL: u16 = 1000;
+const CLOSE_GOING_AWAY: u16 = 1001;
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+const CLOSE_UNSUPPORTED: u16 = 1003;
+const CLOSE_TOO_BIG: u16 = 1009;
+
+enum Control {
+    Pong(Vec<u8>),
+    Close(u16),
+}
+
+enum Message {
+    Text(String),
+    Ping(Vec<u8>),
+    Close,
+}
+
+pub fn accept_key(key: &str) -> String {
+    let mut digest = Sha1::new();
+    digest.update(key.trim().as_bytes());
+    digest.update(HANDSHAKE_GUID.as_bytes());
+    BASE64.encode(digest.finalize())
+}
+
+pub async fn serve<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    incoming: mpsc::UnboundedSender<String>,
+    outgoing: mpsc::UnboundedReceiver<Vec<u8>>,
+    max_message_bytes: usize,
+) -> Result<()> {
+    let (reader, writer) = tokio::io::split(stream);
+    let (control_tx, control_rx) = mpsc::unbounded_channel();
+    let read = async move {
+        read_loop(reader, incoming, control_tx, max_message_bytes).await;
+        std::future::pending::<()>().await
+    };
+    tokio::select! {
+        written = write_loop(writer, outgoing, control_rx) => written,
+        _ = read => Ok(()),
+    }
+}
+
+async fn read_loop<R: AsyncRead + Unpin>(
+    mut reader: R,
+    incoming: mpsc::UnboundedSender<String>,
+    control: mpsc::UnboundedSender<Control>,
+    max_message_bytes: usize,
+) {
+    loop {
+        match read_message(&mut reader, max_message_bytes).await {
+            Ok(Message::Text(text)) => {
+                if incoming.send(text).is_err() {
+                    let _ = control.send(Control::Close(CLOSE_GOING_AWAY));
+                    return;
+                }
+            }
+            Ok(Message::Ping(data)) => {
+                let _ = control.send(Control::Pong(data));
+            }
+            Ok(Message::Close) => {
+                let _ = control.send(Control::Close(CLOSE_NORMAL));
+                return;
+            }
+            Err(ReadError::Closed) => return,
+            Err(ReadError::Protocol(code)) => {
+                let _ = control.send(Control::Close(code));
+                return;
+            }
+        }
+    }
+}
+
+async fn write_loop<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut outgoing: mpsc::UnboundedReceiver<Vec<u8>>,
+    mut control: mpsc::UnboundedReceiver<Control>,
+) -> Result<()> {
+    let mut pending = Vec::new();
+    let mut open = true;
+    let mut result = Ok(());
+    loop {
+        tokio::select! {
+            Some(message) = control.recv(), if open => {
+                let sent = match message {
+                    Control::Pong(data) => write_frame(&mut writer, 0xA, &data).await,
+                    Control::Close(code) => {
+                        open = false;
+                        write_frame(&mut writer, 0x8, &code.to_be_bytes()).await
+                    }
+                };
+                if let Err(error) = sent {
+                    open = false;
+                    result = Err(error);
+                }
+            }
+            chunk = outgoing.recv() => {
+                let Some(chunk) = chunk else {
+                    break;
+                };
+                pending.extend(chunk);
+                while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+                    let line = pending.drain(..=end).collect::<Vec<_>>();
+                    if open && let Err(error) = write_frame(&mut writer, 0x1, &line[..line.len() - 1]).await {
+                        open = false;
+                        result = Err(error);
+                    }
+                }
+            }
+        }
+    }
+    if open {
+        write_frame(&mut writer, 0x8, &CLOSE_GOING_AWAY.to_be_bytes()).await?;
+    }
+    let _ = writer.shutdown().await;
+    result
+}
+
+enum ReadError {
+    Closed,
+    Protocol(u16),
+}
+
+async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_message_bytes: usize,
+) -> Result<Message, ReadError> {
+    let mut message = Vec::new();
+    let mut fragmented = false;
+    loop {
+        let mut head = [0_u8; 2];
+        reader
+            .read_exact(&mut head)
+            .await
+            .map_err(|_| ReadError::Closed)?;
+        let fin = head[0] & 0x80 != 0;
+        let opcode = head[0] & 0x0F;
+        if head[0] & 0x70 != 0 || head[1] & 0x80 == 0 {
+            return Err(ReadError::Protocol(CLOSE_PROTOCOL_ERROR));
+        }
+        let length = match head[1] & 0x7F {
+            126 => {
+                let mut bytes = [0_u8; 2];
+                reader
+                    .read_exact(&mut bytes)
+                    .await
+                    .map_err(|_| ReadError::Closed)?;
+                u16::from_be_bytes(bytes) as u64
+            }
+            127 => {
+                let mut bytes = [0_u8; 8];
+                reader
+                    .read_exact(&mut bytes)
+                    .await
+                    .map_err(|_| ReadError::Closed)?;
+                u64::from_be_bytes(bytes)
+            }
+            length => length as u64,
+        };
+        let control = opcode & 0x08 != 0;
+        if control && (!fin || length > 125) {
+            return Err(ReadError::Protocol(CLOSE_PROTOCOL_ERROR));
+        }
+        if !control && message.len() as u64 + length > max_message_bytes as u64 {
+            return Err(ReadError::Protocol(CLOSE_TOO_BIG));
+        }
+        let mut mask = [0_u8; 4];
+        reader
+            .read_exact(&mut mask)
+            .await
+            .map_err(|_| ReadError::Closed)?;
+        let mut payload = vec![0_u8; length as usize];
+        reader
+            .read_exact(&mut payload)
+            .await
+            .map_err(|_| ReadError::Closed)?;
+        for (index, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[index % 4];
+        }
+        match opcode {
+            0x8 => return Ok(Message::Close),
+            0x9 => return Ok(Message::Ping(payload)),
+            0xA => continue,
+            0x1 if !fragmented => {}
+            0x0 if fragmented => {}
+            0x2 => return Err(ReadError::Protocol(CLOSE_UNSUPPORTED)),
+            _ => return Err(ReadError::Protocol(CLOSE_PROTOCOL_ERROR)),
+        }
+        message.extend(payload);
+        if !fin {
+            fragmented = true;
+            continue;
+        }
+        return String::from_utf8(message)
+            .map(Message::Text)
+            .map_err(|_| ReadError::Protocol(CLOSE_PROTOCOL_ERROR));
+    }
+}
+
+async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    opcode: u8,
+    payload: &[u8],
+) -> Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length if length < 126 => frame.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend((length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+pub fn offered_token(protocols: &str) -> Option<&str> {
+    protocols
+        .split(',')
+        .map(str::trim)
+        .find_map(|protocol| protocol.strip_prefix("bearer."))
+        .filter(|token| !token.is_empty())
+}
+
+pub fn check_upgrade(version: Option<&str>) -> Result<()> {
+    if version.map(str::trim) != Some("13") {
+        bail!(messages::Message::WebSocketVersion);
+    }
+    Ok(())
+}
@@ -0,0 +1,396 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::messages::Message;
+use crate::shell_escape;
+
+const PLACEHOLDER_PREFIX: &str = "{{wordlist:";
+const PLACEHOLDER_SUFFIX: &str = "}}";
+const MISSING_EXIT: i32 = 4;
+const PREVIEW_LINES: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordlistsConfig {
+    #[serde(default)]
+    pub directories: Vec<String>,
+    #[serde(default)]
+    pub lists: BTreeMap<String, String>,
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    #[serde(default = "default_count_lines_max_bytes")]
+    pub count_lines_max_bytes: u64,
+}
+
+fn default_max_entries() -> usize {
+    500
+}
+
+fn default_count_lines_max_bytes() -> u64 {
+    16 * 1024 * 1024
+}
+
+impl Default for WordlistsConfig {
+    fn default() -> Self {
+        Self {
+            directories: Vec::new(),
+            lists: BTreeMap::new(),
+            max_entries: default_max_entries(),
+            count_lines_max_bytes: default_count_lines_max_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum WordlistAction {
+    List {
+        host: Option<String>,
+        user: Option<String>,
+        filter: Option<String>,
+        limit: Option<usize>,
+    },
+    Info {
+        host: Option<String>,
+        user: Option<String>,
+        name: String,
+    },
+}
+
+pub struct WordlistQuery {
+    pub tool: &'static str,
+    pub host: Option<String>,
+    pub user: Option<String>,
+    pub script: String,
+    name: Option<String>,
+    path: Option<String>,
+    limit: usize,
+}
+
+pub fn validate(config: &WordlistsConfig) -> Result<()> {
+    let mut prefixes = Vec::new();
+    for directory in &config.directories {
+        check_path(directory)
+            .with_context(|| format!("wordlists: ungültiges Verzeichnis '{}'", directory))?;
+        let prefix = prefix(directory);
+        if !is_name(prefix) {
+            bail!(
+                "wordlists: Verzeichnisname '{}' ist als Präfix ungeeignet",
+                prefix
+            );
+        }
+        if prefixes.contains(&prefix) {
+            bail!(
+                "wordlists: Verzeichnisname '{}' ist mehrfach vergeben",
+                prefix
+            );
+        }
+        prefixes.push(prefix);
+    }
+    for (name, path) in &config.lists {
+        if !is_name(name) {
+            bail!("wordlists.lists: ungültiger Name '{}'", name);
+        }
+        check_path(path).with_context(|| format!("wordlists.lists.{}: ungültiger Pfad", name))?;
+    }
+    if config.max_entries == 0 {
+        bail!("wordlists.max_entries muss größer als 0 sein");
+    }
+    Ok(())
+}
+
+pub fn resolve(config: &WordlistsConfig, name: &str) -> Result<String> {
+    if let Some(path) = config.lists.get(name) {
+        return Ok(path.clone());
+    }
+    let (prefix_name, relative) = name.split_once('/').ok_or_else(|| Message::NotConfigured {
+        key: format!("wordlists.lists.{}", name),
+    })?;
+    let directory = config
+        .directories
+        .iter()
+        .find(|directory| prefix(directory) == prefix_name)
+        .ok_or_else(|| Message::NotConfigured {
+            key: format!("wordlists.directories ({})", prefix_name),
+        })?;
+    if relative
+        .split('/')
+        .any(|segment| matches!(segment, "" | "." | ".."))
+        || relative.chars().any(char::is_control)
+    {
+        bail!(Message::InvalidValue {
+            field: "wordlist",
+            value: name.to_string()
+        });
+    }
+    Ok(format!("{}/{}", directory.trim_end_matches('/'), relative))
+}
+
+pub fn expansions<'a>(
+    config: &WordlistsConfig,
+    args: impl IntoIterator<Item = &'a String>,
+) -> Result<Vec<(String, String)>> {
+    let mut expansions = Vec::new();
+    for arg in args {
+        let mut rest = arg.as_str();
+        while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+            let after = &rest[start + PLACEHOLDER_PREFIX.len()..];
+            let end = after
+                .find(PLACEHOLDER_SUFFIX)
+                .ok_or_else(|| Message::PlaceholderIncomplete { arg: arg.clone() })?;
+            let name = &after[..end];
+            let placeholder = placeholder(name);
+            if !expansions.iter().any(|(known, _)| *known == placeholder) {
+                expansions.push((placeholder, resolve(config, name)?));
+            }
+            rest = &after[end + PLACEHOLDER_SUFFIX.len()..];
+        }
+    }
+    Ok(expansions)
+}
+
+pub fn placeholder(name: &str) -> String {
+    format!("{}{}{}", PLACEHOLDER_PREFIX, name, PLACEHOLDER_SUFFIX)
+}
+
+pub fn mcp_tools(config: &WordlistsConfig) -> Vec<Value> {
+    if config.directories.is_empty() && config.lists.is_empty() {
+        return Vec::new();
+    }
+    vec![
+        json!({
+            "name": "wordlists.list",
+            "description": "Lists wordlists on the Kali host (logical name, size, line count); reference them in tool args as {{wordlist:<name>}}",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "filter": {"type": "string", "description": "Case-insensitive substring of the name"},
+                    "limit": {"type": "integer", "minimum": 1}
+                }
+            }
+        }),
+        json!({
+            "name": "wordlists.info",
+            "description": "Shows size, line count, path and the first lines of a wordlist by logical name",
+            "inputSchema": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "host": {"type": "string"},
+                    "user": {"type": "string"},
+                    "name": {"type": "string"}
+                }
+            }
+        }),
+    ]
+}
+
+pub fn prepare(config: &WordlistsConfig, action: &str, arguments: Value) -> Result<WordlistQuery> {
+    let mut arguments = match arguments {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    arguments.insert("action".to_string(), Value::String(action.to_string()));
+    let action =
+        serde_json::from_value::<WordlistAction>(Value::Object(arguments)).map_err(|error| {
+            Message::InvalidArguments {
+                tool: format!("wordlists.{}", action),
+                error: error.to_string(),
+            }
+        })?;
+
+    match action {
+        WordlistAction::List {
+            host,
+            user,
+            filter,
+            limit,
+        } => {
+            let limit = limit
+                .unwrap_or(config.max_entries)
+                .clamp(1, config.max_entries);
+            let mut script = String::new();
+            for (name, path) in &config.lists {
+                script.push_str(&format!(
+                    "p={path}; printf 'L\\t%s\\t' {name}; if [ -f \"$p\" ]; then {stats} printf '%s\\t%s\\n' \"$s\" \"$l\"; \
+                     else printf -- '-\\t-\\n'; fi; ",
+                    path = shell_escape(path),
+                    name = shell_escape(name),
+                    stats = stats(config)
+                ));
+            }
+            if !config.directories.is_empty() {
+                let finds = config
+                    .directories
+                    .iter()
+                    .map(|directory| {
+                        format!(
+                            "find -L {} -type f -printf '{}/%P\\t%p\\n' 2>/dev/null",
+                            shell_escape(directory),
+                            prefix(directory)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                let grep = match filter.as_deref().filter(|filter| !filter.is_empty()) {
+                    Some(filter) => format!("grep -i -F -e {} | ", shell_escape(filter)),
+                    None => String::new(),
+                };
+                script.push_str(&format!(
+                    "{{ {finds}; }} | LC_ALL=C sort | {grep}head -n {count} | while IFS=\"$(printf '\\t')\" read -r n p; do \
+                     {stats} printf 'F\\t%s\\t%s\\t%s\\n' \"$n\" \"$s\" \"$l\"; done",
+                    finds = finds,
+                    grep = grep,
+                    count = limit + 1,
+                    stats = stats(config)
+                ));
+            }
+            if script.is_empty() {
+                script.push(':');
+            }
+            Ok(WordlistQuery {
+                tool: "wordlists.list",
+                host,
+                user,
+                script,
+                name: None,
+                path: None,
+                limit,
+            })
+        }
+        WordlistAction::Info { host, user, name } => {
+            let path = resolve(config, &name)?;
+            let script = format!(
+                "p={path}; [ -f \"$p\" ] || {{ echo 'Wordlist nicht gefunden' >&2; exit {missing}; }}; {stats} \
+                 printf '%s\\t%s\\n' \"$s\" \"$l\"; head -n {preview} < \"$p\" | cut -c1-200",
+                path = shell_escape(&path),
+                missing = MISSING_EXIT,
+                stats = stats(config),
+                preview = PREVIEW_LINES
+            );
+            Ok(WordlistQuery {
+                tool: "wordlists.info",
+                host,
+                user,
+                script,
+                name: Some(name),
+                path: Some(path),
+                limit: 0,
+            })
+        }
+    }
+}
+
+impl WordlistQuery {
+    pub fn finish(&self, exit_code: Option<i32>, stdout: &[u8], stderr: &[u8]) -> Result<Value> {
+        let text = String::from_utf8_lossy(stdout);
+        if exit_code != Some(0) {
+            let detail = String::from_utf8_lossy(stderr).trim().to_string();
+            let tool = self.tool.to_string();
+            match exit_code {
+                Some(MISSING_EXIT) => bail!(Message::WordlistMissing {
+                    name: self.name.clone().unwrap_or_default(),
+                    path: self.path.clone().unwrap_or_default()
+                }),
+                Some(255) => bail!(Message::RemoteSshFailed { tool, detail }),
+                _ => bail!(Message::RemoteFailed {
+                    tool,
+                    exit_code,
+                    detail
+                }),
+            }
+        }
+        if let (Some(name), Some(path)) = (&self.name, &self.path) {
+            let mut lines = text.lines();
+            let (size, count) = lines
+                .next()
+                .and_then(|line| line.split_once('\t'))
+                .context(Message::UnexpectedResponse)?;
+            return Ok(json!({
+                "name": name,
+                "path": path,
+                "placeholder": placeholder(name),
+                "size": number(size),
+                "lines": number(count),
+                "preview": lines.collect::<Vec<_>>()
+            }));
+        }
+        let mut lists = Vec::new();
+        let mut files = Vec::new();
+        for line in text.lines() {
+            let fields = line.split('\t').collect::<Vec<_>>();
+            match fields.as_slice() {
+                ["L", name, size, count] => lists.push(json!({
+                    "name": name,
+                    "exists": *size != "-",
+                    "size": number(size),
+                    "lines": number(count)
+                })),
+                ["F", name, size, count] => files.push(json!({
+                    "name": name,
+                    "size": number(size),
+                    "lines": number(count)
+                })),
+                _ => {}
+            }
+        }
+        let truncated = files.len() > self.limit;
+        files.truncate(self.limit);
+        Ok(json!({
+            "lists": lists,
+            "files": files,
+            "truncated": truncated
+        }))
+    }
+}
+
+fn stats(config: &WordlistsConfig) -> String {
+    format!(
+        "s=$(($(wc -c < \"$p\"))); if [ \"$s\" -le {} ]; then l=$(($(wc -l < \"$p\"))); else l=-; fi;",
+        config.count_lines_max_bytes
+    )
+}
+
+fn number(value: &str) -> Value {
+    value
+        .parse::<u64>()
+        .map_or(Value::Null, |number| json!(number))
+}
+
+fn prefix(directory: &str) -> &str {
+    directory
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn check_path(path: &str) -> Result<()> {
+    if !path.starts_with('/') {
+        bail!("Pfad '{}' muss absolut sein", path);
+    }
+    if path.chars().any(char::is_control) {
+        bail!("Pfad enthält unzulässige Zeichen");
+    }
+    if path
+        .trim_end_matches('/')
+        .split('/')
+        .skip(1)
+        .any(|segment| matches!(segment, "" | "." | ".."))
+    {
+        bail!("Pfad '{}' ist nicht normalisiert", path);
+    }
+    Ok(())
+}
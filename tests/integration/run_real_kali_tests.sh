@@ -44,7 +44,7 @@ MCP_ERR="$TMP_DIR/mcp_err.jsonl"
 WF_OUT="$TMP_DIR/wf_out.jsonl"
 WF_ERR="$TMP_DIR/wf_err.jsonl"
 
-echo "[INFO] Test 1/3: MCP tools/list"
+echo "[INFO] Test 1/5: MCP tools/list"
 cargo run --quiet -- mcp-serve --config "$BRIDGE_CONFIG" >"$MCP_OUT" 2>"$MCP_ERR" <<EOF
 {"id":1,"method":"initialize"}
 {"id":2,"method":"tools/list"}
@@ -63,7 +63,7 @@ if not tools:
 print(f"[OK] tools/list liefert {len(tools)} Tools")
 PY
 
-echo "[INFO] Test 2/3: MCP tools/call nmap"
+echo "[INFO] Test 2/5: MCP tools/call nmap"
 cargo run --quiet -- mcp-serve --config "$BRIDGE_CONFIG" >"$MCP_OUT" 2>"$MCP_ERR" <<EOF
 {"id":3,"method":"tools/call","params":{"name":"nmap","arguments":{"host":"$KALI_HOST","user":"$KALI_USER","args":["-sn","$KALI_TEST_TARGET"],"timeout_sec":$KALI_TEST_TIMEOUT_SEC,"max_output_bytes":$KALI_TEST_MAX_OUTPUT_BYTES}}}
 EOF
@@ -90,7 +90,7 @@ if not any(x.get("event") == "attempt_started" for x in obs):
 print("[OK] tools/call liefert structuredContent + Observability")
 PY
 
-echo "[INFO] Test 3/3: workflow-serve one-step"
+echo "[INFO] Test 3/5: workflow-serve one-step"
 cargo run --quiet -- workflow-serve --config "$BRIDGE_CONFIG" >"$WF_OUT" 2>"$WF_ERR" <<EOF
 {"id":"wf-int-1","host":"$KALI_HOST","user":"$KALI_USER","stop_on_error":true,"steps":[{"tool":"nmap","args":["-sn","$KALI_TEST_TARGET"],"timeout_sec":$KALI_TEST_TIMEOUT_SEC,"max_output_bytes":$KALI_TEST_MAX_OUTPUT_BYTES}]}
 EOF
@@ -109,4 +109,62 @@ if "workflow_finished" not in events:
 print("[OK] workflow events vollständig")
 PY
 
+echo "[INFO] Test 4/5: Remote-Prozessgruppe wird bei Timeout beendet"
+KILL_OUT="$TMP_DIR/kill_out.jsonl"
+KILL_MARKER="--host-timeout=7777s"
+cargo run --quiet -- mcp-serve --config "$BRIDGE_CONFIG" >"$KILL_OUT" 2>/dev/null <<EOF
+{"id":4,"method":"tools/call","params":{"name":"nmap","arguments":{"host":"$KALI_HOST","user":"$KALI_USER","args":["-p-","-T1","$KILL_MARKER","$KALI_TEST_TARGET"],"timeout_sec":3}}}
+EOF
+
+sleep 2
+if ssh -o BatchMode=yes "$KALI_USER@$KALI_HOST" "pgrep -f -- '$KILL_MARKER'" >/dev/null; then
+  echo "[FAIL] nmap läuft nach Timeout auf Kali weiter"
+  exit 1
+fi
+if ssh -o BatchMode=yes "$KALI_USER@$KALI_HOST" 'ls "${XDG_RUNTIME_DIR:-$HOME/.cache}"/ollama-kali-bridge/*/pid' >/dev/null 2>&1; then
+  echo "[FAIL] PID-Datei der Bridge auf Kali nicht aufgeräumt"
+  exit 1
+fi
+echo "[OK] Remote-Prozessgruppe nach Timeout beendet"
+
+echo "[INFO] Test 5/5: output.read liest über max_output_bytes hinaus"
+STORE_CONFIG="$TMP_DIR/store-config.json"
+STORE_OUT="$TMP_DIR/store_out.jsonl"
+python3 - "$BRIDGE_CONFIG" "$STORE_CONFIG" <<'PY'
+import json, sys
+config = json.load(open(sys.argv[1], "r", encoding="utf-8"))
+config["max_output_bytes"] = 4096
+config.setdefault("mcp", {}).update({"inline_output_bytes": 1024, "output_store_bytes": 1048576})
+config.setdefault("tools", {})["seq"] = {"command": "/usr/bin/seq", "default_args": ["1", "20000"]}
+json.dump(config, open(sys.argv[2], "w", encoding="utf-8"))
+PY
+{
+  echo '{"id":5,"method":"tools/call","params":{"name":"seq","arguments":{"host":"'"$KALI_HOST"'","user":"'"$KALI_USER"'","timeout_sec":'"$KALI_TEST_TIMEOUT_SEC"'}}}'
+  for _ in $(seq 1 "$KALI_TEST_TIMEOUT_SEC"); do
+    grep -q '"id":5' "$STORE_OUT" 2>/dev/null && break
+    sleep 1
+  done
+  RUN_ID=$(python3 -c 'import json, sys; print(next((x["result"]["structuredContent"]["run_id"] for x in map(json.loads, open(sys.argv[1], encoding="utf-8")) if x.get("id") == 5), ""))' "$STORE_OUT" 2>/dev/null)
+  echo '{"id":6,"method":"tools/call","params":{"name":"output.read","arguments":{"run_id":"'"$RUN_ID"'","stream":"stdout","offset":100000,"length":1000}}}'
+} | cargo run --quiet -- mcp-serve --config "$STORE_CONFIG" >"$STORE_OUT" 2>/dev/null
+
+python3 - "$STORE_OUT" <<'PY'
+import json, sys
+lines = [json.loads(l) for l in open(sys.argv[1], "r", encoding="utf-8") if l.strip()]
+call = next((x for x in lines if x.get("id") == 5), None)
+read = next((x for x in lines if x.get("id") == 6), None)
+if not call or not read:
+    raise SystemExit("[FAIL] Antwort von tools/call oder output.read fehlt")
+partial = call.get("result", {}).get("structuredContent", {}).get("partial", {}).get("stdout", {})
+expected = sum(len(f"{n}\n") for n in range(1, 20001))
+if partial.get("total_bytes") != expected or partial.get("inline_bytes") != 1024:
+    raise SystemExit(f"[FAIL] unerwartete Kürzung: {partial}")
+if "error" in read:
+    raise SystemExit(f"[FAIL] output.read error: {read['error']}")
+chunk = json.loads(read["result"]["content"][0]["text"])
+if chunk.get("offset") != 100000 or chunk.get("total_bytes") != expected or not chunk.get("data"):
+    raise SystemExit(f"[FAIL] output.read liefert nichts hinter max_output_bytes: {chunk}")
+print("[OK] output.read liefert Ausgabe jenseits von max_output_bytes")
+PY
+
 echo "[PASS] Alle Integrationstests erfolgreich"